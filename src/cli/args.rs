@@ -1,4 +1,9 @@
 use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+
+use crate::core::{symlinks::ConflictResolution, widget::WidgetFormat};
+use crate::services::{settings_service::BundleFormat, status_service::CheckCategory};
+use crate::traits::repository::FetchPolicy;
 
 #[derive(Parser)]
 #[command(name = "dotf")]
@@ -6,10 +11,37 @@ use clap::{Parser, Subcommand};
 #[command(version = env!("CARGO_PKG_VERSION"))]
 #[command(author = "k1-c")]
 pub struct Cli {
+    /// Never prompt; conflicting files are backed up unless --on-conflict says otherwise
+    #[arg(long, short = 'y', visible_alias = "non-interactive", global = true)]
+    pub yes: bool,
+    /// Resolve conflicting files this way instead of asking
+    #[arg(long, value_enum, global = true)]
+    pub on_conflict: Option<ConflictResolution>,
+    /// Print what dotf is doing to stderr; repeat for more detail (-vv, -vvv)
+    #[arg(long, short, action = clap::ArgAction::Count, global = true)]
+    pub verbose: u8,
+    /// Turn on an experimental feature for this run; repeat for several
+    #[arg(long = "enable-feature", value_name = "NAME", global = true)]
+    pub enable_features: Vec<String>,
+    /// Treat the connection as metered: sync fetches only the current branch, and tool
+    /// and package downloads wait for an unmetered run
+    #[arg(long, global = true)]
+    pub metered: bool,
     #[command(subcommand)]
     pub command: Commands,
 }
 
+impl Cli {
+    /// How conflicts are resolved without prompting, `None` to ask
+    pub fn conflict_policy(&self) -> Option<ConflictResolution> {
+        match (&self.on_conflict, self.yes) {
+            (Some(policy), _) => Some(policy.clone()),
+            (None, true) => Some(ConflictResolution::Backup),
+            (None, false) => None,
+        }
+    }
+}
+
 #[derive(Subcommand)]
 pub enum Commands {
     /// Initialize dotf with a remote repository
@@ -17,31 +49,231 @@ pub enum Commands {
         /// Repository URL
         #[arg(long)]
         repo: Option<String>,
+        /// Check out only the paths dotf.toml references for this machine
+        #[arg(long)]
+        sparse: bool,
+        /// Use an existing local clone instead of cloning into ~/.dotf/repo
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["repo", "sparse"])]
+        from_local: Option<String>,
     },
     /// Install various components
     Install {
         #[command(subcommand)]
         target: InstallTarget,
     },
+    /// Remove the symlinks dotf installed and restore the files they replaced
+    Uninstall {
+        /// Only remove the configuration symlinks (the default)
+        #[arg(long, conflicts_with = "all")]
+        config: bool,
+        /// Also delete ~/.dotf with the repository clone, settings and state
+        #[arg(long)]
+        all: bool,
+        /// Leave backed-up files in ~/.dotf/backups instead of restoring them
+        #[arg(long)]
+        keep_backups: bool,
+        /// Show what would be removed and restored without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
     /// Show repository sync status
     Status {
         /// Show minimal status output
         #[arg(long)]
         quiet: bool,
+        /// Show how status evolved since a duration ago (30m, 12h, 7d, 2w) or a date (YYYY-MM-DD)
+        #[arg(long)]
+        since: Option<String>,
+        /// Print a short segment for a status bar from the cached status
+        #[arg(long, value_enum, conflicts_with = "json")]
+        widget: Option<WidgetFormat>,
+        /// Print the full status (or the `--since` history) as JSON
+        #[arg(long, conflicts_with = "quiet")]
+        json: bool,
+        /// Also ask the package managers which [packages] entries are installed
+        #[arg(long, conflicts_with_all = ["since", "widget", "json"])]
+        packages: bool,
+        /// Only check the links from this repository ('base' or an overlay name)
+        #[arg(long, conflicts_with_all = ["since", "widget"])]
+        repo: Option<String>,
+        /// Exit with 3 for symlink issues, 4 when the repository is dirty or behind and
+        /// 5 when dotf is not initialized
+        #[arg(long, conflicts_with_all = ["since", "widget"])]
+        check: bool,
+        /// How long to wait for the remote when counting commits behind; without it the
+        /// counts come from the last fetch
+        #[arg(long, value_enum, default_value_t, conflicts_with = "widget")]
+        fetch: FetchPolicy,
+        /// Print nothing and exit with the sum of the codes of the listed problems that
+        /// were found (broken 8, behind 16, dirty 32, config 64, uninitialized 128); these
+        /// never overlap the --check codes
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            value_name = "CATEGORIES",
+            conflicts_with_all = ["quiet", "since", "widget", "json", "packages", "check"]
+        )]
+        fail_on: Vec<CheckCategory>,
     },
+    /// Print up to two lines about what needs attention, from the cached status; meant for
+    /// shell startup files and silent when all is well
+    Greet,
     /// Sync with remote repository
     Sync {
         /// Force sync (override local changes)
         #[arg(long)]
         force: bool,
+        /// Stash uncommitted changes in the repository, pull, then reapply them
+        #[arg(long, conflicts_with = "force")]
+        autostash: bool,
+        /// Commit local changes in the repository and push them instead of pulling
+        #[arg(long, conflicts_with_all = ["force", "autostash"])]
+        push: bool,
+        /// Commit message to use with --push
+        #[arg(long, short, requires = "push")]
+        message: Option<String>,
+        /// Only sync this repository ('base' or an overlay name)
+        #[arg(long, conflicts_with = "push")]
+        repo: Option<String>,
+        /// Sync without prompting and print what was pulled as JSON
+        #[arg(long, conflicts_with = "push")]
+        json: bool,
+    },
+    /// Move an unmanaged file or directory into the repository and link it back
+    Add {
+        /// Path of the file or directory (e.g. ~/.config/foo/config)
+        target: String,
+        /// Path inside the repository (default: the path under the home directory
+        /// without its leading dot)
+        #[arg(long)]
+        source: Option<String>,
+    },
+    /// Keep running, linking files added to linked directories and reporting links
+    /// replaced by regular files
+    Watch {
+        #[command(subcommand)]
+        action: Option<WatchAction>,
+    },
+    /// Keep the local version of a managed file by copying it back into the repository
+    Adopt {
+        /// Target path of the managed file (e.g. ~/.zshrc)
+        target: String,
+        /// Commit the adopted file after staging it
+        #[arg(long)]
+        commit: bool,
+        /// Commit message to use with --commit
+        #[arg(long, short)]
+        message: Option<String>,
+    },
+    /// Show the dotfiles change history with the targets each commit touched
+    Log {
+        /// Only show commits that changed this target (e.g. ~/.zshrc)
+        target: Option<String>,
+        /// Maximum number of commits to show
+        #[arg(long, short = 'n', default_value_t = 20)]
+        limit: usize,
+    },
+    /// Show what changed since the last install: commits, repository files, links added or
+    /// removed by dotf.toml, and scripts that changed without running again
+    Changes {
+        /// Print the report as JSON
+        #[arg(long)]
+        json: bool,
     },
     /// Manage symlinks
     Symlinks {
         #[command(subcommand)]
         action: Option<SymlinksAction>,
     },
+    /// List and prune the files dotf backed up before linking over them
+    Backups {
+        #[command(subcommand)]
+        action: BackupsAction,
+    },
+    /// Manage overlay repositories whose links are layered over the main repository
+    Repos {
+        #[command(subcommand)]
+        action: ReposAction,
+    },
+    /// Carry local settings to another machine, or repair a damaged settings.toml
+    Settings {
+        #[command(subcommand)]
+        action: SettingsAction,
+    },
+    /// Relink missing, broken and wrong symlinks from dotf.toml
+    Repair {
+        /// Show what would be repaired without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Repair without prompting and print each link's outcome as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Regenerate files composed from repository sources: the ~/.ssh/config fragments
+    /// and the [env] shell fragment
+    Render {
+        /// Only regenerate files whose sources changed since they were rendered
+        #[arg(long)]
+        stale: bool,
+        /// Overwrite the env fragment even if it was edited by hand
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show how deployed targets differ from their repository sources
+    Diff {
+        /// Only compare the entry deployed at this target path
+        target: Option<String>,
+        /// Open each comparison in the [diff] tool from settings.toml instead of printing it
+        #[arg(long)]
+        tool: bool,
+    },
+    /// Re-clone a deleted ~/.dotf and reconnect the symlinks that pointed into it
+    Recover {
+        /// Repository URL
+        #[arg(long)]
+        repo: String,
+    },
+    /// Check the local setup for problems
+    Doctor {
+        /// Report which phases of recent commands were slow, and what to try
+        #[arg(long)]
+        slow: bool,
+    },
+    /// Manage macOS defaults declared in [macos.defaults]
+    MacosDefaults {
+        #[command(subcommand)]
+        action: MacosDefaultsAction,
+    },
+    /// Show or change answers to the [prompts] declared in dotf.toml
+    Prompts {
+        #[command(subcommand)]
+        action: PromptsAction,
+    },
+    /// Encrypt, decrypt and check the files declared in [secrets]
+    Secrets {
+        #[command(subcommand)]
+        action: SecretsAction,
+    },
+    /// Stop linking a target of dotf.toml on this machine, or list the disabled ones
+    Disable {
+        /// Link target to skip (e.g. ~/.gitconfig); a directory covers everything inside
+        target: Option<String>,
+    },
+    /// Link a target turned off with 'dotf disable' again
+    Enable {
+        /// Disabled link target (e.g. ~/.gitconfig)
+        target: String,
+    },
+    /// Choose which [profile.<name>] of dotf.toml this machine uses
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
     /// View and edit dotf configuration
     Config {
+        #[command(subcommand)]
+        action: Option<ConfigAction>,
         /// Show repository configuration (dotf.toml)
         #[arg(long)]
         repo: bool,
@@ -54,6 +286,85 @@ pub enum Commands {
         #[command(subcommand)]
         action: SchemaAction,
     },
+    /// Import dotfiles managed another way into a dotf repository
+    Import {
+        #[command(subcommand)]
+        source: ImportSource,
+    },
+    /// Print or install shell completion scripts
+    Completions {
+        /// Shell to generate completions for (defaults to the shell in $SHELL)
+        shell: Option<Shell>,
+        /// Write the script where the shell loads completions from instead of printing it
+        #[arg(long)]
+        install: bool,
+    },
+    /// Export configuration for other tools
+    Export {
+        #[command(subcommand)]
+        format: ExportFormat,
+    },
+    /// Generate starter files for a dotfiles repository
+    Generate {
+        #[command(subcommand)]
+        target: GenerateTarget,
+    },
+    /// Run a full install in a throwaway home and report what it would change
+    Sandbox {
+        /// Also run the dependency script (always done inside a container)
+        #[arg(long)]
+        with_deps: bool,
+        /// Run the install inside a podman or docker container
+        #[arg(long)]
+        container: bool,
+        /// Image used with --container
+        #[arg(long, default_value = "debian:stable-slim")]
+        image: String,
+        /// Keep the sandbox directory for inspection
+        #[arg(long)]
+        keep: bool,
+    },
+    /// Manage command aliases from settings.toml
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+    /// Run routine upkeep: prune old backups, compact the repository, refresh status, check for updates
+    Maintain {
+        /// Remove backups older than this many days
+        #[arg(long, default_value_t = 30)]
+        backup_days: u64,
+        /// Skip the update check
+        #[arg(long)]
+        offline: bool,
+    },
+    /// Apply a privileged link plan (run by dotf itself through sudo)
+    #[command(name = "__apply-plan", hide = true)]
+    ApplyPlan {
+        /// Serialized plan written by the parent process
+        file: String,
+    },
+}
+
+impl Commands {
+    /// Whether the command prints JSON on stdout, which prompts would corrupt
+    pub fn json(&self) -> bool {
+        matches!(
+            self,
+            Commands::Status { json: true, .. }
+                | Commands::Changes { json: true }
+                | Commands::Sync { json: true, .. }
+                | Commands::Repair { json: true, .. }
+                | Commands::Install {
+                    target: InstallTarget::Config { json: true, .. }
+                }
+        )
+    }
+
+    /// Whether the command promises no output at all, only an exit code
+    pub fn silent(&self) -> bool {
+        matches!(self, Commands::Status { fail_on, .. } if !fail_on.is_empty())
+    }
 }
 
 #[derive(Subcommand, Debug)]
@@ -61,12 +372,68 @@ pub enum InstallTarget {
     /// Install system dependencies
     Deps,
     /// Install configuration symlinks
-    Config,
+    Config {
+        /// Skip missing source files with a warning instead of failing
+        #[arg(long)]
+        skip_missing: bool,
+        /// Also link files that .gitattributes marks export-ignore inside linked directories
+        #[arg(long)]
+        include_export_ignored: bool,
+        /// Only process entries whose source, target or link changed since the last install
+        #[arg(long)]
+        changed_only: bool,
+        /// Show what would be linked and which conflicts would be resolved, without changing anything
+        #[arg(long)]
+        dry_run: bool,
+        /// Only link the entries from this repository ('base' or an overlay name)
+        #[arg(long)]
+        repo: Option<String>,
+        /// Link without prompting and print the links, conflicts and backups as JSON
+        #[arg(long, conflicts_with = "dry_run")]
+        json: bool,
+    },
+    /// Install dependencies and configuration in one go
+    All {
+        /// Skip missing source files with a warning instead of failing
+        #[arg(long)]
+        skip_missing: bool,
+        /// Also run every custom script from [scripts.custom]
+        #[arg(long)]
+        custom: bool,
+    },
     /// Run custom installation script
     Custom {
         /// Name of the custom script
         name: String,
+        /// Arguments passed on to the script, after `--`
+        #[arg(last = true)]
+        args: Vec<String>,
     },
+    /// Bootstrap editor plugin managers declared in [bootstrap]
+    Bootstrap,
+    /// Install the crontab entries declared in [cron]
+    Cron,
+    /// Merge, append and copy the ~/.ssh content declared in [ssh]
+    Ssh,
+    /// Generate the shell environment fragment from [env]
+    Env {
+        /// Overwrite the fragment even if it was edited by hand
+        #[arg(long)]
+        force: bool,
+    },
+    /// Download release binaries declared in [tools]
+    Tools {
+        /// Only install this tool
+        name: Option<String>,
+    },
+    /// Install the missing [packages] entries with their package managers
+    Packages,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum WatchAction {
+    /// Show whether `dotf watch` is running and what it is doing
+    Status,
 }
 
 #[derive(Subcommand, Debug)]
@@ -82,6 +449,184 @@ pub enum SymlinksAction {
         /// Specific file path to restore
         filepath: Option<String>,
     },
+    /// Walk through broken, invalid and conflicting symlinks and choose how to fix each
+    Repair {
+        /// Also relink files that .gitattributes marks export-ignore inside linked directories
+        #[arg(long)]
+        include_export_ignored: bool,
+    },
+    /// Review and delete backups of files whose dotf link has stayed valid for a long time
+    Gc {
+        /// Only offer backups whose link was installed at least this many days ago
+        #[arg(long, default_value_t = 90)]
+        older_than: u64,
+    },
+    /// Write what 'dotf install config' would deploy here as a shell script or a GNU
+    /// Stow package, for machines without dotf
+    Export {
+        /// What to write
+        #[arg(long, value_enum, default_value = "shell")]
+        format: LinkExportFormat,
+        /// Write the script to a file instead of stdout; the package directory to create
+        /// with --format stow
+        #[arg(long, short, required_if_eq("format", "stow"))]
+        output: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum LinkExportFormat {
+    /// A POSIX shell script of ln -s commands that leaves existing files alone
+    Shell,
+    /// A directory laid out like the home directory, for 'stow --no-folding'
+    Stow,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum BackupsAction {
+    /// List backups, newest first
+    List,
+    /// Remove old backups; without options, apply the [backups] policy in settings.toml
+    Prune {
+        /// Remove backups older than this many days
+        #[arg(long)]
+        older_than: Option<u64>,
+        /// Keep only this many of the newest backups
+        #[arg(long)]
+        keep_last: Option<usize>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReposAction {
+    /// List overlay repositories, lowest precedence first
+    List,
+    /// Clone a repository under ~/.dotf/repos/<name>; its links replace links to the same
+    /// targets from the main repository and earlier overlays
+    Add {
+        /// Name used with --repo (letters, digits, '-' and '_')
+        name: String,
+        /// Remote URL to clone
+        url: String,
+        /// Branch to check out
+        #[arg(long, short)]
+        branch: Option<String>,
+    },
+    /// Stop layering a repository; its checkout is kept
+    Remove {
+        /// Name of the repository
+        name: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SettingsAction {
+    /// Print settings.toml as a bundle for 'dotf settings import', without machine paths;
+    /// credentials and secret-looking prompt answers are redacted
+    Export {
+        /// Bundle format
+        #[arg(long, value_enum, default_value = "toml")]
+        format: BundleFormat,
+        /// Write the bundle to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
+        /// Keep credentials and all prompt answers in the bundle
+        #[arg(long)]
+        include_secrets: bool,
+    },
+    /// Validate a bundle, make it this machine's settings and clone the repository
+    Import {
+        /// Bundle written by 'dotf settings export' (TOML or JSON)
+        file: String,
+        /// Replace existing settings, keeping their answers to redacted prompts
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show where settings.toml stopped parsing and rebuild it; the damaged file is
+    /// kept as settings.toml.damaged
+    Repair {
+        /// Put back settings.toml.bak, the last settings that parsed
+        #[arg(long, conflicts_with_all = ["from_clone", "init"])]
+        restore_backup: bool,
+        /// Use the repository checked out in ~/.dotf and keep what still reads
+        #[arg(long, conflicts_with = "init")]
+        from_clone: bool,
+        /// Write minimal settings for this repository URL, cloning it if needed
+        #[arg(long, value_name = "URL")]
+        init: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum MacosDefaultsAction {
+    /// Show values that differ from dotf.toml without changing anything
+    Diff,
+    /// Write values that differ from dotf.toml
+    Apply,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PromptsAction {
+    /// Show each prompt and its stored answer
+    List,
+    /// Ask again, with the stored answers as defaults
+    Edit {
+        /// Only ask this prompt
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    /// List the profiles in dotf.toml and mark the active one
+    List,
+    /// Merge this profile over the base config on this machine
+    Use {
+        /// Profile name from [profile.<name>]
+        name: String,
+    },
+    /// Go back to the base config without a profile
+    Clear,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SecretsAction {
+    /// Encrypt a file into the repository, adding it to [secrets.files] if needed
+    Encrypt {
+        /// Path of the plaintext file (e.g. ~/.netrc)
+        target: String,
+        /// Path of the encrypted file inside the repository (default:
+        /// secrets/<path under the home directory>.<age|asc>)
+        #[arg(long)]
+        source: Option<String>,
+    },
+    /// Decrypt missing or outdated secrets to their targets
+    Decrypt {
+        /// Only decrypt the secret for this target
+        target: Option<String>,
+        /// Also replace targets edited on this machine
+        #[arg(long)]
+        force: bool,
+    },
+    /// Compare each decrypted target with its encrypted file
+    Status,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum AliasAction {
+    /// List aliases defined in the [aliases] table of settings.toml
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigAction {
+    /// Compare dotf.toml between two branches, tags or commits of the remote repository
+    Diff {
+        /// Old side of the comparison
+        from: String,
+        /// New side of the comparison
+        to: String,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -100,4 +645,86 @@ pub enum SchemaAction {
         #[arg(long)]
         quiet: bool,
     },
+    /// Describe every dotf.toml section and field with examples
+    Docs {
+        /// Only this table and the tables below it (e.g. tools, scripts.limits)
+        section: Option<String>,
+        /// Print Markdown instead of formatted terminal output
+        #[arg(long)]
+        markdown: bool,
+    },
+    /// Propose [symlinks] for existing dotfiles and write a starter dotf.toml
+    Generate {
+        /// Files or directories to link (default: common dotfiles found in your home directory)
+        paths: Vec<String>,
+        /// File to write (default: ./dotf.toml)
+        #[arg(long, short)]
+        file: Option<String>,
+        /// Replace the file if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportSource {
+    /// Convert a bare git repository that uses $HOME as its work tree
+    Bare {
+        /// Bare repository to import (default: detect ~/.dotfiles, ~/.cfg, ...)
+        #[arg(long)]
+        git_dir: Option<String>,
+        /// Where to create the dotf repository (default: ~/.dotf/repo)
+        #[arg(long)]
+        dest: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ExportFormat {
+    /// Generate a home-manager home.file module from dotf.toml
+    HomeManager {
+        /// Write the module to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum GenerateTarget {
+    /// Generate a dependency installation script and register it in dotf.toml
+    DepsScript {
+        /// Target platform: linux or macos (default: current platform)
+        #[arg(long)]
+        platform: Option<String>,
+        /// Script path relative to the repository root
+        #[arg(long, short)]
+        output: Option<String>,
+        /// Configuration file to update (default: ./dotf.toml)
+        #[arg(long, short)]
+        file: Option<String>,
+        /// Overwrite an existing script and scripts.deps entry
+        #[arg(long)]
+        force: bool,
+    },
+    /// Generate a Dockerfile that bakes this configuration into an image
+    Dockerfile {
+        /// Base image; its package manager is guessed from the name
+        #[arg(long, default_value = "debian:stable-slim")]
+        base_image: String,
+        /// User to install the configuration for (root installs into /root)
+        #[arg(long, default_value = "dev")]
+        user: String,
+        /// Custom script from [scripts.custom] to run after the configuration (repeatable)
+        #[arg(long = "custom", value_name = "NAME")]
+        custom: Vec<String>,
+        /// Do not run the dependency script during the build
+        #[arg(long)]
+        no_deps: bool,
+        /// Where to write the Dockerfile
+        #[arg(long, short, default_value = "Dockerfile")]
+        output: String,
+        /// Overwrite an existing file
+        #[arg(long)]
+        force: bool,
+    },
 }
@@ -0,0 +1,43 @@
+use super::adopt::resolve_target;
+use crate::cli::{MessageFormatter, Spinner};
+use crate::core::filesystem::RealFileSystem;
+use crate::error::DotfResult;
+use crate::services::AddService;
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_add(target: String, source: Option<String>) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let target_path = resolve_target(&target)?;
+
+    let add_service = AddService::new(RealFileSystem::new(), ConsolePrompt::new());
+
+    let spinner = Spinner::new(&format!("Adding {}...", target_path));
+    let result = match add_service.add(&target_path, source.as_deref()).await {
+        Ok(result) => {
+            spinner.finish_and_clear();
+            result
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to add {}: {}", target_path, e));
+            return Err(e);
+        }
+    };
+
+    println!(
+        "{}",
+        formatter.file_operation("Moved", &result.target_path, &result.source_path)
+    );
+    println!(
+        "{}",
+        formatter.success(&format!(
+            "Added \"{}\" = \"{}\" to [symlinks]",
+            result.config_key, result.config_target
+        ))
+    );
+    println!(
+        "{}",
+        formatter.info("Commit dotf.toml and the new file, e.g. with 'dotf sync --push'")
+    );
+
+    Ok(())
+}
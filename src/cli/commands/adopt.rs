@@ -0,0 +1,86 @@
+use crate::cli::{MessageFormatter, Spinner};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::{DotfError, DotfResult};
+use crate::services::AdoptService;
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_adopt(target: String, commit: bool, message: Option<String>) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let target_path = resolve_target(&target)?;
+
+    let adopt_service = AdoptService::new(
+        RepositoryRegistry::new(),
+        RealFileSystem::new(),
+        ConsolePrompt::new(),
+    );
+
+    let spinner = Spinner::new(&format!("Adopting {}...", target_path));
+    let result = match adopt_service
+        .adopt(&target_path, commit, message.as_deref())
+        .await
+    {
+        Ok(result) => {
+            spinner.finish_and_clear();
+            result
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to adopt {}: {}", target_path, e));
+            return Err(e);
+        }
+    };
+
+    if result.copied {
+        println!(
+            "{}",
+            formatter.file_operation("Copied", &result.target_path, &result.source_path)
+        );
+    }
+    println!(
+        "{}",
+        formatter.git_operation("Staged", &result.repo_relative_path)
+    );
+
+    if result.committed {
+        println!(
+            "{}",
+            formatter.success(&format!(
+                "Committed local version of {}",
+                result.target_path
+            ))
+        );
+    } else {
+        println!(
+            "{}",
+            formatter.success(&format!("Adopted local version of {}", result.target_path))
+        );
+        println!(
+            "{}",
+            formatter.info(
+                "Commit the staged change in your dotfiles repository, or re-run with --commit"
+            )
+        );
+    }
+
+    Ok(())
+}
+
+/// Turn `~/...` and relative paths into the absolute form used for symlink targets.
+/// Symlinks are deliberately not resolved, since the target itself is what dotf manages.
+pub(super) fn resolve_target(target: &str) -> DotfResult<String> {
+    if let Some(rest) = target.strip_prefix("~/") {
+        let home = dirs::home_dir().ok_or_else(|| {
+            DotfError::Operation("Could not determine home directory".to_string())
+        })?;
+        return Ok(home.join(rest).to_string_lossy().to_string());
+    }
+
+    if target.starts_with('/') {
+        return Ok(target.trim_end_matches('/').to_string());
+    }
+
+    let cwd = std::env::current_dir().map_err(DotfError::Io)?;
+    Ok(cwd
+        .join(target.trim_start_matches("./"))
+        .to_string_lossy()
+        .to_string())
+}
@@ -0,0 +1,118 @@
+use clap::CommandFactory;
+
+use crate::cli::args::AliasAction;
+use crate::cli::{Cli, MessageFormatter};
+use crate::core::{
+    aliases::{shadowed_aliases, GlobalArgs},
+    filesystem::RealFileSystem,
+};
+use crate::error::DotfResult;
+use crate::services::ConfigService;
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_alias(action: AliasAction) -> DotfResult<()> {
+    match action {
+        AliasAction::List => handle_alias_list().await,
+    }
+}
+
+async fn handle_alias_list() -> DotfResult<()> {
+    let service = ConfigService::new(RealFileSystem::new(), ConsolePrompt::new());
+    let formatter = MessageFormatter::new();
+    let settings = service.show_settings().await?;
+
+    if settings.aliases.is_empty() {
+        println!(
+            "{}",
+            formatter.info("No aliases defined. Add an [aliases] table to ~/.dotf/settings.toml")
+        );
+        return Ok(());
+    }
+
+    println!("{}", formatter.section("Aliases"));
+    for (index, (name, value)) in settings.aliases.iter().enumerate() {
+        println!(
+            "{}",
+            formatter.tree_item(
+                &format!("{} = {}", name, value),
+                index + 1 == settings.aliases.len(),
+                1
+            )
+        );
+    }
+
+    let builtins = builtin_commands();
+    let builtins: Vec<&str> = builtins.iter().map(String::as_str).collect();
+    for name in shadowed_aliases(&settings.aliases, &builtins) {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "'{}' is a built-in command, so this alias is never used",
+                name
+            ))
+        );
+    }
+
+    Ok(())
+}
+
+/// Names of the built-in commands, including clap's `help`, which aliases cannot replace
+pub fn builtin_commands() -> Vec<String> {
+    let command = Cli::command();
+    command
+        .get_subcommands()
+        .flat_map(|subcommand| {
+            std::iter::once(subcommand.get_name()).chain(subcommand.get_all_aliases())
+        })
+        .map(str::to_string)
+        .chain(std::iter::once("help".to_string()))
+        .collect()
+}
+
+/// Spellings of the global options, which may come before an alias
+pub fn global_args() -> GlobalArgs {
+    let command = Cli::command();
+    let mut globals = GlobalArgs::default();
+    for arg in command.get_arguments().filter(|arg| arg.is_global_set()) {
+        let spellings = arg
+            .get_long_and_visible_aliases()
+            .into_iter()
+            .flatten()
+            .map(|long| format!("--{}", long))
+            .chain(
+                arg.get_short_and_visible_aliases()
+                    .into_iter()
+                    .flatten()
+                    .map(|short| format!("-{}", short)),
+            );
+        if arg.get_action().takes_values() {
+            globals.options.extend(spellings);
+        } else {
+            globals.switches.extend(spellings);
+        }
+    }
+    globals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_global_args() {
+        let globals = global_args();
+        for switch in [
+            "--yes",
+            "-y",
+            "--non-interactive",
+            "--verbose",
+            "-v",
+            "--metered",
+        ] {
+            assert!(globals.switches.iter().any(|s| s == switch), "{}", switch);
+        }
+        for option in ["--on-conflict", "--enable-feature"] {
+            assert!(globals.options.iter().any(|o| o == option), "{}", option);
+        }
+    }
+}
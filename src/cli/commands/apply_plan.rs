@@ -0,0 +1,17 @@
+use crate::core::symlinks::{apply_plan, LinkPlan};
+use crate::error::{DotfError, DotfResult};
+
+/// Apply a serialized link plan and print the results as JSON on stdout.
+///
+/// Internal: run by `dotf install config` through sudo for targets the user cannot write.
+pub async fn handle_apply_plan(file: String) -> DotfResult<()> {
+    let content = std::fs::read_to_string(&file).map_err(DotfError::Io)?;
+    let plan: LinkPlan = serde_json::from_str(&content)
+        .map_err(|e| DotfError::Operation(format!("Invalid plan {}: {}", file, e)))?;
+
+    let results = apply_plan(&plan);
+    let output = serde_json::to_string(&results)
+        .map_err(|e| DotfError::Operation(format!("Failed to serialize results: {}", e)))?;
+    println!("{}", output);
+    Ok(())
+}
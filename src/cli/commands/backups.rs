@@ -0,0 +1,80 @@
+use crate::cli::args::BackupsAction;
+use crate::cli::{BackupEntry, MessageFormatter, PathDisplay, UiComponents};
+use crate::core::config::{BackupSettings, Settings};
+use crate::core::filesystem::RealFileSystem;
+use crate::core::symlinks::BackupManager;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+pub async fn handle_backups(action: BackupsAction) -> DotfResult<()> {
+    match action {
+        BackupsAction::List => handle_list().await,
+        BackupsAction::Prune {
+            older_than,
+            keep_last,
+        } => handle_prune(older_than, keep_last).await,
+    }
+}
+
+async fn handle_list() -> DotfResult<()> {
+    let manager = BackupManager::new(RealFileSystem::new());
+    let paths = PathDisplay::new();
+
+    let backups: Vec<BackupEntry> = manager
+        .list_backups()
+        .await?
+        .into_iter()
+        .map(|backup| BackupEntry {
+            original_path: paths.path(&backup.original_path),
+            backup_path: paths.path(&backup.backup_path),
+            created_at: backup.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        })
+        .collect();
+    println!("{}", UiComponents::new().backup_list(&backups));
+    Ok(())
+}
+
+async fn handle_prune(older_than: Option<u64>, keep_last: Option<usize>) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let formatter = MessageFormatter::new();
+
+    // Options given on the command line replace the policy from settings.toml
+    let policy = if older_than.is_some() || keep_last.is_some() {
+        BackupSettings {
+            keep_days: older_than,
+            keep_last,
+        }
+    } else {
+        load_policy(&filesystem).await?
+    };
+    if policy.is_empty() {
+        return Err(DotfError::Config(
+            "No retention policy: pass --older-than or --keep-last, or set [backups] in settings.toml"
+                .to_string(),
+        ));
+    }
+
+    let removed = BackupManager::new(filesystem)
+        .apply_retention(&policy)
+        .await?;
+    if removed == 0 {
+        println!("{}", formatter.info("No backups to prune"));
+    } else {
+        println!(
+            "{}",
+            formatter.success(&format!("Removed {} backup(s)", removed))
+        );
+    }
+    Ok(())
+}
+
+async fn load_policy(filesystem: &RealFileSystem) -> DotfResult<BackupSettings> {
+    let settings_path = filesystem.dotf_settings_path();
+    if !filesystem.exists(&settings_path).await? {
+        return Ok(BackupSettings::default());
+    }
+    let content = filesystem.read_to_string(&settings_path).await?;
+    let settings = Settings::from_toml(&content)
+        .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+    Ok(settings.backups)
+}
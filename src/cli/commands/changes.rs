@@ -0,0 +1,157 @@
+use chrono::Local;
+
+use crate::cli::{MessageFormatter, PathDisplay};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{
+    changes_service::{ChangesReport, LinkChangeKind},
+    ChangesService, StatusService,
+};
+
+pub async fn handle_changes(json: bool) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let status = StatusService::new(RepositoryRegistry::new(), filesystem.clone());
+    let report = ChangesService::new(status, RepositoryRegistry::new(), filesystem.clone())
+        .changes()
+        .await?;
+
+    if json {
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| DotfError::Operation(format!("Failed to serialize changes: {}", e)))?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    print_report(&report, &report.repo_path);
+    Ok(())
+}
+
+fn print_report(report: &ChangesReport, repo_path: &str) {
+    let formatter = MessageFormatter::new();
+    let paths = PathDisplay::new().with_repo(repo_path);
+
+    let installed_at = report
+        .installed_at
+        .with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M");
+    let revision = report
+        .revision
+        .as_deref()
+        .map(|revision| format!(" at {}", &revision[..revision.len().min(8)]))
+        .unwrap_or_default();
+    println!(
+        "{}",
+        formatter.info(&format!("Last install: {}{}", installed_at, revision))
+    );
+
+    if report.is_empty() {
+        println!(
+            "{}",
+            formatter.success("Nothing changed since the last install")
+        );
+        return;
+    }
+
+    if !report.commits.is_empty() {
+        println!("{}", formatter.section("Commits"));
+        let total = report.commits.len();
+        for (position, commit) in report.commits.iter().enumerate() {
+            let line = format!(
+                "{} {} ({}, {})",
+                commit.id,
+                commit.summary,
+                commit.author,
+                commit.date.format("%Y-%m-%d")
+            );
+            println!("{}", formatter.tree_item(&line, position + 1 == total, 1));
+        }
+    }
+
+    match &report.files {
+        Some(files) if !files.is_empty() => {
+            println!("{}", formatter.section("Repository files"));
+            for (position, file) in files.iter().enumerate() {
+                println!(
+                    "{}",
+                    formatter.tree_item(file, position + 1 == files.len(), 1)
+                );
+            }
+        }
+        Some(_) => {}
+        None => println!(
+            "{}",
+            formatter.warning("Could not compare the repository with the installed revision")
+        ),
+    }
+
+    if !report.links.is_empty() {
+        println!("{}", formatter.section("Links"));
+        let total = report.links.len();
+        for (position, link) in report.links.iter().enumerate() {
+            let target = paths.path(&link.target_path);
+            let line = match link.kind {
+                LinkChangeKind::Added => format!(
+                    "+ {} → {}",
+                    target,
+                    paths.source(link.source_path.as_deref().unwrap_or_default())
+                ),
+                LinkChangeKind::Removed => format!(
+                    "- {} (was {})",
+                    target,
+                    paths.source(link.previous_source_path.as_deref().unwrap_or_default())
+                ),
+                LinkChangeKind::Moved => format!(
+                    "~ {} → {} (was {})",
+                    target,
+                    paths.source(link.source_path.as_deref().unwrap_or_default()),
+                    paths.source(link.previous_source_path.as_deref().unwrap_or_default())
+                ),
+            };
+            println!("{}", formatter.tree_item(&line, position + 1 == total, 1));
+        }
+    }
+
+    if !report.scripts.is_empty() {
+        println!(
+            "{}",
+            formatter.section("Scripts not run since they changed")
+        );
+        let total = report.scripts.len();
+        for (position, script) in report.scripts.iter().enumerate() {
+            let name = script
+                .name
+                .as_deref()
+                .map(|name| format!("{} ({})", name, script.path))
+                .unwrap_or_else(|| format!("{} (dependencies)", script.path));
+            let last_run = match script.last_run {
+                Some(ran_at) => format!(
+                    "last ran {}",
+                    ran_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                None => "never ran".to_string(),
+            };
+            println!(
+                "{}",
+                formatter.tree_item(&format!("{}, {}", name, last_run), position + 1 == total, 1)
+            );
+        }
+    }
+
+    if !report.links.is_empty() {
+        println!();
+        println!(
+            "{}",
+            formatter.info("Run 'dotf install config' to apply the link changes")
+        );
+    }
+    if let Some(script) = report.scripts.first() {
+        let command = match &script.name {
+            Some(name) => format!("dotf install custom {}", name),
+            None => "dotf install deps".to_string(),
+        };
+        println!(
+            "{}",
+            formatter.info(&format!("Run '{}' to run a changed script again", command))
+        );
+    }
+}
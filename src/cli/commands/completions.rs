@@ -0,0 +1,56 @@
+use clap::CommandFactory;
+use clap_complete::Shell;
+
+use crate::cli::{Cli, MessageFormatter};
+use crate::core::filesystem::RealFileSystem;
+use crate::error::{DotfError, DotfResult};
+use crate::services::CompletionsService;
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_completions(shell: Option<Shell>, install: bool) -> DotfResult<()> {
+    let shell = shell.or_else(Shell::from_env).ok_or_else(|| {
+        DotfError::Validation(
+            "Could not detect your shell from $SHELL; pass it explicitly, e.g. `dotf completions zsh`"
+                .to_string(),
+        )
+    })?;
+
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "dotf", &mut script);
+    let script = String::from_utf8_lossy(&script).to_string();
+
+    if !install {
+        print!("{}", script);
+        return Ok(());
+    }
+
+    let formatter = MessageFormatter::new();
+    let service = CompletionsService::new(RealFileSystem::new(), ConsolePrompt::new());
+    let result = service.install(shell, &script).await?;
+
+    println!(
+        "{}",
+        formatter.success(&format!("{} completions written to {}", shell, result.file))
+    );
+    if let Some(rc_file) = result.rc_updated {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Updated {}; open a new shell to use completions",
+                rc_file
+            ))
+        );
+    }
+    if let Some((rc_file, rc_line)) = result.rc_skipped {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "Add this line to {} to load the completions:",
+                rc_file
+            ))
+        );
+        println!("{}", formatter.indent(&rc_line, 1));
+    }
+
+    Ok(())
+}
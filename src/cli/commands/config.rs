@@ -1,10 +1,17 @@
+use crate::cli::args::ConfigAction;
 use crate::cli::{MessageFormatter, Spinner, UiComponents};
-use crate::core::filesystem::RealFileSystem;
+use crate::core::{
+    config::EntryChange, filesystem::RealFileSystem, repository::RepositoryRegistry,
+};
 use crate::error::DotfResult;
-use crate::services::ConfigService;
+use crate::services::{ConfigDiffService, ConfigService};
 use crate::utils::ConsolePrompt;
 
-pub async fn handle_config(repo: bool, edit: bool) -> DotfResult<()> {
+pub async fn handle_config(action: Option<ConfigAction>, repo: bool, edit: bool) -> DotfResult<()> {
+    if let Some(ConfigAction::Diff { from, to }) = action {
+        return handle_config_diff(&from, &to).await;
+    }
+
     let filesystem = RealFileSystem::new();
     let prompt = ConsolePrompt::new();
     let config_service = ConfigService::new(filesystem, prompt);
@@ -68,3 +75,59 @@ pub async fn handle_config(repo: bool, edit: bool) -> DotfResult<()> {
 
     Ok(())
 }
+
+async fn handle_config_diff(from: &str, to: &str) -> DotfResult<()> {
+    let service = ConfigDiffService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+
+    let spinner = Spinner::new(&format!("Reading dotf.toml from {} and {}...", from, to));
+    let diffs = match service.diff(from, to).await {
+        Ok(diffs) => {
+            spinner.finish_and_clear();
+            diffs
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to compare configurations: {}", e));
+            return Err(e);
+        }
+    };
+
+    if diffs.is_empty() {
+        println!(
+            "{}",
+            formatter.success(&format!("dotf.toml is the same in {} and {}", from, to))
+        );
+        return Ok(());
+    }
+
+    for diff in &diffs {
+        println!("{}", formatter.section(&format!("[{}]", diff.section)));
+        for (index, change) in diff.changes.iter().enumerate() {
+            println!(
+                "{}",
+                formatter.tree_item(
+                    &describe_change(&diff.section, change),
+                    index + 1 == diff.changes.len(),
+                    1
+                )
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn describe_change(section: &str, change: &EntryChange) -> String {
+    let is_symlinks = section == "symlinks" || section.starts_with("platform.");
+    match (&change.from, &change.to) {
+        (None, Some(to)) if is_symlinks => format!("+ {} → {}", change.key, to),
+        (Some(from), None) if is_symlinks => format!("- {} → {}", change.key, from),
+        (Some(from), Some(to)) if is_symlinks => {
+            format!("~ {} retargeted: {} → {}", change.key, from, to)
+        }
+        (None, Some(to)) => format!("+ {} = {}", change.key, to),
+        (Some(from), None) => format!("- {} = {}", change.key, from),
+        (Some(from), Some(to)) => format!("~ {}: {} → {}", change.key, from, to),
+        (None, None) => change.key.clone(),
+    }
+}
@@ -0,0 +1,190 @@
+use crate::cli::{MessageFormatter, UiComponents};
+use crate::core::{
+    filesystem::RealFileSystem, repository::RepositoryRegistry, symlinks::SymlinkStatus,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{
+    diff_service::{DiffContent, FileDiff},
+    DiffService, StatusService,
+};
+use crate::utils::{
+    diff::{hunks, Hunk},
+    editor::open_in_difftool,
+};
+
+/// Unchanged lines shown around each change
+const CONTEXT_LINES: usize = 3;
+/// Diff lines printed per file; the rest is left to the [diff] tool
+const MAX_PRINTED_LINES: usize = 400;
+
+type Service = DiffService<RepositoryRegistry, RealFileSystem>;
+
+pub async fn handle_diff(target: Option<String>, tool: bool) -> DotfResult<()> {
+    let status = StatusService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    let service = DiffService::new(status, RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let difftool = service.difftool().await?;
+    if tool && difftool.is_none() {
+        return Err(DotfError::Config(
+            "No diff tool configured; set [diff] tool in ~/.dotf/settings.toml, e.g. tool = \"code --diff --wait\"".to_string(),
+        ));
+    }
+
+    // Accept the target the way it is written in dotf.toml
+    let target = target.map(
+        |target| match (target.strip_prefix("~/"), dirs::home_dir()) {
+            (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+            _ => target,
+        },
+    );
+
+    let diffs = service.diff(target.as_deref()).await?;
+    if diffs.is_empty() {
+        println!(
+            "{}",
+            formatter.success("No local differences: every deployed target matches the repository")
+        );
+        return Ok(());
+    }
+
+    for diff in &diffs {
+        println!(
+            "{}",
+            formatter.section(&format!(
+                "{} ({})",
+                diff.target_path,
+                status_label(&diff.status)
+            ))
+        );
+        let hint = tool_hint(difftool.is_some(), &diff.target_path);
+        match &diff.content {
+            DiffContent::Changes(_) | DiffContent::Binary | DiffContent::TooLarge(_) if tool => {
+                let command = difftool.as_deref().unwrap_or_default();
+                open_with_tool(&service, command, diff).await?;
+            }
+            DiffContent::Changes(lines) => {
+                let (shown, omitted) = limit_hunks(hunks(lines, CONTEXT_LINES), MAX_PRINTED_LINES);
+                println!("{}", ui.unified_diff(&diff.from, &diff.to, &shown));
+                if omitted > 0 {
+                    println!(
+                        "{}",
+                        formatter.info(&format!("{} more lines not shown; {}", omitted, hint))
+                    );
+                }
+            }
+            DiffContent::Identical => {
+                println!(
+                    "{}",
+                    formatter.info("Contents are identical to the repository source")
+                );
+            }
+            DiffContent::Binary => {
+                println!(
+                    "{}",
+                    formatter.info(&format!("Binary files differ; {}", hint))
+                );
+            }
+            DiffContent::TooLarge(size) => {
+                println!(
+                    "{}",
+                    formatter.info(&format!(
+                        "Too large to show ({} KiB); {}",
+                        size.div_ceil(1024),
+                        hint
+                    ))
+                );
+            }
+            DiffContent::NotText => {
+                println!(
+                    "{}",
+                    formatter.info("Not comparable as text (a directory or an unreadable file)")
+                );
+            }
+            DiffContent::NoBase => {
+                println!(
+                    "{}",
+                    formatter
+                        .info("Changed since the last install, but no earlier copy is recorded")
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `command` on both sides of `diff`; the copy from the last install is written to a
+/// temporary file first
+async fn open_with_tool(service: &Service, command: &str, diff: &FileDiff) -> DotfResult<()> {
+    // Removed when dropped, after the tool exits
+    let base_file = if diff.from_path.is_none() {
+        let content = service
+            .base_content(&diff.target_path)
+            .await?
+            .unwrap_or_default();
+        let file = tempfile::Builder::new()
+            .prefix("dotf-last-install-")
+            .tempfile()
+            .map_err(DotfError::Io)?;
+        std::fs::write(file.path(), content).map_err(DotfError::Io)?;
+        Some(file)
+    } else {
+        None
+    };
+    let from_path = diff
+        .from_path
+        .clone()
+        .or_else(|| {
+            base_file
+                .as_ref()
+                .map(|file| file.path().to_string_lossy().to_string())
+        })
+        .unwrap_or_default();
+
+    open_in_difftool(command, &from_path, &diff.to_path)
+}
+
+fn tool_hint(configured: bool, target_path: &str) -> String {
+    if configured {
+        format!("compare them with 'dotf diff --tool {}'", target_path)
+    } else {
+        "set [diff] tool in settings.toml to compare them in an external tool".to_string()
+    }
+}
+
+/// Keep whole hunks while they fit in `max_lines` diff lines, cutting the first one if it
+/// is longer on its own, and count the lines left out
+fn limit_hunks(hunks: Vec<Hunk>, max_lines: usize) -> (Vec<Hunk>, usize) {
+    let mut shown = Vec::new();
+    let mut used = 0;
+    let mut omitted = 0;
+    for mut hunk in hunks {
+        if omitted == 0 && used + hunk.lines.len() <= max_lines {
+            used += hunk.lines.len();
+            shown.push(hunk);
+        } else if shown.is_empty() {
+            omitted += hunk.lines.len() - max_lines;
+            hunk.lines.truncate(max_lines);
+            shown.push(hunk);
+        } else {
+            omitted += hunk.lines.len();
+        }
+    }
+    (shown, omitted)
+}
+
+fn status_label(status: &SymlinkStatus) -> &'static str {
+    match status {
+        SymlinkStatus::Conflict => "conflict",
+        SymlinkStatus::InvalidTarget => "points elsewhere",
+        SymlinkStatus::Modified => "modified",
+        SymlinkStatus::Valid => "valid",
+        SymlinkStatus::Missing => "missing",
+        SymlinkStatus::Broken => "broken",
+        SymlinkStatus::Disabled => "disabled",
+        SymlinkStatus::WrongPermissions => "wrong permissions",
+        SymlinkStatus::Unreadable => "unreadable",
+    }
+}
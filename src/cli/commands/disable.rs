@@ -0,0 +1,75 @@
+use std::path::Path;
+
+use super::adopt::resolve_target;
+use crate::cli::MessageFormatter;
+use crate::core::filesystem::RealFileSystem;
+use crate::error::DotfResult;
+use crate::services::DisableService;
+
+pub async fn handle_disable(target: Option<String>) -> DotfResult<()> {
+    let service = DisableService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+
+    let Some(target) = target else {
+        let disabled = service.list().await?;
+        if disabled.is_empty() {
+            println!(
+                "{}",
+                formatter.info("Every target in dotf.toml is linked on this machine")
+            );
+            return Ok(());
+        }
+        println!("{}", formatter.section("Disabled on this machine"));
+        let total = disabled.len();
+        for (position, target) in disabled.iter().enumerate() {
+            println!(
+                "{}",
+                formatter.tree_item(&formatter.path(target), position + 1 == total, 1)
+            );
+        }
+        return Ok(());
+    };
+
+    let target_path = resolve_target(&target)?;
+    if !service.disable(&target_path).await? {
+        println!(
+            "{}",
+            formatter.info(&format!("{} is already disabled", target))
+        );
+        return Ok(());
+    }
+    println!(
+        "{}",
+        formatter.success(&format!(
+            "Disabled {} on this machine; install, status and repair skip it",
+            target
+        ))
+    );
+    if Path::new(&target_path).is_symlink() {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "The existing link stays; remove it with 'rm {}' if this machine should not have it",
+                target
+            ))
+        );
+    }
+    Ok(())
+}
+
+pub async fn handle_enable(target: String) -> DotfResult<()> {
+    let service = DisableService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+
+    let target_path = resolve_target(&target)?;
+    if !service.enable(&target_path).await? {
+        println!("{}", formatter.info(&format!("{} is not disabled", target)));
+        return Ok(());
+    }
+    println!(
+        "{}",
+        formatter.success(&format!("Enabled {} on this machine", target))
+    );
+    println!("{}", formatter.info("Run 'dotf install config' to link it"));
+    Ok(())
+}
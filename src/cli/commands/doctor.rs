@@ -0,0 +1,67 @@
+use crate::cli::{MessageFormatter, OperationResult, OperationStatus, Spinner, UiComponents};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::DotfResult;
+use crate::services::{doctor_service::CheckStatus, DoctorService};
+
+pub async fn handle_doctor(slow: bool, enabled_features: Vec<String>) -> DotfResult<()> {
+    let doctor_service = DoctorService::new(RealFileSystem::new())
+        .with_repository(Box::new(RepositoryRegistry::new()))
+        .with_enabled_features(enabled_features);
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Running checks...");
+    let checks = if slow {
+        doctor_service.slow_checks().await
+    } else {
+        doctor_service.run().await
+    };
+    let checks = match checks {
+        Ok(checks) => {
+            spinner.finish_and_clear();
+            checks
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Doctor failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    let results: Vec<OperationResult> = checks
+        .iter()
+        .map(|check| OperationResult {
+            operation: format!("{}: {}", check.name, check.message),
+            status: match check.status {
+                CheckStatus::Ok => OperationStatus::Success,
+                CheckStatus::Warning => OperationStatus::Warning,
+                CheckStatus::Error => OperationStatus::Failed,
+            },
+            details: check.hint.clone(),
+        })
+        .collect();
+    let title = if slow { "Slow Steps" } else { "Doctor" };
+    println!("{}", ui.operation_results(title, &results));
+
+    let errors = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Error)
+        .count();
+    let warnings = checks
+        .iter()
+        .filter(|check| check.status == CheckStatus::Warning)
+        .count();
+
+    println!();
+    if errors > 0 {
+        println!(
+            "{}",
+            formatter.error(&format!("{} problem(s), {} warning(s)", errors, warnings))
+        );
+    } else if warnings > 0 {
+        println!("{}", formatter.warning(&format!("{} warning(s)", warnings)));
+    } else {
+        println!("{}", formatter.success("Everything looks good"));
+    }
+
+    Ok(())
+}
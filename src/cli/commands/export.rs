@@ -0,0 +1,43 @@
+use crate::cli::args::ExportFormat;
+use crate::cli::MessageFormatter;
+use crate::core::{
+    filesystem::RealFileSystem, repository::RepositoryRegistry, scripts::SystemScriptExecutor,
+};
+use crate::error::DotfResult;
+use crate::services::{ExportService, InstallService};
+use crate::traits::filesystem::FileSystem;
+use crate::utils::NonInteractivePrompt;
+
+pub async fn handle_export(format: ExportFormat) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let export_service = ExportService::new(filesystem.clone());
+    let formatter = MessageFormatter::new();
+
+    match format {
+        ExportFormat::HomeManager { output } => {
+            // The same plan as install, so profiles, conditions and overlays apply
+            let steps = InstallService::new(
+                filesystem.clone(),
+                SystemScriptExecutor::new(),
+                NonInteractivePrompt::new(),
+            )
+            .with_repository(Box::new(RepositoryRegistry::new()))
+            .plan_config()
+            .await?;
+            let module = export_service.export_home_manager(&steps).await?;
+
+            match output {
+                Some(path) => {
+                    filesystem.write(&path, &module).await?;
+                    println!(
+                        "{}",
+                        formatter.success(&format!("home-manager module written to {}", path))
+                    );
+                }
+                None => print!("{}", module),
+            }
+        }
+    }
+
+    Ok(())
+}
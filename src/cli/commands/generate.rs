@@ -0,0 +1,93 @@
+use crate::cli::args::GenerateTarget;
+use crate::cli::MessageFormatter;
+use crate::core::{config::DotfConfig, filesystem::RealFileSystem};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{generate_service::DockerfileOptions, ConfigService, GenerateService};
+use crate::utils::{platform::detect_platform, ConsolePrompt};
+
+pub async fn handle_generate(target: GenerateTarget) -> DotfResult<()> {
+    let service = GenerateService::new();
+    let formatter = MessageFormatter::new();
+
+    match target {
+        GenerateTarget::DepsScript {
+            platform,
+            output,
+            file,
+            force,
+        } => {
+            let platform = platform.unwrap_or_else(detect_platform);
+            let config_file = file.unwrap_or_else(|| "dotf.toml".to_string());
+
+            let generated = service
+                .deps_script(&platform, output, &config_file, force)
+                .await?;
+
+            println!(
+                "{}",
+                formatter.success(&format!(
+                    "Created {} ({})",
+                    generated.script_path,
+                    generated.package_manager.name()
+                ))
+            );
+            if generated.config_updated {
+                println!(
+                    "{}",
+                    formatter.info(&format!(
+                        "Registered as {} in {}",
+                        generated.config_key, config_file
+                    ))
+                );
+            }
+            println!(
+                "{}",
+                formatter.info("Edit the PACKAGES list, then run 'dotf install deps'")
+            );
+        }
+        GenerateTarget::Dockerfile {
+            base_image,
+            user,
+            custom,
+            no_deps,
+            output,
+            force,
+        } => {
+            let config_service = ConfigService::new(RealFileSystem::new(), ConsolePrompt::new());
+            let settings = config_service.show_settings().await?;
+            let config: DotfConfig =
+                toml::from_str(&config_service.show_repository_config().await?)
+                    .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
+
+            let options = DockerfileOptions {
+                base_image,
+                user,
+                deps: !no_deps,
+                custom,
+            };
+            let generated = service
+                .dockerfile(&settings, &config, &options, &output, force)
+                .await?;
+
+            println!(
+                "{}",
+                formatter.success(&format!("Created {}", generated.path))
+            );
+            if !generated.unanswered.is_empty() {
+                println!(
+                    "{}",
+                    formatter.warning(&format!(
+                        "No answer or default for prompt(s) {}; the build cannot ask for them",
+                        generated.unanswered.join(", ")
+                    ))
+                );
+            }
+            println!(
+                "{}",
+                formatter.info("Stored prompt answers are written into the image")
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,46 @@
+use chrono::Utc;
+use std::time::Duration;
+
+use super::status::refresh_in_background;
+use crate::core::{
+    config::Settings,
+    filesystem::RealFileSystem,
+    history::HistoryStore,
+    widget::{render_greeting, DEFAULT_MAX_AGE},
+};
+use crate::traits::filesystem::FileSystem;
+
+/// Longest a shell startup waits for the greeting; a slower read prints nothing
+const GREET_BUDGET: Duration = Duration::from_millis(100);
+
+/// Summarize the cached status for a shell greeting. Never fails and never prints
+/// anything else, so it cannot break the shell startup file that calls it.
+pub async fn handle_greet() {
+    let filesystem = RealFileSystem::new();
+    let Ok(content) = std::fs::read_to_string(filesystem.dotf_settings_path()) else {
+        return;
+    };
+    let Ok(settings) = Settings::from_toml(&content) else {
+        return;
+    };
+
+    let history = HistoryStore::new(filesystem.clone());
+    let snapshot = match tokio::time::timeout(GREET_BUDGET, history.latest()).await {
+        Ok(Ok(snapshot)) => snapshot,
+        _ => return,
+    };
+
+    let max_age = settings.widget.max_age.unwrap_or(DEFAULT_MAX_AGE) as i64;
+    let stale = snapshot
+        .as_ref()
+        .is_none_or(|snapshot| (Utc::now() - snapshot.taken_at).num_seconds() >= max_age);
+    if stale {
+        refresh_in_background(&filesystem.dotf_directory());
+    }
+
+    if let Some(snapshot) = snapshot {
+        for line in render_greeting(&snapshot) {
+            println!("{}", line);
+        }
+    }
+}
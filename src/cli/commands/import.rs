@@ -0,0 +1,130 @@
+use std::path::Path;
+
+use crate::cli::args::ImportSource;
+use crate::cli::{MessageFormatter, Spinner};
+use crate::core::{
+    filesystem::RealFileSystem,
+    migrate::{BareRepo, BARE_REPO_CANDIDATES},
+    repository::RepositoryRegistry,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::ImportService;
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+use crate::utils::ConsolePrompt;
+
+/// Files listed before the rest are summarised as a count
+const PREVIEW_FILES: usize = 10;
+
+pub async fn handle_import(source: ImportSource) -> DotfResult<()> {
+    match source {
+        ImportSource::Bare { git_dir, dest } => handle_import_bare(git_dir, dest).await,
+    }
+}
+
+async fn handle_import_bare(git_dir: Option<String>, dest: Option<String>) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let filesystem = RealFileSystem::new();
+    let home = dirs::home_dir()
+        .ok_or_else(|| DotfError::Operation("Could not determine home directory".to_string()))?;
+
+    let bare = match git_dir {
+        Some(git_dir) => {
+            BareRepo::open(Path::new(&super::adopt::resolve_target(&git_dir)?), &home)?
+        }
+        None => BareRepo::detect(&home).ok_or_else(|| {
+            DotfError::Repository(format!(
+                "No bare repository found at ~/{}; pass --git-dir",
+                BARE_REPO_CANDIDATES.join(", ~/")
+            ))
+        })?,
+    };
+    let destination = match dest {
+        Some(dest) => super::adopt::resolve_target(&dest)?,
+        None => filesystem.dotf_repo_path(),
+    };
+
+    let import_service = ImportService::new(RepositoryRegistry::new(), filesystem);
+    let plan = import_service.plan(bare)?;
+
+    println!("{}", formatter.section("Bare Repository Import"));
+    println!(
+        "{}",
+        formatter.key_value("Repository", &plan.bare.git_dir.to_string_lossy())
+    );
+    if let Some(remote) = &plan.remote {
+        println!("{}", formatter.key_value("Remote", remote));
+    }
+    println!("{}", formatter.key_value("Destination", &destination));
+    println!(
+        "{}",
+        formatter.key_value("Tracked files", &plan.files.len().to_string())
+    );
+    for file in plan.files.iter().take(PREVIEW_FILES) {
+        println!(
+            "{}",
+            formatter.indent(&formatter.path(&format!("~/{}", file)), 1)
+        );
+    }
+    if plan.files.len() > PREVIEW_FILES {
+        println!(
+            "{}",
+            formatter.indent(
+                &format!("... and {} more", plan.files.len() - PREVIEW_FILES),
+                1
+            )
+        );
+    }
+
+    if !plan.uncommitted.is_empty() {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "Uncommitted changes in: {}",
+                plan.uncommitted.join(", ")
+            ))
+        );
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Commit them first: git --git-dir={} --work-tree=$HOME commit -a",
+                plan.bare.git_dir.display()
+            ))
+        );
+        return Err(DotfError::Validation(
+            "The bare repository has uncommitted changes".to_string(),
+        ));
+    }
+
+    if !ConsolePrompt::new()
+        .confirm(&formatter.question("Convert these files into a dotf repository?"))
+        .await?
+    {
+        println!("{}", formatter.info("Import cancelled"));
+        return Ok(());
+    }
+
+    let spinner = Spinner::new("Cloning and verifying...");
+    match import_service.import(&plan, &destination).await {
+        Ok(()) => spinner.finish_with_success("Imported into a dotf repository"),
+        Err(e) => {
+            spinner.finish_with_error(&format!("Import failed: {}", e));
+            return Err(e);
+        }
+    }
+
+    println!(
+        "{}",
+        formatter.info(
+            "Run 'dotf install config' to replace the files in your home directory with symlinks"
+        )
+    );
+    println!(
+        "{}",
+        formatter.info(&format!(
+            "Once everything works, remove {} and any alias that used it",
+            plan.bare.git_dir.display()
+        ))
+    );
+
+    Ok(())
+}
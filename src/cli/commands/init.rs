@@ -1,12 +1,18 @@
-use crate::cli::{InstallAnimation, InterruptionContext, InterruptionHandler, MessageFormatter};
-use crate::core::{filesystem::RealFileSystem, repository::GitRepository};
+use crate::cli::{
+    InstallAnimation, InstallStage, InterruptionContext, InterruptionHandler, MessageFormatter,
+};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
 use crate::error::{DotfError, DotfResult};
 use crate::services::EnhancedInitService;
 use crate::utils::ConsolePrompt;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-pub async fn handle_init(repo: Option<String>) -> DotfResult<()> {
+pub async fn handle_init(
+    repo: Option<String>,
+    sparse: bool,
+    from_local: Option<String>,
+) -> DotfResult<()> {
     let formatter = MessageFormatter::new();
 
     // Create interruption handler for graceful cancellation
@@ -14,10 +20,11 @@ pub async fn handle_init(repo: Option<String>) -> DotfResult<()> {
     let interrupted = interruption_handler.setup_handlers().await;
 
     // Create enhanced init service for animations
-    let repository = GitRepository::new();
+    let repository = RepositoryRegistry::new();
     let filesystem = RealFileSystem::new();
     let prompt = ConsolePrompt::new();
-    let enhanced_init_service = EnhancedInitService::new(repository, filesystem, prompt);
+    let enhanced_init_service =
+        EnhancedInitService::new(repository, filesystem, prompt).with_sparse(sparse);
 
     // Create animation handler
     let animation = InstallAnimation::new();
@@ -27,13 +34,28 @@ pub async fn handle_init(repo: Option<String>) -> DotfResult<()> {
     animation.show_welcome(version).await;
 
     // Run initialization with animated progress and interruption handling
-    let init_future = enhanced_init_service.init_with_progress(repo, |stage| {
+    let show_stage = |stage: &InstallStage| {
         tokio::task::block_in_place(|| {
             tokio::runtime::Handle::current().block_on(async {
                 animation.show_stage(stage).await;
             })
         });
-    });
+    };
+    let init_future = async {
+        match from_local {
+            Some(path) => {
+                let path = std::path::absolute(&path)?;
+                enhanced_init_service
+                    .init_from_local(&path.to_string_lossy(), show_stage)
+                    .await
+            }
+            None => {
+                enhanced_init_service
+                    .init_with_progress(repo, show_stage)
+                    .await
+            }
+        }
+    };
 
     // Make the operation cancellable
     tokio::select! {
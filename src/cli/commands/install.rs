@@ -1,17 +1,114 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
 use crate::cli::args::InstallTarget;
-use crate::cli::Spinner;
-use crate::core::{filesystem::RealFileSystem, scripts::SystemScriptExecutor};
-use crate::error::DotfResult;
-use crate::services::InstallService;
+use crate::cli::json::{self, JsonProgress};
+use crate::cli::{
+    MessageFormatter, OperationResult, OperationStatus, PathDisplay, Spinner, UiComponents,
+};
+use crate::core::{
+    bootstrap::BootstrapStatus,
+    cron::SystemCrontab,
+    filesystem::RealFileSystem,
+    network,
+    repository::RepositoryRegistry,
+    sandbox::SANDBOX_ENV,
+    scripts::{HookEvent, SystemScriptExecutor},
+    symlinks::{BackupEntry, ConflictResolution, ConflictType, LinkAction, LinkStep, SudoHelper},
+    tools::ToolInstaller,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{
+    env_service::EnvFileStatus,
+    packages_service::PackageInstallStatus,
+    tools_service::{ToolInstallOutcome, ToolInstallStatus},
+    BootstrapService, CronService, EnvService, HookService, InstallService, PackagesService,
+    SshService, ToolsService,
+};
+use crate::traits::progress::{LinkProgress, ProgressEvent, ProgressReporter};
 use crate::utils::ConsolePrompt;
 
-pub async fn handle_install(target: InstallTarget) -> DotfResult<()> {
-    let install_service = create_install_service();
+pub async fn handle_install(
+    target: InstallTarget,
+    non_interactive: bool,
+    conflict_policy: Option<ConflictResolution>,
+    metered: bool,
+) -> DotfResult<()> {
+    // JSON output leaves no room for prompts; conflicting files are kept unless
+    // --on-conflict or --yes says otherwise
+    if let InstallTarget::Config {
+        skip_missing,
+        include_export_ignored,
+        changed_only,
+        repo,
+        json: true,
+        ..
+    } = target
+    {
+        let install_service = create_install_service()
+            .with_conflict_policy(Some(conflict_policy.unwrap_or(ConflictResolution::Skip)))
+            .with_skip_missing(skip_missing)
+            .with_include_export_ignored(include_export_ignored)
+            .with_changed_only(changed_only)
+            .with_only_repository(repo);
+        return install_config_json(install_service).await;
+    }
+    let install_service = create_install_service().with_conflict_policy(conflict_policy.clone());
 
+    // Non-interactive runs leave [prompts] unanswered until 'dotf prompts edit'; dry runs
+    // only look
+    if !non_interactive
+        && !matches!(target, InstallTarget::Config { dry_run: true, .. })
+        && matches!(
+            target,
+            InstallTarget::Deps
+                | InstallTarget::Config { .. }
+                | InstallTarget::Custom { .. }
+                | InstallTarget::All { .. }
+        )
+    {
+        super::prompts::ask_missing().await?;
+    }
+
+    let Some(hook_target) = hook_target(&target) else {
+        return install_target(
+            target,
+            install_service,
+            non_interactive,
+            conflict_policy,
+            metered,
+        )
+        .await;
+    };
+    let hook_env = BTreeMap::from([("DOTF_INSTALL_TARGET".to_string(), hook_target.to_string())]);
+    run_hook(HookEvent::PreInstall, &hook_env).await?;
+    install_target(
+        target,
+        install_service,
+        non_interactive,
+        conflict_policy,
+        metered,
+    )
+    .await?;
+    run_hook(HookEvent::PostInstall, &hook_env).await
+}
+
+async fn install_target(
+    target: InstallTarget,
+    install_service: InstallService<RealFileSystem, SystemScriptExecutor, ConsolePrompt>,
+    non_interactive: bool,
+    conflict_policy: Option<ConflictResolution>,
+    metered: bool,
+) -> DotfResult<()> {
     match target {
         InstallTarget::Deps => {
-            let spinner = Spinner::new("Installing dependencies...");
-            match install_service.install_dependencies().await {
+            let spinner = Arc::new(Spinner::new("Installing dependencies..."));
+            match install_service
+                .with_progress(spinner.clone())
+                .install_dependencies()
+                .await
+            {
                 Ok(_) => spinner.finish_with_success("Dependencies installed successfully!"),
                 Err(e) => {
                     spinner.finish_with_error(&format!("Dependencies installation failed: {}", e));
@@ -19,38 +116,674 @@ pub async fn handle_install(target: InstallTarget) -> DotfResult<()> {
                 }
             }
         }
-        InstallTarget::Config => {
-            let spinner = Spinner::new("Installing configuration...");
-            match install_service.install_config().await {
+        InstallTarget::Config {
+            skip_missing,
+            include_export_ignored,
+            changed_only,
+            dry_run,
+            repo,
+            ..
+        } => {
+            let install_service = install_service
+                .with_skip_missing(skip_missing)
+                .with_include_export_ignored(include_export_ignored)
+                .with_changed_only(changed_only)
+                .with_only_repository(repo);
+            if dry_run {
+                let plan = install_service.plan_config().await?;
+                show_link_plan(&plan, conflict_policy.as_ref(), non_interactive);
+                return Ok(());
+            }
+
+            let spinner = Arc::new(Spinner::new("Installing configuration..."));
+            match install_service
+                .with_progress(spinner.clone())
+                .install_config()
+                .await
+            {
                 Ok(_) => spinner.finish_with_success("Configuration installed successfully!"),
                 Err(e) => {
                     spinner.finish_with_error(&format!("Configuration installation failed: {}", e));
                     return Err(e);
                 }
             }
+            after_install_config().await?;
         }
-        InstallTarget::Custom { name } => {
-            let spinner = Spinner::new(&format!("Running custom script: {}", name));
-            match install_service.install_custom(&name).await {
-                Ok(_) => spinner.finish_with_success(&format!(
+        InstallTarget::All {
+            skip_missing,
+            custom,
+        } => {
+            let spinner = Arc::new(Spinner::new("Installing dependencies and configuration..."));
+            match install_service
+                .with_progress(spinner.clone())
+                .with_skip_missing(skip_missing)
+                .install_all(custom)
+                .await
+            {
+                Ok(_) => spinner.finish_with_success("Installation completed!"),
+                Err(e) => {
+                    spinner.finish_with_error(&format!("Installation failed: {}", e));
+                    return Err(e);
+                }
+            }
+            after_install_config().await?;
+        }
+        InstallTarget::Custom { name, args } => {
+            let spinner = Arc::new(Spinner::new(&format!("Running custom script: {}", name)));
+            match install_service
+                .with_progress(spinner.clone())
+                .install_custom(&name, &args)
+                .await
+            {
+                Ok(Some(_)) => spinner.finish_with_success(&format!(
                     "Custom script '{}' completed successfully!",
                     name
                 )),
+                Ok(None) => spinner.finish_with_success(&format!(
+                    "Custom script '{}' skipped (already ran)",
+                    name
+                )),
                 Err(e) => {
                     spinner.finish_with_error(&format!("Custom script '{}' failed: {}", name, e));
                     return Err(e);
                 }
             }
         }
+        InstallTarget::Bootstrap => handle_bootstrap().await?,
+        InstallTarget::Cron => handle_install_cron().await?,
+        InstallTarget::Ssh => handle_install_ssh().await?,
+        InstallTarget::Env { force } => {
+            if regenerate_env(force).await? == EnvFileStatus::NotConfigured {
+                println!(
+                    "{}",
+                    MessageFormatter::new().info("No environment configured in [env]")
+                );
+            }
+        }
+        InstallTarget::Tools { name } => handle_install_tools(name.as_deref(), metered).await?,
+        InstallTarget::Packages => handle_install_packages(metered).await?,
+    }
+
+    Ok(())
+}
+
+/// What `install config --json` did
+#[derive(Serialize)]
+struct InstallReport {
+    /// What was planned at each target before linking
+    operations: Vec<LinkStep>,
+    links: Option<LinkProgress>,
+    backups: Vec<BackupEntry>,
+}
+
+/// `install config --json`: link and print the outcome as a JSON report. The steps that
+/// follow linking are left to their own `dotf install` targets.
+async fn install_config_json(
+    install_service: InstallService<RealFileSystem, SystemScriptExecutor, ConsolePrompt>,
+) -> DotfResult<()> {
+    let progress = Arc::new(JsonProgress::new());
+    let install_service = install_service.with_progress(progress.clone());
+
+    let hook_env = BTreeMap::from([("DOTF_INSTALL_TARGET".to_string(), "config".to_string())]);
+    let result = async {
+        run_hook_quietly(HookEvent::PreInstall, &hook_env, progress.as_ref()).await?;
+        let operations = install_service.plan_config().await?;
+        let backups = install_service.install_config().await?;
+        run_hook_quietly(HookEvent::PostInstall, &hook_env, progress.as_ref()).await?;
+        Ok(InstallReport {
+            operations,
+            links: progress.links(),
+            backups,
+        })
+    }
+    .await;
+    json::print_report("install", result, progress.warnings())
+}
+
+/// Run the `[hooks]` script for `event` without printing; a failure with
+/// `on_failure = "continue"` becomes a warning on `progress`
+pub(super) async fn run_hook_quietly(
+    event: HookEvent,
+    env: &BTreeMap<String, String>,
+    progress: &dyn ProgressReporter,
+) -> DotfResult<()> {
+    let hook_service = HookService::new(RealFileSystem::new(), SystemScriptExecutor::new());
+    if let Some(outcome) = hook_service.run(event, env).await? {
+        if !outcome.result.success {
+            progress.report(ProgressEvent::Warning(format!(
+                "{} hook {} failed with exit code {}; continuing",
+                event.name(),
+                outcome.script,
+                outcome.result.exit_code
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Run the `[hooks]` script for `event`; one that fails with `on_failure = "continue"`
+/// is reported and the command goes on
+pub(super) async fn run_hook(event: HookEvent, env: &BTreeMap<String, String>) -> DotfResult<()> {
+    let hook_service = HookService::new(RealFileSystem::new(), SystemScriptExecutor::new());
+    let formatter = MessageFormatter::new();
+
+    let Some(outcome) = hook_service.run(event, env).await? else {
+        return Ok(());
+    };
+    if outcome.result.success {
+        println!(
+            "{}",
+            formatter.success(&format!("Ran {} hook {}", event.name(), outcome.script))
+        );
+    } else {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "{} hook {} failed with exit code {}; continuing",
+                event.name(),
+                outcome.script,
+                outcome.result.exit_code
+            ))
+        );
+    }
+    Ok(())
+}
+
+/// Value of `DOTF_INSTALL_TARGET` for the install hooks; dry runs run none
+fn hook_target(target: &InstallTarget) -> Option<&'static str> {
+    match target {
+        InstallTarget::Config { dry_run: true, .. } => None,
+        InstallTarget::Deps => Some("deps"),
+        InstallTarget::Config { .. } => Some("config"),
+        InstallTarget::All { .. } => Some("all"),
+        InstallTarget::Custom { .. } => Some("custom"),
+        InstallTarget::Bootstrap => Some("bootstrap"),
+        InstallTarget::Cron => Some("cron"),
+        InstallTarget::Ssh => Some("ssh"),
+        InstallTarget::Env { .. } => Some("env"),
+        InstallTarget::Tools { .. } => Some("tools"),
+        InstallTarget::Packages => Some("packages"),
+    }
+}
+
+/// Print what `install config` would do at each target
+fn show_link_plan(
+    plan: &[LinkStep],
+    conflict_policy: Option<&ConflictResolution>,
+    non_interactive: bool,
+) {
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    if plan.is_empty() {
+        println!("{}", formatter.info("No symlinks configured"));
+        return;
+    }
+
+    let paths = PathDisplay::new();
+
+    let results: Vec<OperationResult> = plan
+        .iter()
+        .map(|step| {
+            let (status, details) = match &step.action {
+                LinkAction::Create => (OperationStatus::InProgress, "link".to_string()),
+                LinkAction::AlreadyLinked => {
+                    (OperationStatus::Skipped, "already linked".to_string())
+                }
+                LinkAction::Resolve(conflict)
+                    if conflict.conflict_type == ConflictType::NixStore =>
+                {
+                    (
+                        OperationStatus::Skipped,
+                        "Nix-managed, left alone".to_string(),
+                    )
+                }
+                LinkAction::Resolve(_) => match conflict_policy {
+                    Some(ConflictResolution::Backup) => (
+                        OperationStatus::InProgress,
+                        "back up the existing file and link".to_string(),
+                    ),
+                    Some(ConflictResolution::Overwrite) => (
+                        OperationStatus::InProgress,
+                        "replace the existing file".to_string(),
+                    ),
+                    Some(ConflictResolution::Skip) => (
+                        OperationStatus::Skipped,
+                        "keep the existing file".to_string(),
+                    ),
+                    Some(ConflictResolution::Abort) => (
+                        OperationStatus::Failed,
+                        "existing file aborts the install".to_string(),
+                    ),
+                    None if non_interactive => (
+                        OperationStatus::Failed,
+                        "existing file needs --on-conflict".to_string(),
+                    ),
+                    None => (
+                        OperationStatus::Warning,
+                        "asks how to resolve the existing file".to_string(),
+                    ),
+                },
+            };
+            OperationResult {
+                operation: paths.path(&step.operation.target_path),
+                status,
+                details: Some(format!(
+                    "{} ({})",
+                    details,
+                    paths.path(&step.operation.source_path)
+                )),
+            }
+        })
+        .collect();
+    println!("{}", ui.operation_results("Planned Symlinks", &results));
+
+    let count = |matches: fn(&LinkAction) -> bool| {
+        plan.iter()
+            .filter(|step| matches(&step.action))
+            .count()
+            .to_string()
+    };
+    let to_link = count(|action| matches!(action, LinkAction::Create));
+    let conflicts = count(|action| match action {
+        LinkAction::Resolve(conflict) => conflict.conflict_type != ConflictType::NixStore,
+        _ => false,
+    });
+    let linked = count(|action| matches!(action, LinkAction::AlreadyLinked));
+    println!(
+        "\n{}",
+        formatter.summary_box(
+            "Install Plan",
+            &[
+                ("To link", to_link.as_str()),
+                ("Conflicts", conflicts.as_str()),
+                ("Already linked", linked.as_str()),
+            ]
+        )
+    );
+    println!(
+        "{}",
+        formatter.info("Run 'dotf install config' to apply these changes")
+    );
+}
+
+/// The steps that follow linking the configuration: plugin managers, crontab, macOS
+/// defaults, SSH, secrets and the environment fragment
+async fn after_install_config() -> DotfResult<()> {
+    handle_bootstrap().await?;
+    if std::env::var_os(SANDBOX_ENV).is_some() {
+        // The crontab and macOS defaults belong to the real user, not the sandbox home
+        let formatter = MessageFormatter::new();
+        println!(
+            "{}",
+            formatter.info("Sandbox run: skipping crontab and macOS defaults")
+        );
+    } else {
+        super::macos_defaults::apply_after_install().await?;
+        handle_install_cron().await?;
+    }
+    handle_install_ssh().await?;
+    super::secrets::decrypt(None, false).await?;
+    regenerate_env(false).await?;
+    Ok(())
+}
+
+async fn handle_bootstrap() -> DotfResult<()> {
+    let bootstrap_service = BootstrapService::new(RealFileSystem::new());
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Bootstrapping plugin managers...");
+    let outcomes = match bootstrap_service.run().await {
+        Ok(outcomes) => {
+            spinner.finish_and_clear();
+            outcomes
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Bootstrap failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if outcomes.is_empty() {
+        return Ok(());
+    }
+
+    let results: Vec<OperationResult> = outcomes
+        .iter()
+        .map(|outcome| {
+            let elapsed = format!("{:.1}s", outcome.duration.as_secs_f64());
+            let (status, details) = match &outcome.status {
+                BootstrapStatus::Completed => (OperationStatus::Success, elapsed),
+                BootstrapStatus::Skipped(reason) => (OperationStatus::Skipped, reason.clone()),
+                BootstrapStatus::Failed(error) => (OperationStatus::Failed, error.clone()),
+                BootstrapStatus::TimedOut => (
+                    OperationStatus::Failed,
+                    format!(
+                        "timed out after {}; raise [bootstrap] timeout if it needs longer",
+                        elapsed
+                    ),
+                ),
+            };
+            OperationResult {
+                operation: outcome.name.clone(),
+                status,
+                details: Some(details),
+            }
+        })
+        .collect();
+    println!("{}", ui.operation_results("Plugin Bootstrap", &results));
+
+    Ok(())
+}
+
+/// Regenerate the `[env]` fragment and report what happened; silent when nothing changed
+pub async fn regenerate_env(force: bool) -> DotfResult<EnvFileStatus> {
+    let env_service = EnvService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+    let env_path = env_service.env_path();
+
+    let status = env_service.generate(force).await?;
+    match status {
+        EnvFileStatus::Written => {
+            println!(
+                "{}",
+                formatter.success(&format!("Generated {}", formatter.path(&env_path)))
+            );
+            println!(
+                "{}",
+                formatter.info("Run 'dotf doctor' to check that your shell sources it")
+            );
+        }
+        EnvFileStatus::Removed => println!(
+            "{}",
+            formatter.info(&format!("Removed {} ([env] is empty)", env_path))
+        ),
+        EnvFileStatus::Conflict => println!(
+            "{}",
+            formatter.warning(&format!(
+                "{} was edited by hand and was not regenerated. Move the changes into [env], then run 'dotf install env --force'",
+                env_path
+            ))
+        ),
+        EnvFileStatus::Unchanged | EnvFileStatus::NotConfigured => {}
+    }
+
+    Ok(status)
+}
+
+async fn handle_install_cron() -> DotfResult<()> {
+    let cron_service = CronService::new(RealFileSystem::new(), SystemCrontab::new());
+    let formatter = MessageFormatter::new();
+
+    let spinner = Spinner::new("Updating crontab...");
+    let outcome = match cron_service.install().await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            spinner.finish_with_error(&format!("Crontab update failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if outcome.is_unchanged() {
+        spinner.finish_and_clear();
+        return Ok(());
+    }
+
+    spinner.finish_with_success("Crontab updated");
+    for (label, names) in [
+        ("added", &outcome.added),
+        ("changed", &outcome.changed),
+        ("removed", &outcome.removed),
+    ] {
+        if !names.is_empty() {
+            println!(
+                "{}",
+                formatter.indent(&format!("{}: {}", label, names.join(", ")), 1)
+            );
+        }
+    }
+    if outcome.replaced_drift {
+        println!(
+            "{}",
+            formatter.warning("Manual edits inside the dotf-managed crontab block were replaced")
+        );
+    }
+
+    Ok(())
+}
+
+async fn handle_install_ssh() -> DotfResult<()> {
+    let ssh_service = SshService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+
+    let spinner = Spinner::new("Updating ~/.ssh...");
+    let outcome = match ssh_service.install().await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            spinner.finish_with_error(&format!("SSH update failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if outcome.is_unchanged() {
+        spinner.finish_and_clear();
+        return Ok(());
+    }
+
+    spinner.finish_with_success("~/.ssh updated");
+    if outcome.config_updated {
+        println!("{}", formatter.indent("config: managed block rewritten", 1));
+    }
+    if outcome.known_hosts_added > 0 {
+        println!(
+            "{}",
+            formatter.indent(
+                &format!("known_hosts: {} entries added", outcome.known_hosts_added),
+                1
+            )
+        );
+    }
+    for (label, paths) in [
+        ("copied", &outcome.keys_copied),
+        ("permissions tightened", &outcome.permissions_fixed),
+    ] {
+        if !paths.is_empty() {
+            println!(
+                "{}",
+                formatter.indent(&format!("{}: {}", label, paths.join(", ")), 1)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn handle_install_tools(name: Option<&str>, metered: bool) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let metered = network::is_metered(&filesystem, metered).await;
+    let tools_service = ToolsService::new(filesystem).with_metered(metered);
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Installing tools...");
+    let outcomes = match tools_service.install(&ToolInstaller::new(), name).await {
+        Ok(outcomes) => {
+            spinner.finish_and_clear();
+            outcomes
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Tool installation failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if outcomes.is_empty() {
+        println!("{}", formatter.info("No tools configured in [tools]"));
+        return Ok(());
+    }
+
+    let results: Vec<OperationResult> = outcomes.iter().map(tool_result).collect();
+    println!("{}", ui.operation_results("Tools", &results));
+    let deferred: Vec<&str> = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.status, ToolInstallStatus::Deferred))
+        .map(|outcome| outcome.name.as_str())
+        .collect();
+    report_deferred("tools", &deferred);
+
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.status, ToolInstallStatus::Failed(_)))
+        .count();
+    if failed > 0 {
+        return Err(DotfError::Operation(format!(
+            "{} of {} tools failed to install",
+            failed,
+            outcomes.len()
+        )));
     }
 
     Ok(())
 }
 
+async fn handle_install_packages(metered: bool) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let metered = network::is_metered(&filesystem, metered).await;
+    let packages_service = PackagesService::new(filesystem).with_metered(metered);
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Installing packages...");
+    let outcomes = match packages_service.install().await {
+        Ok(outcomes) => {
+            spinner.finish_and_clear();
+            outcomes
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Package installation failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if outcomes.is_empty() {
+        println!("{}", formatter.info("No packages configured in [packages]"));
+        return Ok(());
+    }
+
+    let results: Vec<OperationResult> = outcomes
+        .iter()
+        .map(|outcome| {
+            let (status, details) = match &outcome.status {
+                PackageInstallStatus::Installed => {
+                    (OperationStatus::Success, "installed".to_string())
+                }
+                PackageInstallStatus::AlreadyInstalled => {
+                    (OperationStatus::Skipped, "already installed".to_string())
+                }
+                PackageInstallStatus::Skipped(reason) => {
+                    (OperationStatus::Warning, format!("skipped: {}", reason))
+                }
+                PackageInstallStatus::Deferred => {
+                    (OperationStatus::Skipped, "deferred (metered)".to_string())
+                }
+                PackageInstallStatus::Failed(error) => (OperationStatus::Failed, error.clone()),
+            };
+            OperationResult {
+                operation: outcome.package.name.clone(),
+                status,
+                details: Some(format!("{} ({})", details, outcome.package.kind)),
+            }
+        })
+        .collect();
+    println!("{}", ui.operation_results("Packages", &results));
+    let deferred: Vec<&str> = outcomes
+        .iter()
+        .filter(|outcome| outcome.status == PackageInstallStatus::Deferred)
+        .map(|outcome| outcome.package.name.as_str())
+        .collect();
+    report_deferred("packages", &deferred);
+
+    let failed = outcomes
+        .iter()
+        .filter(|outcome| matches!(outcome.status, PackageInstallStatus::Failed(_)))
+        .count();
+    if failed > 0 {
+        return Err(DotfError::Operation(format!(
+            "{} of {} packages failed to install",
+            failed,
+            outcomes.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Name the downloads that were put off and how to make them later
+fn report_deferred(target: &str, names: &[&str]) {
+    if names.is_empty() {
+        return;
+    }
+    println!(
+        "{}",
+        MessageFormatter::new().info(&format!(
+            "Metered connection: deferred {}. Run 'dotf install {}' on an unmetered connection",
+            names.join(", "),
+            target
+        ))
+    );
+}
+
+fn tool_result(outcome: &ToolInstallOutcome) -> OperationResult {
+    let operation = if outcome.version.is_empty() {
+        outcome.name.clone()
+    } else {
+        format!("{} {}", outcome.name, outcome.version)
+    };
+
+    match &outcome.status {
+        ToolInstallStatus::Installed {
+            path,
+            verified: true,
+        } => OperationResult {
+            operation,
+            status: OperationStatus::Success,
+            details: Some(path.clone()),
+        },
+        ToolInstallStatus::Installed {
+            path,
+            verified: false,
+        } => OperationResult {
+            operation,
+            status: OperationStatus::Warning,
+            details: Some(format!(
+                "{} (no checksum published or pinned; not verified)",
+                path
+            )),
+        },
+        ToolInstallStatus::AlreadyCurrent => OperationResult {
+            operation,
+            status: OperationStatus::Skipped,
+            details: Some("already installed".to_string()),
+        },
+        ToolInstallStatus::Deferred => OperationResult {
+            operation,
+            status: OperationStatus::Skipped,
+            details: Some("deferred (metered)".to_string()),
+        },
+        ToolInstallStatus::Failed(error) => OperationResult {
+            operation,
+            status: OperationStatus::Failed,
+            details: Some(error.clone()),
+        },
+    }
+}
+
 fn create_install_service() -> InstallService<RealFileSystem, SystemScriptExecutor, ConsolePrompt> {
     let filesystem = RealFileSystem::new();
     let script_executor = SystemScriptExecutor::new();
     let prompt = ConsolePrompt::new();
 
     InstallService::new(filesystem, script_executor, prompt)
+        .with_repository(Box::new(RepositoryRegistry::new()))
+        .with_privileged_helper(Box::new(SudoHelper::new()))
 }
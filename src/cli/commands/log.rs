@@ -0,0 +1,29 @@
+use crate::cli::{CommitDetail, UiComponents};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::DotfResult;
+use crate::services::LogService;
+
+pub async fn handle_log(target: Option<String>, limit: usize) -> DotfResult<()> {
+    let target = target
+        .as_deref()
+        .map(super::adopt::resolve_target)
+        .transpose()?;
+
+    let log_service = LogService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    let entries = log_service.log(target.as_deref(), limit).await?;
+
+    let commits: Vec<CommitDetail> = entries
+        .into_iter()
+        .map(|entry| CommitDetail {
+            id: entry.commit.id,
+            summary: entry.commit.summary,
+            author: entry.commit.author,
+            date: entry.commit.date.format("%Y-%m-%d %H:%M").to_string(),
+            targets: entry.targets,
+            other_files: entry.other_files,
+        })
+        .collect();
+    println!("{}", UiComponents::new().commit_log(&commits));
+
+    Ok(())
+}
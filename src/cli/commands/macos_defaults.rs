@@ -0,0 +1,127 @@
+use crate::cli::args::MacosDefaultsAction;
+use crate::cli::{MessageFormatter, OperationResult, OperationStatus, Spinner, UiComponents};
+use crate::core::{filesystem::RealFileSystem, macos::SystemDefaultsStore};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{macos_defaults_service::DefaultsChange, MacosDefaultsService};
+use crate::utils::platform::detect_platform;
+
+pub async fn handle_macos_defaults(action: MacosDefaultsAction) -> DotfResult<()> {
+    if detect_platform() != "macos" {
+        return Err(DotfError::UnsupportedPlatform(
+            "macOS defaults can only be managed on macOS".to_string(),
+        ));
+    }
+
+    match action {
+        MacosDefaultsAction::Diff => handle_diff().await,
+        MacosDefaultsAction::Apply => apply_defaults().await,
+    }
+}
+
+/// Apply `[macos.defaults]` after `dotf install config`; a no-op elsewhere or when none are declared
+pub async fn apply_after_install() -> DotfResult<()> {
+    if detect_platform() != "macos" || !create_service().has_defaults().await? {
+        return Ok(());
+    }
+    apply_defaults().await
+}
+
+async fn handle_diff() -> DotfResult<()> {
+    let service = create_service();
+    let formatter = MessageFormatter::new();
+
+    let spinner = Spinner::new("Reading current defaults...");
+    let changes = match service.diff().await {
+        Ok(changes) => {
+            spinner.finish_and_clear();
+            changes
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to read defaults: {}", e));
+            return Err(e);
+        }
+    };
+
+    if changes.is_empty() {
+        println!("{}", formatter.success("All macOS defaults are up to date"));
+        return Ok(());
+    }
+
+    println!("{}", formatter.section("macOS Defaults"));
+    for change in &changes {
+        println!(
+            "{}",
+            formatter.indent(
+                &format!(
+                    "{} {}: {} → {}",
+                    change.domain,
+                    change.key,
+                    current_label(change),
+                    change.wanted
+                ),
+                1
+            )
+        );
+    }
+    println!();
+    println!(
+        "{}",
+        formatter.info(&format!(
+            "{} value(s) would change. Run 'dotf macos-defaults apply' to write them.",
+            changes.len()
+        ))
+    );
+
+    Ok(())
+}
+
+async fn apply_defaults() -> DotfResult<()> {
+    let service = create_service();
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Applying macOS defaults...");
+    let changes = match service.apply().await {
+        Ok(changes) => {
+            spinner.finish_and_clear();
+            changes
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to apply defaults: {}", e));
+            return Err(e);
+        }
+    };
+
+    if changes.is_empty() {
+        println!("{}", formatter.success("All macOS defaults are up to date"));
+        return Ok(());
+    }
+
+    let results: Vec<OperationResult> = changes
+        .iter()
+        .map(|change| OperationResult {
+            operation: format!("{} {} = {}", change.domain, change.key, change.wanted),
+            status: OperationStatus::Success,
+            details: Some(format!("was {}", current_label(change))),
+        })
+        .collect();
+    println!("{}", ui.operation_results("macOS Defaults", &results));
+    println!(
+        "{}",
+        formatter
+            .info("Some changes only take effect after restarting the affected app or logging out")
+    );
+
+    Ok(())
+}
+
+fn current_label(change: &DefaultsChange) -> String {
+    change
+        .current
+        .clone()
+        .unwrap_or_else(|| "(not set)".to_string())
+}
+
+fn create_service() -> MacosDefaultsService<RealFileSystem, SystemDefaultsStore> {
+    MacosDefaultsService::new(RealFileSystem::new(), SystemDefaultsStore::new())
+}
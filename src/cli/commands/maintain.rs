@@ -0,0 +1,44 @@
+use crate::cli::{MessageFormatter, OperationResult, OperationStatus, UiComponents};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{maintain_service::MaintenanceOutcome, MaintainService, StatusService};
+
+pub async fn handle_maintain(backup_days: u64, offline: bool) -> DotfResult<()> {
+    let status = StatusService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    let service = MaintainService::new(RepositoryRegistry::new(), status, RealFileSystem::new())
+        .with_update_check(!offline);
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let steps = service.run(backup_days).await?;
+
+    let results: Vec<OperationResult> = steps
+        .iter()
+        .map(|step| OperationResult {
+            operation: step.name.clone(),
+            status: match step.outcome {
+                MaintenanceOutcome::Done => OperationStatus::Success,
+                MaintenanceOutcome::Attention => OperationStatus::Warning,
+                MaintenanceOutcome::Skipped => OperationStatus::Skipped,
+                MaintenanceOutcome::Failed => OperationStatus::Failed,
+            },
+            details: Some(step.details.clone()),
+        })
+        .collect();
+    println!("{}", ui.operation_results("Maintenance Report", &results));
+
+    let failed = steps
+        .iter()
+        .filter(|step| step.outcome == MaintenanceOutcome::Failed)
+        .count();
+    if failed > 0 {
+        // A non-zero exit lets a scheduled run surface in cron mail or the timer's status
+        return Err(DotfError::Operation(format!(
+            "{} maintenance step(s) failed",
+            failed
+        )));
+    }
+
+    println!("{}", formatter.success("Maintenance complete"));
+    Ok(())
+}
@@ -0,0 +1,160 @@
+use chrono::Local;
+
+use crate::cli::MessageFormatter;
+use crate::core::{filesystem::RealFileSystem, history::HistoryStore, widget::render_greeting};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+
+/// Entries of the menu shown by a bare `dotf`: label, description and the command line
+/// that runs it
+const MENU: &[(&str, &str, &[&str])] = &[
+    ("Status", "Check the repository and every link", &["status"]),
+    (
+        "Install",
+        "Link the files in dotf.toml",
+        &["install", "config"],
+    ),
+    ("Sync", "Pull the latest dotfiles and relink", &["sync"]),
+    (
+        "Repair",
+        "Relink missing, broken and wrong symlinks",
+        &["repair"],
+    ),
+    (
+        "Backups",
+        "List the files replaced by links",
+        &["backups", "list"],
+    ),
+    ("Config", "Show the local settings", &["config"]),
+];
+
+/// Show the cached status and ask what to do. Returns the command line to run, or
+/// `None` to quit.
+pub async fn handle_menu<P: Prompt>(prompt: &P) -> DotfResult<Option<Vec<String>>> {
+    let filesystem = RealFileSystem::new();
+    let formatter = MessageFormatter::new();
+
+    if !filesystem.exists(&filesystem.dotf_settings_path()).await? {
+        println!(
+            "{}",
+            formatter.info("dotf is not set up yet: run 'dotf init --repo <url>' to start")
+        );
+        println!("{}", formatter.info("See 'dotf --help' for every command"));
+        return Ok(None);
+    }
+
+    // The last recorded status; the menu never waits for a fresh one
+    match HistoryStore::new(filesystem).latest().await.unwrap_or(None) {
+        Some(snapshot) => {
+            let taken_at = snapshot
+                .taken_at
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M");
+            let lines = render_greeting(&snapshot);
+            if lines.is_empty() {
+                println!(
+                    "{}",
+                    formatter.success(&format!(
+                        "{} link(s) valid, repository in sync (as of {})",
+                        snapshot.valid, taken_at
+                    ))
+                );
+            } else {
+                for line in lines {
+                    println!("{}", line);
+                }
+                println!("{}", formatter.info(&format!("As of {}", taken_at)));
+            }
+        }
+        None => println!(
+            "{}",
+            formatter.info("No status recorded yet; choose Status to check")
+        ),
+    }
+    println!();
+
+    choose_command(prompt).await
+}
+
+/// Ask which entry of the menu to run; quitting or cancelling the question is `None`
+async fn choose_command<P: Prompt>(prompt: &P) -> DotfResult<Option<Vec<String>>> {
+    let mut options: Vec<(&str, &str)> = MENU
+        .iter()
+        .map(|(label, description, _)| (*label, *description))
+        .collect();
+    options.push(("Quit", "Leave without doing anything"));
+
+    let choice = match prompt.select("What would you like to do?", &options).await {
+        Ok(choice) => choice,
+        Err(DotfError::UserCancelled) => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    Ok(MENU
+        .get(choice)
+        .map(|(_, _, args)| args.iter().map(|arg| arg.to_string()).collect()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::args::{BackupsAction, InstallTarget};
+    use crate::cli::{Cli, Commands};
+    use crate::traits::prompt::tests::MockPrompt;
+    use clap::Parser;
+
+    async fn choose(index: Option<usize>) -> Option<Commands> {
+        let prompt = MockPrompt::new();
+        if let Some(index) = index {
+            prompt.set_select_response(index);
+        }
+        let command_line = choose_command(&prompt).await.unwrap()?;
+        let cli = Cli::try_parse_from(std::iter::once("dotf".to_string()).chain(command_line));
+        Some(cli.unwrap().command)
+    }
+
+    #[tokio::test]
+    async fn test_menu_choices_run_their_commands() {
+        let labels: Vec<&str> = MENU.iter().map(|(label, _, _)| *label).collect();
+        assert_eq!(
+            labels,
+            vec!["Status", "Install", "Sync", "Repair", "Backups", "Config"]
+        );
+
+        assert!(matches!(
+            choose(Some(0)).await,
+            Some(Commands::Status { .. })
+        ));
+        assert!(matches!(
+            choose(Some(1)).await,
+            Some(Commands::Install {
+                target: InstallTarget::Config { .. }
+            })
+        ));
+        assert!(matches!(choose(Some(2)).await, Some(Commands::Sync { .. })));
+        assert!(matches!(
+            choose(Some(3)).await,
+            Some(Commands::Repair {
+                dry_run: false,
+                json: false
+            })
+        ));
+        assert!(matches!(
+            choose(Some(4)).await,
+            Some(Commands::Backups {
+                action: BackupsAction::List
+            })
+        ));
+        assert!(matches!(
+            choose(Some(5)).await,
+            Some(Commands::Config { action: None, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_menu_quit_and_cancel_run_nothing() {
+        // Quit is the entry after the commands
+        assert!(choose(Some(MENU.len())).await.is_none());
+        // A question the user backed out of
+        assert!(choose(None).await.is_none());
+    }
+}
@@ -1,16 +1,74 @@
+pub mod add;
+pub mod adopt;
+pub mod alias;
+pub mod apply_plan;
+pub mod backups;
+pub mod changes;
+pub mod completions;
 pub mod config;
+pub mod diff;
+pub mod disable;
+pub mod doctor;
+pub mod export;
+pub mod generate;
+pub mod greet;
+pub mod import;
 pub mod init;
 pub mod install;
+pub mod log;
+pub mod macos_defaults;
+pub mod maintain;
+pub mod menu;
+pub mod profile;
+pub mod prompts;
+pub mod recover;
+pub mod render;
+pub mod repair;
+pub mod repos;
+pub mod sandbox;
 pub mod schema;
+pub mod secrets;
+pub mod settings;
 pub mod status;
 pub mod symlinks;
 pub mod sync;
+pub mod uninstall;
+pub mod watch;
 
 // Re-export command handlers for easy access
+pub use add::handle_add;
+pub use adopt::handle_adopt;
+pub use alias::handle_alias;
+pub use apply_plan::handle_apply_plan;
+pub use backups::handle_backups;
+pub use changes::handle_changes;
+pub use completions::handle_completions;
 pub use config::handle_config;
+pub use diff::handle_diff;
+pub use disable::{handle_disable, handle_enable};
+pub use doctor::handle_doctor;
+pub use export::handle_export;
+pub use generate::handle_generate;
+pub use greet::handle_greet;
+pub use import::handle_import;
 pub use init::handle_init;
 pub use install::handle_install;
+pub use log::handle_log;
+pub use macos_defaults::handle_macos_defaults;
+pub use maintain::handle_maintain;
+pub use menu::handle_menu;
+pub use profile::handle_profile;
+pub use prompts::handle_prompts;
+pub use recover::handle_recover;
+pub use render::handle_render;
+pub use repair::handle_repair;
+pub use repos::handle_repos;
+pub use sandbox::handle_sandbox;
 pub use schema::handle_schema;
-pub use status::handle_status;
+pub use secrets::handle_secrets;
+pub use settings::handle_settings;
+pub use status::{handle_status, handle_status_fail_on};
 pub use symlinks::handle_symlinks;
 pub use sync::handle_sync;
+pub use uninstall::handle_uninstall;
+pub use watch::handle_watch;
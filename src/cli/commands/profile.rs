@@ -0,0 +1,67 @@
+use crate::cli::args::ProfileAction;
+use crate::cli::MessageFormatter;
+use crate::core::filesystem::RealFileSystem;
+use crate::error::DotfResult;
+use crate::services::ProfileService;
+
+pub async fn handle_profile(action: ProfileAction) -> DotfResult<()> {
+    let service = ProfileService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+
+    match action {
+        ProfileAction::List => {
+            let profiles = service.list().await?;
+            if profiles.is_empty() {
+                println!(
+                    "{}",
+                    formatter
+                        .info("No profiles defined. Add [profile.<name>] sections to dotf.toml")
+                );
+                return Ok(());
+            }
+
+            println!("{}", formatter.section("Profiles"));
+            for profile in profiles {
+                let name = if profile.active {
+                    format!("{} (active)", profile.name)
+                } else {
+                    profile.name
+                };
+                println!(
+                    "{}",
+                    formatter.key_value(
+                        &name,
+                        &format!(
+                            "{} symlink(s), {} script(s)",
+                            profile.symlinks, profile.scripts
+                        )
+                    )
+                );
+            }
+        }
+        ProfileAction::Use { name } => {
+            service.set_active(Some(&name)).await?;
+            println!(
+                "{}",
+                formatter.success(&format!("Using profile '{}'", name))
+            );
+            println!(
+                "{}",
+                formatter.info("Run 'dotf install config' to link its symlinks")
+            );
+        }
+        ProfileAction::Clear => {
+            service.set_active(None).await?;
+            println!(
+                "{}",
+                formatter.success("Using the base config without a profile")
+            );
+            println!(
+                "{}",
+                formatter.info("Links from the previous profile stay in place; 'dotf status' lists the active ones")
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+use crate::cli::args::PromptsAction;
+use crate::cli::MessageFormatter;
+use crate::core::filesystem::RealFileSystem;
+use crate::error::DotfResult;
+use crate::services::PromptsService;
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_prompts(action: PromptsAction) -> DotfResult<()> {
+    let service = create_service();
+    let formatter = MessageFormatter::new();
+
+    match action {
+        PromptsAction::List => {
+            let statuses = service.list().await?;
+            if statuses.is_empty() {
+                println!("{}", formatter.info("No prompts declared in [prompts]"));
+                return Ok(());
+            }
+
+            println!("{}", formatter.section("Prompts"));
+            for status in statuses {
+                let answer = status.answer.as_deref().unwrap_or("(not answered)");
+                println!("{}", formatter.key_value(&status.name, answer));
+                println!("{}", formatter.indent(&status.message, 1));
+            }
+        }
+        PromptsAction::Edit { name } => {
+            let edited = service.edit(name.as_deref()).await?;
+            if edited == 0 {
+                println!("{}", formatter.info("No prompts declared in [prompts]"));
+            } else {
+                println!("{}", formatter.success("Answers saved"));
+                println!(
+                    "{}",
+                    formatter.info("Run 'dotf install env' to apply them to the env fragment")
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask `[prompts]` that have no stored answer yet; silent when all are answered
+pub async fn ask_missing() -> DotfResult<()> {
+    if create_service().ask_missing().await? > 0 {
+        println!(
+            "{}",
+            MessageFormatter::new().success("Answers saved; change them with 'dotf prompts edit'")
+        );
+    }
+    Ok(())
+}
+
+fn create_service() -> PromptsService<RealFileSystem, ConsolePrompt> {
+    PromptsService::new(RealFileSystem::new(), ConsolePrompt::new())
+}
@@ -0,0 +1,83 @@
+use crate::cli::{
+    MessageFormatter, OperationResult, OperationStatus, PathDisplay, Spinner, UiComponents,
+};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::DotfResult;
+use crate::services::{recover_service::RecoveryOutcome, RecoverService};
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_recover(repo: String) -> DotfResult<()> {
+    let service = RecoverService::new(
+        RepositoryRegistry::new(),
+        RealFileSystem::new(),
+        ConsolePrompt::new(),
+    );
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Re-cloning repository and checking links...");
+    let recovered = match service.recover(&repo).await {
+        Ok(recovered) => {
+            spinner.finish_with_success("Repository restored");
+            recovered
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Recovery failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if recovered.is_empty() {
+        println!(
+            "{}",
+            formatter.info("No symlinks into ~/.dotf were found next to the configured targets")
+        );
+    } else {
+        let paths = PathDisplay::new();
+
+        let results: Vec<OperationResult> = recovered
+            .iter()
+            .map(|link| {
+                let (status, details) = match &link.outcome {
+                    RecoveryOutcome::Restored => {
+                        (OperationStatus::Success, "works again".to_string())
+                    }
+                    RecoveryOutcome::Relinked { source } => (
+                        OperationStatus::Success,
+                        format!("relinked to {}", paths.path(source)),
+                    ),
+                    RecoveryOutcome::Unrecoverable { reason } => (
+                        OperationStatus::Failed,
+                        format!("{} (was {})", reason, paths.path(&link.previous_source)),
+                    ),
+                };
+                OperationResult {
+                    operation: paths.path(&link.target_path),
+                    status,
+                    details: Some(details),
+                }
+            })
+            .collect();
+        println!("{}", ui.operation_results("Recovery Report", &results));
+
+        let unrecoverable = recovered
+            .iter()
+            .filter(|link| matches!(link.outcome, RecoveryOutcome::Unrecoverable { .. }))
+            .count();
+        if unrecoverable > 0 {
+            println!(
+                "{}",
+                formatter.warning(&format!(
+                    "{} dangling link(s) left in place; remove them or add entries for them to dotf.toml",
+                    unrecoverable
+                ))
+            );
+        }
+    }
+
+    println!(
+        "{}",
+        formatter.info("Run 'dotf install config' to create any links that are still missing")
+    );
+    Ok(())
+}
@@ -0,0 +1,38 @@
+use crate::cli::{MessageFormatter, PathDisplay};
+use crate::core::filesystem::RealFileSystem;
+use crate::error::DotfResult;
+use crate::services::{render_service::RenderOutcome, RenderService};
+
+pub async fn handle_render(stale: bool, force: bool) -> DotfResult<()> {
+    let render_service = RenderService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+    let paths = PathDisplay::new();
+
+    let outcomes = render_service.render(stale, force).await?;
+    if outcomes.is_empty() {
+        let message = if stale {
+            "All rendered files are up to date"
+        } else {
+            "Nothing to render; add [ssh] config fragments or [env] to dotf.toml"
+        };
+        println!("{}", formatter.info(message));
+        return Ok(());
+    }
+
+    for (_, output, outcome) in outcomes {
+        let output = paths.path(&output);
+        let line = match outcome {
+            RenderOutcome::Written => formatter.success(&format!("Rendered {}", output)),
+            RenderOutcome::Unchanged => formatter.info(&format!("{} is up to date", output)),
+            RenderOutcome::Removed => {
+                formatter.info(&format!("Removed {} (its sources are gone)", output))
+            }
+            RenderOutcome::Conflict => formatter.warning(&format!(
+                "{} was edited by hand and was not rendered. Move the changes into dotf.toml, then run 'dotf render --force'",
+                output
+            )),
+        };
+        println!("{}", line);
+    }
+    Ok(())
+}
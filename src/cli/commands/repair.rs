@@ -0,0 +1,165 @@
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::cli::json::{self, JsonProgress};
+use crate::cli::{MessageFormatter, OperationResult, OperationStatus, PathDisplay, UiComponents};
+use crate::core::{
+    filesystem::RealFileSystem,
+    scripts::SystemScriptExecutor,
+    symlinks::{ConflictResolution, RepairOutcome, RepairedLink, SymlinkStatus},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::InstallService;
+use crate::traits::filesystem::FileSystem;
+use crate::utils::ConsolePrompt;
+
+/// What `repair --json` did, or would do with `--dry-run`
+#[derive(Serialize)]
+struct RepairReport {
+    dry_run: bool,
+    links: Vec<RepairedLink>,
+}
+
+pub async fn handle_repair(
+    dry_run: bool,
+    json: bool,
+    conflict_policy: Option<ConflictResolution>,
+) -> DotfResult<()> {
+    if json {
+        return repair_json(dry_run, conflict_policy).await;
+    }
+
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+    let filesystem = RealFileSystem::new();
+
+    if !filesystem.exists(&filesystem.dotf_settings_path()).await? {
+        return Err(DotfError::NotInitialized);
+    }
+
+    let install_service = InstallService::new(
+        filesystem,
+        SystemScriptExecutor::new(),
+        ConsolePrompt::new(),
+    )
+    .with_conflict_policy(conflict_policy);
+    let repaired = install_service.repair_config(dry_run).await?;
+
+    if repaired.is_empty() {
+        println!(
+            "{}",
+            formatter.success("All symlinks are valid; nothing to repair")
+        );
+        return Ok(());
+    }
+
+    let paths = PathDisplay::new();
+
+    let fixed_status = if dry_run {
+        OperationStatus::InProgress
+    } else {
+        OperationStatus::Success
+    };
+    let results: Vec<OperationResult> = repaired
+        .iter()
+        .map(|link| {
+            let was = match link.status {
+                SymlinkStatus::Missing => "missing",
+                SymlinkStatus::Broken => "broken",
+                SymlinkStatus::InvalidTarget => "linked elsewhere",
+                SymlinkStatus::Conflict => "file in the way",
+                SymlinkStatus::Valid | SymlinkStatus::Modified => "valid",
+                SymlinkStatus::Disabled => "disabled",
+                SymlinkStatus::WrongPermissions => "wrong permissions",
+                SymlinkStatus::Unreadable => "unreadable",
+            };
+            let (status, details) = match &link.outcome {
+                RepairOutcome::Created => (fixed_status, "created".to_string()),
+                RepairOutcome::Recreated => (fixed_status, "recreated".to_string()),
+                RepairOutcome::BackedUp => {
+                    (fixed_status, "backed up the file and linked".to_string())
+                }
+                RepairOutcome::Removed => (fixed_status, "removed the file and linked".to_string()),
+                RepairOutcome::PermissionsFixed => (fixed_status, "set mode and owner".to_string()),
+                RepairOutcome::Undecided => (
+                    OperationStatus::Warning,
+                    "asks whether to replace the file".to_string(),
+                ),
+                RepairOutcome::Skipped(reason) => {
+                    (OperationStatus::Skipped, format!("skipped: {}", reason))
+                }
+            };
+            OperationResult {
+                operation: paths.path(&link.target_path),
+                status,
+                details: Some(format!("{} ({})", details, was)),
+            }
+        })
+        .collect();
+
+    let title = if dry_run {
+        "Planned Repairs"
+    } else {
+        "Repaired Symlinks"
+    };
+    println!("{}", ui.operation_results(title, &results));
+
+    let count = |matches: fn(&RepairOutcome) -> bool| {
+        repaired
+            .iter()
+            .filter(|link| matches(&link.outcome))
+            .count()
+            .to_string()
+    };
+    let fixed = count(|outcome| {
+        !matches!(
+            outcome,
+            RepairOutcome::Undecided | RepairOutcome::Skipped(_)
+        )
+    });
+    let undecided = count(|outcome| matches!(outcome, RepairOutcome::Undecided));
+    let skipped = count(|outcome| matches!(outcome, RepairOutcome::Skipped(_)));
+    let mut summary = vec![
+        (
+            if dry_run { "To repair" } else { "Repaired" },
+            fixed.as_str(),
+        ),
+        ("Skipped", skipped.as_str()),
+    ];
+    if dry_run {
+        summary.insert(1, ("Needs a decision", undecided.as_str()));
+    }
+    println!("\n{}", formatter.summary_box("Repair Summary", &summary));
+
+    if dry_run {
+        println!(
+            "{}",
+            formatter.info("Run 'dotf repair' to apply these changes")
+        );
+    }
+
+    Ok(())
+}
+
+/// `repair --json`: files in the way are kept unless --on-conflict or --yes says otherwise
+async fn repair_json(dry_run: bool, conflict_policy: Option<ConflictResolution>) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let progress = Arc::new(JsonProgress::new());
+    let result = async {
+        if !filesystem.exists(&filesystem.dotf_settings_path()).await? {
+            return Err(DotfError::NotInitialized);
+        }
+        let links = InstallService::new(
+            filesystem,
+            SystemScriptExecutor::new(),
+            ConsolePrompt::new(),
+        )
+        .with_conflict_policy(Some(conflict_policy.unwrap_or(ConflictResolution::Skip)))
+        .with_progress(progress.clone())
+        .repair_config(dry_run)
+        .await?;
+        Ok(RepairReport { dry_run, links })
+    }
+    .await;
+    json::print_report("repair", result, progress.warnings())
+}
@@ -0,0 +1,81 @@
+use crate::cli::args::ReposAction;
+use crate::cli::{MessageFormatter, PathDisplay, Spinner};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::DotfResult;
+use crate::services::ReposService;
+
+pub async fn handle_repos(action: ReposAction) -> DotfResult<()> {
+    let repos_service = ReposService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+    let paths = PathDisplay::new();
+
+    match action {
+        ReposAction::List => {
+            let overlays = repos_service.list().await?;
+            if overlays.is_empty() {
+                println!(
+                    "{}",
+                    formatter.info(
+                        "No overlay repositories; add one with 'dotf repos add <name> <url>'"
+                    )
+                );
+                return Ok(());
+            }
+            println!(
+                "{}",
+                formatter.section("Overlay repositories (lowest precedence first)")
+            );
+            for overlay in overlays {
+                let branch = overlay
+                    .repository
+                    .branch
+                    .as_deref()
+                    .map(|branch| format!(" ({})", branch))
+                    .unwrap_or_default();
+                println!(
+                    "  {} {}{}",
+                    overlay.repository.name, overlay.repository.remote, branch
+                );
+                let location = if overlay.cloned {
+                    paths.path(&overlay.path)
+                } else {
+                    "not cloned yet; run 'dotf sync'".to_string()
+                };
+                println!("{}", formatter.tree_item(&location, true, 2));
+            }
+        }
+        ReposAction::Add { name, url, branch } => {
+            let spinner = Spinner::new(&format!("Cloning {}...", url));
+            match repos_service.add(&name, &url, branch).await {
+                Ok(overlay) => spinner.finish_with_success(&format!(
+                    "Added repository '{}' at {}",
+                    name,
+                    paths.path(&overlay.path)
+                )),
+                Err(e) => {
+                    spinner.finish_with_error(&format!("Could not add repository: {}", e));
+                    return Err(e);
+                }
+            }
+            println!(
+                "{}",
+                formatter.info("Run 'dotf install config' to link its files")
+            );
+        }
+        ReposAction::Remove { name } => {
+            let path = repos_service.remove(&name).await?;
+            println!(
+                "{}",
+                formatter.success(&format!("Removed repository '{}'", name))
+            );
+            println!(
+                "{}",
+                formatter.info(&format!(
+                    "Its checkout is kept at {}; 'dotf changes' lists the links it provided",
+                    paths.path(&path)
+                ))
+            );
+        }
+    }
+    Ok(())
+}
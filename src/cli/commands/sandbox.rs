@@ -0,0 +1,177 @@
+use std::io::IsTerminal;
+use std::path::Path;
+use std::process::Command;
+
+use crate::cli::MessageFormatter;
+use crate::core::{
+    filesystem::RealFileSystem,
+    sandbox::{container_args, detect_container_engine, SandboxChange, CONTAINER_ENGINES},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{sandbox_service::SandboxOutcome, SandboxService};
+
+pub async fn handle_sandbox(
+    with_deps: bool,
+    container: bool,
+    image: &str,
+    keep: bool,
+) -> DotfResult<()> {
+    let service = SandboxService::new(RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+    let dotf_binary = std::env::current_exe()?;
+
+    if container {
+        return run_in_container(&service, &dotf_binary, image).await;
+    }
+
+    let sandbox = service.prepare().await?;
+    println!(
+        "{}",
+        formatter.info(&format!(
+            "Installing into sandbox {}",
+            sandbox.home().display()
+        ))
+    );
+    if !with_deps {
+        println!(
+            "{}",
+            formatter.info("Skipping the dependency script; pass --with-deps to run it too")
+        );
+    }
+
+    let steps: &[&str] = if with_deps {
+        &["deps", "config"]
+    } else {
+        &["config"]
+    };
+    let mut succeeded = true;
+    for step in steps {
+        if !sandbox.run(&dotf_binary, &["install", step])? {
+            println!(
+                "{}",
+                formatter.error(&format!("'dotf install {}' failed in the sandbox", step))
+            );
+            succeeded = false;
+            break;
+        }
+    }
+
+    let changes = sandbox.changes()?;
+    let report = service.report(&sandbox.repo_path(), changes).await?;
+    let pending: Vec<_> = report
+        .iter()
+        .filter(|entry| entry.outcome != SandboxOutcome::Unchanged)
+        .collect();
+
+    println!("{}", formatter.section("Sandbox Results"));
+    if pending.is_empty() {
+        println!(
+            "{}",
+            formatter.indent("Your home directory already matches the install", 1)
+        );
+    }
+    for (index, entry) in pending.iter().enumerate() {
+        let action = match entry.outcome {
+            SandboxOutcome::Create => "create",
+            SandboxOutcome::Replace => "replace",
+            SandboxOutcome::Unchanged => unreachable!(),
+        };
+        let description = match &entry.change {
+            SandboxChange::Symlink { path, target } => {
+                let target = Path::new(target)
+                    .strip_prefix(sandbox.repo_path())
+                    .map(|relative| relative.display().to_string())
+                    .unwrap_or_else(|_| target.clone());
+                format!("~/{} → {}", path, target)
+            }
+            SandboxChange::File { path } => format!("~/{}", path),
+            SandboxChange::Directory { path } => format!("~/{}/", path),
+        };
+        println!(
+            "{}",
+            formatter.tree_item(
+                &format!("{} {}", action, description),
+                index + 1 == pending.len(),
+                1
+            )
+        );
+    }
+
+    let replaced = pending
+        .iter()
+        .filter(|entry| entry.outcome == SandboxOutcome::Replace)
+        .count();
+    if replaced > 0 {
+        println!();
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "{} existing path(s) would be backed up and replaced",
+                replaced
+            ))
+        );
+    }
+
+    if keep {
+        let path = sandbox.keep();
+        println!(
+            "{}",
+            formatter.info(&format!("Sandbox kept at {}", path.display()))
+        );
+    }
+
+    if !succeeded {
+        return Err(DotfError::Operation(
+            "Install failed in the sandbox".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+async fn run_in_container(
+    service: &SandboxService<RealFileSystem>,
+    dotf_binary: &Path,
+    image: &str,
+) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let engine = detect_container_engine().ok_or_else(|| {
+        DotfError::Operation(format!(
+            "--container needs one of: {}",
+            CONTAINER_ENGINES.join(", ")
+        ))
+    })?;
+
+    let repo_path = service.repo_path().await?;
+    let args = container_args(
+        image,
+        Path::new(&repo_path),
+        dotf_binary,
+        &["deps", "config"],
+        std::io::stdin().is_terminal(),
+    );
+
+    println!(
+        "{}",
+        formatter.info(&format!(
+            "Installing in a {} container from {}",
+            engine, image
+        ))
+    );
+    let status = Command::new(engine)
+        .args(&args)
+        .status()
+        .map_err(|e| DotfError::Operation(format!("Failed to run {}: {}", engine, e)))?;
+
+    if !status.success() {
+        return Err(DotfError::Operation(format!(
+            "Install failed in the container ({})",
+            status
+        )));
+    }
+    println!(
+        "{}",
+        formatter
+            .success("Full install completed in the container; nothing on this system changed")
+    );
+    Ok(())
+}
@@ -1,9 +1,13 @@
+use super::adopt::resolve_target;
 use crate::cli::args::SchemaAction;
-use crate::error::DotfResult;
+use crate::cli::{MessageFormatter, PathDisplay, UiComponents};
+use crate::core::config::reference::{render_markdown, sections};
+use crate::error::{DotfError, DotfResult};
 use crate::services::{SchemaService, SchemaValidator};
+use crate::utils::ConsolePrompt;
 use std::process;
 
-pub async fn handle_schema(action: SchemaAction) -> DotfResult<()> {
+pub async fn handle_schema(action: SchemaAction, non_interactive: bool) -> DotfResult<()> {
     match action {
         SchemaAction::Init => handle_schema_init().await,
         SchemaAction::Test {
@@ -11,7 +15,95 @@ pub async fn handle_schema(action: SchemaAction) -> DotfResult<()> {
             ignore_errors,
             quiet,
         } => handle_schema_test(file, ignore_errors, quiet).await,
+        SchemaAction::Docs { section, markdown } => handle_schema_docs(section, markdown),
+        SchemaAction::Generate { paths, file, force } => {
+            handle_schema_generate(paths, file, force, non_interactive).await
+        }
+    }
+}
+
+async fn handle_schema_generate(
+    paths: Vec<String>,
+    file: Option<String>,
+    force: bool,
+    non_interactive: bool,
+) -> DotfResult<()> {
+    let service = SchemaService::new();
+    let formatter = MessageFormatter::new();
+    let file_path = file.unwrap_or_else(|| "dotf.toml".to_string());
+    let home = dirs::home_dir()
+        .ok_or_else(|| DotfError::Operation("Could not determine home directory".to_string()))?;
+
+    let paths = paths
+        .iter()
+        .map(|path| resolve_target(path))
+        .collect::<DotfResult<Vec<_>>>()?;
+    let proposals = service.propose_links(&home, &paths)?;
+    if proposals.is_empty() {
+        println!(
+            "{}",
+            formatter.info("No common dotfiles found; pass the files to link as arguments")
+        );
+    }
+
+    // --yes keeps every proposal instead of declining them all
+    let links = if non_interactive {
+        proposals
+    } else {
+        service
+            .choose_links(proposals, &ConsolePrompt::new())
+            .await?
+    };
+    service.write_generated(&file_path, &links, force)?;
+
+    println!(
+        "{}",
+        formatter.success(&format!(
+            "Created {} with {} symlink(s)",
+            file_path,
+            links.len()
+        ))
+    );
+    if !links.is_empty() {
+        let display = PathDisplay::new();
+        println!(
+            "{}",
+            formatter.section("Copy these into the repository next to it")
+        );
+        let total = links.len();
+        for (position, link) in links.iter().enumerate() {
+            println!(
+                "{}",
+                formatter.tree_item(
+                    &format!("{} → {}", display.path(&link.target), link.source),
+                    position + 1 == total,
+                    1
+                )
+            );
+        }
+    }
+    println!(
+        "{}",
+        formatter.info("Check it with 'dotf schema test' once the files are in place")
+    );
+    Ok(())
+}
+
+fn handle_schema_docs(section: Option<String>, markdown: bool) -> DotfResult<()> {
+    let sections = sections(section.as_deref());
+    if sections.is_empty() {
+        return Err(DotfError::Config(format!(
+            "Unknown dotf.toml section '{}'",
+            section.unwrap_or_default()
+        )));
+    }
+
+    if markdown {
+        print!("{}", render_markdown(&sections));
+    } else {
+        println!("{}", UiComponents::new().config_reference(&sections));
     }
+    Ok(())
 }
 
 async fn handle_schema_init() -> DotfResult<()> {
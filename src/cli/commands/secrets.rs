@@ -0,0 +1,86 @@
+use super::adopt::resolve_target;
+use super::status::secret_problem;
+use crate::cli::args::SecretsAction;
+use crate::cli::MessageFormatter;
+use crate::core::{filesystem::RealFileSystem, secrets::SystemCipher};
+use crate::error::DotfResult;
+use crate::services::SecretsService;
+
+// No spinners here: age and gpg may ask for a passphrase on the terminal
+
+pub async fn handle_secrets(action: SecretsAction) -> DotfResult<()> {
+    let service = create_secrets_service();
+    let formatter = MessageFormatter::new();
+
+    match action {
+        SecretsAction::Encrypt { target, source } => {
+            let target_path = resolve_target(&target)?;
+            let result = service.encrypt(&target_path, source.as_deref()).await?;
+            println!(
+                "{}",
+                formatter.file_operation("Encrypted", &target_path, &result.source_path)
+            );
+            if result.added {
+                println!(
+                    "{}",
+                    formatter.success(&format!(
+                        "Added \"{}\" to [secrets.files]",
+                        result.config_key
+                    ))
+                );
+            }
+            println!(
+                "{}",
+                formatter.info("Commit the encrypted file, e.g. with 'dotf sync --push'")
+            );
+        }
+        SecretsAction::Decrypt { target, force } => {
+            let target_path = target.as_deref().map(resolve_target).transpose()?;
+            decrypt(target_path.as_deref(), force).await?;
+        }
+        SecretsAction::Status => {
+            let Some(status) = service.status().await? else {
+                println!(
+                    "{}",
+                    formatter.info("No secrets declared in [secrets.files]")
+                );
+                return Ok(());
+            };
+            println!("{}", formatter.section("Secrets"));
+            for file in &status.files {
+                match secret_problem(file) {
+                    Some(problem) => println!("{}", formatter.warning(&problem)),
+                    None => println!(
+                        "{}",
+                        formatter.success(&format!("{}: decrypted", file.target_path))
+                    ),
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Decrypt missing and outdated secrets; also run after `dotf install config`
+pub async fn decrypt(target: Option<&str>, force: bool) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let outcome = create_secrets_service().decrypt(target, force).await?;
+
+    for target_path in &outcome.decrypted {
+        println!(
+            "{}",
+            formatter.success(&format!("Decrypted {}", target_path))
+        );
+    }
+    for file in &outcome.skipped {
+        if let Some(problem) = secret_problem(file) {
+            println!("{}", formatter.warning(&format!("Kept {}", problem)));
+        }
+    }
+    Ok(())
+}
+
+fn create_secrets_service() -> SecretsService<RealFileSystem, SystemCipher> {
+    SecretsService::new(RealFileSystem::new(), SystemCipher::new())
+}
@@ -0,0 +1,207 @@
+use crate::cli::args::SettingsAction;
+use crate::cli::{MessageFormatter, Spinner};
+use crate::core::{filesystem::RealFileSystem, repository::RepositoryRegistry};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{
+    settings_service::{RepairMethod, SettingsDamage},
+    SettingsService,
+};
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+use crate::utils::ConsolePrompt;
+use std::io::IsTerminal;
+
+pub async fn handle_settings(action: SettingsAction) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let settings_service = SettingsService::new(RepositoryRegistry::new(), filesystem.clone());
+    let formatter = MessageFormatter::new();
+
+    match action {
+        SettingsAction::Export {
+            format,
+            output,
+            include_secrets,
+        } => {
+            let bundle = settings_service.export(format, include_secrets).await?;
+            match output {
+                Some(path) => {
+                    filesystem.write(&path, &bundle).await?;
+                    if include_secrets {
+                        filesystem.set_mode(&path, 0o600).await?;
+                    }
+                    println!(
+                        "{}",
+                        formatter.success(&format!("Settings written to {}", path))
+                    );
+                }
+                None => print!("{}", bundle),
+            }
+            if include_secrets {
+                eprintln!(
+                    "{}",
+                    formatter.warning(
+                        "The bundle contains credentials and prompt answers; keep it private"
+                    )
+                );
+            }
+        }
+        SettingsAction::Import { file, force } => {
+            let content = filesystem.read_to_string(&file).await?;
+            let spinner = Spinner::new("Importing settings...");
+            let outcome = match settings_service.import(&content, force).await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    spinner.finish_with_error(&format!("Could not import settings: {}", e));
+                    return Err(e);
+                }
+            };
+            spinner.finish_with_success(&format!("Imported settings from {}", file));
+            if outcome.cloned {
+                println!(
+                    "{}",
+                    formatter.success(&format!(
+                        "Cloned the repository into {}",
+                        filesystem.dotf_repo_path()
+                    ))
+                );
+            }
+            for warning in &outcome.warnings {
+                println!("{}", formatter.warning(warning));
+            }
+            println!(
+                "{}",
+                formatter.info("Run 'dotf install config' to link your dotfiles")
+            );
+        }
+        SettingsAction::Repair {
+            restore_backup,
+            from_clone,
+            init,
+        } => {
+            let Some(damage) = settings_service.diagnose().await? else {
+                println!(
+                    "{}",
+                    formatter.success(&format!(
+                        "{} reads fine; nothing to repair",
+                        filesystem.dotf_settings_path()
+                    ))
+                );
+                return Ok(());
+            };
+            print_damage(&formatter, &damage);
+
+            let method = match (restore_backup, from_clone, init) {
+                (true, _, _) => RepairMethod::RestoreBackup,
+                (_, true, _) => RepairMethod::FromClone,
+                (_, _, Some(remote)) => RepairMethod::Init(remote),
+                _ => match choose_method(&damage).await? {
+                    Some(method) => method,
+                    None => return Ok(()),
+                },
+            };
+
+            let report = settings_service.repair(method).await?;
+            println!("{}", formatter.success(&format!("Rebuilt {}", damage.path)));
+            if report.cloned {
+                println!(
+                    "{}",
+                    formatter.success(&format!(
+                        "Cloned the repository into {}",
+                        filesystem.dotf_repo_path()
+                    ))
+                );
+            }
+            if !report.kept.is_empty() {
+                println!(
+                    "{}",
+                    formatter.info(&format!(
+                        "Kept from the damaged file: {}",
+                        report.kept.join(", ")
+                    ))
+                );
+            }
+            for dropped in &report.dropped {
+                println!("{}", formatter.warning(&format!("Left out {}", dropped)));
+            }
+            println!(
+                "{}",
+                formatter.info(&format!(
+                    "The damaged file is saved as {}",
+                    report.damaged_copy
+                ))
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The parse error with the lines around it, the failing line marked
+fn print_damage(formatter: &MessageFormatter, damage: &SettingsDamage) {
+    let failure = &damage.failure;
+    let location = match (failure.line, failure.column) {
+        (Some(line), Some(column)) => format!(" (line {}, column {})", line, column),
+        _ => String::new(),
+    };
+    eprintln!(
+        "{}",
+        formatter.error(&format!(
+            "{} cannot be read{}: {}",
+            damage.path, location, failure.message
+        ))
+    );
+    for (number, text) in &failure.excerpt {
+        let marker = if Some(*number) == failure.line {
+            ">"
+        } else {
+            " "
+        };
+        eprintln!("  {} {:>4} | {}", marker, number, text);
+    }
+}
+
+/// Ask how to rebuild the file, offering only what is available. `None` if the user
+/// leaves it as it is.
+async fn choose_method(damage: &SettingsDamage) -> DotfResult<Option<RepairMethod>> {
+    if !std::io::stdin().is_terminal() {
+        return Err(DotfError::Config(
+            "Choose a repair with --restore-backup, --from-clone or --init <URL>".to_string(),
+        ));
+    }
+
+    let mut methods = Vec::new();
+    let mut options = Vec::new();
+    if damage.backup.is_some() {
+        methods.push(RepairMethod::RestoreBackup);
+        options.push((
+            "Restore the backup",
+            "put back settings.toml.bak, the last settings that parsed",
+        ));
+    }
+    if damage.clone_remote.is_some() {
+        methods.push(RepairMethod::FromClone);
+        options.push((
+            "Rebuild from the repository",
+            "use the checkout in ~/.dotf and keep what still reads",
+        ));
+    }
+    // The URL is asked for once this is picked
+    methods.push(RepairMethod::Init(String::new()));
+    options.push((
+        "Start over",
+        "write minimal settings for a repository URL, as 'dotf init' would",
+    ));
+    options.push(("Leave it", "fix the file by hand"));
+
+    let prompt = ConsolePrompt::new();
+    let choice = prompt
+        .select("How should dotf repair it?", &options)
+        .await?;
+    match methods.into_iter().nth(choice) {
+        Some(RepairMethod::Init(_)) => {
+            let remote = prompt
+                .input("Repository URL", damage.clone_remote.as_deref())
+                .await?;
+            Ok(Some(RepairMethod::Init(remote)))
+        }
+        method => Ok(method),
+    }
+}
@@ -1,11 +1,60 @@
-use crate::cli::{MessageFormatter, Spinner, SymlinkDetail, UiComponents};
-use crate::core::{filesystem::RealFileSystem, repository::GitRepository};
+use chrono::Utc;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+
+use crate::cli::{
+    MessageFormatter, OperationResult, OperationStatus, PathDisplay, Spinner, SymlinkDetail,
+    ToolDetail, UiComponents,
+};
+use crate::core::{
+    config::Settings,
+    filesystem::RealFileSystem,
+    history::HistoryStore,
+    repository::RepositoryRegistry,
+    secrets::SecretState,
+    tools::ToolState,
+    widget::{render_widget, WidgetFormat, DEFAULT_MAX_AGE},
+};
 use crate::error::DotfResult;
-use crate::services::StatusService;
-use crate::traits::filesystem::FileSystem;
+use crate::services::{
+    cron_service::{CronState, CronStatusInfo},
+    packages_service::PackageState,
+    render_service::StaleRender,
+    secrets_service::SecretStatus,
+    ssh_service::SshStatusInfo,
+    status_service::{CheckCategory, EXIT_OK},
+    PackagesService, StatusService,
+};
+use crate::traits::{filesystem::FileSystem, repository::FetchPolicy};
+
+/// How long after starting a background refresh the widget waits before starting another
+const WIDGET_REFRESH_COOLDOWN: Duration = Duration::from_secs(60);
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_status(
+    quiet: bool,
+    since: Option<String>,
+    widget: Option<WidgetFormat>,
+    json: bool,
+    packages: bool,
+    repo: Option<String>,
+    check: bool,
+    fetch: FetchPolicy,
+) -> DotfResult<()> {
+    if let Some(format) = widget {
+        return handle_status_widget(format).await;
+    }
+    if let Some(since) = since {
+        return handle_status_since(&since, json, repo, fetch).await;
+    }
+    if json {
+        return handle_status_json(repo, check, fetch).await;
+    }
 
-pub async fn handle_status(quiet: bool) -> DotfResult<()> {
-    let status_service = create_status_service();
+    // A status limited to one repository is not a snapshot of the whole machine
+    let record = repo.is_none();
+    let status_service = create_status_service(repo, fetch);
     let formatter = MessageFormatter::new();
     let ui = UiComponents::new();
     let spinner = Spinner::new("Checking status...");
@@ -21,6 +70,13 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
         }
     };
 
+    // History is best-effort: a failure to record must not fail the status check
+    if record {
+        let _ = status_service.record_snapshot(&status).await;
+    }
+    let initialized = status.initialized;
+    let exit_code = status.exit_code();
+
     if quiet {
         // Just show basic status without details
         if status.initialized {
@@ -32,7 +88,11 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
                         formatter.warning("Repository has uncommitted changes")
                     );
                 }
-                if repo.status.behind_count > 0 {
+                if !repo.status.tracks_upstream {
+                    if repo.status.behind_count > 0 {
+                        println!("{}", formatter.info("Newer snapshot available"));
+                    }
+                } else if repo.status.behind_count > 0 {
                     println!(
                         "{}",
                         formatter.info(&format!("{} commits behind", repo.status.behind_count))
@@ -49,7 +109,9 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
             let issues = status.symlinks.missing
                 + status.symlinks.broken
                 + status.symlinks.conflicts
-                + status.symlinks.invalid_targets;
+                + status.symlinks.invalid_targets
+                + status.symlinks.wrong_permissions
+                + status.symlinks.unreadable;
             if issues > 0 {
                 println!(
                     "{}",
@@ -58,6 +120,64 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
             } else {
                 println!("{}", formatter.success("All symlinks OK"));
             }
+            if !status.symlinks.pin_mismatches.is_empty() {
+                println!(
+                    "{}",
+                    formatter.error(&format!(
+                        "{} pinned source(s) changed (see 'dotf status')",
+                        status.symlinks.pin_mismatches.len()
+                    ))
+                );
+            }
+
+            let stale_tools = status
+                .tools
+                .iter()
+                .filter(|tool| matches!(tool.state, ToolState::Outdated | ToolState::Missing))
+                .count();
+            if stale_tools > 0 {
+                println!(
+                    "{}",
+                    formatter.warning(&format!(
+                        "{} tools need installing or updating",
+                        stale_tools
+                    ))
+                );
+            }
+
+            if let Some(cron) = &status.cron {
+                if cron.state != CronState::InSync {
+                    println!("{}", formatter.warning(cron_message(cron)));
+                }
+            }
+
+            if let Some(ssh) = &status.ssh {
+                if !ssh.is_ok() {
+                    println!(
+                        "{}",
+                        formatter.warning("~/.ssh needs attention (see 'dotf status')")
+                    );
+                }
+            }
+
+            if let Some(secrets) = &status.secrets {
+                if !secrets.is_ok() {
+                    println!(
+                        "{}",
+                        formatter.warning("Secrets need attention (see 'dotf secrets status')")
+                    );
+                }
+            }
+
+            if !status.stale_renders.is_empty() {
+                println!(
+                    "{}",
+                    formatter.warning(&format!(
+                        "{} rendered files are out of date (run 'dotf render --stale')",
+                        status.stale_renders.len()
+                    ))
+                );
+            }
         } else {
             println!("{}", formatter.error("Not initialized"));
         }
@@ -69,7 +189,7 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
                 "{}",
                 formatter.info("Run 'dotf init --repo <repository>' to get started")
             );
-            return Ok(());
+            return finish_check(check, exit_code);
         }
 
         // Repository status
@@ -81,6 +201,8 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
                     repo.status.behind_count,
                     repo.status.ahead_count,
                     &repo.status.current_branch,
+                    repo.status.tracks_upstream,
+                    repo.status.fetched,
                 )
             );
         }
@@ -96,6 +218,9 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
                 status.symlinks.conflicts,
                 status.symlinks.invalid_targets,
                 status.symlinks.modified,
+                status.symlinks.disabled,
+                status.symlinks.wrong_permissions,
+                status.symlinks.unreadable,
             )
         );
 
@@ -110,6 +235,7 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
                     target_path: detail.target_path.clone(),
                     source_path: detail.source_path.clone(),
                     current_target: detail.current_target.clone(),
+                    error: detail.error.clone(),
                 })
                 .collect();
 
@@ -117,14 +243,378 @@ pub async fn handle_status(quiet: bool) -> DotfResult<()> {
             let repo_path = filesystem.dotf_repo_path();
             println!("{}", ui.symlinks_status_table(&symlink_details, &repo_path));
         }
+
+        let overriding: Vec<_> = status
+            .symlinks
+            .details
+            .iter()
+            .filter(|detail| !detail.overrides.is_empty())
+            .collect();
+        if !overriding.is_empty() {
+            println!("{}", formatter.section("Overridden Entries"));
+            for detail in overriding {
+                println!(
+                    "{}",
+                    formatter.info(&format!(
+                        "{} uses {} instead of {}",
+                        detail.target_path,
+                        detail.source_path,
+                        detail.overrides.join(", ")
+                    ))
+                );
+            }
+        }
+
+        if !status.symlinks.pin_mismatches.is_empty() {
+            println!("{}", formatter.section("Pinned Sources"));
+            for mismatch in &status.symlinks.pin_mismatches {
+                let message = match &mismatch.actual {
+                    Some(actual) => format!(
+                        "{} changed since it was pinned (pinned {}, now {})",
+                        mismatch.source, mismatch.expected, actual
+                    ),
+                    None => format!(
+                        "{} is a directory; pins only apply to files",
+                        mismatch.source
+                    ),
+                };
+                println!("{}", formatter.error(&message));
+            }
+            println!(
+                "{}",
+                formatter.info(
+                    "Review the changes, then update pin_sha256 in dotf.toml; until then 'dotf install config' refuses to link them"
+                )
+            );
+        }
+
+        if !status.tools.is_empty() {
+            let tool_details: Vec<ToolDetail> = status
+                .tools
+                .iter()
+                .map(|tool| ToolDetail {
+                    name: tool.name.clone(),
+                    wanted: tool.wanted.clone(),
+                    installed: tool.installed.clone(),
+                    state: tool.state.clone(),
+                })
+                .collect();
+            println!("{}", ui.tools_status(&tool_details));
+        }
+
+        if let Some(cron) = &status.cron {
+            println!("{}", formatter.section("Cron"));
+            let message = cron_message(cron);
+            match cron.state {
+                CronState::InSync => println!(
+                    "{}",
+                    formatter.success(&format!("{} entries installed", cron.declared))
+                ),
+                CronState::Pending => println!(
+                    "{}",
+                    formatter.warning(&format!("{} (run 'dotf install cron')", message))
+                ),
+                CronState::Drifted => println!(
+                    "{}",
+                    formatter.warning(&format!(
+                        "{} (move the change into [cron], then run 'dotf install cron')",
+                        message
+                    ))
+                ),
+            }
+        }
+
+        if let Some(ssh) = &status.ssh {
+            println!("{}", formatter.section("SSH"));
+            if ssh.is_ok() {
+                println!("{}", formatter.success("~/.ssh matches [ssh]"));
+            }
+            for problem in ssh_problems(ssh) {
+                println!("{}", formatter.warning(&problem));
+            }
+        }
+
+        if let Some(secrets) = &status.secrets {
+            println!("{}", formatter.section("Secrets"));
+            if secrets.is_ok() {
+                println!(
+                    "{}",
+                    formatter.success(&format!("{} files decrypted", secrets.files.len()))
+                );
+            }
+            for problem in secrets.files.iter().filter_map(secret_problem) {
+                println!("{}", formatter.warning(&problem));
+            }
+        }
+
+        if !status.stale_renders.is_empty() {
+            println!("{}", formatter.section("Rendered files"));
+            for stale in &status.stale_renders {
+                println!("{}", formatter.warning(&stale_render_message(stale)));
+            }
+        }
+    }
+
+    if packages && initialized {
+        show_packages(quiet).await?;
+    }
+
+    finish_check(check, exit_code)
+}
+
+/// `dotf status --fail-on`: print nothing, and exit with the bits of the selected
+/// problems that were found
+pub async fn handle_status_fail_on(
+    repo: Option<String>,
+    categories: &[CheckCategory],
+    fetch: FetchPolicy,
+) -> DotfResult<()> {
+    let record = repo.is_none();
+    let status_service = create_status_service(repo, fetch);
+    let status = status_service.get_status().await?;
+    if record {
+        let _ = status_service.record_snapshot(&status).await;
+    }
+
+    let code = status.failure_code(categories);
+    if code != EXIT_OK {
+        std::process::exit(code);
+    }
+    Ok(())
+}
+
+/// With `--check`, end the process with the status exit code so scripts can act on it
+fn finish_check(check: bool, exit_code: i32) -> DotfResult<()> {
+    if check && exit_code != EXIT_OK {
+        std::process::exit(exit_code);
+    }
+    Ok(())
+}
+
+/// Ask the package managers about `[packages]`; only on request, since they are slow
+async fn show_packages(quiet: bool) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+
+    let spinner = Spinner::new("Checking packages...");
+    let statuses = match PackagesService::new(RealFileSystem::new()).statuses().await {
+        Ok(statuses) => {
+            spinner.finish_and_clear();
+            statuses
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to check packages: {}", e));
+            return Err(e);
+        }
+    };
+
+    if quiet {
+        let missing = statuses
+            .iter()
+            .filter(|status| status.state == PackageState::Missing)
+            .count();
+        if missing > 0 {
+            println!(
+                "{}",
+                formatter.warning(&format!("{} packages missing", missing))
+            );
+        }
+        return Ok(());
+    }
+
+    if statuses.is_empty() {
+        println!("{}", formatter.info("No packages configured in [packages]"));
+        return Ok(());
     }
 
+    let results: Vec<OperationResult> = statuses
+        .iter()
+        .map(|status| {
+            let (state, details) = match &status.state {
+                PackageState::Installed => (OperationStatus::Success, "installed".to_string()),
+                PackageState::Missing => (OperationStatus::Failed, "missing".to_string()),
+                PackageState::Unavailable(command) => (
+                    OperationStatus::Skipped,
+                    format!("{} is not available", command),
+                ),
+                PackageState::Disabled => {
+                    (OperationStatus::Skipped, "disabled in settings".to_string())
+                }
+            };
+            OperationResult {
+                operation: status.package.name.clone(),
+                status: state,
+                details: Some(format!("{} ({})", details, status.package.kind)),
+            }
+        })
+        .collect();
+    println!("{}", ui.operation_results("Packages", &results));
+
     Ok(())
 }
 
-fn create_status_service() -> StatusService<GitRepository, RealFileSystem> {
-    let repository = GitRepository::new();
+fn ssh_problems(ssh: &SshStatusInfo) -> Vec<String> {
+    let mut problems = Vec::new();
+    if !ssh.config_in_sync {
+        problems.push("~/.ssh/config fragments need merging (run 'dotf install ssh')".to_string());
+    }
+    if ssh.missing_known_hosts > 0 {
+        problems.push(format!(
+            "{} known_hosts entries missing (run 'dotf install ssh')",
+            ssh.missing_known_hosts
+        ));
+    }
+    if !ssh.stale_keys.is_empty() {
+        problems.push(format!(
+            "Keys need copying: {} (run 'dotf install ssh')",
+            ssh.stale_keys.join(", ")
+        ));
+    }
+    if !ssh.open_permissions.is_empty() {
+        problems.push(format!(
+            "Permissions too open: {} (run 'dotf install ssh')",
+            ssh.open_permissions.join(", ")
+        ));
+    }
+    if !ssh.linked_private_keys.is_empty() {
+        problems.push(format!(
+            "Private keys in [symlinks]: {} (move them to [ssh.keys])",
+            ssh.linked_private_keys.join(", ")
+        ));
+    }
+    problems
+}
+
+/// What is wrong with a secret's target and how to fix it; `None` when it is in sync
+pub fn secret_problem(file: &SecretStatus) -> Option<String> {
+    let problem = match file.state {
+        SecretState::InSync => return None,
+        SecretState::Missing => "not decrypted yet (run 'dotf secrets decrypt')",
+        SecretState::Outdated => "encrypted file changed (run 'dotf secrets decrypt')",
+        SecretState::Modified => {
+            "edited since it was decrypted (run 'dotf secrets encrypt' to keep the edits)"
+        }
+        SecretState::Untracked => {
+            "exists but was not decrypted by dotf (run 'dotf secrets decrypt --force' to replace it)"
+        }
+        SecretState::SourceMissing => "encrypted file is missing from the repository",
+    };
+    Some(format!("{}: {}", file.target_path, problem))
+}
+
+/// `~/.ssh/config: ssh/work changed (run 'dotf render --stale')`
+pub fn stale_render_message(stale: &StaleRender) -> String {
+    format!(
+        "{}: {} changed (run 'dotf render --stale')",
+        PathDisplay::new().path(&stale.output),
+        stale.changed.join(", ")
+    )
+}
+
+fn cron_message(cron: &CronStatusInfo) -> &'static str {
+    match cron.state {
+        CronState::InSync => "Cron entries are up to date",
+        CronState::Pending => "Cron entries need installing",
+        CronState::Drifted => "Cron entries were edited outside dotf",
+    }
+}
+
+/// `dotf status --since`: the recorded snapshots only, so a query neither checks the
+/// links nor fetches, and adds nothing to the history it reads
+async fn handle_status_since(
+    since: &str,
+    json: bool,
+    repo: Option<String>,
+    fetch: FetchPolicy,
+) -> DotfResult<()> {
+    let status_service = create_status_service(repo, fetch);
+    let snapshots = status_service.history_since(since).await?;
+    if json {
+        println!("{}", status_service.to_json(&snapshots)?);
+    } else {
+        println!("{}", UiComponents::new().status_timeline(&snapshots, since));
+    }
+    Ok(())
+}
+
+/// Print the status as JSON on stdout, without a spinner, for scripts and prompts
+async fn handle_status_json(
+    repo: Option<String>,
+    check: bool,
+    fetch: FetchPolicy,
+) -> DotfResult<()> {
+    let record = repo.is_none();
+    let status_service = create_status_service(repo, fetch);
+    let status = status_service.get_status().await?;
+    if record {
+        let _ = status_service.record_snapshot(&status).await;
+    }
+
+    println!("{}", status_service.to_json(&status)?);
+    finish_check(check, status.exit_code())
+}
+
+/// Print the status bar segment from the last recorded status without computing a new one.
+///
+/// Status bars poll every few seconds, so this only reads the history file; a stale or
+/// missing entry starts `dotf status --quiet` in the background to refresh it.
+async fn handle_status_widget(format: WidgetFormat) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let settings = std::fs::read_to_string(filesystem.dotf_settings_path())
+        .ok()
+        .and_then(|content| Settings::from_toml(&content).ok());
+    let snapshot = HistoryStore::new(filesystem.clone())
+        .latest()
+        .await
+        .unwrap_or(None);
+
+    let widget = settings
+        .as_ref()
+        .map(|settings| settings.widget.clone())
+        .unwrap_or_default();
+    let max_age = widget.max_age.unwrap_or(DEFAULT_MAX_AGE) as i64;
+    let stale = snapshot
+        .as_ref()
+        .is_none_or(|snapshot| (Utc::now() - snapshot.taken_at).num_seconds() >= max_age);
+    if stale && settings.is_some() {
+        refresh_in_background(&filesystem.dotf_directory());
+    }
+
+    println!("{}", render_widget(format, snapshot.as_ref(), &widget));
+    Ok(())
+}
+
+/// Start a detached `dotf status --quiet`, at most once per cooldown. Nobody waits for
+/// it, so it waits for the remote as long as it takes.
+pub(super) fn refresh_in_background(dotf_dir: &str) {
+    let marker = Path::new(dotf_dir).join("widget-refresh");
+    let recently_started = std::fs::metadata(&marker)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|elapsed| elapsed < WIDGET_REFRESH_COOLDOWN);
+    if recently_started || std::fs::write(&marker, "").is_err() {
+        return;
+    }
+
+    if let Ok(executable) = std::env::current_exe() {
+        let _ = std::process::Command::new(executable)
+            .args(["status", "--quiet", "--fetch", "always"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+    }
+}
+
+fn create_status_service(
+    repo: Option<String>,
+    fetch: FetchPolicy,
+) -> StatusService<RepositoryRegistry, RealFileSystem> {
+    let repository = RepositoryRegistry::new();
     let filesystem = RealFileSystem::new();
 
     StatusService::new(repository, filesystem)
+        .with_only_repository(repo)
+        .with_fetch_policy(fetch)
 }
@@ -1,15 +1,23 @@
-use crate::cli::args::SymlinksAction;
+use crate::cli::args::{LinkExportFormat, SymlinksAction};
 use crate::cli::{
-    BackupEntry, MessageFormatter, OperationResult, OperationStatus, Spinner, SymlinkDetail,
-    UiComponents,
+    BackupEntry, MessageFormatter, OperationResult, OperationStatus, PathDisplay, RepairEntry,
+    Spinner, SymlinkDetail, UiComponents,
+};
+use crate::core::{
+    filesystem::RealFileSystem, repository::RepositoryRegistry, scripts::SystemScriptExecutor,
 };
-use crate::core::{filesystem::RealFileSystem, scripts::SystemScriptExecutor};
 use crate::error::{DotfError, DotfResult};
-use crate::services::{InstallService, StatusService};
+use crate::services::repair_service::{RepairAction, RepairDecision};
+use crate::services::{
+    BackupGcService, ExportService, InstallService, RepairService, StatusService,
+};
 use crate::traits::{filesystem::FileSystem, prompt::Prompt};
-use crate::utils::ConsolePrompt;
+use crate::utils::{editor::open_in_editor, ConsolePrompt, NonInteractivePrompt};
 
-pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
+pub async fn handle_symlinks(
+    action: Option<SymlinksAction>,
+    non_interactive: bool,
+) -> DotfResult<()> {
     let formatter = MessageFormatter::new();
     let ui = UiComponents::new();
 
@@ -22,12 +30,10 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
             if list {
                 // List available backups
                 let spinner = Spinner::new("Loading backup list...");
-                let filesystem = RealFileSystem::new();
-                let prompt = ConsolePrompt::new();
                 let install_service = InstallService::new(
-                    filesystem.clone(),
+                    RealFileSystem::new(),
                     SystemScriptExecutor::new(),
-                    prompt.clone(),
+                    NonInteractivePrompt::new(),
                 );
                 let backup_manager = install_service.get_backup_manager();
 
@@ -83,6 +89,9 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
                             "Restored {} files",
                             result.restored_count
                         ));
+                        for warning in &result.ownership_warnings {
+                            println!("{}", formatter.warning(&warning.to_string()));
+                        }
 
                         if !result.failed_restorations.is_empty() {
                             println!(
@@ -127,8 +136,11 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
 
                 let spinner = Spinner::new(&format!("Restoring backup for: {}", path));
                 match backup_manager.restore_specific_backup(&path).await {
-                    Ok(_) => {
+                    Ok(warning) => {
                         spinner.finish_with_success(&format!("Restored backup for: {}", path));
+                        if let Some(warning) = warning {
+                            println!("{}", formatter.warning(&warning.to_string()));
+                        }
                     }
                     Err(e) => {
                         spinner.finish_with_error(&format!("Restore failed for {}: {}", path, e));
@@ -141,6 +153,17 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
                 ));
             }
         }
+        Some(SymlinksAction::Repair {
+            include_export_ignored,
+        }) => {
+            handle_repair_wizard(&formatter, &ui, include_export_ignored).await?;
+        }
+        Some(SymlinksAction::Gc { older_than }) => {
+            handle_backup_gc(&formatter, &ui, older_than, non_interactive).await?;
+        }
+        Some(SymlinksAction::Export { format, output }) => {
+            handle_export(&formatter, format, output).await?;
+        }
         None => {
             // Show symlink status by default
             let spinner = Spinner::new("Checking symlinks...");
@@ -177,6 +200,9 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
                     status.symlinks.conflicts,
                     status.symlinks.invalid_targets,
                     status.symlinks.modified,
+                    status.symlinks.disabled,
+                    status.symlinks.wrong_permissions,
+                    status.symlinks.unreadable,
                 )
             );
 
@@ -191,6 +217,7 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
                         target_path: detail.target_path.clone(),
                         source_path: detail.source_path.clone(),
                         current_target: detail.current_target.clone(),
+                        error: detail.error.clone(),
                     })
                     .collect();
 
@@ -204,12 +231,332 @@ pub async fn handle_symlinks(action: Option<SymlinksAction>) -> DotfResult<()> {
     Ok(())
 }
 
-fn create_status_service(
-) -> StatusService<crate::core::repository::GitRepository, crate::core::filesystem::RealFileSystem>
-{
-    use crate::core::repository::GitRepository;
+async fn handle_repair_wizard(
+    formatter: &MessageFormatter,
+    ui: &UiComponents,
+    include_export_ignored: bool,
+) -> DotfResult<()> {
+    let prompt = ConsolePrompt::new();
+    let repair_service = RepairService::new(RealFileSystem::new(), prompt.clone())
+        .with_repository(Box::new(RepositoryRegistry::new()))
+        .with_include_export_ignored(include_export_ignored);
+
+    let spinner = Spinner::new("Checking symlinks...");
+    let candidates = match repair_service.find_candidates().await {
+        Ok(candidates) => {
+            spinner.finish_and_clear();
+            candidates
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to check symlinks: {}", e));
+            return Err(e);
+        }
+    };
+
+    if candidates.is_empty() {
+        println!(
+            "{}",
+            formatter.success("No broken or conflicting symlinks found")
+        );
+        return Ok(());
+    }
+
+    // Collect a decision for every entry first; nothing is changed until the end
+    let mut decisions = Vec::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        let preview = repair_service.preview(candidate).await?;
+        println!(
+            "{}",
+            ui.repair_preview(
+                index + 1,
+                candidates.len(),
+                &RepairEntry {
+                    target_path: candidate.target_path.clone(),
+                    status: candidate.status.clone(),
+                    expected: preview.expected,
+                    current: preview.current,
+                    diff: preview.diff,
+                },
+            )
+        );
+
+        let actions = repair_service.available_actions(candidate).await?;
+        let options: Vec<(&str, &str)> = actions
+            .iter()
+            .map(|action| (action.label(), action.description()))
+            .collect();
+        let choice = prompt.select("What should dotf do?", &options).await?;
+
+        decisions.push(RepairDecision {
+            candidate: candidate.clone(),
+            action: actions[choice],
+        });
+    }
+
+    let pending: Vec<RepairDecision> = decisions
+        .into_iter()
+        .filter(|decision| decision.action != RepairAction::Skip)
+        .collect();
+
+    if pending.is_empty() {
+        println!("{}", formatter.info("Nothing to do"));
+        return Ok(());
+    }
+
+    let planned: Vec<OperationResult> = pending
+        .iter()
+        .map(|decision| OperationResult {
+            operation: decision.candidate.target_path.clone(),
+            status: OperationStatus::InProgress,
+            details: Some(decision.action.description().to_string()),
+        })
+        .collect();
+    println!("{}", ui.operation_results("Planned Changes", &planned));
+
+    if !prompt
+        .confirm(&formatter.question(&format!("Apply {} change(s)?", pending.len())))
+        .await?
+    {
+        println!("{}", formatter.info("Repair cancelled"));
+        return Ok(());
+    }
+
+    let report = repair_service.apply(&pending).await?;
+
+    let results: Vec<OperationResult> = report
+        .outcomes
+        .iter()
+        .map(|outcome| OperationResult {
+            operation: format!("{} {}", outcome.action.label(), outcome.target_path),
+            status: if outcome.error.is_some() {
+                OperationStatus::Failed
+            } else {
+                OperationStatus::Success
+            },
+            details: outcome.error.clone(),
+        })
+        .collect();
+    println!("{}", ui.operation_results("Repair Results", &results));
+
+    if !report.backups.is_empty() {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Backed up {} file(s). Use 'dotf symlinks restore --list' to see them",
+                report.backups.len()
+            ))
+        );
+    }
+    for source_path in &report.merge_conflicts {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "Merge left conflict markers in {}. Opening your editor to resolve them",
+                source_path
+            ))
+        );
+        if let Err(e) = open_in_editor(source_path) {
+            println!(
+                "{}",
+                formatter.error(&format!("{}. Resolve the conflicts manually", e))
+            );
+        }
+    }
+    if report.config_updated {
+        println!(
+            "{}",
+            formatter.info("dotf.toml was updated. Commit the change in your dotfiles repository")
+        );
+    }
+    if pending
+        .iter()
+        .any(|decision| matches!(decision.action, RepairAction::Adopt | RepairAction::Merge))
+    {
+        println!(
+            "{}",
+            formatter.info(
+                "Adopted and merged files were written to your dotfiles repository. Review and commit them"
+            )
+        );
+    }
+
+    Ok(())
+}
+
+async fn handle_backup_gc(
+    formatter: &MessageFormatter,
+    ui: &UiComponents,
+    older_than: u64,
+    non_interactive: bool,
+) -> DotfResult<()> {
+    let prompt = ConsolePrompt::new();
+    let gc_service = BackupGcService::new(create_status_service(), RealFileSystem::new());
+
+    let spinner = Spinner::new("Looking for stale backups...");
+    let stale = match gc_service.find_stale(older_than).await {
+        Ok(stale) => {
+            spinner.finish_and_clear();
+            stale
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Failed to check backups: {}", e));
+            return Err(e);
+        }
+    };
+
+    if stale.is_empty() {
+        println!(
+            "{}",
+            formatter.success(&format!(
+                "No backups belong to links that have been valid for {} days",
+                older_than
+            ))
+        );
+        return Ok(());
+    }
+
+    let paths = PathDisplay::new();
+
+    let candidates: Vec<OperationResult> = stale
+        .iter()
+        .map(|backup| OperationResult {
+            operation: paths.path(&backup.entry.original_path),
+            status: OperationStatus::Skipped,
+            details: Some(format!(
+                "linked since {}, backup from {}",
+                backup.linked_since.format("%Y-%m-%d"),
+                backup.entry.created_at.format("%Y-%m-%d")
+            )),
+        })
+        .collect();
+    println!("{}", ui.operation_results("Stale Backups", &candidates));
+
+    let selected = if non_interactive {
+        stale
+    } else {
+        let options = [
+            ("Delete all", "Delete every backup listed above"),
+            ("Review", "Decide for each backup"),
+            ("Keep all", "Leave the backups in place"),
+        ];
+        match prompt
+            .select(
+                &format!("What should dotf do with these {} backup(s)?", stale.len()),
+                &options,
+            )
+            .await?
+        {
+            0 => stale,
+            1 => {
+                let mut selected = Vec::new();
+                for backup in stale {
+                    let question = format!(
+                        "Delete the backup of {} from {}?",
+                        paths.path(&backup.entry.original_path),
+                        backup.entry.created_at.format("%Y-%m-%d")
+                    );
+                    if prompt.confirm(&formatter.question(&question)).await? {
+                        selected.push(backup);
+                    }
+                }
+                selected
+            }
+            _ => Vec::new(),
+        }
+    };
+
+    if selected.is_empty() {
+        println!("{}", formatter.info("No backups deleted"));
+        return Ok(());
+    }
+
+    let removed = gc_service.purge(&selected).await?;
+    println!(
+        "{}",
+        formatter.success(&format!("Deleted {} backup(s)", removed))
+    );
+
+    Ok(())
+}
+
+/// `dotf symlinks export`: the install plan as a script or a stow package
+async fn handle_export(
+    formatter: &MessageFormatter,
+    format: LinkExportFormat,
+    output: Option<String>,
+) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let install_service = InstallService::new(
+        filesystem.clone(),
+        SystemScriptExecutor::new(),
+        NonInteractivePrompt::new(),
+    )
+    .with_repository(Box::new(RepositoryRegistry::new()));
+    let steps = install_service.plan_config().await?;
+    let export_service = ExportService::new(filesystem.clone());
+
+    match (format, output) {
+        (LinkExportFormat::Shell, None) => print!("{}", export_service.export_shell(&steps).await?),
+        (LinkExportFormat::Shell, Some(path)) => {
+            filesystem
+                .write(&path, &export_service.export_shell(&steps).await?)
+                .await?;
+            filesystem.set_mode(&path, 0o755).await?;
+            println!(
+                "{}",
+                formatter.success(&format!(
+                    "Script for {} target(s) written to {}",
+                    steps.len(),
+                    path
+                ))
+            );
+        }
+        (LinkExportFormat::Stow, output) => {
+            let package = output.ok_or_else(|| {
+                DotfError::Operation("--format stow needs --output <directory>".to_string())
+            })?;
+            let export = export_service.export_stow(&steps, &package).await?;
+            println!(
+                "{}",
+                formatter.success(&format!(
+                    "Copied {} file(s) into the stow package {}",
+                    export.files.len(),
+                    package
+                ))
+            );
+            for target in &export.skipped {
+                println!(
+                    "{}",
+                    formatter.warning(&format!(
+                        "Left out {}: stow only places files under the home directory",
+                        target
+                    ))
+                );
+            }
+            let path = std::path::Path::new(&package);
+            println!(
+                "{}",
+                formatter.info(&format!(
+                    "Deploy it with 'stow --no-folding -d {} -t ~ {}'",
+                    path.parent()
+                        .map(|parent| parent.to_string_lossy().to_string())
+                        .filter(|parent| !parent.is_empty())
+                        .unwrap_or_else(|| ".".to_string()),
+                    path.file_name()
+                        .map(|name| name.to_string_lossy().to_string())
+                        .unwrap_or_default()
+                ))
+            );
+        }
+    }
+    Ok(())
+}
 
-    let repository = GitRepository::new();
+fn create_status_service() -> StatusService<
+    crate::core::repository::RepositoryRegistry,
+    crate::core::filesystem::RealFileSystem,
+> {
+    let repository = RepositoryRegistry::new();
     let filesystem = RealFileSystem::new();
 
     StatusService::new(repository, filesystem)
@@ -1,19 +1,269 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::cli::json::{self, JsonProgress};
 use crate::cli::{MessageFormatter, Spinner};
-use crate::core::{filesystem::RealFileSystem, repository::GitRepository};
-use crate::error::DotfResult;
-use crate::services::SyncService;
+use crate::core::{
+    config::overlays::BASE_REPOSITORY, filesystem::RealFileSystem, network,
+    repository::RepositoryRegistry, scripts::HookEvent, scripts::SystemScriptExecutor,
+    state::StateStore, symlinks::RepairedLink,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{
+    env_service::EnvFileStatus,
+    render_service::StaleRender,
+    sync_service::{OverlayOutcome, OverlaySync, StashOutcome, SyncResult},
+    EnvService, InstallService, RenderService, SyncService,
+};
+use crate::traits::{
+    progress::{ProgressEvent, ProgressReporter},
+    prompt::Prompt,
+    repository::{Rename, UpstreamHistory},
+};
+use crate::utils::ConsolePrompt;
+
+#[allow(clippy::too_many_arguments)]
+pub async fn handle_sync(
+    force: bool,
+    autostash: bool,
+    push: bool,
+    message: Option<&str>,
+    non_interactive: bool,
+    repo: Option<&str>,
+    metered: bool,
+    json: bool,
+) -> DotfResult<()> {
+    if push {
+        return handle_push(message, non_interactive).await;
+    }
+    if json {
+        return sync_json(force, autostash, non_interactive, repo, metered).await;
+    }
 
-pub async fn handle_sync(force: bool) -> DotfResult<()> {
     let filesystem = RealFileSystem::new();
-    let repository = GitRepository::new();
-    let sync_service = SyncService::new(repository, filesystem);
     let formatter = MessageFormatter::new();
+    let metered = network::is_metered(&filesystem, metered).await;
+    if metered {
+        println!(
+            "{}",
+            formatter.info(
+                "Metered connection: fetching only the current branch, without tags or submodules"
+            )
+        );
+    }
+    let repository = RepositoryRegistry::new().with_minimal_fetch(metered);
+    let sync_service = SyncService::new(repository, filesystem);
+    let sync_base = repo.is_none_or(|name| name == BASE_REPOSITORY);
+
+    if sync_base && !check_history(&sync_service, &formatter).await? {
+        return Ok(());
+    }
+
+    super::install::run_hook(HookEvent::PreSync, &BTreeMap::new()).await?;
+
+    let commits_pulled = if sync_base {
+        sync_main(&sync_service, force, autostash, non_interactive, &formatter).await?
+    } else {
+        0
+    };
+    if repo != Some(BASE_REPOSITORY) {
+        sync_overlays(&sync_service, repo, &formatter).await?;
+    }
+
+    // A broken dotf.toml should not turn a successful sync into a failure
+    if let Err(e) = super::install::regenerate_env(false).await {
+        println!(
+            "{}",
+            formatter.warning(&format!("Could not regenerate [env]: {}", e))
+        );
+    }
+    // Other composed files are left for 'dotf render', since they may need a look first
+    let stale_renders = RenderService::new(RealFileSystem::new())
+        .stale()
+        .await
+        .unwrap_or_default();
+    for stale in &stale_renders {
+        println!(
+            "{}",
+            formatter.warning(&super::status::stale_render_message(stale))
+        );
+    }
+
+    if !metered {
+        remind_deferred(&formatter).await;
+    }
+
+    let hook_env = BTreeMap::from([("DOTF_SYNC_COMMITS".to_string(), commits_pulled.to_string())]);
+    super::install::run_hook(HookEvent::PostSync, &hook_env).await
+}
+
+/// What `sync --json` did
+#[derive(Serialize)]
+struct SyncReport {
+    metered: bool,
+    /// `None` when only overlays were synced
+    repository: Option<SyncResult>,
+    /// dotf.toml entries whose source the pull renamed; followed only with --yes
+    moved_sources: Vec<Rename>,
+    /// Links moved over to renamed sources
+    relinked: Vec<RepairedLink>,
+    overlays: Vec<OverlaySync>,
+    /// `None` when the `[env]` fragment could not be regenerated
+    env: Option<EnvFileStatus>,
+    stale_renders: Vec<StaleRender>,
+    /// Downloads put off on a metered connection, by install target
+    deferred: BTreeMap<String, Vec<String>>,
+}
+
+/// `sync --json`: a rewritten remote history fails the sync instead of asking how to go on
+async fn sync_json(
+    force: bool,
+    autostash: bool,
+    follow_renames: bool,
+    repo: Option<&str>,
+    metered: bool,
+) -> DotfResult<()> {
+    let filesystem = RealFileSystem::new();
+    let progress = Arc::new(JsonProgress::new());
+    let metered = network::is_metered(&filesystem, metered).await;
+    let repository = RepositoryRegistry::new().with_minimal_fetch(metered);
+    let sync_service = SyncService::new(repository, filesystem.clone());
+    let sync_base = repo.is_none_or(|name| name == BASE_REPOSITORY);
+
+    let result = async {
+        super::install::run_hook_quietly(HookEvent::PreSync, &BTreeMap::new(), progress.as_ref())
+            .await?;
+        let repository = if sync_base {
+            Some(sync_service.sync(force, autostash).await?)
+        } else {
+            None
+        };
+        let renames = repository
+            .as_ref()
+            .map_or(&[][..], |result| result.renames.as_slice());
+        let moved_sources = sync_service.moved_sources(renames).await?;
+        let relinked = if follow_renames && !moved_sources.is_empty() {
+            sync_service.follow_renames(&moved_sources).await?;
+            InstallService::new(
+                filesystem.clone(),
+                SystemScriptExecutor::new(),
+                ConsolePrompt::new(),
+            )
+            .with_progress(progress.clone())
+            .relink_renamed(renames)
+            .await?
+        } else {
+            Vec::new()
+        };
+        let overlays = if repo != Some(BASE_REPOSITORY) {
+            sync_service.sync_overlays(repo).await?
+        } else {
+            Vec::new()
+        };
+        let env = match EnvService::new(filesystem.clone()).generate(false).await {
+            Ok(status) => Some(status),
+            Err(e) => {
+                progress.report(ProgressEvent::Warning(format!(
+                    "Could not regenerate [env]: {}",
+                    e
+                )));
+                None
+            }
+        };
+        let stale_renders = RenderService::new(filesystem.clone())
+            .stale()
+            .await
+            .unwrap_or_default();
+        let deferred = StateStore::new(filesystem.clone())
+            .load()
+            .await
+            .map(|state| state.deferred)
+            .unwrap_or_default();
 
+        let commits_pulled = repository
+            .as_ref()
+            .map_or(0, |result| result.commits_pulled);
+        let hook_env =
+            BTreeMap::from([("DOTF_SYNC_COMMITS".to_string(), commits_pulled.to_string())]);
+        super::install::run_hook_quietly(HookEvent::PostSync, &hook_env, progress.as_ref()).await?;
+        Ok(SyncReport {
+            metered,
+            repository,
+            moved_sources,
+            relinked,
+            overlays,
+            env,
+            stale_renders,
+            deferred,
+        })
+    }
+    .await;
+    json::print_report("sync", result, progress.warnings())
+}
+
+/// Point out downloads an earlier run put off on a metered connection
+async fn remind_deferred(formatter: &MessageFormatter) {
+    let Ok(state) = StateStore::new(RealFileSystem::new()).load().await else {
+        return;
+    };
+    for (target, names) in &state.deferred {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Deferred on a metered connection: {}. Run 'dotf install {}' to download them now",
+                names.join(", "),
+                target
+            ))
+        );
+    }
+}
+
+/// Check the remote history of the main repository; `false` when the user aborted
+async fn check_history(
+    sync_service: &SyncService<RepositoryRegistry, RealFileSystem>,
+    formatter: &MessageFormatter,
+) -> DotfResult<bool> {
+    let spinner = Spinner::new("Checking remote history...");
+    let history = match sync_service.upstream_history().await {
+        Ok(history) => {
+            spinner.finish_and_clear();
+            history
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Sync failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    if history != UpstreamHistory::Linear
+        && !resolve_rewritten_history(sync_service, &history).await?
+    {
+        println!("{}", formatter.info("Sync aborted; nothing was changed"));
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+/// Pull the main repository; returns the number of commits pulled
+async fn sync_main(
+    sync_service: &SyncService<RepositoryRegistry, RealFileSystem>,
+    force: bool,
+    autostash: bool,
+    non_interactive: bool,
+    formatter: &MessageFormatter,
+) -> DotfResult<usize> {
     let spinner = Spinner::new("Syncing with remote repository...");
 
-    match sync_service.sync(force).await {
+    match sync_service.sync(force, autostash).await {
         Ok(result) => {
-            if result.commits_pulled > 0 {
+            if !result.tracks_upstream {
+                spinner.finish_with_success(if result.commits_pulled > 0 {
+                    "Downloaded the latest snapshot"
+                } else {
+                    "Snapshot is up to date"
+                });
+            } else if result.commits_pulled > 0 {
                 spinner.finish_with_success(&format!(
                     "Pulled {} commits on branch '{}'",
                     result.commits_pulled, result.current_branch
@@ -25,25 +275,316 @@ pub async fn handle_sync(force: bool) -> DotfResult<()> {
                 ));
             }
 
-            if result.had_uncommitted_changes {
-                println!(
+            match &result.stash {
+                Some(StashOutcome::Restored) => println!(
+                    "{}",
+                    formatter.info("Stashed local changes and reapplied them after the pull")
+                ),
+                Some(StashOutcome::Conflicts(paths)) => {
+                    println!(
+                        "{}",
+                        formatter.warning(&format!(
+                            "Local changes conflict with the pulled commits in: {}",
+                            paths.join(", ")
+                        ))
+                    );
+                    println!(
+                        "{}",
+                        formatter.info(
+                            "Resolve them in ~/.dotf/repo, then drop the kept stash with 'git -C ~/.dotf/repo stash drop'"
+                        )
+                    );
+                }
+                None if result.had_uncommitted_changes => println!(
                     "{}",
                     formatter.warning("Repository had uncommitted changes (forced sync)")
-                );
+                ),
+                None => {}
             }
-
-            if !result.is_clean_after {
+            // Reapplied changes leave the repository dirty on purpose
+            if !result.is_clean_after && result.stash.is_none() {
                 println!(
                     "{}",
                     formatter.warning("Repository still has uncommitted changes after sync")
                 );
             }
+            follow_renames(sync_service, &result.renames, non_interactive, formatter).await?;
+            Ok(result.commits_pulled)
         }
         Err(e) => {
             spinner.finish_with_error(&format!("Sync failed: {}", e));
+            Err(e)
+        }
+    }
+}
+
+/// Report the files the pull renamed and, once the user agrees (or with --yes), point
+/// the dotf.toml entries that used them at the new paths and move their links over
+async fn follow_renames(
+    sync_service: &SyncService<RepositoryRegistry, RealFileSystem>,
+    renames: &[Rename],
+    non_interactive: bool,
+    formatter: &MessageFormatter,
+) -> DotfResult<()> {
+    for rename in renames {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Moved in the repository: {} → {}",
+                rename.from, rename.to
+            ))
+        );
+    }
+    let moved = sync_service.moved_sources(renames).await?;
+    if moved.is_empty() {
+        return Ok(());
+    }
+
+    for rename in &moved {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "dotf.toml still links from '{}', which is now '{}'",
+                rename.from, rename.to
+            ))
+        );
+    }
+    let accepted = non_interactive
+        || ConsolePrompt::new()
+            .confirm("Update dotf.toml and relink the affected targets?")
+            .await?;
+    if !accepted {
+        println!(
+            "{}",
+            formatter.info("Left dotf.toml as it is; those links stay broken until it is updated")
+        );
+        return Ok(());
+    }
+
+    sync_service.follow_renames(&moved).await?;
+    println!(
+        "{}",
+        formatter.success(&format!(
+            "Updated {} entries in dotf.toml; review and share them with 'dotf sync --push'",
+            moved.len()
+        ))
+    );
+    let spinner = Arc::new(Spinner::new("Relinking moved sources..."));
+    let relinked = InstallService::new(
+        RealFileSystem::new(),
+        SystemScriptExecutor::new(),
+        ConsolePrompt::new(),
+    )
+    .with_progress(spinner.clone())
+    .relink_renamed(renames)
+    .await;
+    match relinked {
+        Ok(relinked) => {
+            spinner.finish_with_success(&format!("Relinked {} target(s)", relinked.len()))
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Relinking failed: {}", e));
             return Err(e);
         }
     }
+    Ok(())
+}
 
+/// Clone or pull the overlay repositories and report each one
+async fn sync_overlays(
+    sync_service: &SyncService<RepositoryRegistry, RealFileSystem>,
+    only: Option<&str>,
+    formatter: &MessageFormatter,
+) -> DotfResult<()> {
+    for overlay in sync_service.sync_overlays(only).await? {
+        match overlay.outcome {
+            OverlayOutcome::Cloned => println!(
+                "{}",
+                formatter.success(&format!("Cloned repository '{}'", overlay.name))
+            ),
+            OverlayOutcome::Pulled => println!(
+                "{}",
+                formatter.success(&format!("Pulled repository '{}'", overlay.name))
+            ),
+            OverlayOutcome::Failed(e) => println!(
+                "{}",
+                formatter.warning(&format!(
+                    "Could not sync repository '{}': {}",
+                    overlay.name, e
+                ))
+            ),
+        }
+    }
     Ok(())
 }
+
+/// Commit local changes and push them; a branch that has diverged from the remote is
+/// only rebased after the user agrees, and never with --yes
+async fn handle_push(message: Option<&str>, non_interactive: bool) -> DotfResult<()> {
+    let sync_service = SyncService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    let formatter = MessageFormatter::new();
+    let prompt = ConsolePrompt::new();
+
+    let spinner = Spinner::new("Checking remote history...");
+    let status = match sync_service.push_status().await {
+        Ok(status) => {
+            spinner.finish_and_clear();
+            status
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Push failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    let rebase = status.behind_count > 0;
+    if rebase {
+        if status.is_clean && status.ahead_count == 0 {
+            println!(
+                "{}",
+                formatter.info(&format!(
+                    "Nothing to push; the remote has {} new commit(s), run 'dotf sync' to pull them",
+                    status.behind_count
+                ))
+            );
+            return Ok(());
+        }
+
+        let local = if status.is_clean {
+            format!("{} local commit(s)", status.ahead_count)
+        } else if status.ahead_count > 0 {
+            format!(
+                "{} local commit(s) and uncommitted changes",
+                status.ahead_count
+            )
+        } else {
+            "uncommitted changes".to_string()
+        };
+
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "Branch '{}' has diverged: {} here, {} new commit(s) on the remote.",
+                status.current_branch, local, status.behind_count
+            ))
+        );
+        if non_interactive {
+            return Err(DotfError::Operation(
+                "Refusing to rebase onto the remote without confirmation; run 'dotf sync --push' without --yes".to_string(),
+            ));
+        }
+        if !prompt
+            .confirm(
+                &formatter.question(
+                    "Commit your changes and rebase them onto the remote before pushing?",
+                ),
+            )
+            .await?
+        {
+            println!("{}", formatter.info("Push aborted; nothing was changed"));
+            return Ok(());
+        }
+    }
+
+    let spinner = Spinner::new("Pushing local changes...");
+    let result = match sync_service.push(message, rebase).await {
+        Ok(result) => result,
+        Err(e) => {
+            spinner.finish_with_error(&format!("Push failed: {}", e));
+            if rebase {
+                println!(
+                    "{}",
+                    formatter.info(
+                        "If the rebase stopped on a conflict, resolve it with 'git -C ~/.dotf/repo status' and push again"
+                    )
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    if result.commits_pushed == 0 {
+        spinner.finish_with_success(&format!(
+            "Nothing to push on branch '{}'",
+            result.current_branch
+        ));
+        return Ok(());
+    }
+    spinner.finish_with_success(&format!(
+        "Pushed {} commit(s) on branch '{}'",
+        result.commits_pushed, result.current_branch
+    ));
+
+    if let Some(commit_message) = &result.commit_message {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Committed {} file(s): {}",
+                result.committed.len(),
+                commit_message
+            ))
+        );
+        for path in &result.committed {
+            println!("  {}", path);
+        }
+    }
+    if result.commits_rebased_onto > 0 {
+        println!(
+            "{}",
+            formatter.info(&format!(
+                "Rebased onto {} remote commit(s)",
+                result.commits_rebased_onto
+            ))
+        );
+    }
+
+    Ok(())
+}
+
+/// Explain a force-pushed or unrelated remote and offer to reset onto it.
+/// Returns false if the user chose to abort.
+async fn resolve_rewritten_history(
+    sync_service: &SyncService<RepositoryRegistry, RealFileSystem>,
+    history: &UpstreamHistory,
+) -> DotfResult<bool> {
+    let formatter = MessageFormatter::new();
+    let prompt = ConsolePrompt::new();
+
+    let explanation = match history {
+        UpstreamHistory::Rewritten { local_commits } => format!(
+            "The remote history was rewritten (force-pushed). {} local commit(s) are no longer on the remote.",
+            local_commits
+        ),
+        _ => "The remote branch no longer shares any history with your local copy.".to_string(),
+    };
+    println!("{}", formatter.warning(&explanation));
+
+    let options = [
+        (
+            "Reset to remote",
+            "save the current commit on a rescue branch, then reset --hard to origin",
+        ),
+        ("Abort", "leave the repository untouched"),
+    ];
+    if prompt.select("How should dotf continue?", &options).await? != 0 {
+        return Ok(false);
+    }
+
+    let branch = sync_service.rescue_and_reset().await?;
+    println!(
+        "{}",
+        formatter.success(&format!(
+            "Saved local commits to branch '{}' and reset to the remote",
+            branch
+        ))
+    );
+    println!(
+        "{}",
+        formatter.info(&format!(
+            "Cherry-pick anything you still need with 'git -C ~/.dotf/repo cherry-pick <commit>' (see 'git log {}')",
+            branch
+        ))
+    );
+
+    Ok(true)
+}
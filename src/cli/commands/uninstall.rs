@@ -0,0 +1,191 @@
+use crate::cli::{
+    MessageFormatter, OperationResult, OperationStatus, PathDisplay, Spinner, UiComponents,
+};
+use crate::core::{filesystem::RealFileSystem, scripts::SystemScriptExecutor};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{install_service::UninstallOutcome, InstallService};
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+use crate::utils::ConsolePrompt;
+
+pub async fn handle_uninstall(
+    all: bool,
+    keep_backups: bool,
+    dry_run: bool,
+    non_interactive: bool,
+) -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let ui = UiComponents::new();
+    let filesystem = RealFileSystem::new();
+    let prompt = ConsolePrompt::new();
+
+    if !filesystem.exists(&filesystem.dotf_settings_path()).await? {
+        return Err(DotfError::NotInitialized);
+    }
+
+    if !non_interactive && !dry_run {
+        let mut question = if keep_backups {
+            "Remove the symlinks from dotf.toml?".to_string()
+        } else {
+            "Remove the symlinks from dotf.toml and restore the files they replaced?".to_string()
+        };
+        if all {
+            question.push_str(&format!(
+                " This also deletes {} (repository clone, settings and state){}.",
+                filesystem.dotf_directory(),
+                if keep_backups { " except backups" } else { "" }
+            ));
+        }
+        if !prompt.confirm(&formatter.question(&question)).await? {
+            println!("{}", formatter.info("Uninstall cancelled"));
+            return Ok(());
+        }
+    }
+
+    let install_service =
+        InstallService::new(filesystem.clone(), SystemScriptExecutor::new(), prompt);
+
+    let spinner = Spinner::new(if dry_run {
+        "Checking symlinks..."
+    } else {
+        "Removing symlinks..."
+    });
+    let uninstalled = match install_service
+        .uninstall_config(!keep_backups, dry_run)
+        .await
+    {
+        Ok(uninstalled) => {
+            spinner.finish_and_clear();
+            uninstalled
+        }
+        Err(e) => {
+            spinner.finish_with_error(&format!("Uninstall failed: {}", e));
+            return Err(e);
+        }
+    };
+
+    let paths = PathDisplay::new();
+
+    let done = if dry_run {
+        OperationStatus::InProgress
+    } else {
+        OperationStatus::Success
+    };
+    let results: Vec<OperationResult> = uninstalled
+        .iter()
+        .map(|link| {
+            let (status, details) = match &link.outcome {
+                UninstallOutcome::Removed => (done, "removed".to_string()),
+                UninstallOutcome::Restored => match &link.ownership {
+                    Some(warning) => (
+                        OperationStatus::Warning,
+                        format!("removed, original restored; {}", warning),
+                    ),
+                    None => (done, "removed, original restored from backup".to_string()),
+                },
+                UninstallOutcome::NotInstalled => {
+                    (OperationStatus::Skipped, "not installed".to_string())
+                }
+                UninstallOutcome::Skipped(reason) => (
+                    OperationStatus::Warning,
+                    format!("left in place: {}", reason),
+                ),
+            };
+            OperationResult {
+                operation: paths.path(&link.target_path),
+                status,
+                details: Some(format!("{} ({})", details, paths.path(&link.source_path))),
+            }
+        })
+        .collect();
+    if results.is_empty() {
+        println!("{}", formatter.info("No symlinks configured"));
+    } else {
+        let title = if dry_run {
+            "Planned Uninstall"
+        } else {
+            "Uninstalled Symlinks"
+        };
+        println!("{}", ui.operation_results(title, &results));
+    }
+
+    let count = |matches: fn(&UninstallOutcome) -> bool| {
+        uninstalled
+            .iter()
+            .filter(|link| matches(&link.outcome))
+            .count()
+            .to_string()
+    };
+    let removed = count(|outcome| {
+        matches!(
+            outcome,
+            UninstallOutcome::Removed | UninstallOutcome::Restored
+        )
+    });
+    let restored = count(|outcome| matches!(outcome, UninstallOutcome::Restored));
+    let skipped = count(|outcome| matches!(outcome, UninstallOutcome::Skipped(_)));
+    println!(
+        "\n{}",
+        formatter.summary_box(
+            "Uninstall Summary",
+            &[
+                (
+                    if dry_run { "To remove" } else { "Removed" },
+                    removed.as_str(),
+                ),
+                (
+                    if dry_run {
+                        "To restore from backup"
+                    } else {
+                        "Restored from backup"
+                    },
+                    restored.as_str(),
+                ),
+                ("Left in place", skipped.as_str()),
+            ]
+        )
+    );
+
+    if dry_run {
+        if all {
+            println!(
+                "{}",
+                formatter.info(&format!(
+                    "Would also delete {}{}",
+                    filesystem.dotf_directory(),
+                    if keep_backups { " except backups" } else { "" }
+                ))
+            );
+        }
+        println!(
+            "{}",
+            formatter.info("Run without --dry-run to apply these changes")
+        );
+        return Ok(());
+    }
+
+    if all {
+        install_service.remove_dotf_directory(keep_backups).await?;
+        if keep_backups {
+            println!(
+                "{}",
+                formatter.success(&format!(
+                    "Removed {}; backups are kept in {}",
+                    filesystem.dotf_directory(),
+                    filesystem.dotf_backup_path()
+                ))
+            );
+        } else {
+            println!(
+                "{}",
+                formatter.success(&format!("Removed {}", filesystem.dotf_directory()))
+            );
+        }
+    } else if keep_backups {
+        println!(
+            "{}",
+            formatter.info("Backups were kept; restore them with 'dotf symlinks restore'")
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,227 @@
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::cli::args::WatchAction;
+use crate::cli::{MessageFormatter, PathDisplay};
+use crate::core::{
+    filesystem::RealFileSystem,
+    lock::{
+        is_process_alive,
+        socket::{query_watch, WatchSocket, WatchStatus},
+        OperationLock,
+    },
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{watch_service::WatchEvent, WatchService};
+use crate::traits::filesystem::{ChangeStream, FileSystem};
+
+/// Editors write a file in several steps; changes are handled once they settle
+const SETTLE_TIME: Duration = Duration::from_millis(300);
+
+/// How often a paused watcher checks whether the command holding the lock is done
+const LOCK_POLL: Duration = Duration::from_secs(1);
+
+pub async fn handle_watch(action: Option<WatchAction>) -> DotfResult<()> {
+    match action {
+        Some(WatchAction::Status) => handle_watch_status().await,
+        None => watch().await,
+    }
+}
+
+async fn watch() -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let filesystem = RealFileSystem::new();
+    let dotf_dir = PathBuf::from(filesystem.dotf_directory());
+    let watch_service = WatchService::new(filesystem);
+
+    let mut plan = watch_service.plan().await?;
+    let socket = WatchSocket::bind(
+        &dotf_dir,
+        WatchStatus::new(plan.directories.len(), plan.links.len()),
+    )
+    .await?;
+    let mut changes = watch_service.start(&plan).await?;
+
+    let display = PathDisplay::new();
+
+    println!(
+        "{}",
+        formatter.info(&format!(
+            "Watching {} linked director{} and {} link{}; press Ctrl-C to stop",
+            plan.directories.len(),
+            if plan.directories.len() == 1 {
+                "y"
+            } else {
+                "ies"
+            },
+            plan.links.len(),
+            if plan.links.len() == 1 { "" } else { "s" }
+        ))
+    );
+
+    loop {
+        let first = tokio::select! {
+            path = changes.next() => path,
+            _ = tokio::signal::ctrl_c() => None,
+        };
+        let Some(first) = first else { break };
+
+        let mut paths = BTreeSet::from([first]);
+        while let Ok(Some(path)) = tokio::time::timeout(SETTLE_TIME, changes.next()).await {
+            paths.insert(path);
+        }
+
+        // A command holding the operation lock goes first; the changes wait for it
+        let Some(_lock) = take_lock(&dotf_dir, &socket, &mut changes, &mut paths).await? else {
+            break;
+        };
+        for path in paths {
+            let events = match watch_service.handle_change(&mut plan, &path).await {
+                Ok(events) => events,
+                Err(e) => {
+                    println!(
+                        "{}",
+                        formatter.error(&format!("{}: {}", display.path(&path), e))
+                    );
+                    continue;
+                }
+            };
+            for event in events {
+                let message = match event {
+                    WatchEvent::Linked {
+                        source_path,
+                        target_path,
+                    } => formatter.file_operation(
+                        "Linked",
+                        &display.path(&source_path),
+                        &display.path(&target_path),
+                    ),
+                    WatchEvent::Blocked {
+                        source_path,
+                        target_path,
+                    } => formatter.warning(&format!(
+                        "Not linking {}: {} already exists",
+                        display.path(&source_path),
+                        display.path(&target_path)
+                    )),
+                    WatchEvent::Replaced { target_path, .. } => formatter.warning(&format!(
+                        "{} was replaced by a regular file; run 'dotf repair' to link it again or keep the change",
+                        display.path(&target_path)
+                    )),
+                };
+                println!("{}", message);
+            }
+        }
+        socket.update(|status| {
+            status.links = plan.links.len();
+            status.last_change = Some(Utc::now());
+        });
+    }
+
+    println!("{}", formatter.info("Stopped watching"));
+    Ok(())
+}
+
+/// Take the operation lock for a batch of changes. While another dotf command holds it,
+/// wait, adding the changes that come in meanwhile to `paths`; `None` when Ctrl-C
+/// stopped the wait.
+async fn take_lock(
+    dotf_dir: &Path,
+    socket: &WatchSocket,
+    changes: &mut ChangeStream,
+    paths: &mut BTreeSet<String>,
+) -> DotfResult<Option<OperationLock>> {
+    let formatter = MessageFormatter::new();
+    let mut paused = false;
+    loop {
+        let holder = OperationLock::holder(dotf_dir).filter(|holder| is_process_alive(holder.pid));
+        match &holder {
+            None => match OperationLock::acquire(dotf_dir, "watch") {
+                Ok(lock) => {
+                    if paused {
+                        println!("{}", formatter.info("Resumed watching"));
+                        socket.update(|status| {
+                            status.paused_for = None;
+                            status.pending = 0;
+                        });
+                    }
+                    return Ok(Some(lock));
+                }
+                // Another command took it first
+                Err(DotfError::Operation(_)) => {}
+                Err(e) => return Err(e),
+            },
+            Some(holder) if !paused => {
+                paused = true;
+                println!(
+                    "{}",
+                    formatter.info(&format!(
+                        "Waiting for 'dotf {}' (pid {}) to finish before handling changes",
+                        holder.command, holder.pid
+                    ))
+                );
+            }
+            Some(_) => {}
+        }
+        socket.update(|status| {
+            status.paused_for = holder;
+            status.pending = paths.len();
+        });
+
+        tokio::select! {
+            path = changes.next() => match path {
+                Some(path) => {
+                    paths.insert(path);
+                }
+                None => tokio::time::sleep(LOCK_POLL).await,
+            },
+            _ = tokio::time::sleep(LOCK_POLL) => {}
+            _ = tokio::signal::ctrl_c() => return Ok(None),
+        }
+    }
+}
+
+/// Report what a running `dotf watch` is doing
+async fn handle_watch_status() -> DotfResult<()> {
+    let formatter = MessageFormatter::new();
+    let dotf_dir = PathBuf::from(RealFileSystem::new().dotf_directory());
+
+    let Some(status) = query_watch(&dotf_dir).await? else {
+        println!("{}", formatter.info("dotf watch is not running"));
+        return Ok(());
+    };
+    println!(
+        "{}",
+        formatter.success(&format!(
+            "dotf watch is running (pid {}) since {}, watching {} linked director{} and {} link{}",
+            status.pid,
+            status.started_at.format("%Y-%m-%d %H:%M:%S UTC"),
+            status.directories,
+            if status.directories == 1 { "y" } else { "ies" },
+            status.links,
+            if status.links == 1 { "" } else { "s" }
+        ))
+    );
+    if let Some(last_change) = status.last_change {
+        println!(
+            "  Last change handled at {}",
+            last_change.format("%Y-%m-%d %H:%M:%S UTC")
+        );
+    }
+    if let Some(holder) = status.paused_for {
+        println!(
+            "{}",
+            formatter.warning(&format!(
+                "Paused while 'dotf {}' (pid {}) runs; {} change{} waiting",
+                holder.command,
+                holder.pid,
+                status.pending,
+                if status.pending == 1 { "" } else { "s" }
+            ))
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,107 @@
+//! The envelope `--json` output of commands that change things is printed in
+
+use serde::Serialize;
+use std::sync::Mutex;
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::progress::{LinkProgress, ProgressEvent, ProgressReporter};
+
+/// What one command did, or the error that stopped it
+#[derive(Debug, Serialize)]
+pub struct JsonReport<'a, T> {
+    pub command: &'static str,
+    pub success: bool,
+    /// `None` when the command failed before it finished
+    pub result: Option<&'a T>,
+    /// Problems that did not stop the command
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl<'a, T: Serialize> JsonReport<'a, T> {
+    pub fn new(command: &'static str, result: &'a DotfResult<T>, warnings: Vec<String>) -> Self {
+        let (result, errors) = match result {
+            Ok(result) => (Some(result), Vec::new()),
+            Err(e) => (None, vec![e.to_string()]),
+        };
+        Self {
+            command,
+            success: errors.is_empty(),
+            result,
+            warnings,
+            errors,
+        }
+    }
+}
+
+/// Print the report for `result` on stdout, then hand back its error so the command still
+/// exits non-zero when it failed
+pub fn print_report<T: Serialize>(
+    command: &'static str,
+    result: DotfResult<T>,
+    warnings: Vec<String>,
+) -> DotfResult<()> {
+    let report = JsonReport::new(command, &result, warnings);
+    let json = serde_json::to_string_pretty(&report)
+        .map_err(|e| DotfError::Operation(format!("Failed to serialize {}: {}", command, e)))?;
+    println!("{}", json);
+    result.map(|_| ())
+}
+
+/// Keeps what services report while a `--json` command runs, instead of drawing it
+#[derive(Default)]
+pub struct JsonProgress {
+    links: Mutex<Option<LinkProgress>>,
+    warnings: Mutex<Vec<String>>,
+}
+
+impl JsonProgress {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last link counts reported, if any links were handled
+    pub fn links(&self) -> Option<LinkProgress> {
+        *self.links.lock().unwrap()
+    }
+
+    pub fn warnings(&self) -> Vec<String> {
+        self.warnings.lock().unwrap().clone()
+    }
+}
+
+impl ProgressReporter for JsonProgress {
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::Links(progress) => *self.links.lock().unwrap() = Some(progress),
+            ProgressEvent::Warning(message) => self.warnings.lock().unwrap().push(message),
+            ProgressEvent::StageStarted(_) | ProgressEvent::ItemCompleted(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_report_carries_result_or_error() {
+        let repaired: DotfResult<Vec<u8>> = Ok(vec![1, 2]);
+        let report = JsonReport::new("repair", &repaired, vec!["slow".to_string()]);
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["command"], "repair");
+        assert_eq!(json["success"], true);
+        assert_eq!(json["result"], serde_json::json!([1, 2]));
+        assert_eq!(json["warnings"], serde_json::json!(["slow"]));
+        assert_eq!(json["errors"], serde_json::json!([]));
+
+        let failed: DotfResult<Vec<u8>> = Err(DotfError::NotInitialized);
+        let json = serde_json::to_value(JsonReport::new("sync", &failed, Vec::new())).unwrap();
+        assert_eq!(json["success"], false);
+        assert!(json["result"].is_null());
+        assert_eq!(
+            json["errors"],
+            serde_json::json!([DotfError::NotInitialized.to_string()])
+        );
+    }
+}
@@ -1,5 +1,6 @@
 pub mod args;
 pub mod commands;
+pub mod json;
 pub mod ui;
 
 pub use args::{Cli, Commands};
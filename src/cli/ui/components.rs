@@ -1,7 +1,14 @@
 //! High-level UI components combining multiple UI elements
 
-use crate::cli::ui::{Icons, MessageFormatter, OperationStatus, Theme};
+use crate::cli::ui::{Icons, MessageFormatter, OperationStatus, PathDisplay, Theme};
+use crate::core::config::reference::SectionDoc;
+use crate::core::history::{drift_start, StatusSnapshot};
 use crate::core::symlinks::SymlinkStatus;
+use crate::core::tools::ToolState;
+use crate::utils::diff::{truncate_line, DiffLine, Hunk, MAX_LINE_CHARS};
+
+/// Diff lines shown per entry in the repair wizard before truncating
+const MAX_PREVIEW_DIFF_LINES: usize = 40;
 
 /// High-level UI components for common CLI patterns
 pub struct UiComponents {
@@ -46,12 +53,37 @@ impl UiComponents {
         behind: usize,
         ahead: usize,
         branch: &str,
+        tracks_upstream: bool,
+        fetched: bool,
     ) -> String {
         let mut output = Vec::new();
 
         output.push(self.formatter.section("Repository Status"));
         output.push(self.formatter.key_value("Branch", branch));
 
+        // Snapshot sources have no working tree or commit counts, only "update available"
+        if !tracks_upstream {
+            if !fetched {
+                output.push(format!(
+                    "  {}",
+                    self.formatter
+                        .info("Remote not checked for a newer snapshot")
+                ));
+            } else if behind > 0 {
+                output.push(format!(
+                    "  {} {}",
+                    Icons::DOWNLOAD,
+                    self.theme.warning("A newer snapshot is available")
+                ));
+            } else {
+                output.push(format!(
+                    "  {}",
+                    self.formatter.success("Snapshot is up to date")
+                ));
+            }
+            return output.join("\n");
+        }
+
         if is_clean {
             output.push(format!(
                 "  {}",
@@ -73,7 +105,13 @@ impl UiComponents {
             output.push(format!("  {} {} commits ahead", Icons::UPLOAD, ahead));
         }
 
-        if behind == 0 && ahead == 0 {
+        if !fetched {
+            output.push(format!(
+                "  {}",
+                self.formatter
+                    .info("Remote not checked; counts are from the last fetch")
+            ));
+        } else if behind == 0 && ahead == 0 {
             output.push(format!(
                 "  {}",
                 self.formatter.success("Up to date with remote")
@@ -91,6 +129,7 @@ impl UiComponents {
 
         let mut output = Vec::new();
         output.push(self.formatter.section("Symlinks Status"));
+        let paths = PathDisplay::new().with_repo(repo_path);
 
         // Group symlinks by status for better organization
         let mut by_status: std::collections::HashMap<String, Vec<&SymlinkDetail>> =
@@ -107,8 +146,11 @@ impl UiComponents {
             "InvalidTarget",
             "Missing",
             "Broken",
+            "WrongPermissions",
+            "Unreadable",
             "Modified",
             "Valid",
+            "Disabled",
         ];
 
         for status_name in &status_order {
@@ -129,33 +171,19 @@ impl UiComponents {
                             (Icons::INVALID_TARGET, self.theme.warning("Wrong target"))
                         }
                         SymlinkStatus::Modified => (Icons::MODIFIED, self.theme.info("Modified")),
-                    };
-
-                    // Convert home directory to ~ notation for target display
-                    let home_dir = dirs::home_dir().map(|d| d.to_string_lossy().to_string());
-                    let target_display = if let Some(ref home) = home_dir {
-                        symlink.target_path.replace(home, "~")
-                    } else {
-                        symlink.target_path.clone()
-                    };
-
-                    // For source, remove the repository path prefix
-                    let source_display = if symlink.source_path.starts_with(repo_path) {
-                        let stripped = symlink
-                            .source_path
-                            .strip_prefix(repo_path)
-                            .unwrap_or(&symlink.source_path);
-                        if let Some(without_slash) = stripped.strip_prefix('/') {
-                            without_slash.to_string()
-                        } else {
-                            stripped.to_string()
+                        SymlinkStatus::Disabled => (Icons::DISABLED, self.theme.muted("Disabled")),
+                        SymlinkStatus::WrongPermissions => (
+                            Icons::WRONG_PERMISSIONS,
+                            self.theme.warning("Wrong permissions"),
+                        ),
+                        SymlinkStatus::Unreadable => {
+                            (Icons::UNREADABLE, self.theme.error("Unreadable"))
                         }
-                    } else if let Some(ref home) = home_dir {
-                        symlink.source_path.replace(home, "~")
-                    } else {
-                        symlink.source_path.clone()
                     };
 
+                    let target_display = paths.path(&symlink.target_path);
+                    let source_display = paths.source(&symlink.source_path);
+
                     // Format the entry
                     let status_part = format!("{} {}", status_icon, status_text);
                     let path_part = format!(
@@ -171,6 +199,14 @@ impl UiComponents {
                         SymlinkStatus::Broken => Some(self.theme.muted(" (target missing)")),
                         SymlinkStatus::Conflict => Some(self.theme.muted(" (file exists)")),
                         SymlinkStatus::Modified => Some(self.theme.muted(" (content changed)")),
+                        SymlinkStatus::Disabled => Some(self.theme.muted(" (on this machine)")),
+                        SymlinkStatus::WrongPermissions => {
+                            Some(self.theme.muted(" (mode or owner differs)"))
+                        }
+                        SymlinkStatus::Unreadable => Some(self.theme.muted(&format!(
+                            " ({})",
+                            symlink.error.as_deref().unwrap_or("cannot be opened")
+                        ))),
                         SymlinkStatus::Valid => None,
                     };
 
@@ -199,6 +235,9 @@ impl UiComponents {
         conflicts: usize,
         invalid_targets: usize,
         modified: usize,
+        disabled: usize,
+        wrong_permissions: usize,
+        unreadable: usize,
     ) -> String {
         let total_str = total.to_string();
         let valid_str = format!("{} {}", valid, Icons::SUCCESS);
@@ -207,6 +246,9 @@ impl UiComponents {
         let conflicts_str = format!("{} {}", conflicts, Icons::WARNING);
         let invalid_targets_str = format!("{} {}", invalid_targets, Icons::INVALID_TARGET);
         let modified_str = format!("{} {}", modified, Icons::MODIFIED);
+        let disabled_str = format!("{} {}", disabled, Icons::DISABLED);
+        let wrong_permissions_str = format!("{} {}", wrong_permissions, Icons::WRONG_PERMISSIONS);
+        let unreadable_str = format!("{} {}", unreadable, Icons::UNREADABLE);
 
         let mut items = Vec::new();
 
@@ -228,6 +270,15 @@ impl UiComponents {
         if modified > 0 {
             items.push(("Modified", modified_str.as_str()));
         }
+        if disabled > 0 {
+            items.push(("Disabled", disabled_str.as_str()));
+        }
+        if wrong_permissions > 0 {
+            items.push(("Wrong permissions", wrong_permissions_str.as_str()));
+        }
+        if unreadable > 0 {
+            items.push(("Unreadable", unreadable_str.as_str()));
+        }
 
         self.formatter.summary_box("Symlinks Summary", &items)
     }
@@ -319,6 +370,62 @@ impl UiComponents {
         format!("{}\n", result)
     }
 
+    /// Display installed release tools compared with dotf.toml
+    pub fn tools_status(&self, tools: &[ToolDetail]) -> String {
+        let mut output = Vec::new();
+        output.push(self.formatter.section("Tools"));
+
+        for tool in tools {
+            let installed = tool.installed.as_deref().unwrap_or("-");
+            let line = match tool.state {
+                ToolState::UpToDate => format!(
+                    "{} {} {}",
+                    Icons::SUCCESS,
+                    tool.name,
+                    self.theme.muted(installed)
+                ),
+                ToolState::Tracking => format!(
+                    "{} {} {}",
+                    Icons::SUCCESS,
+                    tool.name,
+                    self.theme.muted(&format!("{} (latest)", installed))
+                ),
+                ToolState::Outdated => format!(
+                    "{} {} {}",
+                    Icons::WARNING,
+                    tool.name,
+                    self.theme.warning(&format!(
+                        "{} {} {}",
+                        installed,
+                        Icons::ARROW_RIGHT,
+                        tool.wanted
+                    ))
+                ),
+                ToolState::Missing => format!(
+                    "{} {} {}",
+                    Icons::MISSING,
+                    tool.name,
+                    self.theme
+                        .error(&format!("not installed (wants {})", tool.wanted))
+                ),
+            };
+            output.push(format!("  {}", line));
+        }
+
+        if tools
+            .iter()
+            .any(|tool| matches!(tool.state, ToolState::Outdated | ToolState::Missing))
+        {
+            output.push(format!(
+                "  {}",
+                self.formatter
+                    .info("Run 'dotf install tools' to install or update them")
+            ));
+        }
+
+        output.join("\n")
+    }
+
     /// Display operation results
     pub fn operation_results(&self, title: &str, results: &[OperationResult]) -> String {
         let mut output = Vec::new();
@@ -356,6 +463,272 @@ impl UiComponents {
         }
     }
 
+    /// Display a timeline of recorded status snapshots, showing only state changes
+    pub fn status_timeline(&self, snapshots: &[StatusSnapshot], since: &str) -> String {
+        if snapshots.is_empty() {
+            return self
+                .formatter
+                .info(&format!("No status history recorded in the last {}", since));
+        }
+
+        let mut output = Vec::new();
+        output.push(
+            self.formatter
+                .section(&format!("Status History (since {})", since)),
+        );
+
+        let drift = drift_start(snapshots);
+        let mut previous: Option<&StatusSnapshot> = None;
+        let mut unchanged = 0;
+
+        for snapshot in snapshots {
+            if let Some(prev) = previous {
+                if prev.same_state(snapshot) {
+                    unchanged += 1;
+                    continue;
+                }
+            }
+
+            if unchanged > 0 {
+                output.push(format!(
+                    "  {}",
+                    self.theme
+                        .muted(&format!("{} {} unchanged", Icons::TREE_PIPE, unchanged))
+                ));
+                unchanged = 0;
+            }
+
+            let time = self
+                .theme
+                .muted(&snapshot.taken_at.format("%Y-%m-%d %H:%M").to_string());
+            let health = if snapshot.is_healthy() {
+                format!(
+                    "{} {}/{} valid",
+                    Icons::SUCCESS,
+                    snapshot.valid,
+                    snapshot.total
+                )
+            } else {
+                format!(
+                    "{} {}",
+                    Icons::WARNING,
+                    self.theme.warning(&format!("{} issues", snapshot.issues()))
+                )
+            };
+
+            let mut repo = Vec::new();
+            if !snapshot.is_clean {
+                repo.push("dirty".to_string());
+            }
+            if snapshot.behind > 0 {
+                repo.push(format!("{} behind", snapshot.behind));
+            }
+            if snapshot.ahead > 0 {
+                repo.push(format!("{} ahead", snapshot.ahead));
+            }
+            let repo = if repo.is_empty() {
+                String::new()
+            } else {
+                self.theme.muted(&format!(" ({})", repo.join(", ")))
+            };
+
+            let marker = if drift == Some(snapshot) {
+                format!(" {}", self.theme.error("← drift started"))
+            } else {
+                String::new()
+            };
+
+            output.push(format!("  {}  {}{}{}", time, health, repo, marker));
+            previous = Some(snapshot);
+        }
+
+        if unchanged > 0 {
+            output.push(format!(
+                "  {}",
+                self.theme
+                    .muted(&format!("{} {} unchanged", Icons::TREE_PIPE, unchanged))
+            ));
+        }
+
+        output.join("\n")
+    }
+
+    /// Display commit history with the targets each commit changed
+    pub fn commit_log(&self, commits: &[CommitDetail]) -> String {
+        if commits.is_empty() {
+            return self.formatter.info("No commits found");
+        }
+
+        let mut output = Vec::new();
+        for (index, commit) in commits.iter().enumerate() {
+            if index > 0 {
+                output.push(String::new());
+            }
+            output.push(format!(
+                "{} {}",
+                self.theme.warning(&commit.id),
+                self.theme.accent(&commit.summary)
+            ));
+            output.push(
+                self.formatter.indent(
+                    &self
+                        .theme
+                        .muted(&format!("{} · {}", commit.author, commit.date)),
+                    1,
+                ),
+            );
+
+            let total = commit.targets.len() + commit.other_files.len();
+            let paths = commit
+                .targets
+                .iter()
+                .map(|target| self.formatter.path(target))
+                .chain(commit.other_files.iter().map(|file| self.theme.muted(file)));
+            for (position, path) in paths.enumerate() {
+                output.push(self.formatter.tree_item(&path, position + 1 == total, 2));
+            }
+        }
+
+        output.join("\n")
+    }
+
+    /// Display one repair wizard entry: what exists now versus what dotf expects
+    pub fn repair_preview(&self, index: usize, total: usize, entry: &RepairEntry) -> String {
+        let (icon, label) = match entry.status {
+            SymlinkStatus::Broken => (Icons::BROKEN, "Broken"),
+            SymlinkStatus::InvalidTarget => (Icons::INVALID_TARGET, "Invalid target"),
+            SymlinkStatus::Conflict => (Icons::CONFLICT, "Conflict"),
+            SymlinkStatus::Missing => (Icons::MISSING, "Missing"),
+            SymlinkStatus::Modified => (Icons::MODIFIED, "Modified"),
+            SymlinkStatus::Valid => (Icons::VALID, "Valid"),
+            SymlinkStatus::Disabled => (Icons::DISABLED, "Disabled"),
+            SymlinkStatus::WrongPermissions => (Icons::WRONG_PERMISSIONS, "Wrong permissions"),
+            SymlinkStatus::Unreadable => (Icons::UNREADABLE, "Unreadable"),
+        };
+
+        let mut output = Vec::new();
+        output.push(
+            self.formatter
+                .section(&format!("[{}/{}] {}", index, total, label)),
+        );
+        output.push(format!(
+            "  {} {}",
+            icon,
+            self.theme.path(&entry.target_path)
+        ));
+        output.push(format!(
+            "  {}",
+            self.formatter.key_value("Expected", &entry.expected)
+        ));
+        output.push(format!(
+            "  {}",
+            self.formatter.key_value("Current", &entry.current)
+        ));
+
+        if let Some(ref diff) = entry.diff {
+            let changed: Vec<&DiffLine> = diff
+                .iter()
+                .filter(|line| !matches!(line, DiffLine::Context(_)))
+                .collect();
+
+            if changed.is_empty() {
+                output.push(format!(
+                    "  {}",
+                    self.theme
+                        .muted("Contents are identical to the repository source")
+                ));
+            } else {
+                output.push(format!(
+                    "  {}",
+                    self.theme.muted("Diff (repository → current):")
+                ));
+                for line in changed.iter().take(MAX_PREVIEW_DIFF_LINES) {
+                    let rendered = match line {
+                        DiffLine::Added(text) => self
+                            .theme
+                            .success(&format!("+ {}", truncate_line(text, MAX_LINE_CHARS))),
+                        DiffLine::Removed(text) => self
+                            .theme
+                            .error(&format!("- {}", truncate_line(text, MAX_LINE_CHARS))),
+                        DiffLine::Context(text) => self
+                            .theme
+                            .muted(&format!("  {}", truncate_line(text, MAX_LINE_CHARS))),
+                    };
+                    output.push(format!("    {}", rendered));
+                }
+                if changed.len() > MAX_PREVIEW_DIFF_LINES {
+                    output.push(format!(
+                        "    {}",
+                        self.theme.muted(&format!(
+                            "... {} more changed lines",
+                            changed.len() - MAX_PREVIEW_DIFF_LINES
+                        ))
+                    ));
+                }
+            }
+        }
+
+        output.join("\n")
+    }
+
+    /// Display hunks as a colored unified diff from `from` to `to`
+    pub fn unified_diff(&self, from: &str, to: &str, hunks: &[Hunk]) -> String {
+        let mut output = vec![
+            self.theme.error(&format!("--- {}", from)),
+            self.theme.success(&format!("+++ {}", to)),
+        ];
+
+        for hunk in hunks {
+            output.push(self.theme.info(&format!(
+                "@@ -{},{} +{},{} @@",
+                hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+            )));
+            for line in &hunk.lines {
+                output.push(match line {
+                    DiffLine::Added(text) => self
+                        .theme
+                        .success(&format!("+{}", truncate_line(text, MAX_LINE_CHARS))),
+                    DiffLine::Removed(text) => self
+                        .theme
+                        .error(&format!("-{}", truncate_line(text, MAX_LINE_CHARS))),
+                    DiffLine::Context(text) => self
+                        .theme
+                        .muted(&format!(" {}", truncate_line(text, MAX_LINE_CHARS))),
+                });
+            }
+        }
+
+        output.join("\n")
+    }
+
+    /// Display the dotf.toml reference: each table with its fields and an example
+    pub fn config_reference(&self, sections: &[&SectionDoc]) -> String {
+        let mut output = Vec::new();
+
+        for section in sections {
+            output.push(self.formatter.section(&format!("[{}]", section.table)));
+            output.push(section.description.to_string());
+            output.push(String::new());
+            for field in section.fields {
+                output.push(format!(
+                    "  {} {}",
+                    self.theme.label(field.name),
+                    self.theme.muted(&format!(
+                        "({}, default: {})",
+                        field.type_name, field.default
+                    ))
+                ));
+                output.push(format!("      {}", field.description));
+            }
+            output.push(String::new());
+            for line in section.example.lines() {
+                output.push(format!("    {}", self.theme.muted(line)));
+            }
+        }
+
+        output.join("\n")
+    }
+
     /// Display an error with suggestions
     pub fn error_with_suggestions(&self, error: &str, suggestions: &[&str]) -> String {
         let mut output = Vec::new();
@@ -380,6 +753,37 @@ pub struct SymlinkDetail {
     pub target_path: String,
     pub source_path: String,
     pub current_target: Option<String>,
+    /// Why the file cannot be opened, for Unreadable entries
+    pub error: Option<String>,
+}
+
+/// Repair wizard entry for display
+pub struct RepairEntry {
+    pub target_path: String,
+    pub status: SymlinkStatus,
+    pub expected: String,
+    pub current: String,
+    pub diff: Option<Vec<DiffLine>>,
+}
+
+/// Release tool status for display
+pub struct ToolDetail {
+    pub name: String,
+    pub wanted: String,
+    pub installed: Option<String>,
+    pub state: ToolState,
+}
+
+/// Commit for display in the change log
+pub struct CommitDetail {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub date: String,
+    /// Targets changed by the commit, shown first
+    pub targets: Vec<String>,
+    /// Repository files not linked to any target
+    pub other_files: Vec<String>,
 }
 
 /// Backup entry for display
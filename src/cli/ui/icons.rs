@@ -49,6 +49,9 @@ impl Icons {
     pub const CONFLICT: &'static str = "⚠️";
     pub const INVALID_TARGET: &'static str = "❓";
     pub const MODIFIED: &'static str = "🔄";
+    pub const DISABLED: &'static str = "⏸️";
+    pub const WRONG_PERMISSIONS: &'static str = "🔒";
+    pub const UNREADABLE: &'static str = "🚫";
 
     // UI elements
     pub const ARROW_RIGHT: &'static str = "→";
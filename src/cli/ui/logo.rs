@@ -100,6 +100,10 @@ pub enum InstallStage {
     FetchingConfiguration,
     SettingUpDirectories,
     CloningRepository,
+    /// Registering a checkout that already exists instead of cloning
+    UsingLocalCheckout,
+    /// Finishing an init that stopped after cloning
+    ResumingSetup,
     CreatingSymlinks,
     FinalizeSetup,
     Complete,
@@ -115,6 +119,8 @@ impl InstallStage {
             InstallStage::FetchingConfiguration => "Fetching configuration from repository",
             InstallStage::SettingUpDirectories => "Setting up dotf directories",
             InstallStage::CloningRepository => "Cloning dotfiles repository",
+            InstallStage::UsingLocalCheckout => "Using existing local checkout",
+            InstallStage::ResumingSetup => "Resuming the unfinished setup",
             InstallStage::CreatingSymlinks => "Creating symbolic links",
             InstallStage::FinalizeSetup => "Finalizing setup",
             InstallStage::Complete => "Setup complete!",
@@ -130,6 +136,8 @@ impl InstallStage {
             InstallStage::FetchingConfiguration => "📥",
             InstallStage::SettingUpDirectories => "📁",
             InstallStage::CloningRepository => "📦",
+            InstallStage::UsingLocalCheckout => "📂",
+            InstallStage::ResumingSetup => "🔁",
             InstallStage::CreatingSymlinks => "🔗",
             InstallStage::FinalizeSetup => "⚙️",
             InstallStage::Complete => "✨",
@@ -5,6 +5,7 @@ pub mod formatter;
 pub mod icons;
 pub mod interruption;
 pub mod logo;
+pub mod path_display;
 pub mod spinner;
 pub mod theme;
 
@@ -13,5 +14,6 @@ pub use formatter::*;
 pub use icons::*;
 pub use interruption::*;
 pub use logo::*;
+pub use path_display::*;
 pub use spinner::*;
 pub use theme::*;
@@ -0,0 +1,133 @@
+//! Consistent rendering of file paths in command output
+
+use dialoguer::console::Term;
+
+/// Marks the part left out of a shortened path
+const ELLIPSIS: &str = "…";
+
+/// Renders paths for people: the home directory as `~`, repository files relative to
+/// the checkout, and paths too long for the terminal shortened in the middle. Machine
+/// output (JSON, porcelain) uses [`PathDisplay::full`] so paths stay absolute.
+#[derive(Debug, Clone, Default)]
+pub struct PathDisplay {
+    home: Option<String>,
+    repo: Option<String>,
+    max_width: Option<usize>,
+}
+
+impl PathDisplay {
+    /// Shorten home and fit paths into half of the terminal, if stdout is one
+    pub fn new() -> Self {
+        Self {
+            home: dirs::home_dir().map(|home| home.to_string_lossy().to_string()),
+            repo: None,
+            max_width: Term::stdout()
+                .size_checked()
+                .map(|(_, columns)| usize::from(columns) / 2),
+        }
+    }
+
+    /// Render every path as given
+    pub fn full() -> Self {
+        Self::default()
+    }
+
+    /// Also show paths inside `repo_path` relative to it
+    pub fn with_repo(mut self, repo_path: &str) -> Self {
+        self.repo = Some(repo_path.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Render `home` as `~` instead of the user's home directory
+    pub fn with_home(mut self, home: Option<&str>) -> Self {
+        self.home = home.map(|home| home.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Shorten paths longer than `width` characters; `None` never shortens
+    pub fn with_width(mut self, width: Option<usize>) -> Self {
+        self.max_width = width;
+        self
+    }
+
+    /// A path on the machine, with the home directory as `~`
+    pub fn path(&self, path: &str) -> String {
+        let shown = match self.home.as_deref().and_then(|home| strip_dir(path, home)) {
+            Some("") => "~".to_string(),
+            Some(rest) => format!("~/{}", rest),
+            None => path.to_string(),
+        };
+        self.fit(shown)
+    }
+
+    /// A path that usually lies in the repository: relative to the checkout when it
+    /// does, otherwise like [`PathDisplay::path`]
+    pub fn source(&self, path: &str) -> String {
+        match self.repo.as_deref().and_then(|repo| strip_dir(path, repo)) {
+            Some(rest) if !rest.is_empty() => self.fit(rest.to_string()),
+            _ => self.path(path),
+        }
+    }
+
+    fn fit(&self, shown: String) -> String {
+        let Some(max_width) = self.max_width else {
+            return shown;
+        };
+        let length = shown.chars().count();
+        if length <= max_width {
+            return shown;
+        }
+
+        // Keep the file name whole and as much of the start as still fits
+        let name_start = shown.rfind('/').map_or(0, |slash| slash + 1);
+        let name_length = shown[name_start..].chars().count();
+        let head_length = max_width.saturating_sub(name_length + ELLIPSIS.chars().count() + 1);
+        if head_length == 0 {
+            return shown;
+        }
+        let head: String = shown.chars().take(head_length).collect();
+        format!("{}{}/{}", head, ELLIPSIS, &shown[name_start..])
+    }
+}
+
+/// `path` below `dir` without the separating slash, `""` for `dir` itself
+fn strip_dir<'a>(path: &'a str, dir: &str) -> Option<&'a str> {
+    match path.strip_prefix(dir)? {
+        "" => Some(""),
+        rest => rest.strip_prefix('/'),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_display() {
+        let display = PathDisplay::full()
+            .with_home(Some("/home/user"))
+            .with_repo("/home/user/.dotf/repo");
+
+        assert_eq!(display.path("/home/user/.zshrc"), "~/.zshrc");
+        assert_eq!(display.path("/home/user"), "~");
+        // Only a whole leading directory is replaced
+        assert_eq!(display.path("/home/user2/.zshrc"), "/home/user2/.zshrc");
+        assert_eq!(display.path("/etc/home/user/x"), "/etc/home/user/x");
+        assert_eq!(
+            display.source("/home/user/.dotf/repo/zsh/.zshrc"),
+            "zsh/.zshrc"
+        );
+        assert_eq!(display.source("/home/user/other/.zshrc"), "~/other/.zshrc");
+        assert_eq!(
+            PathDisplay::full().path("/home/user/.zshrc"),
+            "/home/user/.zshrc"
+        );
+
+        let narrow = display.with_width(Some(20));
+        assert_eq!(narrow.path("/home/user/.zshrc"), "~/.zshrc");
+        assert_eq!(
+            narrow.path("/home/user/.config/nvim/lua/plugins/init.lua"),
+            "~/.config/…/init.lua"
+        );
+    }
+}
@@ -1,39 +1,79 @@
 //! Beautiful spinner and progress indicators
 
 use crate::cli::ui::{Icons, Theme};
+use crate::traits::progress::{LinkProgress, ProgressEvent, ProgressReporter};
 use indicatif::{ProgressBar, ProgressState, ProgressStyle};
 use std::fmt::Write;
+use std::io::IsTerminal;
 use std::time::Duration;
 
-/// A beautiful spinner for long-running operations
+/// Where progress goes as plain lines when there is no terminal to draw on
+#[derive(Clone)]
+enum PlainOutput {
+    Stderr,
+    #[cfg(test)]
+    Lines(std::sync::Arc<std::sync::Mutex<Vec<String>>>),
+}
+
+impl PlainOutput {
+    /// Plain lines on stderr unless it is a terminal
+    fn detect() -> Option<Self> {
+        (!std::io::stderr().is_terminal()).then_some(PlainOutput::Stderr)
+    }
+
+    fn line(&self, line: &str) {
+        match self {
+            PlainOutput::Stderr => eprintln!("{}", line),
+            #[cfg(test)]
+            PlainOutput::Lines(lines) => lines.lock().unwrap().push(line.to_string()),
+        }
+    }
+}
+
+/// A beautiful spinner for long-running operations. Without a terminal to draw on, it
+/// prints each update as a line of its own instead.
 pub struct Spinner {
     bar: ProgressBar,
     theme: Theme,
+    plain: Option<PlainOutput>,
 }
 
 impl Spinner {
     /// Create a new spinner with a message
     pub fn new(message: &str) -> Self {
-        let theme = Theme::new();
-        let bar = ProgressBar::new_spinner();
-
-        bar.set_style(
-            ProgressStyle::with_template(&format!(
-                "{} {{spinner:.cyan}} {}",
-                Icons::GEAR,
-                theme.primary(message)
-            ))
-            .unwrap()
-            .tick_strings(Icons::SPINNER_FRAMES),
-        );
-
-        bar.enable_steady_tick(Duration::from_millis(80));
+        Self::create(None, PlainOutput::detect(), message)
+    }
 
-        Self { bar, theme }
+    /// A spinner on a line of its own below those already in `multi`, or writing plain
+    /// lines to `plain`
+    fn create(
+        multi: Option<&indicatif::MultiProgress>,
+        plain: Option<PlainOutput>,
+        message: &str,
+    ) -> Self {
+        let bar = match (&plain, multi) {
+            (Some(_), _) => ProgressBar::hidden(),
+            (None, Some(multi)) => multi.add(ProgressBar::new_spinner()),
+            (None, None) => ProgressBar::new_spinner(),
+        };
+        let spinner = Self {
+            bar,
+            theme: Theme::new(),
+            plain,
+        };
+        spinner.set_message(message);
+        if spinner.plain.is_none() {
+            spinner.bar.enable_steady_tick(Duration::from_millis(80));
+        }
+        spinner
     }
 
     /// Update the spinner message
     pub fn set_message(&self, message: &str) {
+        if let Some(plain) = &self.plain {
+            plain.line(&format!("{} {}", Icons::GEAR, message));
+            return;
+        }
         self.bar.set_style(
             ProgressStyle::with_template(&format!(
                 "{} {{spinner:.cyan}} {}",
@@ -47,7 +87,7 @@ impl Spinner {
 
     /// Finish the spinner with a success message
     pub fn finish_with_success(&self, message: &str) {
-        self.bar.finish_with_message(format!(
+        self.finish_with(format!(
             "{} {}",
             Icons::SUCCESS,
             self.theme.success(message)
@@ -56,13 +96,12 @@ impl Spinner {
 
     /// Finish the spinner with an error message
     pub fn finish_with_error(&self, message: &str) {
-        self.bar
-            .finish_with_message(format!("{} {}", Icons::ERROR, self.theme.error(message)));
+        self.finish_with(format!("{} {}", Icons::ERROR, self.theme.error(message)));
     }
 
     /// Finish the spinner with a warning message
     pub fn finish_with_warning(&self, message: &str) {
-        self.bar.finish_with_message(format!(
+        self.finish_with(format!(
             "{} {}",
             Icons::WARNING,
             self.theme.warning(message)
@@ -73,6 +112,91 @@ impl Spinner {
     pub fn finish_and_clear(&self) {
         self.bar.finish_and_clear();
     }
+
+    /// Replace the spinner with `line`, which stays where the spinner was
+    fn finish_with(&self, line: String) {
+        if let Some(plain) = &self.plain {
+            plain.line(&line);
+            return;
+        }
+        self.bar
+            .set_style(ProgressStyle::with_template("{msg}").unwrap());
+        self.bar.finish_with_message(line);
+    }
+
+    /// Turn the spinner into a bar counting links; the next stage turns it back
+    fn show_links(&self, progress: &LinkProgress) {
+        if let Some(plain) = &self.plain {
+            // A line per link would drown the rest; only the final count is printed
+            if progress.done >= progress.total {
+                plain.line(&format!(
+                    "{} {}/{} links ({})",
+                    Icons::LINK,
+                    progress.done,
+                    progress.total,
+                    progress
+                ));
+            }
+            return;
+        }
+        self.bar.set_style(
+            ProgressStyle::with_template(&format!(
+                "{} [{{wide_bar:.cyan/blue}}] {{pos}}/{{len}} {{msg}}",
+                Icons::LINK
+            ))
+            .unwrap()
+            .progress_chars(&format!(
+                "{}{}",
+                Icons::PROGRESS_FULL,
+                Icons::PROGRESS_EMPTY
+            )),
+        );
+        self.bar.set_length(progress.total as u64);
+        self.bar.set_position(progress.done as u64);
+        self.bar
+            .set_message(self.theme.muted(&progress.to_string()));
+    }
+
+    /// Print a line above the spinner without disturbing it
+    pub fn println(&self, line: &str) {
+        if let Some(plain) = &self.plain {
+            plain.line(line);
+            return;
+        }
+        self.bar.println(line);
+    }
+}
+
+impl ProgressReporter for Spinner {
+    fn report(&self, event: ProgressEvent) {
+        match event {
+            ProgressEvent::StageStarted(message) => self.set_message(&message),
+            ProgressEvent::ItemCompleted(message) => self.println(&format!(
+                "  {} {}",
+                self.theme.success(Icons::CHECKMARK),
+                self.theme.muted(&message)
+            )),
+            ProgressEvent::Warning(message) => self.println(&format!(
+                "  {} {}",
+                Icons::WARNING,
+                self.theme.warning(&message)
+            )),
+            ProgressEvent::Links(progress) => self.show_links(&progress),
+        }
+    }
+
+    fn pause(&self) {
+        if self.plain.is_none() {
+            self.bar.finish_and_clear();
+        }
+    }
+
+    fn resume(&self) {
+        if self.plain.is_none() {
+            self.bar.reset();
+            self.bar.enable_steady_tick(Duration::from_millis(80));
+        }
+    }
 }
 
 /// A progress bar for operations with known progress
@@ -135,10 +259,13 @@ impl ProgressIndicator {
     }
 }
 
-/// Multi-progress manager for handling multiple progress bars
+/// Owns the terminal while several operations report progress at once. Each spinner
+/// gets a line of its own, in the order they were added, and keeps it when it finishes.
+/// Without a terminal, spinners print their updates as lines instead.
 pub struct MultiProgress {
     multi: indicatif::MultiProgress,
     theme: Theme,
+    plain: Option<PlainOutput>,
 }
 
 impl MultiProgress {
@@ -147,28 +274,16 @@ impl MultiProgress {
         Self {
             multi: indicatif::MultiProgress::new(),
             theme: Theme::new(),
+            plain: PlainOutput::detect(),
         }
     }
 
-    /// Add a spinner to the multi-progress
-    pub fn add_spinner(&self, message: &str) -> ProgressBar {
-        let bar = self.multi.add(ProgressBar::new_spinner());
-
-        bar.set_style(
-            ProgressStyle::with_template(&format!(
-                "{} {{spinner:.cyan}} {}",
-                Icons::GEAR,
-                self.theme.primary(message)
-            ))
-            .unwrap()
-            .tick_strings(Icons::SPINNER_FRAMES),
-        );
-
-        bar.enable_steady_tick(Duration::from_millis(80));
-        bar
+    /// Add a spinner below the existing lines
+    pub fn add_spinner(&self, message: &str) -> Spinner {
+        Spinner::create(Some(&self.multi), self.plain.clone(), message)
     }
 
-    /// Add a progress bar to the multi-progress
+    /// Add a progress bar below the existing lines
     pub fn add_progress(&self, total: u64, message: &str) -> ProgressBar {
         let bar = self.multi.add(ProgressBar::new(total));
 
@@ -185,6 +300,17 @@ impl MultiProgress {
         bar
     }
 
+    /// Print a line above all progress lines
+    pub fn println(&self, line: &str) {
+        if let Some(plain) = &self.plain {
+            plain.line(line);
+        } else if self.multi.is_hidden() {
+            eprintln!("{}", line);
+        } else {
+            let _ = self.multi.println(line);
+        }
+    }
+
     /// Clear all progress bars
     pub fn clear(&self) -> std::io::Result<()> {
         self.multi.clear()
@@ -196,3 +322,70 @@ impl Default for MultiProgress {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// `line` without its color codes
+    fn plain_text(line: &str) -> String {
+        let mut text = String::new();
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                text.push(c);
+            }
+        }
+        text
+    }
+
+    #[test]
+    fn test_plain_output_keeps_the_order_of_events() {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let multi = MultiProgress {
+            multi: indicatif::MultiProgress::new(),
+            theme: Theme::new(),
+            plain: Some(PlainOutput::Lines(lines.clone())),
+        };
+
+        let install = multi.add_spinner("Installing configuration symlinks");
+        let sync = multi.add_spinner("Syncing repository");
+        for done in 1..=2 {
+            install.report(ProgressEvent::Links(LinkProgress {
+                done,
+                total: 2,
+                created: done,
+                ..Default::default()
+            }));
+        }
+        sync.report(ProgressEvent::ItemCompleted("Pulled 3 commits".to_string()));
+        sync.finish_with_success("Repository up to date");
+        multi.println("Both done");
+        install.finish_with_warning("1 link skipped");
+
+        let lines: Vec<String> = lines
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|l| plain_text(l))
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!("{} Installing configuration symlinks", Icons::GEAR),
+                format!("{} Syncing repository", Icons::GEAR),
+                format!(
+                    "{} 2/2 links (2 created, 0 skipped, 0 conflicts)",
+                    Icons::LINK
+                ),
+                format!("  {} Pulled 3 commits", Icons::CHECKMARK),
+                format!("{} Repository up to date", Icons::SUCCESS),
+                "Both done".to_string(),
+                format!("{} 1 link skipped", Icons::WARNING),
+            ]
+        );
+    }
+}
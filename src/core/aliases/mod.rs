@@ -0,0 +1,248 @@
+use std::collections::BTreeMap;
+
+use crate::error::{DotfError, DotfResult};
+
+/// Separates the commands of a chained alias such as `up = "sync && install config"`
+pub const CHAIN_SEPARATOR: &str = "&&";
+
+/// Global options, which may come before the command: `dotf -v up`
+#[derive(Debug, Clone, Default)]
+pub struct GlobalArgs {
+    /// Spellings of options that take no value, such as `--yes` and `-y`
+    pub switches: Vec<String>,
+    /// Spellings of options followed by a value, such as `--on-conflict`
+    pub options: Vec<String>,
+}
+
+impl GlobalArgs {
+    /// Index of the first argument that is not a global option, i.e. the command name
+    fn command_index(&self, args: &[String]) -> usize {
+        let mut index = 0;
+        while let Some(arg) = args.get(index) {
+            if self.options.contains(arg) {
+                index += 2;
+            } else if self.switches.contains(arg)
+                || arg
+                    .split_once('=')
+                    .is_some_and(|(option, _)| self.options.iter().any(|o| o == option))
+                || self.is_switch_cluster(arg)
+            {
+                index += 1;
+            } else {
+                break;
+            }
+        }
+        index
+    }
+
+    /// Short switches written together, such as `-vv` or `-vy`
+    fn is_switch_cluster(&self, arg: &str) -> bool {
+        match arg.strip_prefix('-') {
+            Some(shorts) if !shorts.is_empty() && !shorts.starts_with('-') => shorts
+                .chars()
+                .all(|short| self.switches.contains(&format!("-{}", short))),
+            _ => false,
+        }
+    }
+}
+
+/// Expand a user alias at the start of `args` (the arguments after the program name).
+///
+/// Returns the command lines to run in order; a chained alias yields several, anything
+/// that is not an alias yields `args` unchanged. Built-in commands always win over an
+/// alias of the same name. Global options before the alias are passed to every command
+/// of the chain, and arguments after it go to the last one.
+/// Alias values are split on whitespace.
+pub fn expand_aliases(
+    args: &[String],
+    aliases: &BTreeMap<String, String>,
+    builtins: &[&str],
+    globals: &GlobalArgs,
+) -> DotfResult<Vec<Vec<String>>> {
+    let position = globals.command_index(args);
+    let Some(name) = args.get(position) else {
+        return Ok(vec![args.to_vec()]);
+    };
+    if !is_alias(name, aliases, builtins) {
+        return Ok(vec![args.to_vec()]);
+    }
+
+    let (leading, trailing) = (&args[..position], &args[position + 1..]);
+    let mut commands: Vec<Vec<String>> = expand_alias(name, aliases, builtins, &mut Vec::new())?
+        .into_iter()
+        .map(|command| leading.iter().cloned().chain(command).collect())
+        .collect();
+    if let Some(last) = commands.last_mut() {
+        last.extend(trailing.iter().cloned());
+    }
+    Ok(commands)
+}
+
+/// Aliases that can never run because a built-in command has the same name
+pub fn shadowed_aliases<'a>(
+    aliases: &'a BTreeMap<String, String>,
+    builtins: &[&str],
+) -> Vec<&'a str> {
+    aliases
+        .keys()
+        .map(String::as_str)
+        .filter(|name| builtins.contains(name))
+        .collect()
+}
+
+fn is_alias(name: &str, aliases: &BTreeMap<String, String>, builtins: &[&str]) -> bool {
+    aliases.contains_key(name) && !builtins.contains(&name)
+}
+
+fn expand_alias(
+    name: &str,
+    aliases: &BTreeMap<String, String>,
+    builtins: &[&str],
+    seen: &mut Vec<String>,
+) -> DotfResult<Vec<Vec<String>>> {
+    if seen.iter().any(|alias| alias == name) {
+        seen.push(name.to_string());
+        return Err(DotfError::Config(format!(
+            "Alias '{}' refers to itself: {}",
+            name,
+            seen.join(" → ")
+        )));
+    }
+    seen.push(name.to_string());
+
+    let value = &aliases[name];
+    let mut commands = Vec::new();
+    for segment in value.split(CHAIN_SEPARATOR) {
+        let words: Vec<String> = segment.split_whitespace().map(str::to_string).collect();
+        match words.first() {
+            None => {
+                return Err(DotfError::Config(format!(
+                    "Alias '{}' has an empty command: \"{}\"",
+                    name, value
+                )))
+            }
+            Some(first) if is_alias(first, aliases, builtins) => {
+                let mut expanded = expand_alias(first, aliases, builtins, seen)?;
+                if let Some(last) = expanded.last_mut() {
+                    last.extend(words[1..].iter().cloned());
+                }
+                commands.extend(expanded);
+            }
+            Some(_) => commands.push(words),
+        }
+    }
+
+    seen.pop();
+    Ok(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BUILTINS: &[&str] = &["status", "sync", "install"];
+
+    fn aliases(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_expand_aliases() {
+        let aliases = aliases(&[
+            ("s", "status --quiet"),
+            ("up", "sync && install config"),
+            ("daily", "up && s"),
+            ("status", "sync"),
+        ]);
+
+        assert_eq!(
+            expand_aliases(
+                &args("s --since 1d"),
+                &aliases,
+                BUILTINS,
+                &GlobalArgs::default()
+            )
+            .unwrap(),
+            vec![args("status --quiet --since 1d")]
+        );
+        assert_eq!(
+            expand_aliases(&args("daily"), &aliases, BUILTINS, &GlobalArgs::default()).unwrap(),
+            vec![args("sync"), args("install config"), args("status --quiet")]
+        );
+        // Built-ins are never replaced, and unknown commands are left to clap
+        assert_eq!(
+            expand_aliases(&args("status"), &aliases, BUILTINS, &GlobalArgs::default()).unwrap(),
+            vec![args("status")]
+        );
+        assert_eq!(
+            expand_aliases(&args("nope"), &aliases, BUILTINS, &GlobalArgs::default()).unwrap(),
+            vec![args("nope")]
+        );
+        assert_eq!(shadowed_aliases(&aliases, BUILTINS), vec!["status"]);
+    }
+
+    #[test]
+    fn test_recursive_alias() {
+        let aliases = aliases(&[("a", "b --x"), ("b", "sync && a")]);
+
+        let error =
+            expand_aliases(&args("a"), &aliases, BUILTINS, &GlobalArgs::default()).unwrap_err();
+        assert!(error.to_string().contains("a → b → a"));
+    }
+
+    #[test]
+    fn test_alias_after_global_options() {
+        let aliases = aliases(&[("up", "sync && install config")]);
+        let globals = GlobalArgs {
+            switches: args("--yes -y --verbose -v"),
+            options: args("--on-conflict"),
+        };
+
+        assert_eq!(
+            expand_aliases(&args("-v up --force"), &aliases, BUILTINS, &globals).unwrap(),
+            vec![args("-v sync"), args("-v install config --force")]
+        );
+        assert_eq!(
+            expand_aliases(
+                &args("--yes --on-conflict skip up"),
+                &aliases,
+                BUILTINS,
+                &globals
+            )
+            .unwrap(),
+            vec![
+                args("--yes --on-conflict skip sync"),
+                args("--yes --on-conflict skip install config")
+            ]
+        );
+        assert_eq!(
+            expand_aliases(
+                &args("-vy --on-conflict=skip up"),
+                &aliases,
+                BUILTINS,
+                &globals
+            )
+            .unwrap(),
+            vec![
+                args("-vy --on-conflict=skip sync"),
+                args("-vy --on-conflict=skip install config")
+            ]
+        );
+        // An option's value is never taken for the command
+        assert_eq!(
+            expand_aliases(&args("--on-conflict up"), &aliases, BUILTINS, &globals).unwrap(),
+            vec![args("--on-conflict up")]
+        );
+        assert_eq!(
+            expand_aliases(&args("-x up"), &aliases, BUILTINS, &globals).unwrap(),
+            vec![args("-x up")]
+        );
+    }
+}
@@ -0,0 +1,179 @@
+pub mod runner;
+
+pub use runner::{BootstrapOutcome, BootstrapRunner, BootstrapStatus};
+
+use std::path::{Path, PathBuf};
+
+use crate::core::config::dotf_config::{BootstrapConfig, PluginManager};
+use crate::error::{DotfError, DotfResult};
+
+const VIM_PLUG_URL: &str = "https://raw.githubusercontent.com/junegunn/vim-plug/master/plug.vim";
+const TPM_URL: &str = "https://github.com/tmux-plugins/tpm";
+
+/// Seconds a bootstrap step may run when `[bootstrap] timeout` is not set
+pub const DEFAULT_BOOTSTRAP_TIMEOUT: u64 = 300;
+
+/// One thing a bootstrap step does, in order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepAction {
+    /// Download `url` to `destination` unless the file already exists
+    Download {
+        url: String,
+        destination: PathBuf,
+    },
+    /// Shallow-clone `url` into `destination` unless it already exists
+    Clone {
+        url: String,
+        destination: PathBuf,
+    },
+    Run {
+        program: String,
+        args: Vec<String>,
+    },
+}
+
+/// Bootstrapping of one editor's or tool's plugin manager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BootstrapStep {
+    /// Display name, e.g. `nvim (lazy)`
+    pub name: String,
+    /// Program that must be on `PATH`; the step is skipped otherwise
+    pub requires: String,
+    pub actions: Vec<StepAction>,
+}
+
+fn run(program: &str, args: &[&str]) -> StepAction {
+    StepAction::Run {
+        program: program.to_string(),
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+    }
+}
+
+/// Turn `[bootstrap]` into steps. `data_home` is `$XDG_DATA_HOME` (or `~/.local/share`).
+pub fn plan_steps(
+    config: &BootstrapConfig,
+    home: &Path,
+    data_home: &Path,
+) -> DotfResult<Vec<BootstrapStep>> {
+    let mut steps = Vec::new();
+
+    if let Some(manager) = config.nvim {
+        let actions = match manager {
+            PluginManager::Lazy => vec![run("nvim", &["--headless", "+Lazy! sync", "+qa"])],
+            PluginManager::Packer => vec![run(
+                "nvim",
+                &[
+                    "--headless",
+                    "-c",
+                    "autocmd User PackerComplete quitall",
+                    "-c",
+                    "PackerSync",
+                ],
+            )],
+            PluginManager::VimPlug => vec![
+                StepAction::Download {
+                    url: VIM_PLUG_URL.to_string(),
+                    destination: data_home.join("nvim/site/autoload/plug.vim"),
+                },
+                run("nvim", &["--headless", "+PlugInstall --sync", "+qa"]),
+            ],
+            PluginManager::Tpm => return Err(unsupported("nvim", manager)),
+        };
+        steps.push(step("nvim", manager, actions));
+    }
+
+    if let Some(manager) = config.vim {
+        let actions = match manager {
+            PluginManager::VimPlug => vec![
+                StepAction::Download {
+                    url: VIM_PLUG_URL.to_string(),
+                    destination: home.join(".vim/autoload/plug.vim"),
+                },
+                run("vim", &["-E", "-s", "+PlugInstall --sync", "+qa"]),
+            ],
+            other => return Err(unsupported("vim", other)),
+        };
+        steps.push(step("vim", manager, actions));
+    }
+
+    if let Some(manager) = config.tmux {
+        let actions = match manager {
+            PluginManager::Tpm => {
+                let tpm = home.join(".tmux/plugins/tpm");
+                vec![
+                    StepAction::Clone {
+                        url: TPM_URL.to_string(),
+                        destination: tpm.clone(),
+                    },
+                    run(&tpm.join("bin/install_plugins").to_string_lossy(), &[]),
+                ]
+            }
+            other => return Err(unsupported("tmux", other)),
+        };
+        steps.push(step("tmux", manager, actions));
+    }
+
+    Ok(steps)
+}
+
+fn step(program: &str, manager: PluginManager, actions: Vec<StepAction>) -> BootstrapStep {
+    BootstrapStep {
+        name: format!("{} ({})", program, manager.as_str()),
+        requires: program.to_string(),
+        actions,
+    }
+}
+
+fn unsupported(program: &str, manager: PluginManager) -> DotfError {
+    DotfError::Config(format!(
+        "[bootstrap] {} does not support the '{}' plugin manager",
+        program,
+        manager.as_str()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_steps() {
+        let config = BootstrapConfig {
+            nvim: Some(PluginManager::Lazy),
+            vim: None,
+            tmux: Some(PluginManager::Tpm),
+            timeout: None,
+        };
+        let steps = plan_steps(
+            &config,
+            Path::new("/home/user"),
+            Path::new("/home/user/.local/share"),
+        )
+        .unwrap();
+
+        assert_eq!(steps.len(), 2);
+        assert_eq!(steps[0].name, "nvim (lazy)");
+        assert_eq!(
+            steps[0].actions,
+            vec![run("nvim", &["--headless", "+Lazy! sync", "+qa"])]
+        );
+        assert_eq!(steps[1].requires, "tmux");
+        assert_eq!(
+            steps[1].actions[0],
+            StepAction::Clone {
+                url: TPM_URL.to_string(),
+                destination: PathBuf::from("/home/user/.tmux/plugins/tpm"),
+            }
+        );
+    }
+
+    #[test]
+    fn test_plan_steps_rejects_mismatched_manager() {
+        let config = BootstrapConfig {
+            tmux: Some(PluginManager::Lazy),
+            ..Default::default()
+        };
+        let result = plan_steps(&config, Path::new("/home/user"), Path::new("/tmp"));
+        assert!(matches!(result, Err(DotfError::Config(_))));
+    }
+}
@@ -0,0 +1,177 @@
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::process::Command;
+
+use crate::core::bootstrap::{BootstrapStep, StepAction};
+use crate::error::{DotfError, DotfResult};
+use crate::utils::platform::find_in_path;
+
+/// Stderr lines kept in a failure message
+const ERROR_TAIL_LINES: usize = 5;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BootstrapStatus {
+    Completed,
+    /// The required program is not installed
+    Skipped(String),
+    Failed(String),
+    TimedOut,
+}
+
+#[derive(Debug, Clone)]
+pub struct BootstrapOutcome {
+    pub name: String,
+    pub status: BootstrapStatus,
+    pub duration: Duration,
+}
+
+/// Runs bootstrap steps, stopping any step that exceeds the timeout
+pub struct BootstrapRunner {
+    timeout: Duration,
+    client: reqwest::Client,
+}
+
+impl BootstrapRunner {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn run(&self, step: &BootstrapStep) -> BootstrapOutcome {
+        let started = Instant::now();
+
+        let status = if find_in_path(&step.requires).is_none() {
+            BootstrapStatus::Skipped(format!("{} is not installed", step.requires))
+        } else {
+            // Dropping the future kills any child process still running (kill_on_drop)
+            match tokio::time::timeout(self.timeout, self.run_actions(&step.actions)).await {
+                Ok(Ok(())) => BootstrapStatus::Completed,
+                Ok(Err(e)) => BootstrapStatus::Failed(e.to_string()),
+                Err(_) => BootstrapStatus::TimedOut,
+            }
+        };
+
+        BootstrapOutcome {
+            name: step.name.clone(),
+            status,
+            duration: started.elapsed(),
+        }
+    }
+
+    async fn run_actions(&self, actions: &[StepAction]) -> DotfResult<()> {
+        for action in actions {
+            match action {
+                StepAction::Download { url, destination } => {
+                    if destination.exists() {
+                        continue;
+                    }
+                    if let Some(parent) = destination.parent() {
+                        tokio::fs::create_dir_all(parent)
+                            .await
+                            .map_err(DotfError::Io)?;
+                    }
+                    let bytes = self
+                        .client
+                        .get(url)
+                        .send()
+                        .await?
+                        .error_for_status()?
+                        .bytes()
+                        .await?;
+                    tokio::fs::write(destination, &bytes)
+                        .await
+                        .map_err(DotfError::Io)?;
+                }
+                StepAction::Clone { url, destination } => {
+                    if destination.exists() {
+                        continue;
+                    }
+                    let destination = destination.to_string_lossy().to_string();
+                    run_command("git", &["clone", "--depth", "1", url, &destination]).await?;
+                }
+                StepAction::Run { program, args } => {
+                    let args: Vec<&str> = args.iter().map(|arg| arg.as_str()).collect();
+                    run_command(program, &args).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn run_command(program: &str, args: &[&str]) -> DotfResult<()> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output()
+        .await
+        .map_err(|e| DotfError::ScriptExecution(format!("Failed to run {}: {}", program, e)))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let lines: Vec<&str> = stderr.lines().filter(|l| !l.trim().is_empty()).collect();
+    let tail = lines[lines.len().saturating_sub(ERROR_TAIL_LINES)..].join("\n");
+
+    Err(DotfError::ScriptExecution(format!(
+        "{} exited with code {}{}",
+        program,
+        output.status.code().unwrap_or(-1),
+        if tail.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", tail)
+        }
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shell_step(script: &str) -> BootstrapStep {
+        BootstrapStep {
+            name: "test".to_string(),
+            requires: "sh".to_string(),
+            actions: vec![StepAction::Run {
+                program: "sh".to_string(),
+                args: vec!["-c".to_string(), script.to_string()],
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_reports_failure_and_timeout() {
+        let runner = BootstrapRunner::new(Duration::from_millis(200));
+
+        let completed = runner.run(&shell_step("true")).await;
+        assert_eq!(completed.status, BootstrapStatus::Completed);
+
+        let failed = runner.run(&shell_step("echo broken >&2; exit 3")).await;
+        assert_eq!(
+            failed.status,
+            BootstrapStatus::Failed(
+                "Script execution failed: sh exited with code 3: broken".to_string()
+            )
+        );
+
+        let timed_out = runner.run(&shell_step("sleep 5")).await;
+        assert_eq!(timed_out.status, BootstrapStatus::TimedOut);
+        assert!(timed_out.duration < Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_run_skips_missing_program() {
+        let runner = BootstrapRunner::new(Duration::from_secs(1));
+        let mut step = shell_step("true");
+        step.requires = "definitely-not-installed-dotf".to_string();
+
+        let outcome = runner.run(&step).await;
+        assert!(matches!(outcome.status, BootstrapStatus::Skipped(_)));
+    }
+}
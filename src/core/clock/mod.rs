@@ -0,0 +1,3 @@
+pub mod system;
+
+pub use system::{RandomIdGenerator, SystemClock};
@@ -0,0 +1,62 @@
+use chrono::{DateTime, Utc};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::traits::clock::{Clock, IdGenerator};
+
+#[derive(Clone, Default)]
+pub struct SystemClock;
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Generates 8 hex character ids from a randomly seeded hasher.
+///
+/// Mixing in a process-wide counter keeps ids unique within one run even
+/// when the clock does not advance between calls.
+#[derive(Default)]
+pub struct RandomIdGenerator {
+    state: RandomState,
+}
+
+impl RandomIdGenerator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl IdGenerator for RandomIdGenerator {
+    fn next_id(&self) -> String {
+        let mut hasher = self.state.build_hasher();
+        hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+        hasher.write_u32(std::process::id());
+        hasher.write_i64(Utc::now().timestamp_nanos_opt().unwrap_or_default());
+        format!("{:08x}", hasher.finish() as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_random_ids_are_unique() {
+        let ids = RandomIdGenerator::new();
+        let generated: HashSet<String> = (0..1000).map(|_| ids.next_id()).collect();
+        assert_eq!(generated.len(), 1000);
+        assert!(generated.iter().all(|id| id.len() == 8));
+    }
+}
@@ -0,0 +1,164 @@
+use std::path::Path;
+
+use clap_complete::Shell;
+
+use crate::error::{DotfError, DotfResult};
+
+/// Where a shell loads completions from, and what its rc file needs for that to happen
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionLocation {
+    /// The completion file to write
+    pub file: String,
+    /// The rc file that has to load `file`, when the shell does not pick it up on its own
+    pub rc_file: Option<String>,
+    /// The line to add to `rc_file`
+    pub rc_line: Option<String>,
+}
+
+/// The conventional completion location for `shell` under `home`.
+/// `config_home` and `data_home` are `$XDG_CONFIG_HOME` and `$XDG_DATA_HOME` when set.
+pub fn location(
+    shell: Shell,
+    home: &Path,
+    config_home: Option<&Path>,
+    data_home: Option<&Path>,
+) -> DotfResult<CompletionLocation> {
+    let home_path = |relative: &str| home.join(relative).to_string_lossy().to_string();
+
+    match shell {
+        Shell::Zsh => Ok(CompletionLocation {
+            file: home_path(".zsh/completions/_dotf"),
+            rc_file: Some(home_path(".zshrc")),
+            rc_line: Some("fpath=(~/.zsh/completions $fpath)".to_string()),
+        }),
+        Shell::Fish => {
+            let config_home = config_home.map_or_else(|| home.join(".config"), Path::to_path_buf);
+            Ok(CompletionLocation {
+                file: config_home
+                    .join("fish/completions/dotf.fish")
+                    .to_string_lossy()
+                    .to_string(),
+                rc_file: None,
+                rc_line: None,
+            })
+        }
+        Shell::Bash => {
+            let data_home =
+                data_home.map_or_else(|| home.join(".local/share"), Path::to_path_buf);
+            let file = data_home
+                .join("bash-completion/completions/dotf")
+                .to_string_lossy()
+                .to_string();
+            // bash-completion loads this directory on demand; the source line covers shells without it
+            Ok(CompletionLocation {
+                rc_file: Some(home_path(".bashrc")),
+                rc_line: Some(format!("[ -f \"{}\" ] && . \"{}\"", file, file)),
+                file,
+            })
+        }
+        other => Err(DotfError::UnsupportedPlatform(format!(
+            "Installing completions for {} is not supported; redirect `dotf completions {}` to a file instead",
+            other, other
+        ))),
+    }
+}
+
+/// `rc_content` with `line` added, or `None` when it is already there.
+/// For zsh the `fpath` line has to come before `compinit`, so it is inserted above the first
+/// `compinit` call, or appended together with one when the file has none.
+pub fn add_rc_line(shell: Shell, rc_content: &str, line: &str) -> Option<String> {
+    if rc_content.lines().any(|existing| existing.trim() == line) {
+        return None;
+    }
+
+    let comment = "# dotf completions";
+    if shell == Shell::Zsh {
+        let mut lines: Vec<&str> = rc_content.lines().collect();
+        match lines.iter().position(|existing| {
+            existing.contains("compinit") && !existing.trim_start().starts_with('#')
+        }) {
+            Some(index) => {
+                lines.splice(index..index, [comment, line]);
+                return Some(format!("{}\n", lines.join("\n")));
+            }
+            None => {
+                return Some(format!(
+                    "{}{}{}\n{}\nautoload -Uz compinit && compinit\n",
+                    rc_content,
+                    separator(rc_content),
+                    comment,
+                    line
+                ));
+            }
+        }
+    }
+
+    Some(format!(
+        "{}{}{}\n{}\n",
+        rc_content,
+        separator(rc_content),
+        comment,
+        line
+    ))
+}
+
+/// What to put between existing rc content and an appended block
+fn separator(content: &str) -> &'static str {
+    match content {
+        "" => "",
+        content if content.ends_with('\n') => "\n",
+        _ => "\n\n",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_location() {
+        let home = Path::new("/home/user");
+
+        let zsh = location(Shell::Zsh, home, None, None).unwrap();
+        assert_eq!(zsh.file, "/home/user/.zsh/completions/_dotf");
+        assert_eq!(zsh.rc_file.as_deref(), Some("/home/user/.zshrc"));
+
+        let fish = location(Shell::Fish, home, Some(Path::new("/xdg/config")), None).unwrap();
+        assert_eq!(fish.file, "/xdg/config/fish/completions/dotf.fish");
+        assert!(fish.rc_line.is_none());
+
+        let bash = location(Shell::Bash, home, None, None).unwrap();
+        assert_eq!(
+            bash.file,
+            "/home/user/.local/share/bash-completion/completions/dotf"
+        );
+
+        assert!(location(Shell::PowerShell, home, None, None).is_err());
+    }
+
+    #[test]
+    fn test_add_rc_line() {
+        let line = "fpath=(~/.zsh/completions $fpath)";
+
+        let updated = add_rc_line(
+            Shell::Zsh,
+            "export A=1\nautoload -Uz compinit\ncompinit\n",
+            line,
+        )
+        .unwrap();
+        assert_eq!(
+            updated,
+            format!(
+                "export A=1\n# dotf completions\n{}\nautoload -Uz compinit\ncompinit\n",
+                line
+            )
+        );
+        assert!(add_rc_line(Shell::Zsh, &updated, line).is_none());
+
+        let appended = add_rc_line(Shell::Zsh, "export A=1\n", line).unwrap();
+        assert!(appended.ends_with("autoload -Uz compinit && compinit\n"));
+
+        let bash = add_rc_line(Shell::Bash, "alias ll='ls -l'", ". x").unwrap();
+        assert_eq!(bash, "alias ll='ls -l'\n\n# dotf completions\n. x\n");
+    }
+}
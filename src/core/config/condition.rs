@@ -0,0 +1,128 @@
+//! Conditions on symlink entries (`when = { hostname = "work-laptop" }`), so machines that
+//! differ slightly can share one branch
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::utils::platform::hostname;
+
+/// When a symlink entry applies. Every key given must hold; an empty condition always does.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Condition {
+    /// Name of the machine, with or without its domain; case does not matter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// CPU architecture, e.g. `x86_64` or `aarch64` (`amd64` and `arm64` work too)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    /// `NAME` for a variable that is set and not empty, `NAME=value` for a value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env: Option<String>,
+}
+
+/// What conditions are checked against
+#[derive(Debug, Clone)]
+pub struct Machine {
+    pub hostname: String,
+    pub arch: String,
+    pub env: HashMap<String, String>,
+}
+
+impl Machine {
+    /// The machine dotf runs on
+    pub fn current() -> Self {
+        Self {
+            hostname: hostname(),
+            arch: std::env::consts::ARCH.to_string(),
+            env: std::env::vars().collect(),
+        }
+    }
+}
+
+impl Condition {
+    pub fn holds(&self, machine: &Machine) -> bool {
+        let hostname = self.hostname.as_deref().is_none_or(|wanted| {
+            let actual = machine.hostname.to_lowercase();
+            let wanted = wanted.to_lowercase();
+            actual == wanted || actual.split('.').next() == Some(wanted.as_str())
+        });
+        let arch = self
+            .arch
+            .as_deref()
+            .is_none_or(|wanted| canonical_arch(wanted) == canonical_arch(&machine.arch));
+        let env = self
+            .env
+            .as_deref()
+            .is_none_or(|wanted| match wanted.split_once('=') {
+                Some((name, value)) => machine.env.get(name).map(String::as_str) == Some(value),
+                None => machine
+                    .env
+                    .get(wanted)
+                    .is_some_and(|value| !value.is_empty()),
+            });
+        hostname && arch && env
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let keys: Vec<String> = [
+            ("hostname", &self.hostname),
+            ("arch", &self.arch),
+            ("env", &self.env),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| value.as_ref().map(|value| format!("{} = {}", key, value)))
+        .collect();
+        write!(f, "{}", keys.join(", "))
+    }
+}
+
+/// The name Rust uses for an architecture also known by another
+fn canonical_arch(arch: &str) -> String {
+    match arch.to_lowercase().as_str() {
+        "amd64" | "x64" => "x86_64".to_string(),
+        "arm64" => "aarch64".to_string(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn machine() -> Machine {
+        Machine {
+            hostname: "Work-Laptop.corp.example.com".to_string(),
+            arch: "aarch64".to_string(),
+            env: HashMap::from([
+                ("WSL_DISTRO_NAME".to_string(), "Ubuntu".to_string()),
+                ("EMPTY".to_string(), String::new()),
+            ]),
+        }
+    }
+
+    fn condition(toml: &str) -> Condition {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_condition_holds() {
+        let machine = machine();
+        assert!(Condition::default().holds(&machine));
+        assert!(condition("hostname = \"work-laptop\"").holds(&machine));
+        assert!(condition("hostname = \"work-laptop.corp.example.com\"").holds(&machine));
+        assert!(!condition("hostname = \"home\"").holds(&machine));
+        assert!(condition("arch = \"arm64\"").holds(&machine));
+        assert!(!condition("arch = \"x86_64\"").holds(&machine));
+        assert!(condition("env = \"WSL_DISTRO_NAME\"").holds(&machine));
+        assert!(condition("env = \"WSL_DISTRO_NAME=Ubuntu\"").holds(&machine));
+        assert!(!condition("env = \"WSL_DISTRO_NAME=Debian\"").holds(&machine));
+        assert!(!condition("env = \"EMPTY\"").holds(&machine));
+        assert!(!condition("hostname = \"work-laptop\"\narch = \"x86_64\"").holds(&machine));
+
+        assert!(toml::from_str::<Condition>("host = \"work-laptop\"").is_err());
+    }
+}
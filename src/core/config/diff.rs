@@ -0,0 +1,268 @@
+use std::collections::BTreeMap;
+
+use super::dotf_config::{DotfConfig, SymlinkMap};
+
+/// One entry that differs between two configurations
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryChange {
+    pub key: String,
+    /// Value on the old side, `None` if the entry was added
+    pub from: Option<String>,
+    /// Value on the new side, `None` if the entry was removed
+    pub to: Option<String>,
+}
+
+/// Changed entries of one section of dotf.toml, e.g. `symlinks` or `platform.linux`
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionDiff {
+    pub section: String,
+    pub changes: Vec<EntryChange>,
+}
+
+/// Compare two configurations entry by entry. Only sections with changes are listed:
+/// symlinks, scripts and platform sections first, the rest alphabetically.
+pub fn diff_configs(old: &DotfConfig, new: &DotfConfig) -> Vec<SectionDiff> {
+    let old_sections = sections(old);
+    let mut new_sections = sections(new);
+
+    let mut diffs = Vec::new();
+    for (section, old_entries) in old_sections {
+        let new_entries = new_sections.remove(&section).unwrap_or_default();
+        push_diff(&mut diffs, section, &old_entries, &new_entries);
+    }
+    for (section, new_entries) in new_sections {
+        push_diff(&mut diffs, section, &BTreeMap::new(), &new_entries);
+    }
+
+    diffs.sort_by_key(|diff| section_order(&diff.section));
+    diffs
+}
+
+fn push_diff(
+    diffs: &mut Vec<SectionDiff>,
+    section: String,
+    old: &BTreeMap<String, String>,
+    new: &BTreeMap<String, String>,
+) {
+    let mut changes = Vec::new();
+    for (key, from) in old {
+        match new.get(key) {
+            Some(to) if to == from => {}
+            to => changes.push(EntryChange {
+                key: key.clone(),
+                from: Some(from.clone()),
+                to: to.cloned(),
+            }),
+        }
+    }
+    for (key, to) in new {
+        if !old.contains_key(key) {
+            changes.push(EntryChange {
+                key: key.clone(),
+                from: None,
+                to: Some(to.clone()),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.key.cmp(&b.key));
+
+    if !changes.is_empty() {
+        diffs.push(SectionDiff { section, changes });
+    }
+}
+
+/// Every section of `config` flattened to `key → value`. Symlinks, scripts and platform
+/// sections are split per entry; other sections per top-level key, with the value in
+/// inline TOML.
+fn sections(config: &DotfConfig) -> BTreeMap<String, BTreeMap<String, String>> {
+    let mut sections = BTreeMap::new();
+    sections.insert("symlinks".to_string(), symlink_entries(&config.symlinks));
+
+    if let Some(macos) = &config.platform.macos {
+        sections.insert(
+            "platform.macos".to_string(),
+            symlink_entries(&macos.symlinks),
+        );
+    }
+    if let Some(linux) = &config.platform.linux {
+        sections.insert(
+            "platform.linux".to_string(),
+            symlink_entries(&linux.symlinks),
+        );
+    }
+    if let Some(windows) = &config.platform.windows {
+        sections.insert(
+            "platform.windows".to_string(),
+            symlink_entries(&windows.symlinks),
+        );
+    }
+
+    let mut scripts = BTreeMap::new();
+    let deps = &config.scripts.deps;
+    for (key, script) in [("deps.macos", &deps.macos), ("deps.linux", &deps.linux)] {
+        if let Some(script) = script {
+            scripts.insert(key.to_string(), script.clone());
+        }
+    }
+    for (name, script) in &config.scripts.custom {
+        scripts.insert(format!("custom.{}", name), script.clone());
+    }
+    for (path, limits) in &config.scripts.limits {
+        if let Ok(value) = toml::Value::try_from(limits) {
+            scripts.insert(format!("limits.{}", path), inline(&value));
+        }
+    }
+    for (path, policy) in &config.scripts.run {
+        if let Ok(value) = toml::Value::try_from(policy) {
+            scripts.insert(format!("run.{}", path), inline(&value));
+        }
+    }
+    for (path, env) in &config.scripts.env {
+        if let Ok(value) = toml::Value::try_from(env) {
+            scripts.insert(format!("env.{}", path), inline(&value));
+        }
+    }
+    for (path, dir) in &config.scripts.working_dir {
+        scripts.insert(format!("working_dir.{}", path), dir.clone());
+    }
+    sections.insert("scripts".to_string(), scripts);
+
+    if let Ok(toml::Value::Table(table)) = toml::Value::try_from(config) {
+        for (section, value) in table {
+            if matches!(section.as_str(), "symlinks" | "scripts" | "platform") {
+                continue;
+            }
+            let entries = match value {
+                toml::Value::Table(entries) => entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), inline(value)))
+                    .collect(),
+                value => BTreeMap::from([(section.clone(), inline(&value))]),
+            };
+            sections.insert(section, entries);
+        }
+    }
+
+    sections
+}
+
+fn symlink_entries(symlinks: &SymlinkMap) -> BTreeMap<String, String> {
+    symlinks
+        .iter()
+        .map(|(source, target)| {
+            let mut target = if symlinks.is_optional(source) {
+                format!("{} (optional)", target)
+            } else {
+                target.clone()
+            };
+            if let Some(condition) = symlinks.condition(source) {
+                target = format!("{} (when {})", target, condition);
+            }
+            if let Some(group) = symlinks.group(source) {
+                target = format!("{} (group {})", target, group);
+            }
+            (source.clone(), target)
+        })
+        .collect()
+}
+
+/// A value on one line, tables written as inline tables
+fn inline(value: &toml::Value) -> String {
+    match value {
+        toml::Value::Table(table) if table.is_empty() => "{}".to_string(),
+        toml::Value::Table(table) => {
+            let fields: Vec<String> = table
+                .iter()
+                .map(|(key, value)| format!("{} = {}", key, inline(value)))
+                .collect();
+            format!("{{ {} }}", fields.join(", "))
+        }
+        toml::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(inline).collect();
+            format!("[{}]", items.join(", "))
+        }
+        value => value.to_string(),
+    }
+}
+
+fn section_order(section: &str) -> (usize, String) {
+    let rank = match section {
+        "symlinks" => 0,
+        "scripts" => 1,
+        s if s.starts_with("platform.") => 2,
+        _ => 3,
+    };
+    (rank, section.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(toml: &str) -> DotfConfig {
+        toml::from_str(toml).unwrap()
+    }
+
+    #[test]
+    fn test_diff_configs() {
+        let old = config(
+            r#"
+[symlinks]
+"zsh/.zshrc" = "~/.zshrc"
+"vim/.vimrc" = "~/.vimrc"
+
+[scripts.deps]
+linux = "scripts/deps-linux.sh"
+
+[cron]
+backup = "@daily backup.sh"
+"#,
+        );
+        let new = config(
+            r#"
+[symlinks]
+"zsh/.zshrc" = "~/.config/zsh/.zshrc"
+"git/.gitconfig" = "~/.gitconfig"
+
+[scripts.deps]
+linux = "scripts/deps-linux.sh"
+
+[scripts.custom]
+fonts = "scripts/fonts.sh"
+
+[platform.linux.symlinks]
+"i3/config" = "~/.config/i3/config"
+
+[cron]
+backup = "@daily backup.sh"
+"#,
+        );
+
+        let diffs = diff_configs(&old, &new);
+        let sections: Vec<&str> = diffs.iter().map(|diff| diff.section.as_str()).collect();
+        assert_eq!(sections, vec!["symlinks", "scripts", "platform.linux"]);
+
+        assert_eq!(
+            diffs[0].changes,
+            vec![
+                EntryChange {
+                    key: "git/.gitconfig".to_string(),
+                    from: None,
+                    to: Some("~/.gitconfig".to_string()),
+                },
+                EntryChange {
+                    key: "vim/.vimrc".to_string(),
+                    from: Some("~/.vimrc".to_string()),
+                    to: None,
+                },
+                EntryChange {
+                    key: "zsh/.zshrc".to_string(),
+                    from: Some("~/.zshrc".to_string()),
+                    to: Some("~/.config/zsh/.zshrc".to_string()),
+                },
+            ]
+        );
+        assert_eq!(diffs[1].changes[0].key, "custom.fonts");
+        assert!(diff_configs(&new, &new).is_empty());
+    }
+}
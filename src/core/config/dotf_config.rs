@@ -1,14 +1,193 @@
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use serde::{ser::SerializeMap, Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use super::condition::{Condition, Machine};
+use super::variables::expand_target;
+use crate::core::scripts::{HookEvent, HookFailure, RunPolicy, ScriptLimits};
+use crate::core::secrets::SecretBackend;
+use crate::core::symlinks::{
+    permissions::{format_chmod, parse_chmod},
+    LinkMode, Permissions,
+};
+use crate::error::{DotfError, DotfResult};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct DotfConfig {
     #[serde(default)]
-    pub symlinks: HashMap<String, String>,
+    pub symlinks: SymlinkMap,
     #[serde(default)]
     pub scripts: ScriptsConfig,
     #[serde(default)]
     pub platform: PlatformConfig,
+    #[serde(default)]
+    pub tools: HashMap<String, ToolConfig>,
+    #[serde(default)]
+    pub bootstrap: BootstrapConfig,
+    #[serde(default)]
+    pub macos: MacosConfig,
+    /// User crontab entries (`[cron]`), keyed by a name shown as a comment in the crontab
+    #[serde(default)]
+    pub cron: BTreeMap<String, String>,
+    #[serde(default)]
+    pub env: EnvConfig,
+    /// Scripts run before and after `dotf install` and `dotf sync` (`[hooks]`)
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    /// Values asked on first install (`[prompts]`) and kept in local settings
+    #[serde(default)]
+    pub prompts: BTreeMap<String, PromptConfig>,
+    /// `~/.ssh` files that are merged, appended or copied rather than linked (`[ssh]`)
+    #[serde(default)]
+    pub ssh: SshConfig,
+    /// Files stored encrypted in the repository and decrypted into place (`[secrets]`)
+    #[serde(default)]
+    pub secrets: SecretsConfig,
+    /// Packages installed by `dotf install packages` (`[packages]`), listed per kind
+    #[serde(default)]
+    pub packages: PackagesConfig,
+    /// Package managers defined by the repository (`[package_managers.custom.<name>]`)
+    #[serde(default)]
+    pub package_managers: PackageManagersConfig,
+    /// Extra symlinks and scripts per kind of machine (`[profile.<name>]`), merged over the
+    /// base config on machines that use the profile
+    #[serde(default)]
+    pub profile: BTreeMap<String, ProfileConfig>,
+}
+
+impl DotfConfig {
+    /// Merge profile `name` over the base config: its symlinks replace base entries with the
+    /// same source or target, its custom scripts replace those with the same name, and its
+    /// deps scripts and limits win
+    pub fn apply_profile(&mut self, name: &str) -> DotfResult<()> {
+        let profile = self.profile.get(name).cloned().ok_or_else(|| {
+            DotfError::Config(format!("Profile '{}' is not defined in dotf.toml", name))
+        })?;
+
+        let targets: HashSet<&String> = profile.symlinks.values().collect();
+        let replaced: Vec<String> = self
+            .symlinks
+            .iter()
+            .filter(|(_, target)| targets.contains(target))
+            .map(|(source, _)| source.clone())
+            .collect();
+        for source in replaced {
+            self.symlinks.remove_source(&source);
+        }
+        self.symlinks.extend(profile.symlinks);
+        if profile.scripts.deps.macos.is_some() {
+            self.scripts.deps.macos = profile.scripts.deps.macos;
+        }
+        if profile.scripts.deps.linux.is_some() {
+            self.scripts.deps.linux = profile.scripts.deps.linux;
+        }
+        self.scripts.custom.extend(profile.scripts.custom);
+        self.scripts.limits.extend(profile.scripts.limits);
+        self.scripts.run.extend(profile.scripts.run);
+        self.scripts.env.extend(profile.scripts.env);
+        self.scripts.working_dir.extend(profile.scripts.working_dir);
+
+        Ok(())
+    }
+
+    /// Resolve the `{name}` placeholders in every symlink target, those of the platform
+    /// sections and profiles included
+    pub fn expand_targets(&mut self, variables: &BTreeMap<String, String>) -> DotfResult<()> {
+        let maps = std::iter::once(&mut self.symlinks)
+            .chain(
+                self.platform
+                    .sections_mut()
+                    .into_iter()
+                    .flatten()
+                    .map(|section| &mut section.symlinks),
+            )
+            .chain(
+                self.profile
+                    .values_mut()
+                    .map(|profile| &mut profile.symlinks),
+            );
+        for symlinks in maps {
+            for target in symlinks.values_mut() {
+                *target = expand_target(target, variables)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drop the symlink entries, those of the platform sections and profiles included, whose
+    /// `when` condition `machine` does not meet
+    pub fn retain_applicable(&mut self, machine: &Machine) {
+        let maps = std::iter::once(&mut self.symlinks)
+            .chain(
+                self.platform
+                    .sections_mut()
+                    .into_iter()
+                    .flatten()
+                    .map(|section| &mut section.symlinks),
+            )
+            .chain(
+                self.profile
+                    .values_mut()
+                    .map(|profile| &mut profile.symlinks),
+            );
+        for symlinks in maps {
+            symlinks.retain_applicable(machine);
+        }
+    }
+
+    /// Layer the symlinks of an overlay repository checked out at `overlay_path` over this
+    /// config. Its sources become absolute paths into the checkout, and each of its entries
+    /// replaces the entries here that link the same target: entries for every platform
+    /// everywhere, platform entries only on that platform. Everything else in the overlay
+    /// is ignored.
+    pub fn apply_overlay(&mut self, overlay: DotfConfig, overlay_path: &str) {
+        let common = overlay.symlinks.rebased(overlay_path);
+        let targets: HashSet<&String> = common.values().collect();
+        self.symlinks.take_targets(&targets);
+        for section in self.platform.sections_mut().into_iter().flatten() {
+            section.symlinks.take_targets(&targets);
+        }
+
+        let overlay_sections = [
+            overlay.platform.macos,
+            overlay.platform.linux,
+            overlay.platform.windows,
+        ];
+        for (index, overlay_section) in overlay_sections.into_iter().enumerate() {
+            let Some(overlay_section) = overlay_section else {
+                continue;
+            };
+            let symlinks = overlay_section.symlinks.rebased(overlay_path);
+            let targets: HashSet<&String> = symlinks.values().collect();
+            // Entries for every platform keep linking these targets on the other platforms
+            let moved = self.symlinks.take_targets(&targets);
+            for (other, section) in self.platform.sections_mut().into_iter().enumerate() {
+                if other != index && moved.is_empty() {
+                    continue;
+                }
+                let section = section.get_or_insert_with(|| PlatformSymlinks {
+                    symlinks: SymlinkMap::default(),
+                });
+                if other == index {
+                    section.symlinks.take_targets(&targets);
+                    section.symlinks.extend(symlinks.clone());
+                } else {
+                    section.symlinks.extend(moved.clone());
+                }
+            }
+        }
+        self.symlinks.extend(common);
+    }
+}
+
+/// Symlinks and scripts of one profile (`[profile.<name>]`)
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub symlinks: SymlinkMap,
+    #[serde(default)]
+    pub scripts: ScriptsConfig,
 }
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
@@ -17,6 +196,93 @@ pub struct ScriptsConfig {
     pub deps: DepsScripts,
     #[serde(default)]
     pub custom: HashMap<String, String>,
+    /// Resource limits by script path, over the limits in local settings
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub limits: HashMap<String, ScriptLimits>,
+    /// When each script runs again, by script path; scripts not listed always run
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub run: HashMap<String, RunPolicy>,
+    /// Extra environment variables by script path, over dotf's own environment
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub env: HashMap<String, BTreeMap<String, String>>,
+    /// Directory each script runs in, by script path; relative to the repository. Scripts
+    /// not listed run where dotf was started.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub working_dir: HashMap<String, String>,
+}
+
+/// One optional script per hook event
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct HooksConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_install: Option<HookEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_install: Option<HookEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_sync: Option<HookEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_sync: Option<HookEntry>,
+    /// Hooks around the links of one `group` of `[symlinks]` entries (`[hooks.groups.<name>]`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub groups: BTreeMap<String, GroupHooks>,
+}
+
+/// Scripts run before and after dotf installs, uninstalls or repairs the links of a group,
+/// e.g. to quit an app before its config is relinked and start it again after
+#[derive(Debug, Deserialize, Serialize, Default, Clone, PartialEq)]
+pub struct GroupHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre: Option<HookEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post: Option<HookEntry>,
+}
+
+impl HooksConfig {
+    pub fn get(&self, event: HookEvent) -> Option<&HookEntry> {
+        match event {
+            HookEvent::PreInstall => self.pre_install.as_ref(),
+            HookEvent::PostInstall => self.post_install.as_ref(),
+            HookEvent::PreSync => self.pre_sync.as_ref(),
+            HookEvent::PostSync => self.post_sync.as_ref(),
+        }
+    }
+}
+
+/// A hook: a script path in the repository, or a table with its timeout and failure policy
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum HookEntry {
+    Script(String),
+    Detailed {
+        script: String,
+        /// Stop the script after this many seconds
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        timeout_secs: Option<u64>,
+        #[serde(default)]
+        on_failure: HookFailure,
+    },
+}
+
+impl HookEntry {
+    pub fn script(&self) -> &str {
+        match self {
+            HookEntry::Script(script) | HookEntry::Detailed { script, .. } => script,
+        }
+    }
+
+    pub fn timeout_secs(&self) -> Option<u64> {
+        match self {
+            HookEntry::Script(_) => None,
+            HookEntry::Detailed { timeout_secs, .. } => *timeout_secs,
+        }
+    }
+
+    pub fn on_failure(&self) -> HookFailure {
+        match self {
+            HookEntry::Script(_) => HookFailure::default(),
+            HookEntry::Detailed { on_failure, .. } => *on_failure,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
@@ -29,9 +295,721 @@ pub struct DepsScripts {
 pub struct PlatformConfig {
     pub macos: Option<PlatformSymlinks>,
     pub linux: Option<PlatformSymlinks>,
+    pub windows: Option<PlatformSymlinks>,
+}
+
+impl PlatformConfig {
+    /// Every section, in the order macos, linux, windows
+    pub fn sections_mut(&mut self) -> [&mut Option<PlatformSymlinks>; 3] {
+        [&mut self.macos, &mut self.linux, &mut self.windows]
+    }
+
+    /// Section for a platform name returned by `detect_platform`
+    pub fn for_platform(&self, platform: &str) -> Option<&PlatformSymlinks> {
+        match platform {
+            "macos" => self.macos.as_ref(),
+            "linux" => self.linux.as_ref(),
+            "windows" => self.windows.as_ref(),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct PlatformSymlinks {
-    pub symlinks: HashMap<String, String>,
+    pub symlinks: SymlinkMap,
+}
+
+/// Symlinks keyed by source path: `"source" = "target"` or `"source" = { target = ..., ... }`.
+/// The entry fields are listed under "Configuration Reference" in the README and by
+/// `dotf schema docs symlinks`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SymlinkMap {
+    entries: HashMap<String, String>,
+    optional: HashSet<String>,
+    pins: HashMap<String, String>,
+    priorities: HashMap<String, i32>,
+    modes: HashMap<String, LinkMode>,
+    permissions: HashMap<String, Permissions>,
+    conditions: HashMap<String, Condition>,
+    groups: HashMap<String, String>,
+}
+
+impl SymlinkMap {
+    pub fn is_optional(&self, source: &str) -> bool {
+        self.optional.contains(source)
+    }
+
+    pub fn set_optional(&mut self, source: &str, optional: bool) {
+        if optional {
+            self.optional.insert(source.to_string());
+        } else {
+            self.optional.remove(source);
+        }
+    }
+
+    /// Expected SHA-256 of the source's content, if the entry is pinned
+    pub fn pinned_sha256(&self, source: &str) -> Option<&str> {
+        self.pins.get(source).map(String::as_str)
+    }
+
+    pub fn set_pinned_sha256(&mut self, source: &str, sha256: Option<String>) {
+        match sha256 {
+            Some(sha256) => {
+                self.pins.insert(source.to_string(), sha256);
+            }
+            None => {
+                self.pins.remove(source);
+            }
+        }
+    }
+
+    /// Rank of the entry among entries with the same target; 0 unless set
+    pub fn priority(&self, source: &str) -> i32 {
+        self.priorities.get(source).copied().unwrap_or_default()
+    }
+
+    pub fn set_priority(&mut self, source: &str, priority: Option<i32>) {
+        match priority {
+            Some(priority) => {
+                self.priorities.insert(source.to_string(), priority);
+            }
+            None => {
+                self.priorities.remove(source);
+            }
+        }
+    }
+
+    /// How the entry is deployed; a symlink unless set
+    pub fn mode(&self, source: &str) -> LinkMode {
+        self.modes.get(source).copied().unwrap_or_default()
+    }
+
+    pub fn set_mode(&mut self, source: &str, mode: LinkMode) {
+        if mode.is_symlink() {
+            self.modes.remove(source);
+        } else {
+            self.modes.insert(source.to_string(), mode);
+        }
+    }
+
+    /// Mode and owner the deployed file must have; none unless set
+    pub fn permissions(&self, source: &str) -> Permissions {
+        self.permissions.get(source).cloned().unwrap_or_default()
+    }
+
+    pub fn set_permissions(&mut self, source: &str, permissions: Permissions) {
+        if permissions.is_empty() {
+            self.permissions.remove(source);
+        } else {
+            self.permissions.insert(source.to_string(), permissions);
+        }
+    }
+
+    /// The machines the entry applies on; all of them unless set
+    pub fn condition(&self, source: &str) -> Option<&Condition> {
+        self.conditions.get(source)
+    }
+
+    pub fn set_condition(&mut self, source: &str, condition: Option<Condition>) {
+        match condition {
+            Some(condition) => {
+                self.conditions.insert(source.to_string(), condition);
+            }
+            None => {
+                self.conditions.remove(source);
+            }
+        }
+    }
+
+    /// The group the entry belongs to, if any
+    pub fn group(&self, source: &str) -> Option<&str> {
+        self.groups.get(source).map(String::as_str)
+    }
+
+    pub fn set_group(&mut self, source: &str, group: Option<String>) {
+        match group {
+            Some(group) => {
+                self.groups.insert(source.to_string(), group);
+            }
+            None => {
+                self.groups.remove(source);
+            }
+        }
+    }
+
+    /// Drop every entry whose condition `machine` does not meet
+    pub fn retain_applicable(&mut self, machine: &Machine) {
+        let unmet: Vec<String> = self
+            .conditions
+            .iter()
+            .filter(|(_, condition)| !condition.holds(machine))
+            .map(|(source, _)| source.clone())
+            .collect();
+        for source in unmet {
+            self.remove_source(&source);
+        }
+    }
+
+    /// Drop the entry for `source` together with its options
+    pub fn remove_source(&mut self, source: &str) {
+        self.entries.remove(source);
+        self.optional.remove(source);
+        self.pins.remove(source);
+        self.priorities.remove(source);
+        self.modes.remove(source);
+        self.permissions.remove(source);
+        self.conditions.remove(source);
+        self.groups.remove(source);
+    }
+
+    /// Remove every entry that links one of `targets` and return them with their options
+    pub fn take_targets(&mut self, targets: &HashSet<&String>) -> SymlinkMap {
+        let sources: Vec<String> = self
+            .entries
+            .iter()
+            .filter(|(_, target)| targets.contains(target))
+            .map(|(source, _)| source.clone())
+            .collect();
+        let mut taken = SymlinkMap::default();
+        for source in sources {
+            if let Some(target) = self.entries.remove(&source) {
+                taken.set_optional(&source, self.optional.remove(&source));
+                taken.set_pinned_sha256(&source, self.pins.remove(&source));
+                taken.set_priority(&source, self.priorities.remove(&source));
+                taken.set_mode(&source, self.modes.remove(&source).unwrap_or_default());
+                taken.set_permissions(
+                    &source,
+                    self.permissions.remove(&source).unwrap_or_default(),
+                );
+                taken.set_condition(&source, self.conditions.remove(&source));
+                taken.set_group(&source, self.groups.remove(&source));
+                taken.entries.insert(source, target);
+            }
+        }
+        taken
+    }
+
+    /// Drop every entry whose source `keep` rejects
+    pub fn retain_sources(&mut self, keep: impl Fn(&str) -> bool) {
+        let sources: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|source| !keep(source))
+            .cloned()
+            .collect();
+        for source in sources {
+            self.remove_source(&source);
+        }
+    }
+
+    /// The same entries with relative sources resolved against `repo_path`
+    pub fn rebased(&self, repo_path: &str) -> SymlinkMap {
+        let rebase = |source: &String| {
+            if source.starts_with('/') {
+                source.clone()
+            } else {
+                format!("{}/{}", repo_path.trim_end_matches('/'), source)
+            }
+        };
+        SymlinkMap {
+            entries: self
+                .entries
+                .iter()
+                .map(|(source, target)| (rebase(source), target.clone()))
+                .collect(),
+            optional: self.optional.iter().map(rebase).collect(),
+            pins: self
+                .pins
+                .iter()
+                .map(|(source, sha256)| (rebase(source), sha256.clone()))
+                .collect(),
+            priorities: self
+                .priorities
+                .iter()
+                .map(|(source, priority)| (rebase(source), *priority))
+                .collect(),
+            modes: self
+                .modes
+                .iter()
+                .map(|(source, mode)| (rebase(source), *mode))
+                .collect(),
+            permissions: self
+                .permissions
+                .iter()
+                .map(|(source, permissions)| (rebase(source), permissions.clone()))
+                .collect(),
+            conditions: self
+                .conditions
+                .iter()
+                .map(|(source, condition)| (rebase(source), condition.clone()))
+                .collect(),
+            groups: self
+                .groups
+                .iter()
+                .map(|(source, group)| (rebase(source), group.clone()))
+                .collect(),
+        }
+    }
+
+    /// Add `other`'s entries, replacing entries with the same source
+    pub fn extend(&mut self, other: SymlinkMap) {
+        for source in other.entries.keys() {
+            self.optional.remove(source);
+            self.pins.remove(source);
+            self.priorities.remove(source);
+            self.modes.remove(source);
+            self.permissions.remove(source);
+            self.conditions.remove(source);
+            self.groups.remove(source);
+        }
+        self.optional.extend(other.optional);
+        self.pins.extend(other.pins);
+        self.priorities.extend(other.priorities);
+        self.modes.extend(other.modes);
+        self.permissions.extend(other.permissions);
+        self.conditions.extend(other.conditions);
+        self.groups.extend(other.groups);
+        self.entries.extend(other.entries);
+    }
+}
+
+impl Deref for SymlinkMap {
+    type Target = HashMap<String, String>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.entries
+    }
+}
+
+impl DerefMut for SymlinkMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.entries
+    }
+}
+
+impl From<HashMap<String, String>> for SymlinkMap {
+    fn from(entries: HashMap<String, String>) -> Self {
+        Self {
+            entries,
+            optional: HashSet::new(),
+            pins: HashMap::new(),
+            priorities: HashMap::new(),
+            modes: HashMap::new(),
+            permissions: HashMap::new(),
+            conditions: HashMap::new(),
+            groups: HashMap::new(),
+        }
+    }
+}
+
+impl IntoIterator for SymlinkMap {
+    type Item = (String, String);
+    type IntoIter = std::collections::hash_map::IntoIter<String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a SymlinkMap {
+    type Item = (&'a String, &'a String);
+    type IntoIter = std::collections::hash_map::Iter<'a, String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum SymlinkEntry {
+    Target(String),
+    Detailed {
+        target: String,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        optional: bool,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pin_sha256: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        priority: Option<i32>,
+        #[serde(default, skip_serializing_if = "LinkMode::is_symlink")]
+        mode: LinkMode,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        chmod: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        owner: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        when: Option<Condition>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        group: Option<String>,
+    },
+}
+
+impl Serialize for SymlinkMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.entries.len()))?;
+        for (source, target) in &self.entries {
+            let pin_sha256 = self.pinned_sha256(source).map(str::to_string);
+            let priority = self.priorities.get(source).copied();
+            let mode = self.mode(source);
+            let permissions = self.permissions(source);
+            let when = self.condition(source).cloned();
+            let group = self.group(source).map(str::to_string);
+            if self.is_optional(source)
+                || pin_sha256.is_some()
+                || priority.is_some()
+                || !mode.is_symlink()
+                || !permissions.is_empty()
+                || when.is_some()
+                || group.is_some()
+            {
+                map.serialize_entry(
+                    source,
+                    &SymlinkEntry::Detailed {
+                        target: target.clone(),
+                        optional: self.is_optional(source),
+                        pin_sha256,
+                        priority,
+                        mode,
+                        chmod: permissions.chmod.map(format_chmod),
+                        owner: permissions.owner,
+                        when,
+                        group,
+                    },
+                )?;
+            } else {
+                map.serialize_entry(source, target)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SymlinkMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = HashMap::<String, SymlinkEntry>::deserialize(deserializer)?;
+        let mut map = SymlinkMap::default();
+        for (source, entry) in raw {
+            let target = match entry {
+                SymlinkEntry::Target(target) => target,
+                SymlinkEntry::Detailed {
+                    target,
+                    optional,
+                    pin_sha256,
+                    priority,
+                    mode,
+                    chmod,
+                    owner,
+                    when,
+                    group,
+                } => {
+                    let chmod = chmod
+                        .as_deref()
+                        .map(parse_chmod)
+                        .transpose()
+                        .map_err(serde::de::Error::custom)?;
+                    map.set_optional(&source, optional);
+                    map.set_pinned_sha256(&source, pin_sha256);
+                    map.set_priority(&source, priority);
+                    map.set_mode(&source, mode);
+                    map.set_permissions(&source, Permissions { chmod, owner });
+                    map.set_condition(&source, when);
+                    map.set_group(&source, group);
+                    target
+                }
+            };
+            map.entries.insert(source, target);
+        }
+        Ok(map)
+    }
+}
+
+/// A value the repository needs from each machine's user
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct PromptConfig {
+    /// Question shown to the user
+    pub message: String,
+    /// Suggested answer
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// Environment rendered into a shell fragment (`[env]`)
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct EnvConfig {
+    /// Entries for every platform
+    #[serde(flatten)]
+    pub common: EnvSection,
+    pub macos: Option<EnvSection>,
+    pub linux: Option<EnvSection>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct EnvSection {
+    /// Directories prepended to `PATH`, in order
+    #[serde(default)]
+    pub path: Vec<String>,
+    /// Exported variables
+    #[serde(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+/// Managed `~/.ssh` content (`[ssh]`)
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct SshConfig {
+    /// Repository files merged, in order, into a managed block at the top of `~/.ssh/config`
+    #[serde(default)]
+    pub config: Vec<String>,
+    /// Repository files whose host keys are appended to `~/.ssh/known_hosts` when missing
+    #[serde(default)]
+    pub known_hosts: Vec<String>,
+    /// Private keys copied with mode 600 instead of linked: repository path → target
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+}
+
+impl SshConfig {
+    pub fn is_empty(&self) -> bool {
+        self.config.is_empty() && self.known_hosts.is_empty() && self.keys.is_empty()
+    }
+}
+
+/// Encrypted files (`[secrets]`), decrypted to their targets instead of linked
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct SecretsConfig {
+    #[serde(default)]
+    pub backend: SecretBackend,
+    /// age public keys or gpg key IDs that `dotf secrets encrypt` encrypts to
+    #[serde(default)]
+    pub recipients: Vec<String>,
+    /// age key file used to decrypt (defaults to `~/.config/age/keys.txt`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity: Option<String>,
+    /// Encrypted repository path → target
+    #[serde(default)]
+    pub files: BTreeMap<String, String>,
+}
+
+impl SecretsConfig {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Packages by kind (`[packages]`); each kind is installed by its own package manager
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct PackagesConfig {
+    /// Homebrew formulae
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub brew: Vec<String>,
+    /// Homebrew casks (macOS)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cask: Vec<String>,
+    /// Mac App Store apps installed with `mas`: display name → app ID
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mas: BTreeMap<String, u64>,
+    /// Flatpak application IDs, from flathub unless the entry names another remote
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub flatpak: Vec<FlatpakEntry>,
+    /// URLs of `.flatpakrepo` files for flatpak remotes other than flathub, by remote name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub flatpak_remotes: BTreeMap<String, String>,
+    /// Snap names (Linux)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snap: Vec<String>,
+    /// Packages for `[package_managers.custom.<name>]` managers, keyed by manager name
+    #[serde(flatten)]
+    pub custom: BTreeMap<String, Vec<String>>,
+}
+
+/// Package managers declared in dotf.toml (`[package_managers]`)
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct PackageManagersConfig {
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, CustomPackageManager>,
+}
+
+/// Shell commands that drive a package manager dotf does not know, e.g. pipx
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct CustomPackageManager {
+    /// Succeeds when the manager can be used on this machine
+    pub check: String,
+    /// Prints installed packages, one per line; the first word of each line is the name
+    pub list: String,
+    /// Installs one package; `{package}` is replaced by the quoted package name
+    pub install: String,
+}
+
+/// A `flatpak` entry: an application ID, or a table naming the remote it comes from
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum FlatpakEntry {
+    Id(String),
+    Detailed { id: String, remote: String },
+}
+
+/// One `[packages]` entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    /// Key of its list in `[packages]`, e.g. `cask`
+    pub kind: String,
+    /// Name shown to the user
+    pub name: String,
+    /// What the package manager installs: the same as `name` except for mas app IDs
+    pub id: String,
+    /// Where the package is installed from, for managers with several sources
+    pub remote: Option<PackageRemote>,
+}
+
+/// A named package source, added before installing from it when its URL is known
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageRemote {
+    pub name: String,
+    pub url: Option<String>,
+}
+
+impl PackagesConfig {
+    pub fn is_empty(&self) -> bool {
+        self.brew.is_empty()
+            && self.cask.is_empty()
+            && self.mas.is_empty()
+            && self.flatpak.is_empty()
+            && self.snap.is_empty()
+            && self.custom.values().all(Vec::is_empty)
+    }
+
+    /// Every entry in declaration order, kind by kind
+    pub fn packages(&self) -> Vec<Package> {
+        let package = |kind: &str, name: &String| Package {
+            kind: kind.to_string(),
+            name: name.clone(),
+            id: name.clone(),
+            remote: None,
+        };
+
+        let mut packages: Vec<Package> = Vec::new();
+        for (kind, names) in [("brew", &self.brew), ("cask", &self.cask)] {
+            packages.extend(names.iter().map(|name| package(kind, name)));
+        }
+        packages.extend(self.mas.iter().map(|(name, id)| Package {
+            id: id.to_string(),
+            ..package("mas", name)
+        }));
+        packages.extend(self.flatpak.iter().map(|entry| {
+            let (id, remote) = match entry {
+                FlatpakEntry::Id(id) => (id, "flathub"),
+                FlatpakEntry::Detailed { id, remote } => (id, remote.as_str()),
+            };
+            Package {
+                remote: Some(PackageRemote {
+                    name: remote.to_string(),
+                    url: self.flatpak_remotes.get(remote).cloned(),
+                }),
+                ..package("flatpak", id)
+            }
+        }));
+        packages.extend(self.snap.iter().map(|name| package("snap", name)));
+        for (kind, names) in &self.custom {
+            packages.extend(names.iter().map(|name| package(kind, name)));
+        }
+        packages
+    }
+}
+
+/// macOS-only settings (`[macos]`)
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct MacosConfig {
+    /// `defaults write` values, keyed by domain and then by key
+    #[serde(default)]
+    pub defaults: BTreeMap<String, BTreeMap<String, DefaultsValue>>,
+}
+
+/// A typed `defaults` value; the TOML type picks the `-bool`/`-int`/`-float`/`-string` flag
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum DefaultsValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl fmt::Display for DefaultsValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DefaultsValue::Bool(value) => write!(f, "{}", value),
+            DefaultsValue::Int(value) => write!(f, "{}", value),
+            DefaultsValue::Float(value) => write!(f, "{}", value),
+            DefaultsValue::String(value) => write!(f, "\"{}\"", value),
+        }
+    }
+}
+
+/// Plugin managers to bootstrap after `dotf install config` (`[bootstrap]`)
+#[derive(Debug, Deserialize, Serialize, Default, Clone)]
+pub struct BootstrapConfig {
+    pub nvim: Option<PluginManager>,
+    pub vim: Option<PluginManager>,
+    pub tmux: Option<PluginManager>,
+    /// Seconds each step may run before it is stopped (default: 300)
+    pub timeout: Option<u64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PluginManager {
+    Lazy,
+    Packer,
+    VimPlug,
+    Tpm,
+}
+
+impl PluginManager {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PluginManager::Lazy => "lazy",
+            PluginManager::Packer => "packer",
+            PluginManager::VimPlug => "vim-plug",
+            PluginManager::Tpm => "tpm",
+        }
+    }
+}
+
+/// A binary fetched from a GitHub or GitLab release (`[tools.<name>]`)
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ToolConfig {
+    /// Project path, e.g. `BurntSushi/ripgrep`
+    pub repo: String,
+    #[serde(default)]
+    pub host: ReleaseHost,
+    /// Release tag to install, or `latest`
+    #[serde(default = "default_tool_version")]
+    pub version: String,
+    /// Asset name pattern with `*` wildcards and `{version}`/`{tag}` placeholders.
+    /// Matches are narrowed down to the current OS and architecture.
+    pub asset: Option<String>,
+    /// Executable name inside the asset (defaults to the tool name)
+    pub bin: Option<String>,
+    /// Directory the executable is installed into (defaults to `~/.local/bin`)
+    pub install_path: Option<String>,
+    /// Checksums file published with the release (e.g. `SHA256SUMS`)
+    pub checksums: Option<String>,
+    /// Expected SHA-256 of the asset per platform, keyed by `<os>-<arch>` (e.g. `linux-x86_64`)
+    #[serde(default)]
+    pub sha256: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseHost {
+    #[default]
+    Github,
+    Gitlab,
+}
+
+pub const LATEST_TOOL_VERSION: &str = "latest";
+
+fn default_tool_version() -> String {
+    LATEST_TOOL_VERSION.to_string()
 }
@@ -0,0 +1,173 @@
+//! Reading what is still usable out of a damaged TOML file
+
+use toml::{Table, Value};
+
+/// Lines shown before and after the line a parse error points at
+const EXCERPT_CONTEXT: usize = 2;
+
+/// Where a TOML parse error is, with the lines around it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseFailure {
+    pub message: String,
+    /// 1-based line and column of the error, when the parser reports one
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// Numbered lines around the error
+    pub excerpt: Vec<(usize, String)>,
+}
+
+/// Describe a parse error of `content`
+pub fn locate(content: &str, error: &toml::de::Error) -> ParseFailure {
+    let Some(span) = error.span() else {
+        return ParseFailure {
+            message: error.message().to_string(),
+            line: None,
+            column: None,
+            excerpt: Vec::new(),
+        };
+    };
+
+    let before = &content[..span.start.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rsplit('\n')
+        .next()
+        .unwrap_or_default()
+        .chars()
+        .count()
+        + 1;
+    let excerpt = content
+        .lines()
+        .enumerate()
+        .map(|(index, text)| (index + 1, text.to_string()))
+        .filter(|(number, _)| number.abs_diff(line) <= EXCERPT_CONTEXT)
+        .collect();
+    ParseFailure {
+        message: error.message().to_string(),
+        line: Some(line),
+        column: Some(column),
+        excerpt,
+    }
+}
+
+/// The parts of a damaged file that still parse
+#[derive(Debug, Default)]
+pub struct Salvage {
+    pub table: Table,
+    /// Numbered lines that had to be left out
+    pub dropped: Vec<(usize, String)>,
+}
+
+/// Parse `content` one table at a time, and a broken table one line at a time, keeping
+/// whatever parses. Values spanning several lines are lost when their table is broken.
+pub fn salvage(content: &str) -> Salvage {
+    let mut salvage = Salvage::default();
+    for chunk in chunks(content) {
+        let text: String = chunk
+            .iter()
+            .map(|(_, line)| format!("{}\n", line))
+            .collect();
+        if let Ok(table) = text.parse::<Table>() {
+            merge(&mut salvage.table, table);
+            continue;
+        }
+
+        let (header, body) = match chunk.split_first() {
+            Some((first, rest)) if is_header(&first.1) => (Some(first), rest),
+            _ => (None, chunk.as_slice()),
+        };
+        let mut kept = match header {
+            Some((_, line)) if line.parse::<Table>().is_ok() => format!("{}\n", line),
+            Some(_) => {
+                // Keys under a broken header would land in the wrong table
+                salvage
+                    .dropped
+                    .extend(chunk.iter().filter(|line| is_content(line)).cloned());
+                continue;
+            }
+            None => String::new(),
+        };
+        for line in body.iter().filter(|line| is_content(line)) {
+            let candidate = format!("{}{}\n", kept, line.1);
+            if candidate.parse::<Table>().is_ok() {
+                kept = candidate;
+            } else {
+                salvage.dropped.push(line.clone());
+            }
+        }
+        merge(&mut salvage.table, kept.parse().unwrap_or_default());
+    }
+    salvage
+}
+
+/// Merge `other` into `table`: tables are merged key by key, arrays of tables are
+/// appended to, and other values are replaced
+pub fn merge(table: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (table.get_mut(&key), value) {
+            (Some(Value::Table(existing)), Value::Table(value)) => merge(existing, value),
+            (Some(Value::Array(existing)), Value::Array(value))
+                if value.iter().all(Value::is_table) =>
+            {
+                existing.extend(value)
+            }
+            (_, value) => {
+                table.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Numbered lines split before each table header; the first chunk holds the top-level keys
+fn chunks(content: &str) -> Vec<Vec<(usize, String)>> {
+    let mut chunks = vec![Vec::new()];
+    for (index, line) in content.lines().enumerate() {
+        if is_header(line) {
+            chunks.push(Vec::new());
+        }
+        if let Some(chunk) = chunks.last_mut() {
+            chunk.push((index + 1, line.to_string()));
+        }
+    }
+    chunks
+}
+
+fn is_header(line: &str) -> bool {
+    line.trim_start().starts_with('[')
+}
+
+fn is_content((_, line): &(usize, String)) -> bool {
+    let line = line.trim();
+    !line.is_empty() && !line.starts_with('#')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_salvage_keeps_what_parses() {
+        let content = "initialized_at = \"2024-01-01T00:00:00Z\"\nlast_sync = \n\n[repository]\nremote = \"https://github.com/user/dotfiles\"\n\n[aliases\ns = \"status\"\n\n[[repositories]]\nname = \"work\"\nremote = \"git@github.com:acme/dotfiles.git\"\n\n[prompts]\ngit_email = \"me@example.com\"\ngit_name = \"Me";
+
+        let error = content.parse::<Table>().unwrap_err();
+        let failure = locate(content, &error);
+        assert_eq!((failure.line, failure.column), (Some(7), Some(9)));
+        assert_eq!(failure.excerpt.first().map(|(n, _)| *n), Some(5));
+        assert_eq!(failure.excerpt.last().map(|(n, _)| *n), Some(9));
+
+        let salvage = salvage(content);
+        assert_eq!(
+            salvage.table["repository"]["remote"].as_str(),
+            Some("https://github.com/user/dotfiles")
+        );
+        assert!(salvage.table.contains_key("initialized_at"));
+        assert!(!salvage.table.contains_key("aliases"));
+        assert_eq!(salvage.table["repositories"].as_array().unwrap().len(), 1);
+        assert_eq!(
+            salvage.table["prompts"]["git_email"].as_str(),
+            Some("me@example.com")
+        );
+        let dropped: Vec<usize> = salvage.dropped.iter().map(|(n, _)| *n).collect();
+        assert_eq!(dropped, vec![2, 7, 8, 16]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Reading dotf.toml. Every command that works with links loads it through `load_config`,
+//! so they all see the same entries.
+
+use crate::core::config::{
+    condition::Machine, overlays::apply_overlays, variables::target_variables, DotfConfig, Settings,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+/// Checkout of the main repository: `repository.local` when set, else ~/.dotf/repo
+pub fn repo_path<F: FileSystem>(filesystem: &F, settings: &Settings) -> String {
+    settings
+        .repository
+        .local
+        .clone()
+        .unwrap_or_else(|| filesystem.dotf_repo_path())
+}
+
+/// dotf.toml of the repository at `repo_path` as written, with nothing applied
+pub async fn read_config<F: FileSystem>(filesystem: &F, repo_path: &str) -> DotfResult<DotfConfig> {
+    let config_path = format!("{}/dotf.toml", repo_path);
+    if !filesystem.exists(&config_path).await? {
+        return Err(DotfError::Config(
+            "dotf.toml not found in repository".to_string(),
+        ));
+    }
+
+    let content = filesystem.read_to_string(&config_path).await?;
+    toml::from_str(&content)
+        .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))
+}
+
+/// dotf.toml as it applies to this machine: targets expanded, entries whose `when` does
+/// not hold dropped, then the active profile and the overlay repositories applied
+pub async fn load_config<F: FileSystem>(
+    filesystem: &F,
+    settings: &Settings,
+) -> DotfResult<DotfConfig> {
+    let mut config = read_config(filesystem, &repo_path(filesystem, settings)).await?;
+    config.expand_targets(&target_variables(settings))?;
+    config.retain_applicable(&Machine::current());
+    if let Some(profile) = &settings.profile {
+        config.apply_profile(profile)?;
+    }
+    apply_overlays(filesystem, settings, &mut config).await?;
+    Ok(config)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{overlays::overlay_path, NamedRepository};
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_load_config_applies_profile_and_overlays() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.profile = Some("work".to_string());
+        settings.repositories.push(NamedRepository {
+            name: "laptop".to_string(),
+            remote: "https://github.com/user/laptop".to_string(),
+            branch: None,
+        });
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[symlinks]
+"vimrc" = "~/.vimrc"
+
+[profile.work.symlinks]
+"gitconfig-work" = "~/.gitconfig"
+"#,
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", overlay_path(&filesystem, "laptop")),
+            "[symlinks]\n\"vimrc\" = \"~/.vimrc\"\n",
+        );
+
+        let raw = read_config(&filesystem, &filesystem.dotf_repo_path())
+            .await
+            .unwrap();
+        assert_eq!(raw.symlinks.len(), 1);
+
+        let config = load_config(&filesystem, &settings).await.unwrap();
+        let laptop = overlay_path(&filesystem, "laptop");
+        assert_eq!(config.symlinks.len(), 2);
+        assert_eq!(config.symlinks[&format!("{}/vimrc", laptop)], "~/.vimrc");
+        assert_eq!(config.symlinks["gitconfig-work"], "~/.gitconfig");
+    }
+}
@@ -1,6 +1,19 @@
+pub mod condition;
+pub mod diff;
 pub mod dotf_config;
+pub mod lenient;
+pub mod loader;
+pub mod overlays;
+pub mod reference;
+pub mod renames;
 pub mod settings;
 pub mod validation;
+pub mod variables;
 
+pub use diff::{diff_configs, EntryChange, SectionDiff};
 pub use dotf_config::DotfConfig;
-pub use settings::{Repository, Settings};
+pub use settings::{
+    BackupSettings, CommitContext, CommitSettings, DiffSettings, LogSettings, MeteredMode,
+    NamedRepository, NetworkSettings, PackageSettings, Repository, Settings, SymlinkSettings,
+    WidgetSettings,
+};
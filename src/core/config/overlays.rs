@@ -0,0 +1,158 @@
+//! Overlay repositories (`[[repositories]]` in settings.toml) layered over the main one
+
+use crate::core::config::{condition::Machine, variables::target_variables, DotfConfig, Settings};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+/// Name by which `--repo` selects the main repository
+pub const BASE_REPOSITORY: &str = "base";
+
+/// Checkout of overlay `name`
+pub fn overlay_path<F: FileSystem>(filesystem: &F, name: &str) -> String {
+    format!("{}/{}", filesystem.dotf_repos_path(), name)
+}
+
+/// Overlay names become directory names and `--repo` values
+pub fn validate_overlay_name(name: &str) -> DotfResult<()> {
+    if name == BASE_REPOSITORY {
+        return Err(DotfError::Config(format!(
+            "'{}' is the name of the main repository",
+            BASE_REPOSITORY
+        )));
+    }
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(DotfError::Config(format!(
+            "Invalid repository name '{}': use letters, digits, '-' and '_'",
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// Merge the symlinks of every cloned overlay over `config`, in settings order so later
+/// overlays win. Overlays that are not cloned yet are skipped; `dotf sync` clones them.
+pub async fn apply_overlays<F: FileSystem>(
+    filesystem: &F,
+    settings: &Settings,
+    config: &mut DotfConfig,
+) -> DotfResult<()> {
+    for repository in &settings.repositories {
+        let path = overlay_path(filesystem, &repository.name);
+        let config_path = format!("{}/dotf.toml", path);
+        if !filesystem.exists(&config_path).await? {
+            continue;
+        }
+
+        let content = filesystem.read_to_string(&config_path).await?;
+        let mut overlay: DotfConfig = toml::from_str(&content).map_err(|e| {
+            DotfError::Config(format!(
+                "Failed to parse dotf.toml of repository '{}': {}",
+                repository.name, e
+            ))
+        })?;
+        overlay.expand_targets(&target_variables(settings))?;
+        overlay.retain_applicable(&Machine::current());
+        config.apply_overlay(overlay, &path);
+    }
+    Ok(())
+}
+
+/// Keep only the links whose source lies in repository `name` (`base` for the main one)
+pub fn retain_repository<F: FileSystem>(
+    filesystem: &F,
+    settings: &Settings,
+    config: &mut DotfConfig,
+    name: &str,
+) -> DotfResult<()> {
+    let overlays: Vec<String> = settings
+        .repositories
+        .iter()
+        .map(|repository| format!("{}/", overlay_path(filesystem, &repository.name)))
+        .collect();
+    let keep: Box<dyn Fn(&str) -> bool> = if name == BASE_REPOSITORY {
+        Box::new(|source| !overlays.iter().any(|overlay| source.starts_with(overlay)))
+    } else if settings.repositories.iter().any(|r| r.name == name) {
+        let overlay = format!("{}/", overlay_path(filesystem, name));
+        Box::new(move |source| source.starts_with(&overlay))
+    } else {
+        return Err(DotfError::Config(format!(
+            "Unknown repository '{}' (see 'dotf repos list')",
+            name
+        )));
+    };
+
+    config.symlinks.retain_sources(&keep);
+    for section in config.platform.sections_mut().into_iter().flatten() {
+        section.symlinks.retain_sources(&keep);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::NamedRepository;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_overlays_replace_links_by_target() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        for name in ["work", "laptop"] {
+            settings.repositories.push(NamedRepository {
+                name: name.to_string(),
+                remote: format!("https://github.com/user/{}", name),
+                branch: None,
+            });
+        }
+        let work = overlay_path(&filesystem, "work");
+        filesystem.add_file(
+            &format!("{}/dotf.toml", work),
+            "[symlinks]\n\"git/.gitconfig\" = \"~/.gitconfig\"\n\"ssh/config\" = \"~/.ssh/config\"\n\n[platform.linux.symlinks]\n\"linux/.zshrc\" = \"~/.zshrc\"\n",
+        );
+        let laptop = overlay_path(&filesystem, "laptop");
+        filesystem.add_file(
+            &format!("{}/dotf.toml", laptop),
+            "[symlinks]\n\".gitconfig\" = \"~/.gitconfig\"\n",
+        );
+
+        let mut config: DotfConfig = toml::from_str(
+            "[symlinks]\n\".gitconfig\" = \"~/.gitconfig\"\n\".zshrc\" = \"~/.zshrc\"\n\".vimrc\" = \"~/.vimrc\"\n",
+        )
+        .unwrap();
+        apply_overlays(&filesystem, &settings, &mut config)
+            .await
+            .unwrap();
+
+        // The last overlay wins; platform entries only replace links on that platform
+        let gitconfig = format!("{}/.gitconfig", laptop);
+        assert_eq!(config.symlinks.len(), 3);
+        assert_eq!(config.symlinks[&gitconfig], "~/.gitconfig");
+        assert_eq!(
+            config.symlinks[&format!("{}/ssh/config", work)],
+            "~/.ssh/config"
+        );
+        assert!(!config.symlinks.contains_key(".gitconfig"));
+        assert!(!config.symlinks.contains_key(".zshrc"));
+        let linux = config.platform.linux.as_ref().unwrap();
+        assert_eq!(
+            linux.symlinks[&format!("{}/linux/.zshrc", work)],
+            "~/.zshrc"
+        );
+        let macos = config.platform.macos.as_ref().unwrap();
+        assert_eq!(macos.symlinks[".zshrc"], "~/.zshrc");
+
+        let mut base = config.clone();
+        retain_repository(&filesystem, &settings, &mut base, BASE_REPOSITORY).unwrap();
+        assert_eq!(base.symlinks.keys().collect::<Vec<_>>(), vec![".vimrc"]);
+        retain_repository(&filesystem, &settings, &mut config, "work").unwrap();
+        assert_eq!(config.symlinks.len(), 1);
+        assert!(retain_repository(&filesystem, &settings, &mut base, "home").is_err());
+        assert!(validate_overlay_name(BASE_REPOSITORY).is_err());
+        assert!(validate_overlay_name("../x").is_err());
+    }
+}
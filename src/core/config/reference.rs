@@ -0,0 +1,552 @@
+//! Reference documentation for every dotf.toml section, shown by `dotf schema docs`.
+//! A test parses each example into `DotfConfig` and checks that every documented field
+//! survives the round trip, so a renamed or removed field cannot go unnoticed here.
+
+/// One table of dotf.toml, e.g. `tools.<name>`
+#[derive(Debug, Clone, Copy)]
+pub struct SectionDoc {
+    /// Table path; `<...>` segments stand for names chosen by the repository author
+    pub table: &'static str,
+    pub description: &'static str,
+    pub fields: &'static [FieldDoc],
+    pub example: &'static str,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FieldDoc {
+    /// Key name, or a `<...>` placeholder for tables keyed by user-chosen names
+    pub name: &'static str,
+    pub type_name: &'static str,
+    /// `required`, `none` or the value used when the field is left out
+    pub default: &'static str,
+    pub description: &'static str,
+}
+
+const fn field(
+    name: &'static str,
+    type_name: &'static str,
+    default: &'static str,
+    description: &'static str,
+) -> FieldDoc {
+    FieldDoc {
+        name,
+        type_name,
+        default,
+        description,
+    }
+}
+
+pub const SECTIONS: &[SectionDoc] = &[
+    SectionDoc {
+        table: "symlinks",
+        description: "Files and directories linked from the repository into place by `dotf install config`.",
+        fields: &[field(
+            "<source>",
+            "string or table",
+            "none",
+            "Target path for the repository path `<source>`; `~` expands to the home directory",
+        )],
+        example: "[symlinks]\n\"vim/.vimrc\" = \"~/.vimrc\"\n",
+    },
+    SectionDoc {
+        table: "symlinks.<source>",
+        description: "The table form of a symlink entry, for entries with options.",
+        fields: &[
+            field("target", "string", "required", "Where the link is created"),
+            field(
+                "optional",
+                "bool",
+                "false",
+                "Skip the entry with a warning when the source is missing",
+            ),
+            field(
+                "pin_sha256",
+                "string",
+                "none",
+                "Only link while the source content has this SHA-256",
+            ),
+            field(
+                "priority",
+                "integer",
+                "0",
+                "Decides between entries with the same target; the highest wins",
+            ),
+            field(
+                "mode",
+                "string",
+                "\"symlink\"",
+                "Deploy a \"symlink\", a \"copy\" or a \"hardlink\" (same filesystem only)",
+            ),
+            field(
+                "chmod",
+                "string",
+                "none",
+                "Octal mode the deployed file must have, e.g. \"600\"; for a symlink, the file it points to",
+            ),
+            field(
+                "owner",
+                "string",
+                "none",
+                "\"user\" or \"user:group\" that must own the deployed file; changing it needs root",
+            ),            field(
+                "when",
+                "table",
+                "none",
+                "Only link on machines that meet this condition (see `symlinks.<source>.when`)",
+            ),
+            field(
+                "group",
+                "string",
+                "none",
+                "Name of a group whose links are handled together, between the hooks in `hooks.groups.<name>`",
+            ),
+        ],
+        example: "[symlinks]\n\"gnupg/gpg.conf\" = { target = \"~/.gnupg/gpg.conf\", optional = true, pin_sha256 = \"3f9a\", priority = 10, mode = \"copy\", chmod = \"600\", owner = \"alice\", when = { arch = \"aarch64\" }, group = \"gnupg\" }\n",
+    },
+    SectionDoc {
+        table: "symlinks.<source>.when",
+        description: "The machines a symlink entry applies on; every key given must hold.",
+        fields: &[
+            field(
+                "hostname",
+                "string",
+                "none",
+                "Machine name, with or without its domain; case does not matter",
+            ),
+            field(
+                "arch",
+                "string",
+                "none",
+                "CPU architecture: \"x86_64\" (or \"amd64\"), \"aarch64\" (or \"arm64\"), ...",
+            ),
+            field(
+                "env",
+                "string",
+                "none",
+                "\"NAME\" for a variable that is set and not empty, \"NAME=value\" for a value",
+            ),
+        ],
+        example: "[symlinks]\n\"wsl/.bashrc\" = { target = \"~/.bashrc\", when = { hostname = \"work-laptop\", arch = \"x86_64\", env = \"WSL_DISTRO_NAME\" } }\n",
+    },
+    SectionDoc {
+        table: "scripts.deps",
+        description: "Dependency scripts run by `dotf install deps` on each platform.",
+        fields: &[
+            field("macos", "string", "none", "Script path for macOS"),
+            field("linux", "string", "none", "Script path for Linux"),
+        ],
+        example: "[scripts.deps]\nmacos = \"scripts/deps-macos.sh\"\nlinux = \"scripts/deps-linux.sh\"\n",
+    },
+    SectionDoc {
+        table: "scripts.custom",
+        description: "Named scripts run by `dotf install custom <name>`.",
+        fields: &[field("<name>", "string", "none", "Script path in the repository")],
+        example: "[scripts.custom]\nsetup-vim = \"scripts/setup-vim.sh\"\n",
+    },
+    SectionDoc {
+        table: "scripts.limits.<path>",
+        description: "Resource limits for one script, over the limits in local settings.",
+        fields: &[
+            field(
+                "nice",
+                "integer",
+                "none",
+                "Scheduling priority passed to `nice -n`",
+            ),
+            field(
+                "max_runtime_secs",
+                "integer",
+                "none",
+                "Stop the script after this many seconds",
+            ),
+            field(
+                "max_output_bytes",
+                "integer",
+                "none",
+                "Keep at most this many bytes of stdout and of stderr each",
+            ),
+        ],
+        example: "[scripts.limits.\"scripts/setup-vim.sh\"]\nnice = 10\nmax_runtime_secs = 600\nmax_output_bytes = 65536\n",
+    },
+    SectionDoc {
+        table: "scripts.run",
+        description: "When a script runs again, by script path; the last successful run is kept in ~/.dotf/state.json.",
+        fields: &[field(
+            "<path>",
+            "string",
+            "\"always\"",
+            "`always`, `once` per machine, or `on-change` when the script's content changed",
+        )],
+        example: "[scripts.run]\n\"scripts/ssh-keygen.sh\" = \"once\"\n\"scripts/setup-vim.sh\" = \"on-change\"\n",
+    },
+    SectionDoc {
+        table: "scripts.env.<path>",
+        description: "Extra environment variables for one script, over dotf's own environment.",
+        fields: &[field("<NAME>", "string", "none", "Value of the variable")],
+        example: "[scripts.env.\"scripts/deploy.sh\"]\nDEPLOY_TARGET = \"staging\"\n",
+    },
+    SectionDoc {
+        table: "scripts.working_dir",
+        description: "Directory a script runs in, by script path; scripts not listed run where dotf was started.",
+        fields: &[field(
+            "<path>",
+            "string",
+            "none",
+            "Directory relative to the repository, or an absolute path",
+        )],
+        example: "[scripts.working_dir]\n\"scripts/deploy.sh\" = \"site\"\n",
+    },
+    SectionDoc {
+        table: "platform.macos.symlinks",
+        description: "Symlinks added on macOS only; `platform.linux.symlinks` and `platform.windows.symlinks` work the same on Linux and Windows.",
+        fields: &[field(
+            "<source>",
+            "string or table",
+            "none",
+            "Like an entry in `[symlinks]`",
+        )],
+        example: "[platform.macos.symlinks]\n\"macos/karabiner\" = \"~/.config/karabiner\"\n",
+    },
+    SectionDoc {
+        table: "profile.<name>",
+        description: "Symlinks and scripts for one kind of machine, merged over the base config after `dotf profile use <name>`.",
+        fields: &[
+            field(
+                "symlinks",
+                "table",
+                "{}",
+                "Like `[symlinks]`; replaces base entries with the same source or target",
+            ),
+            field(
+                "scripts",
+                "table",
+                "{}",
+                "Like `[scripts]`; replaces deps scripts and custom scripts with the same name",
+            ),
+        ],
+        example: "[profile.work.symlinks]\n\"work/.gitconfig\" = \"~/.gitconfig\"\n\n[profile.work.scripts.custom]\nvpn = \"work/vpn.sh\"\n",
+    },
+    SectionDoc {
+        table: "tools.<name>",
+        description: "Release binaries downloaded by `dotf install tools`.",
+        fields: &[
+            field("repo", "string", "required", "Project path, e.g. `BurntSushi/ripgrep`"),
+            field("host", "string", "github", "`github` or `gitlab`"),
+            field("version", "string", "latest", "Release tag to install, or `latest`"),
+            field(
+                "asset",
+                "string",
+                "none",
+                "Asset name pattern with `*` wildcards and `{version}`/`{tag}` placeholders",
+            ),
+            field("bin", "string", "the tool name", "Executable name inside the asset"),
+            field(
+                "install_path",
+                "string",
+                "~/.local/bin",
+                "Directory the executable is installed into",
+            ),
+            field(
+                "checksums",
+                "string",
+                "none",
+                "Checksums file published with the release, e.g. `SHA256SUMS`",
+            ),
+            field(
+                "sha256",
+                "table",
+                "none",
+                "Expected SHA-256 of the asset keyed by `<os>-<arch>`",
+            ),
+        ],
+        example: "[tools.rg]\nrepo = \"BurntSushi/ripgrep\"\nhost = \"github\"\nversion = \"14.1.0\"\nasset = \"ripgrep-{version}-*\"\nbin = \"rg\"\ninstall_path = \"~/.local/bin\"\nchecksums = \"SHA256SUMS\"\nsha256 = { linux-x86_64 = \"4cf9\" }\n",
+    },
+    SectionDoc {
+        table: "bootstrap",
+        description: "Editor and tmux plugin managers set up after `dotf install config`.",
+        fields: &[
+            field("nvim", "string", "none", "`lazy`, `packer` or `vim-plug`"),
+            field("vim", "string", "none", "`vim-plug`"),
+            field("tmux", "string", "none", "`tpm`"),
+            field("timeout", "integer", "300", "Seconds each step may run"),
+        ],
+        example: "[bootstrap]\nnvim = \"lazy\"\nvim = \"vim-plug\"\ntmux = \"tpm\"\ntimeout = 300\n",
+    },
+    SectionDoc {
+        table: "macos.defaults.<domain>",
+        description: "`defaults write` values applied on macOS; the TOML type picks the value type.",
+        fields: &[field(
+            "<key>",
+            "bool, integer, float or string",
+            "none",
+            "Value written for `<key>` in `<domain>`",
+        )],
+        example: "[macos.defaults.\"com.apple.dock\"]\nautohide = true\ntilesize = 48\n",
+    },
+    SectionDoc {
+        table: "cron",
+        description: "Entries kept in a managed block of the user crontab.",
+        fields: &[field(
+            "<name>",
+            "string",
+            "none",
+            "Schedule and command, e.g. `0 3 * * * ~/bin/backup.sh`",
+        )],
+        example: "[cron]\nbrew-update = \"@weekly brew update\"\n",
+    },
+    SectionDoc {
+        table: "env",
+        description: "Environment written to `~/.config/dotf/env.sh`; `env.macos` and `env.linux` add to it per platform.",
+        fields: &[
+            field("path", "array of strings", "[]", "Directories prepended to `PATH`, in order"),
+            field("vars", "table", "{}", "Exported variables"),
+        ],
+        example: "[env]\npath = [\"~/.local/bin\"]\nvars = { EDITOR = \"nvim\" }\n",
+    },
+    SectionDoc {
+        table: "hooks",
+        description: "Scripts run around `dotf install` and `dotf sync` with `DOTF_HOOK`, `DOTF_REPO` and the `[prompts]` answers set; each is a repository path or `{ script, timeout_secs, on_failure }`.",
+        fields: &[
+            field(
+                "pre_install",
+                "string or table",
+                "none",
+                "Runs before `dotf install`, with `DOTF_INSTALL_TARGET` set",
+            ),
+            field("post_install", "string or table", "none", "Runs after a successful `dotf install`"),
+            field("pre_sync", "string or table", "none", "Runs before `dotf sync` pulls"),
+            field(
+                "post_sync",
+                "string or table",
+                "none",
+                "Runs after a successful pull, with `DOTF_SYNC_COMMITS` set",
+            ),
+        ],
+        example: "[hooks]\npre_install = \"hooks/check.sh\"\npost_install = \"hooks/caches.sh\"\npre_sync = { script = \"hooks/stash.sh\", timeout_secs = 30 }\npost_sync = { script = \"hooks/caches.sh\", on_failure = \"continue\" }\n",
+    },
+    SectionDoc {
+        table: "hooks.groups.<name>",
+        description: "Scripts run before and after `dotf install config`, `dotf uninstall` and `dotf repair` handle the links of one group, with `DOTF_GROUP` and `DOTF_OPERATION` set. A failing group is reported and the other groups carry on.",
+        fields: &[
+            field(
+                "pre",
+                "string or table",
+                "none",
+                "Runs before the group's links; if it fails (and `on_failure` is not \"continue\") they are left alone",
+            ),
+            field("post", "string or table", "none", "Runs after the group's links"),
+        ],
+        example: "[symlinks]\n\"karabiner\" = { target = \"~/.config/karabiner\", group = \"karabiner\" }\n\n[hooks.groups.karabiner]\npre = \"scripts/karabiner-quit.sh\"\npost = { script = \"scripts/karabiner-start.sh\", on_failure = \"continue\" }\n",
+    },
+    SectionDoc {
+        table: "prompts.<name>",
+        description: "Values asked once per machine and kept in local settings.",
+        fields: &[
+            field("message", "string", "required", "Question shown to the user"),
+            field("default", "string", "none", "Suggested answer"),
+        ],
+        example: "[prompts.git_email]\nmessage = \"Email for git commits\"\ndefault = \"me@example.com\"\n",
+    },
+    SectionDoc {
+        table: "ssh",
+        description: "`~/.ssh` content that is merged, appended or copied rather than linked.",
+        fields: &[
+            field(
+                "config",
+                "array of strings",
+                "[]",
+                "Files merged into a managed block at the top of `~/.ssh/config`",
+            ),
+            field(
+                "known_hosts",
+                "array of strings",
+                "[]",
+                "Files whose host keys are appended to `~/.ssh/known_hosts`",
+            ),
+            field(
+                "keys",
+                "table",
+                "{}",
+                "Private keys copied with mode 600: repository path to target",
+            ),
+        ],
+        example: "[ssh]\nconfig = [\"ssh/config\"]\nknown_hosts = [\"ssh/known_hosts\"]\nkeys = { \"ssh/id_ed25519\" = \"~/.ssh/id_ed25519\" }\n",
+    },
+    SectionDoc {
+        table: "secrets",
+        description: "Files stored encrypted in the repository and decrypted with mode 600 by `dotf install config` or `dotf secrets decrypt`; they are never linked.",
+        fields: &[
+            field("backend", "string", "age", "`age` or `gpg`"),
+            field(
+                "recipients",
+                "array of strings",
+                "[]",
+                "age public keys or gpg key IDs that `dotf secrets encrypt` encrypts to",
+            ),
+            field(
+                "identity",
+                "string",
+                "~/.config/age/keys.txt",
+                "age key file used to decrypt; gpg asks its agent instead",
+            ),
+            field(
+                "files",
+                "table",
+                "{}",
+                "Encrypted repository path to target",
+            ),
+        ],
+        example: "[secrets]\nbackend = \"age\"\nrecipients = [\"age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p\"]\nidentity = \"~/.config/age/keys.txt\"\nfiles = { \"secrets/netrc.age\" = \"~/.netrc\" }\n",
+    },
+    SectionDoc {
+        table: "packages",
+        description: "System packages installed by `dotf install packages`; managers missing on a machine, or kinds listed in `[packages] disabled` of settings.toml, are skipped.",
+        fields: &[
+            field("brew", "array of strings", "[]", "Homebrew formulae"),
+            field("cask", "array of strings", "[]", "Homebrew casks, macOS only"),
+            field(
+                "mas",
+                "table",
+                "{}",
+                "Mac App Store apps: name to numeric app id, installed with `mas`",
+            ),
+            field(
+                "flatpak",
+                "array of strings or tables",
+                "[]",
+                "Flatpak application ids from flathub, or `{ id, remote }` for another remote",
+            ),
+            field(
+                "flatpak_remotes",
+                "table",
+                "{}",
+                "`.flatpakrepo` URLs of remotes other than flathub, added before installing",
+            ),
+            field("snap", "array of strings", "[]", "Snap names, installed through sudo"),
+        ],
+        example: "[packages]\nbrew = [\"ripgrep\"]\ncask = [\"iterm2\"]\nmas = { Xcode = 497799835 }\nflatpak = [\"org.mozilla.firefox\", { id = \"org.gimp.GIMP\", remote = \"flathub-beta\" }]\nflatpak_remotes = { flathub-beta = \"https://flathub.org/beta-repo/flathub-beta.flatpakrepo\" }\nsnap = [\"spotify\"]\n",
+    },
+    SectionDoc {
+        table: "package_managers.custom.<name>",
+        description: "A package manager driven by shell commands; list its packages as `<name> = [...]` in `[packages]`.",
+        fields: &[
+            field(
+                "check",
+                "string",
+                "required",
+                "Command that succeeds when the manager is usable on this machine",
+            ),
+            field(
+                "list",
+                "string",
+                "required",
+                "Command printing installed packages, one per line; the first word is the name",
+            ),
+            field(
+                "install",
+                "string",
+                "required",
+                "Command installing one package; `{package}` is replaced by its quoted name",
+            ),
+        ],
+        example: "[packages]\npipx = [\"black\"]\n\n[package_managers.custom.pipx]\ncheck = \"pipx --version\"\nlist = \"pipx list --short\"\ninstall = \"pipx install {package}\"\n",
+    },
+];
+
+/// Sections whose table is `filter` or lies below it; all sections without a filter
+pub fn sections(filter: Option<&str>) -> Vec<&'static SectionDoc> {
+    SECTIONS
+        .iter()
+        .filter(|section| {
+            filter.is_none_or(|filter| {
+                section.table == filter
+                    || section
+                        .table
+                        .strip_prefix(filter)
+                        .is_some_and(|rest| rest.starts_with('.'))
+            })
+        })
+        .collect()
+}
+
+/// The reference as Markdown, one heading and field table per section
+pub fn render_markdown(sections: &[&SectionDoc]) -> String {
+    let mut output = String::from("# dotf.toml reference\n");
+    for section in sections {
+        output.push_str(&format!(
+            "\n## `[{}]`\n\n{}\n\n| Field | Type | Default | Description |\n| --- | --- | --- | --- |\n",
+            section.table, section.description
+        ));
+        for field in section.fields {
+            output.push_str(&format!(
+                "| `{}` | {} | {} | {} |\n",
+                field.name, field.type_name, field.default, field.description
+            ));
+        }
+        output.push_str(&format!("\n```toml\n{}```\n", section.example));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::DotfConfig;
+
+    #[test]
+    fn test_examples_cover_documented_fields() {
+        for section in SECTIONS {
+            let config: DotfConfig = toml::from_str(section.example)
+                .unwrap_or_else(|e| panic!("[{}] example: {}", section.table, e));
+            let mut value = toml::Value::try_from(&config).unwrap();
+
+            for segment in section.table.split('.') {
+                let table = value
+                    .as_table()
+                    .unwrap_or_else(|| panic!("[{}] is not a table", section.table));
+                let next = if segment.starts_with('<') {
+                    table.values().next()
+                } else {
+                    table.get(segment)
+                };
+                value = next
+                    .unwrap_or_else(|| panic!("[{}] missing after parsing", section.table))
+                    .clone();
+            }
+
+            let table = value.as_table().unwrap();
+            for field in section.fields {
+                if field.name.starts_with('<') {
+                    assert!(!table.is_empty(), "[{}] has no entries", section.table);
+                } else {
+                    assert!(
+                        table.contains_key(field.name),
+                        "[{}] {} is not a config field",
+                        section.table,
+                        field.name
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_sections_filter() {
+        let tables: Vec<&str> = sections(Some("scripts"))
+            .iter()
+            .map(|section| section.table)
+            .collect();
+        assert_eq!(
+            tables,
+            vec![
+                "scripts.deps",
+                "scripts.custom",
+                "scripts.limits.<path>",
+                "scripts.run",
+                "scripts.env.<path>",
+                "scripts.working_dir"
+            ]
+        );
+        assert!(sections(Some("script")).is_empty());
+    }
+}
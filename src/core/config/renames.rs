@@ -0,0 +1,182 @@
+//! Following files the repository moved: dotf.toml sources a pull renamed are pointed at
+//! their new paths, with the rest of the file left as it was
+
+use toml_edit::{DocumentMut, Item, Key, Table};
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::repository::Rename;
+
+/// Where `source` went: the path a rename moved it to, or for a directory whose files
+/// all moved into one new directory, that directory
+pub fn renamed_source(source: &str, renames: &[Rename]) -> Option<String> {
+    let source = source.trim_end_matches('/');
+    if let Some(rename) = renames.iter().find(|rename| rename.from == source) {
+        return Some(rename.to.clone());
+    }
+
+    let prefix = format!("{}/", source);
+    let mut directories = renames
+        .iter()
+        .filter_map(|rename| {
+            let rest = rename.from.strip_prefix(&prefix)?;
+            Some(
+                rename
+                    .to
+                    .strip_suffix(rest)
+                    .and_then(|directory| directory.strip_suffix('/')),
+            )
+        })
+        .peekable();
+    let first = (*directories.peek()?)?;
+    directories
+        .all(|directory| directory == Some(first))
+        .then(|| first.to_string())
+}
+
+/// Every source in `[symlinks]`, `[platform.*.symlinks]` and `[profile.*.symlinks]`
+pub fn config_sources(content: &str) -> DotfResult<Vec<String>> {
+    let mut document = parse(content)?;
+    let mut sources = Vec::new();
+    for table in symlink_tables(&mut document) {
+        sources.extend(table.iter().map(|(source, _)| source.to_string()));
+    }
+    sources.sort();
+    sources.dedup();
+    Ok(sources)
+}
+
+/// Rename the sources `moved` lists wherever they appear, keeping each entry's place,
+/// value and comments. A table that already has the new source keeps both entries as
+/// they are.
+pub fn rename_sources(content: &str, moved: &[Rename]) -> DotfResult<String> {
+    let mut document = parse(content)?;
+    for table in symlink_tables(&mut document) {
+        let applicable: Vec<&Rename> = moved
+            .iter()
+            .filter(|rename| table.contains_key(&rename.from) && !table.contains_key(&rename.to))
+            .collect();
+        if applicable.is_empty() {
+            continue;
+        }
+
+        let entries: Vec<(Key, Item)> = table
+            .iter()
+            .filter_map(|(source, _)| table.get_key_value(source))
+            .map(|(key, item)| (key.clone(), item.clone()))
+            .collect();
+        table.clear();
+        for (key, item) in entries {
+            let key = match applicable.iter().find(|rename| rename.from == key.get()) {
+                Some(rename) => {
+                    Key::new(rename.to.as_str()).with_leaf_decor(key.leaf_decor().clone())
+                }
+                None => key,
+            };
+            table.insert_formatted(&key, item);
+        }
+    }
+    Ok(document.to_string())
+}
+
+fn parse(content: &str) -> DotfResult<DocumentMut> {
+    content
+        .parse()
+        .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))
+}
+
+fn symlink_tables(document: &mut DocumentMut) -> Vec<&mut Table> {
+    let mut tables = Vec::new();
+    for (name, item) in document.iter_mut() {
+        match name.get() {
+            "symlinks" => tables.extend(item.as_table_mut()),
+            "platform" | "profile" => {
+                let Some(sections) = item.as_table_mut() else {
+                    continue;
+                };
+                for (_, section) in sections.iter_mut() {
+                    tables.extend(section.get_mut("symlinks").and_then(Item::as_table_mut));
+                }
+            }
+            _ => {}
+        }
+    }
+    tables
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn renames(pairs: &[(&str, &str)]) -> Vec<Rename> {
+        pairs
+            .iter()
+            .map(|(from, to)| Rename {
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_renamed_source() {
+        let moved = renames(&[
+            ("vim/vimrc", "nvim/init.vim"),
+            ("alacritty/alacritty.yml", "term/alacritty/alacritty.yml"),
+            (
+                "alacritty/themes/dark.yml",
+                "term/alacritty/themes/dark.yml",
+            ),
+            ("tmux/a", "tmux-new/a"),
+            ("tmux/b", "elsewhere/b"),
+        ]);
+        assert_eq!(
+            renamed_source("vim/vimrc", &moved).as_deref(),
+            Some("nvim/init.vim")
+        );
+        assert_eq!(
+            renamed_source("alacritty/", &moved).as_deref(),
+            Some("term/alacritty")
+        );
+        assert_eq!(renamed_source("tmux", &moved), None);
+        assert_eq!(renamed_source("zsh/.zshrc", &moved), None);
+    }
+
+    #[test]
+    fn test_rename_sources_keeps_layout() {
+        let content = r#"# My dotfiles
+[symlinks]
+"zsh/.zshrc" = "~/.zshrc"
+"vim/vimrc" = "~/.vimrc"   # editor
+"git/gitconfig" = "~/.gitconfig"
+
+[platform.macos.symlinks]
+"vim/vimrc" = { target = "~/.vimrc", optional = true }
+
+[profile.work.symlinks]
+"vim/vimrc" = "~/.vimrc"
+"nvim/init.vim" = "~/.config/nvim/init.vim"
+"#;
+        assert_eq!(
+            config_sources(content).unwrap(),
+            vec!["git/gitconfig", "nvim/init.vim", "vim/vimrc", "zsh/.zshrc"]
+        );
+
+        let updated = rename_sources(content, &renames(&[("vim/vimrc", "nvim/init.vim")])).unwrap();
+        assert_eq!(
+            updated,
+            r#"# My dotfiles
+[symlinks]
+"zsh/.zshrc" = "~/.zshrc"
+"nvim/init.vim" = "~/.vimrc"   # editor
+"git/gitconfig" = "~/.gitconfig"
+
+[platform.macos.symlinks]
+"nvim/init.vim" = { target = "~/.vimrc", optional = true }
+
+[profile.work.symlinks]
+"vim/vimrc" = "~/.vimrc"
+"nvim/init.vim" = "~/.config/nvim/init.vim"
+"#
+        );
+    }
+}
@@ -1,11 +1,60 @@
+use crate::core::scripts::ScriptLimits;
 use crate::error::DotfResult;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Settings {
     pub repository: Repository,
     pub last_sync: Option<chrono::DateTime<chrono::Utc>>,
     pub initialized_at: chrono::DateTime<chrono::Utc>,
+    #[serde(default, skip_serializing_if = "CommitSettings::is_empty")]
+    pub commit: CommitSettings,
+    /// Answers to `[prompts]` from dotf.toml, by prompt name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub prompts: BTreeMap<String, String>,
+    /// Resource limits for every script run on this machine
+    #[serde(default, skip_serializing_if = "ScriptLimits::is_empty")]
+    pub scripts: ScriptLimits,
+    /// User-defined command shortcuts, e.g. `s = "status --quiet"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Status bar segments printed by `dotf status --widget`
+    #[serde(default, skip_serializing_if = "WidgetSettings::is_empty")]
+    pub widget: WidgetSettings,
+    /// `[profile.<name>]` of dotf.toml merged over the base config on this machine
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+    /// Which `[packages]` kinds this machine installs
+    #[serde(default, skip_serializing_if = "PackageSettings::is_empty")]
+    pub packages: PackageSettings,
+    /// Link targets from dotf.toml that this machine never links
+    #[serde(default, skip_serializing_if = "SymlinkSettings::is_empty")]
+    pub symlinks: SymlinkSettings,
+    /// How `dotf diff` shows files
+    #[serde(default, skip_serializing_if = "DiffSettings::is_empty")]
+    pub diff: DiffSettings,
+    /// Log file written under `~/.dotf/logs/`
+    #[serde(default, skip_serializing_if = "LogSettings::is_empty")]
+    pub logging: LogSettings,
+    /// Backups kept in `~/.dotf/backups/` after an install replaces files
+    #[serde(default, skip_serializing_if = "BackupSettings::is_empty")]
+    pub backups: BackupSettings,
+    /// Whether this machine's connection is treated as metered
+    #[serde(default, skip_serializing_if = "NetworkSettings::is_empty")]
+    pub network: NetworkSettings,
+    /// Placeholders for symlink targets (`[variables]`), e.g. `work_dir = "~/src/work"` for
+    /// `{work_dir}/.envrc`; they override the built-in ones of the same name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    /// Overlay repositories cloned under `~/.dotf/repos/<name>`, in order of precedence:
+    /// their links replace links to the same target from `repository` and from the
+    /// overlays listed before them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub repositories: Vec<NamedRepository>,
+    /// Experimental features turned on or off for this user, e.g. `changes = true`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub features: BTreeMap<String, bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone, Default)]
@@ -13,6 +62,221 @@ pub struct Repository {
     pub remote: String,
     pub branch: Option<String>,
     pub local: Option<String>,
+    /// Check out only the paths dotf.toml references for this machine
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sparse: bool,
+}
+
+/// An overlay repository layered over the main one
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct NamedRepository {
+    /// Name used by `--repo` and for the checkout under `~/.dotf/repos/`
+    pub name: String,
+    pub remote: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+}
+
+/// How commits made by dotf on this machine are worded
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct CommitSettings {
+    /// Message with `{message}`, `{hostname}`, `{date}`, `{files}` and `{count}` placeholders
+    pub message_template: Option<String>,
+    /// Conventional-commit prefix such as `chore(dotfiles)`, joined with `": "`
+    pub prefix: Option<String>,
+    /// Always sign commits (`git commit -S`), even if the repository does not ask for it
+    #[serde(default)]
+    pub sign: bool,
+}
+
+/// Templates and cache lifetime for `dotf status --widget`
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct WidgetSettings {
+    /// tmux segment with `{color}`, `{summary}`, `{state}`, `{behind}`, `{ahead}`,
+    /// `{issues}` and `{modified}` placeholders
+    pub tmux: Option<String>,
+    /// Waybar `text` with the same placeholders
+    pub waybar: Option<String>,
+    /// Seconds after which the cached status is refreshed in the background
+    pub max_age: Option<u64>,
+}
+
+impl WidgetSettings {
+    pub fn is_empty(&self) -> bool {
+        self.tmux.is_none() && self.waybar.is_none() && self.max_age.is_none()
+    }
+}
+
+/// Package kinds turned off on this machine, e.g. for users who refuse snaps
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct PackageSettings {
+    /// `[packages]` kinds that are reported but never installed, e.g. `["snap"]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+}
+
+impl PackageSettings {
+    pub fn is_empty(&self) -> bool {
+        self.disabled.is_empty()
+    }
+
+    pub fn is_enabled(&self, kind: &str) -> bool {
+        !self.disabled.iter().any(|disabled| disabled == kind)
+    }
+}
+
+/// Link targets turned off on this machine, e.g. no `.gitconfig` override on a server
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct SymlinkSettings {
+    /// Targets such as `~/.gitconfig`; a directory also covers the files linked inside it
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled: Vec<String>,
+}
+
+impl SymlinkSettings {
+    pub fn is_empty(&self) -> bool {
+        self.disabled.is_empty()
+    }
+
+    /// Whether the absolute `target_path` is, or is inside, a disabled target
+    pub fn is_disabled(&self, target_path: &str) -> bool {
+        self.disabled.iter().any(|disabled| {
+            let disabled = expand_home(disabled);
+            let disabled = disabled.trim_end_matches('/');
+            target_path == disabled || target_path.starts_with(&format!("{}/", disabled))
+        })
+    }
+}
+
+/// External comparison for files too large or too long-lined for the terminal
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct DiffSettings {
+    /// Command given the old and new file as its last two arguments, e.g. `code --diff --wait`
+    pub tool: Option<String>,
+}
+
+impl DiffSettings {
+    pub fn is_empty(&self) -> bool {
+        self.tool.is_none()
+    }
+}
+
+/// How dotf decides that the connection is metered
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MeteredMode {
+    /// Ask NetworkManager where it runs; otherwise assume unmetered
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct NetworkSettings {
+    /// On a metered connection sync fetches as little as it can and tool and package
+    /// downloads wait for an unmetered run
+    #[serde(default)]
+    pub metered: MeteredMode,
+}
+
+impl NetworkSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Whether dotf keeps a log file on this machine, and how much of it
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct LogSettings {
+    /// Write a daily log file under `~/.dotf/logs/`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub file: bool,
+    /// Level or filter for the log file, e.g. `debug` (the default) or `dotf=trace`
+    pub level: Option<String>,
+    /// Log files kept before the oldest is removed (default 7)
+    pub max_files: Option<usize>,
+}
+
+impl LogSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Retention of the files `dotf install` backs up, applied after every install that
+/// creates backups
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq)]
+pub struct BackupSettings {
+    /// Remove backups older than this many days
+    pub keep_days: Option<u64>,
+    /// Keep at most this many backups, newest first
+    pub keep_last: Option<usize>,
+}
+
+impl BackupSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+/// Values substituted into a commit message template
+#[derive(Debug, Clone)]
+pub struct CommitContext {
+    /// The message dotf would have used without a template
+    pub message: String,
+    pub hostname: String,
+    pub date: String,
+    pub files: Vec<String>,
+}
+
+impl CommitContext {
+    pub fn current(message: &str, files: &[String]) -> Self {
+        Self {
+            message: message.to_string(),
+            hostname: crate::utils::platform::hostname(),
+            date: chrono::Local::now().format("%Y-%m-%d").to_string(),
+            files: files.to_vec(),
+        }
+    }
+}
+
+impl CommitSettings {
+    pub fn is_empty(&self) -> bool {
+        self.message_template.is_none() && self.prefix.is_none() && !self.sign
+    }
+
+    /// Render the commit message for `context`; without a template the
+    /// default message is used, and the prefix is added unless already present
+    pub fn render(&self, context: &CommitContext) -> String {
+        let message = match &self.message_template {
+            Some(template) => template
+                .replace("{message}", &context.message)
+                .replace("{hostname}", &context.hostname)
+                .replace("{date}", &context.date)
+                .replace("{files}", &context.files.join(", "))
+                .replace("{count}", &context.files.len().to_string()),
+            None => context.message.clone(),
+        };
+
+        match self
+            .prefix
+            .as_deref()
+            .map(|prefix| prefix.trim().trim_end_matches(':'))
+        {
+            Some(prefix) if !prefix.is_empty() && !message.starts_with(prefix) => {
+                format!("{}: {}", prefix, message)
+            }
+            _ => message,
+        }
+    }
 }
 
 impl Default for Settings {
@@ -21,6 +285,21 @@ impl Default for Settings {
             repository: Repository::default(),
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: CommitSettings::default(),
+            prompts: BTreeMap::new(),
+            scripts: ScriptLimits::default(),
+            aliases: BTreeMap::new(),
+            widget: WidgetSettings::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         }
     }
 }
@@ -32,9 +311,25 @@ impl Settings {
                 remote: repository_url.to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: CommitSettings::default(),
+            prompts: BTreeMap::new(),
+            scripts: ScriptLimits::default(),
+            aliases: BTreeMap::new(),
+            widget: WidgetSettings::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         }
     }
 
@@ -48,9 +343,25 @@ impl Settings {
                 remote: repository_url.to_string(),
                 branch,
                 local: local_path,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: CommitSettings::default(),
+            prompts: BTreeMap::new(),
+            scripts: ScriptLimits::default(),
+            aliases: BTreeMap::new(),
+            widget: WidgetSettings::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         }
     }
 
@@ -99,4 +410,37 @@ mod tests {
         assert_eq!(settings.repository.local, deserialized.repository.local);
         assert_eq!(settings.last_sync, deserialized.last_sync);
     }
+
+    #[test]
+    fn test_commit_template_rendering() {
+        let context = CommitContext {
+            message: "Adopt local changes to zsh/.zshrc".to_string(),
+            hostname: "laptop".to_string(),
+            date: "2024-05-01".to_string(),
+            files: vec!["zsh/.zshrc".to_string()],
+        };
+
+        assert_eq!(
+            CommitSettings::default().render(&context),
+            "Adopt local changes to zsh/.zshrc"
+        );
+
+        let settings = Settings::from_toml(
+            r#"initialized_at = "2024-05-01T00:00:00Z"
+
+[repository]
+remote = "https://github.com/user/dotfiles.git"
+
+[commit]
+message_template = "sync {count} file(s) from {hostname} on {date}: {files}"
+prefix = "chore(dotfiles):"
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            settings.commit.render(&context),
+            "chore(dotfiles): sync 1 file(s) from laptop on 2024-05-01: zsh/.zshrc"
+        );
+        assert!(!Settings::new("url").to_toml().unwrap().contains("[commit]"));
+    }
 }
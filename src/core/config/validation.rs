@@ -55,9 +55,21 @@ mod tests {
 
     fn create_valid_config() -> DotfConfig {
         DotfConfig {
-            symlinks: HashMap::new(),
+            symlinks: HashMap::new().into(),
             scripts: ScriptsConfig::default(),
             platform: PlatformConfig::default(),
+            tools: Default::default(),
+            bootstrap: Default::default(),
+            macos: Default::default(),
+            cron: Default::default(),
+            env: Default::default(),
+            prompts: Default::default(),
+            ssh: Default::default(),
+            secrets: Default::default(),
+            hooks: Default::default(),
+            packages: Default::default(),
+            package_managers: Default::default(),
+            profile: Default::default(),
         }
     }
 
@@ -0,0 +1,134 @@
+//! Placeholders in symlink targets, like `{vscode_user_dir}/settings.json`, resolved per
+//! platform from a built-in table and the `[variables]` of settings.toml
+
+use std::collections::BTreeMap;
+
+use super::Settings;
+use crate::error::{DotfError, DotfResult};
+use crate::utils::platform::detect_platform;
+
+/// The variables every machine has, with the path each stands for on `platform`
+pub fn builtin_variables(platform: &str) -> BTreeMap<String, String> {
+    let (config_dir, data_dir, cache_dir) = match platform {
+        "macos" => (
+            "~/Library/Application Support",
+            "~/Library/Application Support",
+            "~/Library/Caches",
+        ),
+        "windows" => ("~/AppData/Roaming", "~/AppData/Roaming", "~/AppData/Local"),
+        _ => ("~/.config", "~/.local/share", "~/.cache"),
+    };
+    BTreeMap::from([
+        ("config_dir".to_string(), config_dir.to_string()),
+        ("data_dir".to_string(), data_dir.to_string()),
+        ("cache_dir".to_string(), cache_dir.to_string()),
+        (
+            "vscode_user_dir".to_string(),
+            format!("{}/Code/User", config_dir),
+        ),
+    ])
+}
+
+/// The built-in variables of this platform, overridden and extended by `[variables]`
+pub fn target_variables(settings: &Settings) -> BTreeMap<String, String> {
+    let mut variables = builtin_variables(&detect_platform());
+    variables.extend(settings.variables.clone());
+    variables
+}
+
+/// Replace every `{name}` in `target` with its value. Braces around anything but a name
+/// are left alone; a name that is not defined is an error.
+pub fn expand_target(target: &str, variables: &BTreeMap<String, String>) -> DotfResult<String> {
+    let mut expanded = String::with_capacity(target.len());
+    let mut rest = target;
+    while let Some(start) = rest.find('{') {
+        expanded.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(name) = after
+            .find('}')
+            .map(|end| &after[..end])
+            .filter(|name| is_name(name))
+        else {
+            expanded.push('{');
+            rest = after;
+            continue;
+        };
+        let value = variables.get(name).ok_or_else(|| {
+            DotfError::Config(format!(
+                "Unknown variable '{{{}}}' in target '{}'; define it under [variables] in settings.toml",
+                name, target
+            ))
+        })?;
+        expanded.push_str(value);
+        rest = &after[name.len() + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::DotfConfig;
+
+    #[test]
+    fn test_expand_target() {
+        let mut variables = builtin_variables("macos");
+        variables.insert("work_dir".to_string(), "~/src/work".to_string());
+
+        assert_eq!(
+            expand_target("{vscode_user_dir}/settings.json", &variables).unwrap(),
+            "~/Library/Application Support/Code/User/settings.json"
+        );
+        assert_eq!(
+            expand_target("{work_dir}/.envrc", &variables).unwrap(),
+            "~/src/work/.envrc"
+        );
+        assert_eq!(
+            expand_target("~/.config/{ not a name }/x", &variables).unwrap(),
+            "~/.config/{ not a name }/x"
+        );
+        assert_eq!(
+            builtin_variables("linux")["vscode_user_dir"],
+            "~/.config/Code/User"
+        );
+
+        let error = expand_target("{missing}/x", &variables).unwrap_err();
+        assert!(error.to_string().contains("{missing}"));
+    }
+
+    #[test]
+    fn test_expand_config_targets() {
+        let mut config: DotfConfig = toml::from_str(
+            r#"
+[symlinks]
+"vscode/settings.json" = "{vscode_user_dir}/settings.json"
+
+[platform.linux.symlinks]
+"nvim" = "{config_dir}/nvim"
+
+[profile.work.symlinks]
+"work/envrc" = "{work_dir}/.envrc"
+"#,
+        )
+        .unwrap();
+        let mut variables = builtin_variables("linux");
+        variables.insert("work_dir".to_string(), "~/src/work".to_string());
+        config.expand_targets(&variables).unwrap();
+
+        assert_eq!(
+            config.symlinks["vscode/settings.json"],
+            "~/.config/Code/User/settings.json"
+        );
+        let linux = config.platform.for_platform("linux").unwrap();
+        assert_eq!(linux.symlinks["nvim"], "~/.config/nvim");
+        assert_eq!(
+            config.profile["work"].symlinks["work/envrc"],
+            "~/src/work/.envrc"
+        );
+    }
+}
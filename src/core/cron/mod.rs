@@ -0,0 +1,222 @@
+use async_trait::async_trait;
+use std::collections::BTreeMap;
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::crontab::Crontab;
+
+pub const BEGIN_MARKER: &str = "# >>> dotf managed entries >>>";
+pub const END_MARKER: &str = "# <<< dotf managed entries <<<";
+
+const SCHEDULE_KEYWORDS: &[&str] = &[
+    "@reboot",
+    "@yearly",
+    "@annually",
+    "@monthly",
+    "@weekly",
+    "@daily",
+    "@midnight",
+    "@hourly",
+];
+
+/// Lines between the dotf markers, or `None` if the crontab has no managed block
+pub fn managed_block(content: &str) -> Option<String> {
+    let mut lines = content
+        .lines()
+        .skip_while(|line| line.trim() != BEGIN_MARKER);
+    lines.next()?;
+    let block: Vec<&str> = lines.take_while(|line| line.trim() != END_MARKER).collect();
+    Some(block.join("\n"))
+}
+
+/// The managed block body for `entries`: each entry preceded by a `# <name>` comment
+pub fn render_entries(entries: &BTreeMap<String, String>) -> String {
+    entries
+        .iter()
+        .map(|(name, line)| format!("# {}\n{}", name, line.trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Replace the managed block in `content` with `entries`, keeping everything else.
+///
+/// The block stays where it was, or is appended; with no entries it is removed.
+pub fn replace_managed_block(content: &str, entries: &BTreeMap<String, String>) -> String {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    let mut in_block = false;
+    let mut seen_block = false;
+
+    for line in content.lines() {
+        if !seen_block && line.trim() == BEGIN_MARKER {
+            in_block = true;
+            seen_block = true;
+        } else if in_block {
+            if line.trim() == END_MARKER {
+                in_block = false;
+            }
+        } else if seen_block {
+            after.push(line);
+        } else {
+            before.push(line);
+        }
+    }
+
+    let mut lines: Vec<String> = before.iter().map(|line| line.to_string()).collect();
+    if !entries.is_empty() {
+        lines.push(BEGIN_MARKER.to_string());
+        lines.push(render_entries(entries));
+        lines.push(END_MARKER.to_string());
+    }
+    lines.extend(after.iter().map(|line| line.to_string()));
+
+    let mut result = lines.join("\n");
+    // cron ignores a final line without a newline
+    if !result.is_empty() {
+        result.push('\n');
+    }
+    result
+}
+
+/// Check that `line` starts with a five-field schedule or an `@` keyword followed by a command
+pub fn validate_entry(line: &str) -> Result<(), String> {
+    let fields: Vec<&str> = line.split_whitespace().collect();
+    let Some(first) = fields.first() else {
+        return Err("entry cannot be empty".to_string());
+    };
+
+    if first.starts_with('@') {
+        if !SCHEDULE_KEYWORDS.contains(first) {
+            return Err(format!("unknown schedule keyword '{}'", first));
+        }
+        if fields.len() < 2 {
+            return Err("missing command after the schedule".to_string());
+        }
+        return Ok(());
+    }
+
+    if fields.len() < 6 {
+        return Err("expected five schedule fields followed by a command".to_string());
+    }
+    let allowed = |c: char| c.is_ascii_alphanumeric() || "*/,-".contains(c);
+    if let Some(field) = fields[..5].iter().find(|field| !field.chars().all(allowed)) {
+        return Err(format!("invalid schedule field '{}'", field));
+    }
+
+    Ok(())
+}
+
+/// `Crontab` backed by the `crontab` command
+pub struct SystemCrontab;
+
+impl SystemCrontab {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SystemCrontab {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Crontab for SystemCrontab {
+    async fn read(&self) -> DotfResult<String> {
+        let output = Command::new("crontab")
+            .arg("-l")
+            .output()
+            .await
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run crontab: {}", e)))?;
+
+        if output.status.success() {
+            return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("no crontab") {
+            return Ok(String::new());
+        }
+
+        Err(DotfError::ScriptExecution(format!(
+            "crontab -l failed: {}",
+            stderr.trim()
+        )))
+    }
+
+    async fn write(&self, content: &str) -> DotfResult<()> {
+        let mut child = Command::new("crontab")
+            .arg("-")
+            .stdin(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run crontab: {}", e)))?;
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(content.as_bytes())
+                .await
+                .map_err(DotfError::Io)?;
+        }
+
+        let output = child.wait_with_output().await.map_err(DotfError::Io)?;
+        if !output.status.success() {
+            return Err(DotfError::ScriptExecution(format!(
+                "crontab rejected the new table: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, line)| (name.to_string(), line.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_replace_managed_block() {
+        let content = format!(
+            "MAILTO=me\n{}\n# old\n* * * * * old\n{}\n0 1 * * * mine\n",
+            BEGIN_MARKER, END_MARKER
+        );
+
+        let updated =
+            replace_managed_block(&content, &entries(&[("backup", "0 3 * * * ~/bin/backup")]));
+        assert_eq!(
+            updated,
+            format!(
+                "MAILTO=me\n{}\n# backup\n0 3 * * * ~/bin/backup\n{}\n0 1 * * * mine\n",
+                BEGIN_MARKER, END_MARKER
+            )
+        );
+        assert_eq!(
+            managed_block(&updated).unwrap(),
+            "# backup\n0 3 * * * ~/bin/backup"
+        );
+
+        let removed = replace_managed_block(&updated, &BTreeMap::new());
+        assert_eq!(removed, "MAILTO=me\n0 1 * * * mine\n");
+        assert_eq!(managed_block(&removed), None);
+    }
+
+    #[test]
+    fn test_validate_entry() {
+        assert!(validate_entry("*/15 9-17 * * 1-5 ~/bin/sync").is_ok());
+        assert!(validate_entry("@daily brew update").is_ok());
+        assert!(validate_entry("@sometimes run").is_err());
+        assert!(validate_entry("0 3 * * ~/bin/backup").is_err());
+        assert!(validate_entry("").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use crate::core::config::dotf_config::{EnvConfig, EnvSection};
+use crate::core::tools::asset::sha256_hex;
+
+const CHECKSUM_PREFIX: &str = "# dotf-checksum: ";
+
+/// Shell startup files, relative to the home directory, that may source the env fragment
+pub const SHELL_RC_FILES: &[&str] = &[
+    ".zshenv",
+    ".zshrc",
+    ".zprofile",
+    ".bashrc",
+    ".bash_profile",
+    ".profile",
+];
+
+/// Render `[env]` for `platform` into a POSIX shell fragment, or `None` if nothing is declared.
+///
+/// Platform PATH entries follow the common ones; platform variables override common ones.
+pub fn render_env(config: &EnvConfig, platform: &str) -> Option<String> {
+    let platform_section = match platform {
+        "macos" => config.macos.as_ref(),
+        "linux" => config.linux.as_ref(),
+        _ => None,
+    };
+
+    let mut path: Vec<&String> = config.common.path.iter().collect();
+    let mut vars: BTreeMap<&String, &String> = config.common.vars.iter().collect();
+    if let Some(EnvSection {
+        path: extra_path,
+        vars: extra_vars,
+    }) = platform_section
+    {
+        path.extend(extra_path);
+        vars.extend(extra_vars);
+    }
+
+    if path.is_empty() && vars.is_empty() {
+        return None;
+    }
+
+    let mut body = Vec::new();
+    if !path.is_empty() {
+        let entries: Vec<String> = path.iter().map(|entry| quote(entry)).collect();
+        body.push(format!("export PATH=\"{}:$PATH\"", entries.join(":")));
+    }
+    for (name, value) in vars {
+        body.push(format!("export {}=\"{}\"", name, quote(value)));
+    }
+    let body = body.join("\n") + "\n";
+
+    Some(format!(
+        "# Generated by dotf from [env] in dotf.toml. Edits are overwritten; change dotf.toml instead.\n\
+         {}{}\n{}",
+        CHECKSUM_PREFIX,
+        sha256_hex(body.as_bytes()),
+        body
+    ))
+}
+
+/// Whether a generated file was edited since dotf wrote it (or was not written by dotf at all)
+pub fn is_hand_edited(content: &str) -> bool {
+    let Some((header, body)) = content
+        .split_once(CHECKSUM_PREFIX)
+        .and_then(|(_, rest)| rest.split_once('\n'))
+    else {
+        return true;
+    };
+    header.trim() != sha256_hex(body.as_bytes())
+}
+
+/// Escape a value for a double-quoted shell string; a leading `~` becomes `$HOME`.
+/// `$` is left alone so values may reference other variables.
+fn quote(value: &str) -> String {
+    let value = match value.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("$HOME{}", rest),
+        _ => value.to_string(),
+    };
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('`', "\\`")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> EnvConfig {
+        toml::from_str(
+            r#"
+path = ["~/.local/bin"]
+vars = { EDITOR = "nvim", GREETING = "say \"hi\"" }
+
+[macos]
+path = ["/opt/homebrew/bin"]
+vars = { EDITOR = "code -w" }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_render_env() {
+        let rendered = render_env(&config(), "macos").unwrap();
+        let body: Vec<&str> = rendered.lines().skip(2).collect();
+        assert_eq!(
+            body,
+            vec![
+                "export PATH=\"$HOME/.local/bin:/opt/homebrew/bin:$PATH\"",
+                "export EDITOR=\"code -w\"",
+                "export GREETING=\"say \\\"hi\\\"\"",
+            ]
+        );
+        assert!(!is_hand_edited(&rendered));
+        assert!(is_hand_edited(&format!("{}export FOO=1\n", rendered)));
+        assert!(is_hand_edited("export FOO=1\n"));
+
+        assert!(render_env(&EnvConfig::default(), "linux").is_none());
+    }
+}
@@ -0,0 +1,106 @@
+//! Experimental features ship dark: their commands refuse to run until a user turns
+//! them on with `name = true` under `[features]` in settings.toml or `--enable-feature`
+
+use std::collections::BTreeMap;
+
+use crate::error::{DotfError, DotfResult};
+
+/// A subsystem that may still change shape between releases
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Experiment {
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+/// Every experiment this version knows about
+pub const EXPERIMENTS: &[Experiment] = &[
+    Experiment {
+        name: "changes",
+        description: "'dotf changes' reports what changed since the last install",
+    },
+    Experiment {
+        name: "overlays",
+        description: "'dotf repos' and --repo layer overlay repositories over the main one",
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Experiment> {
+    EXPERIMENTS
+        .iter()
+        .find(|experiment| experiment.name == name)
+}
+
+/// Experiments turned on by settings or by `--enable-feature`, in `EXPERIMENTS` order.
+/// The flag wins over `name = false` in settings; unknown flag values are an error.
+pub fn enabled(
+    settings: &BTreeMap<String, bool>,
+    flags: &[String],
+) -> DotfResult<Vec<&'static Experiment>> {
+    if let Some(name) = flags.iter().find(|name| find(name).is_none()) {
+        return Err(DotfError::Config(format!(
+            "Unknown feature '{}' (known: {})",
+            name,
+            known_names()
+        )));
+    }
+    Ok(EXPERIMENTS
+        .iter()
+        .filter(|experiment| {
+            flags.iter().any(|name| name == experiment.name)
+                || settings.get(experiment.name).copied().unwrap_or(false)
+        })
+        .collect())
+}
+
+/// Names under `[features]` that this version does not know, e.g. typos or
+/// experiments that have since graduated
+pub fn unknown(settings: &BTreeMap<String, bool>) -> Vec<&str> {
+    settings
+        .keys()
+        .map(String::as_str)
+        .filter(|name| find(name).is_none())
+        .collect()
+}
+
+/// Fail with instructions for turning `name` on unless it is in `enabled`
+pub fn require(name: &str, enabled: &[&Experiment]) -> DotfResult<()> {
+    if enabled.iter().any(|experiment| experiment.name == name) {
+        return Ok(());
+    }
+    Err(DotfError::Config(format!(
+        "'{}' is an experimental feature; pass --enable-feature {} or set {} = true under [features] in settings.toml",
+        name, name, name
+    )))
+}
+
+fn known_names() -> String {
+    EXPERIMENTS
+        .iter()
+        .map(|experiment| experiment.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_enabled_features() {
+        let mut settings = BTreeMap::new();
+        settings.insert("changes".to_string(), true);
+        settings.insert("overlays".to_string(), false);
+        settings.insert("telepathy".to_string(), true);
+
+        let names =
+            |experiments: Vec<&Experiment>| experiments.iter().map(|e| e.name).collect::<Vec<_>>();
+        assert_eq!(names(enabled(&settings, &[]).unwrap()), vec!["changes"]);
+        let flags = vec!["overlays".to_string()];
+        let all = enabled(&settings, &flags).unwrap();
+        assert_eq!(names(all.clone()), vec!["changes", "overlays"]);
+        assert!(require("overlays", &all).is_ok());
+        assert!(require("overlays", &enabled(&settings, &[]).unwrap()).is_err());
+        assert!(enabled(&settings, &["telepathy".to_string()]).is_err());
+        assert_eq!(unknown(&settings), vec!["telepathy"]);
+    }
+}
@@ -1,3 +1,5 @@
 pub mod operations;
+#[cfg(windows)]
+pub mod windows;
 
-pub use operations::RealFileSystem;
+pub use operations::{is_cross_device, RealFileSystem, CROSS_DEVICE};
@@ -1,10 +1,23 @@
 use async_trait::async_trait;
-use std::path::PathBuf;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::unbounded_channel;
 
 use crate::error::{DotfError, DotfResult};
-use crate::traits::filesystem::{FileEntry, FileSystem};
+use crate::traits::filesystem::{ChangeStream, FileEntry, FileOwner, FileSystem, WatchPath};
+
+/// OS error code of a rename between two filesystems (EXDEV, or ERROR_NOT_SAME_DEVICE)
+#[cfg(not(windows))]
+pub const CROSS_DEVICE: i32 = 18;
+#[cfg(windows)]
+pub const CROSS_DEVICE: i32 = 17;
+
+/// Whether `error` came from moving a path to another filesystem
+pub fn is_cross_device(error: &std::io::Error) -> bool {
+    error.raw_os_error() == Some(CROSS_DEVICE)
+}
 
 #[derive(Clone)]
 pub struct RealFileSystem;
@@ -49,17 +62,22 @@ impl FileSystem for RealFileSystem {
 
         #[cfg(windows)]
         {
-            // On Windows, we need to check if source is a directory or file
-            let source_metadata = fs::metadata(source).await.map_err(|e| DotfError::Io(e))?;
+            use super::windows::{create_link, LinkKind};
 
-            if source_metadata.is_dir() {
-                tokio::fs::symlink_dir(source, target)
-                    .await
-                    .map_err(|e| DotfError::Io(e))?;
-            } else {
-                tokio::fs::symlink_file(source, target)
-                    .await
-                    .map_err(|e| DotfError::Io(e))?;
+            let (source, target) = (PathBuf::from(source), PathBuf::from(target));
+            let kind = tokio::task::spawn_blocking({
+                let target = target.clone();
+                move || create_link(&source, &target)
+            })
+            .await
+            .map_err(|e| DotfError::Operation(format!("Failed to create link: {}", e)))?
+            .map_err(DotfError::Io)?;
+
+            if kind == LinkKind::Copy {
+                eprintln!(
+                    "warning: symlinks are not allowed, copied {} instead; enable Developer Mode to link it",
+                    target.display()
+                );
             }
         }
 
@@ -69,6 +87,13 @@ impl FileSystem for RealFileSystem {
     async fn remove_file(&self, path: &str) -> DotfResult<()> {
         let metadata = fs::symlink_metadata(path).await.map_err(DotfError::Io)?;
 
+        // Directory symlinks and junctions are removed like empty directories on Windows
+        #[cfg(windows)]
+        if std::os::windows::fs::FileTypeExt::is_symlink_dir(&metadata.file_type()) {
+            fs::remove_dir(path).await.map_err(DotfError::Io)?;
+            return Ok(());
+        }
+
         if metadata.is_dir() {
             fs::remove_dir_all(path).await.map_err(DotfError::Io)?;
         } else {
@@ -95,6 +120,24 @@ impl FileSystem for RealFileSystem {
         Ok(())
     }
 
+    async fn hard_link(&self, source: &str, target: &str) -> DotfResult<()> {
+        if let Some(parent) = std::path::Path::new(target).parent() {
+            if !self.exists(&parent.to_string_lossy()).await? {
+                self.create_dir_all(&parent.to_string_lossy()).await?;
+            }
+        }
+
+        fs::hard_link(source, target).await.map_err(DotfError::Io)
+    }
+
+    async fn rename(&self, source: &str, target: &str) -> DotfResult<()> {
+        fs::rename(source, target).await.map_err(DotfError::Io)
+    }
+
+    async fn read(&self, path: &str) -> DotfResult<Vec<u8>> {
+        fs::read(path).await.map_err(DotfError::Io)
+    }
+
     async fn read_to_string(&self, path: &str) -> DotfResult<String> {
         fs::read_to_string(path).await.map_err(DotfError::Io)
     }
@@ -153,6 +196,95 @@ impl FileSystem for RealFileSystem {
 
         Ok(entries)
     }
+
+    async fn check_readable(&self, path: &str) -> DotfResult<()> {
+        if fs::metadata(path).await.map_err(DotfError::Io)?.is_dir() {
+            let mut entries = fs::read_dir(path).await.map_err(DotfError::Io)?;
+            entries.next_entry().await.map_err(DotfError::Io)?;
+        } else {
+            fs::File::open(path).await.map_err(DotfError::Io)?;
+        }
+        Ok(())
+    }
+
+    async fn mode(&self, path: &str) -> DotfResult<Option<u32>> {
+        let metadata = fs::metadata(path).await.map_err(DotfError::Io)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            Ok(Some(metadata.permissions().mode() & 0o7777))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            Ok(None)
+        }
+    }
+
+    async fn set_mode(&self, path: &str, mode: u32) -> DotfResult<()> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(path, std::fs::Permissions::from_mode(mode))
+                .await
+                .map_err(DotfError::Io)?;
+        }
+        #[cfg(not(unix))]
+        let _ = (path, mode);
+        Ok(())
+    }
+
+    async fn owner(&self, path: &str) -> DotfResult<Option<FileOwner>> {
+        let metadata = fs::metadata(path).await.map_err(DotfError::Io)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Ok(Some(FileOwner {
+                uid: metadata.uid(),
+                gid: metadata.gid(),
+            }))
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = metadata;
+            Ok(None)
+        }
+    }
+
+    async fn set_owner(&self, path: &str, owner: FileOwner) -> DotfResult<()> {
+        #[cfg(unix)]
+        std::os::unix::fs::chown(path, Some(owner.uid), Some(owner.gid)).map_err(DotfError::Io)?;
+        #[cfg(not(unix))]
+        let _ = (path, owner);
+        Ok(())
+    }
+
+    async fn watch(&self, paths: &[WatchPath]) -> DotfResult<ChangeStream> {
+        let (sender, receiver) = unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<Event>| {
+            // Reads and opens change nothing; errors leave the stream to the next event
+            let Ok(event) = event else { return };
+            if matches!(event.kind, EventKind::Access(_)) {
+                return;
+            }
+            for path in event.paths {
+                let _ = sender.send(path.to_string_lossy().to_string());
+            }
+        })
+        .map_err(|e| DotfError::Operation(format!("Failed to start file watcher: {}", e)))?;
+
+        for path in paths {
+            let mode = if path.recursive {
+                RecursiveMode::Recursive
+            } else {
+                RecursiveMode::NonRecursive
+            };
+            watcher.watch(Path::new(&path.path), mode).map_err(|e| {
+                DotfError::Operation(format!("Failed to watch {}: {}", path.path, e))
+            })?;
+        }
+        Ok(ChangeStream::new(receiver, Box::new(watcher)))
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,65 @@
+//! Links on Windows. Symlinks need developer mode or an elevated shell; without either,
+//! directories are linked through a junction and files are copied instead.
+
+use std::io;
+use std::os::windows::fs::{symlink_dir, symlink_file};
+use std::path::Path;
+use std::process::Command;
+
+/// `ERROR_PRIVILEGE_NOT_HELD`, returned when symlinks are not allowed for the current user
+const ERROR_PRIVILEGE_NOT_HELD: i32 = 1314;
+
+/// How a link ended up being created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Symlink,
+    Junction,
+    /// A plain copy of the source file, which is not kept in sync
+    Copy,
+}
+
+/// Link `target` to `source`, falling back to a junction or a copy when symlinks are not
+/// allowed
+pub fn create_link(source: &Path, target: &Path) -> io::Result<LinkKind> {
+    let is_dir = source.is_dir();
+    let result = if is_dir {
+        symlink_dir(source, target)
+    } else {
+        symlink_file(source, target)
+    };
+
+    match result {
+        Ok(()) => Ok(LinkKind::Symlink),
+        Err(e) if e.raw_os_error() == Some(ERROR_PRIVILEGE_NOT_HELD) => {
+            if is_dir {
+                create_junction(source, target)?;
+                Ok(LinkKind::Junction)
+            } else {
+                std::fs::copy(source, target)?;
+                Ok(LinkKind::Copy)
+            }
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Junctions need no privilege but only work with an absolute directory path
+fn create_junction(source: &Path, target: &Path) -> io::Result<()> {
+    let source = std::path::absolute(source)?;
+    let output = Command::new("cmd")
+        .arg("/C")
+        .arg("mklink")
+        .arg("/J")
+        .arg(target)
+        .arg(&source)
+        .output()?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "mklink /J failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
@@ -0,0 +1,3 @@
+pub mod snapshot;
+
+pub use snapshot::{drift_start, parse_since, HistoryStore, StatusSnapshot};
@@ -0,0 +1,258 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::core::clock::SystemClock;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::clock::Clock;
+use crate::traits::filesystem::FileSystem;
+
+/// Number of snapshots kept in the history file; older ones are dropped
+const MAX_SNAPSHOTS: usize = 1000;
+
+/// Point-in-time summary of symlink health and repository sync state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub total: usize,
+    pub valid: usize,
+    pub missing: usize,
+    pub broken: usize,
+    pub conflicts: usize,
+    pub invalid_targets: usize,
+    pub modified: usize,
+    pub is_clean: bool,
+    pub ahead: usize,
+    pub behind: usize,
+}
+
+impl StatusSnapshot {
+    pub fn issues(&self) -> usize {
+        self.missing + self.broken + self.conflicts + self.invalid_targets
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.issues() == 0
+    }
+
+    /// Whether two snapshots describe the same state, ignoring when they were taken
+    pub fn same_state(&self, other: &StatusSnapshot) -> bool {
+        StatusSnapshot {
+            taken_at: other.taken_at,
+            ..self.clone()
+        } == *other
+    }
+}
+
+/// Append-only store of status snapshots kept as JSON lines
+pub struct HistoryStore<F> {
+    filesystem: F,
+    clock: Arc<dyn Clock>,
+}
+
+impl<F: FileSystem> HistoryStore<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self::with_clock(filesystem, Arc::new(SystemClock::new()))
+    }
+
+    pub fn with_clock(filesystem: F, clock: Arc<dyn Clock>) -> Self {
+        Self { filesystem, clock }
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    pub async fn append(&self, snapshot: StatusSnapshot) -> DotfResult<()> {
+        let mut snapshots = self.load_all().await?;
+        snapshots.push(snapshot);
+
+        if snapshots.len() > MAX_SNAPSHOTS {
+            let excess = snapshots.len() - MAX_SNAPSHOTS;
+            snapshots.drain(..excess);
+        }
+
+        let mut content = String::new();
+        for snapshot in &snapshots {
+            content.push_str(&serde_json::to_string(snapshot)?);
+            content.push('\n');
+        }
+
+        self.filesystem
+            .write(&self.filesystem.dotf_history_path(), &content)
+            .await
+    }
+
+    pub async fn load_all(&self) -> DotfResult<Vec<StatusSnapshot>> {
+        let history_path = self.filesystem.dotf_history_path();
+
+        if !self.filesystem.exists(&history_path).await? {
+            return Ok(Vec::new());
+        }
+
+        let content = self.filesystem.read_to_string(&history_path).await?;
+
+        // Skip lines that fail to parse (e.g. a write interrupted mid-line)
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// The most recent snapshot, parsing only as much of the file as needed
+    pub async fn latest(&self) -> DotfResult<Option<StatusSnapshot>> {
+        let history_path = self.filesystem.dotf_history_path();
+
+        if !self.filesystem.exists(&history_path).await? {
+            return Ok(None);
+        }
+
+        let content = self.filesystem.read_to_string(&history_path).await?;
+        Ok(content
+            .lines()
+            .rev()
+            .find_map(|line| serde_json::from_str(line).ok()))
+    }
+
+    pub async fn load_since(&self, since: DateTime<Utc>) -> DotfResult<Vec<StatusSnapshot>> {
+        Ok(self
+            .load_all()
+            .await?
+            .into_iter()
+            .filter(|snapshot| snapshot.taken_at >= since)
+            .collect())
+    }
+}
+
+/// Find the snapshot where the current run of unhealthy snapshots began
+pub fn drift_start(snapshots: &[StatusSnapshot]) -> Option<&StatusSnapshot> {
+    let latest = snapshots.last()?;
+    if latest.is_healthy() {
+        return None;
+    }
+
+    snapshots
+        .iter()
+        .rev()
+        .take_while(|snapshot| !snapshot.is_healthy())
+        .last()
+}
+
+/// Parse a `--since` value: a relative duration (`30m`, `12h`, `7d`, `2w`) or a date (`2024-01-31`)
+pub fn parse_since(input: &str, now: DateTime<Utc>) -> DotfResult<DateTime<Utc>> {
+    let input = input.trim();
+
+    if let Ok(date) = NaiveDate::parse_from_str(input, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+
+    let invalid = || {
+        DotfError::Validation(format!(
+            "Invalid --since value '{}'. Use e.g. 30m, 12h, 7d, 2w or YYYY-MM-DD",
+            input
+        ))
+    };
+
+    let split = input
+        .find(|c: char| !c.is_ascii_digit())
+        .ok_or_else(invalid)?;
+    let (amount, unit) = input.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| invalid())?;
+
+    let duration = match unit {
+        "m" => chrono::Duration::minutes(amount),
+        "h" => chrono::Duration::hours(amount),
+        "d" => chrono::Duration::days(amount),
+        "w" => chrono::Duration::weeks(amount),
+        _ => return Err(invalid()),
+    };
+
+    Ok(now - duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::clock::tests::MockClock;
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::TimeZone;
+
+    fn snapshot(taken_at: DateTime<Utc>, missing: usize) -> StatusSnapshot {
+        StatusSnapshot {
+            taken_at,
+            total: 3,
+            valid: 3 - missing,
+            missing,
+            broken: 0,
+            conflicts: 0,
+            invalid_targets: 0,
+            modified: 0,
+            is_clean: true,
+            ahead: 0,
+            behind: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_and_load_since() {
+        let fs = MockFileSystem::new();
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let store = HistoryStore::with_clock(fs, Arc::new(MockClock::new(start)));
+
+        for day in 0..5 {
+            store
+                .append(snapshot(start + chrono::Duration::days(day), 0))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(store.load_all().await.unwrap().len(), 5);
+        assert_eq!(
+            store.latest().await.unwrap().unwrap().taken_at,
+            start + chrono::Duration::days(4)
+        );
+
+        let recent = store
+            .load_since(start + chrono::Duration::days(3))
+            .await
+            .unwrap();
+        assert_eq!(recent.len(), 2);
+    }
+
+    #[test]
+    fn test_drift_start() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let snapshots = vec![
+            snapshot(start, 1),
+            snapshot(start + chrono::Duration::days(1), 0),
+            snapshot(start + chrono::Duration::days(2), 1),
+            snapshot(start + chrono::Duration::days(3), 2),
+        ];
+
+        let drift = drift_start(&snapshots).unwrap();
+        assert_eq!(drift.taken_at, start + chrono::Duration::days(2));
+
+        assert!(drift_start(&snapshots[..2]).is_none());
+    }
+
+    #[test]
+    fn test_parse_since() {
+        let now = Utc.with_ymd_and_hms(2024, 1, 10, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            parse_since("7d", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 3, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_since("2h", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 10, 10, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_since("2024-01-05", now).unwrap(),
+            Utc.with_ymd_and_hms(2024, 1, 5, 0, 0, 0).unwrap()
+        );
+        assert!(parse_since("yesterday", now).is_err());
+        assert!(parse_since("5y", now).is_err());
+    }
+}
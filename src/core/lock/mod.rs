@@ -0,0 +1,235 @@
+pub mod socket;
+
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DotfError, DotfResult};
+
+/// File name of the operation lock inside `~/.dotf`
+pub const LOCK_FILE: &str = "dotf.lock";
+
+/// Who holds the operation lock, as written to the lock file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LockHolder {
+    pub pid: u32,
+    pub command: String,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Held while a command changes the repository or the home directory, so two dotf
+/// processes never apply changes at the same time. Released when dropped.
+#[derive(Debug)]
+pub struct OperationLock {
+    path: PathBuf,
+    holder: LockHolder,
+}
+
+/// How long a lock file that cannot be read as a holder is taken to be in use. Locks are
+/// published complete, so such a file is left over from an older dotf or a damaged disk.
+const UNREADABLE_LOCK_GRACE: Duration = Duration::from_secs(60);
+
+impl OperationLock {
+    /// Take the lock in `dotf_dir` for `command`, failing if another live process holds it
+    pub fn acquire(dotf_dir: &Path, command: &str) -> DotfResult<Self> {
+        std::fs::create_dir_all(dotf_dir)?;
+        let path = dotf_dir.join(LOCK_FILE);
+        let holder = LockHolder {
+            pid: std::process::id(),
+            command: command.to_string(),
+            started_at: Utc::now(),
+        };
+
+        // Written aside and hard linked into place, so the lock never exists half written
+        let mut pending = tempfile::Builder::new()
+            .prefix(&format!(".{}.", LOCK_FILE))
+            .tempfile_in(dotf_dir)?;
+        pending.write_all(serde_json::to_string(&holder)?.as_bytes())?;
+        pending.flush()?;
+
+        for _ in 0..3 {
+            match std::fs::hard_link(pending.path(), &path) {
+                Ok(()) => return Ok(Self { path, holder }),
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    let Ok(content) = std::fs::read_to_string(&path) else {
+                        // Released between the link and the read
+                        continue;
+                    };
+                    match serde_json::from_str::<LockHolder>(&content) {
+                        Ok(current) if is_process_alive(current.pid) => {
+                            return Err(DotfError::Operation(format!(
+                                "Another dotf command ('{}', pid {}) has been running since {}; wait for it to finish",
+                                current.command,
+                                current.pid,
+                                current.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+                            )));
+                        }
+                        // Left behind by a process that no longer exists
+                        Ok(_) => reclaim(&path, &content)?,
+                        Err(_) if unreadable_lock_expired(&path) => reclaim(&path, &content)?,
+                        Err(_) => {
+                            return Err(DotfError::Operation(format!(
+                                "The lock at {} is held by an unknown process; remove it if no other dotf command is running",
+                                path.display()
+                            )));
+                        }
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Err(DotfError::Operation(format!(
+            "Could not take the lock at {}",
+            path.display()
+        )))
+    }
+
+    /// The process recorded in the lock file, if there is one
+    pub fn holder(dotf_dir: &Path) -> Option<LockHolder> {
+        let content = std::fs::read_to_string(dotf_dir.join(LOCK_FILE)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// The holder of a lock left behind by a process that no longer exists, i.e. a
+    /// command that crashed or was killed before it finished
+    pub fn stale_holder(dotf_dir: &Path) -> Option<LockHolder> {
+        Self::holder(dotf_dir).filter(|holder| !is_process_alive(holder.pid))
+    }
+}
+
+impl Drop for OperationLock {
+    fn drop(&mut self) {
+        // Only release the lock this process took, in case it was broken and taken over
+        if Self::holder(self.path.parent().unwrap_or(Path::new("."))).as_ref() == Some(&self.holder)
+        {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Remove the dead lock at `path` whose content was `seen`. The lock is first renamed aside,
+/// which only one process can do, and put back if it turns out to be a newer lock that
+/// another process took in the meantime.
+fn reclaim(path: &Path, seen: &str) -> DotfResult<()> {
+    let claimed = path.with_extension(format!("lock.{}.stale", std::process::id()));
+    match std::fs::rename(path, &claimed) {
+        Ok(()) => {}
+        // Another process reclaimed it first
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    }
+    if std::fs::read_to_string(&claimed).is_ok_and(|content| content != seen) {
+        let _ = std::fs::hard_link(&claimed, path);
+    }
+    std::fs::remove_file(&claimed)?;
+    Ok(())
+}
+
+fn unreadable_lock_expired(path: &Path) -> bool {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > UNREADABLE_LOCK_GRACE)
+}
+
+/// Whether a process with `pid` exists. A process of another user, which may not be
+/// signalled, still counts as alive.
+#[cfg(unix)]
+pub fn is_process_alive(pid: u32) -> bool {
+    // 0 and values past i32::MAX would address process groups rather than a single process
+    if pid == 0 || pid > i32::MAX as u32 {
+        return false;
+    }
+    // SAFETY: signal 0 only checks that the process exists and may be signalled
+    if unsafe { libc::kill(pid as libc::pid_t, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+/// Whether a process with `pid` exists. Without a way to ask, every holder counts as alive
+/// and a stale lock has to be removed by hand.
+#[cfg(not(unix))]
+pub fn is_process_alive(pid: u32) -> bool {
+    pid != 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let dir = TempDir::new().unwrap();
+
+        let lock = OperationLock::acquire(dir.path(), "install").unwrap();
+        let holder = OperationLock::holder(dir.path()).unwrap();
+        assert_eq!(holder.pid, std::process::id());
+        assert_eq!(holder.command, "install");
+
+        let result = OperationLock::acquire(dir.path(), "sync");
+        assert!(matches!(result.unwrap_err(), DotfError::Operation(_)));
+
+        drop(lock);
+        assert!(OperationLock::holder(dir.path()).is_none());
+        assert!(OperationLock::acquire(dir.path(), "sync").is_ok());
+    }
+
+    #[test]
+    fn test_empty_lock_counts_as_held() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(LOCK_FILE), "").unwrap();
+
+        let result = OperationLock::acquire(dir.path(), "sync");
+        assert!(matches!(result.unwrap_err(), DotfError::Operation(_)));
+        assert!(dir.path().join(LOCK_FILE).exists());
+    }
+
+    #[test]
+    fn test_reclaim_puts_back_a_lock_taken_in_the_meantime() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        let _lock = OperationLock::acquire(dir.path(), "install").unwrap();
+
+        reclaim(&path, "{\"pid\":1}").unwrap();
+
+        assert_eq!(
+            OperationLock::holder(dir.path()).unwrap().command,
+            "install"
+        );
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_current_process_is_alive() {
+        assert!(is_process_alive(std::process::id()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_lock_left_by_dead_process_is_taken_over() {
+        let dir = TempDir::new().unwrap();
+        let stale = LockHolder {
+            pid: i32::MAX as u32,
+            command: "install".to_string(),
+            started_at: Utc::now(),
+        };
+        std::fs::write(
+            dir.path().join(LOCK_FILE),
+            serde_json::to_string(&stale).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(OperationLock::stale_holder(dir.path()), Some(stale));
+
+        let _lock = OperationLock::acquire(dir.path(), "sync").unwrap();
+        assert_eq!(OperationLock::holder(dir.path()).unwrap().command, "sync");
+        assert!(OperationLock::stale_holder(dir.path()).is_none());
+    }
+}
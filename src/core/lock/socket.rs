@@ -0,0 +1,210 @@
+//! The status socket of a running `dotf watch`: the watcher answers each connection
+//! with its state as JSON, which is how `dotf watch status` finds out what it is doing
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use super::LockHolder;
+use crate::error::{DotfError, DotfResult};
+
+/// File name of the watch status socket inside `~/.dotf`
+pub const WATCH_SOCKET: &str = "watch.sock";
+
+/// What a running `dotf watch` reports over its socket
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WatchStatus {
+    pub pid: u32,
+    pub started_at: DateTime<Utc>,
+    /// Linked directories being watched
+    pub directories: usize,
+    /// Links being looked after
+    pub links: usize,
+    /// When the last batch of changes was handled
+    pub last_change: Option<DateTime<Utc>>,
+    /// The command holding the operation lock while the watcher waits for it
+    pub paused_for: Option<LockHolder>,
+    /// Changes waiting for that command to finish
+    pub pending: usize,
+}
+
+impl WatchStatus {
+    pub fn new(directories: usize, links: usize) -> Self {
+        Self {
+            pid: std::process::id(),
+            started_at: Utc::now(),
+            directories,
+            links,
+            last_change: None,
+            paused_for: None,
+            pending: 0,
+        }
+    }
+}
+
+/// Serves the watcher's status at `~/.dotf/watch.sock` until dropped
+#[derive(Debug)]
+pub struct WatchSocket {
+    path: PathBuf,
+    status: Arc<Mutex<WatchStatus>>,
+    server: JoinHandle<()>,
+}
+
+impl WatchSocket {
+    /// Start answering at the socket in `dotf_dir`, failing if another watcher answers
+    /// there already. A socket left by a watcher that is gone is replaced.
+    pub async fn bind(dotf_dir: &Path, status: WatchStatus) -> DotfResult<Self> {
+        if let Some(running) = query_watch(dotf_dir).await? {
+            return Err(DotfError::Operation(format!(
+                "dotf watch is already running (pid {})",
+                running.pid
+            )));
+        }
+        std::fs::create_dir_all(dotf_dir)?;
+        let path = dotf_dir.join(WATCH_SOCKET);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => {}
+        }
+
+        let status = Arc::new(Mutex::new(status));
+        let server = serve(&path, status.clone())?;
+        Ok(Self {
+            path,
+            status,
+            server,
+        })
+    }
+
+    /// Change the status the socket reports
+    pub fn update(&self, change: impl FnOnce(&mut WatchStatus)) {
+        if let Ok(mut status) = self.status.lock() {
+            change(&mut status);
+        }
+    }
+}
+
+impl Drop for WatchSocket {
+    fn drop(&mut self) {
+        self.server.abort();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// The status of the watcher answering at the socket in `dotf_dir`, or `None` when no
+/// watcher is running
+pub async fn query_watch(dotf_dir: &Path) -> DotfResult<Option<WatchStatus>> {
+    let Some(response) = request(&dotf_dir.join(WATCH_SOCKET)).await? else {
+        return Ok(None);
+    };
+    serde_json::from_str(&response)
+        .map(Some)
+        .map_err(|e| DotfError::Operation(format!("Unreadable answer from dotf watch: {}", e)))
+}
+
+#[cfg(unix)]
+fn serve(path: &Path, status: Arc<Mutex<WatchStatus>>) -> DotfResult<JoinHandle<()>> {
+    use tokio::io::AsyncWriteExt;
+
+    let listener = tokio::net::UnixListener::bind(path)?;
+    Ok(tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let response = match status.lock() {
+                Ok(status) => serde_json::to_string(&*status).unwrap_or_default(),
+                Err(_) => continue,
+            };
+            let _ = stream.write_all(response.as_bytes()).await;
+        }
+    }))
+}
+
+#[cfg(not(unix))]
+fn serve(_path: &Path, _status: Arc<Mutex<WatchStatus>>) -> DotfResult<JoinHandle<()>> {
+    Ok(tokio::spawn(async {}))
+}
+
+/// What the socket at `path` answers; `None` when nothing listens there
+#[cfg(unix)]
+async fn request(path: &Path) -> DotfResult<Option<String>> {
+    use tokio::io::AsyncReadExt;
+
+    let mut stream = match tokio::net::UnixStream::connect(path).await {
+        Ok(stream) => stream,
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::NotFound | std::io::ErrorKind::ConnectionRefused
+            ) =>
+        {
+            return Ok(None)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await?;
+    Ok(Some(response))
+}
+
+#[cfg(not(unix))]
+async fn request(_path: &Path) -> DotfResult<Option<String>> {
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_watch_socket_reports_status_until_dropped() {
+        let dir = TempDir::new().unwrap();
+        assert!(query_watch(dir.path()).await.unwrap().is_none());
+
+        let socket = WatchSocket::bind(dir.path(), WatchStatus::new(2, 14))
+            .await
+            .unwrap();
+        let status = query_watch(dir.path()).await.unwrap().unwrap();
+        assert_eq!(status.pid, std::process::id());
+        assert_eq!((status.directories, status.links), (2, 14));
+        assert!(status.paused_for.is_none());
+
+        let holder = LockHolder {
+            pid: 4242,
+            command: "install".to_string(),
+            started_at: Utc::now(),
+        };
+        socket.update(|status| {
+            status.paused_for = Some(holder.clone());
+            status.pending = 3;
+        });
+        let status = query_watch(dir.path()).await.unwrap().unwrap();
+        assert_eq!(status.paused_for, Some(holder));
+        assert_eq!(status.pending, 3);
+
+        let second = WatchSocket::bind(dir.path(), WatchStatus::new(0, 0)).await;
+        assert!(second.unwrap_err().to_string().contains("already running"));
+
+        drop(socket);
+        assert!(!dir.path().join(WATCH_SOCKET).exists());
+        assert!(query_watch(dir.path()).await.unwrap().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_watch_socket_replaces_one_left_behind() {
+        let dir = TempDir::new().unwrap();
+        // A watcher that was killed leaves its socket file with nobody listening
+        drop(std::os::unix::net::UnixListener::bind(dir.path().join(WATCH_SOCKET)).unwrap());
+        assert!(dir.path().join(WATCH_SOCKET).exists());
+        assert!(query_watch(dir.path()).await.unwrap().is_none());
+
+        let _socket = WatchSocket::bind(dir.path(), WatchStatus::new(1, 1))
+            .await
+            .unwrap();
+        assert!(query_watch(dir.path()).await.unwrap().is_some());
+    }
+}
@@ -0,0 +1,105 @@
+use async_trait::async_trait;
+use tokio::process::Command;
+
+use crate::core::config::dotf_config::DefaultsValue;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::macos_defaults::DefaultsStore;
+
+/// `DefaultsStore` backed by the `defaults` command
+pub struct SystemDefaultsStore;
+
+impl SystemDefaultsStore {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SystemDefaultsStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DefaultsStore for SystemDefaultsStore {
+    async fn read(&self, domain: &str, key: &str) -> DotfResult<Option<String>> {
+        let output = Command::new("defaults")
+            .args(["read", domain, key])
+            .output()
+            .await
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run defaults: {}", e)))?;
+
+        // `defaults read` exits non-zero when the domain or key does not exist
+        if !output.status.success() {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            String::from_utf8_lossy(&output.stdout)
+                .trim_end()
+                .to_string(),
+        ))
+    }
+
+    async fn write(&self, domain: &str, key: &str, value: &DefaultsValue) -> DotfResult<()> {
+        let (flag, text) = write_args(value);
+        let output = Command::new("defaults")
+            .args(["write", domain, key, flag, &text])
+            .output()
+            .await
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run defaults: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(DotfError::ScriptExecution(format!(
+                "defaults write {} {} failed: {}",
+                domain,
+                key,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+fn write_args(value: &DefaultsValue) -> (&'static str, String) {
+    match value {
+        DefaultsValue::Bool(value) => ("-bool", value.to_string()),
+        DefaultsValue::Int(value) => ("-int", value.to_string()),
+        DefaultsValue::Float(value) => ("-float", value.to_string()),
+        DefaultsValue::String(value) => ("-string", value.clone()),
+    }
+}
+
+/// Whether `current` (as printed by `defaults read`) already equals `wanted`
+pub fn value_matches(current: &str, wanted: &DefaultsValue) -> bool {
+    match wanted {
+        // Booleans read back as 1/0
+        DefaultsValue::Bool(value) => current == if *value { "1" } else { "0" },
+        DefaultsValue::Int(value) => current.parse::<i64>().is_ok_and(|c| c == *value),
+        DefaultsValue::Float(value) => current.parse::<f64>().is_ok_and(|c| c == *value),
+        DefaultsValue::String(value) => current == value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_matches() {
+        assert!(value_matches("1", &DefaultsValue::Bool(true)));
+        assert!(!value_matches("true", &DefaultsValue::Bool(true)));
+        assert!(value_matches("48", &DefaultsValue::Int(48)));
+        assert!(value_matches("0.5", &DefaultsValue::Float(0.5)));
+        assert!(value_matches("2", &DefaultsValue::Float(2.0)));
+        assert!(value_matches(
+            "Nlsv",
+            &DefaultsValue::String("Nlsv".to_string())
+        ));
+        assert!(!value_matches(
+            "48",
+            &DefaultsValue::String("47".to_string())
+        ));
+    }
+}
@@ -0,0 +1,229 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::error::{DotfError, DotfResult};
+
+/// Where the bare-repo dotfiles pattern usually keeps its git directory, relative to `$HOME`
+pub const BARE_REPO_CANDIDATES: &[&str] = &[".dotfiles", ".cfg", ".dotfiles.git", ".dots"];
+
+/// A bare git repository whose work tree is the home directory
+#[derive(Debug, Clone)]
+pub struct BareRepo {
+    pub git_dir: PathBuf,
+    pub work_tree: PathBuf,
+}
+
+impl BareRepo {
+    /// Open `git_dir` as a bare repository checked out over `work_tree`
+    pub fn open(git_dir: &Path, work_tree: &Path) -> DotfResult<Self> {
+        let repo = Self {
+            git_dir: git_dir.to_path_buf(),
+            work_tree: work_tree.to_path_buf(),
+        };
+        match repo.git(&["config", "--bool", "core.bare"]) {
+            Ok(output) if output == "true" => Ok(repo),
+            _ => Err(DotfError::Repository(format!(
+                "{} is not a bare git repository",
+                git_dir.display()
+            ))),
+        }
+    }
+
+    /// The first well-known bare repository under `home`
+    pub fn detect(home: &Path) -> Option<Self> {
+        BARE_REPO_CANDIDATES
+            .iter()
+            .map(|candidate| home.join(candidate))
+            .filter(|git_dir| git_dir.is_dir())
+            .find_map(|git_dir| Self::open(&git_dir, home).ok())
+    }
+
+    /// Tracked files, relative to the work tree
+    pub fn tracked_files(&self) -> DotfResult<Vec<String>> {
+        Ok(self
+            .git(&["ls-files"])?
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Tracked files with changes in the work tree that were never committed
+    pub fn uncommitted_files(&self) -> DotfResult<Vec<String>> {
+        Ok(self
+            .git(&["status", "--porcelain", "--untracked-files=no"])?
+            .lines()
+            .filter_map(|line| line.get(3..))
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// The `origin` remote, if the bare repository has one
+    pub fn remote_url(&self) -> Option<String> {
+        self.git(&["remote", "get-url", "origin"])
+            .ok()
+            .filter(|url| !url.is_empty())
+    }
+
+    /// Clone the repository, with full history, into a regular checkout at `destination`
+    pub fn clone_into(&self, destination: &Path) -> DotfResult<()> {
+        let output = Command::new("git")
+            .arg("clone")
+            .arg(&self.git_dir)
+            .arg(destination)
+            .output()
+            .map_err(|e| DotfError::Git(format!("Failed to run git command: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(DotfError::Git(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn git(&self, args: &[&str]) -> DotfResult<String> {
+        let output = Command::new("git")
+            .arg("--git-dir")
+            .arg(&self.git_dir)
+            .arg("--work-tree")
+            .arg(&self.work_tree)
+            .args(args)
+            .output()
+            .map_err(|e| DotfError::Git(format!("Failed to run git command: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(DotfError::Git(
+                String::from_utf8_lossy(&output.stderr).to_string(),
+            ));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .trim_end()
+            .to_string())
+    }
+}
+
+/// A `[symlinks]` table linking every tracked file back to where it lived in `$HOME`
+pub fn render_symlinks(files: &[String]) -> String {
+    let mut files = files.to_vec();
+    files.sort();
+
+    let mut content = String::from(
+        "# Generated by `dotf import bare`; each file links back to where the bare repository kept it\n[symlinks]\n",
+    );
+    for file in files {
+        content.push_str(&format!(
+            "{} = {}\n",
+            toml::Value::String(file.clone()),
+            toml::Value::String(format!("~/{}", file))
+        ));
+    }
+    content
+}
+
+/// Files whose content in `checkout` does not match the copy in `work_tree`
+pub fn unmatched_files(work_tree: &Path, checkout: &Path, files: &[String]) -> Vec<String> {
+    files
+        .iter()
+        .filter(|file| {
+            // Tracked symlinks are compared by where they point, everything else byte for byte
+            let home = work_tree.join(file);
+            let repo = checkout.join(file);
+            match (std::fs::read_link(&home), std::fs::read_link(&repo)) {
+                (Ok(home_link), Ok(repo_link)) => home_link != repo_link,
+                _ => match (std::fs::read(&home), std::fs::read(&repo)) {
+                    (Ok(home_content), Ok(repo_content)) => home_content != repo_content,
+                    _ => true,
+                },
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .args(["-c", "user.name=dotf", "-c", "user.email=dotf@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    /// A fake home directory managed by a bare repository at `~/.cfg`
+    fn setup() -> TempDir {
+        let home = TempDir::new().unwrap();
+        let path = home.path();
+        std::fs::create_dir_all(path.join(".config/nvim")).unwrap();
+        std::fs::write(path.join(".zshrc"), "export A=1\n").unwrap();
+        std::fs::write(path.join(".config/nvim/init.lua"), "vim.o.number = true\n").unwrap();
+        std::fs::write(path.join("untracked.txt"), "not managed\n").unwrap();
+
+        git(path, &["init", "--bare", ".cfg"]);
+        let bare = ["--git-dir=.cfg", "--work-tree=."];
+        git(
+            path,
+            &[&bare[..], &["add", ".zshrc", ".config/nvim/init.lua"]].concat(),
+        );
+        git(path, &[&bare[..], &["commit", "-m", "dotfiles"]].concat());
+        home
+    }
+
+    #[test]
+    fn test_detect_and_list_tracked_files() {
+        let home = setup();
+        let bare = BareRepo::detect(home.path()).unwrap();
+
+        assert_eq!(bare.git_dir, home.path().join(".cfg"));
+        assert_eq!(
+            bare.tracked_files().unwrap(),
+            vec![".config/nvim/init.lua", ".zshrc"]
+        );
+        assert!(bare.uncommitted_files().unwrap().is_empty());
+        assert!(bare.remote_url().is_none());
+        assert!(BareRepo::open(&home.path().join(".config"), home.path()).is_err());
+    }
+
+    #[test]
+    fn test_clone_and_verify() {
+        let home = setup();
+        let bare = BareRepo::detect(home.path()).unwrap();
+        let files = bare.tracked_files().unwrap();
+        let checkout = home.path().join("checkout");
+
+        bare.clone_into(&checkout).unwrap();
+        assert!(unmatched_files(home.path(), &checkout, &files).is_empty());
+
+        std::fs::write(home.path().join(".zshrc"), "export A=2\n").unwrap();
+        assert_eq!(bare.uncommitted_files().unwrap(), vec![".zshrc"]);
+        assert_eq!(
+            unmatched_files(home.path(), &checkout, &files),
+            vec![".zshrc"]
+        );
+    }
+
+    #[test]
+    fn test_render_symlinks() {
+        let config = render_symlinks(&[".zshrc".to_string(), ".config/nvim/init.lua".to_string()]);
+        let parsed: crate::core::config::DotfConfig = toml::from_str(&config).unwrap();
+
+        assert_eq!(parsed.symlinks.len(), 2);
+        assert_eq!(parsed.symlinks[".zshrc"], "~/.zshrc");
+        assert_eq!(
+            parsed.symlinks[".config/nvim/init.lua"],
+            "~/.config/nvim/init.lua"
+        );
+    }
+}
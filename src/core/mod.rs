@@ -1,5 +1,27 @@
+pub mod aliases;
+pub mod bootstrap;
+pub mod clock;
+pub mod completions;
 pub mod config;
+pub mod cron;
+pub mod env;
+pub mod features;
 pub mod filesystem;
+pub mod history;
+pub mod lock;
+pub mod macos;
+pub mod migrate;
+pub mod network;
+pub mod packages;
+pub mod prompts;
 pub mod repository;
+pub mod sandbox;
 pub mod scripts;
+pub mod secrets;
+pub mod ssh;
+pub mod state;
 pub mod symlinks;
+pub mod timings;
+pub mod tools;
+pub mod version_managers;
+pub mod widget;
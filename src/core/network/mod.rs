@@ -0,0 +1,69 @@
+//! Whether the network connection is metered
+
+use std::process::Command;
+
+use crate::core::config::{MeteredMode, Settings};
+use crate::traits::filesystem::FileSystem;
+use crate::utils::platform::find_in_path;
+
+/// Whether to treat the connection as metered: always with `--metered`, otherwise as
+/// `[network] metered` in settings.toml says
+pub async fn is_metered<F: FileSystem>(filesystem: &F, flag: bool) -> bool {
+    if flag {
+        return true;
+    }
+    let mode = filesystem
+        .read_to_string(&filesystem.dotf_settings_path())
+        .await
+        .ok()
+        .and_then(|content| Settings::from_toml(&content).ok())
+        .map(|settings| settings.network.metered)
+        .unwrap_or_default();
+    match mode {
+        MeteredMode::Always => true,
+        MeteredMode::Never => false,
+        MeteredMode::Auto => detect().unwrap_or(false),
+    }
+}
+
+/// Ask NetworkManager whether the primary connection is metered; `None` where it does
+/// not run or does not know
+pub fn detect() -> Option<bool> {
+    find_in_path("busctl")?;
+    let output = Command::new("busctl")
+        .args([
+            "get-property",
+            "org.freedesktop.NetworkManager",
+            "/org/freedesktop/NetworkManager",
+            "org.freedesktop.NetworkManager",
+            "Metered",
+        ])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    parse_metered(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Read NetworkManager's `Metered` property as busctl prints it, e.g. `u 4`
+fn parse_metered(output: &str) -> Option<bool> {
+    // 1 yes, 2 no, 3 guessed yes, 4 guessed no, 0 unknown
+    match output.trim().strip_prefix("u ")?.trim() {
+        "1" | "3" => Some(true),
+        "2" | "4" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_metered() {
+        assert_eq!(parse_metered("u 1\n"), Some(true));
+        assert_eq!(parse_metered("u 3"), Some(true));
+        assert_eq!(parse_metered("u 4\n"), Some(false));
+        assert_eq!(parse_metered("u 0"), None);
+        assert_eq!(parse_metered("Failed to get property"), None);
+    }
+}
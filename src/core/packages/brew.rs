@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+
+use super::{capture, run_install, runs};
+use crate::core::config::dotf_config::Package;
+use crate::error::DotfResult;
+use crate::traits::package_manager::PackageManager;
+
+/// [Homebrew](https://brew.sh) formulae (`brew`) or casks (`cask`)
+pub struct BrewManager {
+    cask: bool,
+}
+
+impl BrewManager {
+    pub fn formulae() -> Self {
+        Self { cask: false }
+    }
+
+    /// Casks are macOS applications; Homebrew on Linux has none
+    pub fn casks() -> Self {
+        Self { cask: true }
+    }
+
+    fn kind_flag(&self) -> &'static str {
+        if self.cask {
+            "--cask"
+        } else {
+            "--formula"
+        }
+    }
+}
+
+#[async_trait]
+impl PackageManager for BrewManager {
+    fn kind(&self) -> &str {
+        if self.cask {
+            "cask"
+        } else {
+            "brew"
+        }
+    }
+
+    fn command(&self) -> &str {
+        "brew"
+    }
+
+    async fn is_available(&self) -> bool {
+        (!self.cask || cfg!(target_os = "macos")) && runs("brew", &["--version"]).await
+    }
+
+    async fn installed(&self) -> DotfResult<Vec<String>> {
+        let output = capture("brew", &["list", self.kind_flag(), "-1"]).await?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn install(&self, package: &Package) -> DotfResult<()> {
+        run_install("brew", &["install", self.kind_flag(), &package.id]).await
+    }
+}
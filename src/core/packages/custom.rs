@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+
+use super::{capture, run_install, runs};
+use crate::core::config::dotf_config::{CustomPackageManager, Package};
+use crate::error::DotfResult;
+use crate::traits::package_manager::PackageManager;
+
+/// Placeholder in the install command that is replaced by the package name
+pub const PACKAGE_PLACEHOLDER: &str = "{package}";
+
+/// A manager declared in `[package_managers.custom.<name>]`, driven through `sh -c`
+pub struct CustomManager {
+    name: String,
+    command: String,
+    config: CustomPackageManager,
+}
+
+impl CustomManager {
+    pub fn new(name: &str, config: CustomPackageManager) -> Self {
+        let command = config
+            .check
+            .split_whitespace()
+            .next()
+            .unwrap_or(name)
+            .to_string();
+        Self {
+            name: name.to_string(),
+            command,
+            config,
+        }
+    }
+}
+
+#[async_trait]
+impl PackageManager for CustomManager {
+    fn kind(&self) -> &str {
+        &self.name
+    }
+
+    fn command(&self) -> &str {
+        &self.command
+    }
+
+    async fn is_available(&self) -> bool {
+        runs("sh", &["-c", &self.config.check]).await
+    }
+
+    async fn installed(&self) -> DotfResult<Vec<String>> {
+        let output = capture("sh", &["-c", &self.config.list]).await?;
+        Ok(output
+            .lines()
+            .filter_map(|line| line.split_whitespace().next())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn install(&self, package: &Package) -> DotfResult<()> {
+        let command = render_install(&self.config.install, &package.id);
+        run_install("sh", &["-c", &command]).await
+    }
+}
+
+/// `{...}` placeholders used in a command template, in order
+pub fn placeholders(template: &str) -> Vec<&str> {
+    let mut found = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        match rest[start..].find('}') {
+            Some(end) => {
+                found.push(&rest[start..start + end + 1]);
+                rest = &rest[start + end + 1..];
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+/// The install command for `id`, single-quoted so the shell passes it as one word
+fn render_install(template: &str, id: &str) -> String {
+    let quoted = format!("'{}'", id.replace('\'', r"'\''"));
+    template.replace(PACKAGE_PLACEHOLDER, &quoted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_install() {
+        assert_eq!(
+            render_install("pipx install {package}", "black"),
+            "pipx install 'black'"
+        );
+        assert_eq!(
+            render_install("nix profile install nixpkgs#{package}", "it's; rm -rf ~"),
+            r"nix profile install nixpkgs#'it'\''s; rm -rf ~'"
+        );
+        assert_eq!(
+            placeholders("pipx install {package} --python {python}"),
+            vec!["{package}", "{python}"]
+        );
+    }
+}
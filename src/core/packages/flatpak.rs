@@ -0,0 +1,72 @@
+use async_trait::async_trait;
+
+use super::{capture, run_install, runs};
+use crate::core::config::dotf_config::Package;
+use crate::error::DotfResult;
+use crate::traits::package_manager::PackageManager;
+
+const FLATHUB_URL: &str = "https://dl.flathub.org/repo/flathub.flatpakrepo";
+
+/// [Flatpak](https://flatpak.org) applications, installed for the current user. The
+/// entry's remote is added first when dotf knows its URL: flathub always, others from
+/// `[packages.flatpak_remotes]`.
+pub struct FlatpakManager;
+
+impl Default for FlatpakManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FlatpakManager {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl PackageManager for FlatpakManager {
+    fn kind(&self) -> &str {
+        "flatpak"
+    }
+
+    fn command(&self) -> &str {
+        "flatpak"
+    }
+
+    async fn is_available(&self) -> bool {
+        cfg!(target_os = "linux") && runs("flatpak", &["--version"]).await
+    }
+
+    async fn installed(&self) -> DotfResult<Vec<String>> {
+        // Covers user and system installations alike
+        let output = capture("flatpak", &["list", "--app", "--columns=application"]).await?;
+        Ok(output
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn install(&self, package: &Package) -> DotfResult<()> {
+        let (remote, url) = match &package.remote {
+            Some(remote) => (remote.name.as_str(), remote.url.as_deref()),
+            None => ("flathub", None),
+        };
+        let url = url.or((remote == "flathub").then_some(FLATHUB_URL));
+        if let Some(url) = url {
+            run_install(
+                "flatpak",
+                &["remote-add", "--user", "--if-not-exists", remote, url],
+            )
+            .await?;
+        }
+
+        run_install(
+            "flatpak",
+            &["install", "--user", "--noninteractive", remote, &package.id],
+        )
+        .await
+    }
+}
@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+
+use super::{capture, run_install, runs};
+use crate::core::config::dotf_config::Package;
+use crate::error::DotfResult;
+use crate::traits::package_manager::PackageManager;
+
+/// Mac App Store apps through [mas](https://github.com/mas-cli/mas), by app ID. The
+/// user must be signed in to the App Store; mas cannot do that for them.
+pub struct MasManager;
+
+impl Default for MasManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MasManager {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl PackageManager for MasManager {
+    fn kind(&self) -> &str {
+        "mas"
+    }
+
+    fn command(&self) -> &str {
+        "mas"
+    }
+
+    async fn is_available(&self) -> bool {
+        cfg!(target_os = "macos") && runs("mas", &["version"]).await
+    }
+
+    async fn installed(&self) -> DotfResult<Vec<String>> {
+        // Lines look like `497799835  Xcode  (15.0)`
+        let output = capture("mas", &["list"]).await?;
+        Ok(parse_list(&output))
+    }
+
+    async fn install(&self, package: &Package) -> DotfResult<()> {
+        run_install("mas", &["install", &package.id]).await
+    }
+}
+
+fn parse_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|id| id.chars().all(|c| c.is_ascii_digit()))
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let output = "497799835  Xcode          (15.0)\n  1295203466 Microsoft Remote Desktop (10.9.5)\nNo installed apps found\n";
+        assert_eq!(parse_list(output), vec!["497799835", "1295203466"]);
+    }
+}
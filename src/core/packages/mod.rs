@@ -0,0 +1,96 @@
+pub mod brew;
+pub mod custom;
+pub mod flatpak;
+pub mod mas;
+pub mod snap;
+
+pub use brew::BrewManager;
+pub use custom::CustomManager;
+pub use flatpak::FlatpakManager;
+pub use mas::MasManager;
+pub use snap::SnapManager;
+
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::core::config::DotfConfig;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::package_manager::PackageManager;
+
+/// The built-in managers, one per `[packages]` kind
+pub fn default_managers() -> Vec<Box<dyn PackageManager>> {
+    vec![
+        Box::new(BrewManager::formulae()),
+        Box::new(BrewManager::casks()),
+        Box::new(MasManager::new()),
+        Box::new(FlatpakManager::new()),
+        Box::new(SnapManager::new()),
+    ]
+}
+
+/// Kinds handled by [`default_managers`]; custom managers cannot reuse them
+pub const BUILT_IN_KINDS: [&str; 5] = ["brew", "cask", "mas", "flatpak", "snap"];
+
+/// Managers declared in `[package_managers.custom]` of dotf.toml
+pub fn custom_managers(config: &DotfConfig) -> Vec<Box<dyn PackageManager>> {
+    config
+        .package_managers
+        .custom
+        .iter()
+        .map(|(name, manager)| {
+            Box::new(CustomManager::new(name, manager.clone())) as Box<dyn PackageManager>
+        })
+        .collect()
+}
+
+/// Whether `program` runs and exits successfully with `args`
+async fn runs(program: &str, args: &[&str]) -> bool {
+    Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Stdout of `program`, or its stderr as the error
+async fn capture(program: &str, args: &[&str]) -> DotfResult<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| DotfError::ScriptExecution(format!("Failed to run {}: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(DotfError::ScriptExecution(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Run an install command; stdin stays attached so installers can ask for a password
+async fn run_install(program: &str, args: &[&str]) -> DotfResult<()> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .await
+        .map_err(|e| DotfError::ScriptExecution(format!("Failed to run {}: {}", program, e)))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let reason = stderr
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or("no output");
+        return Err(DotfError::ScriptExecution(format!(
+            "{} {} failed: {}",
+            program,
+            args.join(" "),
+            reason.trim()
+        )));
+    }
+    Ok(())
+}
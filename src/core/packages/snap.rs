@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+
+use super::{capture, run_install, runs};
+use crate::core::config::dotf_config::Package;
+use crate::error::DotfResult;
+use crate::traits::package_manager::PackageManager;
+
+/// [Snap](https://snapcraft.io) packages. snapd only installs for root, so dotf goes
+/// through sudo when run as a normal user.
+pub struct SnapManager;
+
+impl Default for SnapManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapManager {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl PackageManager for SnapManager {
+    fn kind(&self) -> &str {
+        "snap"
+    }
+
+    fn command(&self) -> &str {
+        "snap"
+    }
+
+    async fn is_available(&self) -> bool {
+        cfg!(target_os = "linux") && runs("snap", &["version"]).await
+    }
+
+    async fn installed(&self) -> DotfResult<Vec<String>> {
+        let output = capture("snap", &["list"]).await?;
+        Ok(parse_list(&output))
+    }
+
+    async fn install(&self, package: &Package) -> DotfResult<()> {
+        if capture("id", &["-u"]).await?.trim() == "0" {
+            run_install("snap", &["install", &package.id]).await
+        } else {
+            run_install("sudo", &["snap", "install", &package.id]).await
+        }
+    }
+}
+
+/// Snap names from `snap list`, whose first line is a header
+fn parse_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .skip(1)
+        .filter_map(|line| line.split_whitespace().next())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        let output = "Name    Version   Rev    Tracking       Publisher   Notes\ncore22  20240111  1122   latest/stable  canonical✓  base\nspotify 1.2.31    80     latest/stable  spotify✓    -\n";
+        assert_eq!(parse_list(output), vec!["core22", "spotify"]);
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::BTreeMap;
+
+use crate::core::config::dotf_config::{EnvConfig, EnvSection, PromptConfig};
+
+/// Prefix of the environment variables that carry prompt answers into scripts
+pub const SCRIPT_ENV_PREFIX: &str = "DOTF_PROMPT_";
+
+/// Declared prompts that have no stored answer yet, in name order
+pub fn unanswered<'a>(
+    declared: &'a BTreeMap<String, PromptConfig>,
+    answers: &BTreeMap<String, String>,
+) -> Vec<(&'a String, &'a PromptConfig)> {
+    declared
+        .iter()
+        .filter(|(name, _)| !answers.contains_key(*name))
+        .collect()
+}
+
+/// Whether `name` can be used as a placeholder and environment variable suffix
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Answers as environment variables for scripts: `git_email` becomes `DOTF_PROMPT_GIT_EMAIL`
+pub fn script_env(answers: &BTreeMap<String, String>) -> BTreeMap<String, String> {
+    answers
+        .iter()
+        .map(|(name, value)| {
+            (
+                format!(
+                    "{}{}",
+                    SCRIPT_ENV_PREFIX,
+                    name.to_ascii_uppercase().replace('-', "_")
+                ),
+                value.clone(),
+            )
+        })
+        .collect()
+}
+
+/// Replace `{{ prompts.<name> }}` placeholders with stored answers; unknown names are left as is
+pub fn substitute(text: &str, answers: &BTreeMap<String, String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        let Some(end) = rest[start..].find("}}") else {
+            break;
+        };
+        let placeholder = &rest[start..start + end + 2];
+        let value = placeholder[2..placeholder.len() - 2]
+            .trim()
+            .strip_prefix("prompts.")
+            .and_then(|name| answers.get(name));
+
+        output.push_str(&rest[..start]);
+        output.push_str(value.map_or(placeholder, String::as_str));
+        rest = &rest[start + end + 2..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// `[env]` with prompt placeholders in variable values filled in
+pub fn apply_to_env(config: &EnvConfig, answers: &BTreeMap<String, String>) -> EnvConfig {
+    let apply = |section: &EnvSection| EnvSection {
+        path: section.path.clone(),
+        vars: section
+            .vars
+            .iter()
+            .map(|(name, value)| (name.clone(), substitute(value, answers)))
+            .collect(),
+    };
+
+    EnvConfig {
+        common: apply(&config.common),
+        macos: config.macos.as_ref().map(apply),
+        linux: config.linux.as_ref().map(apply),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn answers() -> BTreeMap<String, String> {
+        BTreeMap::from([("git_email".to_string(), "me@example.com".to_string())])
+    }
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(
+            substitute("email = {{ prompts.git_email }}", &answers()),
+            "email = me@example.com"
+        );
+        assert_eq!(
+            substitute("{{prompts.git_email}} {{ prompts.missing }} {{", &answers()),
+            "me@example.com {{ prompts.missing }} {{"
+        );
+    }
+
+    #[test]
+    fn test_script_env_and_unanswered() {
+        assert_eq!(
+            script_env(&answers()).get("DOTF_PROMPT_GIT_EMAIL"),
+            Some(&"me@example.com".to_string())
+        );
+
+        let declared: BTreeMap<String, PromptConfig> = toml::from_str(
+            r#"
+git_email = { message = "Git email" }
+work-laptop = { message = "Is this a work laptop?", default = "no" }
+"#,
+        )
+        .unwrap();
+        let pending = unanswered(&declared, &answers());
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, "work-laptop");
+        assert!(is_valid_name("work-laptop"));
+        assert!(!is_valid_name("git email"));
+    }
+}
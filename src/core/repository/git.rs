@@ -1,10 +1,25 @@
 use crate::core::config::DotfConfig;
+use crate::core::repository::{
+    is_local_path, local_remote_path, normalize_remote, parse_log, LOG_FIELD_SEPARATOR,
+    LOG_RECORD_SEPARATOR,
+};
+use crate::core::timings;
 use crate::error::{DotfError, DotfResult};
-use crate::traits::repository::{Repository, RepositoryStatus};
+use crate::traits::repository::{
+    CommitEntry, FetchPolicy, Rename, Repository, RepositoryBackend, RepositoryStatus,
+    UpstreamHistory, AUTO_FETCH_TIMEOUT,
+};
 use async_trait::async_trait;
-use std::process::Command;
+use chrono::Utc;
+use std::collections::HashSet;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::time::Duration;
 
-pub struct GitRepository;
+pub struct GitRepository {
+    /// Fetch only the upstream branch, without tags or submodules (metered connections)
+    minimal_fetch: bool,
+}
 
 impl Default for GitRepository {
     fn default() -> Self {
@@ -14,10 +29,87 @@ impl Default for GitRepository {
 
 impl GitRepository {
     pub fn new() -> Self {
-        Self
+        Self {
+            minimal_fetch: false,
+        }
+    }
+
+    /// Keep clones, pulls and fetches to the current branch, without tags or submodules
+    pub fn with_minimal_fetch(mut self, minimal_fetch: bool) -> Self {
+        self.minimal_fetch = minimal_fetch;
+        self
+    }
+
+    /// Arguments that keep a clone, pull or fetch minimal when minimal fetching is on
+    fn minimal_args(&self, single_branch: bool) -> Vec<&'static str> {
+        if !self.minimal_fetch {
+            return Vec::new();
+        }
+        let mut args = vec!["--no-tags", "--no-recurse-submodules"];
+        if single_branch {
+            args.push("--single-branch");
+        }
+        args
+    }
+
+    /// Update the remote-tracking branches; with minimal fetching only the upstream of
+    /// the current branch
+    fn fetch(&self, repo_path: &str) -> DotfResult<()> {
+        let args = self.fetch_args(repo_path)?;
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        self.run_git_command(&args, Some(repo_path))?;
+        Ok(())
+    }
+
+    fn fetch_args(&self, repo_path: &str) -> DotfResult<Vec<String>> {
+        if !self.minimal_fetch {
+            return Ok(vec!["fetch".to_string(), "origin".to_string()]);
+        }
+        let upstream = self.run_git_command(
+            &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+            Some(repo_path),
+        )?;
+        let (remote, branch) = upstream.split_once('/').unwrap_or(("origin", &upstream));
+        let mut args = vec!["fetch"];
+        args.extend(self.minimal_args(false));
+        args.extend([remote, branch]);
+        Ok(args.into_iter().map(str::to_string).collect())
+    }
+
+    /// Like `fetch`, but stop waiting after `timeout`; true when the fetch succeeded in
+    /// time. A fetch that is still running is left to finish in the background, so the
+    /// next status sees its result.
+    async fn fetch_within(&self, repo_path: &str, timeout: Duration) -> bool {
+        let Ok(args) = self.fetch_args(repo_path) else {
+            return false;
+        };
+        tracing::debug!(?args, repo_path, "running git with a timeout");
+        let Ok(mut child) = tokio::process::Command::new("git")
+            .args(&args)
+            .current_dir(repo_path)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            return false;
+        };
+        match tokio::time::timeout(timeout, child.wait()).await {
+            Ok(status) => status.is_ok_and(|status| status.success()),
+            Err(_) => {
+                tracing::debug!(repo_path, "fetch still running, using the last fetch");
+                false
+            }
+        }
     }
 
     fn run_git_command(&self, args: &[&str], cwd: Option<&str>) -> DotfResult<String> {
+        Ok(self.git_output(args, cwd)?.trim().to_string())
+    }
+
+    /// Like `run_git_command`, with the output left untrimmed
+    fn git_output(&self, args: &[&str], cwd: Option<&str>) -> DotfResult<String> {
         let mut cmd = Command::new("git");
         cmd.args(args);
 
@@ -25,29 +117,143 @@ impl GitRepository {
             cmd.current_dir(cwd);
         }
 
+        tracing::debug!(?args, ?cwd, "running git");
         let output = cmd
             .output()
             .map_err(|e| DotfError::Git(format!("Failed to run git command: {}", e)))?;
 
         if !output.status.success() {
-            return Err(DotfError::Git(
-                String::from_utf8_lossy(&output.stderr).to_string(),
-            ));
+            return Err(command_error(args, cwd, &output));
         }
 
-        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Like `run_git_command`, with `input` on stdin and the output left untrimmed
+    fn run_git_with_input(&self, args: &[&str], cwd: &str, input: &str) -> DotfResult<String> {
+        tracing::debug!(?args, cwd, "running git with input");
+        let mut child = Command::new("git")
+            .args(args)
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| DotfError::Git(format!("Failed to run git command: {}", e)))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(input.as_bytes())?;
+        }
+        let output = child
+            .wait_with_output()
+            .map_err(|e| DotfError::Git(format!("Failed to run git command: {}", e)))?;
+        if !output.status.success() {
+            return Err(command_error(args, Some(cwd), &output));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn is_ancestor(&self, ancestor: &str, descendant: &str, repo_path: &str) -> bool {
+        self.run_git_command(
+            &["merge-base", "--is-ancestor", ancestor, descendant],
+            Some(repo_path),
+        )
+        .is_ok()
+    }
+}
+
+/// The failure of `git <args>`, logged with its arguments and named by its subcommand
+fn command_error(args: &[&str], cwd: Option<&str>, output: &std::process::Output) -> DotfError {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    tracing::warn!(?args, ?cwd, status = ?output.status.code(), stderr = %stderr.trim(), "git failed");
+    DotfError::Git(format!(
+        "git {} failed: {}",
+        args.first().copied().unwrap_or_default(),
+        stderr.trim()
+    ))
+}
+
+/// Explain signing failures, which git reports in gpg/ssh-keygen terms
+fn signing_error(error: DotfError) -> DotfError {
+    match error {
+        DotfError::Git(message)
+            if ["failed to sign", "signing failed", "gpg.ssh", "cannot run gpg"]
+                .iter()
+                .any(|needle| message.to_lowercase().contains(needle)) =>
+        {
+            DotfError::Git(format!(
+                "Commit signing failed: {}. Check that your signing key (user.signingkey, gpg.format) is available and gpg-agent or ssh-agent is running",
+                message.trim()
+            ))
+        }
+        other => other,
+    }
+}
+
+/// Sparse-checkout pattern matching exactly `path` and, for a directory, its content
+fn sparse_pattern(path: &str) -> String {
+    let mut pattern = String::from("/");
+    for c in path.trim_end_matches('/').chars() {
+        if matches!(c, '*' | '?' | '[' | '\\') {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    pattern
+}
+
+/// Paths in `git status --porcelain -z` output; both sides of a rename are included
+fn parse_porcelain(output: &str) -> HashSet<String> {
+    let mut paths = HashSet::new();
+    let mut records = output.split('\0').filter(|record| !record.is_empty());
+    while let Some(record) = records.next() {
+        let (status, path) = (record.get(..2).unwrap_or_default(), record.get(3..));
+        if let Some(path) = path {
+            paths.insert(path.to_string());
+        }
+        // The source of a rename or copy follows as a record of its own
+        if status.contains(['R', 'C']) {
+            if let Some(source) = records.next() {
+                paths.insert(source.to_string());
+            }
+        }
+    }
+    paths
+}
+
+impl RepositoryBackend for GitRepository {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn handles_url(&self, _url: &str) -> bool {
+        // Git accepts too many URL forms to recognise reliably; it is the default backend
+        true
+    }
+
+    fn handles_checkout(&self, repo_path: &str) -> bool {
+        std::path::Path::new(repo_path).join(".git").exists()
     }
 }
 
 #[async_trait]
 impl Repository for GitRepository {
     async fn validate_remote(&self, url: &str) -> DotfResult<()> {
+        let url = &normalize_remote(url);
+        if let Some(path) = local_remote_path(url).filter(|path| !path.exists()) {
+            return Err(DotfError::Repository(format!(
+                "No repository at '{}'",
+                path.display()
+            )));
+        }
+
         // Use git ls-remote to validate the repository
         self.run_git_command(&["ls-remote", "--exit-code", url], None)?;
         Ok(())
     }
 
     async fn fetch_config(&self, url: &str) -> DotfResult<DotfConfig> {
+        let url = &normalize_remote(url);
         // Create a temporary directory for sparse checkout
         let temp_dir = tempfile::tempdir().map_err(DotfError::Io)?;
         let temp_path = temp_dir.path().to_string_lossy();
@@ -97,6 +303,7 @@ impl Repository for GitRepository {
     }
 
     async fn fetch_config_from_branch(&self, url: &str, branch: &str) -> DotfResult<DotfConfig> {
+        let url = &normalize_remote(url);
         // Create a temporary directory for sparse checkout
         let temp_dir = tempfile::tempdir().map_err(DotfError::Io)?;
         let temp_path = temp_dir.path().to_string_lossy();
@@ -139,37 +346,44 @@ impl Repository for GitRepository {
     }
 
     async fn clone(&self, url: &str, destination: &str) -> DotfResult<()> {
+        let url = &normalize_remote(url);
         // Get default branch and clone with that branch
         let default_branch = self
             .get_default_branch(url)
             .await
             .unwrap_or_else(|_| "main".to_string());
-        self.run_git_command(
-            &["clone", "--branch", &default_branch, url, destination],
-            None,
-        )?;
-        Ok(())
+        self.clone_branch(url, &default_branch, destination).await
     }
 
     async fn clone_branch(&self, url: &str, branch: &str, destination: &str) -> DotfResult<()> {
-        self.run_git_command(&["clone", "--branch", branch, url, destination], None)?;
+        let url = &normalize_remote(url);
+        let mut args = vec!["clone"];
+        args.extend(self.minimal_args(true));
+        args.extend(["--branch", branch, url, destination]);
+        self.run_git_command(&args, None)?;
         Ok(())
     }
 
     async fn pull(&self, repo_path: &str) -> DotfResult<()> {
+        let _timer = timings::phase("git pull");
         // Get the current branch
         let current_branch =
             self.run_git_command(&["rev-parse", "--abbrev-ref", "HEAD"], Some(repo_path))?;
 
         // Pull from origin with the current branch
-        self.run_git_command(
-            &["pull", "--rebase", "origin", &current_branch],
-            Some(repo_path),
-        )?;
+        let mut args = vec!["pull", "--rebase"];
+        args.extend(self.minimal_args(false));
+        args.extend(["origin", &current_branch]);
+        self.run_git_command(&args, Some(repo_path))?;
         Ok(())
     }
 
-    async fn get_status(&self, repo_path: &str) -> DotfResult<RepositoryStatus> {
+    async fn get_status(
+        &self,
+        repo_path: &str,
+        fetch: FetchPolicy,
+    ) -> DotfResult<RepositoryStatus> {
+        let status_timer = timings::phase("git status");
         // Check if working tree is clean
         let status_output = self.run_git_command(&["status", "--porcelain"], Some(repo_path))?;
         let is_clean = status_output.is_empty();
@@ -177,9 +391,17 @@ impl Repository for GitRepository {
         // Get current branch
         let current_branch =
             self.run_git_command(&["rev-parse", "--abbrev-ref", "HEAD"], Some(repo_path))?;
+        drop(status_timer);
 
-        // Fetch to get latest remote info
-        let _ = self.run_git_command(&["fetch"], Some(repo_path));
+        // Fetch to get latest remote info; without it the remote-tracking branch still
+        // holds what the last fetch saw
+        let fetch_timer = timings::phase("git fetch");
+        let fetched = match fetch {
+            FetchPolicy::Always => self.fetch(repo_path).is_ok(),
+            FetchPolicy::Auto => self.fetch_within(repo_path, AUTO_FETCH_TIMEOUT).await,
+            FetchPolicy::Never => false,
+        };
+        drop(fetch_timer);
 
         // Get ahead/behind counts
         let rev_list = self
@@ -204,6 +426,8 @@ impl Repository for GitRepository {
             ahead_count,
             behind_count,
             current_branch,
+            tracks_upstream: true,
+            fetched,
         })
     }
 
@@ -224,7 +448,19 @@ impl Repository for GitRepository {
         Ok(!output.trim().is_empty())
     }
 
+    async fn get_modified_files(&self, repo_path: &str) -> DotfResult<HashSet<String>> {
+        let _timer = timings::phase("git status");
+        // -z keeps unusual file names unquoted; every untracked file is listed on its
+        // own rather than collapsed into its directory
+        let output = self.git_output(
+            &["status", "--porcelain", "-z", "--untracked-files=all"],
+            Some(repo_path),
+        )?;
+        Ok(parse_porcelain(&output))
+    }
+
     async fn get_default_branch(&self, url: &str) -> DotfResult<String> {
+        let url = &normalize_remote(url);
         // Use git ls-remote to get the default branch (HEAD)
         let output = self.run_git_command(&["ls-remote", "--symref", url, "HEAD"], None)?;
 
@@ -245,6 +481,7 @@ impl Repository for GitRepository {
     }
 
     async fn branch_exists(&self, url: &str, branch: &str) -> DotfResult<bool> {
+        let url = &normalize_remote(url);
         // Use git ls-remote to check if branch exists
         let result = self.run_git_command(&["ls-remote", "--heads", url, branch], None);
 
@@ -259,16 +496,719 @@ impl Repository for GitRepository {
             }
         }
     }
+
+    async fn stage(&self, repo_path: &str, file_path: &str) -> DotfResult<()> {
+        self.run_git_command(&["add", "--", file_path], Some(repo_path))?;
+        Ok(())
+    }
+
+    async fn commit(
+        &self,
+        repo_path: &str,
+        message: &str,
+        paths: &[String],
+        sign: bool,
+    ) -> DotfResult<()> {
+        let mut args = vec!["commit", "-m", message];
+        if sign {
+            args.push("-S");
+        }
+        if !paths.is_empty() {
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+        }
+        self.run_git_command(&args, Some(repo_path))
+            .map_err(signing_error)?;
+        Ok(())
+    }
+
+    async fn add_all(&self, repo_path: &str) -> DotfResult<Vec<String>> {
+        self.run_git_command(&["add", "--all"], Some(repo_path))?;
+        let staged =
+            self.run_git_command(&["diff", "--cached", "--name-only", "-z"], Some(repo_path))?;
+        Ok(staged
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn push(&self, repo_path: &str) -> DotfResult<()> {
+        // Also sets the upstream for branches created locally
+        self.run_git_command(
+            &["push", "--set-upstream", "origin", "HEAD"],
+            Some(repo_path),
+        )?;
+        Ok(())
+    }
+
+    async fn log(
+        &self,
+        repo_path: &str,
+        paths: &[String],
+        limit: usize,
+    ) -> DotfResult<Vec<CommitEntry>> {
+        let format = format!(
+            "--format={0}%h{1}%an{1}%aI{1}%s",
+            LOG_RECORD_SEPARATOR, LOG_FIELD_SEPARATOR
+        );
+        let limit = format!("--max-count={}", limit);
+        let mut args = vec!["log", "--name-only", format.as_str(), limit.as_str(), "--"];
+        args.extend(paths.iter().map(String::as_str));
+
+        Ok(parse_log(&self.run_git_command(&args, Some(repo_path))?))
+    }
+
+    async fn export_ignored(&self, repo_path: &str, paths: &[String]) -> DotfResult<Vec<String>> {
+        if paths.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Paths go through stdin so large directories don't hit the argument length limit
+        let output = self.run_git_with_input(
+            &["check-attr", "-z", "--stdin", "export-ignore"],
+            repo_path,
+            &paths.join("\0"),
+        )?;
+
+        // Output is `<path> NUL <attribute> NUL <value> NUL` for every path
+        let fields: Vec<&str> = output.split('\0').collect();
+        Ok(fields
+            .chunks_exact(3)
+            .filter(|record| record[2] == "set")
+            .map(|record| record[0].to_string())
+            .collect())
+    }
+
+    async fn gc(&self, repo_path: &str) -> DotfResult<()> {
+        // --auto leaves a repository alone until git's own thresholds say it needs packing
+        self.run_git_command(&["gc", "--auto", "--quiet"], Some(repo_path))?;
+        Ok(())
+    }
+
+    async fn revision(&self, repo_path: &str) -> DotfResult<Option<String>> {
+        Ok(Some(self.run_git_command(
+            &["rev-parse", "HEAD"],
+            Some(repo_path),
+        )?))
+    }
+
+    async fn changed_since(&self, repo_path: &str, revision: &str) -> DotfResult<Vec<String>> {
+        // Diffing against the working tree covers committed, staged and unstaged changes
+        let changed = self.run_git_command(
+            &["diff", "--name-only", "-z", revision, "--"],
+            Some(repo_path),
+        )?;
+        let untracked = self.run_git_command(
+            &["ls-files", "-z", "--others", "--exclude-standard"],
+            Some(repo_path),
+        )?;
+
+        Ok(changed
+            .split('\0')
+            .chain(untracked.split('\0'))
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn renames(&self, repo_path: &str, from: &str, to: &str) -> DotfResult<Vec<Rename>> {
+        // With -z each rename is "R<similarity>", the old path and the new path
+        let output = self.git_output(
+            &[
+                "diff",
+                "--name-status",
+                "-z",
+                "--find-renames",
+                "--diff-filter=R",
+                from,
+                to,
+            ],
+            Some(repo_path),
+        )?;
+        let fields: Vec<&str> = output.split('\0').collect();
+        Ok(fields
+            .chunks(3)
+            .filter(|record| record.len() == 3 && record[0].starts_with('R'))
+            .map(|record| Rename {
+                from: record[1].to_string(),
+                to: record[2].to_string(),
+            })
+            .collect())
+    }
+
+    async fn clone_sparse(
+        &self,
+        url: &str,
+        branch: &str,
+        destination: &str,
+        paths: &[String],
+    ) -> DotfResult<()> {
+        // A plain path makes git copy the object store as-is and ignore --filter; the
+        // file:// transport honours it
+        let mut url = normalize_remote(url);
+        if is_local_path(&url) {
+            url = format!("file://{}", url);
+        }
+
+        // Blobs outside the sparse set are fetched only if they are ever checked out
+        self.run_git_command(
+            &[
+                "clone",
+                "--filter=blob:none",
+                "--no-checkout",
+                "--branch",
+                branch,
+                &url,
+                destination,
+            ],
+            None,
+        )?;
+        self.set_sparse_paths(destination, Some(paths)).await?;
+        self.run_git_command(&["checkout", branch], Some(destination))?;
+        Ok(())
+    }
+
+    async fn set_sparse_paths(&self, repo_path: &str, paths: Option<&[String]>) -> DotfResult<()> {
+        let Some(paths) = paths else {
+            let sparse = self
+                .run_git_command(
+                    &["config", "--bool", "core.sparseCheckout"],
+                    Some(repo_path),
+                )
+                .is_ok_and(|value| value == "true");
+            if sparse {
+                self.run_git_command(&["sparse-checkout", "disable"], Some(repo_path))?;
+            }
+            return Ok(());
+        };
+
+        // Non-cone patterns, anchored to the root, so single files can be listed too
+        let patterns: Vec<String> = paths.iter().map(|path| sparse_pattern(path)).collect();
+        self.run_git_with_input(
+            &["sparse-checkout", "set", "--no-cone", "--stdin"],
+            repo_path,
+            &patterns.join("\n"),
+        )?;
+        Ok(())
+    }
+
+    async fn signing_configured(&self, repo_path: &str) -> DotfResult<bool> {
+        // `git config` exits non-zero when the key is unset
+        Ok(self
+            .run_git_command(&["config", "--bool", "commit.gpgsign"], Some(repo_path))
+            .is_ok_and(|value| value == "true"))
+    }
+
+    async fn test_signing(&self, repo_path: &str) -> DotfResult<()> {
+        // commit-tree writes a dangling object without moving any ref
+        self.run_git_command(
+            &[
+                "commit-tree",
+                "-S",
+                "-m",
+                "dotf signing check",
+                "HEAD^{tree}",
+            ],
+            Some(repo_path),
+        )
+        .map_err(signing_error)?;
+        Ok(())
+    }
+
+    async fn upstream_history(&self, repo_path: &str) -> DotfResult<UpstreamHistory> {
+        // Without an upstream there is nothing to compare; pull reports that itself
+        let Ok(upstream) = self.run_git_command(
+            &["rev-parse", "--abbrev-ref", "--symbolic-full-name", "@{u}"],
+            Some(repo_path),
+        ) else {
+            return Ok(UpstreamHistory::Linear);
+        };
+
+        self.fetch(repo_path)?;
+
+        if self
+            .run_git_command(&["merge-base", "HEAD", &upstream], Some(repo_path))
+            .is_err()
+        {
+            return Ok(UpstreamHistory::Unrelated);
+        }
+
+        // Pulling just fast-forwards when HEAD is already part of the upstream
+        if self.is_ancestor("HEAD", &upstream, repo_path) {
+            return Ok(UpstreamHistory::Linear);
+        }
+
+        // After a force push the previous upstream tip (kept in the reflog) is no
+        // longer contained in the new one
+        let previous = format!("{}@{{1}}", upstream);
+        let has_previous = self
+            .run_git_command(
+                &["rev-parse", "--verify", "--quiet", &previous],
+                Some(repo_path),
+            )
+            .is_ok();
+        if has_previous && !self.is_ancestor(&previous, &upstream, repo_path) {
+            let local_commits = self
+                .run_git_command(
+                    &["rev-list", "--count", &format!("{}..HEAD", upstream)],
+                    Some(repo_path),
+                )?
+                .parse()
+                .unwrap_or(0);
+            return Ok(UpstreamHistory::Rewritten { local_commits });
+        }
+
+        Ok(UpstreamHistory::Linear)
+    }
+
+    async fn rescue_and_reset(&self, repo_path: &str) -> DotfResult<String> {
+        let status = self.run_git_command(&["status", "--porcelain"], Some(repo_path))?;
+        if !status.is_empty() {
+            return Err(DotfError::Repository(
+                "Commit or stash local changes before resetting to the upstream".to_string(),
+            ));
+        }
+
+        let branch = format!("dotf-rescue/{}", Utc::now().format("%Y%m%d-%H%M%S"));
+        self.run_git_command(&["branch", &branch, "HEAD"], Some(repo_path))?;
+        self.run_git_command(&["reset", "--hard", "@{u}"], Some(repo_path))?;
+
+        Ok(branch)
+    }
+
+    async fn stash(&self, repo_path: &str) -> DotfResult<bool> {
+        let status = self.run_git_command(&["status", "--porcelain"], Some(repo_path))?;
+        if status.is_empty() {
+            return Ok(false);
+        }
+        self.run_git_command(
+            &[
+                "stash",
+                "push",
+                "--include-untracked",
+                "--message",
+                "dotf sync --autostash",
+            ],
+            Some(repo_path),
+        )?;
+        Ok(true)
+    }
+
+    async fn stash_pop(&self, repo_path: &str) -> DotfResult<Vec<String>> {
+        let error = match self.run_git_command(&["stash", "pop"], Some(repo_path)) {
+            Ok(_) => return Ok(Vec::new()),
+            Err(e) => e,
+        };
+        // A conflicting pop leaves markers in the files and keeps the stash entry
+        let conflicts =
+            self.run_git_command(&["diff", "--name-only", "--diff-filter=U"], Some(repo_path))?;
+        if conflicts.is_empty() {
+            return Err(error);
+        }
+        Ok(conflicts.lines().map(str::to_string).collect())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use std::path::Path;
+    use tempfile::TempDir;
+
     #[test]
     fn test_git_repository_creation() {
         let repo = GitRepository::new();
         // Just ensure we can create an instance
         let _ = repo;
     }
+
+    fn git(dir: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .args(["-c", "user.name=dotf", "-c", "user.email=dotf@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "git {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    fn commit_file(dir: &Path, name: &str, content: &str) {
+        std::fs::write(dir.join(name), content).unwrap();
+        git(dir, &["add", name]);
+        git(dir, &["commit", "-m", name]);
+    }
+
+    /// A bare remote, an author clone that pushes to it and a local clone under test
+    fn setup() -> (TempDir, std::path::PathBuf, std::path::PathBuf) {
+        let temp = TempDir::new().unwrap();
+        let remote = temp.path().join("remote.git");
+        let author = temp.path().join("author");
+        let local = temp.path().join("local");
+
+        std::fs::create_dir(&remote).unwrap();
+        git(&remote, &["init", "--bare", "-b", "main"]);
+        git(temp.path(), &["clone", "remote.git", "author"]);
+        git(&author, &["checkout", "-b", "main"]);
+        commit_file(&author, "zshrc", "one");
+        commit_file(&author, "gitconfig", "two");
+        git(&author, &["push", "origin", "main"]);
+        git(temp.path(), &["clone", "remote.git", "local"]);
+
+        (temp, author, local)
+    }
+
+    #[tokio::test]
+    async fn test_get_modified_files() {
+        let (_temp, _author, local) = setup();
+        std::fs::write(local.join("zshrc"), "changed").unwrap();
+        git(&local, &["mv", "gitconfig", "git config"]);
+        std::fs::create_dir(local.join("nvim")).unwrap();
+        std::fs::write(local.join("nvim/init.lua"), "new").unwrap();
+
+        let repo = GitRepository::new();
+        let modified = repo
+            .get_modified_files(&local.to_string_lossy())
+            .await
+            .unwrap();
+        assert_eq!(
+            modified,
+            HashSet::from(
+                ["zshrc", "gitconfig", "git config", "nvim/init.lua"].map(str::to_string)
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_commit_paths_leaves_other_staged_changes() {
+        let (_temp, _author, local) = setup();
+        git(&local, &["config", "user.name", "dotf"]);
+        git(&local, &["config", "user.email", "dotf@example.com"]);
+        std::fs::write(local.join("zshrc"), "adopted").unwrap();
+        std::fs::write(local.join("gitconfig"), "staged by the user").unwrap();
+        git(&local, &["add", "zshrc", "gitconfig"]);
+
+        let repo = GitRepository::new();
+        repo.commit(
+            &local.to_string_lossy(),
+            "Adopt zshrc",
+            &["zshrc".to_string()],
+            false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            git(&local, &["show", "--name-only", "--format=", "HEAD"]).trim(),
+            "zshrc"
+        );
+        assert_eq!(
+            git(&local, &["diff", "--cached", "--name-only"]).trim(),
+            "gitconfig"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upstream_history_linear() {
+        let (_temp, author, local) = setup();
+        commit_file(&local, "vimrc", "local");
+        commit_file(&author, "tmux.conf", "upstream");
+        git(&author, &["push", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let local = local.to_string_lossy();
+        assert_eq!(
+            repo.upstream_history(&local).await.unwrap(),
+            UpstreamHistory::Linear
+        );
+    }
+
+    #[tokio::test]
+    async fn test_status_fetch_policy() {
+        let (_temp, author, local) = setup();
+        commit_file(&author, "tmux.conf", "upstream");
+        git(&author, &["push", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let path = local.to_string_lossy().to_string();
+        let status = repo.get_status(&path, FetchPolicy::Never).await.unwrap();
+        assert!(!status.fetched);
+        assert_eq!(status.behind_count, 0);
+
+        let status = repo.get_status(&path, FetchPolicy::Auto).await.unwrap();
+        assert!(status.fetched);
+        assert_eq!(status.behind_count, 1);
+
+        // Counts from the last fetch remain once the remote is unreachable
+        git(
+            &local,
+            &["remote", "set-url", "origin", "/nonexistent/dotfiles"],
+        );
+        let status = repo.get_status(&path, FetchPolicy::Always).await.unwrap();
+        assert!(!status.fetched);
+        assert_eq!(status.behind_count, 1);
+    }
+
+    #[tokio::test]
+    async fn test_renames_between_revisions() {
+        let (_temp, author, local) = setup();
+        std::fs::create_dir(author.join("zsh")).unwrap();
+        git(&author, &["mv", "zshrc", "zsh/.zshrc"]);
+        commit_file(&author, "gitconfig", "changed, not moved");
+        git(&author, &["push", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let path = local.to_string_lossy().to_string();
+        let before = repo.revision(&path).await.unwrap().unwrap();
+        repo.pull(&path).await.unwrap();
+        let after = repo.revision(&path).await.unwrap().unwrap();
+        assert_eq!(
+            repo.renames(&path, &before, &after).await.unwrap(),
+            vec![Rename {
+                from: "zshrc".to_string(),
+                to: "zsh/.zshrc".to_string(),
+            }]
+        );
+        assert!(repo
+            .renames(&path, &after, &after)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_stash_around_pull() {
+        let (_temp, author, local) = setup();
+        git(&local, &["config", "user.name", "dotf"]);
+        git(&local, &["config", "user.email", "dotf@example.com"]);
+        std::fs::write(local.join("zshrc"), "local edit").unwrap();
+        std::fs::write(local.join("vimrc"), "untracked").unwrap();
+        commit_file(&author, "tmux.conf", "upstream");
+        git(&author, &["push", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let path = local.to_string_lossy().to_string();
+        assert!(repo.stash(&path).await.unwrap());
+        assert!(
+            repo.get_status(&path, FetchPolicy::Never)
+                .await
+                .unwrap()
+                .is_clean
+        );
+        repo.pull(&path).await.unwrap();
+        assert!(repo.stash_pop(&path).await.unwrap().is_empty());
+        assert_eq!(
+            std::fs::read_to_string(local.join("zshrc")).unwrap(),
+            "local edit"
+        );
+        assert!(local.join("vimrc").exists());
+        assert!(local.join("tmux.conf").exists());
+
+        // An edit the pulled commits also made conflicts and stays stashed
+        git(&local, &["checkout", "--", "zshrc"]);
+        std::fs::remove_file(local.join("vimrc")).unwrap();
+        assert!(!repo.stash(&path).await.unwrap());
+        std::fs::write(local.join("gitconfig"), "local edit").unwrap();
+        commit_file(&author, "gitconfig", "upstream edit");
+        git(&author, &["push", "origin", "main"]);
+        assert!(repo.stash(&path).await.unwrap());
+        repo.pull(&path).await.unwrap();
+        assert_eq!(repo.stash_pop(&path).await.unwrap(), vec!["gitconfig"]);
+        assert_eq!(git(&local, &["stash", "list"]).lines().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rescue_after_force_push() {
+        let (_temp, author, local) = setup();
+        commit_file(&local, "vimrc", "local");
+        let local_head = git(&local, &["rev-parse", "HEAD"]);
+
+        std::fs::write(author.join("gitconfig"), "rewritten").unwrap();
+        git(
+            &author,
+            &["commit", "--amend", "-am", "gitconfig (amended)"],
+        );
+        git(&author, &["push", "--force", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let path = local.to_string_lossy().to_string();
+        assert_eq!(
+            repo.upstream_history(&path).await.unwrap(),
+            UpstreamHistory::Rewritten { local_commits: 2 }
+        );
+
+        let branch = repo.rescue_and_reset(&path).await.unwrap();
+        assert!(branch.starts_with("dotf-rescue/"));
+        assert_eq!(git(&local, &["rev-parse", &branch]), local_head);
+        assert_eq!(
+            git(&local, &["rev-parse", "HEAD"]),
+            git(&author, &["rev-parse", "HEAD"])
+        );
+        assert_eq!(
+            repo.upstream_history(&path).await.unwrap(),
+            UpstreamHistory::Linear
+        );
+    }
+
+    #[tokio::test]
+    async fn test_upstream_history_unrelated() {
+        let (_temp, author, local) = setup();
+        git(&author, &["checkout", "--orphan", "fresh"]);
+        commit_file(&author, "bashrc", "new start");
+        git(&author, &["push", "--force", "origin", "fresh:main"]);
+
+        let repo = GitRepository::new();
+        let local = local.to_string_lossy();
+        assert_eq!(
+            repo.upstream_history(&local).await.unwrap(),
+            UpstreamHistory::Unrelated
+        );
+    }
+
+    #[tokio::test]
+    async fn test_signing_failure_is_explained() {
+        let (_temp, _author, local) = setup();
+        git(&local, &["config", "user.name", "dotf"]);
+        git(&local, &["config", "user.email", "dotf@example.com"]);
+        git(&local, &["config", "commit.gpgsign", "true"]);
+        git(&local, &["config", "gpg.program", "false"]);
+
+        let repo = GitRepository::new();
+        let path = local.to_string_lossy();
+        assert!(repo.signing_configured(&path).await.unwrap());
+
+        let error = repo.test_signing(&path).await.unwrap_err().to_string();
+        assert!(error.contains("Commit signing failed"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_log_lists_changed_files() {
+        let (_temp, author, _local) = setup();
+        commit_file(&author, "zshrc", "three");
+
+        let repo = GitRepository::new();
+        let path = author.to_string_lossy();
+        let commits = repo.log(&path, &[], 10).await.unwrap();
+        assert_eq!(commits.len(), 3);
+        assert_eq!(commits[0].files, vec!["zshrc"]);
+        assert_eq!(commits[0].summary, "zshrc");
+        assert_eq!(commits[0].author, "dotf");
+
+        let commits = repo
+            .log(&path, &["gitconfig".to_string()], 10)
+            .await
+            .unwrap();
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].files, vec!["gitconfig"]);
+    }
+
+    #[tokio::test]
+    async fn test_export_ignored_reads_gitattributes() {
+        let temp = TempDir::new().unwrap();
+        std::fs::write(
+            temp.path().join(".gitattributes"),
+            "vim/pack/plugin/doc export-ignore\n*.md export-ignore\n",
+        )
+        .unwrap();
+        git(temp.path(), &["init"]);
+
+        let repo = GitRepository::new();
+        let paths: Vec<String> = [
+            "vim/pack/plugin/doc",
+            "vim/pack/plugin/plugin.vim",
+            "vim/README.md",
+        ]
+        .iter()
+        .map(|path| path.to_string())
+        .collect();
+        let ignored = repo
+            .export_ignored(&temp.path().to_string_lossy(), &paths)
+            .await
+            .unwrap();
+
+        assert_eq!(ignored, vec!["vim/pack/plugin/doc", "vim/README.md"]);
+    }
+
+    #[tokio::test]
+    async fn test_local_path_remotes() {
+        let (temp, author, _) = setup();
+        commit_file(
+            &author,
+            "dotf.toml",
+            "[symlinks]\n\"zshrc\" = \"~/.zshrc\"\n",
+        );
+        git(&author, &["push", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let remote = temp.path().join("remote.git");
+        let path = format!("{}/", remote.display());
+        let file_url = format!("file://{}", remote.display());
+
+        for (index, url) in [path, file_url].iter().enumerate() {
+            repo.validate_remote(url).await.unwrap();
+            assert_eq!(repo.get_default_branch(url).await.unwrap(), "main");
+            assert!(repo.branch_exists(url, "main").await.unwrap());
+            let config = repo.fetch_config_from_branch(url, "main").await.unwrap();
+            assert_eq!(config.symlinks.len(), 1);
+
+            let clone = temp.path().join(format!("clone-{}", index));
+            let clone_path = clone.to_string_lossy().to_string();
+            repo.clone_branch(url, "main", &clone_path).await.unwrap();
+            assert!(clone.join("dotf.toml").exists());
+
+            commit_file(&author, &format!("file-{}", index), "new");
+            git(&author, &["push", "origin", "main"]);
+            repo.pull(&clone_path).await.unwrap();
+            assert!(clone.join(format!("file-{}", index)).exists());
+        }
+
+        let missing = temp.path().join("missing.git");
+        let error = repo
+            .validate_remote(&missing.to_string_lossy())
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("No repository at"));
+    }
+
+    #[tokio::test]
+    async fn test_clone_sparse_then_widen() {
+        let (temp, author, _) = setup();
+        std::fs::create_dir(author.join("walls")).unwrap();
+        commit_file(&author, "walls/big.png", "pixels");
+        git(&author, &["push", "origin", "main"]);
+
+        let repo = GitRepository::new();
+        let url = format!("file://{}", temp.path().join("remote.git").display());
+        let sparse = temp.path().join("sparse");
+        let sparse_path = sparse.to_string_lossy().to_string();
+        repo.clone_sparse(&url, "main", &sparse_path, &["zshrc".to_string()])
+            .await
+            .unwrap();
+
+        assert!(sparse.join("zshrc").exists());
+        assert!(!sparse.join("gitconfig").exists());
+        assert!(!sparse.join("walls").exists());
+        assert!(
+            repo.get_status(&sparse_path, FetchPolicy::Never)
+                .await
+                .unwrap()
+                .is_clean
+        );
+
+        let paths = vec!["zshrc".to_string(), "walls".to_string()];
+        repo.set_sparse_paths(&sparse_path, Some(&paths))
+            .await
+            .unwrap();
+        assert!(sparse.join("walls/big.png").exists());
+
+        repo.set_sparse_paths(&sparse_path, None).await.unwrap();
+        assert!(sparse.join("gitconfig").exists());
+    }
 }
@@ -1,6 +1,6 @@
 use crate::core::config::DotfConfig;
 use crate::error::DotfResult;
-use crate::traits::repository::{Repository, RepositoryStatus};
+use crate::traits::repository::{FetchPolicy, Repository, RepositoryStatus};
 use std::sync::Arc;
 
 pub struct RepositoryManager<R>
@@ -36,8 +36,12 @@ where
         self.repository.pull(repo_path).await
     }
 
-    pub async fn get_repository_status(&self, repo_path: &str) -> DotfResult<RepositoryStatus> {
-        self.repository.get_status(repo_path).await
+    pub async fn get_repository_status(
+        &self,
+        repo_path: &str,
+        fetch: FetchPolicy,
+    ) -> DotfResult<RepositoryStatus> {
+        self.repository.get_status(repo_path, fetch).await
     }
 
     pub async fn get_remote_url(&self, repo_path: &str) -> DotfResult<String> {
@@ -54,9 +58,21 @@ mod tests {
     async fn test_repository_manager_validate_and_fetch() {
         let mut mock_repo = MockRepository::new();
         mock_repo.set_config_response(DotfConfig {
-            symlinks: std::collections::HashMap::new(),
+            symlinks: Default::default(),
             scripts: crate::core::config::dotf_config::ScriptsConfig::default(),
             platform: crate::core::config::dotf_config::PlatformConfig::default(),
+            tools: Default::default(),
+            bootstrap: Default::default(),
+            macos: Default::default(),
+            cron: Default::default(),
+            env: Default::default(),
+            prompts: Default::default(),
+            ssh: Default::default(),
+            secrets: Default::default(),
+            hooks: Default::default(),
+            packages: Default::default(),
+            package_managers: Default::default(),
+            profile: Default::default(),
         });
 
         let manager = RepositoryManager::new(mock_repo);
@@ -93,10 +109,15 @@ mod tests {
             ahead_count: 2,
             behind_count: 1,
             current_branch: "main".to_string(),
+            tracks_upstream: true,
+            fetched: true,
         });
 
         let manager = RepositoryManager::new(mock_repo);
-        let status = manager.get_repository_status("/tmp/repo").await.unwrap();
+        let status = manager
+            .get_repository_status("/tmp/repo", FetchPolicy::Always)
+            .await
+            .unwrap();
 
         assert!(status.is_clean);
         assert_eq!(status.ahead_count, 2);
@@ -0,0 +1,411 @@
+use crate::core::config::DotfConfig;
+use crate::core::repository::{parse_log, LOG_FIELD_SEPARATOR, LOG_RECORD_SEPARATOR};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::repository::{
+    CommitEntry, FetchPolicy, Rename, Repository, RepositoryBackend, RepositoryStatus,
+    UpstreamHistory,
+};
+use async_trait::async_trait;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Command;
+
+/// Mercurial repositories, selected with `hg+<url>` or `hg::<url>` remotes
+pub struct MercurialRepository;
+
+impl Default for MercurialRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MercurialRepository {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Strip the dotf scheme prefix, leaving a URL hg understands
+    fn hg_url(url: &str) -> &str {
+        url.strip_prefix("hg::")
+            .or_else(|| url.strip_prefix("hg+"))
+            .unwrap_or(url)
+    }
+
+    fn run_hg_command(&self, args: &[&str], cwd: Option<&str>) -> DotfResult<String> {
+        let mut cmd = Command::new("hg");
+        cmd.args(args);
+
+        if let Some(cwd) = cwd {
+            cmd.current_dir(cwd);
+        }
+
+        tracing::debug!(?args, ?cwd, "running hg");
+        let output = cmd
+            .output()
+            .map_err(|e| DotfError::Repository(format!("Failed to run hg command: {}", e)))?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            tracing::warn!(?args, ?cwd, status = ?output.status.code(), stderr = %stderr.trim(), "hg failed");
+            return Err(DotfError::Repository(stderr.to_string()));
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    }
+
+    /// Count changesets reported by `hg incoming`/`hg outgoing` (which exit 1 when there are none)
+    fn count_changesets(&self, command: &str, repo_path: &str) -> usize {
+        self.run_hg_command(&[command, "-q", "--template", "x\\n"], Some(repo_path))
+            .map(|output| output.lines().filter(|l| !l.is_empty()).count())
+            .unwrap_or(0)
+    }
+
+    fn read_config(&self, checkout: &Path) -> DotfResult<DotfConfig> {
+        let config_path = checkout.join("dotf.toml");
+        let alt_config_path = checkout.join(".dotf/dotf.toml");
+
+        let config_content = if config_path.exists() {
+            std::fs::read_to_string(config_path).map_err(DotfError::Io)?
+        } else if alt_config_path.exists() {
+            std::fs::read_to_string(alt_config_path).map_err(DotfError::Io)?
+        } else {
+            return Err(DotfError::Config(
+                "dotf.toml not found in repository".to_string(),
+            ));
+        };
+
+        toml::from_str(&config_content)
+            .map_err(|e| DotfError::Config(format!("Invalid dotf.toml: {}", e)))
+    }
+}
+
+impl RepositoryBackend for MercurialRepository {
+    fn name(&self) -> &'static str {
+        "mercurial"
+    }
+
+    fn handles_url(&self, url: &str) -> bool {
+        url.starts_with("hg+") || url.starts_with("hg::")
+    }
+
+    fn handles_checkout(&self, repo_path: &str) -> bool {
+        Path::new(repo_path).join(".hg").is_dir()
+    }
+}
+
+#[async_trait]
+impl Repository for MercurialRepository {
+    async fn validate_remote(&self, url: &str) -> DotfResult<()> {
+        self.run_hg_command(&["identify", Self::hg_url(url)], None)?;
+        Ok(())
+    }
+
+    async fn fetch_config(&self, url: &str) -> DotfResult<DotfConfig> {
+        self.fetch_config_from_branch(url, "default").await
+    }
+
+    async fn fetch_config_from_branch(&self, url: &str, branch: &str) -> DotfResult<DotfConfig> {
+        let temp_dir = tempfile::tempdir().map_err(DotfError::Io)?;
+        let checkout = temp_dir.path().join("checkout");
+
+        self.run_hg_command(
+            &[
+                "clone",
+                "--updaterev",
+                branch,
+                Self::hg_url(url),
+                &checkout.to_string_lossy(),
+            ],
+            None,
+        )?;
+
+        self.read_config(&checkout)
+    }
+
+    async fn clone(&self, url: &str, destination: &str) -> DotfResult<()> {
+        self.run_hg_command(&["clone", Self::hg_url(url), destination], None)?;
+        Ok(())
+    }
+
+    async fn clone_branch(&self, url: &str, branch: &str, destination: &str) -> DotfResult<()> {
+        self.run_hg_command(
+            &[
+                "clone",
+                "--updaterev",
+                branch,
+                Self::hg_url(url),
+                destination,
+            ],
+            None,
+        )?;
+        Ok(())
+    }
+
+    async fn pull(&self, repo_path: &str) -> DotfResult<()> {
+        self.run_hg_command(&["pull", "--update"], Some(repo_path))?;
+        Ok(())
+    }
+
+    async fn get_status(
+        &self,
+        repo_path: &str,
+        fetch: FetchPolicy,
+    ) -> DotfResult<RepositoryStatus> {
+        let status_output = self.run_hg_command(&["status"], Some(repo_path))?;
+        let current_branch = self.run_hg_command(&["branch"], Some(repo_path))?;
+
+        // Mercurial keeps no record of the remote, so without asking it the counts are unknown
+        let fetched = fetch != FetchPolicy::Never;
+        let (ahead_count, behind_count) = if fetched {
+            (
+                self.count_changesets("outgoing", repo_path),
+                self.count_changesets("incoming", repo_path),
+            )
+        } else {
+            (0, 0)
+        };
+
+        Ok(RepositoryStatus {
+            is_clean: status_output.is_empty(),
+            ahead_count,
+            behind_count,
+            current_branch,
+            tracks_upstream: true,
+            fetched,
+        })
+    }
+
+    async fn get_remote_url(&self, repo_path: &str) -> DotfResult<String> {
+        self.run_hg_command(&["paths", "default"], Some(repo_path))
+    }
+
+    async fn is_file_modified(&self, repo_path: &str, file_path: &str) -> DotfResult<bool> {
+        let output = self.run_hg_command(&["status", file_path], Some(repo_path))?;
+        Ok(!output.trim().is_empty())
+    }
+
+    async fn get_modified_files(&self, repo_path: &str) -> DotfResult<HashSet<String>> {
+        let output =
+            self.run_hg_command(&["status", "--no-status", "--print0"], Some(repo_path))?;
+        Ok(output
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn get_default_branch(&self, _url: &str) -> DotfResult<String> {
+        Ok("default".to_string())
+    }
+
+    async fn branch_exists(&self, url: &str, branch: &str) -> DotfResult<bool> {
+        Ok(self
+            .run_hg_command(&["identify", "--rev", branch, Self::hg_url(url)], None)
+            .is_ok())
+    }
+
+    async fn stage(&self, repo_path: &str, file_path: &str) -> DotfResult<()> {
+        // Mercurial has no index: modified tracked files are committed as-is, and
+        // `hg add` only matters (and only succeeds) for untracked files
+        let _ = self.run_hg_command(&["add", file_path], Some(repo_path));
+        Ok(())
+    }
+
+    async fn commit(
+        &self,
+        repo_path: &str,
+        message: &str,
+        paths: &[String],
+        sign: bool,
+    ) -> DotfResult<()> {
+        if sign {
+            return Err(DotfError::Repository(
+                "Signed commits are not supported for Mercurial checkouts; unset commit.sign"
+                    .to_string(),
+            ));
+        }
+        let mut args = vec!["commit", "-m", message];
+        args.extend(paths.iter().map(String::as_str));
+        self.run_hg_command(&args, Some(repo_path))?;
+        Ok(())
+    }
+
+    async fn add_all(&self, repo_path: &str) -> DotfResult<Vec<String>> {
+        self.run_hg_command(&["addremove"], Some(repo_path))?;
+        let changed = self.run_hg_command(&["status", "-mard", "--no-status"], Some(repo_path))?;
+        Ok(changed.lines().map(str::to_string).collect())
+    }
+
+    async fn push(&self, repo_path: &str) -> DotfResult<()> {
+        // `hg push` exits 1 when there is nothing to push
+        if self.count_changesets("outgoing", repo_path) == 0 {
+            return Ok(());
+        }
+        self.run_hg_command(&["push"], Some(repo_path))?;
+        Ok(())
+    }
+
+    async fn log(
+        &self,
+        repo_path: &str,
+        paths: &[String],
+        limit: usize,
+    ) -> DotfResult<Vec<CommitEntry>> {
+        let template = format!(
+            "{0}{{node|short}}{1}{{author|person}}{1}{{date|rfc3339date}}{1}{{desc|firstline}}\n{{files % '{{file}}\n'}}",
+            LOG_RECORD_SEPARATOR, LOG_FIELD_SEPARATOR
+        );
+        let limit = limit.to_string();
+        let mut args = vec![
+            "log",
+            "--limit",
+            limit.as_str(),
+            "--template",
+            template.as_str(),
+        ];
+        args.extend(paths.iter().map(String::as_str));
+
+        Ok(parse_log(&self.run_hg_command(&args, Some(repo_path))?))
+    }
+
+    async fn export_ignored(&self, _repo_path: &str, _paths: &[String]) -> DotfResult<Vec<String>> {
+        // Mercurial has no gitattributes equivalent
+        Ok(Vec::new())
+    }
+
+    async fn gc(&self, _repo_path: &str) -> DotfResult<()> {
+        // Revlogs need no periodic packing
+        Ok(())
+    }
+
+    async fn revision(&self, repo_path: &str) -> DotfResult<Option<String>> {
+        Ok(Some(self.run_hg_command(
+            &["log", "-r", ".", "--template", "{node}"],
+            Some(repo_path),
+        )?))
+    }
+
+    async fn changed_since(&self, repo_path: &str, revision: &str) -> DotfResult<Vec<String>> {
+        // Without a second revision hg compares against the working copy, unknown files included
+        let changed = self.run_hg_command(
+            &["status", "--rev", revision, "--no-status", "--print0"],
+            Some(repo_path),
+        )?;
+        Ok(changed
+            .split('\0')
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    async fn renames(&self, repo_path: &str, from: &str, to: &str) -> DotfResult<Vec<Rename>> {
+        // Added files are followed by the file they were copied from; a copy whose
+        // original was removed is a rename
+        let output = self.run_hg_command(
+            &[
+                "status",
+                "--rev",
+                from,
+                "--rev",
+                to,
+                "--added",
+                "--removed",
+                "--copies",
+            ],
+            Some(repo_path),
+        )?;
+        let removed: HashSet<&str> = output
+            .lines()
+            .filter_map(|line| line.strip_prefix("R "))
+            .collect();
+        let mut renames = Vec::new();
+        let mut added = None;
+        for line in output.lines() {
+            if let Some(path) = line.strip_prefix("A ") {
+                added = Some(path);
+            } else if let (Some(origin), Some(path)) = (line.strip_prefix("  "), added.take()) {
+                if removed.contains(origin) {
+                    renames.push(Rename {
+                        from: origin.to_string(),
+                        to: path.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(renames)
+    }
+
+    async fn clone_sparse(
+        &self,
+        url: &str,
+        branch: &str,
+        destination: &str,
+        _paths: &[String],
+    ) -> DotfResult<()> {
+        // hg's narrow clones need a server extension, so the whole repository is cloned
+        self.clone_branch(url, branch, destination).await
+    }
+
+    async fn set_sparse_paths(
+        &self,
+        _repo_path: &str,
+        _paths: Option<&[String]>,
+    ) -> DotfResult<()> {
+        Ok(())
+    }
+
+    async fn signing_configured(&self, _repo_path: &str) -> DotfResult<bool> {
+        Ok(false)
+    }
+
+    async fn test_signing(&self, _repo_path: &str) -> DotfResult<()> {
+        Err(DotfError::Repository(
+            "Signed commits are not supported for Mercurial checkouts".to_string(),
+        ))
+    }
+
+    async fn upstream_history(&self, _repo_path: &str) -> DotfResult<UpstreamHistory> {
+        // Mercurial history is append-only by default, so pulls never need rescuing
+        Ok(UpstreamHistory::Linear)
+    }
+
+    async fn rescue_and_reset(&self, _repo_path: &str) -> DotfResult<String> {
+        Err(DotfError::Repository(
+            "Rescue branches are only supported for git repositories".to_string(),
+        ))
+    }
+
+    async fn stash(&self, _repo_path: &str) -> DotfResult<bool> {
+        Err(DotfError::Repository(
+            "--autostash is only supported for git repositories; commit or shelve local changes first".to_string(),
+        ))
+    }
+
+    async fn stash_pop(&self, _repo_path: &str) -> DotfResult<Vec<String>> {
+        Err(DotfError::Repository(
+            "--autostash is only supported for git repositories".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handles_url() {
+        let repo = MercurialRepository::new();
+        assert!(repo.handles_url("hg+https://hg.example.com/dotfiles"));
+        assert!(repo.handles_url("hg::ssh://hg@example.com/dotfiles"));
+        assert!(!repo.handles_url("https://github.com/user/dotfiles"));
+
+        assert_eq!(
+            MercurialRepository::hg_url("hg+https://hg.example.com/dotfiles"),
+            "https://hg.example.com/dotfiles"
+        );
+        assert_eq!(
+            MercurialRepository::hg_url("hg::ssh://hg@example.com/dotfiles"),
+            "ssh://hg@example.com/dotfiles"
+        );
+    }
+}
@@ -1,5 +1,127 @@
 pub mod git;
 pub mod manager;
+pub mod mercurial;
+pub mod registry;
+pub mod sparse;
+pub mod tarball;
 
 pub use git::GitRepository;
 pub use manager::RepositoryManager;
+pub use mercurial::MercurialRepository;
+pub use registry::RepositoryRegistry;
+pub use sparse::sparse_paths;
+pub use tarball::TarballRepository;
+
+use std::path::{Path, PathBuf};
+
+use crate::traits::repository::CommitEntry;
+
+/// Separates commits in log output requested from the backends' CLIs
+pub(crate) const LOG_RECORD_SEPARATOR: char = '\u{1e}';
+/// Separates the header fields of one commit in log output
+pub(crate) const LOG_FIELD_SEPARATOR: char = '\u{1f}';
+
+/// Parse log output where each record is `id␟author␟rfc3339-date␟summary`
+/// followed by one changed file per line
+pub(crate) fn parse_log(output: &str) -> Vec<CommitEntry> {
+    output
+        .split(LOG_RECORD_SEPARATOR)
+        .filter_map(|record| {
+            let mut lines = record.lines().filter(|line| !line.trim().is_empty());
+            let mut fields = lines.next()?.splitn(4, LOG_FIELD_SEPARATOR);
+            let id = fields.next()?.trim().to_string();
+            let author = fields.next()?.to_string();
+            let date = chrono::DateTime::parse_from_rfc3339(fields.next()?).ok()?;
+            let summary = fields.next().unwrap_or_default().to_string();
+            Some(CommitEntry {
+                id,
+                author,
+                date,
+                summary,
+                files: lines.map(|line| line.trim().to_string()).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Whether git reads `url` as a path on this machine: it has no scheme, and no `host:`
+/// prefix as in `git@github.com:user/dotfiles`
+pub fn is_local_path(url: &str) -> bool {
+    if url.contains("://") {
+        return false;
+    }
+    match url.find(':') {
+        Some(colon) => url[..colon].contains('/'),
+        None => true,
+    }
+}
+
+/// `url` with a local path made absolute, so it names the same repository when git runs
+/// in another directory or dotf stores it in settings. `~/` expands to the home
+/// directory; `file://` URLs and network remotes are returned unchanged.
+pub fn normalize_remote(url: &str) -> String {
+    if !is_local_path(url) {
+        return url.to_string();
+    }
+    let path = match (url.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest),
+        _ => PathBuf::from(url),
+    };
+    std::path::absolute(&path)
+        .map(|path| path.to_string_lossy().trim_end_matches('/').to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// The directory a `file://` URL or local path points at, or `None` for network remotes
+pub fn local_remote_path(url: &str) -> Option<PathBuf> {
+    match url.strip_prefix("file://") {
+        Some(path) => Some(PathBuf::from(path)),
+        None if is_local_path(url) => Some(Path::new(&normalize_remote(url)).to_path_buf()),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_remote() {
+        assert_eq!(
+            normalize_remote("https://github.com/user/dotfiles"),
+            "https://github.com/user/dotfiles"
+        );
+        assert_eq!(
+            normalize_remote("git@github.com:user/dotfiles.git"),
+            "git@github.com:user/dotfiles.git"
+        );
+        assert_eq!(
+            normalize_remote("file:///srv/git/dotfiles.git"),
+            "file:///srv/git/dotfiles.git"
+        );
+        assert_eq!(
+            normalize_remote("/srv/git/dotfiles.git/"),
+            "/srv/git/dotfiles.git"
+        );
+        assert_eq!(
+            normalize_remote("dotfiles.git"),
+            std::env::current_dir()
+                .unwrap()
+                .join("dotfiles.git")
+                .to_string_lossy()
+        );
+        assert_eq!(
+            normalize_remote("~/code/dotfiles"),
+            dirs::home_dir()
+                .unwrap()
+                .join("code/dotfiles")
+                .to_string_lossy()
+        );
+
+        assert_eq!(
+            local_remote_path("file:///srv/git/dotfiles.git"),
+            Some(PathBuf::from("/srv/git/dotfiles.git"))
+        );
+        assert_eq!(local_remote_path("git@github.com:user/dotfiles.git"), None);
+    }
+}
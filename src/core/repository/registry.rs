@@ -0,0 +1,284 @@
+use super::{GitRepository, MercurialRepository, TarballRepository};
+use crate::core::config::DotfConfig;
+use crate::error::DotfResult;
+use crate::traits::repository::{
+    CommitEntry, FetchPolicy, Rename, Repository, RepositoryBackend, RepositoryStatus,
+    UpstreamHistory,
+};
+use async_trait::async_trait;
+use std::collections::HashSet;
+
+/// Dispatches repository operations to the backend that handles a URL or checkout.
+///
+/// URL-based operations (validate, fetch, clone) select a backend by remote URL;
+/// checkout-based ones (pull, status, commit) by the layout of the local clone.
+/// Git is the fallback for anything no other backend claims.
+pub struct RepositoryRegistry {
+    backends: Vec<Box<dyn RepositoryBackend>>,
+    fallback: GitRepository,
+}
+
+impl Default for RepositoryRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepositoryRegistry {
+    /// Registry with the built-in Mercurial and tarball backends
+    pub fn new() -> Self {
+        Self {
+            backends: vec![
+                Box::new(MercurialRepository::new()),
+                Box::new(TarballRepository::new()),
+            ],
+            fallback: GitRepository::new(),
+        }
+    }
+
+    /// Keep git clones, pulls and fetches to the current branch, without tags or
+    /// submodules. The other backends always transfer what they need.
+    pub fn with_minimal_fetch(mut self, minimal_fetch: bool) -> Self {
+        self.fallback = GitRepository::new().with_minimal_fetch(minimal_fetch);
+        self
+    }
+
+    /// Register an additional backend; it takes precedence over the built-in ones
+    pub fn register(mut self, backend: Box<dyn RepositoryBackend>) -> Self {
+        self.backends.insert(0, backend);
+        self
+    }
+
+    fn for_url(&self, url: &str) -> &dyn RepositoryBackend {
+        self.backends
+            .iter()
+            .find(|backend| backend.handles_url(url))
+            .map(|backend| backend.as_ref())
+            .unwrap_or(&self.fallback)
+    }
+
+    fn for_checkout(&self, repo_path: &str) -> &dyn RepositoryBackend {
+        self.backends
+            .iter()
+            .find(|backend| backend.handles_checkout(repo_path))
+            .map(|backend| backend.as_ref())
+            .unwrap_or(&self.fallback)
+    }
+
+    /// Name of the backend that would handle `url`
+    pub fn backend_name(&self, url: &str) -> &'static str {
+        self.for_url(url).name()
+    }
+}
+
+#[async_trait]
+impl Repository for RepositoryRegistry {
+    async fn validate_remote(&self, url: &str) -> DotfResult<()> {
+        self.for_url(url).validate_remote(url).await
+    }
+
+    async fn fetch_config(&self, url: &str) -> DotfResult<DotfConfig> {
+        self.for_url(url).fetch_config(url).await
+    }
+
+    async fn fetch_config_from_branch(&self, url: &str, branch: &str) -> DotfResult<DotfConfig> {
+        self.for_url(url)
+            .fetch_config_from_branch(url, branch)
+            .await
+    }
+
+    async fn clone(&self, url: &str, destination: &str) -> DotfResult<()> {
+        self.for_url(url).clone(url, destination).await
+    }
+
+    async fn clone_branch(&self, url: &str, branch: &str, destination: &str) -> DotfResult<()> {
+        self.for_url(url)
+            .clone_branch(url, branch, destination)
+            .await
+    }
+
+    async fn pull(&self, repo_path: &str) -> DotfResult<()> {
+        self.for_checkout(repo_path).pull(repo_path).await
+    }
+
+    async fn get_status(
+        &self,
+        repo_path: &str,
+        fetch: FetchPolicy,
+    ) -> DotfResult<RepositoryStatus> {
+        self.for_checkout(repo_path)
+            .get_status(repo_path, fetch)
+            .await
+    }
+
+    async fn get_remote_url(&self, repo_path: &str) -> DotfResult<String> {
+        self.for_checkout(repo_path).get_remote_url(repo_path).await
+    }
+
+    async fn is_file_modified(&self, repo_path: &str, file_path: &str) -> DotfResult<bool> {
+        self.for_checkout(repo_path)
+            .is_file_modified(repo_path, file_path)
+            .await
+    }
+
+    async fn get_modified_files(&self, repo_path: &str) -> DotfResult<HashSet<String>> {
+        self.for_checkout(repo_path)
+            .get_modified_files(repo_path)
+            .await
+    }
+
+    async fn get_default_branch(&self, url: &str) -> DotfResult<String> {
+        self.for_url(url).get_default_branch(url).await
+    }
+
+    async fn branch_exists(&self, url: &str, branch: &str) -> DotfResult<bool> {
+        self.for_url(url).branch_exists(url, branch).await
+    }
+
+    async fn stage(&self, repo_path: &str, file_path: &str) -> DotfResult<()> {
+        self.for_checkout(repo_path)
+            .stage(repo_path, file_path)
+            .await
+    }
+
+    async fn commit(
+        &self,
+        repo_path: &str,
+        message: &str,
+        paths: &[String],
+        sign: bool,
+    ) -> DotfResult<()> {
+        self.for_checkout(repo_path)
+            .commit(repo_path, message, paths, sign)
+            .await
+    }
+
+    async fn add_all(&self, repo_path: &str) -> DotfResult<Vec<String>> {
+        self.for_checkout(repo_path).add_all(repo_path).await
+    }
+
+    async fn push(&self, repo_path: &str) -> DotfResult<()> {
+        self.for_checkout(repo_path).push(repo_path).await
+    }
+
+    async fn log(
+        &self,
+        repo_path: &str,
+        paths: &[String],
+        limit: usize,
+    ) -> DotfResult<Vec<CommitEntry>> {
+        self.for_checkout(repo_path)
+            .log(repo_path, paths, limit)
+            .await
+    }
+
+    async fn export_ignored(&self, repo_path: &str, paths: &[String]) -> DotfResult<Vec<String>> {
+        self.for_checkout(repo_path)
+            .export_ignored(repo_path, paths)
+            .await
+    }
+
+    async fn gc(&self, repo_path: &str) -> DotfResult<()> {
+        self.for_checkout(repo_path).gc(repo_path).await
+    }
+
+    async fn revision(&self, repo_path: &str) -> DotfResult<Option<String>> {
+        self.for_checkout(repo_path).revision(repo_path).await
+    }
+
+    async fn changed_since(&self, repo_path: &str, revision: &str) -> DotfResult<Vec<String>> {
+        self.for_checkout(repo_path)
+            .changed_since(repo_path, revision)
+            .await
+    }
+
+    async fn renames(&self, repo_path: &str, from: &str, to: &str) -> DotfResult<Vec<Rename>> {
+        self.for_checkout(repo_path)
+            .renames(repo_path, from, to)
+            .await
+    }
+
+    async fn clone_sparse(
+        &self,
+        url: &str,
+        branch: &str,
+        destination: &str,
+        paths: &[String],
+    ) -> DotfResult<()> {
+        self.for_url(url)
+            .clone_sparse(url, branch, destination, paths)
+            .await
+    }
+
+    async fn set_sparse_paths(&self, repo_path: &str, paths: Option<&[String]>) -> DotfResult<()> {
+        self.for_checkout(repo_path)
+            .set_sparse_paths(repo_path, paths)
+            .await
+    }
+
+    async fn signing_configured(&self, repo_path: &str) -> DotfResult<bool> {
+        self.for_checkout(repo_path)
+            .signing_configured(repo_path)
+            .await
+    }
+
+    async fn test_signing(&self, repo_path: &str) -> DotfResult<()> {
+        self.for_checkout(repo_path).test_signing(repo_path).await
+    }
+
+    async fn upstream_history(&self, repo_path: &str) -> DotfResult<UpstreamHistory> {
+        self.for_checkout(repo_path)
+            .upstream_history(repo_path)
+            .await
+    }
+
+    async fn rescue_and_reset(&self, repo_path: &str) -> DotfResult<String> {
+        self.for_checkout(repo_path)
+            .rescue_and_reset(repo_path)
+            .await
+    }
+
+    async fn stash(&self, repo_path: &str) -> DotfResult<bool> {
+        self.for_checkout(repo_path).stash(repo_path).await
+    }
+
+    async fn stash_pop(&self, repo_path: &str) -> DotfResult<Vec<String>> {
+        self.for_checkout(repo_path).stash_pop(repo_path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backend_selection_by_url() {
+        let registry = RepositoryRegistry::new();
+        assert_eq!(
+            registry.backend_name("hg+https://hg.example.com/dotfiles"),
+            "mercurial"
+        );
+        assert_eq!(
+            registry.backend_name("https://example.com/dotfiles.tar.gz"),
+            "tarball"
+        );
+        assert_eq!(
+            registry.backend_name("https://github.com/user/dotfiles"),
+            "git"
+        );
+        assert_eq!(registry.backend_name("git@github.com:user/dotfiles"), "git");
+    }
+
+    #[test]
+    fn test_backend_selection_by_checkout() {
+        let registry = RepositoryRegistry::new();
+        let temp_dir = TempDir::new().unwrap();
+        let repo_path = temp_dir.path().to_string_lossy().to_string();
+
+        assert_eq!(registry.for_checkout(&repo_path).name(), "git");
+
+        std::fs::create_dir(temp_dir.path().join(".hg")).unwrap();
+        assert_eq!(registry.for_checkout(&repo_path).name(), "mercurial");
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeSet;
+
+use crate::core::config::DotfConfig;
+
+/// Repository paths a machine on `platform` needs: dotf.toml itself and every source,
+/// script and SSH file it references, sorted. Paths of every profile are included so
+/// switching profiles needs no new checkout. Absolute sources live outside the
+/// repository and are left out.
+pub fn sparse_paths(config: &DotfConfig, platform: &str) -> Vec<String> {
+    let mut paths = vec!["dotf.toml".to_string()];
+
+    paths.extend(config.symlinks.keys().cloned());
+    if let Some(platform_config) = config.platform.for_platform(platform) {
+        paths.extend(platform_config.symlinks.keys().cloned());
+    }
+
+    for scripts in std::iter::once(&config.scripts)
+        .chain(config.profile.values().map(|profile| &profile.scripts))
+    {
+        let deps = match platform {
+            "macos" => scripts.deps.macos.as_ref(),
+            "linux" => scripts.deps.linux.as_ref(),
+            _ => None,
+        };
+        paths.extend(deps.cloned());
+        paths.extend(scripts.custom.values().cloned());
+    }
+    for profile in config.profile.values() {
+        paths.extend(profile.symlinks.keys().cloned());
+    }
+
+    paths.extend(config.ssh.config.iter().cloned());
+    paths.extend(config.ssh.known_hosts.iter().cloned());
+    paths.extend(config.ssh.keys.keys().cloned());
+
+    paths
+        .iter()
+        .map(|path| path.trim_start_matches("./"))
+        .filter(|path| !path.is_empty() && !path.starts_with('/'))
+        .map(str::to_string)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sparse_paths() {
+        let config: DotfConfig = toml::from_str(
+            r#"
+[symlinks]
+"nvim" = "~/.config/nvim"
+"./zsh/.zshrc" = "~/.zshrc"
+"/etc/dotf/hosts" = "~/.hosts"
+
+[platform.macos.symlinks]
+"macos/karabiner" = "~/.config/karabiner"
+
+[platform.linux.symlinks]
+"linux/sway" = "~/.config/sway"
+
+[scripts.deps]
+macos = "scripts/deps-macos.sh"
+linux = "scripts/deps-linux.sh"
+
+[scripts.custom]
+fonts = "scripts/fonts.sh"
+
+[ssh]
+config = ["ssh/config"]
+
+[profile.work.symlinks]
+"work/.gitconfig" = "~/.gitconfig"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            sparse_paths(&config, "linux"),
+            vec![
+                "dotf.toml",
+                "linux/sway",
+                "nvim",
+                "scripts/deps-linux.sh",
+                "scripts/fonts.sh",
+                "ssh/config",
+                "work/.gitconfig",
+                "zsh/.zshrc",
+            ]
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use crate::core::config::DotfConfig;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::repository::{
+    CommitEntry, FetchPolicy, Rename, Repository, RepositoryBackend, RepositoryStatus,
+    UpstreamHistory, AUTO_FETCH_TIMEOUT,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// File written at the root of an extracted tarball to remember where it came from
+const SOURCE_FILE: &str = ".dotf-source.json";
+
+/// Label shown in place of a branch name; tarballs have no branches
+const TARBALL_BRANCH: &str = "tarball";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TarballSource {
+    url: String,
+    etag: Option<String>,
+    fetched_at: DateTime<Utc>,
+}
+
+/// Plain `.tar.gz` snapshots served over HTTP(S), selected with `tar+<url>` remotes or
+/// URLs ending in `.tar.gz`/`.tgz`/`.tar`.
+///
+/// Updates are detected by comparing the server's ETag with the one recorded at the
+/// last download. There is no history, so the checkout is read-only from dotf's view.
+pub struct TarballRepository {
+    client: reqwest::Client,
+}
+
+impl Default for TarballRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TarballRepository {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn http_url(url: &str) -> &str {
+        url.strip_prefix("tar+").unwrap_or(url)
+    }
+
+    async fn remote_etag(&self, url: &str) -> DotfResult<Option<String>> {
+        let response = self
+            .client
+            .head(Self::http_url(url))
+            .send()
+            .await?
+            .error_for_status()?;
+
+        Ok(response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string()))
+    }
+
+    /// Download and extract the archive, returning the directory holding its contents
+    /// together with the response ETag.
+    async fn download(&self, url: &str, into: &Path) -> DotfResult<(PathBuf, Option<String>)> {
+        let response = self
+            .client
+            .get(Self::http_url(url))
+            .send()
+            .await?
+            .error_for_status()?;
+        let etag = response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string());
+        let bytes = response.bytes().await?;
+
+        fs::create_dir_all(into).map_err(DotfError::Io)?;
+        let archive = into.join("archive");
+        fs::write(&archive, &bytes).map_err(DotfError::Io)?;
+
+        let extracted = into.join("extracted");
+        fs::create_dir_all(&extracted).map_err(DotfError::Io)?;
+
+        let output = Command::new("tar")
+            .arg("-xf")
+            .arg(&archive)
+            .arg("-C")
+            .arg(&extracted)
+            .output()
+            .map_err(|e| DotfError::Repository(format!("Failed to run tar: {}", e)))?;
+        if !output.status.success() {
+            return Err(DotfError::Repository(format!(
+                "Failed to extract {}: {}",
+                url,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        Ok((archive_root(&extracted)?, etag))
+    }
+
+    fn read_source(repo_path: &str) -> DotfResult<TarballSource> {
+        let content =
+            fs::read_to_string(Path::new(repo_path).join(SOURCE_FILE)).map_err(DotfError::Io)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    fn write_source(root: &Path, url: &str, etag: Option<String>) -> DotfResult<()> {
+        let source = TarballSource {
+            url: url.to_string(),
+            etag,
+            fetched_at: Utc::now(),
+        };
+        fs::write(
+            root.join(SOURCE_FILE),
+            serde_json::to_string_pretty(&source)?,
+        )
+        .map_err(DotfError::Io)
+    }
+
+    /// Staging directory next to `destination`, so the final rename stays on one filesystem
+    fn staging_dir(destination: &str) -> PathBuf {
+        PathBuf::from(format!(
+            "{}.dotf-staging",
+            destination.trim_end_matches('/')
+        ))
+    }
+}
+
+/// Archives commonly wrap everything in a single top-level directory; descend into it
+fn archive_root(extracted: &Path) -> DotfResult<PathBuf> {
+    if extracted.join("dotf.toml").exists() || extracted.join(".dotf/dotf.toml").exists() {
+        return Ok(extracted.to_path_buf());
+    }
+
+    let entries: Vec<PathBuf> = fs::read_dir(extracted)
+        .map_err(DotfError::Io)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+
+    match entries.as_slice() {
+        [single] if single.is_dir() => Ok(single.clone()),
+        _ => Ok(extracted.to_path_buf()),
+    }
+}
+
+fn read_config(root: &Path) -> DotfResult<DotfConfig> {
+    let config_path = root.join("dotf.toml");
+    let alt_config_path = root.join(".dotf/dotf.toml");
+
+    let config_content = if config_path.exists() {
+        fs::read_to_string(config_path).map_err(DotfError::Io)?
+    } else if alt_config_path.exists() {
+        fs::read_to_string(alt_config_path).map_err(DotfError::Io)?
+    } else {
+        return Err(DotfError::Config(
+            "dotf.toml not found in archive".to_string(),
+        ));
+    };
+
+    toml::from_str(&config_content)
+        .map_err(|e| DotfError::Config(format!("Invalid dotf.toml: {}", e)))
+}
+
+impl RepositoryBackend for TarballRepository {
+    fn name(&self) -> &'static str {
+        "tarball"
+    }
+
+    fn handles_url(&self, url: &str) -> bool {
+        if url.starts_with("tar+http://") || url.starts_with("tar+https://") {
+            return true;
+        }
+
+        (url.starts_with("http://") || url.starts_with("https://"))
+            && [".tar.gz", ".tgz", ".tar"]
+                .iter()
+                .any(|ext| url.ends_with(ext))
+    }
+
+    fn handles_checkout(&self, repo_path: &str) -> bool {
+        Path::new(repo_path).join(SOURCE_FILE).is_file()
+    }
+}
+
+#[async_trait]
+impl Repository for TarballRepository {
+    async fn validate_remote(&self, url: &str) -> DotfResult<()> {
+        self.remote_etag(url).await.map(|_| ())
+    }
+
+    async fn fetch_config(&self, url: &str) -> DotfResult<DotfConfig> {
+        let temp_dir = tempfile::tempdir().map_err(DotfError::Io)?;
+        let (root, _) = self.download(url, temp_dir.path()).await?;
+        read_config(&root)
+    }
+
+    async fn fetch_config_from_branch(&self, url: &str, _branch: &str) -> DotfResult<DotfConfig> {
+        self.fetch_config(url).await
+    }
+
+    async fn clone(&self, url: &str, destination: &str) -> DotfResult<()> {
+        let staging = Self::staging_dir(destination);
+        let result = async {
+            let (root, etag) = self.download(url, &staging).await?;
+            Self::write_source(&root, url, etag)?;
+            if let Some(parent) = Path::new(destination).parent() {
+                fs::create_dir_all(parent).map_err(DotfError::Io)?;
+            }
+            fs::rename(&root, destination).map_err(DotfError::Io)
+        }
+        .await;
+
+        let _ = fs::remove_dir_all(&staging);
+        result
+    }
+
+    async fn clone_branch(&self, url: &str, _branch: &str, destination: &str) -> DotfResult<()> {
+        self.clone(url, destination).await
+    }
+
+    async fn pull(&self, repo_path: &str) -> DotfResult<()> {
+        let source = Self::read_source(repo_path)?;
+
+        let remote_etag = self.remote_etag(&source.url).await?;
+        if remote_etag.is_some() && remote_etag == source.etag {
+            return Ok(());
+        }
+
+        // Swap the whole checkout so symlinks into it never see a half-extracted tree
+        let staging = Self::staging_dir(repo_path);
+        let previous = PathBuf::from(format!("{}.dotf-previous", repo_path.trim_end_matches('/')));
+        let result = async {
+            let (root, etag) = self.download(&source.url, &staging).await?;
+            Self::write_source(&root, &source.url, etag)?;
+            fs::rename(repo_path, &previous).map_err(DotfError::Io)?;
+            if let Err(e) = fs::rename(&root, repo_path) {
+                let _ = fs::rename(&previous, repo_path);
+                return Err(DotfError::Io(e));
+            }
+            Ok(())
+        }
+        .await;
+
+        let _ = fs::remove_dir_all(&staging);
+        let _ = fs::remove_dir_all(&previous);
+        result
+    }
+
+    async fn get_status(
+        &self,
+        repo_path: &str,
+        fetch: FetchPolicy,
+    ) -> DotfResult<RepositoryStatus> {
+        let source = Self::read_source(repo_path)?;
+
+        // Offline or ETag-less servers simply report no known update
+        let remote_etag = match fetch {
+            FetchPolicy::Always => Some(self.remote_etag(&source.url).await),
+            FetchPolicy::Auto => {
+                tokio::time::timeout(AUTO_FETCH_TIMEOUT, self.remote_etag(&source.url))
+                    .await
+                    .ok()
+            }
+            FetchPolicy::Never => None,
+        };
+        let fetched = remote_etag.as_ref().is_some_and(Result::is_ok);
+        let update_available = match remote_etag {
+            Some(Ok(Some(etag))) => source.etag.as_deref() != Some(etag.as_str()),
+            _ => false,
+        };
+
+        Ok(RepositoryStatus {
+            is_clean: true,
+            ahead_count: 0,
+            behind_count: usize::from(update_available),
+            current_branch: TARBALL_BRANCH.to_string(),
+            tracks_upstream: false,
+            fetched,
+        })
+    }
+
+    async fn get_remote_url(&self, repo_path: &str) -> DotfResult<String> {
+        Ok(Self::read_source(repo_path)?.url)
+    }
+
+    async fn is_file_modified(&self, _repo_path: &str, _file_path: &str) -> DotfResult<bool> {
+        // No history to compare against
+        Ok(false)
+    }
+
+    async fn get_modified_files(&self, _repo_path: &str) -> DotfResult<HashSet<String>> {
+        Ok(HashSet::new())
+    }
+
+    async fn get_default_branch(&self, _url: &str) -> DotfResult<String> {
+        Ok(TARBALL_BRANCH.to_string())
+    }
+
+    async fn branch_exists(&self, _url: &str, branch: &str) -> DotfResult<bool> {
+        Ok(branch == TARBALL_BRANCH)
+    }
+
+    async fn stage(&self, _repo_path: &str, _file_path: &str) -> DotfResult<()> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is nothing to stage".to_string(),
+        ))
+    }
+
+    async fn commit(
+        &self,
+        _repo_path: &str,
+        _message: &str,
+        _paths: &[String],
+        _sign: bool,
+    ) -> DotfResult<()> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is nothing to commit".to_string(),
+        ))
+    }
+
+    async fn add_all(&self, _repo_path: &str) -> DotfResult<Vec<String>> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is nothing to stage".to_string(),
+        ))
+    }
+
+    async fn push(&self, _repo_path: &str) -> DotfResult<()> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is no remote to push to".to_string(),
+        ))
+    }
+
+    async fn log(
+        &self,
+        _repo_path: &str,
+        _paths: &[String],
+        _limit: usize,
+    ) -> DotfResult<Vec<CommitEntry>> {
+        Err(DotfError::Repository(
+            "Tarball sources have no history to show".to_string(),
+        ))
+    }
+
+    async fn export_ignored(&self, _repo_path: &str, _paths: &[String]) -> DotfResult<Vec<String>> {
+        // Release tarballs are usually built with `git archive`, which already dropped these files
+        Ok(Vec::new())
+    }
+
+    async fn gc(&self, _repo_path: &str) -> DotfResult<()> {
+        // An unpacked tarball has no history to compact
+        Ok(())
+    }
+
+    async fn revision(&self, _repo_path: &str) -> DotfResult<Option<String>> {
+        Ok(None)
+    }
+
+    async fn changed_since(&self, _repo_path: &str, _revision: &str) -> DotfResult<Vec<String>> {
+        Err(DotfError::Repository(
+            "Tarball checkouts have no revisions to compare".to_string(),
+        ))
+    }
+
+    async fn renames(&self, _repo_path: &str, _from: &str, _to: &str) -> DotfResult<Vec<Rename>> {
+        // A new snapshot replaces the old one whole
+        Ok(Vec::new())
+    }
+
+    async fn clone_sparse(
+        &self,
+        url: &str,
+        branch: &str,
+        destination: &str,
+        _paths: &[String],
+    ) -> DotfResult<()> {
+        // The archive is downloaded whole either way
+        self.clone_branch(url, branch, destination).await
+    }
+
+    async fn set_sparse_paths(
+        &self,
+        _repo_path: &str,
+        _paths: Option<&[String]>,
+    ) -> DotfResult<()> {
+        Ok(())
+    }
+
+    async fn signing_configured(&self, _repo_path: &str) -> DotfResult<bool> {
+        Ok(false)
+    }
+
+    async fn test_signing(&self, _repo_path: &str) -> DotfResult<()> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is nothing to sign".to_string(),
+        ))
+    }
+
+    async fn upstream_history(&self, _repo_path: &str) -> DotfResult<UpstreamHistory> {
+        // Every download replaces the snapshot, so there is no history to diverge
+        Ok(UpstreamHistory::Linear)
+    }
+
+    async fn rescue_and_reset(&self, _repo_path: &str) -> DotfResult<String> {
+        Err(DotfError::Repository(
+            "Tarball sources have no history to rescue".to_string(),
+        ))
+    }
+
+    async fn stash(&self, _repo_path: &str) -> DotfResult<bool> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is nothing to stash".to_string(),
+        ))
+    }
+
+    async fn stash_pop(&self, _repo_path: &str) -> DotfResult<Vec<String>> {
+        Err(DotfError::Repository(
+            "Tarball sources are read-only; there is nothing to stash".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_handles_url() {
+        let repo = TarballRepository::new();
+        assert!(repo.handles_url("tar+https://example.com/dotfiles"));
+        assert!(repo.handles_url("https://example.com/dotfiles.tar.gz"));
+        assert!(!repo.handles_url("https://github.com/user/dotfiles"));
+        assert!(!repo.handles_url("git@github.com:user/dotfiles.tar.gz"));
+    }
+
+    #[test]
+    fn test_archive_root_descends_into_single_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let wrapped = temp_dir.path().join("dotfiles-main");
+        fs::create_dir_all(&wrapped).unwrap();
+        fs::write(wrapped.join("dotf.toml"), "[symlinks]\n").unwrap();
+
+        assert_eq!(archive_root(temp_dir.path()).unwrap(), wrapped);
+        assert_eq!(archive_root(&wrapped).unwrap(), wrapped);
+    }
+}
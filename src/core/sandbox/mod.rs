@@ -0,0 +1,266 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use tempfile::TempDir;
+
+use crate::core::config::Settings;
+use crate::error::{DotfError, DotfResult};
+
+/// Set for commands run inside a sandbox; steps that change the system outside `$HOME`
+/// (crontab, macOS defaults) check it and skip themselves
+pub const SANDBOX_ENV: &str = "DOTF_SANDBOX";
+
+/// Container engines tried, in order, for `dotf sandbox --container`
+pub const CONTAINER_ENGINES: &[&str] = &["podman", "docker"];
+
+/// What an install left in the sandbox home, relative to it
+#[derive(Debug, Clone, PartialEq)]
+pub enum SandboxChange {
+    Symlink { path: String, target: String },
+    File { path: String },
+    Directory { path: String },
+}
+
+impl SandboxChange {
+    pub fn path(&self) -> &str {
+        match self {
+            SandboxChange::Symlink { path, .. }
+            | SandboxChange::File { path }
+            | SandboxChange::Directory { path } => path,
+        }
+    }
+}
+
+/// A throwaway home directory under the system temp dir holding a copy of the repository
+/// and dotf settings that point at it. Removed when dropped unless kept.
+pub struct Sandbox {
+    root: TempDir,
+}
+
+impl Sandbox {
+    /// Copy `repo_path` into a fresh home and write `settings` with the copy as local repository
+    pub fn prepare(repo_path: &Path, settings: &Settings) -> DotfResult<Self> {
+        let root = tempfile::Builder::new().prefix("dotf-sandbox-").tempdir()?;
+        let sandbox = Self { root };
+
+        let repo_copy = sandbox.repo_path();
+        copy_tree(repo_path, &repo_copy)?;
+
+        let mut settings = settings.clone();
+        settings.repository.local = Some(repo_copy.to_string_lossy().to_string());
+        std::fs::write(
+            sandbox.dotf_dir().join("settings.toml"),
+            settings.to_toml()?,
+        )?;
+
+        Ok(sandbox)
+    }
+
+    pub fn home(&self) -> &Path {
+        self.root.path()
+    }
+
+    pub fn dotf_dir(&self) -> PathBuf {
+        self.home().join(".dotf")
+    }
+
+    pub fn repo_path(&self) -> PathBuf {
+        self.dotf_dir().join("repo")
+    }
+
+    /// Run `program` with the sandbox as `$HOME`; output goes straight to the terminal
+    pub fn run(&self, program: &Path, args: &[&str]) -> DotfResult<bool> {
+        let status = Command::new(program)
+            .args(args)
+            .env("HOME", self.home())
+            .env(SANDBOX_ENV, "1")
+            .env("XDG_CONFIG_HOME", self.home().join(".config"))
+            .env("XDG_DATA_HOME", self.home().join(".local/share"))
+            .env("XDG_CACHE_HOME", self.home().join(".cache"))
+            .status()
+            .map_err(|e| {
+                DotfError::Operation(format!("Failed to run {}: {}", program.display(), e))
+            })?;
+        Ok(status.success())
+    }
+
+    /// Everything in the sandbox home except dotf's own directory, sorted by path.
+    /// Linked directories are listed once, not walked.
+    pub fn changes(&self) -> DotfResult<Vec<SandboxChange>> {
+        let mut changes = Vec::new();
+        let mut pending = vec![self.home().to_path_buf()];
+
+        while let Some(dir) = pending.pop() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                let path = entry.path();
+                if path == self.dotf_dir() {
+                    continue;
+                }
+                let relative = path
+                    .strip_prefix(self.home())
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .to_string();
+
+                let file_type = entry.file_type()?;
+                if file_type.is_symlink() {
+                    let target = std::fs::read_link(&path)?;
+                    changes.push(SandboxChange::Symlink {
+                        path: relative,
+                        target: target.to_string_lossy().to_string(),
+                    });
+                } else if file_type.is_dir() {
+                    changes.push(SandboxChange::Directory { path: relative });
+                    pending.push(path);
+                } else {
+                    changes.push(SandboxChange::File { path: relative });
+                }
+            }
+        }
+
+        changes.sort_by(|a, b| a.path().cmp(b.path()));
+        Ok(changes)
+    }
+
+    /// Leave the sandbox on disk for inspection and return its location
+    pub fn keep(self) -> PathBuf {
+        self.root.keep()
+    }
+}
+
+/// The first container engine that answers `--version`
+pub fn detect_container_engine() -> Option<&'static str> {
+    CONTAINER_ENGINES.iter().copied().find(|engine| {
+        Command::new(engine)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// Arguments for `<engine> run` that install the mounted repository copy inside `image`.
+/// The repository is mounted read-only and copied, so the install cannot touch the host.
+pub fn container_args(
+    image: &str,
+    repo_path: &Path,
+    dotf_binary: &Path,
+    steps: &[&str],
+    tty: bool,
+) -> Vec<String> {
+    let install = steps
+        .iter()
+        .map(|step| format!("dotf install {}", step))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    let script = format!(
+        "mkdir -p ~/.dotf && cp -a /dotfiles ~/.dotf/repo && \
+         printf 'initialized_at = \"%s\"\\n\\n[repository]\\nremote = \"/dotfiles\"\\n' \"$(date -u +%Y-%m-%dT%H:%M:%SZ)\" > ~/.dotf/settings.toml && \
+         {}",
+        install
+    );
+
+    let mut args = vec!["run".to_string(), "--rm".to_string(), "-i".to_string()];
+    if tty {
+        // Prompts during the install need a terminal
+        args.push("-t".to_string());
+    }
+    args.extend([
+        "-v".to_string(),
+        format!("{}:/dotfiles:ro", repo_path.display()),
+        "-v".to_string(),
+        format!("{}:/usr/local/bin/dotf:ro", dotf_binary.display()),
+        image.to_string(),
+        "sh".to_string(),
+        "-c".to_string(),
+        script,
+    ]);
+    args
+}
+
+/// Copy a directory tree, recreating symlinks instead of following them
+fn copy_tree(source: &Path, destination: &Path) -> DotfResult<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let from = entry.path();
+        let to = destination.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_symlink() {
+            #[cfg(unix)]
+            std::os::unix::fs::symlink(std::fs::read_link(&from)?, &to)?;
+        } else if file_type.is_dir() {
+            copy_tree(&from, &to)?;
+        } else {
+            std::fs::copy(&from, &to)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prepare_and_collect_changes() {
+        let repo = TempDir::new().unwrap();
+        std::fs::create_dir_all(repo.path().join("zsh")).unwrap();
+        std::fs::write(repo.path().join("zsh/.zshrc"), "export A=1\n").unwrap();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+
+        let sandbox = Sandbox::prepare(repo.path(), &settings).unwrap();
+        assert!(sandbox.repo_path().join("zsh/.zshrc").exists());
+        let written = std::fs::read_to_string(sandbox.dotf_dir().join("settings.toml")).unwrap();
+        let written = Settings::from_toml(&written).unwrap();
+        assert_eq!(
+            written.repository.local,
+            Some(sandbox.repo_path().to_string_lossy().to_string())
+        );
+
+        // Stand in for dotf: link a file into the sandbox home
+        let linked = sandbox.run(
+            Path::new("sh"),
+            &["-c", "mkdir -p \"$HOME/.config\" && ln -s \"$HOME/.dotf/repo/zsh/.zshrc\" \"$HOME/.zshrc\""],
+        );
+        assert!(linked.unwrap());
+
+        let changes = sandbox.changes().unwrap();
+        assert_eq!(
+            changes,
+            vec![
+                SandboxChange::Directory {
+                    path: ".config".to_string()
+                },
+                SandboxChange::Symlink {
+                    path: ".zshrc".to_string(),
+                    target: sandbox
+                        .repo_path()
+                        .join("zsh/.zshrc")
+                        .to_string_lossy()
+                        .to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_container_args() {
+        let args = container_args(
+            "debian:stable-slim",
+            Path::new("/home/user/.dotf/repo"),
+            Path::new("/usr/local/bin/dotf"),
+            &["deps", "config"],
+            false,
+        );
+
+        assert!(args.contains(&"/home/user/.dotf/repo:/dotfiles:ro".to_string()));
+        assert!(args
+            .last()
+            .unwrap()
+            .ends_with("dotf install deps && dotf install config"));
+    }
+}
@@ -1,8 +1,12 @@
 use async_trait::async_trait;
+use std::collections::BTreeMap;
 use std::process::Stdio;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tokio::process::Command;
 
+use crate::core::scripts::limits::{timeout_marker, truncation_marker, ScriptLimits};
 use crate::error::{DotfError, DotfResult};
 use crate::traits::script_executor::{ExecutionResult, ScriptExecutor};
 
@@ -71,39 +75,51 @@ impl SystemScriptExecutor {
         &self,
         script_path: &str,
         args: &[String],
+        env: &BTreeMap<String, String>,
+        limits: &ScriptLimits,
+        working_dir: Option<&str>,
     ) -> DotfResult<ExecutionResult> {
         let script_extension = std::path::Path::new(script_path)
             .extension()
             .and_then(|ext| ext.to_str())
             .unwrap_or("");
 
-        let mut command = if script_extension == "sh"
+        let mut argv: Vec<String> = if script_extension == "sh"
             || script_extension == "bash"
             || script_path.starts_with("#!")
         {
             // Execute shell scripts through shell
             let (shell, shell_arg) = self.get_shell_command();
-            let mut cmd = Command::new(shell);
-
-            if args.is_empty() {
-                cmd.arg(shell_arg).arg(script_path);
-            } else {
-                let command_line = format!("{} {}", script_path, args.join(" "));
-                cmd.arg(shell_arg).arg(command_line);
-            }
-            cmd
+            shell_argv(shell, shell_arg, script_path, args)
         } else {
             // Execute directly
-            let mut cmd = Command::new(script_path);
-            cmd.args(args);
-            cmd
+            std::iter::once(script_path.to_string())
+                .chain(args.iter().cloned())
+                .collect()
         };
 
+        #[cfg(unix)]
+        if let Some(nice) = limits.nice {
+            argv.splice(
+                0..0,
+                ["nice".to_string(), "-n".to_string(), nice.to_string()],
+            );
+        }
+
+        tracing::info!(script = script_path, ?argv, ?limits, "running script");
+        let mut command = Command::new(&argv[0]);
+        command.args(&argv[1..]);
+        command.envs(env);
+        if let Some(working_dir) = working_dir {
+            command.current_dir(working_dir);
+        }
+
         // Capture both stdout and stderr
         command
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .stdin(Stdio::null());
+            .stdin(Stdio::null())
+            .kill_on_drop(true);
 
         let mut child = command
             .spawn()
@@ -120,51 +136,152 @@ impl SystemScriptExecutor {
             .ok_or_else(|| DotfError::ScriptExecution("Failed to capture stderr".to_string()))?;
 
         // Read output in parallel
-        let stdout_handle = tokio::spawn(async move {
-            let mut lines = Vec::new();
-            let mut reader = BufReader::new(stdout).lines();
-            while let Ok(Some(line)) = reader.next_line().await {
-                println!("  | {}", line);
-                lines.push(line);
-            }
-            lines.join("\n")
-        });
-
-        let stderr_handle = tokio::spawn(async move {
-            let mut lines = Vec::new();
-            let mut reader = BufReader::new(stderr).lines();
-            while let Ok(Some(line)) = reader.next_line().await {
-                eprintln!("  ! {}", line);
-                lines.push(line);
+        let stdout_output = Arc::new(Mutex::new(CapturedOutput::default()));
+        let stderr_output = Arc::new(Mutex::new(CapturedOutput::default()));
+        let stdout_handle = tokio::spawn(capture(
+            stdout,
+            stdout_output.clone(),
+            limits.max_output_bytes,
+            |line| println!("  | {}", line),
+        ));
+        let stderr_handle = tokio::spawn(capture(
+            stderr,
+            stderr_output.clone(),
+            limits.max_output_bytes,
+            |line| eprintln!("  ! {}", line),
+        ));
+
+        // Wait for process to complete, stopping it once it runs out of time
+        let wait = child.wait();
+        let exit_status = match limits.max_runtime_secs {
+            Some(secs) => tokio::time::timeout(Duration::from_secs(secs), wait)
+                .await
+                .ok(),
+            None => Some(wait.await),
+        }
+        .transpose()
+        .map_err(|e| DotfError::ScriptExecution(format!("Failed to wait for process: {}", e)))?;
+
+        let timed_out = exit_status.is_none();
+        if timed_out {
+            child.kill().await.map_err(|e| {
+                DotfError::ScriptExecution(format!("Failed to stop process: {}", e))
+            })?;
+            // Background processes the script started may still hold the pipes open
+            for handle in [stdout_handle, stderr_handle] {
+                let _ = tokio::time::timeout(Duration::from_secs(1), handle).await;
             }
-            lines.join("\n")
-        });
-
-        // Wait for process to complete
-        let exit_status = child.wait().await.map_err(|e| {
-            DotfError::ScriptExecution(format!("Failed to wait for process: {}", e))
-        })?;
+        } else {
+            stdout_handle
+                .await
+                .map_err(|e| DotfError::ScriptExecution(format!("Failed to read stdout: {}", e)))?;
+            stderr_handle
+                .await
+                .map_err(|e| DotfError::ScriptExecution(format!("Failed to read stderr: {}", e)))?;
+        }
 
         // Collect output
-        let stdout_output = stdout_handle
-            .await
-            .map_err(|e| DotfError::ScriptExecution(format!("Failed to read stdout: {}", e)))?;
-        let stderr_output = stderr_handle
-            .await
-            .map_err(|e| DotfError::ScriptExecution(format!("Failed to read stderr: {}", e)))?;
+        let stdout_output = stdout_output.lock().unwrap().finish();
+        let mut stderr_output = stderr_output.lock().unwrap().finish();
+
+        match exit_status {
+            Some(exit_status) if !exit_status.success() => {
+                tracing::warn!(script = script_path, code = ?exit_status.code(), stderr = %stderr_output, "script failed");
+                Ok(ExecutionResult {
+                    success: false,
+                    exit_code: exit_status.code().unwrap_or(-1),
+                    stdout: stdout_output,
+                    stderr: stderr_output,
+                })
+            }
+            Some(exit_status) => Ok(ExecutionResult {
+                success: exit_status.success(),
+                exit_code: exit_status.code().unwrap_or(-1),
+                stdout: stdout_output,
+                stderr: stderr_output,
+            }),
+            None => {
+                let marker = timeout_marker(limits.max_runtime_secs.unwrap_or_default());
+                tracing::warn!(script = script_path, ?limits, "script timed out");
+                eprintln!("  ! {}", marker);
+                if !stderr_output.is_empty() {
+                    stderr_output.push('\n');
+                }
+                stderr_output.push_str(&marker);
+                Ok(ExecutionResult {
+                    success: false,
+                    exit_code: -1,
+                    stdout: stdout_output,
+                    stderr: stderr_output,
+                })
+            }
+        }
+    }
+}
 
-        let exit_code = exit_status.code().unwrap_or(-1);
-        let success = exit_status.success();
+/// Lines read from one output stream, up to the output limit
+#[derive(Default)]
+struct CapturedOutput {
+    lines: Vec<String>,
+    bytes: usize,
+    omitted_bytes: usize,
+}
 
-        Ok(ExecutionResult {
-            success,
-            exit_code,
-            stdout: stdout_output,
-            stderr: stderr_output,
-        })
+impl CapturedOutput {
+    fn finish(&mut self) -> String {
+        let mut text = std::mem::take(&mut self.lines).join("\n");
+        if self.omitted_bytes > 0 {
+            text.push('\n');
+            text.push_str(&truncation_marker(self.omitted_bytes));
+        }
+        text
     }
 }
 
+/// Echo and keep lines from `stream` until `max_bytes` is reached, then only count what is dropped
+async fn capture<R: AsyncRead + Unpin>(
+    stream: R,
+    output: Arc<Mutex<CapturedOutput>>,
+    max_bytes: Option<usize>,
+    echo: fn(&str),
+) {
+    let mut reader = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = reader.next_line().await {
+        let mut output = output.lock().unwrap();
+        let within_limit = max_bytes.is_none_or(|max| output.bytes + line.len() <= max);
+        if within_limit && output.omitted_bytes == 0 {
+            echo(&line);
+            output.bytes += line.len() + 1;
+            output.lines.push(line);
+        } else {
+            if output.omitted_bytes == 0 {
+                echo("[dotf: output limit reached, further output is not shown]");
+            }
+            output.omitted_bytes += line.len() + 1;
+        }
+    }
+}
+
+/// Command line running a shell script through `shell`. The script and its arguments
+/// reach it as positional parameters, so the shell neither splits nor expands them.
+#[cfg(unix)]
+fn shell_argv(shell: &str, shell_arg: &str, script_path: &str, args: &[String]) -> Vec<String> {
+    [shell, shell_arg, "\"$0\" \"$@\"", script_path]
+        .into_iter()
+        .map(str::to_string)
+        .chain(args.iter().cloned())
+        .collect()
+}
+
+#[cfg(windows)]
+fn shell_argv(shell: &str, shell_arg: &str, script_path: &str, args: &[String]) -> Vec<String> {
+    let command_line = std::iter::once(script_path.to_string())
+        .chain(args.iter().cloned())
+        .collect::<Vec<_>>()
+        .join(" ");
+    vec![shell.to_string(), shell_arg.to_string(), command_line]
+}
+
 #[async_trait]
 impl ScriptExecutor for SystemScriptExecutor {
     async fn execute(&self, script_path: &str) -> DotfResult<ExecutionResult> {
@@ -175,6 +292,28 @@ impl ScriptExecutor for SystemScriptExecutor {
         &self,
         script_path: &str,
         args: &[String],
+    ) -> DotfResult<ExecutionResult> {
+        self.execute_with_env(script_path, args, &BTreeMap::new())
+            .await
+    }
+
+    async fn execute_with_env(
+        &self,
+        script_path: &str,
+        args: &[String],
+        env: &BTreeMap<String, String>,
+    ) -> DotfResult<ExecutionResult> {
+        self.execute_limited(script_path, args, env, &ScriptLimits::default(), None)
+            .await
+    }
+
+    async fn execute_limited(
+        &self,
+        script_path: &str,
+        args: &[String],
+        env: &BTreeMap<String, String>,
+        limits: &ScriptLimits,
+        working_dir: Option<&str>,
     ) -> DotfResult<ExecutionResult> {
         // Check if script exists
         if tokio::fs::metadata(script_path).await.is_err() {
@@ -187,8 +326,21 @@ impl ScriptExecutor for SystemScriptExecutor {
         // Ensure script has execute permissions
         self.check_and_set_permissions(script_path).await?;
 
+        if let Some(working_dir) = working_dir {
+            if !tokio::fs::metadata(working_dir)
+                .await
+                .is_ok_and(|metadata| metadata.is_dir())
+            {
+                return Err(DotfError::ScriptExecution(format!(
+                    "Working directory not found: {}",
+                    working_dir
+                )));
+            }
+        }
+
         // Execute the script
-        self.execute_command(script_path, args).await
+        self.execute_command(script_path, args, env, limits, working_dir)
+            .await
     }
 
     async fn has_permission(&self, script_path: &str) -> DotfResult<bool> {
@@ -339,6 +491,49 @@ echo "All args: $@"
         assert!(result.stdout.contains("All args: first second"));
     }
 
+    #[tokio::test]
+    async fn test_system_script_executor_keeps_args_whole_in_working_dir() {
+        let executor = SystemScriptExecutor::new();
+
+        let script_content = r#"#!/bin/bash
+echo "Count: $#"
+echo "Arg 1: $1"
+echo "Dir: $(pwd)"
+"#;
+
+        let (temp_dir, script_path) = create_test_script(script_content, "sh").await;
+        let working_dir = temp_dir.path().canonicalize().unwrap();
+        let working_dir = working_dir.to_string_lossy();
+
+        let args = vec!["two words; $HOME".to_string(), "--flag".to_string()];
+        let result = executor
+            .execute_limited(
+                &script_path,
+                &args,
+                &BTreeMap::new(),
+                &ScriptLimits::default(),
+                Some(&working_dir),
+            )
+            .await
+            .unwrap();
+
+        assert!(result.success);
+        assert!(result.stdout.contains("Count: 2"));
+        assert!(result.stdout.contains("Arg 1: two words; $HOME"));
+        assert!(result.stdout.contains(&format!("Dir: {}", working_dir)));
+
+        let missing = executor
+            .execute_limited(
+                &script_path,
+                &[],
+                &BTreeMap::new(),
+                &ScriptLimits::default(),
+                Some(&format!("{}/missing", working_dir)),
+            )
+            .await;
+        assert!(missing.is_err());
+    }
+
     #[tokio::test]
     async fn test_system_script_executor_stderr() {
         let executor = SystemScriptExecutor::new();
@@ -414,4 +609,37 @@ echo "permission test"
         assert!(result.success);
         assert!(result.stdout.contains("permission test"));
     }
+
+    #[tokio::test]
+    #[cfg(unix)]
+    async fn test_system_script_executor_limits() {
+        let executor = SystemScriptExecutor::new();
+
+        let script_content = r#"#!/bin/bash
+echo "nice: $(nice)"
+for i in 1 2 3 4 5; do echo "line $i"; done
+sleep 5
+echo "never printed"
+"#;
+
+        let (_temp_dir, script_path) = create_test_script(script_content, "sh").await;
+
+        let limits = ScriptLimits {
+            nice: Some(5),
+            max_runtime_secs: Some(1),
+            max_output_bytes: Some(24),
+        };
+        let result = executor
+            .execute_limited(&script_path, &[], &BTreeMap::new(), &limits, None)
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.stdout.starts_with("nice: 5\nline 1\nline 2"));
+        assert!(result
+            .stdout
+            .ends_with("[dotf: output truncated, 21 bytes omitted]"));
+        assert!(!result.stdout.contains("never printed"));
+        assert!(result.stderr.contains("max runtime of 1s"));
+    }
 }
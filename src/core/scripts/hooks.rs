@@ -0,0 +1,40 @@
+use serde::{Deserialize, Serialize};
+
+/// Points in `dotf install` and `dotf sync` where a `[hooks]` script can run
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    PreInstall,
+    PostInstall,
+    PreSync,
+    PostSync,
+}
+
+impl HookEvent {
+    pub const ALL: [HookEvent; 4] = [
+        HookEvent::PreInstall,
+        HookEvent::PostInstall,
+        HookEvent::PreSync,
+        HookEvent::PostSync,
+    ];
+
+    /// Key in `[hooks]`, also passed to the script as `DOTF_HOOK`
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::PreInstall => "pre_install",
+            HookEvent::PostInstall => "post_install",
+            HookEvent::PreSync => "pre_sync",
+            HookEvent::PostSync => "post_sync",
+        }
+    }
+}
+
+/// What a failing hook does to the command it belongs to
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HookFailure {
+    /// Stop the command; a failing pre hook keeps it from running at all
+    #[default]
+    Abort,
+    /// Warn and carry on
+    Continue,
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// Resource limits applied to a script run. Every limit is optional; none are set by default.
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScriptLimits {
+    /// Scheduling priority passed to `nice -n` (higher is lower priority)
+    pub nice: Option<i32>,
+    /// Stop the script after this many seconds
+    pub max_runtime_secs: Option<u64>,
+    /// Keep and print at most this many bytes of stdout and of stderr each
+    pub max_output_bytes: Option<usize>,
+}
+
+impl ScriptLimits {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// These limits with any limit set in `overrides` taking its place
+    pub fn merged_with(&self, overrides: &ScriptLimits) -> ScriptLimits {
+        ScriptLimits {
+            nice: overrides.nice.or(self.nice),
+            max_runtime_secs: overrides.max_runtime_secs.or(self.max_runtime_secs),
+            max_output_bytes: overrides.max_output_bytes.or(self.max_output_bytes),
+        }
+    }
+}
+
+/// Marker appended to captured output when `max_output_bytes` was reached
+pub fn truncation_marker(omitted_bytes: usize) -> String {
+    format!("[dotf: output truncated, {} bytes omitted]", omitted_bytes)
+}
+
+/// Marker appended to stderr when `max_runtime_secs` was reached
+pub fn timeout_marker(max_runtime_secs: u64) -> String {
+    format!(
+        "[dotf: script stopped after exceeding max runtime of {}s]",
+        max_runtime_secs
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merged_with_prefers_overrides() {
+        let global: ScriptLimits = toml::from_str("nice = 10\nmax_output_bytes = 1024").unwrap();
+        let script: ScriptLimits = toml::from_str("max_runtime_secs = 600\nnice = 5").unwrap();
+
+        let merged = global.merged_with(&script);
+        assert_eq!(merged.nice, Some(5));
+        assert_eq!(merged.max_runtime_secs, Some(600));
+        assert_eq!(merged.max_output_bytes, Some(1024));
+        assert!(ScriptLimits::default().is_empty());
+        assert!(!merged.is_empty());
+    }
+}
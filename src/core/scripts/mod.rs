@@ -1,3 +1,9 @@
 pub mod executor;
+pub mod hooks;
+pub mod limits;
+pub mod run_policy;
 
 pub use executor::SystemScriptExecutor;
+pub use hooks::{HookEvent, HookFailure};
+pub use limits::ScriptLimits;
+pub use run_policy::RunPolicy;
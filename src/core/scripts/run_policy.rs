@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// When a script runs again after its first successful run (`[scripts.run]`)
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RunPolicy {
+    /// Every time it is installed
+    #[default]
+    Always,
+    /// Only until it has succeeded once on this machine
+    Once,
+    /// Whenever its content differs from the last successful run
+    OnChange,
+}
+
+impl RunPolicy {
+    /// Whether a script whose content hashes to `sha256` should run, given the hash of
+    /// its last successful run here
+    pub fn should_run(&self, last_sha256: Option<&str>, sha256: &str) -> bool {
+        match self {
+            RunPolicy::Always => true,
+            RunPolicy::Once => last_sha256.is_none(),
+            RunPolicy::OnChange => last_sha256 != Some(sha256),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_run() {
+        assert!(RunPolicy::Always.should_run(Some("a"), "a"));
+        assert!(RunPolicy::Once.should_run(None, "a"));
+        assert!(!RunPolicy::Once.should_run(Some("a"), "b"));
+        assert!(RunPolicy::OnChange.should_run(Some("a"), "b"));
+        assert!(!RunPolicy::OnChange.should_run(Some("a"), "a"));
+
+        let policy: RunPolicy = toml::from_str::<toml::Value>("run = \"on-change\"").unwrap()
+            ["run"]
+            .clone()
+            .try_into()
+            .unwrap();
+        assert_eq!(policy, RunPolicy::OnChange);
+    }
+}
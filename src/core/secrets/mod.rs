@@ -0,0 +1,207 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::core::state::InstalledSecret;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::cipher::Cipher;
+
+/// Mode of decrypted files
+pub const FILE_MODE: u32 = 0o600;
+/// Key file age decrypts with when `[secrets] identity` is not set
+pub const DEFAULT_AGE_IDENTITY: &str = "~/.config/age/keys.txt";
+
+/// Tool that encrypts `[secrets]` files
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretBackend {
+    #[default]
+    Age,
+    Gpg,
+}
+
+impl SecretBackend {
+    pub fn command(&self) -> &'static str {
+        match self {
+            SecretBackend::Age => "age",
+            SecretBackend::Gpg => "gpg",
+        }
+    }
+
+    /// Extension of encrypted files added by `dotf secrets encrypt`
+    pub fn extension(&self) -> &'static str {
+        match self {
+            SecretBackend::Age => "age",
+            SecretBackend::Gpg => "asc",
+        }
+    }
+}
+
+/// How a decrypted target compares with the repository and the last decryption
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretState {
+    InSync,
+    /// Not decrypted on this machine yet
+    Missing,
+    /// The encrypted file changed since it was last decrypted
+    Outdated,
+    /// The target was edited after dotf decrypted it
+    Modified,
+    /// The target exists but dotf never wrote it
+    Untracked,
+    /// The encrypted file is not in the repository
+    SourceMissing,
+}
+
+impl SecretState {
+    /// Whether decrypting would replace content that only exists on this machine
+    pub fn has_local_changes(&self) -> bool {
+        matches!(self, SecretState::Modified | SecretState::Untracked)
+    }
+}
+
+/// Compare content hashes of the encrypted source and the target with the last decryption
+pub fn secret_state(
+    record: Option<&InstalledSecret>,
+    source_sha256: Option<&str>,
+    target_sha256: Option<&str>,
+) -> SecretState {
+    let Some(source_sha256) = source_sha256 else {
+        return SecretState::SourceMissing;
+    };
+    let Some(target_sha256) = target_sha256 else {
+        return SecretState::Missing;
+    };
+    match record {
+        None => SecretState::Untracked,
+        Some(record) if record.target_sha256 != target_sha256 => SecretState::Modified,
+        Some(record) if record.source_sha256 != source_sha256 => SecretState::Outdated,
+        Some(_) => SecretState::InSync,
+    }
+}
+
+/// `Cipher` backed by the `age` and `gpg` commands
+pub struct SystemCipher;
+
+impl SystemCipher {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SystemCipher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl Cipher for SystemCipher {
+    async fn encrypt(
+        &self,
+        backend: SecretBackend,
+        recipients: &[String],
+        plaintext: &str,
+    ) -> DotfResult<String> {
+        let (flag, mut args) = match backend {
+            SecretBackend::Age => ("-r", vec!["--encrypt", "--armor"]),
+            SecretBackend::Gpg => ("--recipient", vec!["--batch", "--armor", "--encrypt"]),
+        };
+        for recipient in recipients {
+            args.extend([flag, recipient.as_str()]);
+        }
+        run_with_input(backend.command(), &args, plaintext).await
+    }
+
+    async fn decrypt(
+        &self,
+        backend: SecretBackend,
+        identity: Option<&str>,
+        ciphertext: &str,
+    ) -> DotfResult<String> {
+        let mut args = match backend {
+            SecretBackend::Age => vec!["--decrypt"],
+            SecretBackend::Gpg => vec!["--quiet", "--decrypt"],
+        };
+        if let (SecretBackend::Age, Some(identity)) = (backend, identity) {
+            args.extend(["--identity", identity]);
+        }
+        run_with_input(backend.command(), &args, ciphertext).await
+    }
+}
+
+/// Feed `input` to `program` and return what it prints
+async fn run_with_input(program: &str, args: &[&str], input: &str) -> DotfResult<String> {
+    tracing::debug!(program, ?args, "running cipher");
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| DotfError::Operation(format!("Failed to run {}: {}", program, e)))?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .await
+            .map_err(DotfError::Io)?;
+    }
+
+    let output = child.wait_with_output().await.map_err(DotfError::Io)?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        tracing::warn!(program, ?args, stderr = %stderr.trim(), "cipher failed");
+        return Err(DotfError::Operation(format!(
+            "{} failed: {}",
+            program,
+            stderr.trim()
+        )));
+    }
+    String::from_utf8(output.stdout)
+        .map_err(|_| DotfError::Operation(format!("{} output is not UTF-8 text", program)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_secret_state() {
+        let record = InstalledSecret {
+            source_sha256: "source".to_string(),
+            target_sha256: "target".to_string(),
+            decrypted_at: Utc::now(),
+        };
+
+        assert_eq!(
+            secret_state(Some(&record), None, Some("target")),
+            SecretState::SourceMissing
+        );
+        assert_eq!(
+            secret_state(Some(&record), Some("source"), None),
+            SecretState::Missing
+        );
+        assert_eq!(
+            secret_state(None, Some("source"), Some("target")),
+            SecretState::Untracked
+        );
+        assert_eq!(
+            secret_state(Some(&record), Some("source"), Some("target")),
+            SecretState::InSync
+        );
+        assert_eq!(
+            secret_state(Some(&record), Some("changed"), Some("target")),
+            SecretState::Outdated
+        );
+        // Local edits win over a changed source, so they are not overwritten
+        assert_eq!(
+            secret_state(Some(&record), Some("changed"), Some("edited")),
+            SecretState::Modified
+        );
+    }
+}
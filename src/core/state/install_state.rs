@@ -0,0 +1,405 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::core::clock::SystemClock;
+use crate::core::symlinks::{LinkMode, Permissions, SymlinkOperation};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::clock::Clock;
+use crate::traits::filesystem::FileSystem;
+
+/// What dotf last installed at each target, keyed by absolute target path
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallState {
+    #[serde(default)]
+    pub entries: BTreeMap<String, InstalledEntry>,
+    /// Release binaries installed from `[tools]`, keyed by tool name
+    #[serde(default)]
+    pub tools: BTreeMap<String, InstalledTool>,
+    /// Crontab entries last written from `[cron]`
+    #[serde(default)]
+    pub cron: Option<InstalledCron>,
+    /// Links from the last `dotf install config`, compared by `--changed-only`
+    #[serde(default)]
+    pub config: Option<InstalledConfig>,
+    /// Last successful run of each script, keyed by its path in the repository
+    #[serde(default)]
+    pub scripts: BTreeMap<String, ScriptRun>,
+    /// Last decryption of each `[secrets]` file, keyed by absolute target path
+    #[serde(default)]
+    pub secrets: BTreeMap<String, InstalledSecret>,
+    /// Files composed from repository sources, keyed by absolute output path
+    #[serde(default)]
+    pub renders: BTreeMap<String, RenderedFile>,
+    /// Downloads put off on a metered connection, keyed by the `dotf install` target
+    /// that makes them (`tools`, `packages`)
+    #[serde(default)]
+    pub deferred: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledEntry {
+    pub source_path: String,
+    pub installed_at: DateTime<Utc>,
+    /// Source content at install time, used as the merge base; `None` for non-text files
+    #[serde(default)]
+    pub base_content: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledTool {
+    /// Release tag that was installed
+    pub version: String,
+    pub asset: String,
+    /// Absolute path of the installed executable
+    pub path: String,
+    pub sha256: String,
+    /// Whether the download was checked against a published or pinned checksum
+    pub verified: bool,
+    pub installed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledCron {
+    pub entries: BTreeMap<String, String>,
+    pub installed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledConfig {
+    /// Repository revision the links were installed from; `None` for backends without history
+    pub revision: Option<String>,
+    /// How each target was deployed, keyed by target path
+    pub links: BTreeMap<String, InstalledLink>,
+    pub installed_at: DateTime<Utc>,
+}
+
+/// The source, mode and permissions a target was deployed with. Older state files
+/// recorded only the source path, which reads back as a plain symlink.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "LinkRecord")]
+pub struct InstalledLink {
+    pub source_path: String,
+    pub mode: LinkMode,
+    pub permissions: Permissions,
+}
+
+impl From<&SymlinkOperation> for InstalledLink {
+    fn from(operation: &SymlinkOperation) -> Self {
+        Self {
+            source_path: operation.source_path.clone(),
+            mode: operation.mode,
+            permissions: operation.permissions.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum LinkRecord {
+    Source(String),
+    Link {
+        source_path: String,
+        #[serde(default)]
+        mode: LinkMode,
+        #[serde(default)]
+        permissions: Permissions,
+    },
+}
+
+impl From<LinkRecord> for InstalledLink {
+    fn from(record: LinkRecord) -> Self {
+        match record {
+            LinkRecord::Source(source_path) => Self {
+                source_path,
+                mode: LinkMode::default(),
+                permissions: Permissions::default(),
+            },
+            LinkRecord::Link {
+                source_path,
+                mode,
+                permissions,
+            } => Self {
+                source_path,
+                mode,
+                permissions,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptRun {
+    /// Content hash of the script that ran, compared by `run = "on-change"`
+    pub sha256: String,
+    pub ran_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledSecret {
+    /// Content hash of the encrypted file that was decrypted
+    pub source_sha256: String,
+    /// Content hash of the plaintext written to the target
+    pub target_sha256: String,
+    pub decrypted_at: DateTime<Utc>,
+}
+
+/// What renders a composed file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderKind {
+    /// The managed block of `~/.ssh/config`, from `[ssh] config` fragments
+    Ssh,
+    /// The shell fragment generated from `[env]`
+    Env,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedFile {
+    pub kind: RenderKind,
+    /// Content hash of each source at render time, keyed by its name in the repository
+    pub sources: BTreeMap<String, String>,
+    pub rendered_at: DateTime<Utc>,
+}
+
+/// Reads and writes the install state file (`~/.dotf/state.json`)
+pub struct StateStore<F> {
+    filesystem: F,
+    clock: Arc<dyn Clock>,
+}
+
+impl<F: FileSystem> StateStore<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self::with_clock(filesystem, Arc::new(SystemClock::new()))
+    }
+
+    pub fn with_clock(filesystem: F, clock: Arc<dyn Clock>) -> Self {
+        Self { filesystem, clock }
+    }
+
+    pub async fn load(&self) -> DotfResult<InstallState> {
+        let state_path = self.filesystem.dotf_state_path();
+
+        if !self.filesystem.exists(&state_path).await? {
+            return Ok(InstallState::default());
+        }
+
+        let content = self.filesystem.read_to_string(&state_path).await?;
+        serde_json::from_str(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse install state: {}", e)))
+    }
+
+    pub async fn save(&self, state: &InstallState) -> DotfResult<()> {
+        self.filesystem.create_dotf_directory().await?;
+        let content = serde_json::to_string_pretty(state)?;
+        self.filesystem
+            .write(&self.filesystem.dotf_state_path(), &content)
+            .await
+    }
+
+    /// Remember the current source content of each `(source, target)` pair as installed
+    pub async fn record_installed(&self, links: &[(String, String)]) -> DotfResult<()> {
+        if links.is_empty() {
+            return Ok(());
+        }
+
+        let mut state = self.load().await?;
+        let installed_at = self.clock.now();
+
+        for (source_path, target_path) in links {
+            let base_content = self.filesystem.read_to_string(source_path).await.ok();
+            state.entries.insert(
+                target_path.clone(),
+                InstalledEntry {
+                    source_path: source_path.clone(),
+                    installed_at,
+                    base_content,
+                },
+            );
+        }
+
+        self.save(&state).await
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.clock.now()
+    }
+
+    /// Remember that the tool `name` was installed, replacing any earlier record
+    pub async fn record_tool(&self, name: &str, tool: InstalledTool) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        state.tools.insert(name.to_string(), tool);
+        self.save(&state).await
+    }
+
+    /// Remember the crontab entries just written; `None` once the managed block is removed
+    pub async fn record_cron(&self, entries: Option<BTreeMap<String, String>>) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        state.cron = entries.map(|entries| InstalledCron {
+            entries,
+            installed_at: self.clock.now(),
+        });
+        self.save(&state).await
+    }
+
+    /// Remember the links of a finished `dotf install config` and the revision they came from
+    pub async fn record_config(
+        &self,
+        revision: Option<String>,
+        links: BTreeMap<String, InstalledLink>,
+    ) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        state.config = Some(InstalledConfig {
+            revision,
+            links,
+            installed_at: self.clock.now(),
+        });
+        self.save(&state).await
+    }
+
+    /// Remember that the script at `path` ran successfully with content hashing to `sha256`
+    pub async fn record_script_run(&self, path: &str, sha256: &str) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        state.scripts.insert(
+            path.to_string(),
+            ScriptRun {
+                sha256: sha256.to_string(),
+                ran_at: self.clock.now(),
+            },
+        );
+        self.save(&state).await
+    }
+
+    /// Remember that `target_path` holds the plaintext of an encrypted file
+    pub async fn record_secret(
+        &self,
+        target_path: &str,
+        source_sha256: &str,
+        target_sha256: &str,
+    ) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        state.secrets.insert(
+            target_path.to_string(),
+            InstalledSecret {
+                source_sha256: source_sha256.to_string(),
+                target_sha256: target_sha256.to_string(),
+                decrypted_at: self.clock.now(),
+            },
+        );
+        self.save(&state).await
+    }
+
+    /// Remember the sources `output` was just rendered from; `None` once it is removed
+    pub async fn record_render(
+        &self,
+        output: &str,
+        kind: RenderKind,
+        sources: Option<BTreeMap<String, String>>,
+    ) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        match sources {
+            Some(sources) => {
+                state.renders.insert(
+                    output.to_string(),
+                    RenderedFile {
+                        kind,
+                        sources,
+                        rendered_at: self.clock.now(),
+                    },
+                );
+            }
+            None => {
+                if state.renders.remove(output).is_none() {
+                    return Ok(());
+                }
+            }
+        }
+        self.save(&state).await
+    }
+
+    /// Replace the deferred downloads of `target` among `attempted` with `deferred`
+    pub async fn record_deferred(
+        &self,
+        target: &str,
+        attempted: &[String],
+        deferred: &[String],
+    ) -> DotfResult<()> {
+        let mut state = self.load().await?;
+        let previous = state.deferred.remove(target).unwrap_or_default();
+        let mut names: Vec<String> = previous
+            .iter()
+            .filter(|name| !attempted.contains(name))
+            .chain(deferred)
+            .cloned()
+            .collect();
+        names.sort();
+        names.dedup();
+        if names == previous {
+            return Ok(());
+        }
+        if !names.is_empty() {
+            state.deferred.insert(target.to_string(), names);
+        }
+        self.save(&state).await
+    }
+
+    /// Content of the source as it was when `target_path` was last installed
+    pub async fn base_content(&self, target_path: &str) -> DotfResult<Option<String>> {
+        Ok(self
+            .load()
+            .await?
+            .entries
+            .remove(target_path)
+            .and_then(|entry| entry.base_content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::clock::tests::MockClock;
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_record_and_load_base_content() {
+        let filesystem = MockFileSystem::new();
+        filesystem.add_file("/repo/zsh/.zshrc", "export A=1\n");
+
+        let now = Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap();
+        let store = StateStore::with_clock(filesystem.clone(), Arc::new(MockClock::new(now)));
+
+        store
+            .record_installed(&[(
+                "/repo/zsh/.zshrc".to_string(),
+                "/home/user/.zshrc".to_string(),
+            )])
+            .await
+            .unwrap();
+
+        let state = store.load().await.unwrap();
+        let entry = &state.entries["/home/user/.zshrc"];
+        assert_eq!(entry.source_path, "/repo/zsh/.zshrc");
+        assert_eq!(entry.installed_at, now);
+
+        assert_eq!(
+            store.base_content("/home/user/.zshrc").await.unwrap(),
+            Some("export A=1\n".to_string())
+        );
+        assert_eq!(store.base_content("/home/user/.vimrc").await.unwrap(), None);
+    }
+
+    #[test]
+    fn test_links_recorded_as_source_paths_read_as_symlinks() {
+        let state: InstallState = serde_json::from_str(
+            r#"{"config": {"revision": null, "links": {"/home/user/.zshrc": "/repo/zsh/.zshrc"}, "installed_at": "2024-03-01T12:00:00Z"}}"#,
+        )
+        .unwrap();
+
+        let link = &state.config.unwrap().links["/home/user/.zshrc"];
+        assert_eq!(link.source_path, "/repo/zsh/.zshrc");
+        assert_eq!(link.mode, LinkMode::Symlink);
+        assert!(link.permissions.is_empty());
+    }
+}
@@ -0,0 +1,6 @@
+pub mod install_state;
+
+pub use install_state::{
+    InstallState, InstalledConfig, InstalledCron, InstalledEntry, InstalledLink, InstalledSecret,
+    InstalledTool, RenderKind, RenderedFile, ScriptRun, StateStore,
+};
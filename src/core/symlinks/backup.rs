@@ -1,10 +1,17 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fmt;
 use std::path::Path;
+use std::sync::Arc;
+use thiserror::Error;
 
-use crate::error::DotfResult;
-use crate::traits::filesystem::FileSystem;
+use crate::core::clock::{RandomIdGenerator, SystemClock};
+use crate::core::config::BackupSettings;
+use crate::core::filesystem::is_cross_device;
+use crate::error::{DotfError, DotfResult};
+use crate::traits::clock::{Clock, IdGenerator};
+use crate::traits::filesystem::{FileOwner, FileSystem};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupEntry {
@@ -12,6 +19,12 @@ pub struct BackupEntry {
     pub backup_path: String,
     pub created_at: DateTime<Utc>,
     pub file_type: BackupFileType,
+    /// Permission bits of the original file, put back on restore
+    #[serde(default)]
+    pub mode: Option<u32>,
+    /// Owner of the original file, put back on restore when the user may
+    #[serde(default)]
+    pub owner: Option<FileOwner>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +34,26 @@ pub enum BackupFileType {
     Symlink { target: String },
 }
 
+/// Why a target could not be moved into the backups; the original is still in place
+#[derive(Error, Debug)]
+pub enum BackupError {
+    #[error("permission denied {action} {path}")]
+    PermissionDenied { path: String, action: &'static str },
+
+    #[error("copying {path} to the backups on another filesystem failed: {reason}")]
+    CrossDevice { path: String, reason: String },
+
+    #[error("the copy of {path} in the backups did not match the original")]
+    VerificationFailed { path: String },
+
+    #[error("removing {path} failed partway ({reason}); its complete copy is kept in {backup}")]
+    PartlyRemoved {
+        path: String,
+        backup: String,
+        reason: String,
+    },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BackupManifest {
     pub entries: HashMap<String, BackupEntry>,
@@ -30,6 +63,25 @@ pub struct BackupManifest {
 pub struct RestoreResult {
     pub restored_count: usize,
     pub failed_restorations: Vec<RestoreError>,
+    pub ownership_warnings: Vec<OwnershipWarning>,
+}
+
+/// A restored file left owned by the restoring user, because giving it back to its
+/// original owner needs privileges
+#[derive(Debug, Clone, PartialEq)]
+pub struct OwnershipWarning {
+    pub path: String,
+    pub owner: FileOwner,
+}
+
+impl fmt::Display for OwnershipWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} could not be given back to uid {} and gid {}; run 'sudo chown {}:{} {}'",
+            self.path, self.owner.uid, self.owner.gid, self.owner.uid, self.owner.gid, self.path
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -63,59 +115,363 @@ impl BackupManifest {
 
 pub struct BackupManager<F> {
     filesystem: F,
+    clock: Arc<dyn Clock>,
+    id_generator: Arc<dyn IdGenerator>,
 }
 
 impl<F: FileSystem> BackupManager<F> {
     pub fn new(filesystem: F) -> Self {
-        Self { filesystem }
+        Self::with_clock(
+            filesystem,
+            Arc::new(SystemClock::new()),
+            Arc::new(RandomIdGenerator::new()),
+        )
     }
 
-    pub async fn backup_file(&self, file_path: &str) -> DotfResult<BackupEntry> {
-        let timestamp = Utc::now();
-        let backup_filename = format!(
-            "{}_{}",
-            Path::new(file_path)
-                .file_name()
-                .unwrap_or_default()
-                .to_string_lossy(),
-            timestamp.format("%Y%m%d_%H%M%S")
-        );
+    pub fn with_clock(
+        filesystem: F,
+        clock: Arc<dyn Clock>,
+        id_generator: Arc<dyn IdGenerator>,
+    ) -> Self {
+        Self {
+            filesystem,
+            clock,
+            id_generator,
+        }
+    }
 
-        let backup_path = format!("{}/{}", self.filesystem.dotf_backup_path(), backup_filename);
+    pub async fn backup_file(&self, file_path: &str) -> DotfResult<BackupEntry> {
+        let timestamp = self.clock.now();
+        let backup_path = self.backup_path_for(file_path, timestamp);
 
         // Ensure backup directory exists
         self.filesystem
             .create_dir_all(&self.filesystem.dotf_backup_path())
             .await?;
 
-        let file_type = if self.filesystem.is_symlink(file_path).await? {
-            let target = self.filesystem.read_link(file_path).await?;
-            BackupFileType::Symlink {
-                target: target.to_string_lossy().to_string(),
-            }
-        } else {
-            BackupFileType::File
+        let file_type = self.file_type(file_path).await?;
+        let (mode, owner) = self.file_metadata(file_path, &file_type).await;
+
+        // Copy the file, or everything in the directory, to backup location
+        match file_type {
+            BackupFileType::Directory => self.copy_tree(file_path, &backup_path).await?,
+            _ => self.filesystem.copy_file(file_path, &backup_path).await?,
+        }
+
+        let entry = BackupEntry {
+            original_path: file_path.to_string(),
+            backup_path,
+            created_at: timestamp,
+            file_type,
+            mode,
+            owner,
         };
 
-        // Copy the file to backup location
-        self.filesystem.copy_file(file_path, &backup_path).await?;
+        Ok(entry)
+    }
+
+    /// Move `file_path`, a file, symlink or whole directory, into the backups and record
+    /// it in the manifest. Across filesystems the target is copied, the copy checked
+    /// against the original and only then is the original removed. On failure the
+    /// original stays where it was and nothing is recorded, except when removing a
+    /// directory fails partway: then the copy is kept and recorded so it can be restored.
+    pub async fn move_to_backup(&self, file_path: &str) -> DotfResult<BackupEntry> {
+        let file_type = self.file_type(file_path).await?;
+        let (mode, owner) = self.file_metadata(file_path, &file_type).await;
+
+        let timestamp = self.clock.now();
+        let backup_path = self.backup_path_for(file_path, timestamp);
+        let backup_dir = self.filesystem.dotf_backup_path();
+        self.filesystem
+            .create_dir_all(&backup_dir)
+            .await
+            .map_err(|e| permission_error(e, &backup_dir, "creating"))?;
 
         let entry = BackupEntry {
             original_path: file_path.to_string(),
             backup_path,
             created_at: timestamp,
             file_type,
+            mode,
+            owner,
         };
+        match self.filesystem.rename(file_path, &entry.backup_path).await {
+            Ok(()) => {}
+            Err(DotfError::Io(e)) if is_cross_device(&e) => {
+                tracing::debug!(path = file_path, "backups on another filesystem, copying");
+                let copied = self
+                    .copy_across(file_path, &entry.backup_path, &entry.file_type)
+                    .await;
+                if let Err(e) = copied {
+                    // The backup is the only complete copy left, so it must stay restorable
+                    if matches!(e, DotfError::Backup(BackupError::PartlyRemoved { .. })) {
+                        if let Err(manifest_error) = self.add_backup_entry(entry.clone()).await {
+                            tracing::error!(
+                                path = file_path,
+                                backup = %entry.backup_path,
+                                error = %manifest_error,
+                                "could not record the backup of a partly removed original"
+                            );
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+            Err(e) => return Err(permission_error(e, file_path, "moving")),
+        }
+
+        if let Err(e) = self.add_backup_entry(entry.clone()).await {
+            // An unrecorded backup would never be restored, so put the original back
+            match self.restore_from_backup(&entry).await {
+                Ok(_) => self.discard(&entry.backup_path).await,
+                Err(restore_error) => tracing::error!(
+                    path = file_path,
+                    backup = %entry.backup_path,
+                    error = %restore_error,
+                    "could not put back original after the manifest failed"
+                ),
+            }
+            return Err(e);
+        }
 
         Ok(entry)
     }
 
-    pub async fn restore_from_backup(&self, backup_entry: &BackupEntry) -> DotfResult<()> {
+    /// Copy, verify, then remove: the fallback for a rename across filesystems
+    async fn copy_across(
+        &self,
+        file_path: &str,
+        backup_path: &str,
+        file_type: &BackupFileType,
+    ) -> DotfResult<()> {
+        let copied = match file_type {
+            BackupFileType::Symlink { target } => {
+                self.filesystem.create_symlink(target, backup_path).await
+            }
+            BackupFileType::Directory => self.copy_tree(file_path, backup_path).await,
+            BackupFileType::File => self.filesystem.copy_file(file_path, backup_path).await,
+        };
+        if let Err(e) = copied {
+            self.discard(backup_path).await;
+            return Err(match e {
+                DotfError::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                    BackupError::PermissionDenied {
+                        path: file_path.to_string(),
+                        action: "copying",
+                    }
+                }
+                e => BackupError::CrossDevice {
+                    path: file_path.to_string(),
+                    reason: e.to_string(),
+                },
+            }
+            .into());
+        }
+
+        if !self.is_same(file_path, backup_path, file_type).await {
+            self.discard(backup_path).await;
+            return Err(BackupError::VerificationFailed {
+                path: file_path.to_string(),
+            }
+            .into());
+        }
+
+        if let Err(e) = self.remove_path(file_path).await {
+            // Only a copy of an original that is still whole may be thrown away; once
+            // part of it is gone, the copy is all that is left of those parts
+            if !self.is_same(file_path, backup_path, file_type).await {
+                return Err(BackupError::PartlyRemoved {
+                    path: file_path.to_string(),
+                    backup: backup_path.to_string(),
+                    reason: e.to_string(),
+                }
+                .into());
+            }
+            self.discard(backup_path).await;
+            return Err(permission_error(e, file_path, "removing"));
+        }
+        Ok(())
+    }
+
+    /// Copy the directory `source` with everything below it to `target`. Symlinks are
+    /// copied as links and permission bits are kept.
+    async fn copy_tree(&self, source: &str, target: &str) -> DotfResult<()> {
+        let mut pending = vec![(source.to_string(), target.to_string())];
+        while let Some((source_dir, target_dir)) = pending.pop() {
+            self.filesystem.create_dir_all(&target_dir).await?;
+            if let Some(mode) = self.filesystem.mode(&source_dir).await? {
+                self.filesystem.set_mode(&target_dir, mode).await?;
+            }
+
+            for entry in self.filesystem.list_entries(&source_dir).await? {
+                let name = Path::new(&entry.path)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                let destination = format!("{}/{}", target_dir, name);
+                if entry.is_symlink {
+                    let link = self.filesystem.read_link(&entry.path).await?;
+                    self.filesystem
+                        .create_symlink(&link.to_string_lossy(), &destination)
+                        .await?;
+                } else if entry.is_dir {
+                    pending.push((entry.path, destination));
+                } else {
+                    self.filesystem.copy_file(&entry.path, &destination).await?;
+                    if let Some(mode) = self.filesystem.mode(&entry.path).await? {
+                        self.filesystem.set_mode(&destination, mode).await?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether two directories hold the same entries with the same contents and links
+    async fn is_same_tree(&self, original: &str, copy: &str) -> DotfResult<bool> {
+        let mut pending = vec![(original.to_string(), copy.to_string())];
+        while let Some((original_dir, copy_dir)) = pending.pop() {
+            let mut originals = self.filesystem.list_entries(&original_dir).await?;
+            let copies = self.filesystem.list_entries(&copy_dir).await?;
+            if originals.len() != copies.len() {
+                return Ok(false);
+            }
+
+            originals.sort_by(|a, b| a.path.cmp(&b.path));
+            for entry in originals {
+                let name = Path::new(&entry.path)
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                let counterpart = format!("{}/{}", copy_dir, name);
+                let Some(copied) = copies.iter().find(|copied| copied.path == counterpart) else {
+                    return Ok(false);
+                };
+                if entry.is_symlink != copied.is_symlink || entry.is_dir != copied.is_dir {
+                    return Ok(false);
+                }
+
+                let same = if entry.is_symlink {
+                    self.filesystem.read_link(&entry.path).await?
+                        == self.filesystem.read_link(&counterpart).await?
+                } else if entry.is_dir {
+                    pending.push((entry.path, counterpart));
+                    true
+                } else {
+                    self.filesystem.read(&entry.path).await?
+                        == self.filesystem.read(&counterpart).await?
+                };
+                if !same {
+                    return Ok(false);
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    /// Remove a file, symlink or directory tree
+    async fn remove_path(&self, path: &str) -> DotfResult<()> {
+        let is_dir = !self.filesystem.is_symlink(path).await?
+            && self.filesystem.is_dir(path).await.unwrap_or(false);
+        if is_dir {
+            self.filesystem.remove_dir(path).await
+        } else {
+            self.filesystem.remove_file(path).await
+        }
+    }
+
+    async fn is_same(
+        &self,
+        file_path: &str,
+        backup_path: &str,
+        file_type: &BackupFileType,
+    ) -> bool {
+        match file_type {
+            BackupFileType::Symlink { target } => self
+                .filesystem
+                .read_link(backup_path)
+                .await
+                .is_ok_and(|link| link.to_string_lossy() == target.as_str()),
+            BackupFileType::Directory => self
+                .is_same_tree(file_path, backup_path)
+                .await
+                .unwrap_or(false),
+            BackupFileType::File => match (
+                self.filesystem.read(file_path).await,
+                self.filesystem.read(backup_path).await,
+            ) {
+                (Ok(original), Ok(copy)) => original == copy,
+                _ => false,
+            },
+        }
+    }
+
+    /// Remove a backup that is not going to be recorded; failing to do so only leaks it
+    async fn discard(&self, backup_path: &str) {
+        if let Err(e) = self.remove_path(backup_path).await {
+            tracing::debug!(backup = backup_path, error = %e, "could not remove partial backup");
+        }
+    }
+
+    async fn file_type(&self, file_path: &str) -> DotfResult<BackupFileType> {
+        if self.filesystem.is_symlink(file_path).await? {
+            let target = self.filesystem.read_link(file_path).await?;
+            Ok(BackupFileType::Symlink {
+                target: target.to_string_lossy().to_string(),
+            })
+        } else if self.filesystem.is_dir(file_path).await? {
+            Ok(BackupFileType::Directory)
+        } else {
+            Ok(BackupFileType::File)
+        }
+    }
+
+    /// Mode and owner of a regular file or directory; links carry none of their own.
+    /// Recorded on a best-effort basis, so a failure leaves them unset rather than
+    /// failing the backup.
+    async fn file_metadata(
+        &self,
+        file_path: &str,
+        file_type: &BackupFileType,
+    ) -> (Option<u32>, Option<FileOwner>) {
+        if matches!(file_type, BackupFileType::Symlink { .. }) {
+            return (None, None);
+        }
+        (
+            self.filesystem.mode(file_path).await.ok().flatten(),
+            self.filesystem.owner(file_path).await.ok().flatten(),
+        )
+    }
+
+    fn backup_path_for(&self, file_path: &str, timestamp: DateTime<Utc>) -> String {
+        // Millisecond precision plus a unique suffix so backups taken within the
+        // same instant never overwrite each other
+        let backup_filename = format!(
+            "{}_{}_{}",
+            Path::new(file_path)
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy(),
+            timestamp.format("%Y%m%d_%H%M%S_%3f"),
+            self.id_generator.next_id()
+        );
+
+        format!("{}/{}", self.filesystem.dotf_backup_path(), backup_filename)
+    }
+
+    /// Put a backup back in place. A file or directory gets its recorded mode and owner
+    /// again; an owner that needs privileges to restore is returned as a warning instead.
+    /// Files inside a directory keep their permission bits but belong to the user.
+    pub async fn restore_from_backup(
+        &self,
+        backup_entry: &BackupEntry,
+    ) -> DotfResult<Option<OwnershipWarning>> {
         match &backup_entry.file_type {
             BackupFileType::File => {
                 self.filesystem
                     .copy_file(&backup_entry.backup_path, &backup_entry.original_path)
                     .await?;
+                return self.restore_metadata(backup_entry).await;
             }
             BackupFileType::Symlink { target } => {
                 self.filesystem
@@ -123,12 +479,45 @@ impl<F: FileSystem> BackupManager<F> {
                     .await?;
             }
             BackupFileType::Directory => {
-                self.filesystem
-                    .create_dir_all(&backup_entry.original_path)
+                self.copy_tree(&backup_entry.backup_path, &backup_entry.original_path)
                     .await?;
+                return self.restore_metadata(backup_entry).await;
             }
         }
-        Ok(())
+        Ok(None)
+    }
+
+    async fn restore_metadata(
+        &self,
+        backup_entry: &BackupEntry,
+    ) -> DotfResult<Option<OwnershipWarning>> {
+        let path = &backup_entry.original_path;
+        let mut warning = None;
+        // Ownership first: a privileged chown clears setuid and setgid bits
+        if let Some(owner) = backup_entry.owner {
+            if self.filesystem.owner(path).await? != Some(owner) {
+                match self.filesystem.set_owner(path, owner).await {
+                    Ok(()) => {}
+                    Err(DotfError::Io(e)) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                        tracing::warn!(
+                            path,
+                            uid = owner.uid,
+                            gid = owner.gid,
+                            "owner not restored"
+                        );
+                        warning = Some(OwnershipWarning {
+                            path: path.clone(),
+                            owner,
+                        });
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        }
+        if let Some(mode) = backup_entry.mode {
+            self.filesystem.set_mode(path, mode).await?;
+        }
+        Ok(warning)
     }
 
     pub async fn load_manifest(&self) -> DotfResult<BackupManifest> {
@@ -176,16 +565,17 @@ impl<F: FileSystem> BackupManager<F> {
     pub async fn remove_backup_entry(&self, original_path: &str) -> DotfResult<()> {
         let mut manifest = self.load_manifest().await?;
         if let Some(entry) = manifest.entries.remove(original_path) {
-            // Remove the backup file
-            self.filesystem.remove_file(&entry.backup_path).await?;
+            // Remove the backup file or directory
+            self.remove_path(&entry.backup_path).await?;
         }
         self.save_manifest(&manifest).await?;
         Ok(())
     }
 
-    pub async fn cleanup_old_backups(&self, days: u64) -> DotfResult<()> {
+    /// Remove backups older than `days` and return how many were dropped
+    pub async fn cleanup_old_backups(&self, days: u64) -> DotfResult<usize> {
         let mut manifest = self.load_manifest().await?;
-        let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+        let cutoff = self.clock.now() - chrono::Duration::days(days as i64);
 
         let mut to_remove = Vec::new();
         for (path, entry) in &manifest.entries {
@@ -194,33 +584,84 @@ impl<F: FileSystem> BackupManager<F> {
             }
         }
 
-        for path in to_remove {
-            if let Some(entry) = manifest.entries.remove(&path) {
-                self.filesystem.remove_file(&entry.backup_path).await?;
+        self.remove_from_manifest(&mut manifest, &to_remove).await
+    }
+
+    /// Remove all but the `count` newest backups and return how many were dropped
+    pub async fn keep_last_backups(&self, count: usize) -> DotfResult<usize> {
+        let mut manifest = self.load_manifest().await?;
+        let mut entries: Vec<&BackupEntry> = manifest.entries.values().collect();
+        entries.sort_by_key(|entry| std::cmp::Reverse(entry.created_at));
+
+        let to_remove: Vec<String> = entries
+            .iter()
+            .skip(count)
+            .map(|entry| entry.original_path.clone())
+            .collect();
+        self.remove_from_manifest(&mut manifest, &to_remove).await
+    }
+
+    /// Apply a retention policy from settings and return how many backups were dropped
+    pub async fn apply_retention(&self, policy: &BackupSettings) -> DotfResult<usize> {
+        let mut removed = 0;
+        if let Some(days) = policy.keep_days {
+            removed += self.cleanup_old_backups(days).await?;
+        }
+        if let Some(count) = policy.keep_last {
+            removed += self.keep_last_backups(count).await?;
+        }
+        Ok(removed)
+    }
+
+    /// Remove the backups of the given original paths and return how many were dropped
+    pub async fn remove_backups(&self, original_paths: &[String]) -> DotfResult<usize> {
+        let mut manifest = self.load_manifest().await?;
+        self.remove_from_manifest(&mut manifest, original_paths)
+            .await
+    }
+
+    async fn remove_from_manifest(
+        &self,
+        manifest: &mut BackupManifest,
+        original_paths: &[String],
+    ) -> DotfResult<usize> {
+        let mut removed = 0;
+        for path in original_paths {
+            if let Some(entry) = manifest.entries.remove(path) {
+                // The backup may already be gone; the manifest entry is dropped either way
+                if self.filesystem.exists(&entry.backup_path).await? {
+                    self.remove_path(&entry.backup_path).await?;
+                }
+                removed += 1;
             }
         }
 
-        self.save_manifest(&manifest).await?;
-        Ok(())
+        if removed > 0 {
+            self.save_manifest(manifest).await?;
+        }
+        Ok(removed)
     }
 
-    pub async fn restore_specific_backup(&self, original_path: &str) -> DotfResult<()> {
+    pub async fn restore_specific_backup(
+        &self,
+        original_path: &str,
+    ) -> DotfResult<Option<OwnershipWarning>> {
         let entry = self.get_backup_entry(original_path).await?;
 
         match entry {
             Some(backup_entry) => {
                 // Remove current file/symlink if it exists
                 if self.filesystem.exists(original_path).await? {
-                    self.filesystem.remove_file(original_path).await?;
+                    self.remove_path(original_path).await?;
                 }
 
                 // Restore from backup
-                self.restore_from_backup(&backup_entry).await?;
+                let warning = self.restore_from_backup(&backup_entry).await?;
 
                 // Remove the backup entry from manifest
                 self.remove_backup_entry(original_path).await?;
 
-                Ok(())
+                Ok(warning)
             }
             None => Err(crate::error::DotfError::Operation(format!(
                 "No backup found for: {}",
@@ -236,11 +677,13 @@ impl<F: FileSystem> BackupManager<F> {
             return Ok(RestoreResult {
                 restored_count: 0,
                 failed_restorations: Vec::new(),
+                ownership_warnings: Vec::new(),
             });
         }
 
         let mut restored_count = 0;
         let mut failed_restorations = Vec::new();
+        let mut ownership_warnings = Vec::new();
 
         // Process each backup entry
         for (original_path, entry) in &manifest.entries {
@@ -248,8 +691,9 @@ impl<F: FileSystem> BackupManager<F> {
                 .restore_specific_file_from_entry(original_path, entry)
                 .await
             {
-                Ok(_) => {
+                Ok(warning) => {
                     restored_count += 1;
+                    ownership_warnings.extend(warning);
                 }
                 Err(e) => {
                     failed_restorations.push(RestoreError {
@@ -271,6 +715,7 @@ impl<F: FileSystem> BackupManager<F> {
         Ok(RestoreResult {
             restored_count,
             failed_restorations,
+            ownership_warnings,
         })
     }
 
@@ -278,16 +723,14 @@ impl<F: FileSystem> BackupManager<F> {
         &self,
         original_path: &str,
         entry: &BackupEntry,
-    ) -> DotfResult<()> {
+    ) -> DotfResult<Option<OwnershipWarning>> {
         // Remove current file/symlink if it exists
         if self.filesystem.exists(original_path).await? {
-            self.filesystem.remove_file(original_path).await?;
+            self.remove_path(original_path).await?;
         }
 
         // Restore from backup
-        self.restore_from_backup(entry).await?;
-
-        Ok(())
+        self.restore_from_backup(entry).await
     }
 
     pub async fn list_backups(&self) -> DotfResult<Vec<BackupInfo>> {
@@ -306,16 +749,32 @@ impl<F: FileSystem> BackupManager<F> {
             .collect();
 
         // Sort by creation date (newest first)
-        backups.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        backups.sort_by_key(|b| std::cmp::Reverse(b.created_at));
 
         Ok(backups)
     }
 }
 
+/// Turn a permission error on `path` into a [`BackupError`], passing others through
+fn permission_error(error: DotfError, path: &str, action: &'static str) -> DotfError {
+    match error {
+        DotfError::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            BackupError::PermissionDenied {
+                path: path.to_string(),
+                action,
+            }
+            .into()
+        }
+        e => e,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::traits::clock::tests::{MockClock, SequentialIdGenerator};
     use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::TimeZone;
 
     #[tokio::test]
     async fn test_backup_file() {
@@ -378,6 +837,60 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_restore_keeps_mode_and_owner() {
+        let fs = MockFileSystem::new();
+        let root = FileOwner { uid: 0, gid: 0 };
+        fs.add_file("/home/user/.ssh/config", "Host *");
+        fs.set_mode("/home/user/.ssh/config", 0o600).await.unwrap();
+        fs.set_owner("/home/user/.ssh/config", root).await.unwrap();
+
+        let backup_manager = BackupManager::new(fs.clone());
+        let entry = backup_manager
+            .move_to_backup("/home/user/.ssh/config")
+            .await
+            .unwrap();
+        assert_eq!(entry.mode, Some(0o600));
+        assert_eq!(entry.owner, Some(root));
+
+        // Without privileges the mode comes back and the owner is reported
+        fs.deny_chown();
+        let warning = backup_manager
+            .restore_specific_backup("/home/user/.ssh/config")
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.mode("/home/user/.ssh/config").await.unwrap(),
+            Some(0o600)
+        );
+        assert_eq!(
+            warning,
+            Some(OwnershipWarning {
+                path: "/home/user/.ssh/config".to_string(),
+                owner: root,
+            })
+        );
+        assert!(warning
+            .unwrap()
+            .to_string()
+            .contains("sudo chown 0:0 /home/user/.ssh/config"));
+
+        // Files owned by the user need no privileges
+        let entry = backup_manager
+            .move_to_backup("/home/user/.ssh/config")
+            .await
+            .unwrap();
+        fs.owners.lock().unwrap().clear();
+        let restored = BackupEntry {
+            owner: Some(MockFileSystem::USER),
+            ..entry
+        };
+        assert_eq!(
+            backup_manager.restore_from_backup(&restored).await.unwrap(),
+            None
+        );
+    }
+
     #[tokio::test]
     async fn test_manifest_operations() {
         let fs = MockFileSystem::new();
@@ -388,6 +901,8 @@ mod tests {
             backup_path: "/home/user/.dotf/backups/.vimrc_20240101_120000".to_string(),
             created_at: Utc::now(),
             file_type: BackupFileType::File,
+            mode: None,
+            owner: None,
         };
 
         // Add entry to manifest
@@ -415,4 +930,274 @@ mod tests {
             .unwrap();
         assert!(retrieved.is_none());
     }
+
+    #[tokio::test]
+    async fn test_backup_names_are_unique_within_same_instant() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/home/user/.vimrc", "set number");
+
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+        let backup_manager = BackupManager::with_clock(
+            fs.clone(),
+            Arc::new(clock),
+            Arc::new(SequentialIdGenerator::new()),
+        );
+
+        let first = backup_manager
+            .backup_file("/home/user/.vimrc")
+            .await
+            .unwrap();
+        let second = backup_manager
+            .backup_file("/home/user/.vimrc")
+            .await
+            .unwrap();
+
+        assert_ne!(first.backup_path, second.backup_path);
+        assert!(first
+            .backup_path
+            .ends_with(".vimrc_20240101_120000_000_00000001"));
+        assert_eq!(
+            first.created_at,
+            Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_cleanup_old_backups_uses_clock() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/home/user/.vimrc", "set number");
+
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+        let backup_manager = BackupManager::with_clock(
+            fs.clone(),
+            Arc::new(clock.clone()),
+            Arc::new(SequentialIdGenerator::new()),
+        );
+
+        let entry = backup_manager
+            .backup_file("/home/user/.vimrc")
+            .await
+            .unwrap();
+        backup_manager.add_backup_entry(entry).await.unwrap();
+
+        clock.advance(chrono::Duration::days(10));
+        assert_eq!(backup_manager.cleanup_old_backups(30).await.unwrap(), 0);
+        assert!(backup_manager
+            .get_backup_entry("/home/user/.vimrc")
+            .await
+            .unwrap()
+            .is_some());
+
+        clock.advance(chrono::Duration::days(30));
+        assert_eq!(backup_manager.cleanup_old_backups(30).await.unwrap(), 1);
+        assert!(backup_manager
+            .get_backup_entry("/home/user/.vimrc")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_apply_retention() {
+        let fs = MockFileSystem::new();
+        let clock = MockClock::new(Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap());
+        let backup_manager = BackupManager::with_clock(
+            fs.clone(),
+            Arc::new(clock.clone()),
+            Arc::new(SequentialIdGenerator::new()),
+        );
+        for name in [".vimrc", ".bashrc", ".zshrc", ".gitconfig"] {
+            let path = format!("/home/user/{}", name);
+            fs.add_file(&path, name);
+            let entry = backup_manager.backup_file(&path).await.unwrap();
+            backup_manager.add_backup_entry(entry).await.unwrap();
+            clock.advance(chrono::Duration::days(10));
+        }
+
+        // Backed up 40, 30, 20 and 10 days ago
+        let policy = BackupSettings {
+            keep_days: Some(25),
+            keep_last: Some(1),
+        };
+        assert_eq!(backup_manager.apply_retention(&policy).await.unwrap(), 3);
+        let remaining: Vec<String> = backup_manager
+            .list_backups()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.original_path)
+            .collect();
+        assert_eq!(remaining, vec!["/home/user/.gitconfig"]);
+        assert_eq!(
+            backup_manager
+                .apply_retention(&BackupSettings::default())
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_to_backup_across_filesystems() {
+        let fs = MockFileSystem::new();
+        fs.add_mount("/mnt/data");
+        fs.add_file("/home/user/.vimrc", "set number");
+        fs.add_file("/mnt/data/.npmrc", "registry=local");
+        fs.create_symlink("/elsewhere/.gitconfig", "/mnt/data/.gitconfig")
+            .await
+            .unwrap();
+        let backup_manager = BackupManager::new(fs.clone());
+
+        // Same filesystem: a plain rename
+        let entry = backup_manager
+            .move_to_backup("/home/user/.vimrc")
+            .await
+            .unwrap();
+        assert!(!fs.exists("/home/user/.vimrc").await.unwrap());
+        assert_eq!(
+            fs.read_to_string(&entry.backup_path).await.unwrap(),
+            "set number"
+        );
+
+        // Another filesystem: copied and checked before the original goes
+        let entry = backup_manager
+            .move_to_backup("/mnt/data/.npmrc")
+            .await
+            .unwrap();
+        assert!(!fs.exists("/mnt/data/.npmrc").await.unwrap());
+        assert_eq!(
+            fs.read_to_string(&entry.backup_path).await.unwrap(),
+            "registry=local"
+        );
+        let entry = backup_manager
+            .move_to_backup("/mnt/data/.gitconfig")
+            .await
+            .unwrap();
+        assert_eq!(
+            fs.read_link(&entry.backup_path).await.unwrap(),
+            std::path::PathBuf::from("/elsewhere/.gitconfig")
+        );
+        assert_eq!(
+            backup_manager.load_manifest().await.unwrap().entries.len(),
+            3
+        );
+
+        // A target that cannot be removed stays, and so does nothing else
+        fs.add_file("/mnt/data/locked/.bashrc", "export A=1");
+        fs.add_read_only("/mnt/data/locked");
+        let error = backup_manager
+            .move_to_backup("/mnt/data/locked/.bashrc")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            DotfError::Backup(BackupError::PermissionDenied {
+                action: "removing",
+                ..
+            })
+        ));
+        assert!(fs.exists("/mnt/data/locked/.bashrc").await.unwrap());
+        assert!(backup_manager
+            .get_backup_entry("/mnt/data/locked/.bashrc")
+            .await
+            .unwrap()
+            .is_none());
+        let backups = fs.list_entries(&fs.dotf_backup_path()).await.unwrap();
+        assert!(!backups.iter().any(|b| b.path.contains(".bashrc")));
+    }
+
+    #[tokio::test]
+    async fn test_directory_partly_removed_across_filesystems_keeps_backup() {
+        let fs = MockFileSystem::new();
+        fs.add_mount("/mnt/data");
+        fs.add_directory("/mnt/data/nvim");
+        fs.add_directory("/mnt/data/nvim/lua");
+        fs.add_file("/mnt/data/nvim/init.lua", "require('plugins')");
+        fs.add_file("/mnt/data/nvim/lua/plugins.lua", "return {}");
+        // init.lua can go, but nothing in lua/ can
+        fs.add_read_only("/mnt/data/nvim/lua");
+        let backup_manager = BackupManager::new(fs.clone());
+
+        let error = backup_manager
+            .move_to_backup("/mnt/data/nvim")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error,
+            DotfError::Backup(BackupError::PartlyRemoved { .. })
+        ));
+        assert!(!fs.exists("/mnt/data/nvim/init.lua").await.unwrap());
+        let entry = backup_manager
+            .get_backup_entry("/mnt/data/nvim")
+            .await
+            .unwrap()
+            .expect("the backup is recorded");
+        assert_eq!(
+            fs.read_to_string(&format!("{}/init.lua", entry.backup_path))
+                .await
+                .unwrap(),
+            "require('plugins')"
+        );
+        assert_eq!(
+            fs.read_to_string(&format!("{}/lua/plugins.lua", entry.backup_path))
+                .await
+                .unwrap(),
+            "return {}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_directory_backup_keeps_contents() {
+        let fs = MockFileSystem::new();
+        fs.add_mount("/mnt/data");
+        for root in ["/home/user/.config/nvim", "/mnt/data/nvim"] {
+            fs.add_directory(root);
+            fs.add_directory(&format!("{}/lua", root));
+            fs.add_file(&format!("{}/init.lua", root), "require('plugins')");
+            fs.add_file(&format!("{}/lua/plugins.lua", root), "return {}");
+            fs.set_mode(&format!("{}/init.lua", root), 0o600)
+                .await
+                .unwrap();
+            fs.create_symlink("/opt/colors", &format!("{}/colors", root))
+                .await
+                .unwrap();
+        }
+        let backup_manager = BackupManager::new(fs.clone());
+
+        // Renamed on the same filesystem, copied and checked across filesystems
+        for root in ["/home/user/.config/nvim", "/mnt/data/nvim"] {
+            let entry = backup_manager.move_to_backup(root).await.unwrap();
+            assert!(matches!(entry.file_type, BackupFileType::Directory));
+            assert!(!fs.exists(root).await.unwrap());
+            assert!(!fs.exists(&format!("{}/init.lua", root)).await.unwrap());
+            assert_eq!(
+                fs.read_to_string(&format!("{}/lua/plugins.lua", entry.backup_path))
+                    .await
+                    .unwrap(),
+                "return {}"
+            );
+
+            fs.create_symlink("/home/user/.dotf/repo/nvim", root)
+                .await
+                .unwrap();
+            backup_manager.restore_specific_backup(root).await.unwrap();
+            assert!(fs.is_dir(root).await.unwrap());
+            assert_eq!(
+                fs.read_to_string(&format!("{}/init.lua", root))
+                    .await
+                    .unwrap(),
+                "require('plugins')"
+            );
+            assert_eq!(
+                fs.mode(&format!("{}/init.lua", root)).await.unwrap(),
+                Some(0o600)
+            );
+            assert_eq!(
+                fs.read_link(&format!("{}/colors", root)).await.unwrap(),
+                std::path::PathBuf::from("/opt/colors")
+            );
+            assert!(!fs.exists(&entry.backup_path).await.unwrap());
+        }
+    }
 }
@@ -4,7 +4,7 @@ use super::backup::{BackupEntry, BackupManager};
 use crate::error::{DotfError, DotfResult};
 use crate::traits::{filesystem::FileSystem, prompt::Prompt};
 
-#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, clap::ValueEnum)]
 pub enum ConflictResolution {
     Skip,
     Backup,
@@ -12,12 +12,26 @@ pub enum ConflictResolution {
     Abort,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ConflictType {
+    File,     // A regular file or directory occupies the target
+    Symlink,  // A symlink pointing somewhere else occupies the target
+    NixStore, // A symlink into /nix/store, typically managed by home-manager
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct ConflictInfo {
     pub target_path: String,
     pub source_path: String,
     pub existing_is_symlink: bool,
     pub existing_target: Option<String>,
+    pub conflict_type: ConflictType,
+}
+
+/// Nix-managed files live in the read-only store; replacing the symlink breaks
+/// the next home-manager activation.
+pub fn is_nix_store_path(path: &str) -> bool {
+    path.starts_with("/nix/store/")
 }
 
 pub struct ConflictResolver<F, P> {
@@ -65,11 +79,18 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
             }
         }
 
+        let conflict_type = match existing_target {
+            Some(ref target) if is_nix_store_path(target) => ConflictType::NixStore,
+            Some(_) => ConflictType::Symlink,
+            None => ConflictType::File,
+        };
+
         Ok(Some(ConflictInfo {
             target_path: target_path.to_string(),
             source_path: source_path.to_string(),
             existing_is_symlink,
             existing_target,
+            conflict_type,
         }))
     }
 
@@ -78,6 +99,19 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
         conflict: &ConflictInfo,
         resolution: ConflictResolution,
     ) -> DotfResult<Option<BackupEntry>> {
+        if conflict.conflict_type == ConflictType::NixStore
+            && matches!(
+                resolution,
+                ConflictResolution::Overwrite | ConflictResolution::Backup
+            )
+        {
+            return Err(DotfError::Symlink(format!(
+                "Refusing to replace '{}': it points into the Nix store ({}). Remove it from your home-manager configuration first",
+                conflict.target_path,
+                conflict.existing_target.as_deref().unwrap_or("unknown")
+            )));
+        }
+
         match resolution {
             ConflictResolution::Skip => Ok(None),
             ConflictResolution::Abort => Err(DotfError::Operation(
@@ -90,11 +124,7 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
             ConflictResolution::Backup => {
                 let backup_entry = self
                     .backup_manager
-                    .backup_file(&conflict.target_path)
-                    .await?;
-                self.remove_existing(&conflict.target_path).await?;
-                self.backup_manager
-                    .add_backup_entry(backup_entry.clone())
+                    .move_to_backup(&conflict.target_path)
                     .await?;
                 Ok(Some(backup_entry))
             }
@@ -105,6 +135,11 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
         &self,
         conflict: &ConflictInfo,
     ) -> DotfResult<Option<BackupEntry>> {
+        // Nix-managed targets are never offered for replacement
+        if conflict.conflict_type == ConflictType::NixStore {
+            return Ok(None);
+        }
+
         let existing_type = if conflict.existing_is_symlink {
             format!(
                 "symlink -> {}",
@@ -154,6 +189,13 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
         &self,
         conflicts: &[ConflictInfo],
     ) -> DotfResult<Vec<BackupEntry>> {
+        // Nix-managed targets are left alone regardless of the chosen strategy
+        let conflicts: Vec<ConflictInfo> = conflicts
+            .iter()
+            .filter(|c| c.conflict_type != ConflictType::NixStore)
+            .cloned()
+            .collect();
+
         if conflicts.is_empty() {
             return Ok(Vec::new());
         }
@@ -183,7 +225,7 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
             0 => {
                 // Individual resolution
                 let mut backup_entries = Vec::new();
-                for conflict in conflicts {
+                for conflict in &conflicts {
                     if let Some(entry) = self.resolve_conflict_interactive(conflict).await? {
                         backup_entries.push(entry);
                     }
@@ -195,25 +237,12 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
                 Ok(Vec::new())
             }
             2 => {
-                // Backup all
-                let mut backup_entries = Vec::new();
-                for conflict in conflicts {
-                    if let Some(entry) = self
-                        .resolve_conflict(conflict, ConflictResolution::Backup)
-                        .await?
-                    {
-                        backup_entries.push(entry);
-                    }
-                }
-                Ok(backup_entries)
+                self.resolve_all_conflicts(&conflicts, ConflictResolution::Backup)
+                    .await
             }
             3 => {
-                // Overwrite all
-                for conflict in conflicts {
-                    self.resolve_conflict(conflict, ConflictResolution::Overwrite)
-                        .await?;
-                }
-                Ok(Vec::new())
+                self.resolve_all_conflicts(&conflicts, ConflictResolution::Overwrite)
+                    .await
             }
             _ => {
                 // Abort or invalid choice
@@ -224,6 +253,39 @@ impl<F: FileSystem + Clone, P: Prompt> ConflictResolver<F, P> {
         }
     }
 
+    /// Apply `resolution` to every conflict without prompting, for non-interactive runs.
+    /// Nix-managed targets are skipped like in the interactive flow.
+    pub async fn resolve_all_conflicts(
+        &self,
+        conflicts: &[ConflictInfo],
+        resolution: ConflictResolution,
+    ) -> DotfResult<Vec<BackupEntry>> {
+        let conflicts: Vec<&ConflictInfo> = conflicts
+            .iter()
+            .filter(|c| c.conflict_type != ConflictType::NixStore)
+            .collect();
+
+        if resolution == ConflictResolution::Abort && !conflicts.is_empty() {
+            return Err(DotfError::Operation(format!(
+                "Found {} conflict(s): {}",
+                conflicts.len(),
+                conflicts
+                    .iter()
+                    .map(|c| c.target_path.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+
+        let mut backup_entries = Vec::new();
+        for conflict in conflicts {
+            if let Some(entry) = self.resolve_conflict(conflict, resolution.clone()).await? {
+                backup_entries.push(entry);
+            }
+        }
+        Ok(backup_entries)
+    }
+
     async fn remove_existing(&self, path: &str) -> DotfResult<()> {
         // Remove existing file or symlink
         self.filesystem.remove_file(path).await?;
@@ -326,6 +388,7 @@ mod tests {
             source_path: "/source/.vimrc".to_string(),
             existing_is_symlink: false,
             existing_target: None,
+            conflict_type: ConflictType::File,
         };
 
         let result = resolver
@@ -351,6 +414,7 @@ mod tests {
             source_path: "/source/.vimrc".to_string(),
             existing_is_symlink: false,
             existing_target: None,
+            conflict_type: ConflictType::File,
         };
 
         let result = resolver
@@ -376,6 +440,7 @@ mod tests {
             source_path: "/source/.vimrc".to_string(),
             existing_is_symlink: false,
             existing_target: None,
+            conflict_type: ConflictType::File,
         };
 
         let result = resolver
@@ -402,6 +467,7 @@ mod tests {
             source_path: "/source/.vimrc".to_string(),
             existing_is_symlink: false,
             existing_target: None,
+            conflict_type: ConflictType::File,
         };
 
         let result = resolver
@@ -410,4 +476,33 @@ mod tests {
         assert!(result.is_err());
         assert!(matches!(result.unwrap_err(), DotfError::Operation(_)));
     }
+
+    #[tokio::test]
+    async fn test_conflict_with_nix_store_symlink() {
+        let fs = MockFileSystem::new();
+        let prompt = MockPrompt::new();
+
+        fs.create_symlink(
+            "/nix/store/abc123-home-manager-files/.vimrc",
+            "/home/user/.vimrc",
+        )
+        .await
+        .unwrap();
+
+        let resolver = ConflictResolver::new(fs.clone(), prompt);
+        let conflict = resolver
+            .check_conflict("/source/.vimrc", "/home/user/.vimrc")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(conflict.conflict_type, ConflictType::NixStore);
+
+        let result = resolver
+            .resolve_conflict(&conflict, ConflictResolution::Overwrite)
+            .await;
+        assert!(matches!(result.unwrap_err(), DotfError::Symlink(_)));
+
+        // The Nix-managed symlink must be left untouched
+        assert!(fs.is_symlink("/home/user/.vimrc").await.unwrap());
+    }
 }
@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use crate::core::symlinks::SymlinkOperation;
+use crate::error::DotfResult;
+use crate::traits::repository::Repository;
+
+/// Split operations expanded from a directory into those to link and those whose source
+/// `.gitattributes` marks `export-ignore`, either directly or through a parent directory
+pub async fn partition_export_ignored<R: Repository + ?Sized>(
+    repository: &R,
+    repo_path: &str,
+    operations: Vec<SymlinkOperation>,
+) -> DotfResult<(Vec<SymlinkOperation>, Vec<SymlinkOperation>)> {
+    let prefix = format!("{}/", repo_path);
+    let mut paths = HashSet::new();
+    for operation in &operations {
+        if let Some(relative) = operation.source_path.strip_prefix(&prefix) {
+            paths.extend(path_and_parents(relative));
+        }
+    }
+    let paths: Vec<String> = paths.into_iter().collect();
+    let ignored: HashSet<String> = repository
+        .export_ignored(repo_path, &paths)
+        .await?
+        .into_iter()
+        .collect();
+
+    Ok(operations.into_iter().partition(|operation| {
+        !operation
+            .source_path
+            .strip_prefix(&prefix)
+            .is_some_and(|relative| path_and_parents(relative).any(|path| ignored.contains(&path)))
+    }))
+}
+
+/// `path` followed by each of its parent directories, e.g. `a/b/c`, `a/b`, `a`
+fn path_and_parents(path: &str) -> impl Iterator<Item = String> + '_ {
+    std::iter::successors(Some(path), |path| {
+        path.rsplit_once('/').map(|(parent, _)| parent)
+    })
+    .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::repository::tests::MockRepository;
+
+    #[tokio::test]
+    async fn test_partition_export_ignored() {
+        let repository = MockRepository::new();
+        repository.set_export_ignored(&["vim/plugin/doc", "vim/plugin/README.md"]);
+        let operation = |source: &str| SymlinkOperation {
+            source_path: format!("/repo/{}", source),
+            target_path: format!("/home/user/.{}", source),
+            mode: Default::default(),
+            permissions: Default::default(),
+        };
+
+        let (kept, skipped) = partition_export_ignored(
+            &repository,
+            "/repo",
+            vec![
+                operation("vim/plugin/plugin.vim"),
+                operation("vim/plugin/README.md"),
+                operation("vim/plugin/doc/help.txt"),
+            ],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].source_path, "/repo/vim/plugin/plugin.vim");
+        assert_eq!(skipped.len(), 2);
+    }
+}
@@ -1,21 +1,40 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::path::Path;
+use std::sync::Arc;
 
 use super::{
     backup::{BackupEntry, BackupManager},
-    conflict::{ConflictInfo, ConflictResolver},
+    conflict::{
+        is_nix_store_path, ConflictInfo, ConflictResolution, ConflictResolver, ConflictType,
+    },
+    mode::LinkMode,
+    permissions::{self, Permissions},
+    privileged::LinkPlan,
 };
+use crate::core::state::StateStore;
+use crate::core::timings;
+use crate::core::tools::asset::sha256_hex;
 use crate::error::{DotfError, DotfResult};
-use crate::traits::{filesystem::FileSystem, prompt::Prompt, repository::Repository};
+use crate::traits::{
+    filesystem::FileSystem,
+    privileged_helper::PrivilegedHelper,
+    progress::{LinkProgress, ProgressEvent, ProgressReporter},
+    prompt::Prompt,
+    repository::Repository,
+};
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SymlinkStatus {
-    Valid,         // Symlink exists and points to correct target
-    Missing,       // Symlink does not exist
-    Broken,        // Symlink exists but target does not exist
-    Conflict,      // File exists at target location but is not the expected symlink
-    InvalidTarget, // Symlink exists but points to wrong target
-    Modified,      // Symlink is valid but source file has local changes
+    Valid,            // Symlink exists and points to correct target
+    Missing,          // Symlink does not exist
+    Broken,           // Symlink exists but target does not exist
+    Conflict,         // File exists at target location but is not the expected symlink
+    InvalidTarget,    // Symlink exists but points to wrong target
+    Modified,         // Symlink is valid but source file has local changes
+    Disabled,         // Turned off on this machine in settings; never linked or checked
+    WrongPermissions, // Deployed correctly, but its mode or owner differs from chmod/owner
+    Unreadable,       // Deployed, but opening it through the link fails, e.g. permission denied
 }
 
 #[derive(Debug, Clone)]
@@ -24,12 +43,63 @@ pub struct SymlinkInfo {
     pub target_path: String,
     pub status: SymlinkStatus,
     pub current_target: Option<String>,
+    /// The error opening the file gives, for Unreadable links
+    pub error: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// What `repair_symlinks` did, or would do, at one target
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum RepairOutcome {
+    /// The link was missing and is created
+    Created,
+    /// A broken or wrong link is removed and created again
+    Recreated,
+    /// The file in the way is backed up, then replaced by the link
+    BackedUp,
+    /// The file in the way is removed without a backup, then replaced by the link
+    Removed,
+    /// Dry runs only: the file in the way is kept or replaced depending on the answer to a
+    /// prompt
+    Undecided,
+    /// The mode or owner of the deployed file is set to what dotf.toml asks for
+    PermissionsFixed,
+    /// Left as it is, with the reason
+    Skipped(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepairedLink {
+    pub source_path: String,
+    pub target_path: String,
+    /// Status before the repair
+    pub status: SymlinkStatus,
+    pub outcome: RepairOutcome,
+}
+
+#[derive(Debug, Clone, Serialize)]
 pub struct SymlinkOperation {
     pub source_path: String,
     pub target_path: String,
+    pub mode: LinkMode,
+    pub permissions: Permissions,
+}
+
+/// What `create_symlinks` will do at one target, decided before anything is touched
+#[derive(Debug, Clone, Serialize)]
+pub enum LinkAction {
+    /// Nothing is there yet; the link is created
+    Create,
+    /// The target already links to the source
+    AlreadyLinked,
+    /// Something else is in the way; the conflict policy or a prompt decides whether it
+    /// is replaced. Nix-managed targets are always left alone.
+    Resolve(ConflictInfo),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LinkStep {
+    pub operation: SymlinkOperation,
+    pub action: LinkAction,
 }
 
 pub struct SymlinkManager<F, P> {
@@ -38,18 +108,52 @@ pub struct SymlinkManager<F, P> {
     prompt: P,
     pub backup_manager: BackupManager<F>,
     conflict_resolver: ConflictResolver<F, P>,
+    state_store: StateStore<F>,
+    privileged_helper: Option<Box<dyn PrivilegedHelper>>,
+    conflict_policy: Option<ConflictResolution>,
+    progress: Option<Arc<dyn ProgressReporter>>,
 }
 
 impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
     pub fn new(filesystem: F, prompt: P) -> Self {
         let backup_manager = BackupManager::new(filesystem.clone());
         let conflict_resolver = ConflictResolver::new(filesystem.clone(), prompt.clone());
+        let state_store = StateStore::new(filesystem.clone());
 
         Self {
             filesystem,
             prompt,
             backup_manager,
             conflict_resolver,
+            state_store,
+            privileged_helper: None,
+            conflict_policy: None,
+            progress: None,
+        }
+    }
+
+    /// Create the links the current user is not permitted to create through `helper`,
+    /// all in one batch after the others
+    pub fn with_privileged_helper(mut self, helper: Box<dyn PrivilegedHelper>) -> Self {
+        self.privileged_helper = Some(helper);
+        self
+    }
+
+    /// Resolve every conflict with `policy` instead of prompting
+    pub fn with_conflict_policy(mut self, policy: Option<ConflictResolution>) -> Self {
+        self.conflict_policy = policy;
+        self
+    }
+
+    /// Report created, skipped and conflicting links to `progress` as the plan runs
+    pub fn with_progress(mut self, progress: Arc<dyn ProgressReporter>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    fn report(&self, progress: LinkProgress) {
+        if let Some(reporter) = &self.progress {
+            reporter.report(ProgressEvent::Links(progress));
         }
     }
 
@@ -62,51 +166,276 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
         operations: &[SymlinkOperation],
         interactive: bool,
     ) -> DotfResult<Vec<BackupEntry>> {
-        // Check for conflicts first
-        let conflicts = self.check_conflicts(operations).await?;
+        let plan = self.plan_symlinks(operations).await?;
+        self.execute_plan(&plan, interactive).await
+    }
+
+    /// Decide what `create_symlinks` would do at each target without changing anything
+    pub async fn plan_symlinks(
+        &self,
+        operations: &[SymlinkOperation],
+    ) -> DotfResult<Vec<LinkStep>> {
+        let mut plan = Vec::new();
+        for operation in operations {
+            let action = match self.find_conflict(operation).await? {
+                Some(conflict) => LinkAction::Resolve(conflict),
+                None if self.filesystem.exists(&operation.target_path).await? => {
+                    LinkAction::AlreadyLinked
+                }
+                None => LinkAction::Create,
+            };
+            tracing::debug!(
+                source = %operation.source_path,
+                target = %operation.target_path,
+                ?action,
+                "planned link"
+            );
+            plan.push(LinkStep {
+                operation: operation.clone(),
+                action,
+            });
+        }
+        Ok(plan)
+    }
 
+    /// Carry out a plan from `plan_symlinks`: resolve its conflicts first, then create
+    /// the links whose targets are free
+    pub async fn execute_plan(
+        &self,
+        plan: &[LinkStep],
+        interactive: bool,
+    ) -> DotfResult<Vec<BackupEntry>> {
+        let conflicts: Vec<ConflictInfo> = plan
+            .iter()
+            .filter_map(|step| match &step.action {
+                LinkAction::Resolve(conflict) => Some(conflict.clone()),
+                _ => None,
+            })
+            .collect();
+
+        if !conflicts.is_empty() {
+            tracing::info!(
+                count = conflicts.len(),
+                policy = ?self.conflict_policy,
+                interactive,
+                "resolving conflicts"
+            );
+        }
         let backup_entries = if conflicts.is_empty() {
             Vec::new()
+        } else if let Some(policy) = &self.conflict_policy {
+            self.conflict_resolver
+                .resolve_all_conflicts(&conflicts, policy.clone())
+                .await?
         } else if interactive {
             self.conflict_resolver
                 .resolve_all_conflicts_interactive(&conflicts)
                 .await?
         } else {
             return Err(DotfError::Operation(format!(
-                "Found {} conflict(s) but running in non-interactive mode",
+                "Found {} conflict(s) but running in non-interactive mode; choose a policy with --on-conflict",
                 conflicts.len()
             )));
         };
 
-        // Create all symlinks
-        for operation in operations {
-            // Skip if there was a conflict that still exists (wasn't resolved)
-            if conflicts
-                .iter()
-                .any(|c| c.target_path == operation.target_path)
-                && self.filesystem.exists(&operation.target_path).await?
-            {
+        // Create all symlinks whose target is free now; a target still in the way had its
+        // conflict skipped
+        let operations: Vec<SymlinkOperation> =
+            plan.iter().map(|step| step.operation.clone()).collect();
+        let mut created = Vec::new();
+        let mut privileged = LinkPlan::default();
+        let mut progress = LinkProgress {
+            total: plan.len(),
+            ..Default::default()
+        };
+        for step in plan {
+            let operation = &step.operation;
+            if matches!(step.action, LinkAction::Resolve(_)) {
+                progress.conflicts += 1;
+            }
+            progress.done += 1;
+            if self.filesystem.exists(&operation.target_path).await? {
+                tracing::trace!(target = %operation.target_path, "target in place, not linking");
+                progress.skipped += 1;
+                self.report(progress);
                 continue;
             }
 
-            // Only create if target doesn't exist (conflict was resolved) or no conflict existed
-            if !self.filesystem.exists(&operation.target_path).await? {
-                // Ensure parent directory exists
-                if let Some(parent) = Path::new(&operation.target_path).parent() {
-                    self.filesystem
-                        .create_dir_all(&parent.to_string_lossy())
-                        .await?;
+            match self.link(operation).await {
+                Ok(()) => {
+                    tracing::debug!(
+                        source = %operation.source_path,
+                        target = %operation.target_path,
+                        "created symlink"
+                    );
+                    created.push(operation.clone());
+                    progress.created += 1;
                 }
-
-                self.filesystem
-                    .create_symlink(&operation.source_path, &operation.target_path)
-                    .await?;
+                Err(DotfError::Io(e))
+                    if e.kind() == std::io::ErrorKind::PermissionDenied
+                        && operation.mode.is_symlink()
+                        && self.privileged_helper.is_some() =>
+                {
+                    tracing::info!(target = %operation.target_path, "permission denied, deferring to privileged helper");
+                    privileged.push(&operation.source_path, &operation.target_path);
+                }
+                Err(e) => {
+                    tracing::error!(
+                        source = %operation.source_path,
+                        target = %operation.target_path,
+                        error = %e,
+                        "failed to create symlink"
+                    );
+                    self.record_installed(&created).await;
+                    return Err(e);
+                }
+            }
+            // Links left to the privileged helper are counted once it has run
+            if privileged.is_empty() || progress.done < progress.total {
+                self.report(progress);
             }
         }
 
+        let result = self
+            .apply_privileged(&privileged, &operations, &mut created)
+            .await;
+        self.record_installed(&created).await;
+        result?;
+        if !privileged.is_empty() {
+            progress.created = created.len();
+            self.report(progress);
+        }
+
+        // Links that were already in place may have drifted since the last install
+        let linked = plan
+            .iter()
+            .filter(|step| matches!(step.action, LinkAction::AlreadyLinked))
+            .map(|step| &step.operation);
+        for operation in created.iter().chain(linked) {
+            self.enforce_permissions(operation).await?;
+        }
+
         Ok(backup_entries)
     }
 
+    async fn link(&self, operation: &SymlinkOperation) -> DotfResult<()> {
+        // Ensure parent directory exists
+        if let Some(parent) = Path::new(&operation.target_path).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+
+        let (source, target) = (&operation.source_path, &operation.target_path);
+        match operation.mode {
+            LinkMode::Symlink => self.filesystem.create_symlink(source, target).await,
+            LinkMode::Copy => self.filesystem.copy_file(source, target).await,
+            LinkMode::Hardlink => self.filesystem.hard_link(source, target).await,
+        }
+    }
+
+    /// Give the deployed file of `operation` its `chmod` and `owner`
+    async fn enforce_permissions(&self, operation: &SymlinkOperation) -> DotfResult<bool> {
+        if operation.permissions.is_empty() {
+            return Ok(false);
+        }
+        let changed = permissions::enforce(
+            &self.filesystem,
+            &operation.target_path,
+            &operation.permissions,
+        )
+        .await?;
+        if changed {
+            tracing::debug!(target = %operation.target_path, permissions = ?operation.permissions, "set permissions");
+        }
+        Ok(changed)
+    }
+
+    /// What is in the way of `operation`. Unlike a symlink, a copy or hard link counts as
+    /// deployed only while its content matches the source.
+    async fn find_conflict(
+        &self,
+        operation: &SymlinkOperation,
+    ) -> DotfResult<Option<ConflictInfo>> {
+        if operation.mode.is_symlink() {
+            return self
+                .conflict_resolver
+                .check_conflict(&operation.source_path, &operation.target_path)
+                .await;
+        }
+
+        let status = self.get_single_symlink_status(operation).await?;
+        if matches!(
+            status.status,
+            SymlinkStatus::Missing
+                | SymlinkStatus::Valid
+                | SymlinkStatus::WrongPermissions
+                | SymlinkStatus::Unreadable
+        ) {
+            return Ok(None);
+        }
+        let conflict_type = match status.current_target.as_deref() {
+            Some(target) if is_nix_store_path(target) => ConflictType::NixStore,
+            Some(_) => ConflictType::Symlink,
+            None => ConflictType::File,
+        };
+        Ok(Some(ConflictInfo {
+            target_path: operation.target_path.clone(),
+            source_path: operation.source_path.clone(),
+            existing_is_symlink: status.current_target.is_some(),
+            existing_target: status.current_target,
+            conflict_type,
+        }))
+    }
+
+    /// Hand the links that need elevated permissions to the privileged helper in one call
+    async fn apply_privileged(
+        &self,
+        plan: &LinkPlan,
+        operations: &[SymlinkOperation],
+        created: &mut Vec<SymlinkOperation>,
+    ) -> DotfResult<()> {
+        let Some(helper) = self.privileged_helper.as_ref().filter(|_| !plan.is_empty()) else {
+            return Ok(());
+        };
+
+        let mut failures = Vec::new();
+        for result in helper.apply(plan).await? {
+            match result.error {
+                None => created.extend(
+                    operations
+                        .iter()
+                        .find(|operation| operation.target_path == result.target)
+                        .cloned(),
+                ),
+                Some(error) => {
+                    tracing::error!(target = %result.target, %error, "privileged helper failed");
+                    failures.push(format!("{} ({})", result.target, error))
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(DotfError::Operation(format!(
+                "Failed to create privileged symlink(s): {}",
+                failures.join(", ")
+            )))
+        }
+    }
+
+    /// Remember what was installed so later merges have a common base.
+    ///
+    /// The state file is bookkeeping only; failing to write it never fails an install.
+    pub async fn record_installed(&self, operations: &[SymlinkOperation]) {
+        let links: Vec<(String, String)> = operations
+            .iter()
+            .map(|op| (op.source_path.clone(), op.target_path.clone()))
+            .collect();
+        let _ = self.state_store.record_installed(&links).await;
+    }
+
     pub async fn check_conflicts(
         &self,
         operations: &[SymlinkOperation],
@@ -114,11 +443,7 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
         let mut conflicts = Vec::new();
 
         for operation in operations {
-            if let Some(conflict) = self
-                .conflict_resolver
-                .check_conflict(&operation.source_path, &operation.target_path)
-                .await?
-            {
+            if let Some(conflict) = self.find_conflict(operation).await? {
                 conflicts.push(conflict);
             }
         }
@@ -144,25 +469,76 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
         &self,
         operation: &SymlinkOperation,
     ) -> DotfResult<SymlinkInfo> {
+        let mut info = self.get_link_status(operation).await?;
+        if info.status == SymlinkStatus::Valid {
+            if let Some(error) = self.unreadable(&operation.target_path).await {
+                info.status = SymlinkStatus::Unreadable;
+                info.error = Some(error);
+                return Ok(info);
+            }
+        }
+        if info.status == SymlinkStatus::Valid
+            && !operation.permissions.is_empty()
+            && !permissions::drift(
+                &self.filesystem,
+                &operation.target_path,
+                &operation.permissions,
+            )
+            .await?
+            .is_empty()
+        {
+            info.status = SymlinkStatus::WrongPermissions;
+        }
+        Ok(info)
+    }
+
+    /// Why an app cannot open the deployed file at `target_path`, if it cannot. A target
+    /// that is not there is not unreadable, just missing.
+    pub async fn unreadable(&self, target_path: &str) -> Option<String> {
+        match self.filesystem.check_readable(target_path).await {
+            Ok(()) => None,
+            Err(DotfError::Io(e)) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => Some(e.to_string()),
+        }
+    }
+
+    /// Whether the target is deployed from the source, regardless of its permissions
+    async fn get_link_status(&self, operation: &SymlinkOperation) -> DotfResult<SymlinkInfo> {
         let target_exists = self.filesystem.exists(&operation.target_path).await?;
 
         if !target_exists {
+            // A link into a directory this user cannot search looks missing through it
+            if let Some(error) = self.unreadable(&operation.target_path).await {
+                return Ok(SymlinkInfo {
+                    source_path: operation.source_path.clone(),
+                    target_path: operation.target_path.clone(),
+                    status: SymlinkStatus::Unreadable,
+                    current_target: None,
+                    error: Some(error),
+                });
+            }
             return Ok(SymlinkInfo {
                 source_path: operation.source_path.clone(),
                 target_path: operation.target_path.clone(),
                 status: SymlinkStatus::Missing,
                 current_target: None,
+                error: None,
             });
         }
 
         let is_symlink = self.filesystem.is_symlink(&operation.target_path).await?;
 
+        if !operation.mode.is_symlink() {
+            return self.get_deployed_file_status(operation, is_symlink).await;
+        }
+
         if !is_symlink {
             return Ok(SymlinkInfo {
                 source_path: operation.source_path.clone(),
                 target_path: operation.target_path.clone(),
                 status: SymlinkStatus::Conflict,
                 current_target: None,
+                error: None,
             });
         }
 
@@ -177,6 +553,7 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
                 target_path: operation.target_path.clone(),
                 status: SymlinkStatus::Broken,
                 current_target: Some(current_target_str),
+                error: None,
             });
         }
 
@@ -187,6 +564,7 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
                 target_path: operation.target_path.clone(),
                 status: SymlinkStatus::Valid,
                 current_target: Some(current_target_str),
+                error: None,
             })
         } else {
             Ok(SymlinkInfo {
@@ -194,23 +572,79 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
                 target_path: operation.target_path.clone(),
                 status: SymlinkStatus::InvalidTarget,
                 current_target: Some(current_target_str),
+                error: None,
             })
         }
     }
 
+    /// Status of an existing copy or hard link, which is checked by content hash
+    async fn get_deployed_file_status(
+        &self,
+        operation: &SymlinkOperation,
+        is_symlink: bool,
+    ) -> DotfResult<SymlinkInfo> {
+        let info = |status: SymlinkStatus, current_target: Option<String>| SymlinkInfo {
+            source_path: operation.source_path.clone(),
+            target_path: operation.target_path.clone(),
+            status,
+            current_target,
+            error: None,
+        };
+
+        // A symlink where a copy belongs is replaced like a link to the wrong place
+        if is_symlink {
+            let current_target = self.filesystem.read_link(&operation.target_path).await?;
+            return Ok(info(
+                SymlinkStatus::InvalidTarget,
+                Some(current_target.to_string_lossy().to_string()),
+            ));
+        }
+        if !self.filesystem.exists(&operation.source_path).await? {
+            return Ok(info(SymlinkStatus::Broken, None));
+        }
+        if self.filesystem.is_dir(&operation.target_path).await?
+            || self.filesystem.is_dir(&operation.source_path).await?
+        {
+            return Ok(info(SymlinkStatus::Conflict, None));
+        }
+
+        let source = sha256_hex(&self.filesystem.read(&operation.source_path).await?);
+        let target = sha256_hex(&self.filesystem.read(&operation.target_path).await?);
+        // A copy that differs may hold edits made at the target, so it is not replaced
+        // without going through conflict resolution
+        let status = if source == target {
+            SymlinkStatus::Valid
+        } else {
+            SymlinkStatus::Conflict
+        };
+        Ok(info(status, None))
+    }
+
     pub async fn remove_symlinks(&self, operations: &[SymlinkOperation]) -> DotfResult<()> {
         for operation in operations {
             let status = self.get_single_symlink_status(operation).await?;
 
             match status.status {
+                SymlinkStatus::Broken if !operation.mode.is_symlink() => {
+                    // The source is gone, so this copy is the only one left
+                }
                 SymlinkStatus::Valid
                 | SymlinkStatus::Broken
                 | SymlinkStatus::InvalidTarget
-                | SymlinkStatus::Modified => {
+                | SymlinkStatus::Modified
+                | SymlinkStatus::WrongPermissions
+                | SymlinkStatus::Unreadable => {
+                    tracing::debug!(target = %operation.target_path, status = ?status.status, "removing symlink");
                     self.filesystem.remove_file(&operation.target_path).await?;
                 }
-                SymlinkStatus::Missing => {
-                    // Already doesn't exist, nothing to do
+                SymlinkStatus::Missing | SymlinkStatus::Disabled => {
+                    // Already doesn't exist, or not dotf's to touch on this machine
+                }
+                SymlinkStatus::Conflict if !operation.mode.is_symlink() => {
+                    return Err(DotfError::Operation(format!(
+                        "Cannot remove '{}': it differs from {}",
+                        operation.target_path, operation.source_path
+                    )));
                 }
                 SymlinkStatus::Conflict => {
                     return Err(DotfError::Operation(format!(
@@ -224,70 +658,149 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
         Ok(())
     }
 
+    /// Relink every Missing, Broken, InvalidTarget and Conflict entry and fix the
+    /// permissions of WrongPermissions ones; with `dry_run`
+    /// nothing is touched and the outcomes say what would happen. Conflicts follow the
+    /// conflict policy, or are asked about without one.
     pub async fn repair_symlinks(
         &self,
         operations: &[SymlinkOperation],
-    ) -> DotfResult<Vec<BackupEntry>> {
-        let mut backup_entries = Vec::new();
+        dry_run: bool,
+    ) -> DotfResult<Vec<RepairedLink>> {
+        let mut repaired = Vec::new();
+        let mut relinked = Vec::new();
 
         for operation in operations {
             let status = self.get_single_symlink_status(operation).await?;
 
-            match status.status {
-                SymlinkStatus::Valid | SymlinkStatus::Modified => {
-                    // Nothing to repair for Valid or Modified symlinks
+            let outcome = match status.status {
+                SymlinkStatus::Valid | SymlinkStatus::Modified | SymlinkStatus::Disabled => {
+                    // Nothing to repair for Valid, Modified or Disabled symlinks
                     continue;
                 }
                 SymlinkStatus::Missing => {
-                    // Create the symlink
-                    if let Some(parent) = Path::new(&operation.target_path).parent() {
-                        self.filesystem
-                            .create_dir_all(&parent.to_string_lossy())
-                            .await?;
+                    if !dry_run {
+                        self.relink(operation).await?;
                     }
-                    self.filesystem
-                        .create_symlink(&operation.source_path, &operation.target_path)
-                        .await?;
+                    RepairOutcome::Created
                 }
-                SymlinkStatus::Broken | SymlinkStatus::InvalidTarget => {
-                    // Remove and recreate
-                    self.filesystem.remove_file(&operation.target_path).await?;
-                    self.filesystem
-                        .create_symlink(&operation.source_path, &operation.target_path)
-                        .await?;
+                SymlinkStatus::WrongPermissions => {
+                    if !dry_run {
+                        self.enforce_permissions(operation).await?;
+                    }
+                    RepairOutcome::PermissionsFixed
                 }
-                SymlinkStatus::Conflict => {
-                    // Handle as conflict
-                    if let Some(conflict) = self
-                        .conflict_resolver
-                        .check_conflict(&operation.source_path, &operation.target_path)
-                        .await?
+                SymlinkStatus::Unreadable => {
+                    // Relinking would not help; the source itself needs other permissions
+                    RepairOutcome::Skipped(format!(
+                        "cannot be read: {}",
+                        status.error.unwrap_or_default()
+                    ))
+                }
+                SymlinkStatus::Broken if !operation.mode.is_symlink() => {
+                    RepairOutcome::Skipped("the source is missing".to_string())
+                }
+                SymlinkStatus::Broken | SymlinkStatus::InvalidTarget => {
+                    if status
+                        .current_target
+                        .as_deref()
+                        .is_some_and(is_nix_store_path)
                     {
-                        if let Some(backup_entry) = self
-                            .conflict_resolver
-                            .resolve_conflict_interactive(&conflict)
-                            .await?
-                        {
-                            backup_entries.push(backup_entry);
-                        }
-
-                        // Create symlink if target was cleared
-                        if !self.filesystem.exists(&operation.target_path).await? {
-                            if let Some(parent) = Path::new(&operation.target_path).parent() {
-                                self.filesystem
-                                    .create_dir_all(&parent.to_string_lossy())
-                                    .await?;
-                            }
-                            self.filesystem
-                                .create_symlink(&operation.source_path, &operation.target_path)
-                                .await?;
+                        RepairOutcome::Skipped("points into the Nix store".to_string())
+                    } else {
+                        if !dry_run {
+                            self.filesystem.remove_file(&operation.target_path).await?;
+                            self.relink(operation).await?;
                         }
+                        RepairOutcome::Recreated
                     }
                 }
+                SymlinkStatus::Conflict => self.repair_conflict(operation, dry_run).await?,
+            };
+
+            if !dry_run
+                && !matches!(
+                    outcome,
+                    RepairOutcome::Skipped(_) | RepairOutcome::Undecided
+                )
+            {
+                relinked.push(operation.clone());
             }
+            repaired.push(RepairedLink {
+                source_path: operation.source_path.clone(),
+                target_path: operation.target_path.clone(),
+                status: status.status,
+                outcome,
+            });
         }
 
-        Ok(backup_entries)
+        self.record_installed(&relinked).await;
+
+        Ok(repaired)
+    }
+
+    async fn repair_conflict(
+        &self,
+        operation: &SymlinkOperation,
+        dry_run: bool,
+    ) -> DotfResult<RepairOutcome> {
+        let Some(conflict) = self.find_conflict(operation).await? else {
+            return Ok(RepairOutcome::Skipped("no longer in the way".to_string()));
+        };
+        if conflict.conflict_type == ConflictType::NixStore {
+            return Ok(RepairOutcome::Skipped(
+                "points into the Nix store".to_string(),
+            ));
+        }
+
+        if dry_run {
+            return Ok(match &self.conflict_policy {
+                Some(ConflictResolution::Backup) => RepairOutcome::BackedUp,
+                Some(ConflictResolution::Overwrite) => RepairOutcome::Removed,
+                Some(ConflictResolution::Skip) => {
+                    RepairOutcome::Skipped("kept the existing file".to_string())
+                }
+                Some(ConflictResolution::Abort) => {
+                    RepairOutcome::Skipped("would abort the repair".to_string())
+                }
+                None => RepairOutcome::Undecided,
+            });
+        }
+
+        let backup = match &self.conflict_policy {
+            Some(policy) => {
+                self.conflict_resolver
+                    .resolve_conflict(&conflict, policy.clone())
+                    .await?
+            }
+            None => {
+                self.conflict_resolver
+                    .resolve_conflict_interactive(&conflict)
+                    .await?
+            }
+        };
+
+        // The file stays when the conflict was skipped
+        if self.filesystem.exists(&operation.target_path).await? {
+            return Ok(RepairOutcome::Skipped("kept the existing file".to_string()));
+        }
+        self.relink(operation).await?;
+        Ok(if backup.is_some() {
+            RepairOutcome::BackedUp
+        } else {
+            RepairOutcome::Removed
+        })
+    }
+
+    async fn relink(&self, operation: &SymlinkOperation) -> DotfResult<()> {
+        tracing::debug!(
+            source = %operation.source_path,
+            target = %operation.target_path,
+            "relinking"
+        );
+        self.link(operation).await?;
+        self.enforce_permissions(operation).await?;
+        Ok(())
     }
 
     pub async fn validate_sources(
@@ -312,9 +825,14 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
         repo_path: &str,
     ) -> DotfResult<Vec<SymlinkInfo>> {
         let mut statuses = Vec::new();
+        // Asked once, and only when there is a valid link to check
+        let mut modified: Option<HashSet<String>> = None;
 
         for operation in operations {
+            let mut timer = timings::phase("symlink checks");
+            timer.items(1);
             let mut status = self.get_single_symlink_status(operation).await?;
+            drop(timer);
 
             // If symlink is valid, check for local changes
             if status.status == SymlinkStatus::Valid {
@@ -329,19 +847,22 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
                     &operation.source_path
                 };
 
-                match repository
-                    .is_file_modified(repo_path, relative_source)
-                    .await
+                if modified.is_none() {
+                    // If we can't check git status, links keep their original status
+                    modified = Some(
+                        repository
+                            .get_modified_files(repo_path)
+                            .await
+                            .unwrap_or_default(),
+                    );
+                }
+                let modified = modified.as_ref().expect("modified files are loaded");
+                // A linked directory is modified when anything inside it is
+                let directory = format!("{}/", relative_source.trim_end_matches('/'));
+                if modified.contains(relative_source)
+                    || modified.iter().any(|path| path.starts_with(&directory))
                 {
-                    Ok(true) => {
-                        status.status = SymlinkStatus::Modified;
-                    }
-                    Ok(false) => {
-                        // Keep as Valid
-                    }
-                    Err(_) => {
-                        // If we can't check git status, keep original status
-                    }
+                    status.status = SymlinkStatus::Modified;
                 }
             }
 
@@ -355,7 +876,11 @@ impl<F: FileSystem + Clone, P: Prompt> SymlinkManager<F, P> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::traits::{filesystem::tests::MockFileSystem, prompt::tests::MockPrompt};
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, privileged_helper::tests::MockPrivilegedHelper,
+        progress::tests::RecordingProgress, prompt::tests::MockPrompt,
+        repository::tests::MockRepository,
+    };
 
     #[tokio::test]
     async fn test_create_symlinks_no_conflicts() {
@@ -368,6 +893,8 @@ mod tests {
         let operations = vec![SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         }];
 
         let backups = manager.create_symlinks(&operations, true).await.unwrap();
@@ -380,6 +907,281 @@ mod tests {
         assert_eq!(target.to_string_lossy(), "/source/.vimrc");
     }
 
+    #[tokio::test]
+    async fn test_copy_mode_checked_by_content() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/settings.json", "{}");
+        fs.add_file("/source/hosts", "127.0.0.1 localhost");
+
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new())
+            .with_conflict_policy(Some(ConflictResolution::Backup));
+        let copy = SymlinkOperation {
+            source_path: "/source/settings.json".to_string(),
+            target_path: "/home/user/settings.json".to_string(),
+            mode: LinkMode::Copy,
+            permissions: Default::default(),
+        };
+        let hardlink = SymlinkOperation {
+            source_path: "/source/hosts".to_string(),
+            target_path: "/home/user/hosts".to_string(),
+            mode: LinkMode::Hardlink,
+            permissions: Default::default(),
+        };
+        manager
+            .create_symlinks(&[copy.clone(), hardlink.clone()], false)
+            .await
+            .unwrap();
+        assert!(!fs.is_symlink("/home/user/settings.json").await.unwrap());
+        for operation in [&copy, &hardlink] {
+            let status = manager.get_single_symlink_status(operation).await.unwrap();
+            assert_eq!(status.status, SymlinkStatus::Valid);
+        }
+
+        // The source changed since it was copied
+        fs.add_file("/source/settings.json", "{\"theme\": \"dark\"}");
+        let status = manager.get_single_symlink_status(&copy).await.unwrap();
+        assert_eq!(status.status, SymlinkStatus::Conflict);
+        let plan = manager
+            .plan_symlinks(std::slice::from_ref(&copy))
+            .await
+            .unwrap();
+        assert!(matches!(plan[0].action, LinkAction::Resolve(_)));
+        let backups = manager
+            .create_symlinks(std::slice::from_ref(&copy), false)
+            .await
+            .unwrap();
+        assert_eq!(backups.len(), 1);
+        assert_eq!(
+            fs.read_to_string("/home/user/settings.json").await.unwrap(),
+            "{\"theme\": \"dark\"}"
+        );
+
+        // A symlink where a copy belongs is replaced
+        fs.remove_file("/home/user/settings.json").await.unwrap();
+        fs.create_symlink("/source/settings.json", "/home/user/settings.json")
+            .await
+            .unwrap();
+        let status = manager.get_single_symlink_status(&copy).await.unwrap();
+        assert_eq!(status.status, SymlinkStatus::InvalidTarget);
+    }
+
+    #[tokio::test]
+    async fn test_permissions_enforced_and_repaired() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/etc/passwd", "alice:x:1000:1000::/home/alice:/bin/zsh\n");
+        fs.add_file("/source/ssh/config", "Host *");
+        let operation = SymlinkOperation {
+            source_path: "/source/ssh/config".to_string(),
+            target_path: "/home/alice/.ssh/config".to_string(),
+            mode: LinkMode::Copy,
+            permissions: Permissions {
+                chmod: Some(0o600),
+                owner: Some("alice".to_string()),
+            },
+        };
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new());
+        manager
+            .create_symlinks(std::slice::from_ref(&operation), false)
+            .await
+            .unwrap();
+        assert_eq!(fs.mode(&operation.target_path).await.unwrap(), Some(0o600));
+        let status = manager.get_single_symlink_status(&operation).await.unwrap();
+        assert_eq!(status.status, SymlinkStatus::Valid);
+
+        // Drift is reported, fixed by repair, and fixed by installing again
+        fs.set_mode(&operation.target_path, 0o644).await.unwrap();
+        let status = manager.get_single_symlink_status(&operation).await.unwrap();
+        assert_eq!(status.status, SymlinkStatus::WrongPermissions);
+        let repaired = manager
+            .repair_symlinks(std::slice::from_ref(&operation), false)
+            .await
+            .unwrap();
+        assert_eq!(repaired[0].outcome, RepairOutcome::PermissionsFixed);
+        assert_eq!(fs.mode(&operation.target_path).await.unwrap(), Some(0o600));
+
+        fs.set_mode(&operation.target_path, 0o666).await.unwrap();
+        let plan = manager
+            .plan_symlinks(std::slice::from_ref(&operation))
+            .await
+            .unwrap();
+        assert!(matches!(plan[0].action, LinkAction::AlreadyLinked));
+        manager.execute_plan(&plan, false).await.unwrap();
+        assert_eq!(fs.mode(&operation.target_path).await.unwrap(), Some(0o600));
+    }
+
+    #[tokio::test]
+    async fn test_create_symlinks_with_conflict_policy() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/.vimrc", "vim config");
+        fs.add_file("/home/user/.vimrc", "local config");
+        let operations = vec![SymlinkOperation {
+            source_path: "/source/.vimrc".to_string(),
+            target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
+        }];
+
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new());
+        let result = manager.create_symlinks(&operations, false).await;
+        assert!(matches!(result.unwrap_err(), DotfError::Operation(_)));
+
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new())
+            .with_conflict_policy(Some(ConflictResolution::Backup));
+        let backups = manager.create_symlinks(&operations, false).await.unwrap();
+        assert_eq!(backups.len(), 1);
+        assert!(fs.is_symlink("/home/user/.vimrc").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_plan_symlinks_changes_nothing() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/.vimrc", "vim config");
+        fs.add_file("/source/.zshrc", "zsh config");
+        fs.add_file("/source/.gitconfig", "git config");
+        fs.add_file("/home/user/.zshrc", "local config");
+        fs.create_symlink("/source/.gitconfig", "/home/user/.gitconfig")
+            .await
+            .unwrap();
+        let operations: Vec<SymlinkOperation> = [".vimrc", ".zshrc", ".gitconfig"]
+            .into_iter()
+            .map(|name| SymlinkOperation {
+                source_path: format!("/source/{}", name),
+                target_path: format!("/home/user/{}", name),
+                mode: Default::default(),
+                permissions: Default::default(),
+            })
+            .collect();
+
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new())
+            .with_conflict_policy(Some(ConflictResolution::Overwrite));
+        let plan = manager.plan_symlinks(&operations).await.unwrap();
+
+        assert!(matches!(plan[0].action, LinkAction::Create));
+        assert!(matches!(
+            &plan[1].action,
+            LinkAction::Resolve(conflict) if conflict.conflict_type == ConflictType::File
+        ));
+        assert!(matches!(plan[2].action, LinkAction::AlreadyLinked));
+        assert!(!fs.exists("/home/user/.vimrc").await.unwrap());
+        assert!(!fs.is_symlink("/home/user/.zshrc").await.unwrap());
+
+        manager.execute_plan(&plan, false).await.unwrap();
+        assert!(fs.is_symlink("/home/user/.vimrc").await.unwrap());
+        assert_eq!(
+            fs.read_link("/home/user/.zshrc")
+                .await
+                .unwrap()
+                .to_string_lossy(),
+            "/source/.zshrc"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_plan_reports_link_progress() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/.vimrc", "vim config");
+        fs.add_file("/source/.zshrc", "zsh config");
+        fs.add_file("/source/.gitconfig", "git config");
+        fs.add_file("/home/user/.zshrc", "local config");
+        fs.create_symlink("/source/.gitconfig", "/home/user/.gitconfig")
+            .await
+            .unwrap();
+        let operations: Vec<SymlinkOperation> = [".vimrc", ".zshrc", ".gitconfig"]
+            .into_iter()
+            .map(|name| SymlinkOperation {
+                source_path: format!("/source/{}", name),
+                target_path: format!("/home/user/{}", name),
+                mode: Default::default(),
+                permissions: Default::default(),
+            })
+            .collect();
+
+        let progress = RecordingProgress::new();
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new())
+            .with_conflict_policy(Some(ConflictResolution::Skip))
+            .with_progress(Arc::new(progress.clone()));
+        manager.create_symlinks(&operations, false).await.unwrap();
+
+        let links: Vec<LinkProgress> = progress
+            .get_events()
+            .into_iter()
+            .filter_map(|event| match event {
+                ProgressEvent::Links(links) => Some(links),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            links.iter().map(|links| links.done).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(
+            links.last(),
+            Some(&LinkProgress {
+                done: 3,
+                total: 3,
+                created: 1,
+                skipped: 2,
+                conflicts: 1,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_create_symlinks_batches_privileged_links() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/.vimrc", "vim config");
+        fs.add_file("/source/hosts", "127.0.0.1 dev");
+        fs.add_file("/source/resolv.conf", "nameserver 1.1.1.1");
+        fs.add_read_only("/etc");
+
+        let helper = MockPrivilegedHelper::new();
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new())
+            .with_privileged_helper(Box::new(helper.clone()));
+        let operations: Vec<SymlinkOperation> = [
+            ("/source/.vimrc", "/home/user/.vimrc"),
+            ("/source/hosts", "/etc/hosts"),
+            ("/source/resolv.conf", "/etc/resolv.conf"),
+        ]
+        .into_iter()
+        .map(|(source, target)| SymlinkOperation {
+            source_path: source.to_string(),
+            target_path: target.to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
+        })
+        .collect();
+
+        manager.create_symlinks(&operations, true).await.unwrap();
+
+        assert!(fs.is_symlink("/home/user/.vimrc").await.unwrap());
+        let plans = helper.get_plans();
+        assert_eq!(plans.len(), 1);
+        let targets: Vec<&str> = plans[0]
+            .links
+            .iter()
+            .map(|link| link.target.as_str())
+            .collect();
+        assert_eq!(targets, vec!["/etc/hosts", "/etc/resolv.conf"]);
+    }
+
+    #[tokio::test]
+    async fn test_create_symlinks_permission_denied_without_helper() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/hosts", "127.0.0.1 dev");
+        fs.add_read_only("/etc");
+
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new());
+        let operations = vec![SymlinkOperation {
+            source_path: "/source/hosts".to_string(),
+            target_path: "/etc/hosts".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
+        }];
+
+        let result = manager.create_symlinks(&operations, true).await;
+        assert!(matches!(result.unwrap_err(), DotfError::Io(_)));
+    }
+
     #[tokio::test]
     async fn test_get_symlink_status_missing() {
         let fs = MockFileSystem::new();
@@ -389,6 +1191,8 @@ mod tests {
         let operation = SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         };
 
         let status = manager.get_single_symlink_status(&operation).await.unwrap();
@@ -409,6 +1213,8 @@ mod tests {
         let operation = SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         };
 
         let status = manager.get_single_symlink_status(&operation).await.unwrap();
@@ -416,6 +1222,74 @@ mod tests {
         assert_eq!(status.current_target, Some("/source/.vimrc".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_get_symlink_status_unreadable() {
+        let fs = MockFileSystem::new();
+        fs.add_file("/source/.vimrc", "vim config");
+        fs.create_symlink("/source/.vimrc", "/home/user/.vimrc")
+            .await
+            .unwrap();
+        // e.g. the repository was cloned with sudo and is root's with mode 700
+        fs.add_unreadable("/source");
+
+        let manager = SymlinkManager::new(fs, MockPrompt::new());
+        let operation = SymlinkOperation {
+            source_path: "/source/.vimrc".to_string(),
+            target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
+        };
+
+        let status = manager.get_single_symlink_status(&operation).await.unwrap();
+        assert_eq!(status.status, SymlinkStatus::Unreadable);
+        assert!(status.error.unwrap().contains("Permission denied"));
+        assert!(manager.unreadable("/home/user/.missing").await.is_none());
+
+        let repaired = manager
+            .repair_symlinks(std::slice::from_ref(&operation), false)
+            .await
+            .unwrap();
+        assert!(matches!(repaired[0].outcome, RepairOutcome::Skipped(_)));
+    }
+
+    #[tokio::test]
+    async fn test_get_symlink_status_with_changes() {
+        let fs = MockFileSystem::new();
+        let mut operations = Vec::new();
+        for (source, target) in [
+            ("/repo/vim/.vimrc", "/home/user/.vimrc"),
+            ("/repo/git/.gitconfig", "/home/user/.gitconfig"),
+            ("/repo/nvim", "/home/user/.config/nvim"),
+        ] {
+            fs.add_file(source, "config");
+            fs.create_symlink(source, target).await.unwrap();
+            operations.push(SymlinkOperation {
+                source_path: source.to_string(),
+                target_path: target.to_string(),
+                mode: Default::default(),
+                permissions: Default::default(),
+            });
+        }
+        let repository = MockRepository::new();
+        repository.set_modified_files(&["vim/.vimrc", "nvim/init.lua", "nvim-extra"]);
+
+        let manager = SymlinkManager::new(fs, MockPrompt::new());
+        let statuses = manager
+            .get_symlink_status_with_changes(&operations, &repository, "/repo")
+            .await
+            .unwrap();
+        let statuses: Vec<SymlinkStatus> = statuses.into_iter().map(|s| s.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                SymlinkStatus::Modified,
+                SymlinkStatus::Valid,
+                SymlinkStatus::Modified
+            ]
+        );
+        assert_eq!(repository.get_modified_files_calls(), 1);
+    }
+
     #[tokio::test]
     async fn test_get_symlink_status_broken() {
         let fs = MockFileSystem::new();
@@ -430,6 +1304,8 @@ mod tests {
         let operation = SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         };
 
         let status = manager.get_single_symlink_status(&operation).await.unwrap();
@@ -447,6 +1323,8 @@ mod tests {
         let operation = SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         };
 
         let status = manager.get_single_symlink_status(&operation).await.unwrap();
@@ -468,6 +1346,8 @@ mod tests {
         let operation = SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         };
 
         let status = manager.get_single_symlink_status(&operation).await.unwrap();
@@ -489,6 +1369,8 @@ mod tests {
         let operations = vec![SymlinkOperation {
             source_path: "/source/.vimrc".to_string(),
             target_path: "/home/user/.vimrc".to_string(),
+            mode: Default::default(),
+            permissions: Default::default(),
         }];
 
         assert!(fs.exists("/home/user/.vimrc").await.unwrap());
@@ -498,6 +1380,58 @@ mod tests {
         assert!(!fs.exists("/home/user/.vimrc").await.unwrap());
     }
 
+    #[tokio::test]
+    async fn test_repair_symlinks() {
+        let fs = MockFileSystem::new();
+        for name in ["vimrc", "zshrc", "gitconfig", "tmux.conf"] {
+            fs.add_file(&format!("/source/.{}", name), "");
+        }
+        // .vimrc is missing, .zshrc links elsewhere, .gitconfig is a file, .tmux.conf is valid
+        fs.add_file("/tmp/other", "");
+        fs.create_symlink("/tmp/other", "/home/user/.zshrc")
+            .await
+            .unwrap();
+        fs.add_file("/home/user/.gitconfig", "local config");
+        fs.create_symlink("/source/.tmux.conf", "/home/user/.tmux.conf")
+            .await
+            .unwrap();
+        let operations: Vec<SymlinkOperation> = ["vimrc", "zshrc", "gitconfig", "tmux.conf"]
+            .iter()
+            .map(|name| SymlinkOperation {
+                source_path: format!("/source/.{}", name),
+                target_path: format!("/home/user/.{}", name),
+                mode: Default::default(),
+                permissions: Default::default(),
+            })
+            .collect();
+
+        let manager = SymlinkManager::new(fs.clone(), MockPrompt::new())
+            .with_conflict_policy(Some(ConflictResolution::Backup));
+        let outcomes = |repaired: Vec<RepairedLink>| -> Vec<(String, RepairOutcome)> {
+            repaired
+                .into_iter()
+                .map(|link| (link.target_path, link.outcome))
+                .collect()
+        };
+        let expected = vec![
+            ("/home/user/.vimrc".to_string(), RepairOutcome::Created),
+            ("/home/user/.zshrc".to_string(), RepairOutcome::Recreated),
+            ("/home/user/.gitconfig".to_string(), RepairOutcome::BackedUp),
+        ];
+
+        let planned = manager.repair_symlinks(&operations, true).await.unwrap();
+        assert_eq!(outcomes(planned), expected);
+        assert!(!fs.exists("/home/user/.vimrc").await.unwrap());
+        assert!(!fs.is_symlink("/home/user/.gitconfig").await.unwrap());
+
+        let repaired = manager.repair_symlinks(&operations, false).await.unwrap();
+        assert_eq!(outcomes(repaired), expected);
+        for operation in &operations {
+            let status = manager.get_single_symlink_status(operation).await.unwrap();
+            assert_eq!(status.status, SymlinkStatus::Valid);
+        }
+    }
+
     #[tokio::test]
     async fn test_validate_sources() {
         let fs = MockFileSystem::new();
@@ -511,10 +1445,14 @@ mod tests {
             SymlinkOperation {
                 source_path: "/source/.vimrc".to_string(),
                 target_path: "/home/user/.vimrc".to_string(),
+                mode: Default::default(),
+                permissions: Default::default(),
             },
             SymlinkOperation {
                 source_path: "/source/.bashrc".to_string(),
                 target_path: "/home/user/.bashrc".to_string(),
+                mode: Default::default(),
+                permissions: Default::default(),
             },
         ];
 
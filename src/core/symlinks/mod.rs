@@ -1,7 +1,24 @@
 pub mod backup;
 pub mod conflict;
+pub mod export_ignore;
 pub mod manager;
+pub mod mode;
+pub mod permissions;
+pub mod pin;
+pub mod priority;
+pub mod privileged;
 
-pub use backup::{BackupEntry, BackupFileType, BackupManager, BackupManifest};
-pub use conflict::{ConflictInfo, ConflictResolution, ConflictResolver};
-pub use manager::{SymlinkInfo, SymlinkManager, SymlinkOperation, SymlinkStatus};
+pub use backup::{
+    BackupEntry, BackupError, BackupFileType, BackupManager, BackupManifest, OwnershipWarning,
+};
+pub use conflict::{ConflictInfo, ConflictResolution, ConflictResolver, ConflictType};
+pub use export_ignore::partition_export_ignored;
+pub use manager::{
+    LinkAction, LinkStep, RepairOutcome, RepairedLink, SymlinkInfo, SymlinkManager,
+    SymlinkOperation, SymlinkStatus,
+};
+pub use mode::LinkMode;
+pub use permissions::Permissions;
+pub use pin::{check_pins, PinMismatch};
+pub use priority::{resolve_priorities, EntryOperation, OverriddenLink};
+pub use privileged::{apply_plan, LinkPlan, PlannedLink, PlannedLinkResult, SudoHelper};
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// How an entry is deployed at its target (`mode` in dotf.toml)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkMode {
+    /// A symbolic link to the source in the repository
+    #[default]
+    Symlink,
+    /// A copy of the source, for apps that replace symlinks; it is checked by content
+    /// hash, and a copy that no longer matches is handled like a conflicting file
+    Copy,
+    /// A hard link to the source, which must be on the same filesystem
+    Hardlink,
+}
+
+impl LinkMode {
+    pub fn is_symlink(&self) -> bool {
+        *self == LinkMode::Symlink
+    }
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::{FileOwner, FileSystem};
+
+const PASSWD_PATH: &str = "/etc/passwd";
+const GROUP_PATH: &str = "/etc/group";
+
+/// Mode and owner a deployed target must have (`chmod` and `owner` in dotf.toml). For a
+/// symlink they apply to the file it points to, since a link has no permissions of its own.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Permissions {
+    pub chmod: Option<u32>,
+    /// `user`, `user:group` or numeric ids; the user's primary group when no group is given
+    pub owner: Option<String>,
+}
+
+impl Permissions {
+    pub fn is_empty(&self) -> bool {
+        self.chmod.is_none() && self.owner.is_none()
+    }
+}
+
+/// Parse an octal mode such as `600`, `0600` or `0o600`
+pub fn parse_chmod(value: &str) -> Result<u32, String> {
+    let digits = value.strip_prefix("0o").unwrap_or(value);
+    match u32::from_str_radix(digits, 8) {
+        Ok(mode) if !digits.is_empty() && mode <= 0o7777 => Ok(mode),
+        _ => Err(format!(
+            "invalid chmod '{}': use an octal mode such as \"600\"",
+            value
+        )),
+    }
+}
+
+pub fn format_chmod(mode: u32) -> String {
+    format!("{:03o}", mode)
+}
+
+/// How `path` differs from `permissions`, one description per difference
+pub async fn drift<F: FileSystem>(
+    filesystem: &F,
+    path: &str,
+    permissions: &Permissions,
+) -> DotfResult<Vec<String>> {
+    let mut differences = Vec::new();
+    if let Some(expected) = permissions.chmod {
+        if let Some(mode) = filesystem.mode(path).await? {
+            if mode != expected {
+                differences.push(format!(
+                    "mode {}, expected {}",
+                    format_chmod(mode),
+                    format_chmod(expected)
+                ));
+            }
+        }
+    }
+    if let Some(spec) = &permissions.owner {
+        let expected = resolve_owner(filesystem, spec).await?;
+        if let Some(owner) = filesystem.owner(path).await? {
+            if owner != expected {
+                differences.push(format!(
+                    "owner {}:{}, expected {}",
+                    owner.uid, owner.gid, spec
+                ));
+            }
+        }
+    }
+    Ok(differences)
+}
+
+/// Give `path` the mode and owner from `permissions`; returns whether anything changed
+pub async fn enforce<F: FileSystem>(
+    filesystem: &F,
+    path: &str,
+    permissions: &Permissions,
+) -> DotfResult<bool> {
+    let mut changed = false;
+    if let Some(spec) = &permissions.owner {
+        let expected = resolve_owner(filesystem, spec).await?;
+        if filesystem
+            .owner(path)
+            .await?
+            .is_some_and(|owner| owner != expected)
+        {
+            filesystem
+                .set_owner(path, expected)
+                .await
+                .map_err(|e| match e {
+                    DotfError::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+                        DotfError::Operation(format!(
+                            "Cannot make {} owned by {}: changing owners needs root",
+                            path, spec
+                        ))
+                    }
+                    e => e,
+                })?;
+            changed = true;
+        }
+    }
+    // After the owner, which may reset setuid and setgid bits
+    if let Some(expected) = permissions.chmod {
+        if filesystem
+            .mode(path)
+            .await?
+            .is_some_and(|mode| mode != expected)
+        {
+            filesystem.set_mode(path, expected).await?;
+            changed = true;
+        }
+    }
+    Ok(changed)
+}
+
+/// Look up `user[:group]`, each a name or a numeric id
+pub async fn resolve_owner<F: FileSystem>(filesystem: &F, spec: &str) -> DotfResult<FileOwner> {
+    let (user, group) = match spec.split_once(':') {
+        Some((user, group)) => (user, Some(group)),
+        None => (spec, None),
+    };
+    let unknown = |kind: &str, name: &str| {
+        DotfError::Config(format!("Unknown {} '{}' in owner '{}'", kind, name, spec))
+    };
+
+    let passwd = read_database(filesystem, PASSWD_PATH).await?;
+    let (uid, primary_gid) = match lookup(&passwd, user) {
+        Some(fields) => (fields.0, fields.1),
+        // A numeric user that has no entry still works with an explicit group
+        None => match user.parse::<u32>() {
+            Ok(uid) if group.is_some() => (uid, None),
+            _ => return Err(unknown("user", user)),
+        },
+    };
+    let gid = match group {
+        Some(group) => {
+            let groups = read_database(filesystem, GROUP_PATH).await?;
+            match lookup(&groups, group) {
+                Some((gid, _)) => gid,
+                None => group.parse().map_err(|_| unknown("group", group))?,
+            }
+        }
+        None => primary_gid.ok_or_else(|| unknown("user", user))?,
+    };
+    Ok(FileOwner { uid, gid })
+}
+
+async fn read_database<F: FileSystem>(filesystem: &F, path: &str) -> DotfResult<String> {
+    if filesystem.exists(path).await? {
+        filesystem.read_to_string(path).await
+    } else {
+        Ok(String::new())
+    }
+}
+
+/// Id, and for passwd the primary group, of the `name:x:id:gid:...` line matching `name`
+/// or `id`
+fn lookup(database: &str, name: &str) -> Option<(u32, Option<u32>)> {
+    database.lines().find_map(|line| {
+        let mut fields = line.split(':');
+        let entry = fields.next()?;
+        let id: u32 = fields.nth(1)?.parse().ok()?;
+        let group = fields.next().and_then(|gid| gid.parse().ok());
+        (entry == name || id.to_string() == name).then_some((id, group))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_drift_and_enforce() {
+        let fs = MockFileSystem::new();
+        fs.add_file(
+            PASSWD_PATH,
+            "root:x:0:0:root:/root:/bin/sh\nalice:x:1000:1000::/home/alice:/bin/zsh\n",
+        );
+        fs.add_file(GROUP_PATH, "root:x:0:\nwheel:x:10:alice\n");
+        fs.add_file("/home/alice/.ssh/config", "Host *");
+
+        assert_eq!(parse_chmod("0600"), Ok(0o600));
+        assert_eq!(parse_chmod("0o755"), Ok(0o755));
+        assert!(parse_chmod("999").is_err());
+        assert_eq!(
+            resolve_owner(&fs, "alice:wheel").await.unwrap(),
+            FileOwner { uid: 1000, gid: 10 }
+        );
+        assert_eq!(
+            resolve_owner(&fs, "0").await.unwrap(),
+            FileOwner { uid: 0, gid: 0 }
+        );
+        assert!(resolve_owner(&fs, "bob").await.is_err());
+
+        let path = "/home/alice/.ssh/config";
+        let permissions = Permissions {
+            chmod: Some(0o600),
+            owner: Some("alice".to_string()),
+        };
+        assert_eq!(
+            drift(&fs, path, &permissions).await.unwrap(),
+            vec!["mode 644, expected 600"]
+        );
+        assert!(enforce(&fs, path, &permissions).await.unwrap());
+        assert!(drift(&fs, path, &permissions).await.unwrap().is_empty());
+        assert!(!enforce(&fs, path, &permissions).await.unwrap());
+
+        fs.deny_chown();
+        let root = Permissions {
+            chmod: None,
+            owner: Some("root".to_string()),
+        };
+        assert_eq!(drift(&fs, path, &root).await.unwrap().len(), 1);
+        assert!(enforce(&fs, path, &root).await.is_err());
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::dotf_config::SymlinkMap;
+use crate::core::tools::asset::sha256_hex;
+use crate::error::DotfResult;
+use crate::traits::filesystem::FileSystem;
+
+/// A pinned source whose content does not hash to its `pin_sha256`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PinMismatch {
+    /// Source path as written in dotf.toml
+    pub source: String,
+    pub expected: String,
+    /// Hash of the current content, `None` when the source is a directory
+    pub actual: Option<String>,
+}
+
+/// Compare every pinned source in `symlinks` with its pin. Sources that do not exist are
+/// left to the missing-source checks.
+pub async fn check_pins<F: FileSystem>(
+    filesystem: &F,
+    symlinks: &SymlinkMap,
+    repo_path: &str,
+) -> DotfResult<Vec<PinMismatch>> {
+    let mut sources: Vec<&String> = symlinks
+        .keys()
+        .filter(|source| symlinks.pinned_sha256(source).is_some())
+        .collect();
+    sources.sort();
+
+    let mut mismatches = Vec::new();
+    for source in sources {
+        let expected = symlinks.pinned_sha256(source).unwrap_or_default();
+        let path = if source.starts_with('/') {
+            source.clone()
+        } else {
+            format!("{}/{}", repo_path, source)
+        };
+        if !filesystem.exists(&path).await? {
+            continue;
+        }
+
+        let actual = if filesystem.is_dir(&path).await? {
+            None
+        } else {
+            let content = filesystem.read_to_string(&path).await?;
+            Some(sha256_hex(content.as_bytes()))
+        };
+        if actual
+            .as_deref()
+            .is_some_and(|actual| actual.eq_ignore_ascii_case(expected))
+        {
+            continue;
+        }
+
+        mismatches.push(PinMismatch {
+            source: source.clone(),
+            expected: expected.to_string(),
+            actual,
+        });
+    }
+
+    Ok(mismatches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_check_pins() {
+        let filesystem = MockFileSystem::new();
+        filesystem.add_file("/repo/gnupg/gpg.conf", "keyid-format long\n");
+        filesystem.add_file("/repo/gnupg/gpg-agent.conf", "pinentry-program evil\n");
+        filesystem.add_file("/repo/zsh/.zshrc", "export A=1\n");
+
+        let mut symlinks: SymlinkMap = [
+            ("gnupg/gpg.conf", "~/.gnupg/gpg.conf"),
+            ("gnupg/gpg-agent.conf", "~/.gnupg/gpg-agent.conf"),
+            ("zsh/.zshrc", "~/.zshrc"),
+        ]
+        .into_iter()
+        .map(|(source, target)| (source.to_string(), target.to_string()))
+        .collect::<std::collections::HashMap<_, _>>()
+        .into();
+        symlinks.set_pinned_sha256(
+            "gnupg/gpg.conf",
+            Some(sha256_hex("keyid-format long\n".as_bytes())),
+        );
+        symlinks.set_pinned_sha256(
+            "gnupg/gpg-agent.conf",
+            Some(sha256_hex(
+                "pinentry-program /usr/bin/pinentry\n".as_bytes(),
+            )),
+        );
+
+        let mismatches = check_pins(&filesystem, &symlinks, "/repo").await.unwrap();
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(mismatches[0].source, "gnupg/gpg-agent.conf");
+        assert_eq!(
+            mismatches[0].actual.as_deref(),
+            Some(sha256_hex("pinentry-program evil\n".as_bytes()).as_str())
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use super::manager::SymlinkOperation;
+
+/// A link to create together with the dotf.toml entry it came from
+#[derive(Debug, Clone)]
+pub struct EntryOperation {
+    /// Source key of the entry in dotf.toml
+    pub entry: String,
+    pub priority: i32,
+    /// Expanded from a directory entry rather than linking the entry itself
+    pub from_directory: bool,
+    pub operation: SymlinkOperation,
+}
+
+/// A link that was not created because another entry claims the same target
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverriddenLink {
+    pub target_path: String,
+    pub source_path: String,
+    /// Source path of the link created instead
+    pub winner: String,
+    /// Both entries have the same priority, so the winner was picked by entry name
+    pub tied: bool,
+}
+
+/// Keep one operation per target: the one from the entry with the highest priority, ties
+/// going to the entry that sorts first. Winners come back in the order they are applied,
+/// highest priority first.
+pub fn resolve_priorities(
+    mut operations: Vec<EntryOperation>,
+) -> (Vec<EntryOperation>, Vec<OverriddenLink>) {
+    operations.sort_by(|a, b| {
+        b.priority
+            .cmp(&a.priority)
+            .then_with(|| a.entry.cmp(&b.entry))
+    });
+
+    let mut claimed: HashMap<String, (String, i32)> = HashMap::new();
+    let mut winners = Vec::new();
+    let mut overridden = Vec::new();
+    for operation in operations {
+        let target = &operation.operation.target_path;
+        match claimed.get(target) {
+            Some((winner, priority)) => overridden.push(OverriddenLink {
+                target_path: target.clone(),
+                source_path: operation.operation.source_path.clone(),
+                winner: winner.clone(),
+                tied: *priority == operation.priority,
+            }),
+            None => {
+                claimed.insert(
+                    target.clone(),
+                    (operation.operation.source_path.clone(), operation.priority),
+                );
+                winners.push(operation);
+            }
+        }
+    }
+
+    (winners, overridden)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn operation(entry: &str, priority: i32, target: &str) -> EntryOperation {
+        EntryOperation {
+            entry: entry.to_string(),
+            priority,
+            from_directory: false,
+            operation: SymlinkOperation {
+                source_path: format!("/repo/{}", entry),
+                target_path: target.to_string(),
+                mode: Default::default(),
+                permissions: Default::default(),
+            },
+        }
+    }
+
+    #[test]
+    fn test_resolve_priorities() {
+        let (winners, overridden) = resolve_priorities(vec![
+            operation("zsh/.zshrc", 0, "/home/user/.zshrc"),
+            operation("work/.zshrc", 10, "/home/user/.zshrc"),
+            operation("b/.vimrc", 0, "/home/user/.vimrc"),
+            operation("a/.vimrc", 0, "/home/user/.vimrc"),
+        ]);
+
+        let winners: Vec<&str> = winners.iter().map(|op| op.entry.as_str()).collect();
+        assert_eq!(winners, vec!["work/.zshrc", "a/.vimrc"]);
+        assert_eq!(
+            overridden,
+            vec![
+                OverriddenLink {
+                    target_path: "/home/user/.vimrc".to_string(),
+                    source_path: "/repo/b/.vimrc".to_string(),
+                    winner: "/repo/a/.vimrc".to_string(),
+                    tied: true,
+                },
+                OverriddenLink {
+                    target_path: "/home/user/.zshrc".to_string(),
+                    source_path: "/repo/zsh/.zshrc".to_string(),
+                    winner: "/repo/work/.zshrc".to_string(),
+                    tied: false,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,173 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::privileged_helper::PrivilegedHelper;
+
+/// Symlinks the current user may not create, collected to be applied in one go
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LinkPlan {
+    pub links: Vec<PlannedLink>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedLink {
+    pub source: String,
+    pub target: String,
+}
+
+/// Outcome of one planned link, `error` is `None` when it was created
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlannedLinkResult {
+    pub target: String,
+    pub error: Option<String>,
+}
+
+impl LinkPlan {
+    pub fn is_empty(&self) -> bool {
+        self.links.is_empty()
+    }
+
+    pub fn push(&mut self, source: &str, target: &str) {
+        self.links.push(PlannedLink {
+            source: source.to_string(),
+            target: target.to_string(),
+        });
+    }
+}
+
+/// Create every link of `plan`, continuing past failures. This runs in the elevated
+/// `dotf __apply-plan` process, so it only ever creates links: sources must exist and
+/// existing targets are never replaced.
+pub fn apply_plan(plan: &LinkPlan) -> Vec<PlannedLinkResult> {
+    plan.links
+        .iter()
+        .map(|link| PlannedLinkResult {
+            target: link.target.clone(),
+            error: apply_link(link).err(),
+        })
+        .collect()
+}
+
+fn apply_link(link: &PlannedLink) -> Result<(), String> {
+    let source = Path::new(&link.source);
+    let target = Path::new(&link.target);
+    if !source.is_absolute() || !target.is_absolute() {
+        return Err("paths in a plan must be absolute".to_string());
+    }
+    if !source.exists() {
+        return Err(format!("source {} does not exist", link.source));
+    }
+    if target.symlink_metadata().is_ok() {
+        return Err("target already exists".to_string());
+    }
+
+    if let Some(parent) = target.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(source, target).map_err(|e| e.to_string())?;
+    #[cfg(windows)]
+    {
+        let result = if source.is_dir() {
+            std::os::windows::fs::symlink_dir(source, target)
+        } else {
+            std::os::windows::fs::symlink_file(source, target)
+        };
+        result.map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// `PrivilegedHelper` that re-runs dotf once through `sudo`, so a single password prompt
+/// covers the whole plan
+pub struct SudoHelper;
+
+impl SudoHelper {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+impl Default for SudoHelper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl PrivilegedHelper for SudoHelper {
+    async fn apply(&self, plan: &LinkPlan) -> DotfResult<Vec<PlannedLinkResult>> {
+        let executable = std::env::current_exe().map_err(DotfError::Io)?;
+        let plan_file = tempfile::Builder::new()
+            .prefix("dotf-plan-")
+            .suffix(".json")
+            .tempfile()
+            .map_err(DotfError::Io)?;
+        let content = serde_json::to_string(plan).map_err(|e| {
+            DotfError::Operation(format!("Failed to serialize privileged plan: {}", e))
+        })?;
+        std::fs::write(plan_file.path(), content).map_err(DotfError::Io)?;
+
+        // stdin and stderr stay attached so sudo can ask for the password
+        let output = Command::new("sudo")
+            .arg("-p")
+            .arg(format!(
+                "[dotf] password for %u to link {} file(s): ",
+                plan.links.len()
+            ))
+            .arg(executable)
+            .arg("__apply-plan")
+            .arg(plan_file.path())
+            .stdin(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .output()
+            .await
+            .map_err(|e| DotfError::Operation(format!("Failed to run sudo: {}", e)))?;
+        if !output.status.success() {
+            return Err(DotfError::Operation(format!(
+                "Privileged helper failed with exit code {}",
+                output.status.code().unwrap_or(-1)
+            )));
+        }
+
+        serde_json::from_slice(&output.stdout).map_err(|e| {
+            DotfError::Operation(format!("Unreadable privileged helper output: {}", e))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_apply_plan() {
+        let temp = TempDir::new().unwrap();
+        let root = temp.path().to_string_lossy().to_string();
+        std::fs::write(format!("{}/hosts", root), "127.0.0.1 dev").unwrap();
+        std::fs::write(format!("{}/taken", root), "existing").unwrap();
+
+        let mut plan = LinkPlan::default();
+        plan.push(&format!("{}/hosts", root), &format!("{}/etc/hosts", root));
+        plan.push(&format!("{}/hosts", root), &format!("{}/taken", root));
+        plan.push(&format!("{}/missing", root), &format!("{}/etc/other", root));
+
+        let results = apply_plan(&plan);
+        assert_eq!(results[0].error, None);
+        assert_eq!(
+            std::fs::read_link(format!("{}/etc/hosts", root)).unwrap(),
+            Path::new(&format!("{}/hosts", root))
+        );
+        assert_eq!(results[1].error.as_deref(), Some("target already exists"));
+        assert!(results[2]
+            .error
+            .as_deref()
+            .unwrap()
+            .contains("does not exist"));
+    }
+}
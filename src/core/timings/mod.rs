@@ -0,0 +1,7 @@
+//! Per-phase timings of recent invocations, kept for `dotf doctor --slow`
+
+pub mod recorder;
+pub mod store;
+
+pub use recorder::{finish, phase, start, InvocationTiming, PhaseTimer, PhaseTiming};
+pub use store::{summarize, PhaseSummary, TimingStore};
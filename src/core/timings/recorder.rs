@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Time spent in one named phase during an invocation, summed over every time it ran
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PhaseTiming {
+    pub name: String,
+    pub millis: u64,
+    /// Links, files or other items the phase worked through
+    #[serde(default)]
+    pub items: usize,
+}
+
+/// Phases of one `dotf` invocation, as persisted for `dotf doctor --slow`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvocationTiming {
+    pub started_at: DateTime<Utc>,
+    pub command: String,
+    pub millis: u64,
+    pub phases: Vec<PhaseTiming>,
+}
+
+struct Recording {
+    started_at: DateTime<Utc>,
+    started: Instant,
+    command: String,
+    phases: Vec<PhaseTiming>,
+}
+
+/// The invocation being timed; phases outside of one (tests, the library) are dropped
+static RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+
+/// Start timing an invocation of `command`, discarding any unfinished one
+pub fn start(command: &str) {
+    *lock() = Some(Recording {
+        started_at: Utc::now(),
+        started: Instant::now(),
+        command: command.to_string(),
+        phases: Vec::new(),
+    });
+}
+
+/// Stop timing and return what was recorded; `None` when nothing was started or no
+/// phase ran, so quick commands leave no trace
+pub fn finish() -> Option<InvocationTiming> {
+    let recording = lock().take()?;
+    if recording.phases.is_empty() {
+        return None;
+    }
+    Some(InvocationTiming {
+        started_at: recording.started_at,
+        command: recording.command,
+        millis: millis(recording.started.elapsed()),
+        phases: recording.phases,
+    })
+}
+
+/// Time a phase until the returned guard is dropped
+pub fn phase(name: &'static str) -> PhaseTimer {
+    PhaseTimer {
+        name,
+        started: Instant::now(),
+        items: 0,
+    }
+}
+
+/// Adds the time since [`phase`] to the current invocation when dropped
+pub struct PhaseTimer {
+    name: &'static str,
+    started: Instant,
+    items: usize,
+}
+
+impl PhaseTimer {
+    /// Record how many items the phase handled, e.g. links checked
+    pub fn items(&mut self, items: usize) {
+        self.items += items;
+    }
+}
+
+impl Drop for PhaseTimer {
+    fn drop(&mut self) {
+        let mut recording = lock();
+        let Some(recording) = recording.as_mut() else {
+            return;
+        };
+        let elapsed = millis(self.started.elapsed());
+        match recording
+            .phases
+            .iter_mut()
+            .find(|phase| phase.name == self.name)
+        {
+            Some(phase) => {
+                phase.millis += elapsed;
+                phase.items += self.items;
+            }
+            None => recording.phases.push(PhaseTiming {
+                name: self.name.to_string(),
+                millis: elapsed,
+                items: self.items,
+            }),
+        }
+    }
+}
+
+fn lock() -> std::sync::MutexGuard<'static, Option<Recording>> {
+    // A panic while timing leaves nothing worth protecting
+    RECORDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn millis(duration: Duration) -> u64 {
+    u64::try_from(duration.as_millis()).unwrap_or(u64::MAX)
+}
@@ -0,0 +1,244 @@
+use crate::error::DotfResult;
+use crate::traits::filesystem::FileSystem;
+
+use super::recorder::InvocationTiming;
+
+/// Number of invocations kept in the timings file; older ones are dropped
+const MAX_INVOCATIONS: usize = 50;
+
+/// A phase that has not been given its own limit counts as slow from this long
+const DEFAULT_SLOW_MILLIS: u64 = 2000;
+
+/// Phases users can speed up: the name, how long a run may take before it counts as
+/// slow, and what to try
+const KNOWN_PHASES: &[(&str, u64, &str)] = &[
+    (
+        "git fetch",
+        3000,
+        "Reaching the remote is slow; shell prompts and status bars can read the cached status with 'dotf greet' or 'dotf status --widget' instead",
+    ),
+    (
+        "git status",
+        1000,
+        "The repository is slow to inspect; 'dotf maintain' compacts it",
+    ),
+    (
+        "git pull",
+        5000,
+        "Pulling is slow; check the connection to the remote and run 'dotf maintain' to compact the repository",
+    ),
+    (
+        "symlink checks",
+        1000,
+        "Checking every link is slow; turn off targets this machine does not need with 'dotf disable'",
+    ),
+    (
+        "directory expansion",
+        1000,
+        "Directory sources are expanded into one link per file; map smaller directories in [symlinks] or move generated files out of them",
+    ),
+];
+
+/// How one phase performed across the recorded invocations
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhaseSummary {
+    pub name: String,
+    pub runs: usize,
+    pub median_millis: u64,
+    pub slowest_millis: u64,
+    /// Items handled by the slowest run
+    pub slowest_items: usize,
+    /// Command of the slowest run
+    pub slowest_command: String,
+    pub slow_millis: u64,
+    pub suggestion: Option<&'static str>,
+}
+
+impl PhaseSummary {
+    /// At least one run took longer than the phase should
+    pub fn is_slow(&self) -> bool {
+        self.slowest_millis >= self.slow_millis
+    }
+
+    /// Most runs took longer than the phase should, rather than the odd outlier
+    pub fn is_consistently_slow(&self) -> bool {
+        self.median_millis >= self.slow_millis
+    }
+}
+
+/// Summarize every phase in `invocations`, slowest first
+pub fn summarize(invocations: &[InvocationTiming]) -> Vec<PhaseSummary> {
+    let mut names: Vec<&str> = Vec::new();
+    for invocation in invocations {
+        for phase in &invocation.phases {
+            if !names.contains(&phase.name.as_str()) {
+                names.push(&phase.name);
+            }
+        }
+    }
+
+    let mut summaries: Vec<PhaseSummary> = names
+        .into_iter()
+        .map(|name| {
+            let runs: Vec<(&InvocationTiming, u64, usize)> = invocations
+                .iter()
+                .flat_map(|invocation| {
+                    invocation
+                        .phases
+                        .iter()
+                        .filter(|phase| phase.name == name)
+                        .map(move |phase| (invocation, phase.millis, phase.items))
+                })
+                .collect();
+            let mut millis: Vec<u64> = runs.iter().map(|(_, millis, _)| *millis).collect();
+            millis.sort_unstable();
+            let (slowest, slowest_millis, slowest_items) = runs
+                .iter()
+                .max_by_key(|(_, millis, _)| *millis)
+                .copied()
+                .expect("every name comes from a recorded phase");
+            let known = KNOWN_PHASES.iter().find(|(known, _, _)| *known == name);
+
+            PhaseSummary {
+                name: name.to_string(),
+                runs: runs.len(),
+                median_millis: millis[(millis.len() - 1) / 2],
+                slowest_millis,
+                slowest_items,
+                slowest_command: slowest.command.clone(),
+                slow_millis: known.map_or(DEFAULT_SLOW_MILLIS, |(_, limit, _)| *limit),
+                suggestion: known.map(|(_, _, suggestion)| *suggestion),
+            }
+        })
+        .collect();
+    summaries.sort_by_key(|summary| std::cmp::Reverse(summary.slowest_millis));
+    summaries
+}
+
+/// The timings of recent invocations, kept as JSON lines
+pub struct TimingStore<F> {
+    filesystem: F,
+}
+
+impl<F: FileSystem> TimingStore<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self { filesystem }
+    }
+
+    pub async fn append(&self, invocation: InvocationTiming) -> DotfResult<()> {
+        let mut invocations = self.load_all().await?;
+        invocations.push(invocation);
+
+        if invocations.len() > MAX_INVOCATIONS {
+            let excess = invocations.len() - MAX_INVOCATIONS;
+            invocations.drain(..excess);
+        }
+
+        let mut content = String::new();
+        for invocation in &invocations {
+            content.push_str(&serde_json::to_string(invocation)?);
+            content.push('\n');
+        }
+
+        self.filesystem
+            .write(&self.filesystem.dotf_timings_path(), &content)
+            .await
+    }
+
+    pub async fn load_all(&self) -> DotfResult<Vec<InvocationTiming>> {
+        let timings_path = self.filesystem.dotf_timings_path();
+
+        if !self.filesystem.exists(&timings_path).await? {
+            return Ok(Vec::new());
+        }
+
+        let content = self.filesystem.read_to_string(&timings_path).await?;
+
+        // Skip lines that fail to parse (e.g. a write interrupted mid-line)
+        Ok(content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::timings::{finish, phase, start, PhaseTiming};
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::Utc;
+
+    fn invocation(command: &str, phases: &[(&str, u64, usize)]) -> InvocationTiming {
+        InvocationTiming {
+            started_at: Utc::now(),
+            command: command.to_string(),
+            millis: phases.iter().map(|(_, millis, _)| millis).sum(),
+            phases: phases
+                .iter()
+                .map(|(name, millis, items)| PhaseTiming {
+                    name: name.to_string(),
+                    millis: *millis,
+                    items: *items,
+                })
+                .collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timings_recorded_and_summarized() {
+        start("test");
+        {
+            let mut timer = phase("timings test phase");
+            timer.items(3);
+        }
+        drop(phase("timings test phase"));
+        let recorded = finish().unwrap();
+        let test_phase = recorded
+            .phases
+            .iter()
+            .find(|phase| phase.name == "timings test phase")
+            .unwrap();
+        assert_eq!(test_phase.items, 3);
+        assert!(finish().is_none());
+
+        let store = TimingStore::new(MockFileSystem::new());
+        for _ in 0..MAX_INVOCATIONS {
+            store
+                .append(invocation("sync", &[("git pull", 400, 0)]))
+                .await
+                .unwrap();
+        }
+        store
+            .append(invocation(
+                "status",
+                &[("git fetch", 8000, 0), ("symlink checks", 1500, 400)],
+            ))
+            .await
+            .unwrap();
+        store
+            .append(invocation(
+                "status",
+                &[("git fetch", 200, 0), ("symlink checks", 2500, 400)],
+            ))
+            .await
+            .unwrap();
+        let invocations = store.load_all().await.unwrap();
+        assert_eq!(invocations.len(), MAX_INVOCATIONS);
+
+        let summaries = summarize(&invocations);
+        let names: Vec<&str> = summaries.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["git fetch", "symlink checks", "git pull"]);
+
+        // One slow fetch among fast ones is an outlier
+        let fetch = &summaries[0];
+        assert!(fetch.is_slow() && !fetch.is_consistently_slow());
+        assert_eq!(fetch.slowest_command, "status");
+        assert!(fetch.suggestion.unwrap().contains("dotf greet"));
+        let checks = &summaries[1];
+        assert!(checks.is_consistently_slow());
+        assert_eq!(checks.slowest_items, 400);
+        assert!(!summaries[2].is_slow());
+    }
+}
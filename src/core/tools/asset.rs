@@ -0,0 +1,318 @@
+use sha2::{Digest, Sha256};
+
+use crate::core::config::dotf_config::ToolConfig;
+use crate::core::tools::release::ReleaseAsset;
+use crate::error::{DotfError, DotfResult};
+
+/// Operating system and CPU architecture that release assets are selected for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlatformTarget {
+    pub os: &'static str,
+    pub arch: &'static str,
+}
+
+impl PlatformTarget {
+    pub fn current() -> Self {
+        Self {
+            os: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+        }
+    }
+
+    /// Key used for per-platform checksums in dotf.toml, e.g. `linux-x86_64`
+    pub fn key(&self) -> String {
+        format!("{}-{}", self.os, self.arch)
+    }
+
+    /// Spellings release authors use for this OS in asset names
+    fn os_aliases(&self) -> &'static [&'static str] {
+        match self.os {
+            "macos" => &["darwin", "macos", "apple", "osx", "mac"],
+            "linux" => &["linux"],
+            "windows" => &["windows", "win64"],
+            _ => &[],
+        }
+    }
+
+    /// Spellings release authors use for this architecture in asset names
+    fn arch_aliases(&self) -> &'static [&'static str] {
+        match self.arch {
+            "x86_64" => &["x86_64", "amd64", "x64"],
+            "aarch64" => &["aarch64", "arm64"],
+            "arm" => &["armv7", "armhf", "arm"],
+            "x86" => &["i686", "i386", "x86"],
+            _ => &[],
+        }
+    }
+}
+
+/// Asset names that accompany a release but are never the tool itself
+const SIDECAR_SUFFIXES: &[&str] = &[
+    ".sha256",
+    ".sha256sum",
+    ".sha512",
+    ".md5",
+    ".sig",
+    ".asc",
+    ".pem",
+    ".sbom",
+    ".txt",
+];
+
+fn is_sidecar(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    SIDECAR_SUFFIXES
+        .iter()
+        .any(|suffix| lower.ends_with(suffix))
+        || lower.contains("checksum")
+        || lower.starts_with("sha256sums")
+}
+
+/// Replace `{version}` (tag without a leading `v`) and `{tag}` in an asset pattern
+pub fn expand_pattern(pattern: &str, tag: &str) -> String {
+    pattern
+        .replace("{version}", tag.strip_prefix('v').unwrap_or(tag))
+        .replace("{tag}", tag)
+}
+
+/// Match `name` against a pattern where `*` matches any run of characters and `?` one
+pub fn glob_match(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            p += 1;
+            n += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, n));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            n = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn mentions_any(name: &str, aliases: &[&str]) -> bool {
+    let lower = name.to_lowercase();
+    aliases.iter().any(|alias| lower.contains(alias))
+}
+
+/// Pick the release asset for `target`.
+///
+/// Assets are filtered by the configured pattern (if any), then narrowed to the
+/// target OS and architecture when more than one remains. On Linux, statically
+/// linked `musl` builds win over `gnu` ones since they run everywhere.
+pub fn select_asset<'a>(
+    assets: &'a [ReleaseAsset],
+    tool: &ToolConfig,
+    tag: &str,
+    target: &PlatformTarget,
+) -> DotfResult<&'a ReleaseAsset> {
+    let pattern = tool
+        .asset
+        .as_deref()
+        .map(|pattern| expand_pattern(pattern, tag));
+
+    let mut candidates: Vec<&ReleaseAsset> = assets
+        .iter()
+        .filter(|asset| !is_sidecar(&asset.name))
+        .filter(|asset| match &pattern {
+            Some(pattern) => glob_match(pattern, &asset.name),
+            None => true,
+        })
+        .collect();
+
+    if candidates.len() > 1 {
+        candidates.retain(|asset| mentions_any(&asset.name, target.os_aliases()));
+    }
+    if candidates.len() > 1 {
+        candidates.retain(|asset| mentions_any(&asset.name, target.arch_aliases()));
+    }
+    if candidates.len() > 1 && target.os == "linux" {
+        let musl: Vec<&ReleaseAsset> = candidates
+            .iter()
+            .copied()
+            .filter(|asset| asset.name.contains("musl"))
+            .collect();
+        if !musl.is_empty() {
+            candidates = musl;
+        }
+    }
+
+    match candidates.as_slice() {
+        [asset] => Ok(asset),
+        [] => Err(DotfError::Operation(format!(
+            "No release asset of {} {} matches {}{}",
+            tool.repo,
+            tag,
+            target.key(),
+            pattern
+                .map(|p| format!(" and pattern '{}'", p))
+                .unwrap_or_default()
+        ))),
+        many => Err(DotfError::Operation(format!(
+            "Several release assets of {} {} match {}: {}. Set `asset` to choose one",
+            tool.repo,
+            tag,
+            target.key(),
+            many.iter()
+                .map(|asset| asset.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ))),
+    }
+}
+
+/// Find the checksum for `asset_name` in a `sha256sum`-style listing.
+///
+/// Single-asset checksum files (`foo.tar.gz.sha256`) often hold just the digest,
+/// which is accepted when it is the only entry.
+pub fn find_checksum(listing: &str, asset_name: &str) -> Option<String> {
+    let entries: Vec<Vec<&str>> = listing
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| !fields.is_empty())
+        .collect();
+
+    for fields in &entries {
+        if let [digest, name] = fields.as_slice() {
+            let name = name.trim_start_matches('*');
+            let file_name = name.rsplit('/').next().unwrap_or(name);
+            if file_name == asset_name {
+                return Some(digest.to_lowercase());
+            }
+        }
+    }
+
+    match entries.as_slice() {
+        [fields] if fields.len() == 1 => Some(fields[0].to_lowercase()),
+        _ => None,
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::dotf_config::ReleaseHost;
+    use std::collections::HashMap;
+
+    fn assets(names: &[&str]) -> Vec<ReleaseAsset> {
+        names
+            .iter()
+            .map(|name| ReleaseAsset {
+                name: name.to_string(),
+                url: format!("https://example.com/{}", name),
+            })
+            .collect()
+    }
+
+    fn tool(asset: Option<&str>) -> ToolConfig {
+        ToolConfig {
+            repo: "BurntSushi/ripgrep".to_string(),
+            host: ReleaseHost::Github,
+            version: "14.1.0".to_string(),
+            asset: asset.map(|a| a.to_string()),
+            bin: Some("rg".to_string()),
+            install_path: None,
+            checksums: None,
+            sha256: HashMap::new(),
+        }
+    }
+
+    const LINUX_X64: PlatformTarget = PlatformTarget {
+        os: "linux",
+        arch: "x86_64",
+    };
+    const MACOS_ARM: PlatformTarget = PlatformTarget {
+        os: "macos",
+        arch: "aarch64",
+    };
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("rg-*-linux.tar.gz", "rg-14.1.0-linux.tar.gz"));
+        assert!(glob_match("*", "anything"));
+        assert!(glob_match("a?c", "abc"));
+        assert!(!glob_match("rg-*.zip", "rg-14.1.0.tar.gz"));
+        assert!(!glob_match("rg", "rg2"));
+    }
+
+    #[test]
+    fn test_select_asset_by_platform() {
+        let release = assets(&[
+            "ripgrep-14.1.0-x86_64-unknown-linux-gnu.tar.gz",
+            "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz",
+            "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz.sha256",
+            "ripgrep-14.1.0-aarch64-apple-darwin.tar.gz",
+            "ripgrep-14.1.0-x86_64-apple-darwin.tar.gz",
+        ]);
+
+        let linux = select_asset(&release, &tool(None), "14.1.0", &LINUX_X64).unwrap();
+        assert_eq!(
+            linux.name,
+            "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz"
+        );
+
+        let macos = select_asset(&release, &tool(None), "14.1.0", &MACOS_ARM).unwrap();
+        assert_eq!(macos.name, "ripgrep-14.1.0-aarch64-apple-darwin.tar.gz");
+    }
+
+    #[test]
+    fn test_select_asset_with_pattern() {
+        let release = assets(&[
+            "tool_1.2.0_linux_amd64.tar.gz",
+            "tool_1.2.0_linux_amd64.deb",
+        ]);
+
+        let asset = select_asset(
+            &release,
+            &tool(Some("tool_{version}_linux_*.tar.gz")),
+            "v1.2.0",
+            &LINUX_X64,
+        )
+        .unwrap();
+        assert_eq!(asset.name, "tool_1.2.0_linux_amd64.tar.gz");
+
+        let ambiguous = select_asset(&release, &tool(None), "v1.2.0", &LINUX_X64);
+        assert!(matches!(ambiguous, Err(DotfError::Operation(_))));
+    }
+
+    #[test]
+    fn test_find_checksum() {
+        let listing = "abc123  tool-linux.tar.gz\nDEF456 *tool-darwin.tar.gz\n";
+        assert_eq!(
+            find_checksum(listing, "tool-darwin.tar.gz"),
+            Some("def456".to_string())
+        );
+        assert_eq!(find_checksum(listing, "tool.zip"), None);
+        assert_eq!(
+            find_checksum("abc123\n", "tool.zip"),
+            Some("abc123".to_string())
+        );
+    }
+
+    #[test]
+    fn test_sha256_hex() {
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,265 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::core::config::dotf_config::ToolConfig;
+use crate::core::tools::asset::{
+    expand_pattern, find_checksum, select_asset, sha256_hex, PlatformTarget,
+};
+use crate::core::tools::release::{Release, ReleaseClient};
+use crate::error::{DotfError, DotfResult};
+
+/// Result of installing one release binary
+#[derive(Debug, Clone)]
+pub struct ToolInstallation {
+    pub tag: String,
+    pub asset: String,
+    pub path: String,
+    pub sha256: String,
+    pub verified: bool,
+}
+
+/// Downloads, verifies and unpacks release assets into an install directory
+pub struct ToolInstaller {
+    client: ReleaseClient,
+    target: PlatformTarget,
+}
+
+impl Default for ToolInstaller {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ToolInstaller {
+    pub fn new() -> Self {
+        Self {
+            client: ReleaseClient::new(),
+            target: PlatformTarget::current(),
+        }
+    }
+
+    pub async fn resolve(&self, tool: &ToolConfig) -> DotfResult<Release> {
+        self.client.fetch_release(tool).await
+    }
+
+    /// Install the executable `bin_name` from `release` into `install_dir`
+    pub async fn install(
+        &self,
+        tool: &ToolConfig,
+        bin_name: &str,
+        release: &Release,
+        install_dir: &Path,
+    ) -> DotfResult<ToolInstallation> {
+        let asset = select_asset(&release.assets, tool, &release.tag, &self.target)?;
+        let bytes = self.client.download(&asset.url).await?;
+        let digest = sha256_hex(&bytes);
+
+        let expected = self.expected_checksum(tool, release, &asset.name).await?;
+        if let Some(expected) = &expected {
+            if !expected.eq_ignore_ascii_case(&digest) {
+                return Err(DotfError::Validation(format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    asset.name, expected, digest
+                )));
+            }
+        }
+
+        let work_dir = tempfile::tempdir().map_err(DotfError::Io)?;
+        let downloaded = work_dir.path().join(&asset.name);
+        fs::write(&downloaded, &bytes).map_err(DotfError::Io)?;
+
+        let binary = if is_archive(&asset.name) {
+            let extracted = work_dir.path().join("extracted");
+            fs::create_dir_all(&extracted).map_err(DotfError::Io)?;
+            extract(&downloaded, &extracted)?;
+            find_binary(&extracted, bin_name)?.ok_or_else(|| {
+                DotfError::Operation(format!(
+                    "'{}' was not found inside {}; set `bin` to the executable's name",
+                    bin_name, asset.name
+                ))
+            })?
+        } else {
+            downloaded
+        };
+
+        let installed_path = install_executable(&binary, install_dir, bin_name)?;
+
+        Ok(ToolInstallation {
+            tag: release.tag.clone(),
+            asset: asset.name.clone(),
+            path: installed_path.to_string_lossy().to_string(),
+            sha256: digest,
+            verified: expected.is_some(),
+        })
+    }
+
+    /// Checksum to verify against: a digest pinned in dotf.toml wins over the
+    /// configured checksums file, which wins over a `<asset>.sha256` sidecar.
+    async fn expected_checksum(
+        &self,
+        tool: &ToolConfig,
+        release: &Release,
+        asset_name: &str,
+    ) -> DotfResult<Option<String>> {
+        if let Some(pinned) = tool.sha256.get(&self.target.key()) {
+            return Ok(Some(pinned.to_lowercase()));
+        }
+
+        let checksum_asset = match &tool.checksums {
+            Some(name) => {
+                let name = expand_pattern(name, &release.tag);
+                Some(
+                    release
+                        .assets
+                        .iter()
+                        .find(|asset| asset.name == name)
+                        .ok_or_else(|| {
+                            DotfError::Operation(format!(
+                                "Checksums file '{}' is not part of release {}",
+                                name, release.tag
+                            ))
+                        })?,
+                )
+            }
+            None => release
+                .assets
+                .iter()
+                .find(|asset| asset.name == format!("{}.sha256", asset_name)),
+        };
+
+        let Some(checksum_asset) = checksum_asset else {
+            return Ok(None);
+        };
+
+        let listing = self.client.download(&checksum_asset.url).await?;
+        let digest = find_checksum(&String::from_utf8_lossy(&listing), asset_name);
+        if digest.is_none() && tool.checksums.is_some() {
+            return Err(DotfError::Validation(format!(
+                "{} has no entry for {}",
+                checksum_asset.name, asset_name
+            )));
+        }
+        Ok(digest)
+    }
+}
+
+const ARCHIVE_SUFFIXES: &[&str] = &[
+    ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.bz2", ".tbz", ".tar.zst", ".tar", ".zip",
+];
+
+fn is_archive(name: &str) -> bool {
+    ARCHIVE_SUFFIXES.iter().any(|suffix| name.ends_with(suffix))
+}
+
+fn extract(archive: &Path, into: &Path) -> DotfResult<()> {
+    let is_zip = archive.to_string_lossy().ends_with(".zip");
+    let output = if is_zip {
+        Command::new("unzip")
+            .arg("-q")
+            .arg(archive)
+            .arg("-d")
+            .arg(into)
+            .output()
+    } else {
+        Command::new("tar")
+            .arg("-xf")
+            .arg(archive)
+            .arg("-C")
+            .arg(into)
+            .output()
+    }
+    .map_err(|e| {
+        DotfError::Operation(format!(
+            "Failed to run {}: {}",
+            if is_zip { "unzip" } else { "tar" },
+            e
+        ))
+    })?;
+
+    if !output.status.success() {
+        return Err(DotfError::Operation(format!(
+            "Failed to extract {}: {}",
+            archive.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(())
+}
+
+/// Breadth-first search for a regular file named `bin_name`, so the shallowest match wins
+fn find_binary(root: &Path, bin_name: &str) -> DotfResult<Option<PathBuf>> {
+    let mut queue = vec![root.to_path_buf()];
+    while !queue.is_empty() {
+        let mut next = Vec::new();
+        for dir in queue {
+            for entry in fs::read_dir(&dir).map_err(DotfError::Io)? {
+                let path = entry.map_err(DotfError::Io)?.path();
+                if path.is_dir() {
+                    next.push(path);
+                } else if path.file_name().is_some_and(|name| name == bin_name) {
+                    return Ok(Some(path));
+                }
+            }
+        }
+        queue = next;
+    }
+    Ok(None)
+}
+
+/// Copy `binary` to `install_dir/bin_name` with a rename, so a running copy is never
+/// overwritten in place
+fn install_executable(binary: &Path, install_dir: &Path, bin_name: &str) -> DotfResult<PathBuf> {
+    fs::create_dir_all(install_dir).map_err(DotfError::Io)?;
+
+    let destination = install_dir.join(bin_name);
+    let staging = install_dir.join(format!(".{}.dotf-new", bin_name));
+    fs::copy(binary, &staging).map_err(DotfError::Io)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staging, fs::Permissions::from_mode(0o755)).map_err(DotfError::Io)?;
+    }
+
+    fs::rename(&staging, &destination).map_err(DotfError::Io)?;
+    Ok(destination)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_binary_prefers_shallowest() {
+        let temp_dir = TempDir::new().unwrap();
+        let nested = temp_dir.path().join("tool-1.0/doc/rg");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("rg"), "docs").unwrap();
+        fs::write(temp_dir.path().join("tool-1.0/rg"), "binary").unwrap();
+
+        let found = find_binary(temp_dir.path(), "rg").unwrap().unwrap();
+        assert_eq!(found, temp_dir.path().join("tool-1.0/rg"));
+        assert_eq!(find_binary(temp_dir.path(), "fd").unwrap(), None);
+    }
+
+    #[test]
+    fn test_install_executable() {
+        let temp_dir = TempDir::new().unwrap();
+        let binary = temp_dir.path().join("download");
+        fs::write(&binary, "#!/bin/sh\n").unwrap();
+
+        let bin_dir = temp_dir.path().join("bin");
+        let installed = install_executable(&binary, &bin_dir, "tool").unwrap();
+
+        assert_eq!(installed, bin_dir.join("tool"));
+        assert_eq!(fs::read_to_string(&installed).unwrap(), "#!/bin/sh\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&installed).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+pub mod asset;
+pub mod installer;
+pub mod release;
+
+pub use asset::PlatformTarget;
+pub use installer::{ToolInstallation, ToolInstaller};
+pub use release::{Release, ReleaseAsset, ReleaseClient};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::config::dotf_config::LATEST_TOOL_VERSION;
+use crate::core::state::InstalledTool;
+
+/// How an installed tool compares to its `[tools]` entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolState {
+    /// The pinned version is installed
+    UpToDate,
+    /// Tracking `latest`; whether a newer release exists is only known online
+    Tracking,
+    /// A different version than the pinned one is installed
+    Outdated,
+    /// Never installed, or the executable has since been removed
+    Missing,
+}
+
+/// Compare the install record of a tool with the version wanted in dotf.toml
+pub fn tool_state(
+    wanted_version: &str,
+    installed: Option<&InstalledTool>,
+    binary_exists: bool,
+) -> ToolState {
+    match installed {
+        Some(_) if !binary_exists => ToolState::Missing,
+        None => ToolState::Missing,
+        Some(_) if wanted_version == LATEST_TOOL_VERSION => ToolState::Tracking,
+        Some(tool) if tool.version == wanted_version => ToolState::UpToDate,
+        Some(_) => ToolState::Outdated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn installed(version: &str) -> InstalledTool {
+        InstalledTool {
+            version: version.to_string(),
+            asset: "tool.tar.gz".to_string(),
+            path: "/home/user/.local/bin/tool".to_string(),
+            sha256: String::new(),
+            verified: true,
+            installed_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_tool_state() {
+        let v1 = installed("v1.0.0");
+        assert_eq!(tool_state("v1.0.0", Some(&v1), true), ToolState::UpToDate);
+        assert_eq!(tool_state("v1.1.0", Some(&v1), true), ToolState::Outdated);
+        assert_eq!(tool_state("latest", Some(&v1), true), ToolState::Tracking);
+        assert_eq!(tool_state("v1.0.0", Some(&v1), false), ToolState::Missing);
+        assert_eq!(tool_state("v1.0.0", None, false), ToolState::Missing);
+    }
+}
@@ -0,0 +1,150 @@
+use serde::Deserialize;
+
+use crate::core::config::dotf_config::{ReleaseHost, ToolConfig, LATEST_TOOL_VERSION};
+use crate::error::DotfResult;
+
+#[derive(Debug, Clone)]
+pub struct Release {
+    pub tag: String,
+    pub assets: Vec<ReleaseAsset>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    assets: Vec<GithubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitlabRelease {
+    tag_name: String,
+    assets: GitlabAssets,
+}
+
+#[derive(Deserialize)]
+struct GitlabAssets {
+    #[serde(default)]
+    links: Vec<GitlabLink>,
+}
+
+#[derive(Deserialize)]
+struct GitlabLink {
+    name: String,
+    url: String,
+    direct_asset_url: Option<String>,
+}
+
+/// Talks to the GitHub and GitLab release APIs.
+///
+/// `GITHUB_TOKEN` / `GITLAB_TOKEN` are sent when set, which lifts anonymous rate limits
+/// and allows private projects.
+pub struct ReleaseClient {
+    client: reqwest::Client,
+}
+
+impl Default for ReleaseClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ReleaseClient {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .user_agent(concat!("dotf/", env!("CARGO_PKG_VERSION")))
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    pub async fn fetch_release(&self, tool: &ToolConfig) -> DotfResult<Release> {
+        match tool.host {
+            ReleaseHost::Github => self.fetch_github_release(tool).await,
+            ReleaseHost::Gitlab => self.fetch_gitlab_release(tool).await,
+        }
+    }
+
+    async fn fetch_github_release(&self, tool: &ToolConfig) -> DotfResult<Release> {
+        let url = if tool.version == LATEST_TOOL_VERSION {
+            format!("https://api.github.com/repos/{}/releases/latest", tool.repo)
+        } else {
+            format!(
+                "https://api.github.com/repos/{}/releases/tags/{}",
+                tool.repo, tool.version
+            )
+        };
+
+        let mut request = self
+            .client
+            .get(url)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json");
+        if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+            request = request.bearer_auth(token);
+        }
+
+        let release: GithubRelease = request.send().await?.error_for_status()?.json().await?;
+        Ok(Release {
+            tag: release.tag_name,
+            assets: release
+                .assets
+                .into_iter()
+                .map(|asset| ReleaseAsset {
+                    name: asset.name,
+                    url: asset.browser_download_url,
+                })
+                .collect(),
+        })
+    }
+
+    async fn fetch_gitlab_release(&self, tool: &ToolConfig) -> DotfResult<Release> {
+        let project = tool.repo.replace('/', "%2F");
+        let url = if tool.version == LATEST_TOOL_VERSION {
+            format!(
+                "https://gitlab.com/api/v4/projects/{}/releases/permalink/latest",
+                project
+            )
+        } else {
+            format!(
+                "https://gitlab.com/api/v4/projects/{}/releases/{}",
+                project, tool.version
+            )
+        };
+
+        let mut request = self.client.get(url);
+        if let Ok(token) = std::env::var("GITLAB_TOKEN") {
+            request = request.header("PRIVATE-TOKEN", token);
+        }
+
+        let release: GitlabRelease = request.send().await?.error_for_status()?.json().await?;
+        Ok(Release {
+            tag: release.tag_name,
+            assets: release
+                .assets
+                .links
+                .into_iter()
+                .map(|link| ReleaseAsset {
+                    name: link.name,
+                    url: link.direct_asset_url.unwrap_or(link.url),
+                })
+                .collect(),
+        })
+    }
+
+    pub async fn download(&self, url: &str) -> DotfResult<Vec<u8>> {
+        let response = self.client.get(url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+}
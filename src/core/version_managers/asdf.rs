@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use std::process::Command;
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{
+    script_executor::ExecutionResult,
+    tool_version_manager::{RuntimeVersion, ToolVersionManager},
+};
+
+/// [asdf](https://asdf-vm.com), driven by `.tool-versions`
+pub struct AsdfManager;
+
+impl Default for AsdfManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AsdfManager {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl ToolVersionManager for AsdfManager {
+    fn name(&self) -> &'static str {
+        "asdf"
+    }
+
+    fn config_files(&self) -> &'static [&'static str] {
+        &[".tool-versions"]
+    }
+
+    async fn is_available(&self) -> bool {
+        Command::new("asdf")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    async fn is_installed(&self, repo_path: &str, runtime: &RuntimeVersion) -> DotfResult<bool> {
+        let output = Command::new("asdf")
+            .current_dir(repo_path)
+            .args(["where", &runtime.tool, &runtime.version])
+            .output()
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run asdf: {}", e)))?;
+        Ok(output.status.success())
+    }
+
+    async fn install(&self, repo_path: &str) -> DotfResult<ExecutionResult> {
+        // Inherit stdio so download and build progress stays visible
+        let status = Command::new("asdf")
+            .current_dir(repo_path)
+            .arg("install")
+            .status()
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run asdf: {}", e)))?;
+
+        Ok(ExecutionResult {
+            success: status.success(),
+            exit_code: status.code().unwrap_or(-1),
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+}
@@ -0,0 +1,75 @@
+use async_trait::async_trait;
+use std::process::Command;
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{
+    script_executor::ExecutionResult,
+    tool_version_manager::{RuntimeVersion, ToolVersionManager},
+};
+
+/// [mise](https://mise.jdx.dev), which also reads asdf's `.tool-versions`
+pub struct MiseManager;
+
+impl Default for MiseManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MiseManager {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// mise refuses to read config files in untrusted directories; trust only the
+    /// dotfiles repository, and only for this invocation
+    fn command(&self, repo_path: &str) -> Command {
+        let mut cmd = Command::new("mise");
+        cmd.current_dir(repo_path)
+            .env("MISE_TRUSTED_CONFIG_PATHS", repo_path);
+        cmd
+    }
+}
+
+#[async_trait]
+impl ToolVersionManager for MiseManager {
+    fn name(&self) -> &'static str {
+        "mise"
+    }
+
+    fn config_files(&self) -> &'static [&'static str] {
+        &["mise.toml", ".mise.toml", ".tool-versions"]
+    }
+
+    async fn is_available(&self) -> bool {
+        Command::new("mise")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    async fn is_installed(&self, repo_path: &str, runtime: &RuntimeVersion) -> DotfResult<bool> {
+        let output = self
+            .command(repo_path)
+            .args(["where", &format!("{}@{}", runtime.tool, runtime.version)])
+            .output()
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run mise: {}", e)))?;
+        Ok(output.status.success())
+    }
+
+    async fn install(&self, repo_path: &str) -> DotfResult<ExecutionResult> {
+        // Inherit stdio so download and build progress stays visible
+        let status = self
+            .command(repo_path)
+            .arg("install")
+            .status()
+            .map_err(|e| DotfError::ScriptExecution(format!("Failed to run mise: {}", e)))?;
+
+        Ok(ExecutionResult {
+            success: status.success(),
+            exit_code: status.code().unwrap_or(-1),
+            stdout: String::new(),
+            stderr: String::new(),
+        })
+    }
+}
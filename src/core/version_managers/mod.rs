@@ -0,0 +1,254 @@
+pub mod asdf;
+pub mod mise;
+
+pub use asdf::AsdfManager;
+pub use mise::MiseManager;
+
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{
+    filesystem::FileSystem,
+    tool_version_manager::{RuntimeVersion, ToolVersionManager},
+};
+
+/// The supported managers, preferred first
+pub fn default_managers() -> Vec<Box<dyn ToolVersionManager>> {
+    vec![Box::new(MiseManager::new()), Box::new(AsdfManager::new())]
+}
+
+/// Result of comparing the runtimes a repository declares with what is installed
+pub struct RuntimeCheck<'a> {
+    pub manager: &'a dyn ToolVersionManager,
+    pub manager_available: bool,
+    /// Declaration files found in the repository root
+    pub files: Vec<String>,
+    pub declared: Vec<RuntimeVersion>,
+    /// Declared versions that are not installed (all of them when the manager is unavailable)
+    pub missing: Vec<RuntimeVersion>,
+}
+
+/// Find runtime declarations in `repo_path` and check them with the first available
+/// manager that reads them, falling back to the preferred one if none is installed.
+///
+/// Returns `None` when the repository declares no runtimes.
+pub async fn check_runtimes<'a, F: FileSystem>(
+    filesystem: &F,
+    managers: &'a [Box<dyn ToolVersionManager>],
+    repo_path: &str,
+) -> DotfResult<Option<RuntimeCheck<'a>>> {
+    let mut present = Vec::new();
+    for manager in managers {
+        for file in manager.config_files() {
+            if !present.contains(file)
+                && filesystem
+                    .exists(&format!("{}/{}", repo_path, file))
+                    .await?
+            {
+                present.push(*file);
+            }
+        }
+    }
+
+    let candidates: Vec<&dyn ToolVersionManager> = managers
+        .iter()
+        .map(|manager| manager.as_ref())
+        .filter(|manager| manager.config_files().iter().any(|f| present.contains(f)))
+        .collect();
+
+    let mut chosen = None;
+    for manager in &candidates {
+        if manager.is_available().await {
+            chosen = Some((*manager, true));
+            break;
+        }
+    }
+    let Some((manager, manager_available)) =
+        chosen.or_else(|| candidates.first().map(|manager| (*manager, false)))
+    else {
+        return Ok(None);
+    };
+
+    let mut files = Vec::new();
+    let mut declared: Vec<RuntimeVersion> = Vec::new();
+    for file in manager
+        .config_files()
+        .iter()
+        .filter(|f| present.contains(f))
+    {
+        let content = filesystem
+            .read_to_string(&format!("{}/{}", repo_path, file))
+            .await?;
+        for runtime in parse_runtime_file(file, &content)? {
+            if !declared.contains(&runtime) {
+                declared.push(runtime);
+            }
+        }
+        files.push(file.to_string());
+    }
+
+    let mut missing = Vec::new();
+    for runtime in &declared {
+        if !manager_available || !manager.is_installed(repo_path, runtime).await? {
+            missing.push(runtime.clone());
+        }
+    }
+
+    Ok(Some(RuntimeCheck {
+        manager,
+        manager_available,
+        files,
+        declared,
+        missing,
+    }))
+}
+
+/// Parse a declaration file by name: `*.toml` as mise config, anything else as `.tool-versions`
+pub fn parse_runtime_file(file_name: &str, content: &str) -> DotfResult<Vec<RuntimeVersion>> {
+    if file_name.ends_with(".toml") {
+        parse_mise_toml(file_name, content)
+    } else {
+        Ok(parse_tool_versions(content))
+    }
+}
+
+/// Parse `.tool-versions` lines such as `nodejs 20.11.0 18.19.0  # comment`
+pub fn parse_tool_versions(content: &str) -> Vec<RuntimeVersion> {
+    let mut runtimes = Vec::new();
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut fields = line.split_whitespace();
+        let Some(tool) = fields.next() else {
+            continue;
+        };
+        for version in fields {
+            runtimes.push(RuntimeVersion {
+                tool: tool.to_string(),
+                version: version.to_string(),
+            });
+        }
+    }
+
+    runtimes
+}
+
+/// Parse the `[tools]` table of a mise config, where values are a version, a list of
+/// versions or a table with a `version` key
+pub fn parse_mise_toml(file_name: &str, content: &str) -> DotfResult<Vec<RuntimeVersion>> {
+    let value: toml::Value = toml::from_str(content)
+        .map_err(|e| DotfError::Config(format!("Failed to parse {}: {}", file_name, e)))?;
+
+    let Some(tools) = value.get("tools").and_then(|tools| tools.as_table()) else {
+        return Ok(Vec::new());
+    };
+
+    fn version_of(value: &toml::Value) -> Option<String> {
+        match value {
+            toml::Value::String(version) => Some(version.clone()),
+            toml::Value::Table(table) => table
+                .get("version")
+                .and_then(|version| version.as_str())
+                .map(|version| version.to_string()),
+            _ => None,
+        }
+    }
+
+    let mut runtimes = Vec::new();
+    for (tool, spec) in tools {
+        let versions = match spec {
+            toml::Value::Array(items) => items.iter().filter_map(version_of).collect(),
+            other => version_of(other).into_iter().collect::<Vec<_>>(),
+        };
+        for version in versions {
+            runtimes.push(RuntimeVersion {
+                tool: tool.clone(),
+                version,
+            });
+        }
+    }
+
+    Ok(runtimes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use crate::traits::tool_version_manager::tests::MockToolVersionManager;
+
+    fn runtime(tool: &str, version: &str) -> RuntimeVersion {
+        RuntimeVersion {
+            tool: tool.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_tool_versions() {
+        let runtimes =
+            parse_tool_versions("nodejs 20.11.0 18.19.0\n# comment\n\npython 3.12.1 # pinned\n");
+        assert_eq!(
+            runtimes,
+            vec![
+                runtime("nodejs", "20.11.0"),
+                runtime("nodejs", "18.19.0"),
+                runtime("python", "3.12.1"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_mise_toml() {
+        let content = r#"
+[env]
+EDITOR = "nvim"
+
+[tools]
+node = "20"
+python = ["3.12", "3.11"]
+go = { version = "1.22" }
+"#;
+        let mut runtimes = parse_mise_toml("mise.toml", content).unwrap();
+        runtimes.sort_by(|a, b| (&a.tool, &a.version).cmp(&(&b.tool, &b.version)));
+        assert_eq!(
+            runtimes,
+            vec![
+                runtime("go", "1.22"),
+                runtime("node", "20"),
+                runtime("python", "3.11"),
+                runtime("python", "3.12"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_runtimes_prefers_available_manager() {
+        let filesystem = MockFileSystem::new();
+        filesystem.add_file("/repo/.tool-versions", "nodejs 20.11.0\npython 3.12.1\n");
+
+        let mise =
+            MockToolVersionManager::new("mise", &["mise.toml", ".tool-versions"]).unavailable();
+        let asdf = MockToolVersionManager::new("asdf", &[".tool-versions"]);
+        asdf.add_installed("python", "3.12.1");
+        let managers: Vec<Box<dyn ToolVersionManager>> = vec![Box::new(mise), Box::new(asdf)];
+
+        let check = check_runtimes(&filesystem, &managers, "/repo")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert_eq!(check.manager.name(), "asdf");
+        assert!(check.manager_available);
+        assert_eq!(check.files, vec![".tool-versions"]);
+        assert_eq!(check.missing, vec![runtime("nodejs", "20.11.0")]);
+    }
+
+    #[tokio::test]
+    async fn test_check_runtimes_without_declarations() {
+        let filesystem = MockFileSystem::new();
+        let managers = default_managers();
+        assert!(check_runtimes(&filesystem, &managers, "/repo")
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
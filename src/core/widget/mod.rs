@@ -0,0 +1,292 @@
+use serde_json::json;
+
+use crate::core::config::WidgetSettings;
+use crate::core::history::StatusSnapshot;
+
+/// Seconds a cached status is shown before a background refresh is started
+pub const DEFAULT_MAX_AGE: u64 = 300;
+
+const DEFAULT_TMUX_TEMPLATE: &str = "#[fg={color}]dotf {summary}#[default]";
+const DEFAULT_WAYBAR_TEMPLATE: &str = "dotf {summary}";
+
+/// Status bars `dotf status --widget` can print a segment for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WidgetFormat {
+    /// tmux format string with `#[fg=...]` colors
+    Tmux,
+    /// Waybar custom module JSON (`text`, `tooltip`, `class`)
+    Waybar,
+}
+
+/// Overall state of a snapshot, also used as the Waybar CSS class
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WidgetState {
+    Ok,
+    /// Behind the remote or with local changes
+    Attention,
+    /// Symlink problems
+    Error,
+    /// No status recorded yet
+    Unknown,
+}
+
+impl WidgetState {
+    fn of(snapshot: Option<&StatusSnapshot>) -> Self {
+        match snapshot {
+            None => WidgetState::Unknown,
+            Some(snapshot) if snapshot.issues() > 0 => WidgetState::Error,
+            Some(snapshot)
+                if snapshot.behind > 0 || !snapshot.is_clean || snapshot.modified > 0 =>
+            {
+                WidgetState::Attention
+            }
+            Some(_) => WidgetState::Ok,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WidgetState::Ok => "ok",
+            WidgetState::Attention => "attention",
+            WidgetState::Error => "error",
+            WidgetState::Unknown => "unknown",
+        }
+    }
+
+    fn tmux_color(&self) -> &'static str {
+        match self {
+            WidgetState::Ok => "green",
+            WidgetState::Attention => "yellow",
+            WidgetState::Error => "red",
+            WidgetState::Unknown => "colour244",
+        }
+    }
+}
+
+/// Render the status bar segment for `snapshot`, the latest cached status
+pub fn render_widget(
+    format: WidgetFormat,
+    snapshot: Option<&StatusSnapshot>,
+    settings: &WidgetSettings,
+) -> String {
+    let state = WidgetState::of(snapshot);
+    match format {
+        WidgetFormat::Tmux => {
+            let template = settings.tmux.as_deref().unwrap_or(DEFAULT_TMUX_TEMPLATE);
+            fill(template, snapshot, state)
+        }
+        WidgetFormat::Waybar => {
+            let template = settings
+                .waybar
+                .as_deref()
+                .unwrap_or(DEFAULT_WAYBAR_TEMPLATE);
+            json!({
+                "text": fill(template, snapshot, state),
+                "tooltip": tooltip(snapshot),
+                "class": state.name(),
+            })
+            .to_string()
+        }
+    }
+}
+
+fn fill(template: &str, snapshot: Option<&StatusSnapshot>, state: WidgetState) -> String {
+    let count = |value: fn(&StatusSnapshot) -> usize| {
+        snapshot
+            .map(|snapshot| value(snapshot).to_string())
+            .unwrap_or_default()
+    };
+    template
+        .replace("{color}", state.tmux_color())
+        .replace("{summary}", &summary(snapshot))
+        .replace("{state}", state.name())
+        .replace("{behind}", &count(|s| s.behind))
+        .replace("{ahead}", &count(|s| s.ahead))
+        .replace("{issues}", &count(|s| s.issues()))
+        .replace("{modified}", &count(|s| s.modified))
+}
+
+/// Short markers for what needs attention, e.g. `⇣3 ✗1`; `✓` when nothing does
+fn summary(snapshot: Option<&StatusSnapshot>) -> String {
+    let Some(snapshot) = snapshot else {
+        return "?".to_string();
+    };
+
+    let mut parts = Vec::new();
+    if snapshot.behind > 0 {
+        parts.push(format!("⇣{}", snapshot.behind));
+    }
+    if snapshot.ahead > 0 {
+        parts.push(format!("⇡{}", snapshot.ahead));
+    }
+    if snapshot.issues() > 0 {
+        parts.push(format!("✗{}", snapshot.issues()));
+    }
+    if !snapshot.is_clean || snapshot.modified > 0 {
+        parts.push("*".to_string());
+    }
+
+    if parts.is_empty() {
+        "✓".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Lines for `dotf greet`: one for the repository and one for the links, each with the
+/// command that fixes it; none when nothing needs attention
+pub fn render_greeting(snapshot: &StatusSnapshot) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    let mut repository = Vec::new();
+    if snapshot.behind > 0 {
+        repository.push(count(snapshot.behind, "commit", "commits") + " behind");
+    }
+    if snapshot.ahead > 0 {
+        repository.push(count(snapshot.ahead, "commit", "commits") + " not pushed");
+    }
+    if !snapshot.is_clean {
+        repository.push("uncommitted changes".to_string());
+    }
+    if !repository.is_empty() {
+        let command = if snapshot.behind > 0 {
+            "dotf sync"
+        } else {
+            "dotf sync --push"
+        };
+        lines.push(greeting_line(&repository, command));
+    }
+
+    let mut links = Vec::new();
+    for (value, one, many) in [
+        (snapshot.broken, "broken symlink", "broken symlinks"),
+        (snapshot.missing, "missing symlink", "missing symlinks"),
+        (snapshot.conflicts, "conflict", "conflicts"),
+        (
+            snapshot.invalid_targets,
+            "invalid target",
+            "invalid targets",
+        ),
+        (snapshot.modified, "modified target", "modified targets"),
+    ] {
+        if value > 0 {
+            links.push(count(value, one, many));
+        }
+    }
+    if !links.is_empty() {
+        let command = if snapshot.issues() > 0 {
+            "dotf repair"
+        } else {
+            "dotf diff"
+        };
+        lines.push(greeting_line(&links, command));
+    }
+
+    lines
+}
+
+fn greeting_line(parts: &[String], command: &str) -> String {
+    format!("dotfiles: {} — run `{}`", parts.join(", "), command)
+}
+
+fn count(value: usize, one: &str, many: &str) -> String {
+    format!("{} {}", value, if value == 1 { one } else { many })
+}
+
+fn tooltip(snapshot: Option<&StatusSnapshot>) -> String {
+    let Some(snapshot) = snapshot else {
+        return "No status recorded yet".to_string();
+    };
+
+    format!(
+        "{} behind, {} ahead, {} symlink issue(s), {} modified\nas of {}",
+        snapshot.behind,
+        snapshot.ahead,
+        snapshot.issues(),
+        snapshot.modified,
+        snapshot
+            .taken_at
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn snapshot(behind: usize, broken: usize) -> StatusSnapshot {
+        StatusSnapshot {
+            taken_at: Utc::now(),
+            total: 4,
+            valid: 4 - broken,
+            missing: 0,
+            broken,
+            conflicts: 0,
+            invalid_targets: 0,
+            modified: 0,
+            is_clean: true,
+            ahead: 0,
+            behind,
+        }
+    }
+
+    #[test]
+    fn test_render_tmux() {
+        let settings = WidgetSettings::default();
+
+        assert_eq!(
+            render_widget(WidgetFormat::Tmux, Some(&snapshot(3, 0)), &settings),
+            "#[fg=yellow]dotf ⇣3#[default]"
+        );
+        assert_eq!(
+            render_widget(WidgetFormat::Tmux, Some(&snapshot(0, 0)), &settings),
+            "#[fg=green]dotf ✓#[default]"
+        );
+
+        let custom = WidgetSettings {
+            tmux: Some("#[fg={color}]{behind}/{issues}".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_widget(WidgetFormat::Tmux, Some(&snapshot(2, 1)), &custom),
+            "#[fg=red]2/1"
+        );
+    }
+
+    #[test]
+    fn test_render_greeting() {
+        assert!(render_greeting(&snapshot(0, 0)).is_empty());
+        assert_eq!(
+            render_greeting(&snapshot(2, 1)),
+            vec![
+                "dotfiles: 2 commits behind — run `dotf sync`",
+                "dotfiles: 1 broken symlink — run `dotf repair`"
+            ]
+        );
+
+        let unpushed = StatusSnapshot {
+            ahead: 1,
+            is_clean: false,
+            modified: 2,
+            ..snapshot(0, 0)
+        };
+        assert_eq!(
+            render_greeting(&unpushed),
+            vec![
+                "dotfiles: 1 commit not pushed, uncommitted changes — run `dotf sync --push`",
+                "dotfiles: 2 modified targets — run `dotf diff`"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_waybar() {
+        let output = render_widget(WidgetFormat::Waybar, None, &WidgetSettings::default());
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+        assert_eq!(value["text"], "dotf ?");
+        assert_eq!(value["class"], "unknown");
+    }
+}
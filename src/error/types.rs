@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::core::symlinks::BackupError;
+
 pub type DotfResult<T> = Result<T, DotfError>;
 
 #[derive(Error, Debug)]
@@ -28,6 +30,9 @@ pub enum DotfError {
     #[error("Symlink error: {0}")]
     Symlink(String),
 
+    #[error("Backup error: {0}")]
+    Backup(#[from] BackupError),
+
     #[error("User cancelled operation")]
     UserCancelled,
 
@@ -1,50 +1,576 @@
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use dotf::cli::{
+    args::{
+        BackupsAction, InstallTarget, ProfileAction, PromptsAction, ReposAction, SecretsAction,
+        SettingsAction, SymlinksAction,
+    },
     commands::{
-        handle_config, handle_init, handle_install, handle_schema, handle_status, handle_symlinks,
-        handle_sync,
+        alias::{builtin_commands, global_args},
+        handle_add, handle_adopt, handle_alias, handle_apply_plan, handle_backups, handle_changes,
+        handle_completions, handle_config, handle_diff, handle_disable, handle_doctor,
+        handle_enable, handle_export, handle_generate, handle_greet, handle_import, handle_init,
+        handle_install, handle_log, handle_macos_defaults, handle_maintain, handle_menu,
+        handle_profile, handle_prompts, handle_recover, handle_render, handle_repair, handle_repos,
+        handle_sandbox, handle_schema, handle_secrets, handle_settings, handle_status,
+        handle_status_fail_on, handle_symlinks, handle_sync, handle_uninstall, handle_watch,
     },
     Cli, Commands, MessageFormatter,
 };
+use dotf::core::{
+    aliases::expand_aliases,
+    config::Settings,
+    features,
+    filesystem::RealFileSystem,
+    lock::OperationLock,
+    repository::RepositoryRegistry,
+    timings::{self, TimingStore},
+};
 use dotf::error::DotfResult;
+use dotf::services::SettingsService;
+use dotf::traits::{filesystem::FileSystem, prompt::Prompt};
+use dotf::utils::{logging, ConsolePrompt};
+use std::io::IsTerminal;
+use std::path::Path;
 use std::process;
 
 #[tokio::main]
 async fn main() {
     let formatter = MessageFormatter::new();
 
+    // The privileged helper runs as root through sudo. It skips the rest of startup
+    // (timings, settings checks, logging, the lock), which would leave root-owned files
+    // in the user's ~/.dotf.
+    if let Ok(Cli {
+        command: Commands::ApplyPlan { file },
+        ..
+    }) = Cli::try_parse()
+    {
+        if let Err(err) = handle_apply_plan(file).await {
+            eprintln!("{}", formatter.error(&format!("Error: {}", err)));
+            process::exit(1);
+        }
+        return;
+    }
+
     if let Err(err) = run().await {
+        tracing::error!(error = %err, "command failed");
         eprintln!("{}", formatter.error(&format!("Error: {}", err)));
         process::exit(1);
     }
 }
 
 async fn run() -> DotfResult<()> {
-    let cli = Cli::parse();
+    // Arguments that are not valid UTF-8 cannot name an alias; leave them to clap
+    let args = match std::env::args_os()
+        .skip(1)
+        .map(|arg| arg.into_string())
+        .collect::<Result<Vec<_>, _>>()
+    {
+        Ok(args) => args,
+        Err(_) => return run_command(Cli::parse()).await,
+    };
+
+    // A bare `dotf` at a terminal offers a menu; elsewhere clap prints the help as before
+    if args.is_empty() && std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+        let Some(command_line) = handle_menu(&ConsolePrompt::new()).await? else {
+            return Ok(());
+        };
+        return run_timed(command_line).await;
+    }
+
+    // An alias may expand to several commands; each must succeed before the next runs
+    for command_line in expand_command_line(&args)? {
+        run_timed(command_line).await?;
+    }
+
+    Ok(())
+}
 
-    match cli.command {
-        Commands::Init { repo } => {
-            handle_init(repo).await?;
+/// Run one command line and keep its phase timings for `dotf doctor --slow`; failing
+/// to save them never fails the command
+async fn run_timed(command_line: Vec<String>) -> DotfResult<()> {
+    let matches =
+        Cli::command().get_matches_from(std::iter::once("dotf".to_string()).chain(command_line));
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    timings::start(matches.subcommand_name().unwrap_or_default());
+    let result = run_command(cli).await;
+    if let Some(invocation) = timings::finish() {
+        if let Err(e) = TimingStore::new(RealFileSystem::new())
+            .append(invocation)
+            .await
+        {
+            tracing::debug!(error = %e, "could not save timings");
+        }
+    }
+    result
+}
+
+/// Expand a user alias from settings.toml; without readable settings there are no aliases
+fn expand_command_line(args: &[String]) -> DotfResult<Vec<Vec<String>>> {
+    let aliases = read_settings()
+        .map(|settings| settings.aliases)
+        .unwrap_or_default();
+    if aliases.is_empty() {
+        return Ok(vec![args.to_vec()]);
+    }
+
+    let builtins = builtin_commands();
+    let builtins: Vec<&str> = builtins.iter().map(String::as_str).collect();
+    expand_aliases(args, &aliases, &builtins, &global_args())
+}
+
+fn read_settings() -> Option<Settings> {
+    let settings_path = RealFileSystem::new().dotf_settings_path();
+    std::fs::read_to_string(settings_path)
+        .ok()
+        .and_then(|content| Settings::from_toml(&content).ok())
+}
+
+/// Refuse experimental commands that are not turned on, and label the ones that are.
+/// The label goes to stderr so `--json` output stays parseable.
+fn check_experiment(command: &Commands, flags: &[String]) -> DotfResult<()> {
+    let settings = read_settings()
+        .map(|settings| settings.features)
+        .unwrap_or_default();
+    let enabled = features::enabled(&settings, flags)?;
+    if let Some(name) = experiment(command) {
+        features::require(name, &enabled)?;
+        if command.silent() {
+            return Ok(());
+        }
+        eprintln!(
+            "{}",
+            MessageFormatter::new().warning(&format!(
+                "Experimental feature '{}': output and options may change",
+                name
+            ))
+        );
+    }
+    Ok(())
+}
+
+async fn run_command(cli: Cli) -> DotfResult<()> {
+    init_logging(cli.verbose);
+    // `status --fail-on` prints nothing; an error, too, is only told by the exit code
+    if cli.command.silent() {
+        if let Err(e) = execute(cli).await {
+            tracing::error!(error = %e, "command failed");
+            process::exit(1);
+        }
+        return Ok(());
+    }
+    execute(cli).await
+}
+
+async fn execute(cli: Cli) -> DotfResult<()> {
+    let interactive = !cli.yes && !cli.command.json();
+    check_settings(&cli.command, interactive).await?;
+    let conflict_policy = cli.conflict_policy();
+    check_experiment(&cli.command, &cli.enable_features)?;
+    let enable_features = cli.enable_features;
+    let command = cli.command;
+    let _lock = match lock_name(&command) {
+        Some(name) => {
+            let dotf_dir = RealFileSystem::new().dotf_directory();
+            check_interrupted(Path::new(&dotf_dir), &command, interactive).await?;
+            Some(OperationLock::acquire(Path::new(&dotf_dir), name)?)
+        }
+        None => None,
+    };
+
+    match command {
+        Commands::Init {
+            repo,
+            sparse,
+            from_local,
+        } => {
+            handle_init(repo, sparse, from_local).await?;
         }
         Commands::Install { target } => {
-            handle_install(target).await?;
+            handle_install(target, cli.yes, conflict_policy, cli.metered).await?;
+        }
+        Commands::Uninstall {
+            all,
+            keep_backups,
+            dry_run,
+            ..
+        } => {
+            handle_uninstall(all, keep_backups, dry_run, cli.yes).await?;
+        }
+        Commands::Status {
+            quiet,
+            since,
+            widget,
+            json,
+            packages,
+            repo,
+            check,
+            fail_on,
+            fetch,
+        } => {
+            if !fail_on.is_empty() {
+                handle_status_fail_on(repo, &fail_on, fetch).await?;
+            } else {
+                handle_status(quiet, since, widget, json, packages, repo, check, fetch).await?;
+            }
         }
-        Commands::Status { quiet } => {
-            handle_status(quiet).await?;
+        Commands::Greet => {
+            handle_greet().await;
         }
-        Commands::Sync { force } => {
-            handle_sync(force).await?;
+        Commands::Sync {
+            force,
+            autostash,
+            push,
+            message,
+            repo,
+            json,
+        } => {
+            handle_sync(
+                force,
+                autostash,
+                push,
+                message.as_deref(),
+                cli.yes,
+                repo.as_deref(),
+                cli.metered,
+                json,
+            )
+            .await?;
+        }
+        Commands::Add { target, source } => {
+            handle_add(target, source).await?;
+        }
+        Commands::Watch { action } => {
+            handle_watch(action).await?;
+        }
+        Commands::Adopt {
+            target,
+            commit,
+            message,
+        } => {
+            handle_adopt(target, commit, message).await?;
+        }
+        Commands::Log { target, limit } => {
+            handle_log(target, limit).await?;
+        }
+        Commands::Changes { json } => {
+            handle_changes(json).await?;
         }
         Commands::Symlinks { action } => {
-            handle_symlinks(action).await?;
+            handle_symlinks(action, cli.yes).await?;
+        }
+        Commands::Backups { action } => {
+            handle_backups(action).await?;
+        }
+        Commands::Settings { action } => {
+            handle_settings(action).await?;
+        }
+        Commands::Repos { action } => {
+            handle_repos(action).await?;
+        }
+        Commands::Repair { dry_run, json } => {
+            handle_repair(dry_run, json, conflict_policy).await?;
+        }
+        Commands::Render { stale, force } => {
+            handle_render(stale, force).await?;
+        }
+        Commands::Diff { target, tool } => {
+            handle_diff(target, tool).await?;
         }
-        Commands::Config { repo, edit } => {
-            handle_config(repo, edit).await?;
+        Commands::Recover { repo } => {
+            handle_recover(repo).await?;
+        }
+        Commands::Doctor { slow } => {
+            handle_doctor(slow, enable_features).await?;
+        }
+        Commands::MacosDefaults { action } => {
+            handle_macos_defaults(action).await?;
+        }
+        Commands::Prompts { action } => {
+            handle_prompts(action).await?;
+        }
+        Commands::Secrets { action } => {
+            handle_secrets(action).await?;
+        }
+        Commands::Disable { target } => {
+            handle_disable(target).await?;
+        }
+        Commands::Enable { target } => {
+            handle_enable(target).await?;
+        }
+        Commands::Profile { action } => {
+            handle_profile(action).await?;
+        }
+        Commands::Config { action, repo, edit } => {
+            handle_config(action, repo, edit).await?;
         }
         Commands::Schema { action } => {
-            handle_schema(action).await?;
+            handle_schema(action, cli.yes).await?;
+        }
+        Commands::Import { source } => {
+            handle_import(source).await?;
+        }
+        Commands::Completions { shell, install } => {
+            handle_completions(shell, install).await?;
+        }
+        Commands::Export { format } => {
+            handle_export(format).await?;
+        }
+        Commands::Generate { target } => {
+            handle_generate(target).await?;
+        }
+        Commands::Sandbox {
+            with_deps,
+            container,
+            image,
+            keep,
+        } => {
+            handle_sandbox(with_deps, container, &image, keep).await?;
+        }
+        Commands::Alias { action } => {
+            handle_alias(action).await?;
+        }
+        Commands::Maintain {
+            backup_days,
+            offline,
+        } => {
+            handle_maintain(backup_days, offline).await?;
+        }
+        // Handled in `main` before any of the above
+        Commands::ApplyPlan { file } => {
+            handle_apply_plan(file).await?;
         }
     }
 
     Ok(())
 }
+
+/// Warn about a command that crashed or was killed while holding the lock, and offer to
+/// repair the symlinks it may have left half done before running the next one
+async fn check_interrupted(
+    dotf_dir: &Path,
+    command: &Commands,
+    interactive: bool,
+) -> DotfResult<()> {
+    let Some(stale) = OperationLock::stale_holder(dotf_dir) else {
+        return Ok(());
+    };
+
+    let formatter = MessageFormatter::new();
+    eprintln!(
+        "{}",
+        formatter.warning(&format!(
+            "The previous 'dotf {}' (pid {}, started {}) did not finish",
+            stale.command,
+            stale.pid,
+            stale.started_at.format("%Y-%m-%d %H:%M:%S UTC")
+        ))
+    );
+
+    // Only link-changing commands leave state that 'symlinks repair' can fix
+    let touched_links = LINK_CHANGING_LOCKS.contains(&stale.command.as_str());
+    if !touched_links {
+        eprintln!(
+            "{}",
+            formatter.info("Check 'dotf status' before relying on the repository")
+        );
+        return Ok(());
+    }
+    let repairing = matches!(
+        command,
+        Commands::Symlinks {
+            action: Some(SymlinksAction::Repair { .. })
+        } | Commands::Repair { .. }
+    );
+    if repairing {
+        return Ok(());
+    }
+
+    if interactive && std::io::stdin().is_terminal() {
+        let repair = ConsolePrompt::new()
+            .confirm(&formatter.question("Run 'dotf symlinks repair' first?"))
+            .await?;
+        if repair {
+            handle_symlinks(
+                Some(SymlinksAction::Repair {
+                    include_export_ignored: false,
+                }),
+                false,
+            )
+            .await?;
+        }
+    } else {
+        eprintln!(
+            "{}",
+            formatter.info("Run 'dotf symlinks repair' to fix links it left behind")
+        );
+    }
+    Ok(())
+}
+
+/// Keep settings.toml.bak up to date, and offer to repair a settings.toml that no longer
+/// parses before the command fails on it
+async fn check_settings(command: &Commands, interactive: bool) -> DotfResult<()> {
+    let settings_service = SettingsService::new(RepositoryRegistry::new(), RealFileSystem::new());
+    if let Err(e) = settings_service.keep_backup().await {
+        tracing::debug!(error = %e, "could not back up settings");
+    }
+
+    // These work without settings, or are how they get fixed
+    let exempt = matches!(
+        command,
+        Commands::Init { .. }
+            | Commands::Settings { .. }
+            | Commands::Recover { .. }
+            | Commands::Greet
+            | Commands::Schema { .. }
+            | Commands::Completions { .. }
+            | Commands::Status {
+                widget: Some(_),
+                ..
+            }
+    );
+    // `status --fail-on` prints nothing; settings it cannot read show in its exit code
+    if exempt || command.silent() || settings_service.diagnose().await.ok().flatten().is_none() {
+        return Ok(());
+    }
+
+    let formatter = MessageFormatter::new();
+    if interactive && std::io::stdin().is_terminal() && std::io::stdout().is_terminal() {
+        eprintln!("{}", formatter.warning("settings.toml cannot be read"));
+        let repair = ConsolePrompt::new()
+            .confirm(&formatter.question("Repair it now?"))
+            .await?;
+        if repair {
+            handle_settings(SettingsAction::Repair {
+                restore_backup: false,
+                from_clone: false,
+                init: None,
+            })
+            .await?;
+        }
+    } else {
+        eprintln!(
+            "{}",
+            formatter.warning("settings.toml cannot be read; run 'dotf settings repair'")
+        );
+    }
+    Ok(())
+}
+
+/// Set up `-v` output and the log file from `[logging]` in settings.toml; a log file
+/// that cannot be opened is reported without stopping the command
+fn init_logging(verbosity: u8) {
+    let filesystem = RealFileSystem::new();
+    let logging = std::fs::read_to_string(filesystem.dotf_settings_path())
+        .ok()
+        .and_then(|content| Settings::from_toml(&content).ok())
+        .map(|settings| settings.logging)
+        .unwrap_or_default();
+    if let Err(e) = logging::init(verbosity, &logging, Path::new(&filesystem.dotf_logs_path())) {
+        eprintln!("{}", MessageFormatter::new().warning(&e.to_string()));
+    }
+    tracing::debug!(args = ?std::env::args().skip(1).collect::<Vec<_>>(), "dotf started");
+}
+
+/// The experiment a command belongs to, if it is still experimental
+fn experiment(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Changes { .. } => Some("changes"),
+        Commands::Repos { .. }
+        | Commands::Sync { repo: Some(_), .. }
+        | Commands::Status { repo: Some(_), .. }
+        | Commands::Install {
+            target: InstallTarget::Config { repo: Some(_), .. },
+        } => Some("overlays"),
+        _ => None,
+    }
+}
+
+/// Lock names from `lock_name` of the commands that create, replace or remove links
+const LINK_CHANGING_LOCKS: &[&str] = &[
+    "install",
+    "uninstall",
+    "sync",
+    "add",
+    "adopt",
+    "symlinks",
+    "repair",
+    "render",
+    "recover",
+    "import",
+    "maintain",
+];
+
+/// Commands that change the repository, ~/.dotf or the home directory and must not run
+/// concurrently. Every command is listed, so a new one has to decide.
+fn lock_name(command: &Commands) -> Option<&'static str> {
+    match command {
+        Commands::Init { .. } => Some("init"),
+        Commands::Install {
+            target: InstallTarget::Config { dry_run: true, .. },
+        } => None,
+        Commands::Install { .. } => Some("install"),
+        Commands::Uninstall { dry_run: true, .. } => None,
+        Commands::Uninstall { .. } => Some("uninstall"),
+        Commands::Sync { .. } => Some("sync"),
+        Commands::Add { .. } => Some("add"),
+        Commands::Adopt { .. } => Some("adopt"),
+        Commands::Symlinks { .. } => Some("symlinks"),
+        Commands::Backups { action } => match action {
+            BackupsAction::Prune { .. } => Some("backups"),
+            BackupsAction::List => None,
+        },
+        Commands::Repos { action } => match action {
+            ReposAction::Add { .. } | ReposAction::Remove { .. } => Some("repos"),
+            ReposAction::List => None,
+        },
+        Commands::Settings { action } => match action {
+            SettingsAction::Import { .. } | SettingsAction::Repair { .. } => Some("settings"),
+            SettingsAction::Export { .. } => None,
+        },
+        Commands::Repair { dry_run: true, .. } => None,
+        Commands::Repair { .. } => Some("repair"),
+        Commands::Render { .. } => Some("render"),
+        Commands::Recover { .. } => Some("recover"),
+        Commands::Prompts { action } => match action {
+            PromptsAction::Edit { .. } => Some("prompts"),
+            PromptsAction::List => None,
+        },
+        Commands::Secrets { action } => match action {
+            SecretsAction::Encrypt { .. } | SecretsAction::Decrypt { .. } => Some("secrets"),
+            SecretsAction::Status => None,
+        },
+        Commands::Disable { target: Some(_) } => Some("disable"),
+        Commands::Disable { target: None } => None,
+        Commands::Enable { .. } => Some("enable"),
+        Commands::Profile { action } => match action {
+            ProfileAction::Use { .. } | ProfileAction::Clear => Some("profile"),
+            ProfileAction::List => None,
+        },
+        Commands::Import { .. } => Some("import"),
+        Commands::Maintain { .. } => Some("maintain"),
+        // The watcher takes the lock for each batch of changes, so commands run in between
+        Commands::Watch { .. } => None,
+        // Run by a command that already holds the lock
+        Commands::ApplyPlan { .. } => None,
+        // Read only, or writing outside ~/.dotf and the links: into the current
+        // directory, a throwaway home, shell completions or macOS preferences. The
+        // config editor is left unlocked so an open editor does not block other commands.
+        Commands::Status { .. }
+        | Commands::Greet
+        | Commands::Log { .. }
+        | Commands::Changes { .. }
+        | Commands::Diff { .. }
+        | Commands::Doctor { .. }
+        | Commands::MacosDefaults { .. }
+        | Commands::Config { .. }
+        | Commands::Schema { .. }
+        | Commands::Completions { .. }
+        | Commands::Export { .. }
+        | Commands::Generate { .. }
+        | Commands::Sandbox { .. }
+        | Commands::Alias { .. } => None,
+    }
+}
@@ -0,0 +1,318 @@
+use std::path::Path;
+
+use crate::core::symlinks::{LinkMode, Permissions, SymlinkManager, SymlinkOperation};
+use crate::error::{DotfError, DotfResult};
+use crate::services::RepairService;
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+
+#[derive(Debug, Clone)]
+pub struct AddResult {
+    pub target_path: String,
+    pub source_path: String,
+    /// Key written to `[symlinks]`, relative to the repository root
+    pub config_key: String,
+    /// Value written for the key; `~/...` for targets under the home directory
+    pub config_target: String,
+}
+
+/// Brings an unmanaged file or directory under dotf: moves it into the repository,
+/// links it back and adds the mapping to `[symlinks]` without reformatting dotf.toml
+pub struct AddService<F, P> {
+    filesystem: F,
+    repair_service: RepairService<F, P>,
+    symlink_manager: SymlinkManager<F, P>,
+}
+
+impl<F: FileSystem + Clone, P: Prompt> AddService<F, P> {
+    pub fn new(filesystem: F, prompt: P) -> Self {
+        Self {
+            repair_service: RepairService::new(filesystem.clone(), prompt.clone()),
+            symlink_manager: SymlinkManager::new(filesystem.clone(), prompt),
+            filesystem,
+        }
+    }
+
+    /// Move `target_path` to `source` in the repository (by default its path under the
+    /// home directory without the leading dot) and replace it with a symlink
+    pub async fn add(&self, target_path: &str, source: Option<&str>) -> DotfResult<AddResult> {
+        let repo_path = self.repair_service.repo_path().await?;
+        self.check_target(target_path, &repo_path).await?;
+
+        let config_key = match source {
+            Some(source) => source
+                .trim_start_matches("./")
+                .trim_matches('/')
+                .to_string(),
+            None => default_source(target_path)?,
+        };
+        if config_key.is_empty() || config_key.split('/').any(|part| part == "..") {
+            return Err(DotfError::Operation(format!(
+                "'{}' is not a path inside the repository",
+                config_key
+            )));
+        }
+        let source_path = format!("{}/{}", repo_path, config_key);
+        if self.filesystem.exists(&source_path).await? {
+            return Err(DotfError::Operation(format!(
+                "'{}' already exists in the repository; choose another path with --source",
+                config_key
+            )));
+        }
+
+        let config_path = format!("{}/dotf.toml", repo_path);
+        let config_target = display_target(target_path);
+        let content = self.filesystem.read_to_string(&config_path).await?;
+        let updated = add_symlink_entry(&content, &config_key, &config_target)?;
+
+        let mut operations = Vec::new();
+        self.copy_tree(target_path, &source_path, &mut operations)
+            .await?;
+        if let Err(e) = self.filesystem.write(&config_path, &updated).await {
+            self.remove_path(&source_path).await?;
+            return Err(e);
+        }
+
+        // Directories stay in place and get a link per file, as `dotf install config`
+        // creates them
+        for operation in &operations {
+            self.filesystem.remove_file(&operation.target_path).await?;
+        }
+        self.symlink_manager
+            .create_symlinks(&operations, false)
+            .await
+            .map_err(|e| {
+                DotfError::Operation(format!(
+                    "Moved {} to {} but could not link it back ({}); run 'dotf install config' to retry",
+                    target_path, source_path, e
+                ))
+            })?;
+
+        Ok(AddResult {
+            target_path: target_path.to_string(),
+            source_path,
+            config_key,
+            config_target,
+        })
+    }
+
+    /// Only plain files and directories outside dotf's own tree that no entry manages yet
+    async fn check_target(&self, target_path: &str, repo_path: &str) -> DotfResult<()> {
+        if !self.filesystem.exists(target_path).await? {
+            return Err(DotfError::Operation(format!(
+                "'{}' does not exist",
+                target_path
+            )));
+        }
+        if self.filesystem.is_symlink(target_path).await? {
+            return Err(DotfError::Operation(format!(
+                "'{}' is a symlink; only regular files and directories can be added",
+                target_path
+            )));
+        }
+
+        let dotf_directory = self.filesystem.dotf_directory();
+        for ancestor in Path::new(target_path).ancestors() {
+            let ancestor = ancestor.to_string_lossy();
+            let inside_dotf = ancestor == repo_path || ancestor == dotf_directory;
+            let linked_into_repo = ancestor != target_path
+                && self.filesystem.is_symlink(&ancestor).await?
+                && self
+                    .filesystem
+                    .read_link(&ancestor)
+                    .await?
+                    .starts_with(repo_path);
+            if inside_dotf || linked_into_repo {
+                return Err(DotfError::Operation(format!(
+                    "'{}' is already inside the dotfiles repository",
+                    target_path
+                )));
+            }
+        }
+
+        if self.repair_service.find_entry(target_path).await?.is_some() {
+            return Err(DotfError::Operation(format!(
+                "'{}' is already a target in dotf.toml",
+                target_path
+            )));
+        }
+        Ok(())
+    }
+
+    /// Copy `from` to `to`, collecting the link from each copied file back to its original
+    async fn copy_tree(
+        &self,
+        from: &str,
+        to: &str,
+        operations: &mut Vec<SymlinkOperation>,
+    ) -> DotfResult<()> {
+        if !self.filesystem.is_dir(from).await? {
+            if let Some(parent) = Path::new(to).parent() {
+                self.filesystem
+                    .create_dir_all(&parent.to_string_lossy())
+                    .await?;
+            }
+            self.filesystem.copy_file(from, to).await?;
+            operations.push(SymlinkOperation {
+                source_path: to.to_string(),
+                target_path: from.to_string(),
+                mode: LinkMode::Symlink,
+                permissions: Permissions::default(),
+            });
+            return Ok(());
+        }
+
+        self.filesystem.create_dir_all(to).await?;
+        for entry in self.filesystem.list_entries(from).await? {
+            let name = Path::new(&entry.path)
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let destination = format!("{}/{}", to, name);
+            if entry.is_symlink {
+                let link = self.filesystem.read_link(&entry.path).await?;
+                self.filesystem
+                    .create_symlink(&link.to_string_lossy(), &destination)
+                    .await?;
+                operations.push(SymlinkOperation {
+                    source_path: destination,
+                    target_path: entry.path,
+                    mode: LinkMode::Symlink,
+                    permissions: Permissions::default(),
+                });
+            } else {
+                Box::pin(self.copy_tree(&entry.path, &destination, operations)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn remove_path(&self, path: &str) -> DotfResult<()> {
+        if self.filesystem.is_dir(path).await? {
+            self.filesystem.remove_dir(path).await
+        } else {
+            self.filesystem.remove_file(path).await
+        }
+    }
+}
+
+/// `~/.config/foo/config` becomes `config/foo/config`, so nothing in the repository is hidden
+fn default_source(target_path: &str) -> DotfResult<String> {
+    let home = dirs::home_dir()
+        .ok_or_else(|| DotfError::Operation("Could not determine home directory".to_string()))?;
+    let relative = Path::new(target_path).strip_prefix(&home).map_err(|_| {
+        DotfError::Operation(format!(
+            "'{}' is outside the home directory; choose its repository path with --source",
+            target_path
+        ))
+    })?;
+    Ok(relative
+        .to_string_lossy()
+        .trim_start_matches('.')
+        .to_string())
+}
+
+fn display_target(target_path: &str) -> String {
+    match dirs::home_dir().and_then(|home| {
+        Path::new(target_path)
+            .strip_prefix(home)
+            .ok()
+            .map(Path::to_path_buf)
+    }) {
+        Some(relative) => format!("~/{}", relative.to_string_lossy()),
+        None => target_path.to_string(),
+    }
+}
+
+/// Append `source = target` to `[symlinks]`, keeping comments and layout of the rest
+fn add_symlink_entry(content: &str, source: &str, target: &str) -> DotfResult<String> {
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
+    let symlinks = document
+        .entry("symlinks")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| DotfError::Config("[symlinks] in dotf.toml is not a table".to_string()))?;
+    if symlinks.contains_key(source) {
+        return Err(DotfError::Operation(format!(
+            "'{}' is already a source in dotf.toml",
+            source
+        )));
+    }
+    symlinks.insert(source, toml_edit::value(target));
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Settings;
+    use crate::traits::{filesystem::tests::MockFileSystem, prompt::tests::MockPrompt};
+
+    const CONFIG: &str = r#"# My dotfiles
+[symlinks]
+"zsh/.zshrc" = "~/.zshrc"   # shell
+
+[scripts.custom]
+fonts = "scripts/fonts.sh"
+"#;
+
+    #[tokio::test]
+    async fn test_add_moves_file_and_keeps_config_layout() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(&format!("{}/dotf.toml", repo), CONFIG);
+        filesystem.add_file("/home/user/.config/foo/config", "answer = 42\n");
+
+        let service = AddService::new(filesystem.clone(), MockPrompt::new());
+        let result = service
+            .add("/home/user/.config/foo/config", Some("foo/config"))
+            .await
+            .unwrap();
+        assert_eq!(result.source_path, format!("{}/foo/config", repo));
+
+        assert_eq!(
+            filesystem
+                .read_to_string(&result.source_path)
+                .await
+                .unwrap(),
+            "answer = 42\n"
+        );
+        assert_eq!(
+            filesystem
+                .get_symlinks()
+                .get("/home/user/.config/foo/config"),
+            Some(&result.source_path)
+        );
+        assert_eq!(
+            filesystem
+                .read_to_string(&format!("{}/dotf.toml", repo))
+                .await
+                .unwrap(),
+            r#"# My dotfiles
+[symlinks]
+"zsh/.zshrc" = "~/.zshrc"   # shell
+"foo/config" = "/home/user/.config/foo/config"
+
+[scripts.custom]
+fonts = "scripts/fonts.sh"
+"#
+        );
+
+        // Now a managed symlink, and the source path is taken
+        assert!(service
+            .add("/home/user/.config/foo/config", Some("foo/other"))
+            .await
+            .is_err());
+        filesystem.add_file("/home/user/.bashrc", "");
+        assert!(service
+            .add("/home/user/.bashrc", Some("foo/config"))
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use crate::core::{config::CommitContext, symlinks::SymlinkStatus};
+use crate::error::{DotfError, DotfResult};
+use crate::services::RepairService;
+use crate::traits::{filesystem::FileSystem, prompt::Prompt, repository::Repository};
+
+#[derive(Debug, Clone)]
+pub struct AdoptResult {
+    pub target_path: String,
+    pub source_path: String,
+    /// Source path relative to the repository root, as staged in git
+    pub repo_relative_path: String,
+    /// Whether the target content had to be copied into the repository
+    pub copied: bool,
+    pub committed: bool,
+}
+
+pub struct AdoptService<R, F, P> {
+    repository: R,
+    repair_service: RepairService<F, P>,
+}
+
+impl<R: Repository, F: FileSystem + Clone, P: Prompt> AdoptService<R, F, P> {
+    pub fn new(repository: R, filesystem: F, prompt: P) -> Self {
+        Self {
+            repository,
+            repair_service: RepairService::new(filesystem, prompt),
+        }
+    }
+
+    /// Keep the local version of `target_path`: copy it over the repository source,
+    /// stage the source and, when `commit` is set, commit it.
+    ///
+    /// Targets that are already valid symlinks share their content with the source,
+    /// so only staging (and committing) happens for them. Without `message`, the
+    /// commit is worded by the `[commit]` template in settings.
+    pub async fn adopt(
+        &self,
+        target_path: &str,
+        commit: bool,
+        message: Option<&str>,
+    ) -> DotfResult<AdoptResult> {
+        let entry = self
+            .repair_service
+            .find_entry(target_path)
+            .await?
+            .ok_or_else(|| {
+                DotfError::Config(format!(
+                    "'{}' is not a target managed by dotf.toml",
+                    target_path
+                ))
+            })?;
+
+        let repo_path = self.repair_service.repo_path().await?;
+        let repo_relative_path = entry
+            .source_path
+            .strip_prefix(&format!("{}/", repo_path))
+            .ok_or_else(|| {
+                DotfError::Operation(format!(
+                    "Source '{}' is outside the dotfiles repository and cannot be adopted",
+                    entry.source_path
+                ))
+            })?
+            .to_string();
+
+        let copied = match entry.status {
+            SymlinkStatus::Conflict | SymlinkStatus::InvalidTarget => {
+                self.repair_service.adopt(&entry).await?;
+                true
+            }
+            SymlinkStatus::Valid | SymlinkStatus::Modified | SymlinkStatus::WrongPermissions => {
+                if !self
+                    .repository
+                    .is_file_modified(&repo_path, &repo_relative_path)
+                    .await?
+                {
+                    return Err(DotfError::Operation(format!(
+                        "'{}' has no local changes to adopt",
+                        target_path
+                    )));
+                }
+                false
+            }
+            SymlinkStatus::Missing | SymlinkStatus::Broken => {
+                return Err(DotfError::Operation(format!(
+                    "Nothing to adopt at '{}': the target does not exist",
+                    target_path
+                )));
+            }
+            SymlinkStatus::Disabled => {
+                return Err(DotfError::Operation(format!(
+                    "'{}' is disabled on this machine; run 'dotf enable' first",
+                    target_path
+                )));
+            }
+            SymlinkStatus::Unreadable => {
+                return Err(DotfError::Operation(format!(
+                    "'{}' cannot be read through its link; check the permissions of {}",
+                    target_path, entry.source_path
+                )));
+            }
+        };
+
+        self.repository
+            .stage(&repo_path, &repo_relative_path)
+            .await?;
+
+        if commit {
+            let settings = self.repair_service.load_settings().await?;
+            let message = match message {
+                Some(message) => message.to_string(),
+                None => settings.commit.render(&CommitContext::current(
+                    &format!("Adopt local changes to {}", repo_relative_path),
+                    std::slice::from_ref(&repo_relative_path),
+                )),
+            };
+            self.repository
+                .commit(
+                    &repo_path,
+                    &message,
+                    std::slice::from_ref(&repo_relative_path),
+                    settings.commit.sign,
+                )
+                .await?;
+        }
+
+        Ok(AdoptResult {
+            target_path: entry.target_path,
+            source_path: entry.source_path,
+            repo_relative_path,
+            copied,
+            committed: commit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{settings::Repository as RepositorySettings, Settings};
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, prompt::tests::MockPrompt,
+        repository::tests::MockRepository,
+    };
+    use chrono::Utc;
+
+    fn setup() -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo),
+            "[symlinks]\n\"zsh/.zshrc\" = \"/home/user/.zshrc\"\n",
+        );
+        filesystem.add_file(&format!("{}/zsh/.zshrc", repo), "export A=1\n");
+        filesystem
+    }
+
+    #[tokio::test]
+    async fn test_adopt_conflicting_file() {
+        let filesystem = setup();
+        filesystem.add_file("/home/user/.zshrc", "export A=2\n");
+        let repository = MockRepository::new();
+
+        let service = AdoptService::new(
+            Clone::clone(&repository),
+            filesystem.clone(),
+            MockPrompt::new(),
+        );
+        let result = service
+            .adopt("/home/user/.zshrc", true, None)
+            .await
+            .unwrap();
+
+        let repo = filesystem.dotf_repo_path();
+        assert!(result.copied);
+        assert_eq!(result.repo_relative_path, "zsh/.zshrc");
+        assert_eq!(
+            filesystem
+                .read_to_string(&format!("{}/zsh/.zshrc", repo))
+                .await
+                .unwrap(),
+            "export A=2\n"
+        );
+        assert_eq!(
+            repository.get_stage_calls(),
+            vec![(repo.clone(), "zsh/.zshrc".to_string())]
+        );
+        assert_eq!(
+            repository.get_commit_calls(),
+            vec![(repo, "Adopt local changes to zsh/.zshrc".to_string())]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_adopt_valid_link_without_changes() {
+        let filesystem = setup();
+        filesystem.symlinks.lock().unwrap().insert(
+            "/home/user/.zshrc".to_string(),
+            format!("{}/zsh/.zshrc", filesystem.dotf_repo_path()),
+        );
+        let repository = MockRepository::new();
+
+        let service = AdoptService::new(Clone::clone(&repository), filesystem, MockPrompt::new());
+        let result = service.adopt("/home/user/.zshrc", false, None).await;
+
+        assert!(matches!(result.unwrap_err(), DotfError::Operation(_)));
+        assert!(repository.get_stage_calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_adopt_unknown_target() {
+        let service = AdoptService::new(MockRepository::new(), setup(), MockPrompt::new());
+        let result = service.adopt("/home/user/.bashrc", false, None).await;
+        assert!(matches!(result.unwrap_err(), DotfError::Config(_)));
+    }
+}
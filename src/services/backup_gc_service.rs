@@ -0,0 +1,203 @@
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::core::{
+    clock::{RandomIdGenerator, SystemClock},
+    state::StateStore,
+    symlinks::{BackupEntry, BackupManager, SymlinkStatus},
+};
+use crate::error::DotfResult;
+use crate::services::StatusService;
+use crate::traits::{clock::Clock, filesystem::FileSystem, repository::Repository};
+
+/// A backup of a file that dotf replaced long ago with a link that is still healthy
+#[derive(Debug, Clone)]
+pub struct StaleBackup {
+    pub entry: BackupEntry,
+    /// Repository source the target currently links to
+    pub source_path: String,
+    /// Last install of the link, or when the backup was taken if dotf has no record of it
+    pub linked_since: DateTime<Utc>,
+}
+
+/// Finds backups that are unlikely to be needed again: the original path has been a
+/// valid dotf link for at least the given number of days.
+pub struct BackupGcService<R, F> {
+    status: StatusService<R, F>,
+    backups: BackupManager<F>,
+    state: StateStore<F>,
+    clock: Arc<dyn Clock>,
+}
+
+impl<R: Repository, F: FileSystem + Clone> BackupGcService<R, F> {
+    pub fn new(status: StatusService<R, F>, filesystem: F) -> Self {
+        Self::with_clock(status, filesystem, Arc::new(SystemClock::new()))
+    }
+
+    pub fn with_clock(status: StatusService<R, F>, filesystem: F, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            status,
+            backups: BackupManager::with_clock(
+                filesystem.clone(),
+                clock.clone(),
+                Arc::new(RandomIdGenerator::new()),
+            ),
+            state: StateStore::with_clock(filesystem, clock.clone()),
+            clock,
+        }
+    }
+
+    /// Backups whose original path is a valid managed link installed more than
+    /// `min_age_days` ago, oldest link first
+    pub async fn find_stale(&self, min_age_days: u64) -> DotfResult<Vec<StaleBackup>> {
+        let manifest = self.backups.load_manifest().await?;
+        if manifest.entries.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let symlinks = self.status.get_symlinks_status().await?;
+        let healthy: HashMap<&str, &str> = symlinks
+            .details
+            .iter()
+            .filter(|detail| detail.status == SymlinkStatus::Valid)
+            .map(|detail| (detail.target_path.as_str(), detail.source_path.as_str()))
+            .collect();
+
+        let state = self.state.load().await?;
+        let cutoff = self.clock.now() - chrono::Duration::days(min_age_days as i64);
+
+        let mut stale: Vec<StaleBackup> = manifest
+            .entries
+            .into_values()
+            .filter_map(|entry| {
+                let source_path = healthy.get(entry.original_path.as_str())?;
+                let linked_since = state
+                    .entries
+                    .get(&entry.original_path)
+                    .map(|installed| installed.installed_at)
+                    .unwrap_or(entry.created_at);
+                (linked_since < cutoff).then(|| StaleBackup {
+                    source_path: source_path.to_string(),
+                    linked_since,
+                    entry,
+                })
+            })
+            .collect();
+        stale.sort_by(|a, b| {
+            a.linked_since
+                .cmp(&b.linked_since)
+                .then_with(|| a.entry.original_path.cmp(&b.entry.original_path))
+        });
+
+        Ok(stale)
+    }
+
+    /// Delete the given backups and their manifest entries, returning how many were removed
+    pub async fn purge(&self, stale: &[StaleBackup]) -> DotfResult<usize> {
+        let original_paths: Vec<String> = stale
+            .iter()
+            .map(|backup| backup.entry.original_path.clone())
+            .collect();
+        self.backups.remove_backups(&original_paths).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Settings;
+    use crate::traits::{
+        clock::tests::MockClock, filesystem::tests::MockFileSystem,
+        repository::tests::MockRepository,
+    };
+
+    const CONFIG: &str = r#"[symlinks]
+"zsh/.zshrc" = "/home/user/.zshrc"
+"vim/.vimrc" = "/home/user/.vimrc"
+"git/.gitconfig" = "/home/user/.gitconfig"
+"#;
+
+    #[tokio::test]
+    async fn test_find_and_purge_stale_backups() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(&format!("{}/dotf.toml", repo), CONFIG);
+        for source in ["zsh/.zshrc", "vim/.vimrc", "git/.gitconfig"] {
+            filesystem.add_file(&format!("{}/{}", repo, source), "");
+        }
+
+        let start = "2026-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let clock = MockClock::new(start);
+        let backups = BackupManager::with_clock(
+            filesystem.clone(),
+            Arc::new(clock.clone()),
+            Arc::new(RandomIdGenerator::new()),
+        );
+        for target in [
+            "/home/user/.zshrc",
+            "/home/user/.vimrc",
+            "/home/user/.gitconfig",
+        ] {
+            filesystem.add_file(target, "original\n");
+            let entry = backups.backup_file(target).await.unwrap();
+            backups.add_backup_entry(entry).await.unwrap();
+            filesystem.remove_file(target).await.unwrap();
+        }
+
+        {
+            let mut symlinks = filesystem.symlinks.lock().unwrap();
+            // Valid links; .gitconfig was reinstalled recently
+            symlinks.insert(
+                "/home/user/.zshrc".to_string(),
+                format!("{}/zsh/.zshrc", repo),
+            );
+            symlinks.insert(
+                "/home/user/.gitconfig".to_string(),
+                format!("{}/git/.gitconfig", repo),
+            );
+            // Points elsewhere, so its backup may still matter
+            symlinks.insert(
+                "/home/user/.vimrc".to_string(),
+                "/tmp/other-vimrc".to_string(),
+            );
+        }
+        filesystem.add_file("/tmp/other-vimrc", "");
+
+        clock.advance(chrono::Duration::days(60));
+        StateStore::with_clock(filesystem.clone(), Arc::new(clock.clone()))
+            .record_installed(&[(
+                format!("{}/git/.gitconfig", repo),
+                "/home/user/.gitconfig".to_string(),
+            )])
+            .await
+            .unwrap();
+        clock.advance(chrono::Duration::days(5));
+
+        let service = BackupGcService::with_clock(
+            StatusService::new(MockRepository::new(), filesystem.clone()),
+            filesystem.clone(),
+            Arc::new(clock.clone()),
+        );
+
+        let stale = service.find_stale(30).await.unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].entry.original_path, "/home/user/.zshrc");
+        assert_eq!(stale[0].source_path, format!("{}/zsh/.zshrc", repo));
+        assert_eq!(stale[0].linked_since, start);
+
+        assert_eq!(service.purge(&stale).await.unwrap(), 1);
+        assert!(!filesystem
+            .exists(&stale[0].entry.backup_path)
+            .await
+            .unwrap());
+        let manifest = backups.load_manifest().await.unwrap();
+        assert_eq!(manifest.entries.len(), 2);
+        assert!(service.find_stale(30).await.unwrap().is_empty());
+    }
+}
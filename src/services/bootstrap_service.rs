@@ -0,0 +1,69 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::core::{
+    bootstrap::{plan_steps, BootstrapOutcome, BootstrapRunner, DEFAULT_BOOTSTRAP_TIMEOUT},
+    config::{loader, DotfConfig, Settings},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+/// Runs the plugin manager bootstraps declared in `[bootstrap]`
+pub struct BootstrapService<F> {
+    filesystem: F,
+}
+
+impl<F: FileSystem> BootstrapService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self { filesystem }
+    }
+
+    /// Run every configured step in order; failures are reported, not raised
+    pub async fn run(&self) -> DotfResult<Vec<BootstrapOutcome>> {
+        let config = self.load_config().await?;
+
+        let home = dirs::home_dir().ok_or_else(|| {
+            DotfError::Operation("Could not determine home directory".to_string())
+        })?;
+        let data_home = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| home.join(".local/share"));
+
+        let steps = plan_steps(&config.bootstrap, &home, &data_home)?;
+        let timeout = config
+            .bootstrap
+            .timeout
+            .unwrap_or(DEFAULT_BOOTSTRAP_TIMEOUT);
+        let runner = BootstrapRunner::new(Duration::from_secs(timeout));
+
+        let mut outcomes = Vec::new();
+        for step in &steps {
+            outcomes.push(runner.run(step).await);
+        }
+
+        Ok(outcomes)
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        let settings = self.load_settings().await?;
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, &settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    config::{loader, DotfConfig, Settings},
+    state::StateStore,
+    symlinks::SymlinkStatus,
+    tools::asset::sha256_hex,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::StatusService;
+use crate::traits::{filesystem::FileSystem, repository::Repository};
+use crate::utils::platform::detect_platform;
+
+/// How many commits are searched for the revision of the last install
+const COMMIT_SEARCH_LIMIT: usize = 200;
+
+/// Everything that changed since the last `dotf install config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangesReport {
+    pub repo_path: String,
+    pub installed_at: DateTime<Utc>,
+    /// Repository revision of the last install; `None` for backends without history
+    pub revision: Option<String>,
+    /// Commits made since the last install, newest first
+    pub commits: Vec<ChangedCommit>,
+    /// Repository files (relative to the checkout) that differ from the installed
+    /// revision, including uncommitted ones; `None` when that cannot be determined
+    pub files: Option<Vec<String>>,
+    pub links: Vec<LinkChange>,
+    pub scripts: Vec<ScriptChange>,
+}
+
+impl ChangesReport {
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty()
+            && self.files.as_ref().is_none_or(Vec::is_empty)
+            && self.links.is_empty()
+            && self.scripts.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedCommit {
+    pub id: String,
+    pub summary: String,
+    pub author: String,
+    pub date: DateTime<chrono::FixedOffset>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkChangeKind {
+    /// dotf.toml now links the target, but the last install did not
+    Added,
+    /// The last install linked the target, but dotf.toml no longer does
+    Removed,
+    /// The target now comes from a different source
+    Moved,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkChange {
+    pub target_path: String,
+    pub kind: LinkChangeKind,
+    /// Source in dotf.toml now; `None` for removed links
+    pub source_path: Option<String>,
+    /// Source at the last install; `None` for added links
+    pub previous_source_path: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScriptChange {
+    /// Script path relative to the repository, as written in dotf.toml
+    pub path: String,
+    /// Name of a `[scripts.custom]` entry, `None` for the dependency script
+    pub name: Option<String>,
+    /// When the script last ran; `None` if it never did
+    pub last_run: Option<DateTime<Utc>>,
+}
+
+/// Combines the install state, the repository history and dotf.toml to show what
+/// changed since the last `dotf install config`
+pub struct ChangesService<R, F> {
+    status: StatusService<R, F>,
+    repository: R,
+    filesystem: F,
+    state_store: StateStore<F>,
+}
+
+impl<R: Repository, F: FileSystem + Clone> ChangesService<R, F> {
+    pub fn new(status: StatusService<R, F>, repository: R, filesystem: F) -> Self {
+        let state_store = StateStore::new(filesystem.clone());
+        Self {
+            status,
+            repository,
+            filesystem,
+            state_store,
+        }
+    }
+
+    pub async fn changes(&self) -> DotfResult<ChangesReport> {
+        let settings = self.load_settings().await?;
+        let repo_path = settings
+            .repository
+            .local
+            .clone()
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
+        let state = self.state_store.load().await?;
+        let installed = state.config.ok_or_else(|| {
+            DotfError::Operation(
+                "No install recorded yet: run 'dotf install config' first".to_string(),
+            )
+        })?;
+
+        let (commits, files) = match &installed.revision {
+            Some(revision) => (
+                self.commits_since(&repo_path, revision).await,
+                self.repository
+                    .changed_since(&repo_path, revision)
+                    .await
+                    .ok(),
+            ),
+            None => (Vec::new(), None),
+        };
+
+        let current: BTreeMap<String, String> = self
+            .status
+            .get_symlinks_status()
+            .await?
+            .details
+            .into_iter()
+            .filter(|detail| detail.status != SymlinkStatus::Disabled)
+            .map(|detail| (detail.target_path, detail.source_path))
+            .collect();
+        let installed_sources: BTreeMap<String, String> = installed
+            .links
+            .iter()
+            .map(|(target, link)| (target.clone(), link.source_path.clone()))
+            .collect();
+        let links = link_changes(&installed_sources, &current);
+
+        let config = loader::load_config(&self.filesystem, &settings).await?;
+        let mut scripts = Vec::new();
+        for (path, name) in configured_scripts(&config) {
+            let full_path = format!("{}/{}", repo_path, path);
+            if !self.filesystem.exists(&full_path).await? {
+                continue;
+            }
+            let content = self.filesystem.read_to_string(&full_path).await?;
+            let last = state.scripts.get(&path);
+            if last.is_some_and(|run| run.sha256 == sha256_hex(content.as_bytes())) {
+                continue;
+            }
+            scripts.push(ScriptChange {
+                path,
+                name,
+                last_run: last.map(|run| run.ran_at),
+            });
+        }
+
+        Ok(ChangesReport {
+            repo_path,
+            installed_at: installed.installed_at,
+            revision: installed.revision,
+            commits,
+            files,
+            links,
+            scripts,
+        })
+    }
+
+    /// Commits newer than `revision`; empty when it is not among the recent history
+    async fn commits_since(&self, repo_path: &str, revision: &str) -> Vec<ChangedCommit> {
+        let Ok(log) = self
+            .repository
+            .log(repo_path, &[], COMMIT_SEARCH_LIMIT)
+            .await
+        else {
+            return Vec::new();
+        };
+        // Log ids may be abbreviated, the recorded revision is not
+        let Some(position) = log
+            .iter()
+            .position(|commit| revision.starts_with(&commit.id))
+        else {
+            return Vec::new();
+        };
+        log.into_iter()
+            .take(position)
+            .map(|commit| ChangedCommit {
+                id: commit.id,
+                summary: commit.summary,
+                author: commit.author,
+                date: commit.date,
+            })
+            .collect()
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+/// Compare the links of the last install with the links dotf.toml asks for now
+fn link_changes(
+    installed: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<LinkChange> {
+    let mut changes = Vec::new();
+    for (target, source) in current {
+        match installed.get(target) {
+            None => changes.push(LinkChange {
+                target_path: target.clone(),
+                kind: LinkChangeKind::Added,
+                source_path: Some(source.clone()),
+                previous_source_path: None,
+            }),
+            Some(previous) if previous != source => changes.push(LinkChange {
+                target_path: target.clone(),
+                kind: LinkChangeKind::Moved,
+                source_path: Some(source.clone()),
+                previous_source_path: Some(previous.clone()),
+            }),
+            Some(_) => {}
+        }
+    }
+    for (target, previous) in installed {
+        if !current.contains_key(target) {
+            changes.push(LinkChange {
+                target_path: target.clone(),
+                kind: LinkChangeKind::Removed,
+                source_path: None,
+                previous_source_path: Some(previous.clone()),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.target_path.cmp(&b.target_path));
+    changes
+}
+
+/// The dependency script for this platform and every custom script, with their names
+fn configured_scripts(config: &DotfConfig) -> Vec<(String, Option<String>)> {
+    let deps = match detect_platform().as_str() {
+        "macos" => config.scripts.deps.macos.clone(),
+        "linux" => config.scripts.deps.linux.clone(),
+        _ => None,
+    };
+    let mut custom: Vec<(String, Option<String>)> = config
+        .scripts
+        .custom
+        .iter()
+        .map(|(name, path)| (path.clone(), Some(name.clone())))
+        .collect();
+    custom.sort_by(|a, b| a.1.cmp(&b.1));
+    deps.map(|path| (path, None))
+        .into_iter()
+        .chain(custom)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::state::InstalledLink;
+    use crate::core::symlinks::{LinkMode, Permissions};
+    use crate::traits::{
+        filesystem::tests::MockFileSystem,
+        repository::{tests::MockRepository, CommitEntry},
+    };
+
+    fn commit(id: &str) -> CommitEntry {
+        CommitEntry {
+            id: id.to_string(),
+            author: "user".to_string(),
+            date: chrono::DateTime::parse_from_rfc3339("2024-05-01T10:00:00+02:00").unwrap(),
+            summary: format!("commit {}", id),
+            files: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changes_since_last_install() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.repository.local = Some("/repo".to_string());
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            "/repo/dotf.toml",
+            "[symlinks]\n\".vimrc\" = \"/home/user/.vimrc\"\n\"zsh/.zshrc\" = \"/home/user/.zshrc\"\n\".gitconfig\" = \"/home/user/.gitconfig\"\n\n[scripts.custom]\nfonts = \"scripts/fonts.sh\"\nmacos = \"scripts/macos.sh\"\n",
+        );
+        for path in ["/repo/.vimrc", "/repo/zsh/.zshrc", "/repo/.gitconfig"] {
+            filesystem.add_file(path, "");
+        }
+        filesystem.add_file("/repo/scripts/fonts.sh", "install fonts v2");
+        filesystem.add_file("/repo/scripts/macos.sh", "defaults write");
+
+        let state_store = StateStore::new(filesystem.clone());
+        state_store
+            .record_config(
+                Some("c3d4e5f6a7b8".to_string()),
+                [
+                    ("/home/user/.vimrc", "/repo/.vimrc"),
+                    ("/home/user/.zshrc", "/repo/.zshrc"),
+                    ("/home/user/.bashrc", "/repo/.bashrc"),
+                ]
+                .into_iter()
+                .map(|(target, source)| {
+                    (
+                        target.to_string(),
+                        InstalledLink {
+                            source_path: source.to_string(),
+                            mode: LinkMode::Symlink,
+                            permissions: Permissions::default(),
+                        },
+                    )
+                })
+                .collect(),
+            )
+            .await
+            .unwrap();
+        state_store
+            .record_script_run("scripts/fonts.sh", &sha256_hex(b"install fonts v1"))
+            .await
+            .unwrap();
+        state_store
+            .record_script_run("scripts/macos.sh", &sha256_hex(b"defaults write"))
+            .await
+            .unwrap();
+
+        let repository = MockRepository::new();
+        repository.set_log(vec![
+            commit("e5f6"),
+            commit("d4e5"),
+            commit("c3d4"),
+            commit("b2c3"),
+        ]);
+        repository.set_revision(Some("e5f6a7b8c9d0"), &["zsh/.zshrc", "scripts/fonts.sh"]);
+        let status = StatusService::new(Clone::clone(&repository), filesystem.clone());
+        let report = ChangesService::new(status, repository, filesystem)
+            .changes()
+            .await
+            .unwrap();
+
+        let commits: Vec<&str> = report.commits.iter().map(|c| c.id.as_str()).collect();
+        assert_eq!(commits, vec!["e5f6", "d4e5"]);
+        assert_eq!(
+            report.files,
+            Some(vec![
+                "zsh/.zshrc".to_string(),
+                "scripts/fonts.sh".to_string()
+            ])
+        );
+
+        let links: Vec<(&str, LinkChangeKind)> = report
+            .links
+            .iter()
+            .map(|link| (link.target_path.as_str(), link.kind))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("/home/user/.bashrc", LinkChangeKind::Removed),
+                ("/home/user/.gitconfig", LinkChangeKind::Added),
+                ("/home/user/.zshrc", LinkChangeKind::Moved),
+            ]
+        );
+        assert_eq!(
+            report.links[2].previous_source_path.as_deref(),
+            Some("/repo/.zshrc")
+        );
+
+        // Only the script whose content changed since it last ran
+        assert_eq!(report.scripts.len(), 1);
+        assert_eq!(report.scripts[0].name.as_deref(), Some("fonts"));
+        assert!(report.scripts[0].last_run.is_some());
+        assert!(!report.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use std::path::{Path, PathBuf};
+
+use clap_complete::Shell;
+
+use crate::core::completions::{add_rc_line, location};
+use crate::error::DotfResult;
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+
+/// What `dotf completions --install` changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompletionInstall {
+    pub file: String,
+    /// The rc file the load line was added to
+    pub rc_updated: Option<String>,
+    /// A load line the user chose not to add, with the rc file it belongs in
+    pub rc_skipped: Option<(String, String)>,
+}
+
+/// Writes shell completion scripts where each shell looks for them
+pub struct CompletionsService<F, P> {
+    filesystem: F,
+    prompt: P,
+    home: PathBuf,
+}
+
+impl<F: FileSystem, P: Prompt> CompletionsService<F, P> {
+    pub fn new(filesystem: F, prompt: P) -> Self {
+        Self {
+            filesystem,
+            prompt,
+            home: dirs::home_dir().unwrap_or_default(),
+        }
+    }
+
+    /// Write `script` to the completion directory for `shell` and offer to add
+    /// the line that loads it to the shell's rc file when one is needed
+    pub async fn install(&self, shell: Shell, script: &str) -> DotfResult<CompletionInstall> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from);
+        let data_home = std::env::var_os("XDG_DATA_HOME").map(PathBuf::from);
+        let location = location(
+            shell,
+            &self.home,
+            config_home.as_deref(),
+            data_home.as_deref(),
+        )?;
+
+        if let Some(parent) = Path::new(&location.file).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.filesystem.write(&location.file, script).await?;
+
+        let mut install = CompletionInstall {
+            file: location.file,
+            rc_updated: None,
+            rc_skipped: None,
+        };
+
+        let (Some(rc_file), Some(rc_line)) = (location.rc_file, location.rc_line) else {
+            return Ok(install);
+        };
+        let rc_content = if self.filesystem.exists(&rc_file).await? {
+            self.filesystem.read_to_string(&rc_file).await?
+        } else {
+            String::new()
+        };
+        let Some(updated) = add_rc_line(shell, &rc_content, &rc_line) else {
+            return Ok(install);
+        };
+
+        if self
+            .prompt
+            .confirm(&format!("Add '{}' to {}?", rc_line, rc_file))
+            .await?
+        {
+            // A managed rc file is a symlink into the repository, so this edits the tracked copy
+            self.filesystem.write(&rc_file, &updated).await?;
+            install.rc_updated = Some(rc_file);
+        } else {
+            install.rc_skipped = Some((rc_file, rc_line));
+        }
+
+        Ok(install)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{filesystem::tests::MockFileSystem, prompt::tests::MockPrompt};
+
+    #[tokio::test]
+    async fn test_install_zsh_completion() {
+        let filesystem = MockFileSystem::new();
+        let prompt = MockPrompt::new();
+        prompt.set_confirm_response(true);
+        let mut service = CompletionsService::new(filesystem.clone(), prompt);
+        service.home = PathBuf::from("/home/user");
+        filesystem.add_file("/home/user/.zshrc", "autoload -Uz compinit\ncompinit\n");
+
+        let install = service.install(Shell::Zsh, "#compdef dotf").await.unwrap();
+
+        assert_eq!(install.file, "/home/user/.zsh/completions/_dotf");
+        assert_eq!(install.rc_updated.as_deref(), Some("/home/user/.zshrc"));
+        assert_eq!(
+            filesystem.read_to_string(&install.file).await.unwrap(),
+            "#compdef dotf"
+        );
+        let rc = filesystem
+            .read_to_string("/home/user/.zshrc")
+            .await
+            .unwrap();
+        assert!(rc.starts_with("# dotf completions\nfpath=(~/.zsh/completions $fpath)\n"));
+
+        // Installing again rewrites the script but leaves the rc file alone without asking
+        let install = service.install(Shell::Zsh, "#compdef dotf").await.unwrap();
+        assert_eq!(install.rc_updated, None);
+        assert_eq!(install.rc_skipped, None);
+    }
+
+    #[tokio::test]
+    async fn test_install_bash_completion_declined() {
+        let filesystem = MockFileSystem::new();
+        let prompt = MockPrompt::new();
+        prompt.set_confirm_response(false);
+        let mut service = CompletionsService::new(filesystem.clone(), prompt);
+        service.home = PathBuf::from("/home/user");
+
+        let install = service.install(Shell::Bash, "complete -F _dotf dotf").await;
+        let install = install.unwrap();
+
+        assert!(install.rc_skipped.is_some());
+        assert!(!filesystem.exists("/home/user/.bashrc").await.unwrap());
+    }
+}
@@ -0,0 +1,91 @@
+use crate::core::config::{diff_configs, SectionDiff, Settings};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, repository::Repository};
+
+/// Compares dotf.toml between two branches, tags or commits of the remote repository
+pub struct ConfigDiffService<R, F> {
+    repository: R,
+    filesystem: F,
+}
+
+impl<R: Repository, F: FileSystem> ConfigDiffService<R, F> {
+    pub fn new(repository: R, filesystem: F) -> Self {
+        Self {
+            repository,
+            filesystem,
+        }
+    }
+
+    /// Sections of dotf.toml that differ from `from` to `to`
+    pub async fn diff(&self, from: &str, to: &str) -> DotfResult<Vec<SectionDiff>> {
+        let settings = self.load_settings().await?;
+        let url = &settings.repository.remote;
+
+        let old = self
+            .repository
+            .fetch_config_from_branch(url, from)
+            .await
+            .map_err(|e| DotfError::Repository(format!("Failed to read '{}': {}", from, e)))?;
+        let new = self
+            .repository
+            .fetch_config_from_branch(url, to)
+            .await
+            .map_err(|e| DotfError::Repository(format!("Failed to read '{}': {}", to, e)))?;
+
+        Ok(diff_configs(&old, &new))
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{filesystem::tests::MockFileSystem, repository::tests::MockRepository};
+
+    #[tokio::test]
+    async fn test_diff_between_branches() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+
+        let repository = MockRepository::new();
+        repository.set_branch_config(
+            "main",
+            toml::from_str("[symlinks]\n\"zsh/.zshrc\" = \"~/.zshrc\"\n").unwrap(),
+        );
+        repository.set_branch_config(
+            "work",
+            toml::from_str(
+                "[symlinks]\n\"zsh/.zshrc\" = \"~/.zshrc\"\n\n[cron]\nvpn = \"@reboot vpn.sh\"\n",
+            )
+            .unwrap(),
+        );
+
+        let service = ConfigDiffService::new(repository, filesystem);
+        let diffs = service.diff("main", "work").await.unwrap();
+
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].section, "cron");
+        assert_eq!(diffs[0].changes[0].key, "vpn");
+        assert_eq!(
+            diffs[0].changes[0].to.as_deref(),
+            Some("\"@reboot vpn.sh\"")
+        );
+    }
+}
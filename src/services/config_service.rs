@@ -92,6 +92,21 @@ impl<F: FileSystem, P: Prompt> ConfigService<F, P> {
                 repository: updated_repository,
                 last_sync: current_settings.last_sync,
                 initialized_at: current_settings.initialized_at,
+                commit: current_settings.commit.clone(),
+                prompts: current_settings.prompts.clone(),
+                scripts: current_settings.scripts,
+                aliases: current_settings.aliases.clone(),
+                widget: current_settings.widget.clone(),
+                profile: current_settings.profile.clone(),
+                packages: current_settings.packages.clone(),
+                logging: current_settings.logging.clone(),
+                symlinks: current_settings.symlinks.clone(),
+                diff: current_settings.diff.clone(),
+                backups: current_settings.backups.clone(),
+                repositories: current_settings.repositories.clone(),
+                features: current_settings.features.clone(),
+                network: current_settings.network.clone(),
+                variables: current_settings.variables.clone(),
             };
 
             let settings_content = updated_settings
@@ -315,9 +330,25 @@ mod tests {
                 remote: "https://github.com/user/dotfiles".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
         let settings_content = settings.to_toml().unwrap();
         filesystem.add_file(&filesystem.dotf_settings_path(), &settings_content);
@@ -332,15 +363,31 @@ mod tests {
         custom_scripts.insert("setup".to_string(), "scripts/setup.sh".to_string());
 
         DotfConfig {
-            symlinks,
+            symlinks: symlinks.into(),
             scripts: ScriptsConfig {
                 deps: DepsScripts {
                     macos: None,
                     linux: Some("scripts/install-linux.sh".to_string()),
                 },
                 custom: custom_scripts,
+                limits: Default::default(),
+                run: Default::default(),
+                env: Default::default(),
+                working_dir: Default::default(),
             },
             platform: Default::default(),
+            tools: Default::default(),
+            bootstrap: Default::default(),
+            macos: Default::default(),
+            cron: Default::default(),
+            env: Default::default(),
+            prompts: Default::default(),
+            ssh: Default::default(),
+            secrets: Default::default(),
+            hooks: Default::default(),
+            packages: Default::default(),
+            package_managers: Default::default(),
+            profile: Default::default(),
         }
     }
 
@@ -381,9 +428,25 @@ mod tests {
                 remote: "https://github.com/user/dotfiles".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: Some(Utc::now()),
             initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         let settings_content = settings.to_toml().unwrap();
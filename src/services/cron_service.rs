@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::core::{
+    config::{loader, DotfConfig, Settings},
+    cron::{managed_block, render_entries, replace_managed_block},
+    state::StateStore,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{crontab::Crontab, filesystem::FileSystem};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CronState {
+    /// The managed block matches `[cron]`
+    InSync,
+    /// `[cron]` changed since the entries were last installed
+    Pending,
+    /// The managed block was edited or removed outside dotf
+    Drifted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CronStatusInfo {
+    pub state: CronState,
+    /// Number of entries declared in `[cron]`
+    pub declared: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CronInstallOutcome {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<String>,
+    /// Manual edits inside the managed block were overwritten
+    pub replaced_drift: bool,
+}
+
+impl CronInstallOutcome {
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && !self.replaced_drift
+    }
+}
+
+pub struct CronService<F, C> {
+    filesystem: F,
+    crontab: C,
+    state_store: StateStore<F>,
+}
+
+impl<F: FileSystem + Clone, C: Crontab> CronService<F, C> {
+    pub fn new(filesystem: F, crontab: C) -> Self {
+        let state_store = StateStore::new(filesystem.clone());
+        Self {
+            filesystem,
+            crontab,
+            state_store,
+        }
+    }
+
+    /// Compare the crontab with `[cron]`; `None` when dotf manages no cron entries
+    pub async fn status(&self) -> DotfResult<Option<CronStatusInfo>> {
+        let declared = self.load_config().await?.cron;
+        let installed = self.installed_entries().await?;
+
+        if declared.is_empty() && installed.is_none() {
+            return Ok(None);
+        }
+
+        let content = self.crontab.read().await?;
+        let state = if is_drifted(&content, installed.as_ref()) {
+            CronState::Drifted
+        } else if installed.as_ref() != Some(&declared) {
+            CronState::Pending
+        } else {
+            CronState::InSync
+        };
+
+        Ok(Some(CronStatusInfo {
+            state,
+            declared: declared.len(),
+        }))
+    }
+
+    /// Write `[cron]` into the managed block, removing entries no longer declared
+    pub async fn install(&self) -> DotfResult<CronInstallOutcome> {
+        let declared = self.load_config().await?.cron;
+        let installed = self.installed_entries().await?;
+
+        if declared.is_empty() && installed.is_none() {
+            return Ok(CronInstallOutcome::default());
+        }
+
+        let content = self.crontab.read().await?;
+        let previous = installed.clone().unwrap_or_default();
+        let outcome = CronInstallOutcome {
+            added: declared
+                .keys()
+                .filter(|name| !previous.contains_key(*name))
+                .cloned()
+                .collect(),
+            removed: previous
+                .keys()
+                .filter(|name| !declared.contains_key(*name))
+                .cloned()
+                .collect(),
+            changed: declared
+                .iter()
+                .filter(|(name, line)| previous.get(*name).is_some_and(|old| old != *line))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            replaced_drift: is_drifted(&content, installed.as_ref()),
+        };
+
+        let updated = replace_managed_block(&content, &declared);
+        if updated != content {
+            self.crontab.write(&updated).await?;
+        }
+
+        self.state_store
+            .record_cron((!declared.is_empty()).then_some(declared))
+            .await?;
+
+        Ok(outcome)
+    }
+
+    async fn installed_entries(&self) -> DotfResult<Option<BTreeMap<String, String>>> {
+        Ok(self.state_store.load().await?.cron.map(|cron| cron.entries))
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        let settings = self.load_settings().await?;
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, &settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+/// Whether the managed block differs from what dotf last wrote
+fn is_drifted(content: &str, installed: Option<&BTreeMap<String, String>>) -> bool {
+    managed_block(content) != installed.map(render_entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{settings::Repository as RepositorySettings, Settings};
+    use crate::core::cron::{BEGIN_MARKER, END_MARKER};
+    use crate::traits::crontab::tests::MockCrontab;
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::Utc;
+
+    fn setup(cron: &str) -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        set_config(&filesystem, cron);
+        filesystem
+    }
+
+    fn set_config(filesystem: &MockFileSystem, cron: &str) {
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            &format!("[symlinks]\n\n[cron]\n{}", cron),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_install_and_reconcile() {
+        let filesystem =
+            setup("backup = \"0 3 * * * ~/bin/backup\"\nsync = \"@hourly dotf sync\"\n");
+        let crontab = MockCrontab::new("MAILTO=me\n");
+        let service = CronService::new(filesystem.clone(), crontab.clone());
+
+        let status = service.status().await.unwrap().unwrap();
+        assert_eq!(status.state, CronState::Pending);
+
+        let outcome = service.install().await.unwrap();
+        assert_eq!(outcome.added, vec!["backup", "sync"]);
+        assert!(crontab.content().starts_with("MAILTO=me\n"));
+        assert!(crontab.content().contains("@hourly dotf sync"));
+        assert_eq!(
+            service.status().await.unwrap().unwrap().state,
+            CronState::InSync
+        );
+
+        // Reinstalling an unchanged config leaves the crontab alone
+        assert!(service.install().await.unwrap().is_unchanged());
+        assert_eq!(crontab.get_write_count(), 1);
+
+        set_config(&filesystem, "backup = \"0 4 * * * ~/bin/backup\"\n");
+        let outcome = service.install().await.unwrap();
+        assert_eq!(outcome.changed, vec!["backup"]);
+        assert_eq!(outcome.removed, vec!["sync"]);
+        assert!(!crontab.content().contains("@hourly"));
+    }
+
+    #[tokio::test]
+    async fn test_status_detects_manual_edits() {
+        let filesystem = setup("backup = \"0 3 * * * ~/bin/backup\"\n");
+        let crontab = MockCrontab::new("");
+        let service = CronService::new(filesystem, crontab.clone());
+        service.install().await.unwrap();
+
+        let edited = crontab.content().replace("0 3", "30 3");
+        *crontab.content.lock().unwrap() = edited;
+        assert_eq!(
+            service.status().await.unwrap().unwrap().state,
+            CronState::Drifted
+        );
+
+        let outcome = service.install().await.unwrap();
+        assert!(outcome.replaced_drift);
+        assert_eq!(
+            crontab.content(),
+            format!(
+                "{}\n# backup\n0 3 * * * ~/bin/backup\n{}\n",
+                BEGIN_MARKER, END_MARKER
+            )
+        );
+    }
+}
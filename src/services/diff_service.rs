@@ -0,0 +1,253 @@
+use crate::core::{config::Settings, state::StateStore, symlinks::SymlinkStatus};
+use crate::error::{DotfError, DotfResult};
+use crate::services::{status_service::SymlinkStatusDetail, StatusService};
+use crate::traits::{filesystem::FileSystem, repository::Repository};
+use crate::utils::diff::{check_diffable, line_diff, DiffLine, NotDiffable};
+
+/// What a deployed target looks like compared to its repository source
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub source_path: String,
+    pub target_path: String,
+    pub status: SymlinkStatus,
+    /// Label of the old side, e.g. the source path or "last install"
+    pub from: String,
+    /// Label of the new side, e.g. the target path or the file a stray link points to
+    pub to: String,
+    /// File holding the old side; `None` when it is the copy kept from the last install
+    pub from_path: Option<String>,
+    /// File holding the new side
+    pub to_path: String,
+    pub content: DiffContent,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffContent {
+    Changes(Vec<DiffLine>),
+    Identical,
+    Binary,
+    /// Too large to diff or print; the size of the larger side in bytes
+    TooLarge(usize),
+    /// A directory, or a file that cannot be read
+    NotText,
+    /// Modified in place, but no copy from the last install to compare with
+    NoBase,
+}
+
+/// Shows the content that `install config` or `symlinks restore` would replace
+pub struct DiffService<R, F> {
+    status: StatusService<R, F>,
+    filesystem: F,
+    state_store: StateStore<F>,
+}
+
+impl<R: Repository, F: FileSystem + Clone> DiffService<R, F> {
+    pub fn new(status: StatusService<R, F>, filesystem: F) -> Self {
+        let state_store = StateStore::new(filesystem.clone());
+        Self {
+            status,
+            filesystem,
+            state_store,
+        }
+    }
+
+    /// Differences for every entry in Conflict, InvalidTarget or Modified state, sorted by
+    /// target. With `target`, only the entry deployed there is compared.
+    pub async fn diff(&self, target: Option<&str>) -> DotfResult<Vec<FileDiff>> {
+        let details = self.status.get_symlinks_status().await?.details;
+
+        let mut diffs = Vec::new();
+        for detail in details {
+            if target.is_some_and(|target| target != detail.target_path) {
+                continue;
+            }
+            if let Some(diff) = self.diff_entry(detail).await? {
+                diffs.push(diff);
+            }
+        }
+
+        diffs.sort_by(|a, b| a.target_path.cmp(&b.target_path));
+        Ok(diffs)
+    }
+
+    async fn diff_entry(&self, detail: SymlinkStatusDetail) -> DotfResult<Option<FileDiff>> {
+        let (from, to, from_path, to_path, content) = match detail.status {
+            // A file in the way: the install would back it up and link the source
+            SymlinkStatus::Conflict => (
+                detail.source_path.clone(),
+                detail.target_path.clone(),
+                Some(detail.source_path.clone()),
+                detail.target_path.clone(),
+                self.compare(&detail.source_path, &detail.target_path)
+                    .await?,
+            ),
+            // A link to somewhere else: compare with the file it points to
+            SymlinkStatus::InvalidTarget => {
+                let Some(current) = detail.current_target.clone() else {
+                    return Ok(None);
+                };
+                let content = self.compare(&detail.source_path, &current).await?;
+                (
+                    detail.source_path.clone(),
+                    current.clone(),
+                    Some(detail.source_path.clone()),
+                    current,
+                    content,
+                )
+            }
+            // Edited through the link: compare with the content at the last install
+            SymlinkStatus::Modified => {
+                let content = match self.state_store.base_content(&detail.target_path).await {
+                    Ok(Some(base)) => match self.read_text(&detail.source_path).await? {
+                        Text::Content(current) => text_diff(&base, &current),
+                        Text::Binary => DiffContent::Binary,
+                        Text::Unreadable => DiffContent::NotText,
+                    },
+                    _ => DiffContent::NoBase,
+                };
+                (
+                    "last install".to_string(),
+                    detail.source_path.clone(),
+                    None,
+                    detail.source_path.clone(),
+                    content,
+                )
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(FileDiff {
+            source_path: detail.source_path,
+            target_path: detail.target_path,
+            status: detail.status,
+            from,
+            to,
+            from_path,
+            to_path,
+            content,
+        }))
+    }
+
+    /// Content of `target_path`'s source as it was at the last install
+    pub async fn base_content(&self, target_path: &str) -> DotfResult<Option<String>> {
+        self.state_store.base_content(target_path).await
+    }
+
+    /// External diff command from `[diff] tool` in local settings
+    pub async fn difftool(&self) -> DotfResult<Option<String>> {
+        let settings_path = self.filesystem.dotf_settings_path();
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+        Ok(settings.diff.tool)
+    }
+
+    async fn compare(&self, old_path: &str, new_path: &str) -> DotfResult<DiffContent> {
+        match (
+            self.read_text(old_path).await?,
+            self.read_text(new_path).await?,
+        ) {
+            (Text::Content(old), Text::Content(new)) => Ok(text_diff(&old, &new)),
+            (Text::Binary, _) | (_, Text::Binary) => Ok(DiffContent::Binary),
+            _ => Ok(DiffContent::NotText),
+        }
+    }
+
+    async fn read_text(&self, path: &str) -> DotfResult<Text> {
+        if !self.filesystem.exists(path).await? || self.filesystem.is_dir(path).await? {
+            return Ok(Text::Unreadable);
+        }
+        Ok(match self.filesystem.read_to_string(path).await {
+            Ok(content) => Text::Content(content),
+            // Not UTF-8
+            Err(DotfError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidData => Text::Binary,
+            Err(_) => Text::Unreadable,
+        })
+    }
+}
+
+/// What reading one side of a comparison found
+enum Text {
+    Content(String),
+    Binary,
+    /// A directory, or a file that is missing or cannot be read
+    Unreadable,
+}
+
+fn text_diff(old: &str, new: &str) -> DiffContent {
+    if old == new {
+        return DiffContent::Identical;
+    }
+    match check_diffable(old, new) {
+        Err(NotDiffable::Binary) => return DiffContent::Binary,
+        Err(NotDiffable::TooLarge(size)) => return DiffContent::TooLarge(size),
+        Ok(()) => {}
+    }
+    match line_diff(old, new) {
+        Some(lines)
+            if lines
+                .iter()
+                .all(|line| matches!(line, DiffLine::Context(_))) =>
+        {
+            DiffContent::Identical
+        }
+        Some(lines) => DiffContent::Changes(lines),
+        None => DiffContent::TooLarge(old.len().max(new.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{filesystem::tests::MockFileSystem, repository::tests::MockRepository};
+    use crate::utils::diff::MAX_DIFF_BYTES;
+
+    #[tokio::test]
+    async fn test_diff_conflicting_target() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.repository.local = Some("/repo".to_string());
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            "/repo/dotf.toml",
+            "[symlinks]\n\".vimrc\" = \"/home/user/.vimrc\"\n\".bashrc\" = \"/home/user/.bashrc\"\n\"logo.png\" = \"/home/user/logo.png\"\n\"data.json\" = \"/home/user/data.json\"\n",
+        );
+        filesystem.add_file("/repo/logo.png", "\u{89}PNG\r\n\u{1a}\n\0\0\0\rIHDR");
+        filesystem.add_file("/home/user/logo.png", "\u{89}PNG\r\n\u{1a}\n\0\0\0\rIDAT");
+        filesystem.add_file("/repo/data.json", "{}");
+        filesystem.add_file("/home/user/data.json", &"[1]".repeat(MAX_DIFF_BYTES));
+        filesystem.add_file("/repo/.vimrc", "set number\nsyntax on\n");
+        filesystem.add_file("/home/user/.vimrc", "set number\nset paste\n");
+        filesystem.add_file("/repo/.bashrc", "export A=1\n");
+        filesystem.add_file("/home/user/.bashrc", "export A=1\n");
+
+        let status = StatusService::new(MockRepository::new(), filesystem.clone());
+        let service = DiffService::new(status, filesystem);
+        let diffs = service.diff(None).await.unwrap();
+
+        assert_eq!(diffs.len(), 4);
+        assert_eq!(diffs[0].target_path, "/home/user/.bashrc");
+        assert_eq!(diffs[0].content, DiffContent::Identical);
+        assert_eq!(diffs[2].content, DiffContent::TooLarge(3 * MAX_DIFF_BYTES));
+        assert_eq!(diffs[3].content, DiffContent::Binary);
+        assert_eq!(diffs[1].status, SymlinkStatus::Conflict);
+        assert_eq!(diffs[1].from_path.as_deref(), Some("/repo/.vimrc"));
+        assert_eq!(
+            diffs[1].content,
+            DiffContent::Changes(vec![
+                DiffLine::Context("set number".to_string()),
+                DiffLine::Removed("syntax on".to_string()),
+                DiffLine::Added("set paste".to_string()),
+            ])
+        );
+
+        let only = service.diff(Some("/home/user/.vimrc")).await.unwrap();
+        assert_eq!(only.len(), 1);
+    }
+}
@@ -0,0 +1,181 @@
+use crate::core::config::{loader, Settings};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+use crate::utils::platform::detect_platform;
+
+/// Keeps the link targets this machine skips in local settings, so one machine can go
+/// without a file the shared dotf.toml links everywhere else
+pub struct DisableService<F> {
+    filesystem: F,
+}
+
+impl<F: FileSystem> DisableService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self { filesystem }
+    }
+
+    /// Targets disabled on this machine, as written in settings.toml
+    pub async fn list(&self) -> DotfResult<Vec<String>> {
+        Ok(self.load_settings().await?.symlinks.disabled)
+    }
+
+    /// Stop linking the absolute `target_path`, which must be, contain or lie inside a
+    /// target in dotf.toml. Returns false when it was already disabled.
+    pub async fn disable(&self, target_path: &str) -> DotfResult<bool> {
+        let mut settings = self.load_settings().await?;
+        let config = loader::load_config(&self.filesystem, &settings).await?;
+
+        let mut symlinks = config.symlinks;
+        if let Some(platform_config) = config.platform.for_platform(&detect_platform()) {
+            symlinks.extend(platform_config.symlinks.clone());
+        }
+        let home = home_dir()?;
+        let linked = symlinks.values().any(|target| {
+            let target = match target.strip_prefix("~/") {
+                Some(rest) => format!("{}/{}", home, rest),
+                None => target.clone(),
+            };
+            let target = target.trim_end_matches('/');
+            target_path == target
+                || target_path.starts_with(&format!("{}/", target))
+                || target.starts_with(&format!("{}/", target_path))
+        });
+        if !linked {
+            return Err(DotfError::Config(format!(
+                "No entry in dotf.toml links to {}",
+                target_path
+            )));
+        }
+
+        if settings.symlinks.is_disabled(target_path) {
+            return Ok(false);
+        }
+        let entry = match target_path.strip_prefix(&format!("{}/", home)) {
+            Some(rest) => format!("~/{}", rest),
+            None => target_path.to_string(),
+        };
+        settings.symlinks.disabled.push(entry);
+        self.save_settings(&settings).await?;
+        Ok(true)
+    }
+
+    /// Link the absolute `target_path` again. Returns false when it was not disabled.
+    pub async fn enable(&self, target_path: &str) -> DotfResult<bool> {
+        let mut settings = self.load_settings().await?;
+        let home = home_dir()?;
+
+        let before = settings.symlinks.disabled.len();
+        settings.symlinks.disabled.retain(|disabled| {
+            let disabled = match disabled.strip_prefix("~/") {
+                Some(rest) => format!("{}/{}", home, rest),
+                None => disabled.clone(),
+            };
+            disabled.trim_end_matches('/') != target_path
+        });
+        if settings.symlinks.disabled.len() == before {
+            if settings.symlinks.is_disabled(target_path) {
+                return Err(DotfError::Config(format!(
+                    "{} is inside a disabled directory; enable that directory instead",
+                    target_path
+                )));
+            }
+            return Ok(false);
+        }
+        self.save_settings(&settings).await?;
+        Ok(true)
+    }
+
+    async fn save_settings(&self, settings: &Settings) -> DotfResult<()> {
+        let content = settings
+            .to_toml()
+            .map_err(|e| DotfError::Serialization(e.to_string()))?;
+        self.filesystem
+            .write(&self.filesystem.dotf_settings_path(), &content)
+            .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+fn home_dir() -> DotfResult<String> {
+    dirs::home_dir()
+        .map(|home| home.to_string_lossy().to_string())
+        .ok_or_else(|| DotfError::Operation("Could not determine home directory".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_disable_and_enable() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[symlinks]
+"git/.gitconfig" = "~/.gitconfig"
+"nvim" = "~/.config/nvim"
+"#,
+        );
+        let home = home_dir().unwrap();
+        let service = DisableService::new(filesystem.clone());
+
+        assert!(service.disable(&format!("{}/.npmrc", home)).await.is_err());
+        assert!(service
+            .disable(&format!("{}/.gitconfig", home))
+            .await
+            .unwrap());
+        assert!(!service
+            .disable(&format!("{}/.gitconfig", home))
+            .await
+            .unwrap());
+        assert!(service
+            .disable(&format!("{}/.config/nvim", home))
+            .await
+            .unwrap());
+        assert_eq!(
+            service.list().await.unwrap(),
+            vec!["~/.gitconfig", "~/.config/nvim"]
+        );
+
+        let content = filesystem
+            .read_to_string(&filesystem.dotf_settings_path())
+            .await
+            .unwrap();
+        let settings = Settings::from_toml(&content).unwrap();
+        assert!(settings
+            .symlinks
+            .is_disabled(&format!("{}/.config/nvim/init.lua", home)));
+
+        // A file inside a disabled directory comes back with its directory only
+        assert!(service
+            .enable(&format!("{}/.config/nvim/init.lua", home))
+            .await
+            .is_err());
+        assert!(service
+            .enable(&format!("{}/.gitconfig", home))
+            .await
+            .unwrap());
+        assert!(!service
+            .enable(&format!("{}/.gitconfig", home))
+            .await
+            .unwrap());
+        assert_eq!(service.list().await.unwrap(), vec!["~/.config/nvim"]);
+    }
+}
@@ -0,0 +1,600 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::{
+    config::{DotfConfig, Settings},
+    env::{is_hand_edited, render_env, SHELL_RC_FILES},
+    features,
+    timings::{summarize, TimingStore},
+    version_managers::{check_runtimes, default_managers},
+};
+use crate::error::DotfResult;
+use crate::traits::{
+    filesystem::FileSystem, repository::Repository, tool_version_manager::ToolVersionManager,
+};
+use crate::utils::platform::detect_platform;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CheckStatus {
+    Ok,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DoctorCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+    /// What to run or change to fix a failing check
+    pub hint: Option<String>,
+}
+
+impl DoctorCheck {
+    fn new(name: &str, status: CheckStatus, message: String) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message,
+            hint: None,
+        }
+    }
+
+    fn with_hint(mut self, hint: &str) -> Self {
+        self.hint = Some(hint.to_string());
+        self
+    }
+}
+
+/// Diagnoses the local dotf setup
+pub struct DoctorService<F> {
+    filesystem: F,
+    version_managers: Vec<Box<dyn ToolVersionManager>>,
+    repository: Option<Box<dyn Repository + Send + Sync>>,
+    enabled_features: Vec<String>,
+}
+
+impl<F: FileSystem> DoctorService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self {
+            filesystem,
+            version_managers: default_managers(),
+            repository: None,
+            enabled_features: Vec::new(),
+        }
+    }
+
+    pub fn with_version_managers(mut self, managers: Vec<Box<dyn ToolVersionManager>>) -> Self {
+        self.version_managers = managers;
+        self
+    }
+
+    /// Enable checks that need the repository backend, such as commit signing
+    pub fn with_repository(mut self, repository: Box<dyn Repository + Send + Sync>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    /// Experiments turned on with `--enable-feature` for this run, listed with the ones
+    /// turned on in settings
+    pub fn with_enabled_features(mut self, names: Vec<String>) -> Self {
+        self.enabled_features = names;
+        self
+    }
+
+    /// Run every check; later checks are skipped when dotf is not initialized
+    pub async fn run(&self) -> DotfResult<Vec<DoctorCheck>> {
+        let mut checks = Vec::new();
+
+        let settings = match self.check_settings().await? {
+            Ok(settings) => {
+                checks.push(DoctorCheck::new(
+                    "Settings",
+                    CheckStatus::Ok,
+                    format!("Tracking {}", settings.repository.remote),
+                ));
+                settings
+            }
+            Err(check) => {
+                checks.push(check);
+                return Ok(checks);
+            }
+        };
+
+        let repo_path = settings
+            .repository
+            .local
+            .clone()
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
+
+        checks.push(self.check_config(&repo_path).await?);
+
+        if let Some(check) = self.check_runtimes(&repo_path).await? {
+            checks.push(check);
+        }
+
+        if let Some(check) = self.check_env(&repo_path).await? {
+            checks.push(check);
+        }
+
+        if let Some(check) = self.check_signing(&settings, &repo_path).await? {
+            checks.push(check);
+        }
+
+        checks.extend(self.check_features(&settings)?);
+
+        Ok(checks)
+    }
+
+    async fn check_settings(&self) -> DotfResult<Result<Settings, DoctorCheck>> {
+        let settings_path = self.filesystem.dotf_settings_path();
+        if !self.filesystem.exists(&settings_path).await? {
+            return Ok(Err(DoctorCheck::new(
+                "Settings",
+                CheckStatus::Error,
+                "dotf is not initialized".to_string(),
+            )
+            .with_hint("Run 'dotf init --repo <repository>'")));
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Ok(Settings::from_toml(&content).map_err(|e| {
+            DoctorCheck::new(
+                "Settings",
+                CheckStatus::Error,
+                format!("{} is invalid: {}", settings_path, e),
+            )
+            .with_hint("Run 'dotf settings repair'")
+        }))
+    }
+
+    async fn check_config(&self, repo_path: &str) -> DotfResult<DoctorCheck> {
+        let config_path = format!("{}/dotf.toml", repo_path);
+        if !self.filesystem.exists(&config_path).await? {
+            return Ok(DoctorCheck::new(
+                "dotf.toml",
+                CheckStatus::Error,
+                format!("{} not found", config_path),
+            )
+            .with_hint("Run 'dotf schema init' in the repository to create one"));
+        }
+
+        let content = self.filesystem.read_to_string(&config_path).await?;
+        Ok(match toml::from_str::<DotfConfig>(&content) {
+            Ok(config) => DoctorCheck::new(
+                "dotf.toml",
+                CheckStatus::Ok,
+                format!("{} symlinks configured", config.symlinks.len()),
+            ),
+            Err(e) => DoctorCheck::new(
+                "dotf.toml",
+                CheckStatus::Error,
+                format!("Failed to parse: {}", e),
+            )
+            .with_hint("Run 'dotf schema test' for details"),
+        })
+    }
+
+    async fn check_runtimes(&self, repo_path: &str) -> DotfResult<Option<DoctorCheck>> {
+        let Some(check) =
+            check_runtimes(&self.filesystem, &self.version_managers, repo_path).await?
+        else {
+            return Ok(None);
+        };
+
+        let manager = check.manager.name();
+        let doctor_check = if !check.manager_available {
+            DoctorCheck::new(
+                "Runtimes",
+                CheckStatus::Warning,
+                format!(
+                    "{} declares runtime versions, but {} is not installed",
+                    check.files.join(", "),
+                    manager
+                ),
+            )
+            .with_hint("Install mise (https://mise.jdx.dev) or asdf (https://asdf-vm.com)")
+        } else if !check.missing.is_empty() {
+            let missing: Vec<String> = check
+                .missing
+                .iter()
+                .map(|runtime| format!("{} {}", runtime.tool, runtime.version))
+                .collect();
+            DoctorCheck::new(
+                "Runtimes",
+                CheckStatus::Warning,
+                format!("Missing runtime versions: {}", missing.join(", ")),
+            )
+            .with_hint("Run 'dotf install deps' to install them")
+        } else {
+            DoctorCheck::new(
+                "Runtimes",
+                CheckStatus::Ok,
+                format!(
+                    "All {} runtime versions installed ({})",
+                    check.declared.len(),
+                    manager
+                ),
+            )
+        };
+
+        Ok(Some(doctor_check))
+    }
+
+    async fn check_env(&self, repo_path: &str) -> DotfResult<Option<DoctorCheck>> {
+        let config_path = format!("{}/dotf.toml", repo_path);
+        if !self.filesystem.exists(&config_path).await? {
+            return Ok(None);
+        }
+        let content = self.filesystem.read_to_string(&config_path).await?;
+        let Ok(config) = toml::from_str::<DotfConfig>(&content) else {
+            return Ok(None);
+        };
+        if render_env(&config.env, &detect_platform()).is_none() {
+            return Ok(None);
+        }
+
+        let env_path = self.filesystem.dotf_env_path();
+        if !self.filesystem.exists(&env_path).await? {
+            return Ok(Some(
+                DoctorCheck::new(
+                    "Env",
+                    CheckStatus::Warning,
+                    format!("{} has not been generated", env_path),
+                )
+                .with_hint("Run 'dotf install env'"),
+            ));
+        }
+
+        if is_hand_edited(&self.filesystem.read_to_string(&env_path).await?) {
+            return Ok(Some(
+                DoctorCheck::new(
+                    "Env",
+                    CheckStatus::Warning,
+                    format!("{} was edited by hand", env_path),
+                )
+                .with_hint("Move the changes into [env], then run 'dotf install env --force'"),
+            ));
+        }
+
+        let home = dirs::home_dir().unwrap_or_default();
+        for rc in SHELL_RC_FILES {
+            let rc_path = home.join(rc).to_string_lossy().to_string();
+            if self.filesystem.exists(&rc_path).await?
+                && self
+                    .filesystem
+                    .read_to_string(&rc_path)
+                    .await?
+                    .contains("dotf/env.sh")
+            {
+                return Ok(Some(DoctorCheck::new(
+                    "Env",
+                    CheckStatus::Ok,
+                    format!("Sourced from ~/{}", rc),
+                )));
+            }
+        }
+
+        Ok(Some(
+            DoctorCheck::new(
+                "Env",
+                CheckStatus::Warning,
+                format!("{} is not sourced by your shell", env_path),
+            )
+            .with_hint(&format!(
+                "Add '[ -f \"{0}\" ] && . \"{0}\"' to your shell rc",
+                env_path
+            )),
+        ))
+    }
+
+    fn check_features(&self, settings: &Settings) -> DotfResult<Vec<DoctorCheck>> {
+        let mut checks = Vec::new();
+        let enabled = features::enabled(&settings.features, &self.enabled_features)?;
+        if !enabled.is_empty() {
+            let names: Vec<&str> = enabled.iter().map(|experiment| experiment.name).collect();
+            checks.push(DoctorCheck::new(
+                "Experiments",
+                CheckStatus::Ok,
+                format!("Enabled: {}", names.join(", ")),
+            ));
+        }
+
+        let unknown = features::unknown(&settings.features);
+        if !unknown.is_empty() {
+            checks.push(
+                DoctorCheck::new(
+                    "Experiments",
+                    CheckStatus::Warning,
+                    format!("Unknown features in settings: {}", unknown.join(", ")),
+                )
+                .with_hint("Remove them from [features] in settings.toml"),
+            );
+        }
+        Ok(checks)
+    }
+
+    async fn check_signing(
+        &self,
+        settings: &Settings,
+        repo_path: &str,
+    ) -> DotfResult<Option<DoctorCheck>> {
+        let Some(repository) = &self.repository else {
+            return Ok(None);
+        };
+        if !self.filesystem.exists(repo_path).await? {
+            return Ok(None);
+        }
+        if !settings.commit.sign && !repository.signing_configured(repo_path).await? {
+            return Ok(None);
+        }
+
+        Ok(Some(match repository.test_signing(repo_path).await {
+            Ok(()) => DoctorCheck::new(
+                "Signing",
+                CheckStatus::Ok,
+                "Commits made by dotf will be signed".to_string(),
+            ),
+            Err(e) => DoctorCheck::new("Signing", CheckStatus::Error, e.to_string()).with_hint(
+                "Fix the signing key or agent, or set 'sign = false' under [commit] in ~/.dotf/settings.toml",
+            ),
+        }))
+    }
+}
+
+impl<F: FileSystem + Clone> DoctorService<F> {
+    /// One check per phase of the recently timed commands, slowest first. A phase is a
+    /// warning when any run took too long, with what to try as the hint.
+    pub async fn slow_checks(&self) -> DotfResult<Vec<DoctorCheck>> {
+        let invocations = TimingStore::new(self.filesystem.clone()).load_all().await?;
+        if invocations.is_empty() {
+            return Ok(vec![DoctorCheck::new(
+                "Timings",
+                CheckStatus::Ok,
+                "Nothing recorded yet".to_string(),
+            )
+            .with_hint(
+                "Commands such as 'dotf status' and 'dotf sync' record how long each step takes",
+            )]);
+        }
+
+        Ok(summarize(&invocations)
+            .into_iter()
+            .map(|summary| {
+                let mut message = format!(
+                    "median {} over {} run(s), slowest {} in 'dotf {}'",
+                    format_millis(summary.median_millis),
+                    summary.runs,
+                    format_millis(summary.slowest_millis),
+                    summary.slowest_command
+                );
+                if summary.slowest_items > 0 {
+                    message.push_str(&format!(" for {} item(s)", summary.slowest_items));
+                }
+                if !summary.is_slow() {
+                    return DoctorCheck::new(&summary.name, CheckStatus::Ok, message);
+                }
+
+                message.push_str(if summary.is_consistently_slow() {
+                    "; slow in most runs"
+                } else {
+                    "; an occasional outlier"
+                });
+                let check = DoctorCheck::new(&summary.name, CheckStatus::Warning, message);
+                match summary.suggestion {
+                    Some(suggestion) => check.with_hint(suggestion),
+                    None => check,
+                }
+            })
+            .collect())
+    }
+}
+
+fn format_millis(millis: u64) -> String {
+    if millis < 1000 {
+        format!("{}ms", millis)
+    } else {
+        format!("{:.1}s", millis as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::settings::Repository as RepositorySettings;
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, repository::tests::MockRepository,
+        tool_version_manager::tests::MockToolVersionManager,
+    };
+    use chrono::Utc;
+
+    fn setup() -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            "[symlinks]\n\"zsh/.zshrc\" = \"~/.zshrc\"\n",
+        );
+        filesystem
+    }
+
+    #[tokio::test]
+    async fn test_doctor_not_initialized() {
+        let checks = DoctorService::new(MockFileSystem::new())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, CheckStatus::Error);
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_missing_runtimes() {
+        let filesystem = setup();
+        filesystem.add_file(
+            &format!("{}/mise.toml", filesystem.dotf_repo_path()),
+            "[tools]\nnode = \"20\"\npython = \"3.12\"\n",
+        );
+        let mise = MockToolVersionManager::new("mise", &["mise.toml", ".tool-versions"]);
+        mise.add_installed("node", "20");
+
+        let checks = DoctorService::new(filesystem)
+            .with_version_managers(vec![Box::new(mise)])
+            .run()
+            .await
+            .unwrap();
+
+        let statuses: Vec<(&str, CheckStatus)> = checks
+            .iter()
+            .map(|check| (check.name.as_str(), check.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ("Settings", CheckStatus::Ok),
+                ("dotf.toml", CheckStatus::Ok),
+                ("Runtimes", CheckStatus::Warning),
+            ]
+        );
+        assert_eq!(checks[2].message, "Missing runtime versions: python 3.12");
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_unsourced_env() {
+        let filesystem = setup();
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            "[symlinks]
+
+[env]
+vars = { EDITOR = \"nvim\" }
+",
+        );
+        let env_path = filesystem.dotf_env_path();
+        let rendered = render_env(
+            &toml::from_str("vars = { EDITOR = \"nvim\" }").unwrap(),
+            &detect_platform(),
+        )
+        .unwrap();
+        filesystem.add_file(&env_path, &rendered);
+
+        let doctor = DoctorService::new(filesystem.clone()).with_version_managers(Vec::new());
+        let checks = doctor.run().await.unwrap();
+        let env = checks.iter().find(|check| check.name == "Env").unwrap();
+        assert_eq!(env.status, CheckStatus::Warning);
+
+        let zshrc = dirs::home_dir().unwrap_or_default().join(".zshrc");
+        filesystem.add_file(
+            &zshrc.to_string_lossy(),
+            "[ -f ~/.config/dotf/env.sh ] && . ~/.config/dotf/env.sh\n",
+        );
+        let checks = doctor.run().await.unwrap();
+        let env = checks.iter().find(|check| check.name == "Env").unwrap();
+        assert_eq!(env.status, CheckStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn test_doctor_reports_broken_signing() {
+        let filesystem = setup();
+        filesystem
+            .create_dir_all(&filesystem.dotf_repo_path())
+            .await
+            .unwrap();
+        let repository = MockRepository::new();
+        let doctor = DoctorService::new(filesystem)
+            .with_version_managers(Vec::new())
+            .with_repository(Box::new(Clone::clone(&repository)));
+
+        let checks = doctor.run().await.unwrap();
+        assert!(checks.iter().all(|check| check.name != "Signing"));
+
+        repository.set_signing(true, Some("gpg failed to sign the data"));
+        let checks = doctor.run().await.unwrap();
+        let signing = checks.iter().find(|check| check.name == "Signing").unwrap();
+        assert_eq!(signing.status, CheckStatus::Error);
+        assert!(signing.message.contains("gpg failed to sign"));
+    }
+
+    #[tokio::test]
+    async fn test_doctor_lists_experiments() {
+        let filesystem = setup();
+        let settings_path = filesystem.dotf_settings_path();
+        let mut settings =
+            Settings::from_toml(&filesystem.read_to_string(&settings_path).await.unwrap()).unwrap();
+        settings.features.insert("changes".to_string(), true);
+        settings.features.insert("teleport".to_string(), true);
+        filesystem.add_file(&settings_path, &settings.to_toml().unwrap());
+
+        let checks = DoctorService::new(filesystem)
+            .with_version_managers(Vec::new())
+            .with_enabled_features(vec!["overlays".to_string()])
+            .run()
+            .await
+            .unwrap();
+        let experiments: Vec<(CheckStatus, &str)> = checks
+            .iter()
+            .filter(|check| check.name == "Experiments")
+            .map(|check| (check.status, check.message.as_str()))
+            .collect();
+        assert_eq!(
+            experiments,
+            vec![
+                (CheckStatus::Ok, "Enabled: changes, overlays"),
+                (
+                    CheckStatus::Warning,
+                    "Unknown features in settings: teleport"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_doctor_slow_checks() {
+        let filesystem = MockFileSystem::new();
+        let doctor = DoctorService::new(filesystem.clone()).with_version_managers(Vec::new());
+        let checks = doctor.slow_checks().await.unwrap();
+        assert_eq!(checks[0].status, CheckStatus::Ok);
+        assert!(checks[0].hint.is_some());
+
+        filesystem.add_file(
+            &filesystem.dotf_timings_path(),
+            r#"{"started_at":"2024-01-01T12:00:00Z","command":"status","millis":8400,"phases":[{"name":"git fetch","millis":8100},{"name":"git status","millis":40}]}
+"#,
+        );
+        let checks = doctor.slow_checks().await.unwrap();
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].name, "git fetch");
+        assert_eq!(checks[0].status, CheckStatus::Warning);
+        assert!(checks[0].message.contains("8.1s in 'dotf status'"));
+        assert!(checks[0].hint.as_deref().unwrap().contains("dotf greet"));
+        assert_eq!(checks[1].status, CheckStatus::Ok);
+    }
+}
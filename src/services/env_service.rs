@@ -0,0 +1,243 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::core::{
+    config::{loader, DotfConfig, Settings},
+    env::{is_hand_edited, render_env},
+    prompts::apply_to_env,
+    state::{RenderKind, StateStore},
+    tools::asset::sha256_hex,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+use crate::utils::platform::detect_platform;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum EnvFileStatus {
+    Written,
+    Unchanged,
+    /// `[env]` was removed, so the generated file was deleted
+    Removed,
+    /// The file was edited by hand and was left alone
+    Conflict,
+    NotConfigured,
+}
+
+/// Name the `[env]` table is recorded under as the source of the fragment
+pub const ENV_SOURCE: &str = "[env]";
+
+pub struct EnvService<F> {
+    filesystem: F,
+    state: StateStore<F>,
+}
+
+impl<F: FileSystem + Clone> EnvService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self {
+            state: StateStore::new(filesystem.clone()),
+            filesystem,
+        }
+    }
+
+    pub fn env_path(&self) -> String {
+        self.filesystem.dotf_env_path()
+    }
+
+    /// Regenerate the env fragment from `[env]`; a hand-edited file is only replaced with `force`
+    pub async fn generate(&self, force: bool) -> DotfResult<EnvFileStatus> {
+        let rendered = self.render().await?;
+        let status = self.write(rendered.as_deref(), force).await?;
+        if status != EnvFileStatus::Conflict {
+            let sources = rendered.map(|rendered| source_hashes(&rendered));
+            self.state
+                .record_render(&self.env_path(), RenderKind::Env, sources)
+                .await?;
+        }
+        Ok(status)
+    }
+
+    /// Content hash of `[env]` with prompt answers applied, or `None` when it is empty
+    pub async fn sources(&self) -> DotfResult<Option<BTreeMap<String, String>>> {
+        Ok(self
+            .render()
+            .await?
+            .map(|rendered| source_hashes(&rendered)))
+    }
+
+    async fn render(&self) -> DotfResult<Option<String>> {
+        let config = self.load_config().await?;
+        let answers = self.load_settings().await?.prompts;
+        Ok(render_env(
+            &apply_to_env(&config.env, &answers),
+            &detect_platform(),
+        ))
+    }
+
+    async fn write(&self, rendered: Option<&str>, force: bool) -> DotfResult<EnvFileStatus> {
+        let path = self.env_path();
+
+        let existing = if self.filesystem.exists(&path).await? {
+            Some(self.filesystem.read_to_string(&path).await?)
+        } else {
+            None
+        };
+        let edited = existing.as_deref().is_some_and(is_hand_edited);
+
+        let Some(rendered) = rendered else {
+            if existing.is_some() && !edited {
+                self.filesystem.remove_file(&path).await?;
+                return Ok(EnvFileStatus::Removed);
+            }
+            return Ok(EnvFileStatus::NotConfigured);
+        };
+
+        if existing.as_deref() == Some(rendered) {
+            return Ok(EnvFileStatus::Unchanged);
+        }
+        if edited && !force {
+            return Ok(EnvFileStatus::Conflict);
+        }
+
+        if let Some(parent) = Path::new(&path).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.filesystem.write(&path, rendered).await?;
+
+        Ok(EnvFileStatus::Written)
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        let settings = self.load_settings().await?;
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, &settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+/// The rendered fragment stands in for `[env]`: it changes exactly when `[env]` or an
+/// answer it uses does
+fn source_hashes(rendered: &str) -> BTreeMap<String, String> {
+    BTreeMap::from([(ENV_SOURCE.to_string(), sha256_hex(rendered.as_bytes()))])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{settings::Repository as RepositorySettings, Settings};
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::Utc;
+
+    fn setup(env: &str) -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        set_config(&filesystem, env);
+        filesystem
+    }
+
+    fn set_config(filesystem: &MockFileSystem, env: &str) {
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            &format!("[symlinks]\n\n{}", env),
+        );
+    }
+
+    #[tokio::test]
+    async fn test_generate_detects_hand_edits() {
+        let filesystem = setup("[env]\npath = [\"~/.local/bin\"]\n");
+        let service = EnvService::new(filesystem.clone());
+        let path = service.env_path();
+
+        assert_eq!(
+            service.generate(false).await.unwrap(),
+            EnvFileStatus::Written
+        );
+        assert_eq!(
+            service.generate(false).await.unwrap(),
+            EnvFileStatus::Unchanged
+        );
+
+        let edited = format!(
+            "{}export EDITOR=vim\n",
+            filesystem.read_to_string(&path).await.unwrap()
+        );
+        filesystem.add_file(&path, &edited);
+        set_config(&filesystem, "[env]\npath = [\"~/bin\"]\n");
+
+        assert_eq!(
+            service.generate(false).await.unwrap(),
+            EnvFileStatus::Conflict
+        );
+        assert_eq!(filesystem.read_to_string(&path).await.unwrap(), edited);
+
+        assert_eq!(
+            service.generate(true).await.unwrap(),
+            EnvFileStatus::Written
+        );
+        assert!(filesystem
+            .read_to_string(&path)
+            .await
+            .unwrap()
+            .contains("$HOME/bin"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_removes_file_when_env_is_dropped() {
+        let filesystem = setup("[env]\nvars = { EDITOR = \"nvim\" }\n");
+        let service = EnvService::new(filesystem.clone());
+        service.generate(false).await.unwrap();
+
+        set_config(&filesystem, "");
+        assert_eq!(
+            service.generate(false).await.unwrap(),
+            EnvFileStatus::Removed
+        );
+        assert!(!filesystem.exists(&service.env_path()).await.unwrap());
+    }
+}
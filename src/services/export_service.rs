@@ -0,0 +1,412 @@
+use serde::Serialize;
+
+use crate::core::config::Settings;
+use crate::core::symlinks::{permissions::format_chmod, LinkMode, LinkStep};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+/// Helpers at the top of an exported link script. Something already at a target is
+/// never replaced; it is reported and makes the script exit with 1 at the end.
+const SCRIPT_PRELUDE: &str = r#"set -u
+
+DOTF_REPO="${DOTF_REPO:-$HOME/.dotf/repo}"
+conflicts=0
+
+in_the_way() {
+    echo "skipped $1: something else is already there" >&2
+    conflicts=$((conflicts + 1))
+}
+
+# link SOURCE TARGET: a symbolic link, as dotf makes by default
+link() {
+    if [ -L "$2" ] && [ "$(readlink "$2")" = "$1" ]; then
+        return 0
+    fi
+    if [ -e "$2" ] || [ -L "$2" ]; then
+        in_the_way "$2"
+        return 0
+    fi
+    mkdir -p "$(dirname "$2")" && ln -s "$1" "$2"
+}
+
+# place cp|ln SOURCE TARGET: a copy or hard link (mode = "copy" or "hardlink")
+place() {
+    if [ -e "$3" ] || [ -L "$3" ]; then
+        cmp -s "$2" "$3" || in_the_way "$3"
+        return 0
+    fi
+    mkdir -p "$(dirname "$3")" && "$1" "$2" "$3"
+}
+"#;
+
+/// What `export_stow` put in the package directory
+#[derive(Debug, Serialize)]
+pub struct StowExport {
+    /// Files copied into the package, relative to it
+    pub files: Vec<String>,
+    /// Targets outside the home directory, which stow cannot place
+    pub skipped: Vec<String>,
+}
+
+pub struct ExportService<F> {
+    filesystem: F,
+}
+
+impl<F: FileSystem> ExportService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self { filesystem }
+    }
+
+    /// Render `steps` (from `InstallService::plan_config`) as a home-manager `home.file`
+    /// module, so it holds the same targets `dotf install config` deploys.
+    ///
+    /// Sources are linked with `mkOutOfStoreSymlink` so edits in the dotf
+    /// repository stay live, matching how dotf itself manages them.
+    pub async fn export_home_manager(&self, steps: &[LinkStep]) -> DotfResult<String> {
+        let home = home_dir()?;
+
+        let mut entries: Vec<(String, String)> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
+
+        for step in steps {
+            let operation = &step.operation;
+            match operation.target_path.strip_prefix(&format!("{}/", home)) {
+                Some(relative) => {
+                    entries.push((relative.to_string(), operation.source_path.clone()))
+                }
+                None => skipped.push(operation.target_path.clone()),
+            }
+        }
+
+        entries.sort();
+        skipped.sort();
+
+        let mut output = vec![
+            "# Generated by dotf from dotf.toml".to_string(),
+            "{ config, ... }:".to_string(),
+            String::new(),
+            "{".to_string(),
+            "  home.file = {".to_string(),
+        ];
+        for (target, source) in &entries {
+            output.push(format!(
+                "    {}.source = config.lib.file.mkOutOfStoreSymlink {};",
+                nix_string(target),
+                nix_string(source)
+            ));
+        }
+        output.push("  };".to_string());
+        output.push("}".to_string());
+
+        if !skipped.is_empty() {
+            output.push(String::new());
+            output.push(
+                "# Targets outside the home directory are not supported by home.file:".to_string(),
+            );
+            for target in &skipped {
+                output.push(format!("#   {}", target));
+            }
+        }
+
+        Ok(format!("{}\n", output.join("\n")))
+    }
+
+    /// Render `steps` (from `InstallService::plan_config`) as a POSIX shell script that
+    /// deploys the same targets with `ln -s`, `cp` and `ln`, for machines without dotf.
+    /// Paths under the repository and the home directory are written relative to
+    /// `$DOTF_REPO` and `$HOME`, so the script works wherever the repository is cloned.
+    pub async fn export_shell(&self, steps: &[LinkStep]) -> DotfResult<String> {
+        let repo_path = self.repo_path().await?;
+        let home = home_dir()?;
+        let portable = |path: &str| portable_path(path, &repo_path, &home);
+
+        let mut output = vec![
+            "#!/bin/sh".to_string(),
+            "# Generated by dotf from dotf.toml: deploys the dotfiles without dotf.".to_string(),
+            "# Set DOTF_REPO to where the repository is cloned (default ~/.dotf/repo).".to_string(),
+            SCRIPT_PRELUDE.to_string(),
+        ];
+        for step in steps {
+            let operation = &step.operation;
+            let source = portable(&operation.source_path);
+            let target = portable(&operation.target_path);
+            output.push(match operation.mode {
+                LinkMode::Symlink => format!("link {} {}", source, target),
+                LinkMode::Copy => format!("place cp {} {}", source, target),
+                LinkMode::Hardlink => format!("place ln {} {}", source, target),
+            });
+            if let Some(chmod) = operation.permissions.chmod {
+                output.push(format!("chmod {} {}", format_chmod(chmod), target));
+            }
+            if let Some(owner) = &operation.permissions.owner {
+                output.push(format!("chown {} {}", shell_quote(owner), target));
+            }
+        }
+        output.push(String::new());
+        output.push("[ \"$conflicts\" -eq 0 ] || exit 1".to_string());
+
+        Ok(format!("{}\n", output.join("\n")))
+    }
+
+    /// Copy the sources of `steps` into `package` laid out like the home directory, so
+    /// `stow --no-folding` links them the way dotf does. Modes and owners are not part
+    /// of a stow package; copies keep the mode of their source.
+    pub async fn export_stow(&self, steps: &[LinkStep], package: &str) -> DotfResult<StowExport> {
+        if self.filesystem.exists(package).await? {
+            return Err(DotfError::Operation(format!(
+                "{} already exists; choose a new directory for the stow package",
+                package
+            )));
+        }
+        let home = home_dir()?;
+
+        let mut files = Vec::new();
+        let mut skipped = Vec::new();
+        for step in steps {
+            let operation = &step.operation;
+            match operation.target_path.strip_prefix(&format!("{}/", home)) {
+                Some(relative) => {
+                    self.filesystem
+                        .copy_file(
+                            &operation.source_path,
+                            &format!("{}/{}", package.trim_end_matches('/'), relative),
+                        )
+                        .await?;
+                    files.push(relative.to_string());
+                }
+                None => skipped.push(operation.target_path.clone()),
+            }
+        }
+
+        files.sort();
+        skipped.sort();
+        Ok(StowExport { files, skipped })
+    }
+
+    async fn repo_path(&self) -> DotfResult<String> {
+        let settings = self.load_settings().await?;
+        Ok(settings
+            .repository
+            .local
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path()))
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+fn home_dir() -> DotfResult<String> {
+    dirs::home_dir()
+        .map(|home| home.to_string_lossy().to_string())
+        .ok_or_else(|| DotfError::Operation("Could not determine home directory".to_string()))
+}
+
+/// `path` quoted for the shell, with the repository and home directory replaced by
+/// `$DOTF_REPO` and `$HOME`
+fn portable_path(path: &str, repo_path: &str, home: &str) -> String {
+    let relative = |base: &str| path.strip_prefix(&format!("{}/", base.trim_end_matches('/')));
+    match (relative(repo_path), relative(home)) {
+        (Some(rest), _) => format!("\"$DOTF_REPO\"/{}", shell_quote(rest)),
+        (None, Some(rest)) => format!("\"$HOME\"/{}", shell_quote(rest)),
+        (None, None) => shell_quote(path),
+    }
+}
+
+/// Quote a value as a single shell word
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Quote a value as a Nix string literal
+fn nix_string(value: &str) -> String {
+    let escaped = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${");
+    format!("\"{}\"", escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::settings::Repository;
+    use crate::core::symlinks::{LinkAction, Permissions, SymlinkOperation};
+    use crate::services::InstallService;
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, prompt::tests::MockPrompt,
+        script_executor::tests::MockScriptExecutor,
+    };
+    use chrono::Utc;
+
+    fn setup(filesystem: &MockFileSystem, config: &str, profile: Option<&str>) {
+        let settings = Settings {
+            repository: Repository {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: profile.map(str::to_string),
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            config,
+        );
+    }
+
+    async fn plan(filesystem: &MockFileSystem) -> DotfResult<Vec<LinkStep>> {
+        InstallService::new(
+            filesystem.clone(),
+            MockScriptExecutor::new(),
+            MockPrompt::new(),
+        )
+        .plan_config()
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_export_home_manager() {
+        let filesystem = MockFileSystem::new();
+        setup(
+            &filesystem,
+            r#"
+[symlinks]
+"zsh/.zshrc" = "~/.zshrc"
+"git/.gitconfig" = "~/.gitconfig"
+"hosts" = "/etc/hosts"
+"work/.npmrc" = { target = "~/.npmrc", when = { hostname = "no-such-host.invalid" } }
+
+[profile.work.symlinks]
+"work/.gitconfig" = "~/.gitconfig"
+"#,
+            Some("work"),
+        );
+        let repo_path = filesystem.dotf_repo_path();
+        for source in ["zsh/.zshrc", "git/.gitconfig", "hosts", "work/.gitconfig"] {
+            filesystem.add_file(&format!("{}/{}", repo_path, source), "");
+        }
+
+        let service = ExportService::new(filesystem.clone());
+        let output = service
+            .export_home_manager(&plan(&filesystem).await.unwrap())
+            .await
+            .unwrap();
+
+        assert!(output.contains(&format!(
+            "\".zshrc\".source = config.lib.file.mkOutOfStoreSymlink \"{}/zsh/.zshrc\";",
+            repo_path
+        )));
+        // The active profile replaces the common entry, as in install
+        assert!(output.contains(&format!(
+            "\".gitconfig\".source = config.lib.file.mkOutOfStoreSymlink \"{}/work/.gitconfig\";",
+            repo_path
+        )));
+        // An entry whose condition this machine does not meet is left out
+        assert!(!output.contains(".npmrc"));
+        assert!(output.contains("#   /etc/hosts"));
+    }
+
+    #[tokio::test]
+    async fn test_export_not_initialized() {
+        let result = plan(&MockFileSystem::new()).await;
+        assert!(matches!(result.unwrap_err(), DotfError::NotInitialized));
+    }
+
+    fn step(source: &str, target: &str, mode: LinkMode, chmod: Option<u32>) -> LinkStep {
+        LinkStep {
+            operation: SymlinkOperation {
+                source_path: source.to_string(),
+                target_path: target.to_string(),
+                mode,
+                permissions: Permissions { chmod, owner: None },
+            },
+            action: LinkAction::Create,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_shell_and_stow() {
+        let filesystem = MockFileSystem::new();
+        setup(&filesystem, "", None);
+        let repo_path = filesystem.dotf_repo_path();
+        let home = home_dir().unwrap();
+        filesystem.add_file(&format!("{}/zsh/.zshrc", repo_path), "export A=1");
+        filesystem.add_file(&format!("{}/ssh/config", repo_path), "Host *");
+        let steps = vec![
+            step(
+                &format!("{}/zsh/.zshrc", repo_path),
+                &format!("{}/.zshrc", home),
+                LinkMode::Symlink,
+                None,
+            ),
+            step(
+                &format!("{}/ssh/config", repo_path),
+                &format!("{}/.ssh/config", home),
+                LinkMode::Copy,
+                Some(0o600),
+            ),
+            step(
+                &format!("{}/zsh/.zshrc", repo_path),
+                "/etc/it's",
+                LinkMode::Symlink,
+                None,
+            ),
+        ];
+
+        let service = ExportService::new(filesystem.clone());
+        let script = service.export_shell(&steps).await.unwrap();
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("link \"$DOTF_REPO\"/'zsh/.zshrc' \"$HOME\"/'.zshrc'\n"));
+        assert!(script.contains("place cp \"$DOTF_REPO\"/'ssh/config' \"$HOME\"/'.ssh/config'\n"));
+        assert!(script.contains("chmod 600 \"$HOME\"/'.ssh/config'\n"));
+        assert!(script.contains("link \"$DOTF_REPO\"/'zsh/.zshrc' '/etc/it'\\''s'\n"));
+
+        let export = service.export_stow(&steps, "/stow/dotfiles").await.unwrap();
+        assert_eq!(export.files, vec![".ssh/config", ".zshrc"]);
+        assert_eq!(export.skipped, vec!["/etc/it's"]);
+        assert_eq!(
+            filesystem
+                .read_to_string("/stow/dotfiles/.ssh/config")
+                .await
+                .unwrap(),
+            "Host *"
+        );
+        filesystem.add_directory("/stow/existing");
+        assert!(service.export_stow(&steps, "/stow/existing").await.is_err());
+    }
+
+    #[test]
+    fn test_nix_string_escaping() {
+        assert_eq!(nix_string("a\"b"), "\"a\\\"b\"");
+        assert_eq!(nix_string("${HOME}"), "\"\\${HOME}\"");
+    }
+}
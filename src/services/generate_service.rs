@@ -0,0 +1,721 @@
+use crate::core::config::{DotfConfig, Repository, Settings};
+use crate::error::{DotfError, DotfResult};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// Release asset downloaded into generated images
+const DOTF_RELEASE_ASSET: &str = "dotf-linux-x86_64-musl";
+
+/// Package managers the generated dependency scripts know how to drive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Brew,
+    Apt,
+    Dnf,
+    Pacman,
+    Zypper,
+    Apk,
+}
+
+impl PackageManager {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew",
+            PackageManager::Apt => "apt-get",
+            PackageManager::Dnf => "dnf",
+            PackageManager::Pacman => "pacman",
+            PackageManager::Zypper => "zypper",
+            PackageManager::Apk => "apk",
+        }
+    }
+
+    /// Shell snippet answering "is package $1 installed?"
+    fn is_installed_check(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew list --versions \"$1\" >/dev/null 2>&1",
+            PackageManager::Apt => "dpkg -s \"$1\" >/dev/null 2>&1",
+            PackageManager::Dnf | PackageManager::Zypper => "rpm -q \"$1\" >/dev/null 2>&1",
+            PackageManager::Pacman => "pacman -Qi \"$1\" >/dev/null 2>&1",
+            PackageManager::Apk => "apk info -e \"$1\" >/dev/null 2>&1",
+        }
+    }
+
+    fn install_command(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew install \"$1\"",
+            PackageManager::Apt => "${SUDO} apt-get install -y \"$1\"",
+            PackageManager::Dnf => "${SUDO} dnf install -y \"$1\"",
+            PackageManager::Pacman => "${SUDO} pacman -S --noconfirm --needed \"$1\"",
+            PackageManager::Zypper => "${SUDO} zypper --non-interactive install \"$1\"",
+            PackageManager::Apk => "${SUDO} apk add \"$1\"",
+        }
+    }
+
+    fn update_command(&self) -> Option<&'static str> {
+        match self {
+            PackageManager::Brew => Some("brew update"),
+            PackageManager::Apt => Some("${SUDO} apt-get update"),
+            PackageManager::Pacman => Some("${SUDO} pacman -Sy"),
+            PackageManager::Apk => Some("${SUDO} apk update"),
+            PackageManager::Dnf | PackageManager::Zypper => None,
+        }
+    }
+
+    /// Detect the package manager available on the current machine for `platform`
+    pub fn detect(platform: &str) -> Self {
+        if platform == "macos" {
+            return PackageManager::Brew;
+        }
+
+        let candidates = [
+            ("apt-get", PackageManager::Apt),
+            ("dnf", PackageManager::Dnf),
+            ("pacman", PackageManager::Pacman),
+            ("zypper", PackageManager::Zypper),
+            ("apk", PackageManager::Apk),
+        ];
+
+        for (binary, manager) in candidates {
+            if command_exists(binary) {
+                return manager;
+            }
+        }
+
+        PackageManager::Apt
+    }
+
+    /// Guess the package manager of a container image from its name
+    pub fn for_image(image: &str) -> Self {
+        let name = image.rsplit('/').next().unwrap_or(image);
+        let candidates = [
+            ("alpine", PackageManager::Apk),
+            ("fedora", PackageManager::Dnf),
+            ("rockylinux", PackageManager::Dnf),
+            ("almalinux", PackageManager::Dnf),
+            ("centos", PackageManager::Dnf),
+            ("archlinux", PackageManager::Pacman),
+            ("opensuse", PackageManager::Zypper),
+        ];
+
+        candidates
+            .iter()
+            .find(|(prefix, _)| name.starts_with(prefix) || image.contains(&format!("{}/", prefix)))
+            .map(|(_, manager)| *manager)
+            .unwrap_or(PackageManager::Apt)
+    }
+
+    /// Command that installs what a generated image needs before dotf can run
+    fn image_setup_command(&self) -> &'static str {
+        match self {
+            PackageManager::Brew => "brew install curl git",
+            PackageManager::Apt => {
+                "apt-get update \\\n    && apt-get install -y --no-install-recommends ca-certificates curl git sudo \\\n    && rm -rf /var/lib/apt/lists/*"
+            }
+            PackageManager::Dnf => "dnf install -y curl git sudo shadow-utils && dnf clean all",
+            PackageManager::Pacman => "pacman -Sy --noconfirm --needed curl git sudo",
+            PackageManager::Zypper => {
+                "zypper --non-interactive install curl git sudo shadow && zypper clean"
+            }
+            PackageManager::Apk => "apk add --no-cache bash ca-certificates curl git sudo",
+        }
+    }
+
+    fn add_user_command(&self, user: &str) -> String {
+        match self {
+            PackageManager::Apk => format!("adduser -D -s /bin/sh {}", user),
+            _ => format!("useradd --create-home --shell /bin/bash {}", user),
+        }
+    }
+}
+
+/// What goes into a generated Dockerfile
+#[derive(Debug, Clone)]
+pub struct DockerfileOptions {
+    pub base_image: String,
+    /// User the configuration is installed for; `root` installs into /root
+    pub user: String,
+    /// Run `dotf install deps` during the build
+    pub deps: bool,
+    /// Custom scripts from `[scripts.custom]` to run after the configuration
+    pub custom: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct GeneratedDockerfile {
+    pub path: String,
+    /// Prompts with neither a stored answer nor a default; the build stops at them
+    pub unanswered: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct GeneratedScript {
+    pub script_path: String,
+    pub config_key: String,
+    pub package_manager: PackageManager,
+    pub config_updated: bool,
+}
+
+pub struct GenerateService;
+
+impl Default for GenerateService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GenerateService {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Create a starter dependency script and register it under [scripts.deps]
+    ///
+    /// `output` is relative to the directory containing `config_file`, which is
+    /// also how dotf resolves script paths at install time.
+    pub async fn deps_script(
+        &self,
+        platform: &str,
+        output: Option<String>,
+        config_file: &str,
+        force: bool,
+    ) -> DotfResult<GeneratedScript> {
+        if platform != "linux" && platform != "macos" {
+            return Err(DotfError::UnsupportedPlatform(format!(
+                "{} (expected 'linux' or 'macos')",
+                platform
+            )));
+        }
+
+        if !Path::new(config_file).exists() {
+            return Err(DotfError::Config(format!(
+                "Configuration file not found: {}. Run 'dotf schema init' first",
+                config_file
+            )));
+        }
+
+        let relative_script =
+            output.unwrap_or_else(|| format!("scripts/install-deps-{}.sh", platform));
+        let repo_root = Path::new(config_file)
+            .parent()
+            .filter(|p| !p.as_os_str().is_empty())
+            .unwrap_or_else(|| Path::new("."));
+        let script_path = repo_root.join(&relative_script);
+
+        if script_path.exists() && !force {
+            return Err(DotfError::Operation(format!(
+                "{} already exists. Use --force to overwrite",
+                script_path.display()
+            )));
+        }
+
+        // Settle the dotf.toml entry before touching the repository, so a refused or
+        // unparsable config leaves no orphan script behind
+        let config_content = fs::read_to_string(config_file).map_err(DotfError::Io)?;
+        let updated = set_deps_script_entry(&config_content, platform, &relative_script, force)?;
+        let config_updated = updated != config_content;
+
+        let package_manager = PackageManager::detect(platform);
+        let content = self.generate_deps_script(platform, package_manager);
+
+        if let Some(parent) = script_path.parent() {
+            fs::create_dir_all(parent).map_err(DotfError::Io)?;
+        }
+        fs::write(&script_path, content).map_err(DotfError::Io)?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))
+                .map_err(DotfError::Io)?;
+        }
+
+        if config_updated {
+            fs::write(config_file, updated).map_err(DotfError::Io)?;
+        }
+
+        Ok(GeneratedScript {
+            script_path: script_path.to_string_lossy().to_string(),
+            config_key: format!("scripts.deps.{}", platform),
+            package_manager,
+            config_updated,
+        })
+    }
+
+    /// Write a Dockerfile that installs dotf, clones the repository from `settings` and
+    /// installs it for `options.user` during the build
+    pub async fn dockerfile(
+        &self,
+        settings: &Settings,
+        config: &DotfConfig,
+        options: &DockerfileOptions,
+        output: &str,
+        force: bool,
+    ) -> DotfResult<GeneratedDockerfile> {
+        if let Some(name) = options
+            .custom
+            .iter()
+            .find(|name| !config.scripts.custom.contains_key(*name))
+        {
+            return Err(DotfError::Config(format!(
+                "Custom script '{}' not found in [scripts.custom]",
+                name
+            )));
+        }
+
+        if Path::new(output).exists() && !force {
+            return Err(DotfError::Operation(format!(
+                "{} already exists. Use --force to overwrite",
+                output
+            )));
+        }
+
+        // Stored answers keep the build from prompting; defaults cover the rest
+        let mut answers = BTreeMap::new();
+        let mut unanswered = Vec::new();
+        for (name, prompt) in &config.prompts {
+            match settings.prompts.get(name).or(prompt.default.as_ref()) {
+                Some(answer) => {
+                    answers.insert(name.clone(), answer.clone());
+                }
+                None => unanswered.push(name.clone()),
+            }
+        }
+
+        let mut image_settings = Settings::new_with_details(
+            &settings.repository.remote,
+            settings.repository.branch.clone(),
+            None,
+        );
+        image_settings.prompts = answers;
+        let content = render_dockerfile(&image_settings.to_toml()?, &settings.repository, options);
+
+        if let Some(parent) = Path::new(output).parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(DotfError::Io)?;
+            }
+        }
+        fs::write(output, content).map_err(DotfError::Io)?;
+
+        Ok(GeneratedDockerfile {
+            path: output.to_string(),
+            unanswered,
+        })
+    }
+
+    /// Render the dependency script template
+    fn generate_deps_script(&self, platform: &str, package_manager: PackageManager) -> String {
+        let update = package_manager
+            .update_command()
+            .map(|cmd| format!("    {}", cmd))
+            .unwrap_or_else(|| "    :".to_string());
+
+        format!(
+            r#"#!/usr/bin/env bash
+# Dependency installation script for {platform}
+# Generated by `dotf generate deps-script`. Run via `dotf install deps`.
+#
+# This script must be idempotent: dotf may run it again on every install.
+
+set -euo pipefail
+
+log_info() {{ printf '\033[36m[dotf]\033[0m %s\n' "$*"; }}
+log_warn() {{ printf '\033[33m[dotf]\033[0m %s\n' "$*" >&2; }}
+log_error() {{ printf '\033[31m[dotf]\033[0m %s\n' "$*" >&2; }}
+
+SUDO=""
+if [ "$(id -u)" -ne 0 ] && command -v sudo >/dev/null 2>&1; then
+    SUDO="sudo"
+fi
+
+if ! command -v {manager} >/dev/null 2>&1; then
+    log_error "{manager} is required but was not found"
+    exit 1
+fi
+
+is_installed() {{
+    {check}
+}}
+
+update_index() {{
+{update}
+}}
+
+install_package() {{
+    if is_installed "$1"; then
+        log_info "$1 already installed"
+    else
+        log_info "Installing $1"
+        {install}
+    fi
+}}
+
+PACKAGES=(
+    git
+    curl
+    # Add your packages here
+)
+
+log_info "Installing dependencies with {manager}"
+update_index
+for package in "${{PACKAGES[@]}}"; do
+    install_package "$package"
+done
+log_info "Dependencies installed"
+"#,
+            platform = platform,
+            manager = package_manager.name(),
+            check = package_manager.is_installed_check(),
+            update = update,
+            install = package_manager.install_command(),
+        )
+    }
+}
+
+/// Render a Dockerfile using BuildKit heredocs for the settings file
+fn render_dockerfile(
+    settings_toml: &str,
+    repository: &Repository,
+    options: &DockerfileOptions,
+) -> String {
+    let package_manager = PackageManager::for_image(&options.base_image);
+    let home = if options.user == "root" {
+        "/root".to_string()
+    } else {
+        format!("/home/{}", options.user)
+    };
+
+    let user_setup = if options.user == "root" {
+        String::new()
+    } else {
+        format!(
+            "RUN {add_user} \\\n    && echo '{user} ALL=(ALL) NOPASSWD:ALL' > /etc/sudoers.d/{user}\n",
+            add_user = package_manager.add_user_command(&options.user),
+            user = options.user,
+        )
+    };
+
+    let branch = repository
+        .branch
+        .as_ref()
+        .map(|branch| format!("--branch {} ", branch))
+        .unwrap_or_default();
+
+    let mut steps = Vec::new();
+    if options.deps {
+        steps.push("dotf install deps".to_string());
+    }
+    steps.push("dotf install config".to_string());
+    steps.extend(
+        options
+            .custom
+            .iter()
+            .map(|name| format!("dotf install custom {}", name)),
+    );
+
+    format!(
+        r#"# syntax=docker/dockerfile:1
+# Generated by `dotf generate dockerfile`. Build with `docker build .` or `podman build .`
+FROM {base_image}
+
+RUN {setup}
+
+ARG DOTF_VERSION=latest
+RUN if [ "$DOTF_VERSION" = latest ]; then \
+        url=https://github.com/k1-c/dotf/releases/latest/download/{asset}; \
+    else \
+        url=https://github.com/k1-c/dotf/releases/download/v$DOTF_VERSION/{asset}; \
+    fi \
+    && curl -fsSL "$url" -o /usr/local/bin/dotf \
+    && chmod +x /usr/local/bin/dotf
+
+{user_setup}USER {user}
+WORKDIR {home}
+
+RUN git clone {branch}{remote} {home}/.dotf/repo
+COPY --chown={user} <<'EOF' {home}/.dotf/settings.toml
+{settings}EOF
+
+RUN {steps}
+"#,
+        base_image = options.base_image,
+        setup = package_manager.image_setup_command(),
+        asset = DOTF_RELEASE_ASSET,
+        user_setup = user_setup,
+        user = options.user,
+        home = home,
+        branch = branch,
+        remote = repository.remote,
+        settings = settings_toml,
+        steps = steps.join(" \\\n    && "),
+    )
+}
+
+fn command_exists(binary: &str) -> bool {
+    std::env::var_os("PATH")
+        .map(|paths| std::env::split_paths(&paths).any(|dir| dir.join(binary).is_file()))
+        .unwrap_or(false)
+}
+
+/// Set `<platform> = "<script>"` in [scripts.deps], whether it is a section or an
+/// inline table, keeping the rest of the file (including comments) untouched.
+fn set_deps_script_entry(
+    content: &str,
+    platform: &str,
+    script: &str,
+    force: bool,
+) -> DotfResult<String> {
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
+    let scripts = document
+        .entry("scripts")
+        .or_insert_with(|| {
+            let mut scripts = toml_edit::Table::new();
+            scripts.set_implicit(true);
+            toml_edit::Item::Table(scripts)
+        })
+        .as_table_like_mut()
+        .ok_or_else(|| DotfError::Config("[scripts] in dotf.toml is not a table".to_string()))?;
+    if !scripts.contains_key("deps") {
+        scripts.insert("deps", toml_edit::table());
+    }
+    let deps = scripts
+        .get_mut("deps")
+        .and_then(toml_edit::Item::as_table_like_mut)
+        .ok_or_else(|| {
+            DotfError::Config("[scripts.deps] in dotf.toml is not a table".to_string())
+        })?;
+
+    if let Some(existing) = deps.get(platform) {
+        if existing.as_str() == Some(script) {
+            return Ok(content.to_string());
+        }
+        if !force {
+            return Err(DotfError::Config(format!(
+                "scripts.deps.{} is already set ({}). Use --force to replace it",
+                platform,
+                existing.to_string().trim()
+            )));
+        }
+    }
+    deps.insert(platform, toml_edit::value(script));
+    // An inline table keeps the spacing after what used to be its last value
+    if let Some(deps) = document["scripts"]["deps"].as_inline_table_mut() {
+        deps.fmt();
+    }
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::DotfConfig;
+    use tempfile::TempDir;
+
+    const TEMPLATE: &str = r#"[symlinks]
+"zsh/.zshrc" = "~/.zshrc"
+
+[scripts.deps]
+# Platform-specific dependency installation scripts
+
+[scripts.custom]
+"#;
+
+    #[test]
+    fn test_set_deps_script_entry_inserts_into_section() {
+        let updated =
+            set_deps_script_entry(TEMPLATE, "linux", "scripts/install-deps-linux.sh", false)
+                .unwrap();
+        let config: DotfConfig = toml::from_str(&updated).unwrap();
+
+        assert_eq!(
+            config.scripts.deps.linux.as_deref(),
+            Some("scripts/install-deps-linux.sh")
+        );
+        assert!(updated.contains("# Platform-specific dependency installation scripts"));
+    }
+
+    #[test]
+    fn test_set_deps_script_entry_requires_force_to_replace() {
+        let content = "[scripts.deps]\nlinux = \"old.sh\"\n";
+
+        assert!(set_deps_script_entry(content, "linux", "new.sh", false).is_err());
+
+        let updated = set_deps_script_entry(content, "linux", "new.sh", true).unwrap();
+        assert_eq!(updated, "[scripts.deps]\nlinux = \"new.sh\"\n");
+    }
+
+    #[test]
+    fn test_set_deps_script_entry_appends_missing_section() {
+        let updated = set_deps_script_entry("[symlinks]\n", "macos", "deps.sh", false).unwrap();
+        let config: DotfConfig = toml::from_str(&updated).unwrap();
+        assert_eq!(config.scripts.deps.macos.as_deref(), Some("deps.sh"));
+    }
+
+    #[test]
+    fn test_set_deps_script_entry_updates_inline_table() {
+        let content = "[scripts]\ndeps = { macos = \"deps-macos.sh\" }\n";
+
+        let updated = set_deps_script_entry(content, "linux", "deps-linux.sh", false).unwrap();
+        assert_eq!(
+            updated,
+            "[scripts]\ndeps = { macos = \"deps-macos.sh\", linux = \"deps-linux.sh\" }\n"
+        );
+        assert!(set_deps_script_entry(content, "macos", "other.sh", false).is_err());
+    }
+
+    #[test]
+    fn test_set_deps_script_entry_escapes_script_path() {
+        let script = r#"scripts/deps "new"\linux.sh"#;
+        let updated = set_deps_script_entry(TEMPLATE, "linux", script, false).unwrap();
+        let config: DotfConfig = toml::from_str(&updated).unwrap();
+        assert_eq!(config.scripts.deps.linux.as_deref(), Some(script));
+    }
+
+    #[tokio::test]
+    async fn test_deps_script_generation() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("dotf.toml");
+        fs::write(&config_path, TEMPLATE).unwrap();
+
+        let service = GenerateService::new();
+        let generated = service
+            .deps_script("macos", None, &config_path.to_string_lossy(), false)
+            .await
+            .unwrap();
+
+        assert_eq!(generated.package_manager, PackageManager::Brew);
+        assert!(generated.config_updated);
+
+        let script =
+            fs::read_to_string(temp_dir.path().join("scripts/install-deps-macos.sh")).unwrap();
+        assert!(script.contains("set -euo pipefail"));
+        assert!(script.contains("brew install"));
+
+        // Running again without --force must not clobber the script
+        let result = service
+            .deps_script("macos", None, &config_path.to_string_lossy(), false)
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deps_script_refused_entry_writes_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = temp_dir.path().join("dotf.toml");
+        let content = "[scripts.deps]\nlinux = \"existing.sh\"\n";
+        fs::write(&config_path, content).unwrap();
+
+        let result = GenerateService::new()
+            .deps_script("linux", None, &config_path.to_string_lossy(), false)
+            .await;
+
+        assert!(result.is_err());
+        assert!(!temp_dir
+            .path()
+            .join("scripts/install-deps-linux.sh")
+            .exists());
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), content);
+    }
+
+    #[tokio::test]
+    async fn test_dockerfile_generation() {
+        let temp_dir = TempDir::new().unwrap();
+        let output = temp_dir.path().join("Dockerfile");
+        let output = output.to_string_lossy();
+
+        let mut settings = Settings::new_with_details(
+            "https://github.com/user/dotfiles.git",
+            Some("main".to_string()),
+            Some("/home/user/.dotf/repo".to_string()),
+        );
+        settings
+            .prompts
+            .insert("git_email".to_string(), "me@example.com".to_string());
+        let config: DotfConfig = toml::from_str(
+            r#"
+[scripts.custom]
+neovim = "scripts/neovim.sh"
+
+[prompts.git_email]
+message = "Git email?"
+
+[prompts.git_name]
+message = "Git name?"
+"#,
+        )
+        .unwrap();
+        let mut options = DockerfileOptions {
+            base_image: "alpine:3.20".to_string(),
+            user: "dev".to_string(),
+            deps: true,
+            custom: vec!["neovim".to_string()],
+        };
+
+        let service = GenerateService::new();
+        let generated = service
+            .dockerfile(&settings, &config, &options, &output, false)
+            .await
+            .unwrap();
+        assert_eq!(generated.unanswered, vec!["git_name".to_string()]);
+
+        let dockerfile = fs::read_to_string(temp_dir.path().join("Dockerfile")).unwrap();
+        assert!(dockerfile.starts_with("# syntax=docker/dockerfile:1\n"));
+        assert!(dockerfile.contains("FROM alpine:3.20\n"));
+        assert!(dockerfile.contains("apk add --no-cache"));
+        assert!(dockerfile.contains("adduser -D -s /bin/sh dev"));
+        assert!(dockerfile.contains(
+            "RUN git clone --branch main https://github.com/user/dotfiles.git /home/dev/.dotf/repo"
+        ));
+        assert!(dockerfile.contains("git_email = \"me@example.com\""));
+        // The host's repository location does not carry over into the image
+        assert!(!dockerfile.contains("local ="));
+        assert!(dockerfile.contains(
+            "RUN dotf install deps \\\n    && dotf install config \\\n    && dotf install custom neovim\n"
+        ));
+
+        // Existing output needs --force, unknown custom scripts are rejected
+        assert!(service
+            .dockerfile(&settings, &config, &options, &output, false)
+            .await
+            .is_err());
+        options.custom = vec!["missing".to_string()];
+        assert!(matches!(
+            service
+                .dockerfile(&settings, &config, &options, &output, true)
+                .await
+                .unwrap_err(),
+            DotfError::Config(_)
+        ));
+    }
+
+    #[test]
+    fn test_package_manager_for_image() {
+        assert_eq!(
+            PackageManager::for_image("debian:stable-slim"),
+            PackageManager::Apt
+        );
+        assert_eq!(PackageManager::for_image("alpine"), PackageManager::Apk);
+        assert_eq!(
+            PackageManager::for_image("quay.io/fedora/fedora:40"),
+            PackageManager::Dnf
+        );
+        assert_eq!(
+            PackageManager::for_image("mcr.microsoft.com/devcontainers/base:ubuntu"),
+            PackageManager::Apt
+        );
+    }
+
+    #[tokio::test]
+    async fn test_deps_script_rejects_unknown_platform() {
+        let service = GenerateService::new();
+        let result = service
+            .deps_script("windows", None, "dotf.toml", false)
+            .await;
+        assert!(matches!(
+            result.unwrap_err(),
+            DotfError::UnsupportedPlatform(_)
+        ));
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use crate::core::{
+    config::{dotf_config::HookEntry, loader, Settings},
+    prompts::script_env,
+    scripts::{HookEvent, HookFailure, ScriptLimits},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{
+    filesystem::FileSystem,
+    script_executor::{ExecutionResult, ScriptExecutor},
+};
+
+#[derive(Debug, Clone)]
+pub struct HookOutcome {
+    pub event: HookEvent,
+    pub script: String,
+    pub result: ExecutionResult,
+}
+
+/// Runs the `[hooks]` script for an event with the repository, event and `[prompts]`
+/// answers in its environment
+pub struct HookService<F, S> {
+    filesystem: F,
+    script_executor: S,
+}
+
+impl<F: FileSystem, S: ScriptExecutor> HookService<F, S> {
+    pub fn new(filesystem: F, script_executor: S) -> Self {
+        Self {
+            filesystem,
+            script_executor,
+        }
+    }
+
+    /// Run the hook for `event` with `env` added, if one is configured. A failing hook is
+    /// an error unless its `on_failure` is `continue`; then its outcome is returned.
+    pub async fn run(
+        &self,
+        event: HookEvent,
+        env: &BTreeMap<String, String>,
+    ) -> DotfResult<Option<HookOutcome>> {
+        let settings = self.load_settings().await?;
+        let repo_path = settings
+            .repository
+            .local
+            .clone()
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
+        // A dotf.toml that cannot be read must not block the sync that may fix it
+        let config = match loader::load_config(&self.filesystem, &settings).await {
+            Ok(config) => config,
+            Err(e) => {
+                tracing::warn!(hook = event.name(), error = %e, "hooks not loaded");
+                return Ok(None);
+            }
+        };
+        let Some(hook) = config.hooks.get(event) else {
+            return Ok(None);
+        };
+
+        let result = run_hook_entry(
+            &self.script_executor,
+            &settings,
+            &repo_path,
+            event.name(),
+            hook,
+            env,
+        )
+        .await?;
+
+        Ok(Some(HookOutcome {
+            event,
+            script: hook.script().to_string(),
+            result,
+        }))
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+/// Run `hook` from the repository at `repo_path` as hook `name`, with the `[prompts]`
+/// answers, `DOTF_HOOK`, `DOTF_REPO` and `env` in its environment and its timeout on
+/// top of `[settings.scripts]`. A failure is an error unless `on_failure` is `continue`.
+pub async fn run_hook_entry<S: ScriptExecutor>(
+    script_executor: &S,
+    settings: &Settings,
+    repo_path: &str,
+    name: &str,
+    hook: &HookEntry,
+    env: &BTreeMap<String, String>,
+) -> DotfResult<ExecutionResult> {
+    let mut hook_env = script_env(&settings.prompts);
+    hook_env.insert("DOTF_HOOK".to_string(), name.to_string());
+    hook_env.insert("DOTF_REPO".to_string(), repo_path.to_string());
+    hook_env.extend(env.clone());
+    let limits = settings.scripts.merged_with(&ScriptLimits {
+        max_runtime_secs: hook.timeout_secs(),
+        ..Default::default()
+    });
+
+    let script_path = format!("{}/{}", repo_path, hook.script());
+    let result = match script_executor
+        .execute_limited(&script_path, &[], &hook_env, &limits, None)
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => ExecutionResult::failure(-1, e.to_string()),
+    };
+    if !result.success && hook.on_failure() == HookFailure::Abort {
+        let mut message = format!(
+            "{} hook {} failed with exit code {}",
+            name,
+            hook.script(),
+            result.exit_code
+        );
+        if !result.stderr.trim().is_empty() {
+            message.push_str(&format!(": {}", result.stderr.trim()));
+        }
+        return Err(DotfError::ScriptExecution(message));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, script_executor::tests::MockScriptExecutor,
+    };
+
+    #[tokio::test]
+    async fn test_hooks_follow_failure_policy() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.scripts.nice = Some(10);
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo),
+            r#"[hooks]
+pre_sync = "hooks/check.sh"
+post_sync = { script = "hooks/caches.sh", timeout_secs = 60, on_failure = "continue" }
+"#,
+        );
+
+        let executor = MockScriptExecutor::new();
+        for script in ["check.sh", "caches.sh"] {
+            executor.set_execution_result(
+                &format!("{}/hooks/{}", repo, script),
+                ExecutionResult::failure(1, "boom".to_string()),
+            );
+        }
+        let service = HookService::new(filesystem.clone(), executor.clone());
+
+        let error = service
+            .run(HookEvent::PreSync, &BTreeMap::new())
+            .await
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("pre_sync hook hooks/check.sh failed with exit code 1: boom"));
+
+        let outcome = service
+            .run(HookEvent::PostSync, &BTreeMap::new())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!outcome.result.success);
+        assert_eq!(
+            executor.get_executed_limits()[1],
+            ScriptLimits {
+                nice: Some(10),
+                max_runtime_secs: Some(60),
+                max_output_bytes: None,
+            }
+        );
+        assert_eq!(
+            executor.get_executed_env()[1].get("DOTF_HOOK"),
+            Some(&"post_sync".to_string())
+        );
+
+        assert!(service
+            .run(HookEvent::PreInstall, &BTreeMap::new())
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
@@ -0,0 +1,227 @@
+use std::path::Path;
+
+use crate::core::{
+    config::{settings::Repository as RepositorySettings, Settings},
+    migrate::{render_symlinks, unmatched_files, BareRepo},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, repository::Repository};
+
+/// What importing a bare repository would do, gathered before anything is written
+#[derive(Debug, Clone)]
+pub struct ImportPlan {
+    pub bare: BareRepo,
+    pub files: Vec<String>,
+    /// Tracked files edited in `$HOME` but never committed; importing them would lose the edits
+    pub uncommitted: Vec<String>,
+    pub remote: Option<String>,
+}
+
+/// Converts a "bare repo with `$HOME` as work tree" setup into a dotf repository
+pub struct ImportService<R, F> {
+    repository: R,
+    filesystem: F,
+}
+
+impl<R: Repository, F: FileSystem> ImportService<R, F> {
+    pub fn new(repository: R, filesystem: F) -> Self {
+        Self {
+            repository,
+            filesystem,
+        }
+    }
+
+    pub fn plan(&self, bare: BareRepo) -> DotfResult<ImportPlan> {
+        let files = bare.tracked_files()?;
+        if files.is_empty() {
+            return Err(DotfError::Repository(format!(
+                "{} does not track any files",
+                bare.git_dir.display()
+            )));
+        }
+
+        Ok(ImportPlan {
+            uncommitted: bare.uncommitted_files()?,
+            remote: bare.remote_url(),
+            files,
+            bare,
+        })
+    }
+
+    /// Clone the bare repository to `destination`, check every tracked file survived,
+    /// commit a generated dotf.toml and only then point dotf's settings at the clone
+    pub async fn import(&self, plan: &ImportPlan, destination: &str) -> DotfResult<()> {
+        if self
+            .filesystem
+            .exists(&self.filesystem.dotf_settings_path())
+            .await?
+        {
+            return Err(DotfError::Operation(
+                "dotf is already initialized; remove ~/.dotf/settings.toml to import instead"
+                    .to_string(),
+            ));
+        }
+        if !plan.uncommitted.is_empty() {
+            return Err(DotfError::Validation(format!(
+                "Commit these changes in the bare repository first: {}",
+                plan.uncommitted.join(", ")
+            )));
+        }
+        if self.filesystem.exists(destination).await? {
+            return Err(DotfError::Operation(format!(
+                "{} already exists",
+                destination
+            )));
+        }
+
+        plan.bare.clone_into(Path::new(destination))?;
+
+        let unmatched = unmatched_files(&plan.bare.work_tree, Path::new(destination), &plan.files);
+        if !unmatched.is_empty() {
+            return Err(DotfError::Validation(format!(
+                "The clone at {} does not match your home directory for: {}. Nothing was switched over",
+                destination,
+                unmatched.join(", ")
+            )));
+        }
+
+        let config_path = format!("{}/dotf.toml", destination);
+        if self.filesystem.exists(&config_path).await? {
+            return Err(DotfError::Config(format!(
+                "{} already tracks a dotf.toml; initialize with 'dotf init' instead",
+                plan.bare.git_dir.display()
+            )));
+        }
+        self.filesystem
+            .write(&config_path, &render_symlinks(&plan.files))
+            .await?;
+        self.repository.stage(destination, "dotf.toml").await?;
+        self.repository
+            .commit(
+                destination,
+                "Add dotf.toml generated from the bare repository",
+                &["dotf.toml".to_string()],
+                false,
+            )
+            .await?;
+
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: plan
+                    .remote
+                    .clone()
+                    .unwrap_or_else(|| plan.bare.git_dir.to_string_lossy().to_string()),
+                branch: None,
+                local: Some(destination.to_string()),
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: chrono::Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        self.filesystem
+            .create_dir_all(&self.filesystem.dotf_directory())
+            .await?;
+        self.filesystem
+            .write(&self.filesystem.dotf_settings_path(), &settings.to_toml()?)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{filesystem::tests::MockFileSystem, repository::tests::MockRepository};
+    use std::process::Command;
+    use tempfile::TempDir;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=dotf", "-c", "user.email=dotf@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .status()
+            .unwrap();
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    fn setup() -> (TempDir, BareRepo) {
+        let home = TempDir::new().unwrap();
+        std::fs::write(home.path().join(".zshrc"), "export A=1\n").unwrap();
+        git(home.path(), &["init", "--bare", ".cfg"]);
+        git(
+            home.path(),
+            &["--git-dir=.cfg", "--work-tree=.", "add", ".zshrc"],
+        );
+        git(
+            home.path(),
+            &["--git-dir=.cfg", "--work-tree=.", "commit", "-m", "zshrc"],
+        );
+        let bare = BareRepo::detect(home.path()).unwrap();
+        (home, bare)
+    }
+
+    #[tokio::test]
+    async fn test_import_generates_config_and_settings() {
+        let (home, bare) = setup();
+        let filesystem = MockFileSystem::new();
+        let repository = MockRepository::new();
+        let service = ImportService::new(Clone::clone(&repository), filesystem.clone());
+        let destination = home.path().join("dotfiles").to_string_lossy().to_string();
+
+        let plan = service.plan(bare).unwrap();
+        assert_eq!(plan.files, vec![".zshrc"]);
+        service.import(&plan, &destination).await.unwrap();
+
+        let config = filesystem
+            .read_to_string(&format!("{}/dotf.toml", destination))
+            .await
+            .unwrap();
+        assert!(config.contains("\".zshrc\" = \"~/.zshrc\""));
+        assert_eq!(repository.get_commit_calls().len(), 1);
+
+        let settings = Settings::from_toml(
+            &filesystem
+                .read_to_string(&filesystem.dotf_settings_path())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        assert_eq!(settings.repository.local, Some(destination));
+    }
+
+    #[tokio::test]
+    async fn test_import_refuses_uncommitted_changes() {
+        let (home, bare) = setup();
+        std::fs::write(home.path().join(".zshrc"), "export A=2\n").unwrap();
+        let filesystem = MockFileSystem::new();
+        let service = ImportService::new(MockRepository::new(), filesystem.clone());
+
+        let plan = service.plan(bare).unwrap();
+        let destination = home.path().join("dotfiles").to_string_lossy().to_string();
+        let result = service.import(&plan, &destination).await;
+
+        assert!(matches!(result.unwrap_err(), DotfError::Validation(_)));
+        assert!(!home.path().join("dotfiles").exists());
+        assert!(!filesystem
+            .exists(&filesystem.dotf_settings_path())
+            .await
+            .unwrap());
+    }
+}
@@ -1,6 +1,12 @@
 use crate::core::config::{DotfConfig, Repository as RepositoryConfig, Settings};
+use crate::core::lock::LOCK_FILE;
+use crate::core::repository::{local_remote_path, normalize_remote};
 use crate::error::{DotfError, DotfResult};
-use crate::traits::{filesystem::FileSystem, prompt::Prompt, repository::Repository};
+use crate::traits::{
+    filesystem::{FileEntry, FileSystem},
+    prompt::Prompt,
+    repository::{FetchPolicy, Repository},
+};
 
 pub struct InitService<R, F, P> {
     repository: R,
@@ -23,6 +29,7 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
             Some(url) => url,
             None => self.prompt_for_repository_url().await?,
         };
+        let url = normalize_remote(&url);
 
         // Validate the repository URL
         self.repository.validate_remote(&url).await.map_err(|e| {
@@ -52,9 +59,25 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
                 remote: url.clone(),
                 branch: None,
                 local: Some(repo_path.clone()),
+                sparse: false,
             },
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         self.save_settings(&settings).await?;
@@ -122,7 +145,9 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
         }
 
         // Try to get repository status
-        self.repository.get_status(&repo_path).await?;
+        self.repository
+            .get_status(&repo_path, FetchPolicy::Never)
+            .await?;
 
         Ok(())
     }
@@ -139,7 +164,10 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
             }
 
             // Basic URL validation
-            if !url.contains("://") && !url.starts_with("git@") {
+            if !url.contains("://")
+                && !url.starts_with("git@")
+                && local_remote_path(&url).is_none_or(|path| !path.exists())
+            {
                 continue;
             }
 
@@ -164,8 +192,9 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
     async fn setup_dotf_directory(&self) -> DotfResult<()> {
         let dotf_dir = self.filesystem.dotf_directory();
 
-        // Check if .dotf directory already exists
-        if self.filesystem.exists(&dotf_dir).await? {
+        // Check if .dotf directory already holds anything besides our own operation lock
+        let leftovers = dotf_directory_contents(&self.filesystem).await?;
+        if !leftovers.is_empty() {
             let should_overwrite = self.prompt.confirm(
                 &format!("Dotf directory already exists at: {}. Do you want to remove it and start fresh?", dotf_dir)
             ).await?;
@@ -176,8 +205,14 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
                 ));
             }
 
-            // Remove existing directory
-            self.filesystem.remove_dir(&dotf_dir).await?;
+            // Remove its contents; the lock stays held until init finishes
+            for entry in leftovers {
+                if entry.is_dir && !entry.is_symlink {
+                    self.filesystem.remove_dir(&entry.path).await?;
+                } else {
+                    self.filesystem.remove_file(&entry.path).await?;
+                }
+            }
         }
 
         // Create main dotf directory
@@ -215,6 +250,24 @@ impl<R: Repository, F: FileSystem, P: Prompt> InitService<R, F, P> {
     }
 }
 
+/// What `~/.dotf` holds apart from the operation lock, which the running init itself
+/// took; empty when the directory does not exist
+pub(crate) async fn dotf_directory_contents<F: FileSystem>(
+    filesystem: &F,
+) -> DotfResult<Vec<FileEntry>> {
+    let dotf_dir = filesystem.dotf_directory();
+    if !filesystem.exists(&dotf_dir).await? {
+        return Ok(Vec::new());
+    }
+    let lock_path = format!("{}/{}", dotf_dir, LOCK_FILE);
+    Ok(filesystem
+        .list_entries(&dotf_dir)
+        .await?
+        .into_iter()
+        .filter(|entry| entry.path != lock_path)
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -227,9 +280,21 @@ mod tests {
 
     fn create_test_config() -> DotfConfig {
         DotfConfig {
-            symlinks: HashMap::from([(".vimrc".to_string(), "~/.vimrc".to_string())]),
+            symlinks: HashMap::from([(".vimrc".to_string(), "~/.vimrc".to_string())]).into(),
             scripts: ScriptsConfig::default(),
             platform: PlatformConfig::default(),
+            tools: Default::default(),
+            bootstrap: Default::default(),
+            macos: Default::default(),
+            cron: Default::default(),
+            env: Default::default(),
+            prompts: Default::default(),
+            ssh: Default::default(),
+            secrets: Default::default(),
+            hooks: Default::default(),
+            packages: Default::default(),
+            package_managers: Default::default(),
+            profile: Default::default(),
         }
     }
 
@@ -302,6 +367,32 @@ mod tests {
             .unwrap());
     }
 
+    #[tokio::test]
+    async fn test_init_ignores_its_own_lock() {
+        let filesystem = MockFileSystem::new();
+        let mut repository = MockRepository::new();
+        let prompt = MockPrompt::new();
+
+        // The lock the init command took before running
+        let lock_path = format!("{}/{}", filesystem.dotf_directory(), LOCK_FILE);
+        filesystem.add_file(&lock_path, "{}");
+        repository.set_config_response(create_test_config());
+        // Asking to start fresh would cancel
+        prompt.set_confirm_response(false);
+
+        let service = InitService::new(repository, filesystem.clone(), prompt);
+        service
+            .init(Some("https://github.com/user/dotfiles.git".to_string()))
+            .await
+            .unwrap();
+
+        assert!(filesystem.exists(&lock_path).await.unwrap());
+        assert!(filesystem
+            .exists(&filesystem.dotf_settings_path())
+            .await
+            .unwrap());
+    }
+
     #[tokio::test]
     async fn test_init_with_existing_directory_user_cancels() {
         let filesystem = MockFileSystem::new();
@@ -348,9 +439,25 @@ mod tests {
                 remote: "https://github.com/old/repo.git".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
         let settings_content = settings.to_toml().unwrap();
         filesystem
@@ -399,9 +506,25 @@ mod tests {
                 remote: "https://github.com/user/dotfiles.git".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
         let settings_content = settings.to_toml().unwrap();
         filesystem
@@ -444,9 +567,22 @@ mod tests {
         let invalid_config = DotfConfig {
             symlinks: HashMap::from([
                 ("".to_string(), ".vimrc".to_string()), // Empty target
-            ]),
+            ])
+            .into(),
             scripts: ScriptsConfig::default(),
             platform: PlatformConfig::default(),
+            tools: Default::default(),
+            bootstrap: Default::default(),
+            macos: Default::default(),
+            cron: Default::default(),
+            env: Default::default(),
+            prompts: Default::default(),
+            ssh: Default::default(),
+            secrets: Default::default(),
+            hooks: Default::default(),
+            packages: Default::default(),
+            package_managers: Default::default(),
+            profile: Default::default(),
         };
 
         let result = service.validate_config(&invalid_config);
@@ -2,8 +2,15 @@
 
 use crate::cli::ui::InstallStage;
 use crate::core::config::{DotfConfig, Repository as RepositoryConfig, Settings};
+use crate::core::repository::{local_remote_path, normalize_remote, sparse_paths};
 use crate::error::{DotfError, DotfResult};
-use crate::traits::{filesystem::FileSystem, prompt::Prompt, repository::Repository};
+use crate::services::init_service::dotf_directory_contents;
+use crate::traits::{
+    filesystem::FileSystem,
+    prompt::Prompt,
+    repository::{FetchPolicy, Repository},
+};
+use crate::utils::platform::detect_platform;
 
 /// Progress callback function type
 pub type ProgressCallback = Box<dyn Fn(&InstallStage) + Send + Sync>;
@@ -12,6 +19,7 @@ pub struct EnhancedInitService<R, F, P> {
     repository: R,
     filesystem: F,
     prompt: P,
+    sparse: bool,
 }
 
 impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
@@ -20,9 +28,17 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
             repository,
             filesystem,
             prompt,
+            sparse: false,
         }
     }
 
+    /// Clone only the paths dotf.toml references for this platform and keep it that way
+    /// on sync
+    pub fn with_sparse(mut self, sparse: bool) -> Self {
+        self.sparse = sparse;
+        self
+    }
+
     pub async fn init_with_progress<C>(
         &self,
         repo_url: Option<String>,
@@ -33,6 +49,13 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
     {
         progress_callback(&InstallStage::Welcome);
 
+        if let Some(url) = self
+            .offer_resume(repo_url.as_deref(), &progress_callback)
+            .await?
+        {
+            return Ok(url);
+        }
+
         // Get repository URL (either provided or prompt for it)
         let url = match repo_url {
             Some(url) => {
@@ -48,6 +71,8 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
                 prompted_url
             }
         };
+        // Local paths are stored absolute so sync works from any directory
+        let url = normalize_remote(&url);
 
         // Validate the repository URL
         self.repository.validate_remote(&url).await.map_err(|e| {
@@ -98,9 +123,16 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
         // Clone the repository
         progress_callback(&InstallStage::CloningRepository);
         let repo_path = self.filesystem.dotf_repo_path();
-        self.repository
-            .clone_branch(&url, &selected_branch, &repo_path)
-            .await?;
+        if self.sparse {
+            let paths = sparse_paths(&config, &detect_platform());
+            self.repository
+                .clone_sparse(&url, &selected_branch, &repo_path, &paths)
+                .await?;
+        } else {
+            self.repository
+                .clone_branch(&url, &selected_branch, &repo_path)
+                .await?;
+        }
 
         // Create local settings
         progress_callback(&InstallStage::FinalizeSetup);
@@ -109,9 +141,25 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
                 remote: url.clone(),
                 branch: Some(selected_branch),
                 local: Some(repo_path.clone()),
+                sparse: self.sparse,
             },
             last_sync: None,
             initialized_at: chrono::Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         self.save_settings(&settings).await?;
@@ -121,6 +169,204 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
         Ok(url)
     }
 
+    /// Register a checkout that already exists at `local_path` instead of cloning. It must
+    /// be a git checkout with a valid dotf.toml and must live outside the dotf directory,
+    /// which init recreates. Returns the checkout's remote, or the path without one.
+    pub async fn init_from_local<C>(
+        &self,
+        local_path: &str,
+        progress_callback: C,
+    ) -> DotfResult<String>
+    where
+        C: Fn(&InstallStage) + Send + Sync,
+    {
+        progress_callback(&InstallStage::Welcome);
+
+        progress_callback(&InstallStage::UsingLocalCheckout);
+        let local_path = local_path.trim_end_matches('/');
+        if !self.filesystem.is_dir(local_path).await? {
+            return Err(DotfError::Repository(format!(
+                "'{}' is not a directory",
+                local_path
+            )));
+        }
+        if !self
+            .filesystem
+            .exists(&format!("{}/.git", local_path))
+            .await?
+        {
+            return Err(DotfError::Repository(format!(
+                "'{}' is not a git checkout",
+                local_path
+            )));
+        }
+        let dotf_dir = self.filesystem.dotf_directory();
+        if local_path == dotf_dir || local_path.starts_with(&format!("{}/", dotf_dir)) {
+            return Err(DotfError::Repository(format!(
+                "'{}' is inside {}, which init replaces; move the checkout elsewhere first",
+                local_path, dotf_dir
+            )));
+        }
+
+        progress_callback(&InstallStage::FetchingConfiguration);
+        self.check_checkout_config(local_path).await?;
+
+        let remote = self
+            .repository
+            .get_remote_url(local_path)
+            .await
+            .unwrap_or_else(|_| local_path.to_string());
+        let branch = self
+            .repository
+            .get_status(local_path, FetchPolicy::Never)
+            .await
+            .ok()
+            .map(|status| status.current_branch)
+            .filter(|branch| !branch.is_empty());
+
+        progress_callback(&InstallStage::SettingUpDirectories);
+        self.setup_dotf_directory().await?;
+
+        progress_callback(&InstallStage::FinalizeSetup);
+        let settings = Settings {
+            repository: RepositoryConfig {
+                remote: remote.clone(),
+                branch,
+                local: Some(local_path.to_string()),
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: chrono::Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        self.save_settings(&settings).await?;
+
+        progress_callback(&InstallStage::Complete);
+
+        Ok(remote)
+    }
+
+    /// The clone an init left behind when it stopped before writing a settings.toml that
+    /// parses; `None` when there is nothing to resume
+    async fn unfinished_clone(&self) -> DotfResult<Option<String>> {
+        let repo_path = self.filesystem.dotf_repo_path();
+        if !self
+            .filesystem
+            .exists(&format!("{}/.git", repo_path))
+            .await?
+        {
+            return Ok(None);
+        }
+        let settings_complete = match self
+            .filesystem
+            .read_to_string(&self.filesystem.dotf_settings_path())
+            .await
+        {
+            Ok(content) => Settings::from_toml(&content).is_ok(),
+            Err(_) => false,
+        };
+        Ok((!settings_complete).then_some(repo_path))
+    }
+
+    /// Offer to finish an init that stopped after cloning, keeping the clone; a different
+    /// `repo_url` starts over. Returns the clone's remote when setup was resumed.
+    async fn offer_resume<C>(
+        &self,
+        repo_url: Option<&str>,
+        progress_callback: &C,
+    ) -> DotfResult<Option<String>>
+    where
+        C: Fn(&InstallStage) + Send + Sync,
+    {
+        let Some(repo_path) = self.unfinished_clone().await? else {
+            return Ok(None);
+        };
+        let Ok(remote) = self.repository.get_remote_url(&repo_path).await else {
+            return Ok(None);
+        };
+        if repo_url.is_some_and(|url| normalize_remote(url) != remote) {
+            return Ok(None);
+        }
+
+        let resume = self
+            .prompt
+            .confirm(&format!(
+                "{} holds a clone of {} from an unfinished 'dotf init'. Keep it and finish the setup?",
+                repo_path, remote
+            ))
+            .await?;
+        if !resume {
+            return Ok(None);
+        }
+
+        progress_callback(&InstallStage::ResumingSetup);
+        self.check_checkout_config(&repo_path).await?;
+        let branch = self
+            .repository
+            .get_status(&repo_path, FetchPolicy::Never)
+            .await
+            .ok()
+            .map(|status| status.current_branch)
+            .filter(|branch| !branch.is_empty());
+        let sparse = self.sparse
+            || self
+                .filesystem
+                .exists(&format!("{}/.git/info/sparse-checkout", repo_path))
+                .await?;
+
+        // Only what an interrupted init may not have got to; the clone stays
+        progress_callback(&InstallStage::SettingUpDirectories);
+        self.filesystem.create_dotf_directory().await?;
+        self.filesystem
+            .create_dir_all(&self.filesystem.dotf_backup_path())
+            .await?;
+
+        progress_callback(&InstallStage::FinalizeSetup);
+        let settings = Settings {
+            repository: RepositoryConfig {
+                remote: remote.clone(),
+                branch,
+                local: Some(repo_path),
+                sparse,
+            },
+            ..Settings::new(&remote)
+        };
+        self.save_settings(&settings).await?;
+
+        progress_callback(&InstallStage::Complete);
+
+        Ok(Some(remote))
+    }
+
+    /// Parse and check the dotf.toml at the root of `checkout`
+    async fn check_checkout_config(&self, checkout: &str) -> DotfResult<()> {
+        let config_path = format!("{}/dotf.toml", checkout);
+        if !self.filesystem.exists(&config_path).await? {
+            return Err(DotfError::Config(format!(
+                "No dotf.toml found in '{}'",
+                checkout
+            )));
+        }
+        let content = self.filesystem.read_to_string(&config_path).await?;
+        let config: DotfConfig = toml::from_str(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
+        self.validate_config(&config)
+    }
+
     async fn prompt_for_branch(&self, default_branch: &str) -> DotfResult<String> {
         #[allow(clippy::never_loop)]
         loop {
@@ -160,7 +406,10 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
                     }
 
                     // Basic URL validation
-                    if !url.contains("://") && !url.starts_with("git@") {
+                    if !url.contains("://")
+                        && !url.starts_with("git@")
+                        && local_remote_path(&url).is_none_or(|path| !path.exists())
+                    {
                         continue;
                     }
 
@@ -196,8 +445,9 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
     async fn setup_dotf_directory(&self) -> DotfResult<()> {
         let dotf_dir = self.filesystem.dotf_directory();
 
-        // Check if .dotf directory already exists
-        if self.filesystem.exists(&dotf_dir).await? {
+        // Check if .dotf directory already holds anything besides our own operation lock
+        let leftovers = dotf_directory_contents(&self.filesystem).await?;
+        if !leftovers.is_empty() {
             let should_overwrite = self.prompt.confirm(
                 &format!("Dotf directory already exists at: {}. Do you want to remove it and start fresh?", dotf_dir)
             ).await?;
@@ -208,8 +458,14 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
                 ));
             }
 
-            // Remove existing directory
-            self.filesystem.remove_dir(&dotf_dir).await?;
+            // Remove its contents; the lock stays held until init finishes
+            for entry in leftovers {
+                if entry.is_dir && !entry.is_symlink {
+                    self.filesystem.remove_dir(&entry.path).await?;
+                } else {
+                    self.filesystem.remove_file(&entry.path).await?;
+                }
+            }
         }
 
         // Create main dotf directory
@@ -232,3 +488,109 @@ impl<R: Repository, F: FileSystem, P: Prompt> EnhancedInitService<R, F, P> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, prompt::tests::MockPrompt,
+        repository::tests::MockRepository,
+    };
+
+    #[tokio::test]
+    async fn test_init_from_local_checkout() {
+        let filesystem = MockFileSystem::new();
+        let checkout = "/work/dotfiles";
+        filesystem.add_directory(checkout);
+        filesystem.add_file(
+            &format!("{}/dotf.toml", checkout),
+            "[symlinks]\n\"vim/.vimrc\" = \"~/.vimrc\"\n",
+        );
+
+        let mut repository = MockRepository::new();
+        repository.set_remote_url("https://github.com/user/dotfiles.git".to_string());
+        let service = EnhancedInitService::new(repository, filesystem.clone(), MockPrompt::new());
+
+        // Not a git checkout yet
+        assert!(service.init_from_local(checkout, |_| {}).await.is_err());
+
+        filesystem.add_directory(&format!("{}/.git", checkout));
+        let remote = service
+            .init_from_local(&format!("{}/", checkout), |_| {})
+            .await
+            .unwrap();
+        assert_eq!(remote, "https://github.com/user/dotfiles.git");
+
+        let content = filesystem
+            .read_to_string(&filesystem.dotf_settings_path())
+            .await
+            .unwrap();
+        let settings = Settings::from_toml(&content).unwrap();
+        assert_eq!(settings.repository.local.as_deref(), Some(checkout));
+        assert_eq!(settings.repository.branch, None);
+        assert!(filesystem
+            .exists(&filesystem.dotf_backup_path())
+            .await
+            .unwrap());
+        assert!(!filesystem
+            .exists(&filesystem.dotf_repo_path())
+            .await
+            .unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_init_resumes_after_clone() {
+        let filesystem = MockFileSystem::new();
+        let repo_path = filesystem.dotf_repo_path();
+        filesystem.add_directory(&format!("{}/.git", repo_path));
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo_path),
+            "[symlinks]\n\"vim/.vimrc\" = \"~/.vimrc\"\n",
+        );
+        // Interrupted while writing settings.toml
+        filesystem.add_file(&filesystem.dotf_settings_path(), "[repository]\nremo");
+
+        let mut repository = MockRepository::new();
+        repository.set_remote_url("https://github.com/user/dotfiles.git".to_string());
+        let prompt = MockPrompt::new();
+        prompt.set_confirm_response(true);
+        let service = EnhancedInitService::new(repository, filesystem.clone(), prompt);
+
+        let remote = service.init_with_progress(None, |_| {}).await.unwrap();
+        assert_eq!(remote, "https://github.com/user/dotfiles.git");
+        assert!(filesystem
+            .exists(&format!("{}/dotf.toml", repo_path))
+            .await
+            .unwrap());
+        let content = filesystem
+            .read_to_string(&filesystem.dotf_settings_path())
+            .await
+            .unwrap();
+        let settings = Settings::from_toml(&content).unwrap();
+        assert_eq!(settings.repository.remote, remote);
+        assert_eq!(
+            settings.repository.local.as_deref(),
+            Some(repo_path.as_str())
+        );
+
+        // With settings in place there is nothing left to resume
+        assert_eq!(service.unfinished_clone().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_init_from_local_rejects_checkout_inside_dotf_directory() {
+        let filesystem = MockFileSystem::new();
+        let checkout = filesystem.dotf_repo_path();
+        filesystem.add_directory(&checkout);
+        filesystem.add_directory(&format!("{}/.git", checkout));
+        filesystem.add_file(&format!("{}/dotf.toml", checkout), "");
+
+        let service =
+            EnhancedInitService::new(MockRepository::new(), filesystem.clone(), MockPrompt::new());
+        let error = service
+            .init_from_local(&checkout, |_| {})
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("init replaces"));
+    }
+}
@@ -0,0 +1,267 @@
+use crate::core::config::{loader, DotfConfig, Settings};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{
+    filesystem::FileSystem,
+    repository::{CommitEntry, Repository},
+};
+
+/// A commit with its changed files resolved through the dotf.toml mappings
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub commit: CommitEntry,
+    /// Home-directory targets the commit changed, as written in dotf.toml
+    pub targets: Vec<String>,
+    /// Changed repository files that no symlink maps (scripts, dotf.toml, ...)
+    pub other_files: Vec<String>,
+}
+
+/// A source → target pair from dotf.toml, with the source relative to the repository
+struct Mapping {
+    source: String,
+    target: String,
+}
+
+pub struct LogService<R, F> {
+    repository: R,
+    filesystem: F,
+}
+
+impl<R: Repository, F: FileSystem> LogService<R, F> {
+    pub fn new(repository: R, filesystem: F) -> Self {
+        Self {
+            repository,
+            filesystem,
+        }
+    }
+
+    /// The newest `limit` commits, optionally only those touching `target`
+    /// (an absolute path to a managed file or a file inside a managed directory)
+    pub async fn log(&self, target: Option<&str>, limit: usize) -> DotfResult<Vec<LogEntry>> {
+        let repo_path = self.repo_path().await?;
+        let mappings = self.mappings(&self.load_config().await?, &repo_path);
+
+        let paths = match target {
+            Some(target) => vec![source_for_target(&mappings, target).ok_or_else(|| {
+                DotfError::Config(format!("'{}' is not a target managed by dotf.toml", target))
+            })?],
+            None => Vec::new(),
+        };
+
+        let commits = self.repository.log(&repo_path, &paths, limit).await?;
+        Ok(commits
+            .into_iter()
+            .map(|commit| {
+                let mut targets = Vec::new();
+                let mut other_files = Vec::new();
+                for file in &commit.files {
+                    match target_for_source(&mappings, file) {
+                        Some(target) => targets.push(target),
+                        None => other_files.push(file.clone()),
+                    }
+                }
+                LogEntry {
+                    commit,
+                    targets,
+                    other_files,
+                }
+            })
+            .collect())
+    }
+
+    /// Every mapping in dotf.toml regardless of platform, since history spans all machines
+    fn mappings(&self, config: &DotfConfig, repo_path: &str) -> Vec<Mapping> {
+        let platforms = [
+            &config.platform.macos,
+            &config.platform.linux,
+            &config.platform.windows,
+        ];
+        let mut mappings: Vec<Mapping> = config
+            .symlinks
+            .iter()
+            .chain(
+                platforms
+                    .into_iter()
+                    .flatten()
+                    .flat_map(|p| p.symlinks.iter()),
+            )
+            .filter_map(|(source, target)| {
+                let source = match source.strip_prefix(&format!("{}/", repo_path)) {
+                    Some(relative) => relative,
+                    None if source.starts_with('/') => return None,
+                    None => source.as_str(),
+                };
+                Some(Mapping {
+                    source: source.trim_end_matches('/').to_string(),
+                    target: target.trim_end_matches('/').to_string(),
+                })
+            })
+            .collect();
+
+        // Longest sources first, so a file inside a linked directory picks the closest entry
+        mappings.sort_by_key(|mapping| std::cmp::Reverse(mapping.source.len()));
+        mappings
+    }
+
+    async fn repo_path(&self) -> DotfResult<String> {
+        let settings = self.load_settings().await?;
+        Ok(settings
+            .repository
+            .local
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path()))
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        loader::load_config(&self.filesystem, &self.load_settings().await?).await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+/// Map a changed repository file to the target it is linked to
+fn target_for_source(mappings: &[Mapping], file: &str) -> Option<String> {
+    mappings.iter().find_map(|mapping| {
+        if file == mapping.source {
+            Some(mapping.target.clone())
+        } else {
+            file.strip_prefix(&format!("{}/", mapping.source))
+                .map(|rest| format!("{}/{}", mapping.target, rest))
+        }
+    })
+}
+
+/// Map an absolute target path back to the repository path that provides it
+fn source_for_target(mappings: &[Mapping], target: &str) -> Option<String> {
+    let target = target.trim_end_matches('/');
+    mappings.iter().find_map(|mapping| {
+        let expanded = expand_home(&mapping.target);
+        if target == expanded {
+            Some(mapping.source.clone())
+        } else {
+            target
+                .strip_prefix(&format!("{}/", expanded))
+                .map(|rest| format!("{}/{}", mapping.source, rest))
+        }
+    })
+}
+
+fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::settings::Repository as RepositorySettings;
+    use crate::traits::{filesystem::tests::MockFileSystem, repository::tests::MockRepository};
+    use chrono::Utc;
+
+    fn setup() -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[symlinks]
+"zsh/.zshrc" = "~/.zshrc"
+"nvim" = "~/.config/nvim"
+
+[platform.macos.symlinks]
+"macos/karabiner.json" = "~/.config/karabiner/karabiner.json"
+"#,
+        );
+        filesystem
+    }
+
+    fn commit(id: &str, files: &[&str]) -> CommitEntry {
+        CommitEntry {
+            id: id.to_string(),
+            author: "user".to_string(),
+            date: chrono::DateTime::parse_from_rfc3339("2024-05-01T10:00:00+02:00").unwrap(),
+            summary: format!("commit {}", id),
+            files: files.iter().map(|file| file.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_log_maps_files_to_targets() {
+        let repository = MockRepository::new();
+        repository.set_log(vec![
+            commit("b2", &["nvim/lua/plugins.lua", "dotf.toml"]),
+            commit("a1", &["zsh/.zshrc", "macos/karabiner.json"]),
+        ]);
+
+        let service = LogService::new(Clone::clone(&repository), setup());
+        let entries = service.log(None, 10).await.unwrap();
+
+        assert_eq!(entries[0].targets, vec!["~/.config/nvim/lua/plugins.lua"]);
+        assert_eq!(entries[0].other_files, vec!["dotf.toml"]);
+        assert_eq!(
+            entries[1].targets,
+            vec!["~/.zshrc", "~/.config/karabiner/karabiner.json"]
+        );
+        assert_eq!(repository.get_log_calls(), vec![Vec::<String>::new()]);
+    }
+
+    #[tokio::test]
+    async fn test_log_filters_by_target() {
+        let repository = MockRepository::new();
+        let service = LogService::new(Clone::clone(&repository), setup());
+        let home = dirs::home_dir().unwrap_or_default();
+
+        let nvim_init = home.join(".config/nvim/init.lua");
+        service
+            .log(Some(&nvim_init.to_string_lossy()), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            repository.get_log_calls(),
+            vec![vec!["nvim/init.lua".to_string()]]
+        );
+
+        let unmanaged = home.join(".bashrc");
+        let result = service.log(Some(&unmanaged.to_string_lossy()), 10).await;
+        assert!(matches!(result.unwrap_err(), DotfError::Config(_)));
+    }
+}
@@ -0,0 +1,180 @@
+use crate::core::{
+    config::{dotf_config::DefaultsValue, loader, DotfConfig, Settings},
+    macos::value_matches,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, macos_defaults::DefaultsStore};
+
+/// A `[macos.defaults]` entry whose current value differs from dotf.toml
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefaultsChange {
+    pub domain: String,
+    pub key: String,
+    /// Value reported by `defaults read`; `None` if the key is not set
+    pub current: Option<String>,
+    pub wanted: DefaultsValue,
+}
+
+pub struct MacosDefaultsService<F, D> {
+    filesystem: F,
+    store: D,
+}
+
+impl<F: FileSystem, D: DefaultsStore> MacosDefaultsService<F, D> {
+    pub fn new(filesystem: F, store: D) -> Self {
+        Self { filesystem, store }
+    }
+
+    /// Whether dotf.toml declares any `[macos.defaults]`
+    pub async fn has_defaults(&self) -> DotfResult<bool> {
+        let config = self.load_config().await?;
+        Ok(!config.macos.defaults.is_empty())
+    }
+
+    /// Entries that `apply` would change, in domain and key order
+    pub async fn diff(&self) -> DotfResult<Vec<DefaultsChange>> {
+        let config = self.load_config().await?;
+
+        let mut changes = Vec::new();
+        for (domain, keys) in &config.macos.defaults {
+            for (key, wanted) in keys {
+                let current = self.store.read(domain, key).await?;
+                if current
+                    .as_deref()
+                    .is_some_and(|current| value_matches(current, wanted))
+                {
+                    continue;
+                }
+
+                changes.push(DefaultsChange {
+                    domain: domain.clone(),
+                    key: key.clone(),
+                    current,
+                    wanted: wanted.clone(),
+                });
+            }
+        }
+
+        Ok(changes)
+    }
+
+    /// Write every entry that differs and return what was changed
+    pub async fn apply(&self) -> DotfResult<Vec<DefaultsChange>> {
+        let changes = self.diff().await?;
+        for change in &changes {
+            self.store
+                .write(&change.domain, &change.key, &change.wanted)
+                .await?;
+        }
+        Ok(changes)
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        let settings = self.load_settings().await?;
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, &settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{settings::Repository as RepositorySettings, Settings};
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use crate::traits::macos_defaults::tests::MockDefaultsStore;
+    use chrono::Utc;
+
+    fn setup(defaults: &str) -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            &format!("[symlinks]\n\n{}", defaults),
+        );
+        filesystem
+    }
+
+    #[tokio::test]
+    async fn test_apply_writes_only_changed_values() {
+        let filesystem = setup(
+            r#"
+[macos.defaults."com.apple.dock"]
+autohide = true
+tilesize = 48
+
+[macos.defaults.NSGlobalDomain]
+AppleShowAllExtensions = true
+"#,
+        );
+        let store = MockDefaultsStore::new();
+        store.set("com.apple.dock", "autohide", "1");
+        store.set("com.apple.dock", "tilesize", "64");
+
+        let service = MacosDefaultsService::new(filesystem, store.clone());
+        let changes = service.apply().await.unwrap();
+
+        assert_eq!(
+            changes,
+            vec![
+                DefaultsChange {
+                    domain: "NSGlobalDomain".to_string(),
+                    key: "AppleShowAllExtensions".to_string(),
+                    current: None,
+                    wanted: DefaultsValue::Bool(true),
+                },
+                DefaultsChange {
+                    domain: "com.apple.dock".to_string(),
+                    key: "tilesize".to_string(),
+                    current: Some("64".to_string()),
+                    wanted: DefaultsValue::Int(48),
+                },
+            ]
+        );
+        assert_eq!(store.get_writes().len(), 2);
+    }
+}
@@ -0,0 +1,262 @@
+use std::collections::HashMap;
+
+use crate::core::{
+    config::{
+        dotf_config::{ReleaseHost, ToolConfig, LATEST_TOOL_VERSION},
+        Settings,
+    },
+    symlinks::BackupManager,
+    tools::ReleaseClient,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::StatusService;
+use crate::traits::{filesystem::FileSystem, repository::Repository};
+
+/// Project the update check looks for new releases in
+const DOTF_REPO: &str = "k1-c/dotf";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaintenanceOutcome {
+    Done,
+    /// Finished, but something needs a look
+    Attention,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MaintenanceStep {
+    pub name: String,
+    pub outcome: MaintenanceOutcome,
+    pub details: String,
+}
+
+impl MaintenanceStep {
+    fn new(name: &str, outcome: MaintenanceOutcome, details: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            outcome,
+            details: details.into(),
+        }
+    }
+}
+
+/// Recurring upkeep: backup retention, repository housekeeping, a fresh status snapshot
+/// and an update check. Every step runs even when an earlier one fails.
+pub struct MaintainService<R, S, F> {
+    repository: R,
+    status: StatusService<S, F>,
+    filesystem: F,
+    backups: BackupManager<F>,
+    check_updates: bool,
+}
+
+impl<R: Repository, S: Repository, F: FileSystem + Clone> MaintainService<R, S, F> {
+    pub fn new(repository: R, status: StatusService<S, F>, filesystem: F) -> Self {
+        Self {
+            repository,
+            status,
+            backups: BackupManager::new(filesystem.clone()),
+            filesystem,
+            check_updates: true,
+        }
+    }
+
+    pub fn with_update_check(mut self, check_updates: bool) -> Self {
+        self.check_updates = check_updates;
+        self
+    }
+
+    pub async fn run(&self, backup_days: u64) -> DotfResult<Vec<MaintenanceStep>> {
+        let settings = self.load_settings().await?;
+
+        Ok(vec![
+            self.prune_backups(backup_days).await,
+            self.compact_repository(&settings).await,
+            self.refresh_status().await,
+            self.check_for_update().await,
+        ])
+    }
+
+    async fn prune_backups(&self, backup_days: u64) -> MaintenanceStep {
+        let name = "Prune backups";
+        match self.backups.cleanup_old_backups(backup_days).await {
+            Ok(0) => MaintenanceStep::new(
+                name,
+                MaintenanceOutcome::Skipped,
+                format!("nothing older than {} days", backup_days),
+            ),
+            Ok(removed) => MaintenanceStep::new(
+                name,
+                MaintenanceOutcome::Done,
+                format!(
+                    "{} backup(s) older than {} days removed",
+                    removed, backup_days
+                ),
+            ),
+            Err(e) => MaintenanceStep::new(name, MaintenanceOutcome::Failed, e.to_string()),
+        }
+    }
+
+    async fn compact_repository(&self, settings: &Settings) -> MaintenanceStep {
+        let name = "Compact repository";
+        let repo_path = settings
+            .repository
+            .local
+            .clone()
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
+
+        match self.repository.gc(&repo_path).await {
+            Ok(()) => MaintenanceStep::new(name, MaintenanceOutcome::Done, repo_path),
+            Err(e) => MaintenanceStep::new(name, MaintenanceOutcome::Failed, e.to_string()),
+        }
+    }
+
+    async fn refresh_status(&self) -> MaintenanceStep {
+        let name = "Refresh status";
+        let status = match self.status.get_status().await {
+            Ok(status) => status,
+            Err(e) => return MaintenanceStep::new(name, MaintenanceOutcome::Failed, e.to_string()),
+        };
+        if let Err(e) = self.status.record_snapshot(&status).await {
+            return MaintenanceStep::new(name, MaintenanceOutcome::Failed, e.to_string());
+        }
+
+        let symlinks = &status.symlinks;
+        let problems = symlinks.missing
+            + symlinks.broken
+            + symlinks.conflicts
+            + symlinks.invalid_targets
+            + symlinks.modified
+            + symlinks.wrong_permissions
+            + symlinks.unreadable
+            + symlinks.pin_mismatches.len();
+        if problems == 0 {
+            MaintenanceStep::new(
+                name,
+                MaintenanceOutcome::Done,
+                format!("{} symlink(s) valid", symlinks.valid),
+            )
+        } else {
+            MaintenanceStep::new(
+                name,
+                MaintenanceOutcome::Attention,
+                format!(
+                    "{} of {} symlink(s) need attention; see 'dotf status'",
+                    problems, symlinks.total
+                ),
+            )
+        }
+    }
+
+    async fn check_for_update(&self) -> MaintenanceStep {
+        let name = "Check for updates";
+        if !self.check_updates {
+            return MaintenanceStep::new(name, MaintenanceOutcome::Skipped, "offline");
+        }
+
+        let current = env!("CARGO_PKG_VERSION");
+        let tool = ToolConfig {
+            repo: DOTF_REPO.to_string(),
+            host: ReleaseHost::Github,
+            version: LATEST_TOOL_VERSION.to_string(),
+            asset: None,
+            bin: None,
+            install_path: None,
+            checksums: None,
+            sha256: HashMap::new(),
+        };
+
+        match ReleaseClient::new().fetch_release(&tool).await {
+            Ok(release) if is_newer(&release.tag, current) => MaintenanceStep::new(
+                name,
+                MaintenanceOutcome::Attention,
+                format!("dotf {} is available (running {})", release.tag, current),
+            ),
+            Ok(_) => MaintenanceStep::new(
+                name,
+                MaintenanceOutcome::Done,
+                format!("dotf {} is the latest release", current),
+            ),
+            Err(e) => MaintenanceStep::new(name, MaintenanceOutcome::Failed, e.to_string()),
+        }
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+/// Whether release tag `tag` (e.g. `v0.4.1`) is a later version than `current`.
+/// Tags that are not dotted numbers never count as newer.
+fn is_newer(tag: &str, current: &str) -> bool {
+    let parse = |version: &str| -> Option<Vec<u64>> {
+        version
+            .trim_start_matches('v')
+            .split('.')
+            .map(|part| part.parse().ok())
+            .collect()
+    };
+    match (parse(tag), parse(current)) {
+        (Some(tag), Some(current)) => tag > current,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{filesystem::tests::MockFileSystem, repository::tests::MockRepository};
+
+    #[tokio::test]
+    async fn test_run_offline() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.repository.local = Some("/home/user/.dotf/repo".to_string());
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+
+        let repository = MockRepository::new();
+        let status = StatusService::new(Clone::clone(&repository), filesystem.clone());
+        let service = MaintainService::new(Clone::clone(&repository), status, filesystem)
+            .with_update_check(false);
+
+        let steps = service.run(30).await.unwrap();
+        let outcomes: Vec<(&str, MaintenanceOutcome)> = steps
+            .iter()
+            .map(|step| (step.name.as_str(), step.outcome.clone()))
+            .collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                ("Prune backups", MaintenanceOutcome::Skipped),
+                ("Compact repository", MaintenanceOutcome::Done),
+                ("Refresh status", MaintenanceOutcome::Done),
+                ("Check for updates", MaintenanceOutcome::Skipped),
+            ]
+        );
+        assert_eq!(repository.get_gc_calls(), vec!["/home/user/.dotf/repo"]);
+    }
+
+    #[test]
+    fn test_is_newer() {
+        assert!(is_newer("v0.5.0", "0.4.9"));
+        assert!(is_newer("0.4.10", "0.4.9"));
+        assert!(!is_newer("v0.4.9", "0.4.9"));
+        assert!(!is_newer("v0.3.0", "0.4.9"));
+        assert!(!is_newer("nightly", "0.4.9"));
+    }
+}
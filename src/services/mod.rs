@@ -1,17 +1,81 @@
+pub mod add_service;
+pub mod adopt_service;
+pub mod backup_gc_service;
+pub mod bootstrap_service;
+pub mod changes_service;
+pub mod completions_service;
+pub mod config_diff_service;
 pub mod config_service;
+pub mod cron_service;
+pub mod diff_service;
+pub mod disable_service;
+pub mod doctor_service;
+pub mod env_service;
+pub mod export_service;
+pub mod generate_service;
+pub mod hook_service;
+pub mod import_service;
 pub mod init_service;
 pub mod init_service_enhanced;
 pub mod install_service;
+pub mod log_service;
+pub mod macos_defaults_service;
+pub mod maintain_service;
+pub mod packages_service;
+pub mod profile_service;
+pub mod prompts_service;
+pub mod recover_service;
+pub mod render_service;
+pub mod repair_service;
+pub mod repos_service;
+pub mod sandbox_service;
 pub mod schema_service;
 pub mod schema_validator;
+pub mod secrets_service;
+pub mod settings_service;
+pub mod ssh_service;
 pub mod status_service;
 pub mod sync_service;
+pub mod tools_service;
+pub mod watch_service;
 
+pub use add_service::AddService;
+pub use adopt_service::AdoptService;
+pub use backup_gc_service::BackupGcService;
+pub use bootstrap_service::BootstrapService;
+pub use changes_service::ChangesService;
+pub use completions_service::CompletionsService;
+pub use config_diff_service::ConfigDiffService;
 pub use config_service::ConfigService;
+pub use cron_service::CronService;
+pub use diff_service::DiffService;
+pub use disable_service::DisableService;
+pub use doctor_service::DoctorService;
+pub use env_service::EnvService;
+pub use export_service::ExportService;
+pub use generate_service::GenerateService;
+pub use hook_service::HookService;
+pub use import_service::ImportService;
 pub use init_service::InitService;
 pub use init_service_enhanced::EnhancedInitService;
 pub use install_service::InstallService;
+pub use log_service::LogService;
+pub use macos_defaults_service::MacosDefaultsService;
+pub use maintain_service::MaintainService;
+pub use packages_service::PackagesService;
+pub use profile_service::ProfileService;
+pub use prompts_service::PromptsService;
+pub use recover_service::RecoverService;
+pub use render_service::RenderService;
+pub use repair_service::RepairService;
+pub use repos_service::ReposService;
+pub use sandbox_service::SandboxService;
 pub use schema_service::SchemaService;
 pub use schema_validator::SchemaValidator;
+pub use secrets_service::SecretsService;
+pub use settings_service::SettingsService;
+pub use ssh_service::SshService;
 pub use status_service::StatusService;
 pub use sync_service::SyncService;
+pub use tools_service::ToolsService;
+pub use watch_service::WatchService;
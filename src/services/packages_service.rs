@@ -0,0 +1,470 @@
+use std::collections::HashMap;
+
+use crate::core::{
+    config::{dotf_config::Package, loader, DotfConfig, Settings},
+    packages::{custom_managers, default_managers},
+    state::StateStore,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, package_manager::PackageManager};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackageState {
+    Installed,
+    Missing,
+    /// The package manager for its kind cannot run here; holds the manager's command
+    Unavailable(String),
+    /// Its kind is turned off in local settings (`[packages] disabled`)
+    Disabled,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageStatus {
+    pub package: Package,
+    pub state: PackageState,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PackageInstallStatus {
+    Installed,
+    AlreadyInstalled,
+    /// Not attempted, with the reason
+    Skipped(String),
+    /// Missing, but the connection is metered
+    Deferred,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageInstallOutcome {
+    pub package: Package,
+    pub status: PackageInstallStatus,
+}
+
+/// Checks and installs `[packages]` entries, each kind through its package manager
+pub struct PackagesService<F> {
+    filesystem: F,
+    managers: Vec<Box<dyn PackageManager>>,
+    state_store: StateStore<F>,
+    metered: bool,
+}
+
+impl<F: FileSystem + Clone> PackagesService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self {
+            state_store: StateStore::new(filesystem.clone()),
+            filesystem,
+            managers: default_managers(),
+            metered: false,
+        }
+    }
+
+    /// On a metered connection missing packages are recorded as deferred instead of
+    /// being installed
+    pub fn with_metered(mut self, metered: bool) -> Self {
+        self.metered = metered;
+        self
+    }
+
+    /// Replace the package managers, one per kind
+    pub fn with_managers(mut self, managers: Vec<Box<dyn PackageManager>>) -> Self {
+        self.managers = managers;
+        self
+    }
+
+    /// Whether each declared package is installed, asking each manager once
+    pub async fn statuses(&self) -> DotfResult<Vec<PackageStatus>> {
+        let settings = self.load_settings().await?;
+        let config = self.load_config(&settings).await?;
+        let custom = custom_managers(&config);
+        self.check(&settings, &config, &self.managers_with(&custom))
+            .await
+    }
+
+    /// Install every missing package. Kinds whose manager is unavailable are skipped, and a
+    /// failing package does not stop the others; its outcome carries the error.
+    pub async fn install(&self) -> DotfResult<Vec<PackageInstallOutcome>> {
+        let settings = self.load_settings().await?;
+        let config = self.load_config(&settings).await?;
+        let custom = custom_managers(&config);
+        let managers = self.managers_with(&custom);
+
+        let mut outcomes = Vec::new();
+        for status in self.check(&settings, &config, &managers).await? {
+            let result = match status.state {
+                PackageState::Installed => PackageInstallStatus::AlreadyInstalled,
+                PackageState::Unavailable(command) => {
+                    PackageInstallStatus::Skipped(format!("{} is not available", command))
+                }
+                PackageState::Disabled => PackageInstallStatus::Skipped(format!(
+                    "{} packages are disabled in settings",
+                    status.package.kind
+                )),
+                PackageState::Missing if self.metered => PackageInstallStatus::Deferred,
+                PackageState::Missing => {
+                    let manager = managers
+                        .iter()
+                        .find(|manager| manager.kind() == status.package.kind)
+                        .ok_or_else(|| no_manager(&status.package.kind))?;
+                    match manager.install(&status.package).await {
+                        Ok(()) => PackageInstallStatus::Installed,
+                        Err(e) => PackageInstallStatus::Failed(e.to_string()),
+                    }
+                }
+            };
+            outcomes.push(PackageInstallOutcome {
+                package: status.package,
+                status: result,
+            });
+        }
+
+        let attempted: Vec<String> = outcomes
+            .iter()
+            .map(|outcome| outcome.package.name.clone())
+            .collect();
+        let deferred: Vec<String> = outcomes
+            .iter()
+            .filter(|outcome| outcome.status == PackageInstallStatus::Deferred)
+            .map(|outcome| outcome.package.name.clone())
+            .collect();
+        self.state_store
+            .record_deferred("packages", &attempted, &deferred)
+            .await?;
+        Ok(outcomes)
+    }
+
+    /// The configured managers followed by the custom ones; a built-in kind keeps its
+    /// own manager
+    fn managers_with<'a>(
+        &'a self,
+        custom: &'a [Box<dyn PackageManager>],
+    ) -> Vec<&'a dyn PackageManager> {
+        let mut managers: Vec<&dyn PackageManager> = self
+            .managers
+            .iter()
+            .map(|manager| manager.as_ref())
+            .collect();
+        for manager in custom {
+            if !managers.iter().any(|known| known.kind() == manager.kind()) {
+                managers.push(manager.as_ref());
+            }
+        }
+        managers
+    }
+
+    async fn check(
+        &self,
+        settings: &Settings,
+        config: &DotfConfig,
+        managers: &[&dyn PackageManager],
+    ) -> DotfResult<Vec<PackageStatus>> {
+        let packages = config.packages.packages();
+
+        let mut installed: HashMap<&str, Result<Vec<String>, String>> = HashMap::new();
+        for manager in managers {
+            if !settings.packages.is_enabled(manager.kind())
+                || !packages
+                    .iter()
+                    .any(|package| package.kind == manager.kind())
+            {
+                continue;
+            }
+            let listed = if manager.is_available().await {
+                manager.installed().await.map_err(|e| e.to_string())
+            } else {
+                Err(manager.command().to_string())
+            };
+            installed.insert(manager.kind(), listed);
+        }
+
+        packages
+            .into_iter()
+            .map(|package| {
+                let state = match installed.get(package.kind.as_str()) {
+                    None if !settings.packages.is_enabled(&package.kind) => PackageState::Disabled,
+                    None => return Err(no_manager(&package.kind)),
+                    Some(Err(command)) => PackageState::Unavailable(command.clone()),
+                    Some(Ok(ids)) if is_listed(ids, &package.id) => PackageState::Installed,
+                    Some(Ok(_)) => PackageState::Missing,
+                };
+                Ok(PackageStatus { package, state })
+            })
+            .collect()
+    }
+
+    async fn load_config(&self, settings: &Settings) -> DotfResult<DotfConfig> {
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+fn no_manager(kind: &str) -> DotfError {
+    DotfError::Config(format!(
+        "No package manager handles '{}' packages; declare it in [package_managers.custom.{}]",
+        kind, kind
+    ))
+}
+
+/// Managers list tapped packages by their short name, e.g. `font-fira-code` for
+/// `homebrew/cask-fonts/font-fira-code`
+fn is_listed(ids: &[String], id: &str) -> bool {
+    let short = id.rsplit('/').next().unwrap_or(id);
+    ids.iter().any(|listed| listed == id || listed == short)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::dotf_config::PackageRemote;
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, package_manager::tests::MockPackageManager,
+    };
+
+    #[tokio::test]
+    async fn test_install_packages() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[packages]
+brew = ["ripgrep"]
+cask = ["iterm2", "homebrew/cask-fonts/font-fira-code"]
+
+[packages.mas]
+Xcode = 497799835
+"#,
+        );
+
+        let brew = MockPackageManager::new("brew");
+        brew.add_installed("ripgrep");
+        let cask = MockPackageManager::new("cask");
+        cask.add_installed("font-fira-code");
+        let mas = MockPackageManager::new("mas").unavailable();
+        let service = PackagesService::new(filesystem).with_managers(vec![
+            Box::new(brew.clone()),
+            Box::new(cask.clone()),
+            Box::new(mas.clone()),
+        ]);
+
+        let states: Vec<(String, PackageState)> = service
+            .statuses()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|status| (status.package.name, status.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("ripgrep".to_string(), PackageState::Installed),
+                ("iterm2".to_string(), PackageState::Missing),
+                (
+                    "homebrew/cask-fonts/font-fira-code".to_string(),
+                    PackageState::Installed
+                ),
+                (
+                    "Xcode".to_string(),
+                    PackageState::Unavailable("mas".to_string())
+                ),
+            ]
+        );
+
+        let outcomes: Vec<PackageInstallStatus> = service
+            .install()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.status)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                PackageInstallStatus::AlreadyInstalled,
+                PackageInstallStatus::Installed,
+                PackageInstallStatus::AlreadyInstalled,
+                PackageInstallStatus::Skipped("mas is not available".to_string()),
+            ]
+        );
+        assert!(brew.get_install_calls().is_empty());
+        assert_eq!(cask.get_install_calls(), vec!["iterm2"]);
+
+        // A second run finds everything installed
+        let second = service.install().await.unwrap();
+        assert_eq!(cask.get_install_calls().len(), 1);
+        assert_eq!(second[1].status, PackageInstallStatus::AlreadyInstalled);
+    }
+
+    #[tokio::test]
+    async fn test_metered_install_defers_missing_packages() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            "[packages]\ncask = [\"iterm2\", \"homebrew/cask-fonts/font-fira-code\"]\n",
+        );
+        let cask = MockPackageManager::new("cask");
+        cask.add_installed("iterm2");
+        let state = StateStore::new(filesystem.clone());
+
+        let service = PackagesService::new(filesystem.clone())
+            .with_managers(vec![Box::new(cask.clone())])
+            .with_metered(true);
+        let outcomes = service.install().await.unwrap();
+        assert_eq!(outcomes[1].status, PackageInstallStatus::Deferred);
+        assert!(cask.get_install_calls().is_empty());
+        assert_eq!(
+            state.load().await.unwrap().deferred["packages"],
+            vec!["homebrew/cask-fonts/font-fira-code"]
+        );
+
+        let service = PackagesService::new(filesystem).with_managers(vec![Box::new(cask.clone())]);
+        service.install().await.unwrap();
+        assert_eq!(cask.get_install_calls().len(), 1);
+        assert!(state.load().await.unwrap().deferred.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_flatpak_remotes_and_disabled_kinds() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.packages.disabled = vec!["snap".to_string()];
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[packages]
+flatpak = ["org.mozilla.firefox", { id = "org.gimp.GIMP", remote = "flathub-beta" }]
+snap = ["spotify"]
+
+[packages.flatpak_remotes]
+flathub-beta = "https://flathub.org/beta-repo/flathub-beta.flatpakrepo"
+"#,
+        );
+
+        let flatpak = MockPackageManager::new("flatpak");
+        let snap = MockPackageManager::new("snap");
+        let service = PackagesService::new(filesystem)
+            .with_managers(vec![Box::new(flatpak.clone()), Box::new(snap.clone())]);
+
+        let statuses = service.statuses().await.unwrap();
+        let remotes: Vec<Option<PackageRemote>> = statuses
+            .iter()
+            .map(|status| status.package.remote.clone())
+            .collect();
+        assert_eq!(
+            remotes,
+            vec![
+                Some(PackageRemote {
+                    name: "flathub".to_string(),
+                    url: None,
+                }),
+                Some(PackageRemote {
+                    name: "flathub-beta".to_string(),
+                    url: Some("https://flathub.org/beta-repo/flathub-beta.flatpakrepo".to_string()),
+                }),
+                None,
+            ]
+        );
+        assert_eq!(statuses[2].state, PackageState::Disabled);
+
+        let outcomes = service.install().await.unwrap();
+        assert_eq!(
+            flatpak.get_install_calls(),
+            vec!["org.mozilla.firefox", "org.gimp.GIMP"]
+        );
+        assert!(snap.get_install_calls().is_empty());
+        assert_eq!(
+            outcomes[2].status,
+            PackageInstallStatus::Skipped("snap packages are disabled in settings".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_custom_package_manager() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[packages]
+brew = ["ripgrep"]
+pipx = ["black", "isort"]
+
+[package_managers.custom.pipx]
+check = "true"
+list = "printf 'black 24.1.0\\n'"
+install = "test {package} = isort"
+
+[package_managers.custom.brew]
+check = "false"
+list = "true"
+install = "true {package}"
+"#,
+        );
+
+        // A custom manager cannot replace a built-in kind
+        let brew = MockPackageManager::new("brew");
+        let service = PackagesService::new(filesystem).with_managers(vec![Box::new(brew.clone())]);
+
+        let states: Vec<(String, PackageState)> = service
+            .statuses()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|status| (status.package.name, status.state))
+            .collect();
+        assert_eq!(
+            states,
+            vec![
+                ("ripgrep".to_string(), PackageState::Missing),
+                ("black".to_string(), PackageState::Installed),
+                ("isort".to_string(), PackageState::Missing),
+            ]
+        );
+
+        let outcomes: Vec<PackageInstallStatus> = service
+            .install()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|outcome| outcome.status)
+            .collect();
+        assert_eq!(
+            outcomes,
+            vec![
+                PackageInstallStatus::Installed,
+                PackageInstallStatus::AlreadyInstalled,
+                PackageInstallStatus::Installed,
+            ]
+        );
+        assert_eq!(brew.get_install_calls(), vec!["ripgrep"]);
+    }
+}
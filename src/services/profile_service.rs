@@ -0,0 +1,162 @@
+use crate::core::config::{loader, DotfConfig, Settings};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfileStatus {
+    pub name: String,
+    pub symlinks: usize,
+    pub scripts: usize,
+    pub active: bool,
+}
+
+/// Lists the `[profile.<name>]` sections of dotf.toml and keeps the one this machine
+/// uses in local settings
+pub struct ProfileService<F> {
+    filesystem: F,
+}
+
+impl<F: FileSystem> ProfileService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self { filesystem }
+    }
+
+    pub async fn list(&self) -> DotfResult<Vec<ProfileStatus>> {
+        let settings = self.load_settings().await?;
+        let config = self.load_config(&settings).await?;
+
+        Ok(config
+            .profile
+            .into_iter()
+            .map(|(name, profile)| ProfileStatus {
+                active: settings.profile.as_deref() == Some(name.as_str()),
+                symlinks: profile.symlinks.len(),
+                scripts: profile.scripts.custom.len()
+                    + usize::from(profile.scripts.deps.macos.is_some())
+                    + usize::from(profile.scripts.deps.linux.is_some()),
+                name,
+            })
+            .collect())
+    }
+
+    /// Make `name` the active profile, or go back to the base config with `None`
+    pub async fn set_active(&self, name: Option<&str>) -> DotfResult<()> {
+        let mut settings = self.load_settings().await?;
+
+        if let Some(name) = name {
+            let config = self.load_config(&settings).await?;
+            if !config.profile.contains_key(name) {
+                let mut known: Vec<&str> = config.profile.keys().map(String::as_str).collect();
+                if known.is_empty() {
+                    known.push("none");
+                }
+                return Err(DotfError::Config(format!(
+                    "Profile '{}' is not defined in dotf.toml (available: {})",
+                    name,
+                    known.join(", ")
+                )));
+            }
+        }
+
+        settings.profile = name.map(str::to_string);
+        self.save_settings(&settings).await
+    }
+
+    async fn save_settings(&self, settings: &Settings) -> DotfResult<()> {
+        let content = settings
+            .to_toml()
+            .map_err(|e| DotfError::Serialization(e.to_string()))?;
+        self.filesystem
+            .write(&self.filesystem.dotf_settings_path(), &content)
+            .await
+    }
+
+    async fn load_config(&self, settings: &Settings) -> DotfResult<DotfConfig> {
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_use_profile() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[symlinks]
+"git/.gitconfig" = "~/.gitconfig"
+
+[profile.work.symlinks]
+"work/.gitconfig" = "~/.gitconfig"
+"work/.npmrc" = "~/.npmrc"
+
+[profile.work.scripts.custom]
+vpn = "work/vpn.sh"
+
+[profile.personal.symlinks]
+"personal/.gitconfig" = "~/.gitconfig"
+"#,
+        );
+
+        let service = ProfileService::new(filesystem.clone());
+        assert!(service.set_active(Some("home")).await.is_err());
+
+        service.set_active(Some("work")).await.unwrap();
+        let content = filesystem
+            .read_to_string(&filesystem.dotf_settings_path())
+            .await
+            .unwrap();
+        assert_eq!(
+            Settings::from_toml(&content).unwrap().profile.as_deref(),
+            Some("work")
+        );
+
+        let profiles = service.list().await.unwrap();
+        assert_eq!(
+            profiles,
+            vec![
+                ProfileStatus {
+                    name: "personal".to_string(),
+                    symlinks: 1,
+                    scripts: 0,
+                    active: false,
+                },
+                ProfileStatus {
+                    name: "work".to_string(),
+                    symlinks: 2,
+                    scripts: 1,
+                    active: true,
+                },
+            ]
+        );
+
+        service.set_active(None).await.unwrap();
+        assert!(service.list().await.unwrap().iter().all(|p| !p.active));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+
+use crate::core::{
+    config::{dotf_config::PromptConfig, loader, DotfConfig, Settings},
+    prompts::unanswered,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, prompt::Prompt};
+
+#[derive(Debug, Clone)]
+pub struct PromptStatus {
+    pub name: String,
+    pub message: String,
+    pub answer: Option<String>,
+}
+
+/// Asks the `[prompts]` declared in dotf.toml and keeps the answers in local settings
+pub struct PromptsService<F, P> {
+    filesystem: F,
+    prompt: P,
+}
+
+impl<F: FileSystem, P: Prompt> PromptsService<F, P> {
+    pub fn new(filesystem: F, prompt: P) -> Self {
+        Self { filesystem, prompt }
+    }
+
+    pub async fn list(&self) -> DotfResult<Vec<PromptStatus>> {
+        let config = self.load_config().await?;
+        let settings = self.load_settings().await?;
+
+        Ok(config
+            .prompts
+            .into_iter()
+            .map(|(name, prompt)| PromptStatus {
+                answer: settings.prompts.get(&name).cloned(),
+                message: prompt.message,
+                name,
+            })
+            .collect())
+    }
+
+    /// Ask every declared prompt that has no answer yet; returns how many were asked
+    pub async fn ask_missing(&self) -> DotfResult<usize> {
+        let config = self.load_config().await?;
+        let mut settings = self.load_settings().await?;
+
+        let pending: Vec<(String, PromptConfig)> = unanswered(&config.prompts, &settings.prompts)
+            .into_iter()
+            .map(|(name, prompt)| (name.clone(), prompt.clone()))
+            .collect();
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        self.ask(&pending, &mut settings.prompts).await?;
+        self.save_settings(&settings).await?;
+        Ok(pending.len())
+    }
+
+    /// Ask again, with the stored answers as defaults: one prompt by name, or all of them
+    pub async fn edit(&self, name: Option<&str>) -> DotfResult<usize> {
+        let config = self.load_config().await?;
+        let mut settings = self.load_settings().await?;
+
+        let selected: Vec<(String, PromptConfig)> = match name {
+            Some(name) => {
+                let prompt = config.prompts.get(name).ok_or_else(|| {
+                    DotfError::Config(format!("No prompt named '{}' in [prompts]", name))
+                })?;
+                vec![(name.to_string(), prompt.clone())]
+            }
+            None => config.prompts.into_iter().collect(),
+        };
+
+        self.ask(&selected, &mut settings.prompts).await?;
+        self.save_settings(&settings).await?;
+        Ok(selected.len())
+    }
+
+    async fn ask(
+        &self,
+        prompts: &[(String, PromptConfig)],
+        answers: &mut BTreeMap<String, String>,
+    ) -> DotfResult<()> {
+        for (name, prompt) in prompts {
+            let default = answers.get(name).or(prompt.default.as_ref()).cloned();
+            let answer = self
+                .prompt
+                .input(&prompt.message, default.as_deref())
+                .await?;
+            answers.insert(name.clone(), answer);
+        }
+        Ok(())
+    }
+
+    async fn save_settings(&self, settings: &Settings) -> DotfResult<()> {
+        let content = settings
+            .to_toml()
+            .map_err(|e| DotfError::Serialization(e.to_string()))?;
+        self.filesystem
+            .write(&self.filesystem.dotf_settings_path(), &content)
+            .await
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        let settings = self.load_settings().await?;
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, &settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::settings::Repository as RepositorySettings;
+    use crate::traits::{filesystem::tests::MockFileSystem, prompt::tests::MockPrompt};
+    use chrono::Utc;
+
+    fn setup() -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            r#"[prompts]
+git_email = { message = "Git email" }
+git_name = { message = "Git name", default = "" }
+"#,
+        );
+        filesystem
+    }
+
+    #[tokio::test]
+    async fn test_ask_missing_only_once() {
+        let filesystem = setup();
+        let prompt = MockPrompt::new();
+        prompt.set_input_response("me@example.com".to_string());
+        prompt.set_input_response("Me".to_string());
+        let service = PromptsService::new(filesystem, prompt);
+
+        assert_eq!(service.ask_missing().await.unwrap(), 2);
+        // No responses are queued, so asking again would fail
+        assert_eq!(service.ask_missing().await.unwrap(), 0);
+
+        let statuses = service.list().await.unwrap();
+        assert_eq!(statuses[0].answer.as_deref(), Some("me@example.com"));
+        assert_eq!(statuses[1].answer.as_deref(), Some("Me"));
+    }
+
+    #[tokio::test]
+    async fn test_edit_single_prompt() {
+        let filesystem = setup();
+        let prompt = MockPrompt::new();
+        prompt.set_input_response("work@example.com".to_string());
+        let service = PromptsService::new(filesystem, prompt);
+
+        assert_eq!(service.edit(Some("git_email")).await.unwrap(), 1);
+        let statuses = service.list().await.unwrap();
+        assert_eq!(statuses[0].answer.as_deref(), Some("work@example.com"));
+        assert_eq!(statuses[1].answer, None);
+
+        let result = service.edit(Some("unknown")).await;
+        assert!(matches!(result.unwrap_err(), DotfError::Config(_)));
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::core::config::{
+    dotf_config::SymlinkMap, loader, variables::builtin_variables, DotfConfig,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::services::InitService;
+use crate::traits::{filesystem::FileSystem, prompt::Prompt, repository::Repository};
+use crate::utils::platform::detect_platform;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecoveryOutcome {
+    /// The link points at the same path in the new clone and works again
+    Restored,
+    /// The link was pointed at the source now configured for its target
+    Relinked { source: String },
+    /// Nothing in the new clone matches; the dangling link was left alone
+    Unrecoverable { reason: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecoveredLink {
+    pub target_path: String,
+    /// Where the link pointed before the recovery
+    pub previous_source: String,
+    pub outcome: RecoveryOutcome,
+}
+
+/// Rebuilds a deleted ~/.dotf and reconnects the home symlinks that pointed into it
+pub struct RecoverService<R, F, P> {
+    init: InitService<R, F, P>,
+    filesystem: F,
+}
+
+impl<R: Repository, F: FileSystem + Clone, P: Prompt> RecoverService<R, F, P> {
+    pub fn new(repository: R, filesystem: F, prompt: P) -> Self {
+        let init = InitService::new(repository, filesystem.clone(), prompt);
+        Self { init, filesystem }
+    }
+
+    /// Re-clone `url` into ~/.dotf, then check every symlink at or next to a configured
+    /// target that points into ~/.dotf
+    pub async fn recover(&self, url: &str) -> DotfResult<Vec<RecoveredLink>> {
+        if self.init.is_initialized().await? {
+            return Err(DotfError::Operation(
+                "dotf is initialized and its repository exists; use 'dotf symlinks repair' to fix links"
+                    .to_string(),
+            ));
+        }
+
+        self.init.init(Some(url.to_string())).await?;
+
+        let repo_path = self.filesystem.dotf_repo_path();
+        let config = self.load_config(&repo_path).await?;
+        let expected = self.expected_links(&self.platform_symlinks(&config), &repo_path);
+
+        let dotf_prefix = format!("{}/", self.filesystem.dotf_directory());
+        let mut recovered = Vec::new();
+        for link in self.candidate_links(&expected).await? {
+            let previous_source = self
+                .filesystem
+                .read_link(&link)
+                .await?
+                .to_string_lossy()
+                .to_string();
+            if !previous_source.starts_with(&dotf_prefix) {
+                continue;
+            }
+
+            let outcome = if self.filesystem.exists(&previous_source).await? {
+                RecoveryOutcome::Restored
+            } else {
+                self.relink(&link, expected_source(&expected, &link))
+                    .await?
+            };
+            recovered.push(RecoveredLink {
+                target_path: link,
+                previous_source,
+                outcome,
+            });
+        }
+
+        Ok(recovered)
+    }
+
+    async fn relink(&self, link: &str, source: Option<String>) -> DotfResult<RecoveryOutcome> {
+        let Some(source) = source else {
+            return Ok(RecoveryOutcome::Unrecoverable {
+                reason: "no configured entry for this target".to_string(),
+            });
+        };
+        if !self.filesystem.exists(&source).await? {
+            return Ok(RecoveryOutcome::Unrecoverable {
+                reason: format!("configured source {} is missing", source),
+            });
+        }
+
+        self.filesystem.remove_file(link).await?;
+        self.filesystem.create_symlink(&source, link).await?;
+        Ok(RecoveryOutcome::Relinked { source })
+    }
+
+    /// Symlinks at configured targets, inside configured target directories and next to
+    /// configured target files, sorted
+    async fn candidate_links(
+        &self,
+        expected: &BTreeMap<String, String>,
+    ) -> DotfResult<Vec<String>> {
+        let mut links = BTreeSet::new();
+        for target in expected.keys() {
+            if self.is_link(target).await {
+                links.insert(target.clone());
+            } else if self.filesystem.is_dir(target).await.unwrap_or(false) {
+                self.collect_links(target, &mut links).await?;
+                continue;
+            }
+
+            // Links of entries since removed from dotf.toml usually sit next to current ones
+            if let Some(parent) = Path::new(target).parent() {
+                let parent = parent.to_string_lossy();
+                if self.filesystem.is_dir(&parent).await.unwrap_or(false) {
+                    for entry in self.filesystem.list_entries(&parent).await? {
+                        if entry.is_symlink {
+                            links.insert(entry.path);
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(links.into_iter().collect())
+    }
+
+    async fn collect_links(&self, dir: &str, links: &mut BTreeSet<String>) -> DotfResult<()> {
+        for entry in self.filesystem.list_entries(dir).await? {
+            if entry.is_symlink {
+                links.insert(entry.path);
+            } else if entry.is_dir {
+                Box::pin(self.collect_links(&entry.path, links)).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn is_link(&self, path: &str) -> bool {
+        self.filesystem.is_symlink(path).await.unwrap_or(false)
+    }
+
+    /// Expanded target path to absolute source path for every configured entry
+    fn expected_links(&self, symlinks: &SymlinkMap, repo_path: &str) -> BTreeMap<String, String> {
+        let home = dirs::home_dir().map(|home| home.to_string_lossy().to_string());
+        symlinks
+            .iter()
+            .map(|(source, target)| {
+                let target = match (target.strip_prefix("~/"), &home) {
+                    (Some(rest), Some(home)) => format!("{}/{}", home, rest),
+                    _ => target.clone(),
+                };
+                let source = if source.starts_with('/') {
+                    source.clone()
+                } else {
+                    format!("{}/{}", repo_path, source)
+                };
+                (target, source)
+            })
+            .collect()
+    }
+
+    fn platform_symlinks(&self, config: &DotfConfig) -> SymlinkMap {
+        let mut symlinks = config.symlinks.clone();
+        if let Some(platform_config) = config.platform.for_platform(&detect_platform()) {
+            symlinks.extend(platform_config.symlinks.clone());
+        }
+        symlinks
+    }
+
+    async fn load_config(&self, repo_path: &str) -> DotfResult<DotfConfig> {
+        let mut config = loader::read_config(&self.filesystem, repo_path).await?;
+        // settings.toml went with ~/.dotf, so only the built-in variables are known
+        config.expand_targets(&builtin_variables(&detect_platform()))?;
+        Ok(config)
+    }
+}
+
+/// The source configured for `link`: its own entry, or the matching file inside the
+/// source directory of an enclosing directory entry
+fn expected_source(expected: &BTreeMap<String, String>, link: &str) -> Option<String> {
+    if let Some(source) = expected.get(link) {
+        return Some(source.clone());
+    }
+    expected.iter().find_map(|(target, source)| {
+        link.strip_prefix(target.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+            .map(|rest| format!("{}/{}", source, rest))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{
+        filesystem::tests::MockFileSystem, prompt::tests::MockPrompt,
+        repository::tests::MockRepository,
+    };
+
+    #[tokio::test]
+    async fn test_recover_relinks_moved_sources() {
+        let filesystem = MockFileSystem::new();
+        let repo = filesystem.dotf_repo_path();
+        let home = dirs::home_dir().unwrap().to_string_lossy().to_string();
+
+        // The fresh clone: zsh/.zshrc moved to shell/zshrc, nvim is a directory entry
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo),
+            "[symlinks]\n\"shell/zshrc\" = \"~/.zshrc\"\n\"vim/.vimrc\" = \"~/.vimrc\"\n\"nvim\" = \"~/.config/nvim\"\n",
+        );
+        filesystem.add_file(&format!("{}/shell/zshrc", repo), "export A=1");
+        filesystem.add_file(&format!("{}/vim/.vimrc", repo), "set number");
+        filesystem.add_directory(&format!("{}/nvim", repo));
+        filesystem.add_file(&format!("{}/nvim/init.lua", repo), "-- init");
+        filesystem.add_directory(&home);
+        filesystem.add_directory(&format!("{}/.config/nvim", home));
+
+        // Links left over from before ~/.dotf was deleted
+        let links = [
+            ("/.zshrc", "/zsh/.zshrc"),
+            ("/.vimrc", "/vim/.vimrc"),
+            ("/.config/nvim/init.lua", "/nvim/init.lua"),
+            ("/.tmux.conf", "/tmux/.tmux.conf"),
+        ];
+        for (target, source) in links {
+            filesystem
+                .create_symlink(
+                    &format!("{}{}", repo, source),
+                    &format!("{}{}", home, target),
+                )
+                .await
+                .unwrap();
+        }
+
+        let mut repository = MockRepository::new();
+        repository.set_config_response(
+            toml::from_str("[symlinks]\n\"shell/zshrc\" = \"~/.zshrc\"\n").unwrap(),
+        );
+        let service = RecoverService::new(repository, filesystem.clone(), MockPrompt::new());
+        let recovered = service
+            .recover("https://github.com/user/dotfiles")
+            .await
+            .unwrap();
+
+        let outcomes: BTreeMap<String, RecoveryOutcome> = recovered
+            .into_iter()
+            .map(|link| (link.target_path.replacen(&home, "~", 1), link.outcome))
+            .collect();
+        assert_eq!(
+            outcomes["~/.zshrc"],
+            RecoveryOutcome::Relinked {
+                source: format!("{}/shell/zshrc", repo)
+            }
+        );
+        assert_eq!(outcomes["~/.vimrc"], RecoveryOutcome::Restored);
+        assert_eq!(
+            outcomes["~/.config/nvim/init.lua"],
+            RecoveryOutcome::Restored
+        );
+        assert!(matches!(
+            outcomes["~/.tmux.conf"],
+            RecoveryOutcome::Unrecoverable { .. }
+        ));
+        assert_eq!(
+            filesystem.get_symlinks()[&format!("{}/.zshrc", home)],
+            format!("{}/shell/zshrc", repo)
+        );
+        assert!(filesystem
+            .exists(&filesystem.dotf_settings_path())
+            .await
+            .unwrap());
+    }
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::core::state::{RenderKind, StateStore};
+use crate::error::DotfResult;
+use crate::services::{
+    env_service::{EnvFileStatus, EnvService},
+    ssh_service::SshService,
+};
+use crate::traits::filesystem::FileSystem;
+
+/// A composed file whose sources changed since it was rendered
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleRender {
+    pub kind: RenderKind,
+    pub output: String,
+    /// Sources added, changed or removed since the last render
+    pub changed: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderOutcome {
+    Written,
+    Unchanged,
+    /// Its sources are gone, so the file was removed
+    Removed,
+    /// The file was edited by hand and was left alone
+    Conflict,
+}
+
+/// Tracks the files dotf composes from repository sources (`[ssh] config` fragments and
+/// `[env]`) and regenerates them
+pub struct RenderService<F> {
+    state: StateStore<F>,
+    ssh: SshService<F>,
+    env: EnvService<F>,
+}
+
+impl<F: FileSystem + Clone> RenderService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self {
+            state: StateStore::new(filesystem.clone()),
+            ssh: SshService::new(filesystem.clone()),
+            env: EnvService::new(filesystem),
+        }
+    }
+
+    /// Rendered files whose sources no longer hash as they did at render time
+    pub async fn stale(&self) -> DotfResult<Vec<StaleRender>> {
+        let mut stale = Vec::new();
+        for (output, rendered) in self.state.load().await?.renders {
+            let current = self.sources(rendered.kind).await?.unwrap_or_default();
+            let changed = changed_sources(&rendered.sources, &current);
+            if !changed.is_empty() {
+                stale.push(StaleRender {
+                    kind: rendered.kind,
+                    output,
+                    changed,
+                });
+            }
+        }
+        Ok(stale)
+    }
+
+    /// Regenerate every composed file, or with `only_stale` those with changed sources.
+    /// A hand-edited env fragment is only replaced with `force`.
+    pub async fn render(
+        &self,
+        only_stale: bool,
+        force: bool,
+    ) -> DotfResult<Vec<(RenderKind, String, RenderOutcome)>> {
+        let kinds: Vec<RenderKind> = if only_stale {
+            self.stale()
+                .await?
+                .into_iter()
+                .map(|stale| stale.kind)
+                .collect()
+        } else {
+            vec![RenderKind::Ssh, RenderKind::Env]
+        };
+
+        let mut outcomes = Vec::new();
+        for kind in kinds {
+            let outcome = match kind {
+                RenderKind::Ssh => {
+                    if !only_stale && self.ssh.config_sources().await?.is_none() {
+                        continue;
+                    }
+                    match self.ssh.render_config().await? {
+                        true => RenderOutcome::Written,
+                        false => RenderOutcome::Unchanged,
+                    }
+                }
+                RenderKind::Env => match self.env.generate(force).await? {
+                    EnvFileStatus::Written => RenderOutcome::Written,
+                    EnvFileStatus::Unchanged => RenderOutcome::Unchanged,
+                    EnvFileStatus::Removed => RenderOutcome::Removed,
+                    EnvFileStatus::Conflict => RenderOutcome::Conflict,
+                    EnvFileStatus::NotConfigured => continue,
+                },
+            };
+            outcomes.push((kind, self.output(kind), outcome));
+        }
+        Ok(outcomes)
+    }
+
+    fn output(&self, kind: RenderKind) -> String {
+        match kind {
+            RenderKind::Ssh => self.ssh.config_path(),
+            RenderKind::Env => self.env.env_path(),
+        }
+    }
+
+    async fn sources(&self, kind: RenderKind) -> DotfResult<Option<BTreeMap<String, String>>> {
+        match kind {
+            RenderKind::Ssh => self.ssh.config_sources().await,
+            RenderKind::Env => self.env.sources().await,
+        }
+    }
+}
+
+/// Names whose hash differs between `recorded` and `current`, in either direction
+fn changed_sources(
+    recorded: &BTreeMap<String, String>,
+    current: &BTreeMap<String, String>,
+) -> Vec<String> {
+    let mut changed: Vec<String> = recorded
+        .keys()
+        .chain(current.keys())
+        .filter(|name| recorded.get(*name) != current.get(*name))
+        .cloned()
+        .collect();
+    changed.sort();
+    changed.dedup();
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::Settings;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_stale_renders_after_sources_change() {
+        let filesystem = MockFileSystem::new();
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &Settings::new("https://github.com/user/dotfiles")
+                .to_toml()
+                .unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        let config = |editor: &str| {
+            format!(
+                "[ssh]\nconfig = [\"ssh/work\", \"ssh/home\"]\n\n[env]\nvars = {{ EDITOR = \"{}\" }}\n",
+                editor
+            )
+        };
+        filesystem.add_file(&format!("{}/dotf.toml", repo), &config("vim"));
+        filesystem.add_file(&format!("{}/ssh/work", repo), "Host work\n");
+        filesystem.add_file(&format!("{}/ssh/home", repo), "Host home\n");
+
+        let service = RenderService::new(filesystem.clone());
+        let outcomes = service.render(false, false).await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(outcomes
+            .iter()
+            .all(|(_, _, outcome)| *outcome == RenderOutcome::Written));
+        assert!(service.stale().await.unwrap().is_empty());
+
+        filesystem.add_file(&format!("{}/ssh/work", repo), "Host work\n  User me\n");
+        let stale = service.stale().await.unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].kind, RenderKind::Ssh);
+        assert_eq!(stale[0].changed, vec!["ssh/work"]);
+
+        filesystem.add_file(&format!("{}/dotf.toml", repo), &config("nvim"));
+        assert_eq!(service.stale().await.unwrap().len(), 2);
+
+        let outcomes = service.render(true, false).await.unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert!(service.stale().await.unwrap().is_empty());
+        let ssh_config = filesystem
+            .read_to_string(&service.output(RenderKind::Ssh))
+            .await
+            .unwrap();
+        assert!(ssh_config.contains("User me"));
+
+        assert!(service.render(true, false).await.unwrap().is_empty());
+    }
+}
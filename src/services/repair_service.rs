@@ -0,0 +1,1059 @@
+use std::path::Path;
+
+use crate::core::{
+    config::{dotf_config::SymlinkMap, loader, DotfConfig, Settings},
+    state::StateStore,
+    symlinks::{
+        conflict::is_nix_store_path, partition_export_ignored, resolve_priorities, BackupEntry,
+        ConflictResolution, ConflictResolver, EntryOperation, LinkMode, Permissions,
+        SymlinkManager, SymlinkOperation, SymlinkStatus,
+    },
+    timings,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, prompt::Prompt, repository::Repository};
+use crate::utils::diff::{check_diffable, line_diff, DiffLine};
+use crate::utils::merge::merge_three_way;
+use crate::utils::platform::detect_platform;
+
+/// Per-entry choices offered by the repair wizard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairAction {
+    /// Point the target back at the repository source (backing up conflicting files)
+    Repair,
+    /// Copy the current target content into the repository, then relink
+    Adopt,
+    /// Three-way merge local and repository edits since the last install, then relink
+    Merge,
+    /// Leave the entry as it is
+    Skip,
+    /// Remove the entry from dotf.toml and leave the target alone
+    Unmanage,
+}
+
+impl RepairAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RepairAction::Repair => "Repair",
+            RepairAction::Adopt => "Adopt",
+            RepairAction::Merge => "Merge",
+            RepairAction::Skip => "Skip",
+            RepairAction::Unmanage => "Unmanage",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            RepairAction::Repair => "Relink the target to the repository source",
+            RepairAction::Adopt => "Copy the current file into the repository and relink",
+            RepairAction::Merge => {
+                "Merge local and repository changes into the repository and relink"
+            }
+            RepairAction::Skip => "Leave this entry untouched",
+            RepairAction::Unmanage => "Remove this entry from dotf.toml",
+        }
+    }
+}
+
+/// A Broken, InvalidTarget or Conflict symlink that needs a decision
+#[derive(Debug, Clone)]
+pub struct RepairCandidate {
+    pub source_path: String,
+    pub target_path: String,
+    pub status: SymlinkStatus,
+    pub current_target: Option<String>,
+    /// Key in dotf.toml this entry came from; `None` for files expanded from a directory entry
+    pub config_key: Option<String>,
+}
+
+/// What currently exists at a target compared to what dotf expects
+#[derive(Debug, Clone)]
+pub struct RepairPreview {
+    pub expected: String,
+    pub current: String,
+    /// Differences from the repository source to the current content, when both are text
+    pub diff: Option<Vec<DiffLine>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepairDecision {
+    pub candidate: RepairCandidate,
+    pub action: RepairAction,
+}
+
+#[derive(Debug, Clone)]
+pub struct RepairOutcome {
+    pub target_path: String,
+    pub action: RepairAction,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    pub outcomes: Vec<RepairOutcome>,
+    pub backups: Vec<BackupEntry>,
+    pub config_updated: bool,
+    /// Repository sources left with conflict markers by a merge
+    pub merge_conflicts: Vec<String>,
+}
+
+pub struct RepairService<F, P> {
+    filesystem: F,
+    symlink_manager: SymlinkManager<F, P>,
+    conflict_resolver: ConflictResolver<F, P>,
+    state_store: StateStore<F>,
+    repository: Option<Box<dyn Repository + Send + Sync>>,
+    include_export_ignored: bool,
+}
+
+impl<F: FileSystem + Clone, P: Prompt> RepairService<F, P> {
+    pub fn new(filesystem: F, prompt: P) -> Self {
+        let symlink_manager = SymlinkManager::new(filesystem.clone(), prompt.clone());
+        let conflict_resolver = ConflictResolver::new(filesystem.clone(), prompt);
+        let state_store = StateStore::new(filesystem.clone());
+        Self {
+            filesystem,
+            symlink_manager,
+            conflict_resolver,
+            state_store,
+            repository: None,
+            include_export_ignored: false,
+        }
+    }
+
+    /// Repository used to find files that `.gitattributes` marks export-ignore, which
+    /// install leaves unlinked
+    pub fn with_repository(mut self, repository: Box<dyn Repository + Send + Sync>) -> Self {
+        self.repository = Some(repository);
+        self
+    }
+
+    pub fn with_include_export_ignored(mut self, include: bool) -> Self {
+        self.include_export_ignored = include;
+        self
+    }
+
+    /// Collect every configured symlink that is Broken, InvalidTarget or in Conflict
+    pub async fn find_candidates(&self) -> DotfResult<Vec<RepairCandidate>> {
+        let config = self.load_config().await?;
+        let symlinks = self.platform_symlinks(&config);
+        let settings = self.load_settings().await?;
+
+        let mut candidates = Vec::new();
+        for (config_key, operation) in self.create_symlink_operations(&symlinks).await? {
+            if settings.symlinks.is_disabled(&operation.target_path) {
+                continue;
+            }
+            let info = self
+                .symlink_manager
+                .get_single_symlink_status(&operation)
+                .await?;
+
+            if matches!(
+                info.status,
+                SymlinkStatus::Broken | SymlinkStatus::InvalidTarget | SymlinkStatus::Conflict
+            ) {
+                candidates.push(RepairCandidate {
+                    source_path: info.source_path,
+                    target_path: info.target_path,
+                    status: info.status,
+                    current_target: info.current_target,
+                    config_key,
+                });
+            }
+        }
+
+        candidates.sort_by(|a, b| a.target_path.cmp(&b.target_path));
+        Ok(candidates)
+    }
+
+    /// Look up the configured entry whose target is `target_path`, whatever its status
+    pub async fn find_entry(&self, target_path: &str) -> DotfResult<Option<RepairCandidate>> {
+        let config = self.load_config().await?;
+        let symlinks = self.platform_symlinks(&config);
+        let settings = self.load_settings().await?;
+
+        for (config_key, operation) in self.create_symlink_operations(&symlinks).await? {
+            if operation.target_path != target_path {
+                continue;
+            }
+
+            let mut info = self
+                .symlink_manager
+                .get_single_symlink_status(&operation)
+                .await?;
+            if settings.symlinks.is_disabled(target_path) {
+                info.status = SymlinkStatus::Disabled;
+            }
+            return Ok(Some(RepairCandidate {
+                source_path: info.source_path,
+                target_path: info.target_path,
+                status: info.status,
+                current_target: info.current_target,
+                config_key,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Actions that make sense for `candidate`, in the order they should be offered
+    pub async fn available_actions(
+        &self,
+        candidate: &RepairCandidate,
+    ) -> DotfResult<Vec<RepairAction>> {
+        let mut actions = Vec::new();
+
+        let nix_managed = candidate
+            .current_target
+            .as_deref()
+            .map(is_nix_store_path)
+            .unwrap_or(false);
+
+        if !nix_managed && self.filesystem.exists(&candidate.source_path).await? {
+            actions.push(RepairAction::Repair);
+        }
+        if !nix_managed && self.adoptable_path(candidate).await?.is_some() {
+            actions.push(RepairAction::Adopt);
+        }
+        if !nix_managed && self.merge_base(candidate).await?.is_some() {
+            actions.push(RepairAction::Merge);
+        }
+        actions.push(RepairAction::Skip);
+        if candidate.config_key.is_some() {
+            actions.push(RepairAction::Unmanage);
+        }
+
+        Ok(actions)
+    }
+
+    /// Describe the current state of `candidate` next to the expected one
+    pub async fn preview(&self, candidate: &RepairCandidate) -> DotfResult<RepairPreview> {
+        let expected = format!("symlink to {}", candidate.source_path);
+
+        let current = match (&candidate.status, &candidate.current_target) {
+            (SymlinkStatus::Broken, Some(target)) => {
+                format!("symlink to {} (source missing)", target)
+            }
+            (_, Some(target)) => format!("symlink to {}", target),
+            _ if self.filesystem.is_dir(&candidate.target_path).await? => "directory".to_string(),
+            _ => "regular file".to_string(),
+        };
+
+        let diff = match self.adoptable_path(candidate).await? {
+            Some(current_path) if self.filesystem.exists(&candidate.source_path).await? => {
+                match (
+                    self.filesystem.read_to_string(&candidate.source_path).await,
+                    self.filesystem.read_to_string(&current_path).await,
+                ) {
+                    // Binary and oversized files are described, never dumped
+                    (Ok(source), Ok(current)) if check_diffable(&source, &current).is_ok() => {
+                        line_diff(&source, &current)
+                    }
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        Ok(RepairPreview {
+            expected,
+            current,
+            diff,
+        })
+    }
+
+    /// Apply the collected decisions in one batch
+    ///
+    /// Filesystem actions are applied per entry and failures are recorded rather than
+    /// aborting the batch. All Unmanage decisions are written to dotf.toml together.
+    pub async fn apply(&self, decisions: &[RepairDecision]) -> DotfResult<RepairReport> {
+        let mut report = RepairReport::default();
+        let mut config_edit: Option<(String, String)> = None;
+
+        for decision in decisions {
+            let result = match decision.action {
+                RepairAction::Skip => Ok(()),
+                RepairAction::Repair => self
+                    .repair(&decision.candidate)
+                    .await
+                    .map(|backup| report.backups.extend(backup)),
+                RepairAction::Adopt => self.adopt(&decision.candidate).await,
+                RepairAction::Merge => {
+                    self.merge(&decision.candidate)
+                        .await
+                        .map(|(backup, conflicts)| {
+                            report.backups.extend(backup);
+                            if conflicts > 0 {
+                                report
+                                    .merge_conflicts
+                                    .push(decision.candidate.source_path.clone());
+                            }
+                        })
+                }
+                RepairAction::Unmanage => match decision.candidate.config_key {
+                    Some(ref key) => {
+                        self.unmanage(&decision.candidate, key, &mut config_edit)
+                            .await
+                    }
+                    None => Err(DotfError::Config(format!(
+                        "'{}' comes from a directory entry and cannot be unmanaged on its own",
+                        decision.candidate.target_path
+                    ))),
+                },
+            };
+
+            report.outcomes.push(RepairOutcome {
+                target_path: decision.candidate.target_path.clone(),
+                action: decision.action,
+                error: result.err().map(|e| e.to_string()),
+            });
+        }
+
+        if let Some((content, updated)) = config_edit {
+            if updated != content {
+                let config_path = format!("{}/dotf.toml", self.repo_path().await?);
+                self.filesystem.write(&config_path, &updated).await?;
+                report.config_updated = true;
+            }
+        }
+
+        Ok(report)
+    }
+
+    async fn repair(&self, candidate: &RepairCandidate) -> DotfResult<Option<BackupEntry>> {
+        let backup = self.clear_target(candidate).await?;
+        self.link(candidate).await?;
+        Ok(backup)
+    }
+
+    /// Merge the local file and the repository source against the content recorded at
+    /// the last install, write the result to the source and relink the target.
+    ///
+    /// Returns the backup of the replaced target and the number of unresolved conflicts.
+    async fn merge(&self, candidate: &RepairCandidate) -> DotfResult<(Option<BackupEntry>, usize)> {
+        let (current_path, base) = self.merge_base(candidate).await?.ok_or_else(|| {
+            DotfError::Operation(format!(
+                "No installed version of '{}' is recorded to merge against",
+                candidate.target_path
+            ))
+        })?;
+
+        let local = self.filesystem.read_to_string(&current_path).await?;
+        let repository = self
+            .filesystem
+            .read_to_string(&candidate.source_path)
+            .await?;
+        let outcome = merge_three_way(&local, &base, &repository)?;
+
+        self.filesystem
+            .write(&candidate.source_path, &outcome.content)
+            .await?;
+        let backup = self.clear_target(candidate).await?;
+        self.link(candidate).await?;
+
+        Ok((backup, outcome.conflicts))
+    }
+
+    /// Get the target out of the way, backing up anything that is not a symlink
+    async fn clear_target(&self, candidate: &RepairCandidate) -> DotfResult<Option<BackupEntry>> {
+        let backup = match candidate.status {
+            SymlinkStatus::Conflict => {
+                let conflict = self
+                    .conflict_resolver
+                    .check_conflict(&candidate.source_path, &candidate.target_path)
+                    .await?;
+                match conflict {
+                    Some(conflict) => {
+                        self.conflict_resolver
+                            .resolve_conflict(&conflict, ConflictResolution::Backup)
+                            .await?
+                    }
+                    None => None,
+                }
+            }
+            _ => {
+                self.filesystem.remove_file(&candidate.target_path).await?;
+                None
+            }
+        };
+
+        Ok(backup)
+    }
+
+    /// Copy the content currently at the target over the repository source and relink
+    pub async fn adopt(&self, candidate: &RepairCandidate) -> DotfResult<()> {
+        let current_path = self.adoptable_path(candidate).await?.ok_or_else(|| {
+            DotfError::Operation(format!(
+                "Nothing to adopt at '{}': it is not a readable file",
+                candidate.target_path
+            ))
+        })?;
+
+        if let Some(parent) = Path::new(&candidate.source_path).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.filesystem
+            .copy_file(&current_path, &candidate.source_path)
+            .await?;
+
+        self.filesystem.remove_file(&candidate.target_path).await?;
+        self.link(candidate).await
+    }
+
+    /// Drop the entry for `config_key` from the pending dotf.toml edit, a pair of the
+    /// content as read and as updated so far, and unlink the target if it is broken
+    async fn unmanage(
+        &self,
+        candidate: &RepairCandidate,
+        config_key: &str,
+        config_edit: &mut Option<(String, String)>,
+    ) -> DotfResult<()> {
+        if config_edit.is_none() {
+            let config_path = format!("{}/dotf.toml", self.repo_path().await?);
+            let content = self.filesystem.read_to_string(&config_path).await?;
+            *config_edit = Some((content.clone(), content));
+        }
+        if let Some((_, updated)) = config_edit {
+            let profile = self.load_settings().await?.profile;
+            *updated =
+                remove_symlink_entry(updated, config_key, &detect_platform(), profile.as_deref())?;
+        }
+        self.unlink_broken(candidate).await
+    }
+
+    /// A broken link points at a source that no longer exists; drop it when unmanaging
+    async fn unlink_broken(&self, candidate: &RepairCandidate) -> DotfResult<()> {
+        if candidate.status == SymlinkStatus::Broken {
+            self.filesystem.remove_file(&candidate.target_path).await?;
+        }
+        Ok(())
+    }
+
+    async fn link(&self, candidate: &RepairCandidate) -> DotfResult<()> {
+        if let Some(parent) = Path::new(&candidate.target_path).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.filesystem
+            .create_symlink(&candidate.source_path, &candidate.target_path)
+            .await?;
+
+        self.symlink_manager
+            .record_installed(&[SymlinkOperation {
+                source_path: candidate.source_path.clone(),
+                target_path: candidate.target_path.clone(),
+                mode: LinkMode::Symlink,
+                permissions: Permissions::default(),
+            }])
+            .await;
+        Ok(())
+    }
+
+    /// The local file and the recorded install-time content, when a merge is possible
+    ///
+    /// A merge only makes sense when both sides moved away from what was installed;
+    /// otherwise Repair or Adopt already gives the right result.
+    async fn merge_base(
+        &self,
+        candidate: &RepairCandidate,
+    ) -> DotfResult<Option<(String, String)>> {
+        let Some(current_path) = self.adoptable_path(candidate).await? else {
+            return Ok(None);
+        };
+        if !self.filesystem.exists(&candidate.source_path).await? {
+            return Ok(None);
+        }
+        // An unreadable state file just means there is nothing to merge against
+        let Ok(Some(base)) = self.state_store.base_content(&candidate.target_path).await else {
+            return Ok(None);
+        };
+
+        let (Ok(local), Ok(repository)) = (
+            self.filesystem.read_to_string(&current_path).await,
+            self.filesystem.read_to_string(&candidate.source_path).await,
+        ) else {
+            return Ok(None);
+        };
+
+        if local == base || repository == base || local == repository {
+            return Ok(None);
+        }
+
+        Ok(Some((current_path, base)))
+    }
+
+    /// Path holding the content currently seen at the target, if it is a regular file
+    async fn adoptable_path(&self, candidate: &RepairCandidate) -> DotfResult<Option<String>> {
+        let path = match (&candidate.status, &candidate.current_target) {
+            (SymlinkStatus::Conflict, None) => candidate.target_path.clone(),
+            (SymlinkStatus::InvalidTarget, Some(target)) => target.clone(),
+            _ => return Ok(None),
+        };
+
+        if self.filesystem.exists(&path).await? && !self.filesystem.is_dir(&path).await? {
+            Ok(Some(path))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Drop files expanded from a directory that `.gitattributes` marks `export-ignore`,
+    /// as install does
+    async fn skip_export_ignored(
+        &self,
+        repo_path: &str,
+        operations: Vec<SymlinkOperation>,
+    ) -> DotfResult<Vec<SymlinkOperation>> {
+        let Some(repository) = self
+            .repository
+            .as_ref()
+            .filter(|_| !self.include_export_ignored)
+        else {
+            return Ok(operations);
+        };
+
+        let (kept, _) =
+            partition_export_ignored(repository.as_ref(), repo_path, operations).await?;
+        Ok(kept)
+    }
+
+    fn platform_symlinks(&self, config: &DotfConfig) -> SymlinkMap {
+        let mut symlinks = config.symlinks.clone();
+
+        if let Some(platform_config) = config.platform.for_platform(&detect_platform()) {
+            symlinks.extend(platform_config.symlinks.clone());
+        }
+
+        symlinks
+    }
+
+    /// Build symlink operations, remembering which dotf.toml key each one came from. Only
+    /// the highest priority entry is kept for a target, as in install.
+    async fn create_symlink_operations(
+        &self,
+        symlinks: &SymlinkMap,
+    ) -> DotfResult<Vec<(Option<String>, SymlinkOperation)>> {
+        let mut operations = Vec::new();
+        let repo_path = self.repo_path().await?;
+
+        for (source, target) in symlinks {
+            let expanded_target = if target.starts_with("~/") {
+                let home = dirs::home_dir().ok_or_else(|| {
+                    DotfError::Operation("Could not determine home directory".to_string())
+                })?;
+                target.replacen("~", &home.to_string_lossy(), 1)
+            } else {
+                target.clone()
+            };
+
+            let absolute_source = if source.starts_with('/') {
+                source.clone()
+            } else {
+                format!("{}/{}", repo_path, source)
+            };
+
+            if self.filesystem.exists(&absolute_source).await?
+                && self.filesystem.is_dir(&absolute_source).await?
+            {
+                let dir_operations = self
+                    .expand_directory_operations(&absolute_source, &expanded_target)
+                    .await?;
+                let dir_operations = self.skip_export_ignored(&repo_path, dir_operations).await?;
+                operations.extend(dir_operations.into_iter().map(|operation| EntryOperation {
+                    entry: source.clone(),
+                    priority: symlinks.priority(source),
+                    from_directory: true,
+                    operation: SymlinkOperation {
+                        mode: symlinks.mode(source),
+                        permissions: symlinks.permissions(source),
+                        ..operation
+                    },
+                }));
+            } else {
+                operations.push(EntryOperation {
+                    entry: source.clone(),
+                    priority: symlinks.priority(source),
+                    from_directory: false,
+                    operation: SymlinkOperation {
+                        source_path: absolute_source,
+                        target_path: expanded_target,
+                        mode: symlinks.mode(source),
+                        permissions: symlinks.permissions(source),
+                    },
+                });
+            }
+        }
+
+        let (operations, _) = resolve_priorities(operations);
+        Ok(operations
+            .into_iter()
+            .map(|operation| {
+                let config_key = (!operation.from_directory).then_some(operation.entry);
+                (config_key, operation.operation)
+            })
+            .collect())
+    }
+
+    async fn expand_directory_operations(
+        &self,
+        source_dir: &str,
+        target_dir: &str,
+    ) -> DotfResult<Vec<SymlinkOperation>> {
+        let mut timer = timings::phase("directory expansion");
+        let mut operations = Vec::new();
+        let mut dir_stack = vec![(source_dir.to_string(), target_dir.to_string())];
+
+        while let Some((current_source, current_target)) = dir_stack.pop() {
+            let entries = self.filesystem.list_entries(&current_source).await?;
+
+            for entry in entries {
+                let relative_path = entry
+                    .path
+                    .strip_prefix(&current_source)
+                    .unwrap_or(&entry.path)
+                    .trim_start_matches('/');
+
+                let target_path = if relative_path.is_empty() {
+                    current_target.clone()
+                } else {
+                    format!("{}/{}", current_target, relative_path)
+                };
+
+                if entry.is_dir && !entry.is_symlink {
+                    dir_stack.push((entry.path.clone(), target_path));
+                } else if entry.is_file || entry.is_symlink {
+                    operations.push(SymlinkOperation {
+                        source_path: entry.path.clone(),
+                        target_path,
+                        mode: LinkMode::Symlink,
+                        permissions: Permissions::default(),
+                    });
+                }
+            }
+        }
+
+        timer.items(operations.len());
+        Ok(operations)
+    }
+
+    pub async fn repo_path(&self) -> DotfResult<String> {
+        let settings = self.load_settings().await?;
+        Ok(settings
+            .repository
+            .local
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path()))
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        loader::load_config(&self.filesystem, &self.load_settings().await?).await
+    }
+
+    pub async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+/// Drop the entry for `source` from [symlinks], [platform.<platform>.symlinks] and, with
+/// an active profile, [profile.<profile>.symlinks], whether it is a plain value or a table
+/// of options, keeping the rest of the file (including comments) untouched. Fails when
+/// none of those tables has it.
+fn remove_symlink_entry(
+    content: &str,
+    source: &str,
+    platform: &str,
+    profile: Option<&str>,
+) -> DotfResult<String> {
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
+
+    let mut removed = false;
+    if let Some(symlinks) = document
+        .get_mut("symlinks")
+        .and_then(toml_edit::Item::as_table_like_mut)
+    {
+        removed |= symlinks.remove(source).is_some();
+    }
+    let sections =
+        std::iter::once(("platform", platform)).chain(profile.map(|profile| ("profile", profile)));
+    for (kind, name) in sections {
+        if let Some(symlinks) = document
+            .get_mut(kind)
+            .and_then(|sections| sections.get_mut(name))
+            .and_then(|section| section.get_mut("symlinks"))
+            .and_then(toml_edit::Item::as_table_like_mut)
+        {
+            removed |= symlinks.remove(source).is_some();
+        }
+    }
+
+    if !removed {
+        return Err(DotfError::Config(format!(
+            "'{}' is not a symlink entry of dotf.toml on this machine",
+            source
+        )));
+    }
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::settings::Repository;
+    use crate::core::repository::GitRepository;
+    use crate::traits::{filesystem::tests::MockFileSystem, prompt::tests::MockPrompt};
+    use chrono::Utc;
+
+    const CONFIG: &str = r#"[symlinks]
+# Shell
+"zsh/.zshrc" = "/home/user/.zshrc"
+"vim/.vimrc" = "/home/user/.vimrc"
+"git/.gitconfig" = "/home/user/.gitconfig"
+"#;
+
+    fn setup() -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: Repository {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(&format!("{}/dotf.toml", repo), CONFIG);
+        filesystem.add_file(&format!("{}/zsh/.zshrc", repo), "export A=1\n");
+        filesystem.add_file(&format!("{}/vim/.vimrc", repo), "set number\n");
+        filesystem.add_file(&format!("{}/git/.gitconfig", repo), "[user]\n");
+
+        // Conflict: a regular file with local edits
+        filesystem.add_file("/home/user/.zshrc", "export A=1\nexport B=2\n");
+        // InvalidTarget: points somewhere else
+        filesystem.add_file("/tmp/other-vimrc", "set nonumber\n");
+        filesystem.symlinks.lock().unwrap().insert(
+            "/home/user/.vimrc".to_string(),
+            "/tmp/other-vimrc".to_string(),
+        );
+        // Valid
+        filesystem.symlinks.lock().unwrap().insert(
+            "/home/user/.gitconfig".to_string(),
+            format!("{}/git/.gitconfig", repo),
+        );
+
+        filesystem
+    }
+
+    #[tokio::test]
+    async fn test_find_candidates_and_preview() {
+        let filesystem = setup();
+        let service = RepairService::new(filesystem.clone(), MockPrompt::new());
+
+        let candidates = service.find_candidates().await.unwrap();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].target_path, "/home/user/.vimrc");
+        assert_eq!(candidates[0].status, SymlinkStatus::InvalidTarget);
+        assert_eq!(candidates[1].status, SymlinkStatus::Conflict);
+        assert_eq!(candidates[1].config_key.as_deref(), Some("zsh/.zshrc"));
+
+        let preview = service.preview(&candidates[1]).await.unwrap();
+        assert_eq!(preview.current, "regular file");
+        assert!(preview
+            .diff
+            .unwrap()
+            .contains(&DiffLine::Added("export B=2".to_string())));
+
+        let actions = service.available_actions(&candidates[1]).await.unwrap();
+        assert_eq!(
+            actions,
+            vec![
+                RepairAction::Repair,
+                RepairAction::Adopt,
+                RepairAction::Skip,
+                RepairAction::Unmanage
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_find_candidates_uses_active_profile() {
+        let filesystem = setup();
+        let mut settings = Settings::from_toml(
+            &filesystem
+                .read_to_string(&filesystem.dotf_settings_path())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        settings.profile = Some("work".to_string());
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo),
+            &format!(
+                "{}\n[profile.work.symlinks]\n\"work/.vimrc\" = \"/home/user/.vimrc\"\n",
+                CONFIG
+            ),
+        );
+        filesystem.add_file(&format!("{}/work/.vimrc", repo), "set relativenumber\n");
+
+        let service = RepairService::new(filesystem.clone(), MockPrompt::new());
+        let candidates = service.find_candidates().await.unwrap();
+        let vimrc = candidates
+            .iter()
+            .find(|candidate| candidate.target_path == "/home/user/.vimrc")
+            .unwrap();
+        assert_eq!(vimrc.config_key.as_deref(), Some("work/.vimrc"));
+        assert_eq!(vimrc.source_path, format!("{}/work/.vimrc", repo));
+    }
+
+    #[tokio::test]
+    async fn test_find_candidates_skips_export_ignored() {
+        let repo = tempfile::TempDir::new().unwrap();
+        std::fs::write(repo.path().join(".gitattributes"), "*.md export-ignore\n").unwrap();
+        let status = std::process::Command::new("git")
+            .args(["init", "--quiet"])
+            .current_dir(repo.path())
+            .status()
+            .unwrap();
+        assert!(status.success());
+        let repo_path = repo.path().to_string_lossy().to_string();
+
+        let filesystem = setup();
+        let mut settings = Settings::from_toml(
+            &filesystem
+                .read_to_string(&filesystem.dotf_settings_path())
+                .await
+                .unwrap(),
+        )
+        .unwrap();
+        settings.repository.local = Some(repo_path.clone());
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo_path),
+            "[symlinks]\n\"vim/pack\" = \"/home/user/.vim/pack\"\n",
+        );
+        filesystem.add_directory(&format!("{}/vim/pack", repo_path));
+        filesystem.add_file(&format!("{}/vim/pack/plugin.vim", repo_path), "\" plugin\n");
+        filesystem.add_file(&format!("{}/vim/pack/README.md", repo_path), "# Plugin\n");
+        // Both targets are in conflict with a local copy
+        filesystem.add_file("/home/user/.vim/pack/plugin.vim", "\" local\n");
+        filesystem.add_file("/home/user/.vim/pack/README.md", "# Local\n");
+
+        let targets = |include_export_ignored: bool| {
+            let service = RepairService::new(filesystem.clone(), MockPrompt::new())
+                .with_repository(Box::new(GitRepository::new()))
+                .with_include_export_ignored(include_export_ignored);
+            async move {
+                let mut targets: Vec<String> = service
+                    .find_candidates()
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|candidate| candidate.target_path)
+                    .collect();
+                targets.sort();
+                targets
+            }
+        };
+
+        assert_eq!(
+            targets(false).await,
+            vec!["/home/user/.vim/pack/plugin.vim"]
+        );
+        assert_eq!(
+            targets(true).await,
+            vec![
+                "/home/user/.vim/pack/README.md",
+                "/home/user/.vim/pack/plugin.vim"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_batches_decisions() {
+        let filesystem = setup();
+        let service = RepairService::new(filesystem.clone(), MockPrompt::new());
+        let repo = filesystem.dotf_repo_path();
+
+        let candidates = service.find_candidates().await.unwrap();
+        let decisions = vec![
+            RepairDecision {
+                candidate: candidates[0].clone(),
+                action: RepairAction::Unmanage,
+            },
+            RepairDecision {
+                candidate: candidates[1].clone(),
+                action: RepairAction::Adopt,
+            },
+        ];
+
+        let report = service.apply(&decisions).await.unwrap();
+        assert!(report.outcomes.iter().all(|o| o.error.is_none()));
+        assert!(report.config_updated);
+
+        // Adopted content now lives in the repository and the target is linked
+        assert_eq!(
+            filesystem
+                .read_to_string(&format!("{}/zsh/.zshrc", repo))
+                .await
+                .unwrap(),
+            "export A=1\nexport B=2\n"
+        );
+        assert_eq!(
+            filesystem.get_symlinks().get("/home/user/.zshrc"),
+            Some(&format!("{}/zsh/.zshrc", repo))
+        );
+
+        // Unmanaged entry is gone from dotf.toml but the target is untouched
+        let config = filesystem
+            .read_to_string(&format!("{}/dotf.toml", repo))
+            .await
+            .unwrap();
+        assert!(!config.contains("vim/.vimrc"));
+        assert!(config.contains("# Shell"));
+        assert_eq!(
+            filesystem.get_symlinks().get("/home/user/.vimrc"),
+            Some(&"/tmp/other-vimrc".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_repair_conflict_creates_backup() {
+        let filesystem = setup();
+        let service = RepairService::new(filesystem.clone(), MockPrompt::new());
+
+        let candidates = service.find_candidates().await.unwrap();
+        let report = service
+            .apply(&[RepairDecision {
+                candidate: candidates[1].clone(),
+                action: RepairAction::Repair,
+            }])
+            .await
+            .unwrap();
+
+        assert_eq!(report.backups.len(), 1);
+        assert!(!report.config_updated);
+        assert_eq!(
+            filesystem.get_symlinks().get("/home/user/.zshrc"),
+            Some(&format!("{}/zsh/.zshrc", filesystem.dotf_repo_path()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_merge_local_and_repository_changes() {
+        let filesystem = setup();
+        let repo = filesystem.dotf_repo_path();
+        let source = format!("{}/zsh/.zshrc", repo);
+
+        // Installed version, then both sides edit different lines
+        filesystem.add_file(&source, "export A=1\n\nexport B=1\n");
+        StateStore::new(filesystem.clone())
+            .record_installed(&[(source.clone(), "/home/user/.zshrc".to_string())])
+            .await
+            .unwrap();
+        filesystem.add_file(&source, "export A=1\n\nexport B=2\n");
+        filesystem.add_file("/home/user/.zshrc", "export A=9\n\nexport B=1\n");
+
+        let service = RepairService::new(filesystem.clone(), MockPrompt::new());
+        let candidates = service.find_candidates().await.unwrap();
+        let conflict = candidates[1].clone();
+        assert!(service
+            .available_actions(&conflict)
+            .await
+            .unwrap()
+            .contains(&RepairAction::Merge));
+
+        let report = service
+            .apply(&[RepairDecision {
+                candidate: conflict,
+                action: RepairAction::Merge,
+            }])
+            .await
+            .unwrap();
+
+        assert!(report.outcomes[0].error.is_none());
+        assert!(report.merge_conflicts.is_empty());
+        assert_eq!(report.backups.len(), 1);
+        assert_eq!(
+            filesystem.read_to_string(&source).await.unwrap(),
+            "export A=9\n\nexport B=2\n"
+        );
+        assert_eq!(
+            filesystem.get_symlinks().get("/home/user/.zshrc"),
+            Some(&source)
+        );
+    }
+
+    #[test]
+    fn test_remove_symlink_entry() {
+        let content = "[symlinks]\n\"a\" = \"~/a\"\nb = \"~/b\"\n\n[platform.linux.symlinks]\n\"a\" = \"~/la\"\n\n[platform.macos.symlinks]\n\"a\" = \"~/ma\"\n\n[scripts.custom]\na = \"x.sh\"\n";
+        let updated = remove_symlink_entry(content, "a", "linux", None).unwrap();
+        assert_eq!(
+            updated,
+            "[symlinks]\nb = \"~/b\"\n\n[platform.linux.symlinks]\n\n[platform.macos.symlinks]\n\"a\" = \"~/ma\"\n\n[scripts.custom]\na = \"x.sh\"\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_symlink_entry_in_table_form() {
+        let content = "[symlinks]\nb = \"~/b\"\n\n[symlinks.\"ssh/config\"]\ntarget = \"~/.ssh/config\"\nchmod = \"600\"\n\n[hooks]\n";
+        let updated = remove_symlink_entry(content, "ssh/config", "linux", None).unwrap();
+        assert_eq!(updated, "[symlinks]\nb = \"~/b\"\n\n[hooks]\n");
+    }
+
+    #[test]
+    fn test_remove_symlink_entry_of_active_profile() {
+        let content = "[profile.work.symlinks]\n\"work/.npmrc\" = \"~/.npmrc\"\n\n[profile.home.symlinks]\n\"work/.npmrc\" = \"~/.npmrc\"\n";
+        let updated = remove_symlink_entry(content, "work/.npmrc", "linux", Some("work")).unwrap();
+        assert_eq!(
+            updated,
+            "[profile.work.symlinks]\n\n[profile.home.symlinks]\n\"work/.npmrc\" = \"~/.npmrc\"\n"
+        );
+    }
+
+    #[test]
+    fn test_remove_symlink_entry_missing() {
+        let content = "[symlinks]\nb = \"~/b\"\n";
+        assert!(remove_symlink_entry(content, "a", "linux", None).is_err());
+    }
+}
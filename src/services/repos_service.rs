@@ -0,0 +1,128 @@
+use crate::core::config::{
+    overlays::{overlay_path, validate_overlay_name},
+    NamedRepository, Settings,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{filesystem::FileSystem, repository::Repository};
+
+/// An overlay repository from settings.toml and where it is checked out
+#[derive(Debug, Clone)]
+pub struct OverlayInfo {
+    pub repository: NamedRepository,
+    pub path: String,
+    pub cloned: bool,
+}
+
+/// Manages the overlay repositories (`[[repositories]]`) whose links are layered over
+/// the main repository
+pub struct ReposService<R, F> {
+    repository: R,
+    filesystem: F,
+}
+
+impl<R: Repository, F: FileSystem> ReposService<R, F> {
+    pub fn new(repository: R, filesystem: F) -> Self {
+        Self {
+            repository,
+            filesystem,
+        }
+    }
+
+    /// Overlays in order of precedence, lowest first
+    pub async fn list(&self) -> DotfResult<Vec<OverlayInfo>> {
+        let settings = self.load_settings().await?;
+        let mut overlays = Vec::new();
+        for repository in settings.repositories {
+            let path = overlay_path(&self.filesystem, &repository.name);
+            overlays.push(OverlayInfo {
+                cloned: self.filesystem.exists(&path).await?,
+                repository,
+                path,
+            });
+        }
+        Ok(overlays)
+    }
+
+    /// Clone `remote` as overlay `name` and add it after the existing overlays, so its
+    /// links take precedence over all of them
+    pub async fn add(
+        &self,
+        name: &str,
+        remote: &str,
+        branch: Option<String>,
+    ) -> DotfResult<OverlayInfo> {
+        validate_overlay_name(name)?;
+        let mut settings = self.load_settings().await?;
+        if settings.repositories.iter().any(|r| r.name == name) {
+            return Err(DotfError::Config(format!(
+                "Repository '{}' already exists",
+                name
+            )));
+        }
+
+        let path = overlay_path(&self.filesystem, name);
+        if self.filesystem.exists(&path).await? {
+            return Err(DotfError::Operation(format!(
+                "{} already exists; move it away first",
+                path
+            )));
+        }
+        self.repository.validate_remote(remote).await?;
+        self.filesystem
+            .create_dir_all(&self.filesystem.dotf_repos_path())
+            .await?;
+        match &branch {
+            Some(branch) => self.repository.clone_branch(remote, branch, &path).await?,
+            None => self.repository.clone(remote, &path).await?,
+        }
+
+        let repository = NamedRepository {
+            name: name.to_string(),
+            remote: remote.to_string(),
+            branch,
+        };
+        settings.repositories.push(repository.clone());
+        self.save_settings(&settings).await?;
+        Ok(OverlayInfo {
+            repository,
+            path,
+            cloned: true,
+        })
+    }
+
+    /// Stop layering overlay `name`. Its checkout is left in place; returns its path.
+    pub async fn remove(&self, name: &str) -> DotfResult<String> {
+        let mut settings = self.load_settings().await?;
+        let before = settings.repositories.len();
+        settings.repositories.retain(|r| r.name != name);
+        if settings.repositories.len() == before {
+            return Err(DotfError::Config(format!(
+                "Unknown repository '{}' (see 'dotf repos list')",
+                name
+            )));
+        }
+        self.save_settings(&settings).await?;
+        Ok(overlay_path(&self.filesystem, name))
+    }
+
+    async fn save_settings(&self, settings: &Settings) -> DotfResult<()> {
+        let content = settings
+            .to_toml()
+            .map_err(|e| DotfError::Serialization(e.to_string()))?;
+        self.filesystem
+            .write(&self.filesystem.dotf_settings_path(), &content)
+            .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
@@ -0,0 +1,210 @@
+use std::path::{Path, PathBuf};
+
+use crate::core::{
+    config::Settings,
+    sandbox::{Sandbox, SandboxChange},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+/// What the change seen in the sandbox would do to the real home directory
+#[derive(Debug, Clone, PartialEq)]
+pub enum SandboxOutcome {
+    /// Nothing is there yet
+    Create,
+    /// Something else is there and would be backed up and replaced
+    Replace,
+    /// The real home already looks like this
+    Unchanged,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SandboxReportEntry {
+    pub change: SandboxChange,
+    pub outcome: SandboxOutcome,
+}
+
+/// Runs installs against a throwaway copy of the configuration and maps the result
+/// back onto the real home directory
+pub struct SandboxService<F> {
+    filesystem: F,
+    home: PathBuf,
+}
+
+impl<F: FileSystem> SandboxService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self {
+            filesystem,
+            home: dirs::home_dir().unwrap_or_default(),
+        }
+    }
+
+    /// The local repository path from the current settings
+    pub async fn repo_path(&self) -> DotfResult<String> {
+        let settings = self.load_settings().await?;
+        Ok(settings
+            .repository
+            .local
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path()))
+    }
+
+    /// Copy the repository and settings into a new sandbox
+    pub async fn prepare(&self) -> DotfResult<Sandbox> {
+        let settings = self.load_settings().await?;
+        let repo_path = settings
+            .repository
+            .local
+            .clone()
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
+
+        if !self.filesystem.exists(&repo_path).await? {
+            return Err(DotfError::Repository(format!(
+                "Repository not found at {}",
+                repo_path
+            )));
+        }
+
+        Sandbox::prepare(Path::new(&repo_path), &settings)
+    }
+
+    /// Compare what an install left in the sandbox with the real home directory.
+    /// `sandbox_repo` is the repository copy the sandbox links point into. Entries inside a
+    /// directory that would be created are folded into that directory.
+    pub async fn report(
+        &self,
+        sandbox_repo: &Path,
+        changes: Vec<SandboxChange>,
+    ) -> DotfResult<Vec<SandboxReportEntry>> {
+        let repo_path = PathBuf::from(self.repo_path().await?);
+        let mut created_dirs: Vec<String> = Vec::new();
+        let mut report = Vec::new();
+
+        for change in changes {
+            if created_dirs
+                .iter()
+                .any(|dir| Path::new(change.path()).starts_with(dir))
+            {
+                continue;
+            }
+
+            let real_path = self.home.join(change.path()).to_string_lossy().to_string();
+            let is_symlink = self
+                .filesystem
+                .is_symlink(&real_path)
+                .await
+                .unwrap_or(false);
+            let exists = is_symlink || self.filesystem.exists(&real_path).await?;
+
+            let outcome = match &change {
+                SandboxChange::Symlink { target, .. } => {
+                    // Links point into the sandbox copy; the real ones point into the repository
+                    let target = match Path::new(target).strip_prefix(sandbox_repo) {
+                        Ok(relative) => repo_path.join(relative),
+                        Err(_) => PathBuf::from(target),
+                    };
+                    if is_symlink && self.filesystem.read_link(&real_path).await? == target {
+                        SandboxOutcome::Unchanged
+                    } else if exists {
+                        SandboxOutcome::Replace
+                    } else {
+                        SandboxOutcome::Create
+                    }
+                }
+                SandboxChange::Directory { path } => {
+                    if is_symlink || !exists {
+                        created_dirs.push(path.clone());
+                        SandboxOutcome::Create
+                    } else if self.filesystem.is_dir(&real_path).await? {
+                        SandboxOutcome::Unchanged
+                    } else {
+                        SandboxOutcome::Replace
+                    }
+                }
+                SandboxChange::File { .. } => {
+                    if exists {
+                        SandboxOutcome::Replace
+                    } else {
+                        SandboxOutcome::Create
+                    }
+                }
+            };
+
+            report.push(SandboxReportEntry { change, outcome });
+        }
+
+        Ok(report)
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_report_against_real_home() {
+        let filesystem = MockFileSystem::new();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.repository.local = Some("/home/user/.dotf/repo".to_string());
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_directory("/home/user/.config");
+        filesystem
+            .create_symlink("/home/user/.dotf/repo/zsh/.zshrc", "/home/user/.zshrc")
+            .await
+            .unwrap();
+        filesystem.add_file("/home/user/.gitconfig", "[user]\n");
+
+        let mut service = SandboxService::new(filesystem);
+        service.home = PathBuf::from("/home/user");
+
+        let sandbox_repo = Path::new("/tmp/dotf-sandbox-x/.dotf/repo");
+        let link = |path: &str, target: &str| SandboxChange::Symlink {
+            path: path.to_string(),
+            target: format!("/tmp/dotf-sandbox-x/.dotf/repo/{}", target),
+        };
+        let changes = vec![
+            SandboxChange::Directory {
+                path: ".config".to_string(),
+            },
+            SandboxChange::Directory {
+                path: ".config/nvim".to_string(),
+            },
+            link(".config/nvim/init.lua", "nvim/init.lua"),
+            link(".gitconfig", "git/.gitconfig"),
+            link(".zshrc", "zsh/.zshrc"),
+        ];
+
+        let report = service.report(sandbox_repo, changes).await.unwrap();
+        let outcomes: Vec<(&str, SandboxOutcome)> = report
+            .iter()
+            .map(|entry| (entry.change.path(), entry.outcome.clone()))
+            .collect();
+
+        assert_eq!(
+            outcomes,
+            vec![
+                (".config", SandboxOutcome::Unchanged),
+                (".config/nvim", SandboxOutcome::Create),
+                (".gitconfig", SandboxOutcome::Replace),
+                (".zshrc", SandboxOutcome::Unchanged),
+            ]
+        );
+    }
+}
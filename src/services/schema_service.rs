@@ -1,7 +1,46 @@
 use crate::error::{DotfError, DotfResult};
+use crate::traits::prompt::Prompt;
 use std::fs;
 use std::path::Path;
 
+/// Files and directories under `$HOME` that `dotf schema generate` looks for, with the
+/// repository path each is proposed at
+const COMMON_DOTFILES: &[(&str, &str)] = &[
+    (".zshrc", "zsh/.zshrc"),
+    (".zprofile", "zsh/.zprofile"),
+    (".zshenv", "zsh/.zshenv"),
+    (".bashrc", "bash/.bashrc"),
+    (".bash_profile", "bash/.bash_profile"),
+    (".profile", "shell/.profile"),
+    (".inputrc", "shell/.inputrc"),
+    (".gitconfig", "git/.gitconfig"),
+    (".gitignore_global", "git/.gitignore_global"),
+    (".vimrc", "vim/.vimrc"),
+    (".tmux.conf", "tmux/.tmux.conf"),
+    (".editorconfig", "editorconfig/.editorconfig"),
+    (".wezterm.lua", "wezterm/.wezterm.lua"),
+    (".config/nvim", "config/nvim"),
+    (".config/helix", "config/helix"),
+    (".config/fish", "config/fish"),
+    (".config/git", "config/git"),
+    (".config/alacritty", "config/alacritty"),
+    (".config/kitty", "config/kitty"),
+    (".config/wezterm", "config/wezterm"),
+    (".config/ghostty", "config/ghostty"),
+    (".config/starship.toml", "config/starship.toml"),
+    (".config/tmux", "config/tmux"),
+    (".config/zed", "config/zed"),
+];
+
+/// A `[symlinks]` entry proposed by `dotf schema generate`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProposedLink {
+    /// Where the file would live in the repository
+    pub source: String,
+    /// Where it is linked to, `~/`-relative when under the home directory
+    pub target: String,
+}
+
 pub struct SchemaService;
 
 impl Default for SchemaService {
@@ -35,6 +74,115 @@ impl SchemaService {
         Ok(())
     }
 
+    /// Propose links for `paths`, or for the common dotfiles that exist in `home` when
+    /// no paths are given. Paths must be absolute.
+    pub fn propose_links(&self, home: &Path, paths: &[String]) -> DotfResult<Vec<ProposedLink>> {
+        let mut proposals = Vec::new();
+        if paths.is_empty() {
+            for (relative, source) in COMMON_DOTFILES {
+                // A link into the repository is already managed by something
+                let path = home.join(relative);
+                if path.exists() && !path.is_symlink() {
+                    proposals.push(ProposedLink {
+                        source: source.to_string(),
+                        target: format!("~/{}", relative),
+                    });
+                }
+            }
+            return Ok(proposals);
+        }
+
+        for path in paths {
+            let path = Path::new(path);
+            if !path.exists() {
+                return Err(DotfError::Operation(format!(
+                    "{} does not exist",
+                    path.display()
+                )));
+            }
+            let proposal = match path.strip_prefix(home) {
+                Ok(relative) if !relative.as_os_str().is_empty() => {
+                    let relative = relative.to_string_lossy();
+                    ProposedLink {
+                        source: repository_path(&relative),
+                        target: format!("~/{}", relative),
+                    }
+                }
+                _ => ProposedLink {
+                    source: repository_path(
+                        &path.file_name().unwrap_or_default().to_string_lossy(),
+                    ),
+                    target: path.to_string_lossy().to_string(),
+                },
+            };
+            if !proposals
+                .iter()
+                .any(|p: &ProposedLink| p.target == proposal.target)
+            {
+                proposals.push(proposal);
+            }
+        }
+        Ok(proposals)
+    }
+
+    /// Ask which proposals to keep
+    pub async fn choose_links<P: Prompt>(
+        &self,
+        proposals: Vec<ProposedLink>,
+        prompt: &P,
+    ) -> DotfResult<Vec<ProposedLink>> {
+        let mut chosen = Vec::new();
+        for proposal in proposals {
+            let question = format!(
+                "Link {} from {} in the repository?",
+                proposal.target, proposal.source
+            );
+            if prompt.confirm(&question).await? {
+                chosen.push(proposal);
+            }
+        }
+        Ok(chosen)
+    }
+
+    /// Write a commented dotf.toml with `links` in its `[symlinks]` table
+    pub fn write_generated(
+        &self,
+        config_path: &str,
+        links: &[ProposedLink],
+        force: bool,
+    ) -> DotfResult<()> {
+        if Path::new(config_path).exists() && !force {
+            return Err(DotfError::Operation(format!(
+                "{} already exists; use --force to replace it",
+                config_path
+            )));
+        }
+        fs::write(config_path, self.render_generated(links)).map_err(DotfError::Io)
+    }
+
+    fn render_generated(&self, links: &[ProposedLink]) -> String {
+        let mut content = String::from(
+            "# Generated by `dotf schema generate`. Each [symlinks] entry links a file in this\n\
+             # repository (left) to where it lives (right); copy the files into the repository\n\
+             # at those paths. `dotf schema docs` describes every other section.\n\n\
+             [symlinks]\n",
+        );
+        for link in links {
+            content.push_str(&format!(
+                "{} = {}\n",
+                toml::Value::String(link.source.clone()),
+                toml::Value::String(link.target.clone())
+            ));
+        }
+
+        // The rest of the template stays as commented examples
+        let template = self.generate_template();
+        if let Some(rest) = template.find("\n[scripts.deps]") {
+            content.push_str(&template[rest..]);
+        }
+        content
+    }
+
     /// Generate the default template content
     fn generate_template(&self) -> String {
         r#"[symlinks]
@@ -54,14 +202,142 @@ impl SchemaService {
 # Custom installation scripts
 # setup-vim = "scripts/setup-vim-plugins.sh"
 # install-fonts = "scripts/install-fonts.sh"
+
+# Resource limits for a script, over the [scripts] limits in ~/.dotf/settings.toml
+# [scripts.limits."scripts/install-deps-linux.sh"]
+# nice = 10                         # run at lower CPU priority
+# max_runtime_secs = 1800           # stop the script after 30 minutes
+# max_output_bytes = 1048576        # keep at most 1 MiB of stdout and of stderr
+
+# When scripts run again: "always" (default), "once" per machine, or "on-change"
+# [scripts.run]
+# "scripts/ssh-keygen.sh" = "once"
+
+# Environment variables and working directory for a script
+# [scripts.env."scripts/deploy.sh"]
+# DEPLOY_TARGET = "staging"
+# [scripts.working_dir]
+# "scripts/deploy.sh" = "site"      # relative to the repository
+
+# Symlinks added on one platform only: [platform.macos.symlinks], [platform.linux.symlinks]
+# or [platform.windows.symlinks]
+# [platform.windows.symlinks]
+# "windows/profile.ps1" = "~/Documents/PowerShell/Microsoft.PowerShell_profile.ps1"
+
+# Symlinks and scripts for one kind of machine, used after `dotf profile use work`
+# [profile.work.symlinks]
+# "work/.gitconfig" = "~/.gitconfig"
+# [profile.work.scripts.custom]
+# vpn = "work/vpn.sh"
+
+# Binaries downloaded from GitHub/GitLab releases by `dotf install tools`
+# [tools.rg]
+# repo = "BurntSushi/ripgrep"
+# version = "14.1.0"                # or "latest"
+# bin = "rg"
+# install_path = "~/.local/bin"
+# checksums = "SHA256SUMS"          # or pin per platform:
+# sha256 = { linux-x86_64 = "..." }
+
+# macOS `defaults write` values applied by `dotf install config`
+# [macos.defaults."com.apple.dock"]
+# autohide = true
+# tilesize = 48
+
+# User crontab entries kept in a dotf-managed block by `dotf install cron`
+# [cron]
+# backup = "0 3 * * * ~/bin/backup.sh"
+# brew-update = "@weekly brew update"
+
+# Scripts run around `dotf install` and `dotf sync` (DOTF_HOOK and DOTF_REPO are set);
+# a failing hook stops the command unless on_failure = "continue"
+# [hooks]
+# pre_install = "hooks/check.sh"
+# post_sync = { script = "hooks/caches.sh", timeout_secs = 60, on_failure = "continue" }
+
+# Scripts run around the links of the [symlinks] entries with group = "karabiner"
+# [hooks.groups.karabiner]
+# pre = "scripts/karabiner-quit.sh"
+# post = "scripts/karabiner-start.sh"
+
+# ~/.ssh content handled by `dotf install ssh`: config fragments merged into a managed
+# block, known_hosts entries appended, private keys copied with mode 600 (never linked)
+# [ssh]
+# config = ["ssh/config.d/work"]
+# known_hosts = ["ssh/known_hosts"]
+# keys = { "ssh/id_deploy" = "~/.ssh/id_deploy" }
+
+# Files kept encrypted in the repository and decrypted with mode 600 by
+# `dotf install config` (never linked); add one with `dotf secrets encrypt ~/.netrc`
+# [secrets]
+# backend = "age"                  # or "gpg"
+# recipients = ["age1..."]         # age public keys or gpg key IDs
+# identity = "~/.config/age/keys.txt"
+# [secrets.files]
+# "secrets/netrc.age" = "~/.netrc"
+
+# System packages installed by `dotf install packages`; managers missing on a
+# machine are skipped
+# [packages]
+# brew = ["ripgrep", "fzf"]
+# cask = ["iterm2"]                # macOS only
+# mas = { Xcode = 497799835 }      # Mac App Store name = app id
+# flatpak = ["org.mozilla.firefox", { id = "org.gimp.GIMP", remote = "flathub-beta" }]
+# snap = ["spotify"]
+# [packages.flatpak_remotes]       # remotes other than flathub
+# flathub-beta = "https://flathub.org/beta-repo/flathub-beta.flatpakrepo"
+
+# Other package managers, driven by shell commands; list their packages in
+# [packages] under the manager's name, e.g. pipx = ["black"]
+# [package_managers.custom.pipx]
+# check = "pipx --version"         # succeeds when the manager is usable
+# list = "pipx list --short"       # first word of each line is an installed package
+# install = "pipx install {package}"
+
+# Environment written to ~/.config/dotf/env.sh; source it from your shell rc
+# [env]
+# path = ["~/.local/bin"]
+# vars = { EDITOR = "nvim" }
+# [env.macos]
+# path = ["/opt/homebrew/bin"]
+
+# Values asked once per machine on install; change them with `dotf prompts edit`.
+# Scripts get them as DOTF_PROMPT_<NAME>; [env] values can use {{ prompts.<name> }}
+# [prompts]
+# git_email = { message = "Git email", default = "" }
 "#
         .to_string()
     }
 }
 
+/// Repository path for a file at `relative` under the home directory: `.config/nvim`
+/// becomes `config/nvim` and `.tmux.conf` goes into `tmux/`
+fn repository_path(relative: &str) -> String {
+    if let Some((_, source)) = COMMON_DOTFILES.iter().find(|(path, _)| *path == relative) {
+        return source.to_string();
+    }
+    if let Some(rest) = relative.strip_prefix(".config/") {
+        return format!("config/{}", rest);
+    }
+    if relative.contains('/') {
+        return relative.trim_start_matches('.').to_string();
+    }
+    let stem = relative
+        .trim_start_matches('.')
+        .split(['.', '_'])
+        .next()
+        .unwrap_or_default();
+    if stem.is_empty() || stem == relative {
+        relative.to_string()
+    } else {
+        format!("{}/{}", stem, relative)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::traits::prompt::tests::MockPrompt;
     use std::fs;
     use tempfile::TempDir;
 
@@ -114,6 +390,62 @@ mod tests {
             .contains("dotf.toml already exists"));
     }
 
+    #[tokio::test]
+    async fn test_generate_links() {
+        let home = TempDir::new().unwrap();
+        fs::write(home.path().join(".zshrc"), "").unwrap();
+        fs::write(home.path().join(".tmux.conf"), "").unwrap();
+        fs::create_dir_all(home.path().join(".config/nvim")).unwrap();
+        std::os::unix::fs::symlink(home.path().join(".zshrc"), home.path().join(".bashrc"))
+            .unwrap();
+
+        let service = SchemaService::new();
+        let proposals = service.propose_links(home.path(), &[]).unwrap();
+        let link = |source: &str, target: &str| ProposedLink {
+            source: source.to_string(),
+            target: target.to_string(),
+        };
+        assert_eq!(
+            proposals,
+            vec![
+                link("zsh/.zshrc", "~/.zshrc"),
+                link("tmux/.tmux.conf", "~/.tmux.conf"),
+                link("config/nvim", "~/.config/nvim"),
+            ]
+        );
+
+        let given = home
+            .path()
+            .join(".config/nvim")
+            .to_string_lossy()
+            .to_string();
+        assert_eq!(
+            service.propose_links(home.path(), &[given]).unwrap(),
+            vec![link("config/nvim", "~/.config/nvim")]
+        );
+        assert!(service
+            .propose_links(home.path(), &["/nonexistent/.npmrc".to_string()])
+            .is_err());
+
+        let prompt = MockPrompt::new();
+        for answer in [true, false, true] {
+            prompt.set_confirm_response(answer);
+        }
+        let chosen = service.choose_links(proposals, &prompt).await.unwrap();
+
+        let config_path = home.path().join("dotf.toml").to_string_lossy().to_string();
+        service
+            .write_generated(&config_path, &chosen, false)
+            .unwrap();
+        assert!(service
+            .write_generated(&config_path, &chosen, false)
+            .is_err());
+        let config: crate::core::config::DotfConfig =
+            toml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+        assert_eq!(config.symlinks.len(), 2);
+        assert_eq!(config.symlinks["config/nvim"], "~/.config/nvim");
+    }
+
     #[test]
     fn test_generate_template() {
         let service = SchemaService::new();
@@ -1,8 +1,19 @@
-use crate::core::config::DotfConfig;
+use crate::core::config::{dotf_config::HookEntry, DotfConfig};
+use crate::core::{
+    bootstrap::plan_steps,
+    cron::validate_entry,
+    packages::{
+        custom::{placeholders, PACKAGE_PLACEHOLDER},
+        BUILT_IN_KINDS,
+    },
+    prompts::is_valid_name,
+    scripts::HookEvent,
+    tools::asset::sha256_hex,
+};
 use crate::error::{DotfError, DotfResult};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Component, Path};
 
 #[derive(Debug, Clone)]
 pub struct ValidationError {
@@ -99,6 +110,33 @@ impl SchemaValidator {
         // 4. Validate scripts
         self.validate_scripts(&config, &mut errors).await;
 
+        // 5. Validate release tools
+        self.validate_tools(&config, &mut errors);
+
+        // 6. Validate plugin manager bootstrap
+        self.validate_bootstrap(&config, &mut errors);
+
+        // 7. Validate cron entries
+        self.validate_cron(&config, &mut errors);
+
+        // 8. Validate environment variables
+        self.validate_env(&config, &mut errors);
+
+        // 9. Validate install-time prompts
+        self.validate_prompts(&config, &mut errors);
+
+        // 10. Validate managed ~/.ssh content
+        self.validate_ssh(&config, &mut errors);
+
+        // 11. Validate custom package managers
+        self.validate_package_managers(&config, &mut errors);
+
+        // 12. Validate encrypted files
+        self.validate_secrets(&config, &mut errors);
+
+        // 13. Validate install and sync hooks
+        self.validate_hooks(&config, &mut errors);
+
         Ok(if errors.is_empty() {
             ValidationResult::success()
         } else {
@@ -118,7 +156,8 @@ impl SchemaValidator {
     }
 
     async fn validate_symlinks(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
-        let mut target_paths = HashSet::new();
+        // Priorities seen per target; entries for one target must differ in priority
+        let mut target_priorities: HashMap<&String, HashSet<i32>> = HashMap::new();
 
         for (source_path, target_path) in &config.symlinks {
             // Check for empty paths
@@ -146,18 +185,27 @@ impl SchemaValidator {
                 continue;
             }
 
-            // Check for duplicate target paths
-            if target_paths.contains(target_path) {
+            // Check for duplicate target paths that priority does not decide between
+            if !target_priorities
+                .entry(target_path)
+                .or_default()
+                .insert(config.symlinks.priority(source_path))
+            {
                 errors.push(ValidationError {
                     line: None,
                     section: "symlinks".to_string(),
-                    message: format!("Duplicate target path: \"{}\"", target_path),
+                    message: format!(
+                        "Duplicate target path: \"{}\" (give the entries different priorities to choose one)",
+                        target_path
+                    ),
                 });
             }
-            target_paths.insert(target_path.clone());
 
-            // Check if source file/directory exists
-            if !source_path.starts_with('/') && !Path::new(source_path).exists() {
+            // Check if source file/directory exists; optional sources may be absent
+            if !source_path.starts_with('/')
+                && !config.symlinks.is_optional(source_path)
+                && !Path::new(source_path).exists()
+            {
                 errors.push(ValidationError {
                     line: None,
                     section: "symlinks".to_string(),
@@ -176,6 +224,30 @@ impl SchemaValidator {
                     ),
                 });
             }
+
+            if let Some(pin) = config.symlinks.pinned_sha256(source_path) {
+                if pin.len() != 64 || !pin.chars().all(|c| c.is_ascii_hexdigit()) {
+                    errors.push(ValidationError {
+                        line: None,
+                        section: "symlinks".to_string(),
+                        message: format!(
+                            "pin_sha256 for \"{}\" is not a SHA-256 hex digest",
+                            source_path
+                        ),
+                    });
+                } else if let Ok(content) = fs::read(source_path) {
+                    if !sha256_hex(&content).eq_ignore_ascii_case(pin) {
+                        errors.push(ValidationError {
+                            line: None,
+                            section: "symlinks".to_string(),
+                            message: format!(
+                                "Source \"{}\" does not match its pin_sha256",
+                                source_path
+                            ),
+                        });
+                    }
+                }
+            }
         }
     }
 
@@ -213,6 +285,264 @@ impl SchemaValidator {
         }
     }
 
+    fn validate_tools(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        let mut names: Vec<&String> = config.tools.keys().collect();
+        names.sort();
+
+        for name in names {
+            let tool = &config.tools[name];
+            let section = format!("tools.{}", name);
+
+            let parts: Vec<&str> = tool.repo.split('/').collect();
+            if parts.len() < 2 || parts.iter().any(|part| part.trim().is_empty()) {
+                errors.push(ValidationError {
+                    line: None,
+                    section: section.clone(),
+                    message: format!("repo must look like \"owner/name\": \"{}\"", tool.repo),
+                });
+            }
+
+            if tool.version.trim().is_empty() {
+                errors.push(ValidationError {
+                    line: None,
+                    section: section.clone(),
+                    message: "version cannot be empty (use \"latest\" to track new releases)"
+                        .to_string(),
+                });
+            }
+
+            for (platform, digest) in &tool.sha256 {
+                if digest.len() != 64 || !digest.chars().all(|c| c.is_ascii_hexdigit()) {
+                    errors.push(ValidationError {
+                        line: None,
+                        section: section.clone(),
+                        message: format!("sha256 for '{}' is not a SHA-256 hex digest", platform),
+                    });
+                }
+            }
+        }
+    }
+
+    fn validate_bootstrap(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        // Paths don't matter here; planning only fails on unsupported managers
+        if let Err(e) = plan_steps(&config.bootstrap, Path::new("~"), Path::new("~")) {
+            errors.push(ValidationError {
+                line: None,
+                section: "bootstrap".to_string(),
+                message: e.to_string(),
+            });
+        }
+
+        if config.bootstrap.timeout == Some(0) {
+            errors.push(ValidationError {
+                line: None,
+                section: "bootstrap".to_string(),
+                message: "timeout must be at least 1 second".to_string(),
+            });
+        }
+    }
+
+    fn validate_cron(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        for (name, line) in &config.cron {
+            if let Err(message) = validate_entry(line) {
+                errors.push(ValidationError {
+                    line: None,
+                    section: format!("cron.{}", name),
+                    message,
+                });
+            }
+        }
+    }
+
+    fn validate_ssh(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        if !config.ssh.config.is_empty()
+            && config
+                .symlinks
+                .values()
+                .any(|target| target == "~/.ssh/config")
+        {
+            errors.push(ValidationError {
+                line: None,
+                section: "ssh.config".to_string(),
+                message: "~/.ssh/config is also linked in [symlinks]; fragments are merged into a real file".to_string(),
+            });
+        }
+        for (source, target) in &config.ssh.keys {
+            if !target.starts_with("~/") && !target.starts_with('/') {
+                errors.push(ValidationError {
+                    line: None,
+                    section: format!("ssh.keys.\"{}\"", source),
+                    message: format!("target '{}' must start with ~/ or /", target),
+                });
+            }
+        }
+    }
+
+    fn validate_secrets(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        for (source, target) in &config.secrets.files {
+            let section = format!("secrets.files.\"{}\"", source);
+            if !target.starts_with("~/") && !target.starts_with('/') {
+                errors.push(ValidationError {
+                    line: None,
+                    section: section.clone(),
+                    message: format!("target '{}' must start with ~/ or /", target),
+                });
+            }
+            // A link would put the ciphertext at the target, or the plaintext in the repository
+            let linked = config.symlinks.iter().any(|(link_source, link_target)| {
+                link_target == target
+                    || source == link_source
+                    || source.starts_with(&format!("{}/", link_source.trim_end_matches('/')))
+            });
+            if linked {
+                errors.push(ValidationError {
+                    line: None,
+                    section,
+                    message: "also linked in [symlinks]; secrets are decrypted, never linked"
+                        .to_string(),
+                });
+            }
+        }
+    }
+
+    fn validate_hooks(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        let mut hooks: Vec<(String, &HookEntry)> = HookEvent::ALL
+            .into_iter()
+            .filter_map(|event| {
+                let hook = config.hooks.get(event)?;
+                Some((format!("hooks.{}", event.name()), hook))
+            })
+            .collect();
+        for (group, group_hooks) in &config.hooks.groups {
+            for (name, hook) in [("pre", &group_hooks.pre), ("post", &group_hooks.post)] {
+                if let Some(hook) = hook {
+                    hooks.push((format!("hooks.groups.{}.{}", group, name), hook));
+                }
+            }
+        }
+
+        for (section, hook) in hooks {
+            let script = hook.script();
+            let message = if script.trim().is_empty() {
+                "script is empty".to_string()
+            } else if script.starts_with('/')
+                || script.starts_with('~')
+                || Path::new(script)
+                    .components()
+                    .any(|c| c == Component::ParentDir)
+            {
+                format!("script '{}' must be a path inside the repository", script)
+            } else if hook.timeout_secs() == Some(0) {
+                "timeout_secs must be greater than 0".to_string()
+            } else {
+                continue;
+            };
+            errors.push(ValidationError {
+                line: None,
+                section,
+                message,
+            });
+        }
+    }
+
+    fn validate_package_managers(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        let managers = &config.package_managers.custom;
+        for (name, manager) in managers {
+            let section = format!("package_managers.custom.{}", name);
+            let mut problems = Vec::new();
+            if BUILT_IN_KINDS.contains(&name.as_str()) {
+                problems.push(format!("'{}' is a built-in package kind", name));
+            }
+            for (field, command) in [("check", &manager.check), ("list", &manager.list)] {
+                if command.trim().is_empty() {
+                    problems.push(format!("{} command is empty", field));
+                }
+            }
+            if !manager.install.contains(PACKAGE_PLACEHOLDER) {
+                problems.push(format!(
+                    "install command must contain {}",
+                    PACKAGE_PLACEHOLDER
+                ));
+            }
+            for placeholder in placeholders(&manager.install) {
+                if placeholder != PACKAGE_PLACEHOLDER {
+                    problems.push(format!(
+                        "install command uses unknown placeholder {}",
+                        placeholder
+                    ));
+                }
+            }
+            errors.extend(problems.into_iter().map(|message| ValidationError {
+                line: None,
+                section: section.clone(),
+                message,
+            }));
+        }
+
+        for kind in config.packages.custom.keys() {
+            if !managers.contains_key(kind) {
+                errors.push(ValidationError {
+                    line: None,
+                    section: "packages".to_string(),
+                    message: format!(
+                        "'{}' is not a package kind; define it in [package_managers.custom.{}]",
+                        kind, kind
+                    ),
+                });
+            }
+        }
+    }
+
+    fn validate_prompts(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        for (name, prompt) in &config.prompts {
+            let message = if !is_valid_name(name) {
+                format!(
+                    "'{}' must only use letters, digits, '_' and '-' to be usable as a placeholder",
+                    name
+                )
+            } else if prompt.message.trim().is_empty() {
+                format!("'{}' needs a message to show when asking", name)
+            } else {
+                continue;
+            };
+            errors.push(ValidationError {
+                line: None,
+                section: "prompts".to_string(),
+                message,
+            });
+        }
+    }
+
+    fn validate_env(&self, config: &DotfConfig, errors: &mut Vec<ValidationError>) {
+        let sections = [
+            ("env", Some(&config.env.common)),
+            ("env.macos", config.env.macos.as_ref()),
+            ("env.linux", config.env.linux.as_ref()),
+        ];
+
+        for (section, env) in sections {
+            let Some(env) = env else { continue };
+            for name in env.vars.keys() {
+                let valid = name
+                    .chars()
+                    .next()
+                    .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+                if !valid || name == "PATH" {
+                    errors.push(ValidationError {
+                        line: None,
+                        section: section.to_string(),
+                        message: if name == "PATH" {
+                            "set PATH entries with `path = [...]` instead of vars".to_string()
+                        } else {
+                            format!("'{}' is not a valid environment variable name", name)
+                        },
+                    });
+                }
+            }
+        }
+    }
+
     /// Show validation results with proper formatting
     pub fn format_result(&self, result: &ValidationResult, quiet: bool) -> String {
         let mut output = Vec::new();
@@ -321,6 +651,216 @@ setup = "{}"
         assert!(result.errors.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_validate_tools() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[symlinks]
+"/etc/hosts" = "~/.hosts"
+
+[tools.rg]
+repo = "BurntSushi/ripgrep"
+version = "14.1.0"
+
+[tools.fd]
+repo = "fd"
+sha256 = { linux-x86_64 = "abc" }
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let tool_errors: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|e| e.section.starts_with("tools."))
+            .collect();
+
+        assert_eq!(tool_errors.len(), 2);
+        assert!(tool_errors.iter().all(|e| e.section == "tools.fd"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_bootstrap() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[symlinks]
+
+[bootstrap]
+nvim = "lazy"
+tmux = "vim-plug"
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let bootstrap_errors: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|e| e.section == "bootstrap")
+            .collect();
+
+        assert_eq!(bootstrap_errors.len(), 1);
+        assert!(bootstrap_errors[0].message.contains("tmux"));
+    }
+
+    #[tokio::test]
+    async fn test_validate_prompts() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[symlinks]
+
+[prompts]
+git_email = { message = "Git email" }
+"git email" = { message = "Git email" }
+work = { message = "" }
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let prompt_errors: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|e| e.section == "prompts")
+            .collect();
+
+        assert_eq!(prompt_errors.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_ssh() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[symlinks]
+"ssh/config" = "~/.ssh/config"
+
+[ssh]
+config = ["ssh/work"]
+keys = { "ssh/id_deploy" = ".ssh/id_deploy" }
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let ssh_errors: Vec<_> = result
+            .errors
+            .iter()
+            .filter(|e| e.section.starts_with("ssh."))
+            .collect();
+
+        assert_eq!(ssh_errors.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_validate_secrets() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[symlinks]
+"secrets" = "~/secrets"
+"git/.gitconfig" = "~/.gitconfig"
+
+[secrets.files]
+"secrets/netrc.age" = "~/.netrc"
+"git/token.age" = ".config/gh/token"
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let sections: Vec<&str> = result
+            .errors
+            .iter()
+            .filter(|e| e.section.starts_with("secrets."))
+            .map(|e| e.section.as_str())
+            .collect();
+
+        assert_eq!(
+            sections,
+            vec![
+                "secrets.files.\"git/token.age\"",
+                "secrets.files.\"secrets/netrc.age\""
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_hooks() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[hooks]
+pre_install = "hooks/check.sh"
+post_install = "../outside.sh"
+pre_sync = { script = "~/bin/pre-sync" }
+post_sync = { script = "hooks/caches.sh", timeout_secs = 0, on_failure = "continue" }
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let sections: Vec<&str> = result
+            .errors
+            .iter()
+            .filter(|e| e.section.starts_with("hooks."))
+            .map(|e| e.section.as_str())
+            .collect();
+
+        assert_eq!(
+            sections,
+            vec!["hooks.post_install", "hooks.pre_sync", "hooks.post_sync"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_package_managers() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[packages]
+pipx = ["black"]
+cargo = ["ripgrep"]
+
+[package_managers.custom.pipx]
+check = "pipx --version"
+list = "pipx list --short"
+install = "pipx install {package}"
+
+[package_managers.custom.nix]
+check = "nix --version"
+list = ""
+install = "nix profile install nixpkgs#{name}"
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        let messages: Vec<(&str, &str)> = result
+            .errors
+            .iter()
+            .filter(|e| e.section.starts_with("package"))
+            .map(|e| (e.section.as_str(), e.message.as_str()))
+            .collect();
+
+        assert_eq!(
+            messages,
+            vec![
+                ("package_managers.custom.nix", "list command is empty"),
+                (
+                    "package_managers.custom.nix",
+                    "install command must contain {package}"
+                ),
+                (
+                    "package_managers.custom.nix",
+                    "install command uses unknown placeholder {name}"
+                ),
+                (
+                    "packages",
+                    "'cargo' is not a package kind; define it in [package_managers.custom.cargo]"
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_pin_sha256() {
+        let validator = SchemaValidator::new();
+        let content = r#"
+[symlinks]
+"/etc/hostname" = { target = "~/.hostname", pin_sha256 = "not-a-digest" }
+"#;
+
+        let result = validator.validate_content(content).await.unwrap();
+        assert!(result
+            .errors
+            .iter()
+            .any(|e| e.message.contains("pin_sha256") && e.message.contains("hex digest")));
+    }
+
     #[tokio::test]
     async fn test_validate_invalid_toml() {
         let validator = SchemaValidator::new();
@@ -389,6 +929,21 @@ setup = "{}"
             .errors
             .iter()
             .any(|e| e.message.contains("Duplicate target path")));
+
+        let content = format!(
+            r#"
+[symlinks]
+"{}" = "~/.config"
+"{}" = {{ target = "~/.config", priority = 10 }}
+"#,
+            file1_path.to_string_lossy(),
+            file2_path.to_string_lossy()
+        );
+        let result = validator.validate_content(&content).await.unwrap();
+        assert!(!result
+            .errors
+            .iter()
+            .any(|e| e.message.contains("Duplicate target path")));
     }
 
     #[test]
@@ -0,0 +1,512 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::core::{
+    config::{loader, DotfConfig, Settings},
+    secrets::{secret_state, SecretState, DEFAULT_AGE_IDENTITY, FILE_MODE},
+    state::StateStore,
+    tools::asset::sha256_hex,
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::{cipher::Cipher, filesystem::FileSystem};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretStatus {
+    /// Encrypted file, relative to the repository
+    pub source: String,
+    pub target_path: String,
+    pub state: SecretState,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecretsStatusInfo {
+    pub files: Vec<SecretStatus>,
+}
+
+impl SecretsStatusInfo {
+    pub fn is_ok(&self) -> bool {
+        self.files
+            .iter()
+            .all(|file| file.state == SecretState::InSync)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SecretsDecryptOutcome {
+    pub decrypted: Vec<String>,
+    /// Targets left alone because they hold changes made on this machine
+    pub skipped: Vec<SecretStatus>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SecretsEncryptResult {
+    pub source_path: String,
+    /// Key in `[secrets.files]`, relative to the repository root
+    pub config_key: String,
+    /// The file was not listed in `[secrets.files]` before
+    pub added: bool,
+}
+
+/// Keeps `[secrets]` files encrypted in the repository and decrypted at their targets.
+/// They are never linked, so the plaintext stays out of the repository.
+pub struct SecretsService<F, C> {
+    filesystem: F,
+    cipher: C,
+    state: StateStore<F>,
+    home: PathBuf,
+}
+
+impl<F: FileSystem + Clone, C: Cipher> SecretsService<F, C> {
+    pub fn new(filesystem: F, cipher: C) -> Self {
+        Self {
+            state: StateStore::new(filesystem.clone()),
+            filesystem,
+            cipher,
+            home: dirs::home_dir().unwrap_or_default(),
+        }
+    }
+
+    /// Compare each target with its encrypted file; `None` when `[secrets]` lists none
+    pub async fn status(&self) -> DotfResult<Option<SecretsStatusInfo>> {
+        let config = self.load_config().await?;
+        if config.secrets.is_empty() {
+            return Ok(None);
+        }
+
+        let repo_path = self.repo_path().await?;
+        let state = self.state.load().await?;
+        let mut files = Vec::new();
+        for (source, target) in &config.secrets.files {
+            let target_path = self.expand(target);
+            let source_sha256 = self
+                .hash_optional(&format!("{}/{}", repo_path, source))
+                .await?;
+            let target_sha256 = self.hash_optional(&target_path).await?;
+            files.push(SecretStatus {
+                source: source.clone(),
+                state: secret_state(
+                    state.secrets.get(&target_path),
+                    source_sha256.as_deref(),
+                    target_sha256.as_deref(),
+                ),
+                target_path,
+            });
+        }
+        Ok(Some(SecretsStatusInfo { files }))
+    }
+
+    /// Decrypt every secret whose target is missing or outdated, or only the one at
+    /// `target`. Targets edited on this machine are skipped unless `force` is set.
+    pub async fn decrypt(
+        &self,
+        target: Option<&str>,
+        force: bool,
+    ) -> DotfResult<SecretsDecryptOutcome> {
+        let mut outcome = SecretsDecryptOutcome::default();
+        let Some(status) = self.status().await? else {
+            return Ok(outcome);
+        };
+        let target = target.map(|target| self.expand(target));
+        let files: Vec<SecretStatus> = status
+            .files
+            .into_iter()
+            .filter(|file| target.as_ref().is_none_or(|t| *t == file.target_path))
+            .collect();
+        if let (Some(target), true) = (&target, files.is_empty()) {
+            return Err(DotfError::Operation(format!(
+                "'{}' is not a target in [secrets.files]",
+                target
+            )));
+        }
+
+        let config = self.load_config().await?;
+        let repo_path = self.repo_path().await?;
+        for file in files {
+            match file.state {
+                SecretState::InSync => continue,
+                SecretState::SourceMissing => {
+                    return Err(DotfError::Config(format!(
+                        "[secrets] source not found in repository: {}",
+                        file.source
+                    )))
+                }
+                state if state.has_local_changes() && !force => {
+                    outcome.skipped.push(file);
+                    continue;
+                }
+                _ => {}
+            }
+
+            let ciphertext = self
+                .filesystem
+                .read_to_string(&format!("{}/{}", repo_path, file.source))
+                .await?;
+            let identity = config
+                .secrets
+                .identity
+                .as_deref()
+                .unwrap_or(DEFAULT_AGE_IDENTITY);
+            let plaintext = self
+                .cipher
+                .decrypt(
+                    config.secrets.backend,
+                    Some(&self.expand(identity)),
+                    &ciphertext,
+                )
+                .await
+                .map_err(|e| {
+                    DotfError::Operation(format!("Could not decrypt {}: {}", file.source, e))
+                })?;
+
+            self.write_target(&file.target_path, &plaintext).await?;
+            self.state
+                .record_secret(
+                    &file.target_path,
+                    &sha256_hex(ciphertext.as_bytes()),
+                    &sha256_hex(plaintext.as_bytes()),
+                )
+                .await?;
+            outcome.decrypted.push(file.target_path);
+        }
+        Ok(outcome)
+    }
+
+    /// Encrypt the file at `target_path` into the repository: over its source when it is
+    /// already listed in `[secrets.files]`, otherwise to `source` (by default
+    /// `secrets/<path under home>.<ext>`) with a new entry written to dotf.toml
+    pub async fn encrypt(
+        &self,
+        target_path: &str,
+        source: Option<&str>,
+    ) -> DotfResult<SecretsEncryptResult> {
+        let config = self.load_config().await?;
+        if config.secrets.recipients.is_empty() {
+            return Err(DotfError::Config(
+                "[secrets] recipients is empty; add the age public keys or gpg key IDs to encrypt to"
+                    .to_string(),
+            ));
+        }
+        if !self.filesystem.exists(target_path).await?
+            || self.filesystem.is_dir(target_path).await?
+        {
+            return Err(DotfError::Operation(format!(
+                "'{}' is not a file",
+                target_path
+            )));
+        }
+        if self
+            .filesystem
+            .is_symlink(target_path)
+            .await
+            .unwrap_or(false)
+        {
+            return Err(DotfError::Operation(format!(
+                "'{}' is a symlink; only regular files can be encrypted",
+                target_path
+            )));
+        }
+
+        let repo_path = self.repo_path().await?;
+        let listed = config
+            .secrets
+            .files
+            .iter()
+            .find(|(_, target)| self.expand(target) == target_path)
+            .map(|(source, _)| source.clone());
+        let added = listed.is_none();
+        let config_key = match (listed, source) {
+            (Some(listed), _) => listed,
+            (None, Some(source)) => source
+                .trim_start_matches("./")
+                .trim_matches('/')
+                .to_string(),
+            (None, None) => format!(
+                "secrets/{}.{}",
+                self.relative_to_home(target_path)?,
+                config.secrets.backend.extension()
+            ),
+        };
+        if config_key.is_empty() || config_key.split('/').any(|part| part == "..") {
+            return Err(DotfError::Operation(format!(
+                "'{}' is not a path inside the repository",
+                config_key
+            )));
+        }
+        let source_path = format!("{}/{}", repo_path, config_key);
+        if added && self.filesystem.exists(&source_path).await? {
+            return Err(DotfError::Operation(format!(
+                "'{}' already exists in the repository; choose another path with --source",
+                config_key
+            )));
+        }
+
+        let plaintext = self.filesystem.read_to_string(target_path).await?;
+        let ciphertext = self
+            .cipher
+            .encrypt(
+                config.secrets.backend,
+                &config.secrets.recipients,
+                &plaintext,
+            )
+            .await?;
+
+        let config_path = format!("{}/dotf.toml", repo_path);
+        if added {
+            let content = self.filesystem.read_to_string(&config_path).await?;
+            let updated =
+                add_secret_entry(&content, &config_key, &self.display_target(target_path))?;
+            self.write_source(&source_path, &ciphertext).await?;
+            self.filesystem.write(&config_path, &updated).await?;
+        } else {
+            self.write_source(&source_path, &ciphertext).await?;
+        }
+        self.state
+            .record_secret(
+                target_path,
+                &sha256_hex(ciphertext.as_bytes()),
+                &sha256_hex(plaintext.as_bytes()),
+            )
+            .await?;
+
+        Ok(SecretsEncryptResult {
+            source_path,
+            config_key,
+            added,
+        })
+    }
+
+    async fn write_source(&self, path: &str, content: &str) -> DotfResult<()> {
+        if let Some(parent) = Path::new(path).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.filesystem.write(path, content).await
+    }
+
+    /// Write the plaintext readable by the user only, replacing a symlink left at the target
+    async fn write_target(&self, path: &str, content: &str) -> DotfResult<()> {
+        if self.filesystem.is_symlink(path).await.unwrap_or(false) {
+            self.filesystem.remove_file(path).await?;
+        }
+        if let Some(parent) = Path::new(path).parent() {
+            self.filesystem
+                .create_dir_all(&parent.to_string_lossy())
+                .await?;
+        }
+        self.filesystem.write(path, content).await?;
+        self.filesystem.set_mode(path, FILE_MODE).await
+    }
+
+    async fn hash_optional(&self, path: &str) -> DotfResult<Option<String>> {
+        if !self.filesystem.exists(path).await? || self.filesystem.is_dir(path).await? {
+            return Ok(None);
+        }
+        let content = self.filesystem.read_to_string(path).await?;
+        Ok(Some(sha256_hex(content.as_bytes())))
+    }
+
+    /// `~/.config/foo/token` becomes `config/foo/token`
+    fn relative_to_home(&self, target_path: &str) -> DotfResult<String> {
+        let relative = Path::new(target_path)
+            .strip_prefix(&self.home)
+            .map_err(|_| {
+                DotfError::Operation(format!(
+                    "'{}' is outside the home directory; choose its repository path with --source",
+                    target_path
+                ))
+            })?;
+        Ok(relative
+            .to_string_lossy()
+            .trim_start_matches('.')
+            .to_string())
+    }
+
+    fn display_target(&self, target_path: &str) -> String {
+        match Path::new(target_path).strip_prefix(&self.home) {
+            Ok(relative) => format!("~/{}", relative.to_string_lossy()),
+            Err(_) => target_path.to_string(),
+        }
+    }
+
+    fn expand(&self, target: &str) -> String {
+        match target.strip_prefix("~/") {
+            Some(rest) => self.home.join(rest).to_string_lossy().to_string(),
+            None => target.to_string(),
+        }
+    }
+
+    async fn repo_path(&self) -> DotfResult<String> {
+        let settings = self.load_settings().await?;
+        Ok(settings
+            .repository
+            .local
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path()))
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        loader::read_config(&self.filesystem, &self.repo_path().await?).await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+/// Add `source = target` to `[secrets.files]`, keeping comments and layout of the rest
+fn add_secret_entry(content: &str, source: &str, target: &str) -> DotfResult<String> {
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
+    let secrets = document
+        .entry("secrets")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| DotfError::Config("[secrets] in dotf.toml is not a table".to_string()))?;
+    let files = secrets
+        .entry("files")
+        .or_insert_with(toml_edit::table)
+        .as_table_like_mut()
+        .ok_or_else(|| {
+            DotfError::Config("[secrets.files] in dotf.toml is not a table".to_string())
+        })?;
+    files.insert(source, toml_edit::value(target));
+    Ok(document.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{cipher::tests::MockCipher, filesystem::tests::MockFileSystem};
+
+    const CONFIG: &str = r#"[secrets]
+recipients = ["age1example"]
+
+[secrets.files]
+"secrets/netrc.age" = "~/.netrc"
+"#;
+
+    fn setup() -> (MockFileSystem, SecretsService<MockFileSystem, MockCipher>) {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(&format!("{}/dotf.toml", repo), CONFIG);
+        filesystem.add_file(
+            &format!("{}/secrets/netrc.age", repo),
+            &MockCipher::encrypted("machine example.com password hunter2\n"),
+        );
+
+        let mut service = SecretsService::new(filesystem.clone(), MockCipher::new());
+        service.home = PathBuf::from("/home/user");
+        (filesystem, service)
+    }
+
+    fn states(status: &SecretsStatusInfo) -> Vec<SecretState> {
+        status.files.iter().map(|file| file.state).collect()
+    }
+
+    #[tokio::test]
+    async fn test_decrypt_and_detect_drift() {
+        let (filesystem, service) = setup();
+        let status = service.status().await.unwrap().unwrap();
+        assert_eq!(states(&status), vec![SecretState::Missing]);
+
+        let outcome = service.decrypt(None, false).await.unwrap();
+        assert_eq!(outcome.decrypted, vec!["/home/user/.netrc"]);
+        assert_eq!(
+            filesystem
+                .read_to_string("/home/user/.netrc")
+                .await
+                .unwrap(),
+            "machine example.com password hunter2\n"
+        );
+        assert_eq!(
+            filesystem.mode("/home/user/.netrc").await.unwrap(),
+            Some(FILE_MODE)
+        );
+        assert!(filesystem.get_symlinks().is_empty());
+        assert!(service.status().await.unwrap().unwrap().is_ok());
+
+        // A local edit is reported and kept until forced
+        filesystem.add_file("/home/user/.netrc", "edited\n");
+        let status = service.status().await.unwrap().unwrap();
+        assert_eq!(states(&status), vec![SecretState::Modified]);
+        let outcome = service.decrypt(None, false).await.unwrap();
+        assert!(outcome.decrypted.is_empty());
+        assert_eq!(outcome.skipped.len(), 1);
+        service.decrypt(Some("~/.netrc"), true).await.unwrap();
+
+        // A new ciphertext in the repository makes the target outdated
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(
+            &format!("{}/secrets/netrc.age", repo),
+            &MockCipher::encrypted("machine example.com password rotated\n"),
+        );
+        let status = service.status().await.unwrap().unwrap();
+        assert_eq!(states(&status), vec![SecretState::Outdated]);
+        service.decrypt(None, false).await.unwrap();
+        assert_eq!(
+            filesystem
+                .read_to_string("/home/user/.netrc")
+                .await
+                .unwrap(),
+            "machine example.com password rotated\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_adds_entry() {
+        let (filesystem, service) = setup();
+        filesystem.add_file("/home/user/.config/gh/hosts.yml", "oauth_token: abc\n");
+
+        let result = service
+            .encrypt("/home/user/.config/gh/hosts.yml", None)
+            .await
+            .unwrap();
+        assert!(result.added);
+        assert_eq!(result.config_key, "secrets/config/gh/hosts.yml.age");
+        assert_eq!(
+            filesystem
+                .read_to_string(&result.source_path)
+                .await
+                .unwrap(),
+            MockCipher::encrypted("oauth_token: abc\n")
+        );
+        let repo = filesystem.dotf_repo_path();
+        assert!(filesystem
+            .read_to_string(&format!("{}/dotf.toml", repo))
+            .await
+            .unwrap()
+            .ends_with("\"secrets/config/gh/hosts.yml.age\" = \"~/.config/gh/hosts.yml\"\n"));
+
+        // The plaintext is what was encrypted, so both are in sync; a listed file is
+        // encrypted over its source
+        let status = service.status().await.unwrap().unwrap();
+        assert_eq!(
+            states(&status),
+            vec![SecretState::InSync, SecretState::Missing]
+        );
+        filesystem.add_file("/home/user/.config/gh/hosts.yml", "oauth_token: def\n");
+        let result = service
+            .encrypt("/home/user/.config/gh/hosts.yml", None)
+            .await
+            .unwrap();
+        assert!(!result.added);
+        assert_eq!(
+            states(&service.status().await.unwrap().unwrap())[0],
+            SecretState::InSync
+        );
+    }
+}
@@ -1,17 +1,77 @@
-use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 
 use crate::core::{
-    config::{DotfConfig, Settings},
-    symlinks::{SymlinkManager, SymlinkOperation, SymlinkStatus},
+    config::{dotf_config::SymlinkMap, loader, overlays::retain_repository, DotfConfig, Settings},
+    cron::SystemCrontab,
+    history::{parse_since, HistoryStore, StatusSnapshot},
+    secrets::SystemCipher,
+    symlinks::{
+        check_pins, partition_export_ignored, resolve_priorities, EntryOperation, LinkMode,
+        OverriddenLink, Permissions, PinMismatch, SymlinkInfo, SymlinkManager, SymlinkOperation,
+        SymlinkStatus,
+    },
+    timings,
 };
 use crate::error::{DotfError, DotfResult};
+use crate::services::{
+    cron_service::{CronService, CronStatusInfo},
+    render_service::{RenderService, StaleRender},
+    secrets_service::{SecretsService, SecretsStatusInfo},
+    ssh_service::{SshService, SshStatusInfo},
+    tools_service::{ToolStatusInfo, ToolsService},
+};
 use crate::traits::{
     filesystem::FileSystem,
-    prompt::Prompt,
-    repository::{Repository, RepositoryStatus},
+    repository::{FetchPolicy, Repository, RepositoryStatus},
 };
+use crate::utils::{platform::detect_platform, NonInteractivePrompt};
+
+/// Exit codes of `dotf status --check`. Errors exit with 1 and usage errors with 2, like
+/// every other command.
+pub const EXIT_OK: i32 = 0;
+pub const EXIT_SYMLINK_ISSUES: i32 = 3;
+pub const EXIT_REPOSITORY_OUT_OF_DATE: i32 = 4;
+pub const EXIT_NOT_INITIALIZED: i32 = 5;
+
+/// What `dotf status --fail-on` looks for. The exit code is the sum of the bits of the
+/// selected categories that apply. The bits start above every `--check` code, so no
+/// `--fail-on` code can be mistaken for an error, a usage error or a `--check` answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CheckCategory {
+    /// Missing, broken, conflicting or misdirected links, wrong permissions, changed pins
+    Broken,
+    /// The repository is behind its remote
+    Behind,
+    /// The repository has uncommitted changes
+    Dirty,
+    /// dotf.toml is missing or invalid
+    Config,
+    /// dotf is not initialized
+    Uninitialized,
+    /// Every category above
+    All,
+}
+
+impl CheckCategory {
+    const CONCRETE: [CheckCategory; 5] = [
+        CheckCategory::Broken,
+        CheckCategory::Behind,
+        CheckCategory::Dirty,
+        CheckCategory::Config,
+        CheckCategory::Uninitialized,
+    ];
+
+    pub fn bit(self) -> i32 {
+        match self {
+            CheckCategory::Broken => 8,
+            CheckCategory::Behind => 16,
+            CheckCategory::Dirty => 32,
+            CheckCategory::Config => 64,
+            CheckCategory::Uninitialized => 128,
+            CheckCategory::All => Self::CONCRETE.iter().map(|category| category.bit()).sum(),
+        }
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DotfStatus {
@@ -19,6 +79,76 @@ pub struct DotfStatus {
     pub repository: Option<RepositoryStatusInfo>,
     pub symlinks: SymlinksStatusInfo,
     pub config: ConfigStatusInfo,
+    #[serde(default)]
+    pub tools: Vec<ToolStatusInfo>,
+    #[serde(default)]
+    pub cron: Option<CronStatusInfo>,
+    #[serde(default)]
+    pub ssh: Option<SshStatusInfo>,
+    #[serde(default)]
+    pub secrets: Option<SecretsStatusInfo>,
+    /// Composed files whose sources changed since they were rendered
+    #[serde(default)]
+    pub stale_renders: Vec<StaleRender>,
+}
+
+impl DotfStatus {
+    /// The `--check` exit code; when several apply, the one listed first in the README wins
+    pub fn exit_code(&self) -> i32 {
+        if !self.initialized {
+            return EXIT_NOT_INITIALIZED;
+        }
+        if self.has_symlink_issues() {
+            return EXIT_SYMLINK_ISSUES;
+        }
+        if self
+            .repository
+            .as_ref()
+            .is_some_and(|repo| !repo.status.is_clean || repo.status.behind_count > 0)
+        {
+            return EXIT_REPOSITORY_OUT_OF_DATE;
+        }
+        EXIT_OK
+    }
+
+    /// The `--fail-on` exit code: the bits of the `selected` categories that apply
+    pub fn failure_code(&self, selected: &[CheckCategory]) -> i32 {
+        CheckCategory::CONCRETE
+            .into_iter()
+            .filter(|category| {
+                selected.contains(category) || selected.contains(&CheckCategory::All)
+            })
+            .filter(|category| self.applies(*category))
+            .map(CheckCategory::bit)
+            .sum()
+    }
+
+    fn applies(&self, category: CheckCategory) -> bool {
+        let repository = self.repository.as_ref().map(|repo| &repo.status);
+        match category {
+            CheckCategory::Uninitialized => !self.initialized,
+            _ if !self.initialized => false,
+            CheckCategory::Broken => self.has_symlink_issues(),
+            CheckCategory::Behind => repository.is_some_and(|status| status.behind_count > 0),
+            CheckCategory::Dirty => repository.is_some_and(|status| !status.is_clean),
+            CheckCategory::Config => !self.config.valid,
+            CheckCategory::All => CheckCategory::CONCRETE
+                .into_iter()
+                .any(|category| self.applies(category)),
+        }
+    }
+
+    fn has_symlink_issues(&self) -> bool {
+        let symlinks = &self.symlinks;
+        symlinks.missing
+            + symlinks.broken
+            + symlinks.conflicts
+            + symlinks.invalid_targets
+            + symlinks.wrong_permissions
+            + symlinks.unreadable
+            > 0
+            || !symlinks.pin_mismatches.is_empty()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,7 +168,19 @@ pub struct SymlinksStatusInfo {
     pub conflicts: usize,
     pub invalid_targets: usize,
     pub modified: usize,
+    /// Entries turned off on this machine (`[symlinks] disabled` in settings.toml)
+    #[serde(default)]
+    pub disabled: usize,
+    /// Deployed entries whose mode or owner differs from `chmod`/`owner` in dotf.toml
+    #[serde(default)]
+    pub wrong_permissions: usize,
+    /// Deployed entries an app cannot open, e.g. for lack of permission on the repository
+    #[serde(default)]
+    pub unreadable: usize,
     pub details: Vec<SymlinkStatusDetail>,
+    /// Pinned sources whose content no longer matches `pin_sha256`
+    #[serde(default)]
+    pub pin_mismatches: Vec<PinMismatch>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +189,12 @@ pub struct SymlinkStatusDetail {
     pub target_path: String,
     pub status: SymlinkStatus,
     pub current_target: Option<String>,
+    /// The error opening an Unreadable entry gives
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Sources of other entries for the same target that lost on priority
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub overrides: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -63,43 +211,98 @@ pub struct StatusService<R, F> {
     repository: R,
     filesystem: F,
     #[allow(dead_code)]
-    symlink_manager: SymlinkManager<F, ConsolePrompt>,
-}
-
-// We need a dummy prompt for the symlink manager since status checking doesn't need interactive prompts
-#[derive(Clone)]
-struct ConsolePrompt;
-
-#[async_trait]
-impl Prompt for ConsolePrompt {
-    async fn input(&self, _message: &str, _default: Option<&str>) -> DotfResult<String> {
-        Err(DotfError::Operation(
-            "Prompt not available in status service".to_string(),
-        ))
-    }
-
-    async fn confirm(&self, _message: &str) -> DotfResult<bool> {
-        Err(DotfError::Operation(
-            "Prompt not available in status service".to_string(),
-        ))
-    }
-
-    async fn select(&self, _message: &str, _options: &[(&str, &str)]) -> DotfResult<usize> {
-        Err(DotfError::Operation(
-            "Prompt not available in status service".to_string(),
-        ))
-    }
+    symlink_manager: SymlinkManager<F, NonInteractivePrompt>,
+    history: HistoryStore<F>,
+    tools: ToolsService<F>,
+    cron: CronService<F, SystemCrontab>,
+    ssh: SshService<F>,
+    secrets: SecretsService<F, SystemCipher>,
+    renders: RenderService<F>,
+    only_repository: Option<String>,
+    fetch: FetchPolicy,
 }
 
 impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
     pub fn new(repository: R, filesystem: F) -> Self {
-        let prompt = ConsolePrompt;
-        let symlink_manager = SymlinkManager::new(filesystem.clone(), prompt);
+        let symlink_manager = SymlinkManager::new(filesystem.clone(), NonInteractivePrompt::new());
+        let history = HistoryStore::new(filesystem.clone());
+        let tools = ToolsService::new(filesystem.clone());
+        let cron = CronService::new(filesystem.clone(), SystemCrontab::new());
+        let ssh = SshService::new(filesystem.clone());
+        let secrets = SecretsService::new(filesystem.clone(), SystemCipher::new());
+        let renders = RenderService::new(filesystem.clone());
         Self {
             repository,
             filesystem,
             symlink_manager,
+            history,
+            tools,
+            cron,
+            ssh,
+            secrets,
+            renders,
+            only_repository: None,
+            fetch: FetchPolicy::default(),
+        }
+    }
+
+    /// Only check the links from repository `name` (`base` or an overlay)
+    pub fn with_only_repository(mut self, name: Option<String>) -> Self {
+        self.only_repository = name;
+        self
+    }
+
+    /// How far to go to reach the remote for the ahead/behind counts
+    pub fn with_fetch_policy(mut self, fetch: FetchPolicy) -> Self {
+        self.fetch = fetch;
+        self
+    }
+
+    /// Persist a snapshot of `status` so drift can be reviewed later with `--since`
+    pub async fn record_snapshot(&self, status: &DotfStatus) -> DotfResult<()> {
+        if !status.initialized {
+            return Ok(());
         }
+
+        let (is_clean, ahead, behind) = status
+            .repository
+            .as_ref()
+            .map(|repo| {
+                (
+                    repo.status.is_clean,
+                    repo.status.ahead_count,
+                    repo.status.behind_count,
+                )
+            })
+            .unwrap_or((true, 0, 0));
+
+        self.history
+            .append(StatusSnapshot {
+                taken_at: self.history.now(),
+                total: status.symlinks.total,
+                valid: status.symlinks.valid,
+                missing: status.symlinks.missing,
+                broken: status.symlinks.broken,
+                conflicts: status.symlinks.conflicts,
+                invalid_targets: status.symlinks.invalid_targets,
+                modified: status.symlinks.modified,
+                is_clean,
+                ahead,
+                behind,
+            })
+            .await
+    }
+
+    /// Load recorded snapshots newer than `since` (e.g. `7d` or `2024-01-31`)
+    pub async fn history_since(&self, since: &str) -> DotfResult<Vec<StatusSnapshot>> {
+        let cutoff = parse_since(since, self.history.now())?;
+        self.history.load_since(cutoff).await
+    }
+
+    /// Serialize `status` as pretty-printed JSON for `dotf status --json`
+    pub fn to_json<T: Serialize + ?Sized>(&self, status: &T) -> DotfResult<String> {
+        serde_json::to_string_pretty(status)
+            .map_err(|e| DotfError::Operation(format!("Failed to serialize status: {}", e)))
     }
 
     pub async fn get_status(&self) -> DotfResult<DotfStatus> {
@@ -117,7 +320,11 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                     conflicts: 0,
                     invalid_targets: 0,
                     modified: 0,
+                    disabled: 0,
+                    wrong_permissions: 0,
+                    unreadable: 0,
                     details: Vec::new(),
+                    pin_mismatches: Vec::new(),
                 },
                 config: ConfigStatusInfo {
                     valid: false,
@@ -127,18 +334,34 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                     has_platform_config: false,
                     errors: vec!["Dotf is not initialized".to_string()],
                 },
+                tools: Vec::new(),
+                cron: None,
+                ssh: None,
+                secrets: None,
+                stale_renders: Vec::new(),
             });
         }
 
         let repository_status = self.get_repository_status().await?;
         let config_status = self.get_config_status().await?;
         let symlinks_status = self.get_symlinks_status().await?;
+        // Like symlinks, tools are simply omitted when dotf.toml cannot be read
+        let tools_status = self.tools.statuses().await.unwrap_or_default();
+        let cron_status = self.cron.status().await.ok().flatten();
+        let ssh_status = self.ssh.status().await.ok().flatten();
+        let secrets_status = self.secrets.status().await.ok().flatten();
+        let stale_renders = self.renders.stale().await.unwrap_or_default();
 
         Ok(DotfStatus {
             initialized: true,
             repository: Some(repository_status),
             symlinks: symlinks_status,
             config: config_status,
+            tools: tools_status,
+            cron: cron_status,
+            ssh: ssh_status,
+            secrets: secrets_status,
+            stale_renders,
         })
     }
 
@@ -150,7 +373,7 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
             .clone()
             .unwrap_or_else(|| self.filesystem.dotf_repo_path());
 
-        let status = self.repository.get_status(&repo_path).await?;
+        let status = self.repository.get_status(&repo_path, self.fetch).await?;
 
         Ok(RepositoryStatusInfo {
             url: settings.repository.remote,
@@ -173,40 +396,46 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                     conflicts: 0,
                     invalid_targets: 0,
                     modified: 0,
+                    disabled: 0,
+                    wrong_permissions: 0,
+                    unreadable: 0,
                     details: Vec::new(),
+                    pin_mismatches: Vec::new(),
                 });
             }
         };
 
-        let platform = self.detect_platform();
+        let platform = detect_platform();
         let mut symlinks = config.symlinks.clone();
 
         // Add platform-specific symlinks
-        match platform.as_str() {
-            "macos" => {
-                if let Some(macos_config) = config.platform.macos {
-                    symlinks.extend(macos_config.symlinks);
-                }
-            }
-            "linux" => {
-                if let Some(linux_config) = config.platform.linux {
-                    symlinks.extend(linux_config.symlinks);
-                }
-            }
-            _ => {}
+        if let Some(platform_config) = config.platform.for_platform(&platform) {
+            symlinks.extend(platform_config.symlinks.clone());
         }
 
-        let operations = self.create_symlink_operations(&symlinks).await?;
+        let (operations, overridden) = self.create_symlink_operations(&symlinks).await?;
         let settings = self.load_settings().await?;
         let repo_path = settings
             .repository
             .local
             .clone()
             .unwrap_or_else(|| self.filesystem.dotf_repo_path());
-        let symlink_infos = self
+        let (disabled, operations): (Vec<_>, Vec<_>) = operations
+            .into_iter()
+            .partition(|operation| settings.symlinks.is_disabled(&operation.target_path));
+        let mut symlink_infos = self
             .symlink_manager
             .get_symlink_status_with_changes(&operations, &self.repository, &repo_path)
             .await?;
+        symlink_infos.extend(disabled.into_iter().map(|operation| SymlinkInfo {
+            source_path: operation.source_path,
+            target_path: operation.target_path,
+            status: SymlinkStatus::Disabled,
+            current_target: None,
+            error: None,
+        }));
+
+        let pin_mismatches = check_pins(&self.filesystem, &symlinks, &repo_path).await?;
 
         let mut status_info = SymlinksStatusInfo {
             total: symlink_infos.len(),
@@ -216,7 +445,11 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
             conflicts: 0,
             invalid_targets: 0,
             modified: 0,
+            disabled: 0,
+            wrong_permissions: 0,
+            unreadable: 0,
             details: Vec::new(),
+            pin_mismatches,
         };
 
         for info in symlink_infos {
@@ -227,13 +460,23 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                 SymlinkStatus::Conflict => status_info.conflicts += 1,
                 SymlinkStatus::InvalidTarget => status_info.invalid_targets += 1,
                 SymlinkStatus::Modified => status_info.modified += 1,
+                SymlinkStatus::Disabled => status_info.disabled += 1,
+                SymlinkStatus::WrongPermissions => status_info.wrong_permissions += 1,
+                SymlinkStatus::Unreadable => status_info.unreadable += 1,
             }
 
+            let overrides = overridden
+                .iter()
+                .filter(|link| link.target_path == info.target_path)
+                .map(|link| link.source_path.clone())
+                .collect();
             status_info.details.push(SymlinkStatusDetail {
                 source_path: info.source_path,
                 target_path: info.target_path,
                 status: info.status,
                 current_target: info.current_target,
+                error: info.error,
+                overrides,
             });
         }
 
@@ -275,8 +518,9 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
             }
         };
 
-        let has_platform_config =
-            config.platform.macos.is_some() || config.platform.linux.is_some();
+        let has_platform_config = config.platform.macos.is_some()
+            || config.platform.linux.is_some()
+            || config.platform.windows.is_some();
 
         Ok(ConfigStatusInfo {
             valid: errors.is_empty(),
@@ -312,7 +556,11 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                 println!("   Ahead: {} commits", repo.status.ahead_count);
             }
             if repo.status.behind_count > 0 {
-                println!("   Behind: {} commits", repo.status.behind_count);
+                if repo.status.tracks_upstream {
+                    println!("   Behind: {} commits", repo.status.behind_count);
+                } else {
+                    println!("   Update available");
+                }
             }
 
             if let Some(last_sync) = repo.last_sync {
@@ -361,30 +609,19 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
 
     async fn load_config(&self) -> DotfResult<DotfConfig> {
         let settings = self.load_settings().await?;
-        let repo_path = settings
-            .repository
-            .local
-            .clone()
-            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
-        let config_path = format!("{}/dotf.toml", repo_path);
-
-        if !self.filesystem.exists(&config_path).await? {
-            return Err(DotfError::Config(
-                "dotf.toml not found in repository".to_string(),
-            ));
+        let mut config = loader::load_config(&self.filesystem, &settings).await?;
+        if let Some(name) = &self.only_repository {
+            retain_repository(&self.filesystem, &settings, &mut config, name)?;
         }
 
-        let content = self.filesystem.read_to_string(&config_path).await?;
-        let config: DotfConfig = toml::from_str(&content)
-            .map_err(|e| DotfError::Config(format!("Failed to parse dotf.toml: {}", e)))?;
-
         Ok(config)
     }
 
+    /// One operation per target, resolved by priority like install, and the links that lost
     async fn create_symlink_operations(
         &self,
-        symlinks: &HashMap<String, String>,
-    ) -> DotfResult<Vec<SymlinkOperation>> {
+        symlinks: &SymlinkMap,
+    ) -> DotfResult<(Vec<SymlinkOperation>, Vec<OverriddenLink>)> {
         let mut operations = Vec::new();
         let settings = self.load_settings().await?;
         let repo_path = settings
@@ -419,17 +656,51 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                 let dir_operations = self
                     .expand_directory_operations(&absolute_source, &expanded_target)
                     .await?;
-                operations.extend(dir_operations);
+                // Match install: files marked export-ignore are not expected to be linked
+                let dir_operations = match partition_export_ignored(
+                    &self.repository,
+                    &repo_path,
+                    dir_operations.clone(),
+                )
+                .await
+                {
+                    Ok((kept, _)) => kept,
+                    Err(_) => dir_operations,
+                };
+                operations.extend(dir_operations.into_iter().map(|operation| EntryOperation {
+                    entry: source.clone(),
+                    priority: symlinks.priority(source),
+                    from_directory: true,
+                    operation: SymlinkOperation {
+                        mode: symlinks.mode(source),
+                        permissions: symlinks.permissions(source),
+                        ..operation
+                    },
+                }));
             } else {
                 // Single file or doesn't exist yet
-                operations.push(SymlinkOperation {
-                    source_path: absolute_source,
-                    target_path: expanded_target,
+                operations.push(EntryOperation {
+                    entry: source.clone(),
+                    priority: symlinks.priority(source),
+                    from_directory: false,
+                    operation: SymlinkOperation {
+                        source_path: absolute_source,
+                        target_path: expanded_target,
+                        mode: symlinks.mode(source),
+                        permissions: symlinks.permissions(source),
+                    },
                 });
             }
         }
 
-        Ok(operations)
+        let (operations, overridden) = resolve_priorities(operations);
+        Ok((
+            operations
+                .into_iter()
+                .map(|operation| operation.operation)
+                .collect(),
+            overridden,
+        ))
     }
 
     async fn expand_directory_operations(
@@ -437,6 +708,7 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
         source_dir: &str,
         target_dir: &str,
     ) -> DotfResult<Vec<SymlinkOperation>> {
+        let mut timer = timings::phase("directory expansion");
         let mut operations = Vec::new();
         let mut dir_stack = vec![(source_dir.to_string(), target_dir.to_string())];
 
@@ -466,25 +738,118 @@ impl<R: Repository, F: FileSystem + Clone> StatusService<R, F> {
                     operations.push(SymlinkOperation {
                         source_path: entry.path.clone(),
                         target_path,
+                        mode: LinkMode::Symlink,
+                        permissions: Permissions::default(),
                     });
                 }
             }
         }
 
+        timer.items(operations.len());
         Ok(operations)
     }
+}
 
-    fn detect_platform(&self) -> String {
-        #[cfg(target_os = "macos")]
-        return "macos".to_string();
-
-        #[cfg(target_os = "linux")]
-        return "linux".to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::{filesystem::tests::MockFileSystem, repository::tests::MockRepository};
+
+    #[tokio::test]
+    async fn test_status_exit_codes() {
+        let service = StatusService::new(MockRepository::new(), MockFileSystem::new());
+        let mut status = service.get_status().await.unwrap();
+        assert_eq!(status.exit_code(), EXIT_NOT_INITIALIZED);
+
+        status.initialized = true;
+        status.repository = Some(RepositoryStatusInfo {
+            url: "https://github.com/user/dotfiles".to_string(),
+            path: "/home/user/.dotf/repo".to_string(),
+            status: RepositoryStatus {
+                is_clean: true,
+                ahead_count: 2,
+                behind_count: 0,
+                current_branch: "main".to_string(),
+                tracks_upstream: true,
+                fetched: true,
+            },
+            last_sync: None,
+        });
+        assert_eq!(status.exit_code(), EXIT_OK);
+
+        status.repository.as_mut().unwrap().status.behind_count = 1;
+        assert_eq!(status.exit_code(), EXIT_REPOSITORY_OUT_OF_DATE);
+        status.symlinks.broken = 1;
+        assert_eq!(status.exit_code(), EXIT_SYMLINK_ISSUES);
+
+        status.config.valid = true;
+        assert_eq!(status.failure_code(&[CheckCategory::Dirty]), 0);
+        assert_eq!(
+            status.failure_code(&[CheckCategory::Broken, CheckCategory::Behind]),
+            24
+        );
+        assert_eq!(status.failure_code(&[CheckCategory::All]), 24);
+        status.initialized = false;
+        assert_eq!(status.failure_code(&[CheckCategory::Broken]), 0);
+        assert_eq!(status.failure_code(&[CheckCategory::All]), 128);
+    }
 
-        #[cfg(target_os = "windows")]
-        return "windows".to_string();
+    #[test]
+    fn test_fail_on_bits_are_disjoint_from_check_codes() {
+        let bits: Vec<i32> = CheckCategory::CONCRETE
+            .into_iter()
+            .map(CheckCategory::bit)
+            .collect();
+        assert_eq!(bits, vec![8, 16, 32, 64, 128]);
+        assert_eq!(CheckCategory::All.bit(), 248);
+
+        let check_codes = [
+            EXIT_OK,
+            EXIT_SYMLINK_ISSUES,
+            EXIT_REPOSITORY_OUT_OF_DATE,
+            EXIT_NOT_INITIALIZED,
+        ];
+        assert!(bits
+            .iter()
+            .all(|bit| check_codes.iter().all(|code| bit > code)));
+    }
 
-        #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
-        return "unknown".to_string();
+    #[tokio::test]
+    async fn test_repository_status_follows_fetch_policy() {
+        let mut repository = MockRepository::new();
+        repository.set_status_response(RepositoryStatus {
+            is_clean: true,
+            ahead_count: 0,
+            behind_count: 2,
+            current_branch: "main".to_string(),
+            tracks_upstream: true,
+            fetched: true,
+        });
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+
+        let service = StatusService::new(Clone::clone(&repository), filesystem.clone());
+        assert!(
+            service
+                .get_repository_status()
+                .await
+                .unwrap()
+                .status
+                .fetched
+        );
+
+        let service = StatusService::new(Clone::clone(&repository), filesystem)
+            .with_fetch_policy(FetchPolicy::Never);
+        let info = service.get_repository_status().await.unwrap();
+        assert!(!info.status.fetched);
+        assert_eq!(info.status.behind_count, 2);
+        assert_eq!(
+            repository.get_status_fetches(),
+            vec![FetchPolicy::Auto, FetchPolicy::Never]
+        );
     }
 }
@@ -1,8 +1,19 @@
 use chrono::Utc;
-
-use crate::core::config::Settings;
+use serde::Serialize;
+
+use crate::core::config::{
+    loader,
+    overlays::overlay_path,
+    renames::{config_sources, rename_sources, renamed_source},
+    CommitContext, Settings,
+};
+use crate::core::repository::sparse_paths;
 use crate::error::{DotfError, DotfResult};
-use crate::traits::{filesystem::FileSystem, repository::Repository};
+use crate::traits::{
+    filesystem::FileSystem,
+    repository::{FetchPolicy, Rename, Repository, RepositoryStatus, UpstreamHistory},
+};
+use crate::utils::platform::detect_platform;
 
 pub struct SyncService<R, F> {
     repository: R,
@@ -17,7 +28,9 @@ impl<R: Repository, F: FileSystem> SyncService<R, F> {
         }
     }
 
-    pub async fn sync(&self, force: bool) -> DotfResult<SyncResult> {
+    /// Pull the main repository. Uncommitted changes stop the sync unless `force` pulls
+    /// over them or `autostash` sets them aside for the pull and reapplies them after.
+    pub async fn sync(&self, force: bool, autostash: bool) -> DotfResult<SyncResult> {
         // Check if dotf is initialized
         let settings_path = self.filesystem.dotf_settings_path();
         if !self.filesystem.exists(&settings_path).await? {
@@ -42,25 +55,94 @@ impl<R: Repository, F: FileSystem> SyncService<R, F> {
         }
 
         // Get repository status before sync
-        let status_before = self.repository.get_status(&repo_path).await?;
+        let status_before = self
+            .repository
+            .get_status(&repo_path, FetchPolicy::Always)
+            .await?;
 
-        if !status_before.is_clean && !force {
+        if !status_before.is_clean && !force && !autostash {
             return Err(DotfError::Operation(
-                "Repository has uncommitted changes. Use --force to sync anyway, or commit your changes first.".to_string()
+                "Repository has uncommitted changes. Use --autostash to set them aside for the pull, --force to sync anyway, or commit your changes first.".to_string()
             ));
         }
 
+        // A rebase onto rewritten history fails confusingly; callers must rescue first
+        match self.repository.upstream_history(&repo_path).await? {
+            UpstreamHistory::Linear => {}
+            UpstreamHistory::Rewritten { .. } => {
+                return Err(DotfError::Repository(
+                    "The remote history was rewritten (force-pushed). Run 'dotf sync' in a terminal to back up local commits and reset.".to_string(),
+                ));
+            }
+            UpstreamHistory::Unrelated => {
+                return Err(DotfError::Repository(
+                    "The remote branch no longer shares history with the local one. Run 'dotf sync' in a terminal to back up local commits and reset.".to_string(),
+                ));
+            }
+        }
+
+        let stashed =
+            autostash && !status_before.is_clean && self.repository.stash(&repo_path).await?;
+        let revision_before = self.repository.revision(&repo_path).await?;
+
         // Perform pull (repository will use the configured branch)
-        self.repository.pull(&repo_path).await?;
+        if let Err(e) = self.repository.pull(&repo_path).await {
+            if stashed {
+                // Nothing was pulled, so the changes go back where they were
+                let _ = self.repository.stash_pop(&repo_path).await;
+            }
+            return Err(e);
+        }
+        let stash = if stashed {
+            Some(match self.repository.stash_pop(&repo_path).await? {
+                conflicts if conflicts.is_empty() => StashOutcome::Restored,
+                conflicts => StashOutcome::Conflicts(conflicts),
+            })
+        } else {
+            None
+        };
+        self.update_sparse_checkout(&settings, &repo_path).await?;
+
+        // Files the pulled commits moved, so links into them can follow. The pull has
+        // already happened, so failing to tell only means nothing is followed.
+        let renames = match (revision_before, self.repository.revision(&repo_path).await?) {
+            (Some(before), Some(after)) => self
+                .repository
+                .renames(&repo_path, &before, &after)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "could not detect renamed files");
+                    Vec::new()
+                }),
+            _ => Vec::new(),
+        };
 
         // Get status after sync
-        let status_after = self.repository.get_status(&repo_path).await?;
+        let status_after = self
+            .repository
+            .get_status(&repo_path, FetchPolicy::Always)
+            .await?;
 
         // Update last sync timestamp
         let updated_settings = Settings {
             repository: settings.repository,
             last_sync: Some(Utc::now()),
             initialized_at: settings.initialized_at,
+            commit: settings.commit,
+            prompts: settings.prompts,
+            scripts: settings.scripts,
+            aliases: settings.aliases,
+            widget: settings.widget,
+            profile: settings.profile,
+            packages: settings.packages,
+            logging: settings.logging,
+            symlinks: settings.symlinks,
+            diff: settings.diff,
+            backups: settings.backups,
+            repositories: settings.repositories,
+            features: settings.features,
+            network: settings.network,
+            variables: settings.variables,
         };
 
         let settings_content = updated_settings
@@ -80,9 +162,220 @@ impl<R: Repository, F: FileSystem> SyncService<R, F> {
             },
             current_branch: status_after.current_branch,
             is_clean_after: status_after.is_clean,
+            tracks_upstream: status_after.tracks_upstream,
+            stash,
+            renames,
         })
     }
 
+    /// Re-apply the sparse set from the pulled dotf.toml so new entries get checked out;
+    /// with `repository.sparse` off, a sparse checkout gets everything back
+    async fn update_sparse_checkout(&self, settings: &Settings, repo_path: &str) -> DotfResult<()> {
+        if !settings.repository.sparse {
+            return self.repository.set_sparse_paths(repo_path, None).await;
+        }
+
+        let config = loader::read_config(&self.filesystem, repo_path).await?;
+        let paths = sparse_paths(&config, &detect_platform());
+        self.repository
+            .set_sparse_paths(repo_path, Some(&paths))
+            .await
+    }
+
+    /// Clone the overlay repositories that are not checked out yet and pull the others;
+    /// with `only`, just that overlay. A failing overlay does not stop the others.
+    pub async fn sync_overlays(&self, only: Option<&str>) -> DotfResult<Vec<OverlaySync>> {
+        let settings = self.load_settings().await?;
+        if let Some(name) = only {
+            if !settings.repositories.iter().any(|r| r.name == name) {
+                return Err(DotfError::Config(format!(
+                    "Unknown repository '{}' (see 'dotf repos list')",
+                    name
+                )));
+            }
+        }
+
+        let mut results = Vec::new();
+        for overlay in &settings.repositories {
+            if only.is_some_and(|name| name != overlay.name) {
+                continue;
+            }
+            let path = overlay_path(&self.filesystem, &overlay.name);
+            let outcome = if self.filesystem.exists(&path).await? {
+                self.repository
+                    .pull(&path)
+                    .await
+                    .map(|_| OverlayOutcome::Pulled)
+            } else {
+                let cloned = match &overlay.branch {
+                    Some(branch) => {
+                        self.repository
+                            .clone_branch(&overlay.remote, branch, &path)
+                            .await
+                    }
+                    None => self.repository.clone(&overlay.remote, &path).await,
+                };
+                cloned.map(|_| OverlayOutcome::Cloned)
+            };
+            results.push(OverlaySync {
+                name: overlay.name.clone(),
+                outcome: outcome.unwrap_or_else(|e| OverlayOutcome::Failed(e.to_string())),
+            });
+        }
+        Ok(results)
+    }
+
+    /// Fetch and check whether the upstream history was rewritten since the last sync
+    pub async fn upstream_history(&self) -> DotfResult<UpstreamHistory> {
+        let repo_path = self.repo_path().await?;
+        self.repository.upstream_history(&repo_path).await
+    }
+
+    /// Keep local commits on a rescue branch and reset to the remote; returns the branch name
+    pub async fn rescue_and_reset(&self) -> DotfResult<String> {
+        let repo_path = self.repo_path().await?;
+        self.repository.rescue_and_reset(&repo_path).await
+    }
+
+    /// Where the local branch stands before `push`, after checking that the remote
+    /// history was not rewritten
+    pub async fn push_status(&self) -> DotfResult<RepositoryStatus> {
+        let repo_path = self.repo_path().await?;
+        if self.repository.upstream_history(&repo_path).await? != UpstreamHistory::Linear {
+            return Err(DotfError::Repository(
+                "The remote history was rewritten or replaced. Run 'dotf sync' to reset onto it before pushing.".to_string(),
+            ));
+        }
+        self.repository
+            .get_status(&repo_path, FetchPolicy::Always)
+            .await
+    }
+
+    /// Commit every local change in the repository and push the branch. When the
+    /// remote has new commits they are only rebased onto with `rebase`, since a
+    /// conflicting rebase stops halfway and needs the user to finish it.
+    pub async fn push(&self, message: Option<&str>, rebase: bool) -> DotfResult<PushResult> {
+        let repo_path = self.repo_path().await?;
+        let settings = self.load_settings().await?;
+
+        let status = self
+            .repository
+            .get_status(&repo_path, FetchPolicy::Always)
+            .await?;
+        if !status.tracks_upstream {
+            return Err(DotfError::Repository(
+                "This repository has no remote history to push to".to_string(),
+            ));
+        }
+        if status.behind_count > 0 && !rebase {
+            return Err(DotfError::Operation(format!(
+                "The remote has {} new commit(s) on '{}'; pull them with 'dotf sync' before pushing",
+                status.behind_count, status.current_branch
+            )));
+        }
+
+        let committed = self.repository.add_all(&repo_path).await?;
+        let commit_message = if committed.is_empty() {
+            None
+        } else {
+            let message = match message {
+                Some(message) => message.to_string(),
+                None => {
+                    let summary = match committed.as_slice() {
+                        [file] => file.clone(),
+                        files => format!("{} files", files.len()),
+                    };
+                    settings.commit.render(&CommitContext::current(
+                        &format!("Update {}", summary),
+                        &committed,
+                    ))
+                }
+            };
+            self.repository
+                .commit(&repo_path, &message, &[], settings.commit.sign)
+                .await?;
+            Some(message)
+        };
+
+        let commits_pushed = status.ahead_count + usize::from(commit_message.is_some());
+        let mut commits_rebased_onto = 0;
+        if commits_pushed > 0 {
+            if status.behind_count > 0 {
+                self.repository.pull(&repo_path).await?;
+                commits_rebased_onto = status.behind_count;
+            }
+            self.repository.push(&repo_path).await?;
+        }
+
+        Ok(PushResult {
+            committed,
+            commit_message,
+            commits_rebased_onto,
+            commits_pushed,
+            current_branch: status.current_branch,
+        })
+    }
+
+    /// Sources in dotf.toml that `renames` moved: the old path is gone from the checkout
+    /// and the new one is there, so the entry can follow it
+    pub async fn moved_sources(&self, renames: &[Rename]) -> DotfResult<Vec<Rename>> {
+        if renames.is_empty() {
+            return Ok(Vec::new());
+        }
+        let repo_path = self.repo_path().await?;
+        let content = self
+            .filesystem
+            .read_to_string(&format!("{}/dotf.toml", repo_path))
+            .await?;
+
+        let mut moved = Vec::new();
+        for source in config_sources(&content)? {
+            let Some(to) = renamed_source(&source, renames) else {
+                continue;
+            };
+            let old_exists = self
+                .filesystem
+                .exists(&format!("{}/{}", repo_path, source.trim_end_matches('/')))
+                .await?;
+            let new_exists = self
+                .filesystem
+                .exists(&format!("{}/{}", repo_path, to))
+                .await?;
+            if !old_exists && new_exists {
+                moved.push(Rename { from: source, to });
+            }
+        }
+        Ok(moved)
+    }
+
+    /// Point the dotf.toml entries `moved` lists at their new sources. The change is left
+    /// uncommitted for the user to review and push.
+    pub async fn follow_renames(&self, moved: &[Rename]) -> DotfResult<()> {
+        let config_path = format!("{}/dotf.toml", self.repo_path().await?);
+        let content = self.filesystem.read_to_string(&config_path).await?;
+        let updated = rename_sources(&content, moved)?;
+        if updated != content {
+            self.filesystem.write(&config_path, &updated).await?;
+        }
+        Ok(())
+    }
+
+    async fn repo_path(&self) -> DotfResult<String> {
+        if !self
+            .filesystem
+            .exists(&self.filesystem.dotf_settings_path())
+            .await?
+        {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let settings = self.load_settings().await?;
+        Ok(settings
+            .repository
+            .local
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path()))
+    }
+
     pub async fn check_sync_status(&self) -> DotfResult<SyncStatus> {
         let settings_path = self.filesystem.dotf_settings_path();
         if !self.filesystem.exists(&settings_path).await? {
@@ -99,7 +392,10 @@ impl<R: Repository, F: FileSystem> SyncService<R, F> {
             return Ok(SyncStatus::RepositoryMissing);
         }
 
-        let status = self.repository.get_status(&repo_path).await?;
+        let status = self
+            .repository
+            .get_status(&repo_path, FetchPolicy::Always)
+            .await?;
 
         if !status.is_clean {
             return Ok(SyncStatus::HasUncommittedChanges {
@@ -141,12 +437,54 @@ impl<R: Repository, F: FileSystem> SyncService<R, F> {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct SyncResult {
     pub had_uncommitted_changes: bool,
     pub commits_pulled: usize,
     pub current_branch: String,
     pub is_clean_after: bool,
+    /// False for backends without commit history, where `commits_pulled` is 1 for
+    /// "a new snapshot was downloaded"
+    pub tracks_upstream: bool,
+    /// What became of the changes `--autostash` set aside; `None` when nothing was
+    pub stash: Option<StashOutcome>,
+    /// Files the pulled commits renamed; empty for backends without history
+    pub renames: Vec<Rename>,
+}
+
+/// How uncommitted changes set aside for the pull were reapplied
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum StashOutcome {
+    /// Back in the working copy on top of the pulled commits
+    Restored,
+    /// These paths conflict with the pulled commits; the changes stay stashed until
+    /// the conflicts are resolved
+    Conflicts(Vec<String>),
+}
+
+/// What `sync_overlays` did with one overlay repository
+#[derive(Debug, Serialize)]
+pub struct OverlaySync {
+    pub name: String,
+    pub outcome: OverlayOutcome,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub enum OverlayOutcome {
+    Cloned,
+    Pulled,
+    Failed(String),
+}
+
+#[derive(Debug)]
+pub struct PushResult {
+    /// Paths committed from the working copy; empty when only earlier commits were pushed
+    pub committed: Vec<String>,
+    pub commit_message: Option<String>,
+    /// Remote commits the local ones were rebased onto before pushing
+    pub commits_rebased_onto: usize,
+    pub commits_pushed: usize,
+    pub current_branch: String,
 }
 
 #[derive(Debug)]
@@ -175,7 +513,7 @@ pub enum SyncStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core::config::settings::Repository;
+    use crate::core::config::{settings::Repository, NamedRepository};
     use crate::traits::{
         filesystem::tests::MockFileSystem,
         repository::{tests::MockRepository, RepositoryStatus},
@@ -196,6 +534,8 @@ mod tests {
             ahead_count: 0,
             behind_count: 0,
             current_branch: "main".to_string(),
+            tracks_upstream: true,
+            fetched: true,
         });
 
         let service = SyncService::new(Clone::clone(&repository), filesystem.clone());
@@ -206,7 +546,7 @@ mod tests {
     async fn test_sync_not_initialized() {
         let (service, _, _) = create_test_service();
 
-        let result = service.sync(false).await;
+        let result = service.sync(false, false).await;
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("not initialized"));
     }
@@ -221,16 +561,32 @@ mod tests {
                 remote: "https://github.com/user/dotfiles".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         let settings_content = settings.to_toml().unwrap();
         filesystem.add_file(&filesystem.dotf_settings_path(), &settings_content);
         filesystem.add_directory(&filesystem.dotf_repo_path());
 
-        let result = service.sync(false).await.unwrap();
+        let result = service.sync(false, false).await.unwrap();
 
         assert!(!result.had_uncommitted_changes);
         assert_eq!(result.commits_pulled, 0);
@@ -241,6 +597,188 @@ mod tests {
         assert_eq!(repository.get_pull_calls().len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_sync_recomputes_sparse_paths() {
+        let (service, repository, filesystem) = create_test_service();
+
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        settings.repository.sparse = true;
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo_path = filesystem.dotf_repo_path();
+        filesystem.add_directory(&repo_path);
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo_path),
+            "[symlinks]\n\"zsh/.zshrc\" = \"~/.zshrc\"\n\n[scripts.custom]\nfonts = \"scripts/fonts.sh\"\n",
+        );
+
+        service.sync(false, false).await.unwrap();
+
+        assert_eq!(
+            repository.get_sparse_calls(),
+            vec![(
+                repo_path,
+                Some(vec![
+                    "dotf.toml".to_string(),
+                    "scripts/fonts.sh".to_string(),
+                    "zsh/.zshrc".to_string(),
+                ])
+            )]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_follows_renamed_sources() {
+        let (service, repository, filesystem) = create_test_service();
+
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo_path = filesystem.dotf_repo_path();
+        filesystem.add_directory(&repo_path);
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo_path),
+            "[symlinks]\n\"zshrc\" = \"~/.zshrc\"\n\"vimrc\" = \"~/.vimrc\"\n",
+        );
+        filesystem.add_file(&format!("{}/zsh/.zshrc", repo_path), "");
+        filesystem.add_file(&format!("{}/vimrc", repo_path), "");
+        repository.set_revision(Some("abc123"), &[]);
+        repository.set_renames(&[("zshrc", "zsh/.zshrc"), ("vimrc", "vim/vimrc")]);
+
+        let result = service.sync(false, false).await.unwrap();
+        assert_eq!(result.renames.len(), 2);
+
+        // vimrc is still there, so only the zshrc entry moves
+        let moved = service.moved_sources(&result.renames).await.unwrap();
+        assert_eq!(
+            moved,
+            vec![Rename {
+                from: "zshrc".to_string(),
+                to: "zsh/.zshrc".to_string(),
+            }]
+        );
+
+        service.follow_renames(&moved).await.unwrap();
+        assert_eq!(
+            filesystem
+                .read_to_string(&format!("{}/dotf.toml", repo_path))
+                .await
+                .unwrap(),
+            "[symlinks]\n\"zsh/.zshrc\" = \"~/.zshrc\"\n\"vimrc\" = \"~/.vimrc\"\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sync_succeeds_when_renames_cannot_be_detected() {
+        let (service, repository, filesystem) = create_test_service();
+
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_directory(&filesystem.dotf_repo_path());
+        repository.set_revision(Some("abc123"), &[]);
+        repository.set_renames_error(Some("diff failed"));
+
+        let result = service.sync(false, false).await.unwrap();
+        assert!(result.renames.is_empty());
+        assert_eq!(repository.get_pull_calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_sync_refuses_rewritten_history_until_rescued() {
+        let (service, repository, filesystem) = create_test_service();
+        let settings = Settings {
+            repository: Repository {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_directory(&filesystem.dotf_repo_path());
+        repository.set_upstream_history(UpstreamHistory::Rewritten { local_commits: 2 });
+
+        let result = service.sync(false, false).await;
+        assert!(matches!(result, Err(DotfError::Repository(_))));
+        assert!(repository.get_pull_calls().is_empty());
+
+        let branch = service.rescue_and_reset().await.unwrap();
+        assert_eq!(branch, "dotf-rescue/test");
+        assert_eq!(
+            repository.get_rescue_calls(),
+            vec![filesystem.dotf_repo_path()]
+        );
+
+        service.sync(false, false).await.unwrap();
+        assert_eq!(repository.get_pull_calls().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_push_commits_and_guards_diverged_branch() {
+        let (service, mut repository, filesystem) = create_test_service();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_directory(&filesystem.dotf_repo_path());
+
+        repository.set_working_changes(&["zsh/.zshrc"]);
+        let result = service.push(None, false).await.unwrap();
+        assert_eq!(result.committed, vec!["zsh/.zshrc"]);
+        assert_eq!(result.commit_message.as_deref(), Some("Update zsh/.zshrc"));
+        assert_eq!(result.commits_pushed, 1);
+        assert_eq!(repository.get_push_calls().len(), 1);
+
+        // Local changes on top of new remote commits: only pushed after a rebase
+        repository.set_status_response(RepositoryStatus {
+            is_clean: false,
+            ahead_count: 1,
+            behind_count: 2,
+            current_branch: "main".to_string(),
+            tracks_upstream: true,
+            fetched: true,
+        });
+        repository.set_working_changes(&["git/.gitconfig", "vim/.vimrc"]);
+        assert!(service.push(Some("Tweak"), false).await.is_err());
+        assert_eq!(repository.get_commit_calls().len(), 1);
+        assert!(repository.get_pull_calls().is_empty());
+
+        let result = service.push(Some("Tweak"), true).await.unwrap();
+        assert_eq!(result.commit_message.as_deref(), Some("Tweak"));
+        assert_eq!(result.commits_rebased_onto, 2);
+        assert_eq!(result.commits_pushed, 2);
+        assert_eq!(repository.get_pull_calls().len(), 1);
+        assert_eq!(repository.get_push_calls().len(), 2);
+    }
+
     #[tokio::test]
     async fn test_sync_with_uncommitted_changes_without_force() {
         let (service, mut repository, filesystem) = create_test_service();
@@ -251,6 +789,8 @@ mod tests {
             ahead_count: 1,
             behind_count: 0,
             current_branch: "main".to_string(),
+            tracks_upstream: true,
+            fetched: true,
         });
 
         // Set up initialized state
@@ -259,21 +799,51 @@ mod tests {
                 remote: "https://github.com/user/dotfiles".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         let settings_content = settings.to_toml().unwrap();
         filesystem.add_file(&filesystem.dotf_settings_path(), &settings_content);
         filesystem.add_directory(&filesystem.dotf_repo_path());
 
-        let result = service.sync(false).await;
+        let result = service.sync(false, false).await;
         assert!(result.is_err());
         assert!(result
             .unwrap_err()
             .to_string()
             .contains("uncommitted changes"));
+        assert!(repository.get_pull_calls().is_empty());
+
+        // --autostash sets the changes aside for the pull and reapplies them
+        let result = service.sync(false, true).await.unwrap();
+        assert_eq!(result.stash, Some(StashOutcome::Restored));
+        assert_eq!(repository.get_pull_calls().len(), 1);
+        assert_eq!(repository.get_stash_calls(), vec!["stash", "pop"]);
+
+        repository.set_stash_conflicts(&["git/.gitconfig"]);
+        let result = service.sync(false, true).await.unwrap();
+        assert_eq!(
+            result.stash,
+            Some(StashOutcome::Conflicts(vec!["git/.gitconfig".to_string()]))
+        );
     }
 
     #[tokio::test]
@@ -286,9 +856,25 @@ mod tests {
                 remote: "https://github.com/user/dotfiles".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: Some(Utc::now()),
             initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         let settings_content = settings.to_toml().unwrap();
@@ -316,6 +902,8 @@ mod tests {
             ahead_count: 0,
             behind_count: 3,
             current_branch: "main".to_string(),
+            tracks_upstream: true,
+            fetched: true,
         });
 
         // Set up initialized state
@@ -324,9 +912,25 @@ mod tests {
                 remote: "https://github.com/user/dotfiles".to_string(),
                 branch: None,
                 local: None,
+                sparse: false,
             },
             last_sync: None,
             initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
         };
 
         let settings_content = settings.to_toml().unwrap();
@@ -346,4 +950,39 @@ mod tests {
             _ => panic!("Expected BehindRemote status"),
         }
     }
+
+    #[tokio::test]
+    async fn test_sync_overlays_clones_and_pulls() {
+        let (service, repository, filesystem) = create_test_service();
+        let mut settings = Settings::new("https://github.com/user/dotfiles");
+        for (name, branch) in [("work", Some("main")), ("laptop", None)] {
+            settings.repositories.push(NamedRepository {
+                name: name.to_string(),
+                remote: format!("https://github.com/user/{}", name),
+                branch: branch.map(str::to_string),
+            });
+        }
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let laptop = overlay_path(&filesystem, "laptop");
+        filesystem.add_directory(&laptop);
+
+        let results = service.sync_overlays(None).await.unwrap();
+        assert_eq!(results[0].outcome, OverlayOutcome::Cloned);
+        assert_eq!(results[1].outcome, OverlayOutcome::Pulled);
+        assert_eq!(
+            repository.clone_calls.lock().unwrap().clone(),
+            vec![(
+                "https://github.com/user/work#main".to_string(),
+                overlay_path(&filesystem, "work")
+            )]
+        );
+        assert_eq!(repository.pull_calls.lock().unwrap().clone(), vec![laptop]);
+
+        let results = service.sync_overlays(Some("laptop")).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(service.sync_overlays(Some("home")).await.is_err());
+    }
 }
@@ -0,0 +1,365 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::core::{
+    config::{dotf_config::ToolConfig, loader, DotfConfig, Settings},
+    state::{InstalledTool, StateStore},
+    tools::{tool_state, ToolInstaller, ToolState},
+};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::FileSystem;
+
+/// Where tools are installed when `install_path` is not set
+const DEFAULT_INSTALL_PATH: &str = "~/.local/bin";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolStatusInfo {
+    pub name: String,
+    /// Version requested in dotf.toml (a tag or `latest`)
+    pub wanted: String,
+    /// Release tag currently installed, if any
+    pub installed: Option<String>,
+    pub state: ToolState,
+}
+
+#[derive(Debug, Clone)]
+pub enum ToolInstallStatus {
+    Installed {
+        path: String,
+        verified: bool,
+    },
+    AlreadyCurrent,
+    /// A download was needed but the connection is metered
+    Deferred,
+    Failed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ToolInstallOutcome {
+    pub name: String,
+    /// Release tag that was installed or found up to date; empty if resolution failed
+    pub version: String,
+    pub status: ToolInstallStatus,
+}
+
+pub struct ToolsService<F> {
+    filesystem: F,
+    state_store: StateStore<F>,
+    metered: bool,
+}
+
+impl<F: FileSystem + Clone> ToolsService<F> {
+    pub fn new(filesystem: F) -> Self {
+        let state_store = StateStore::new(filesystem.clone());
+        Self {
+            filesystem,
+            state_store,
+            metered: false,
+        }
+    }
+
+    /// On a metered connection, releases are still looked up but not downloaded; the
+    /// tools are recorded as deferred instead
+    pub fn with_metered(mut self, metered: bool) -> Self {
+        self.metered = metered;
+        self
+    }
+
+    /// Compare each `[tools]` entry with what is installed, without touching the network
+    pub async fn statuses(&self) -> DotfResult<Vec<ToolStatusInfo>> {
+        let config = self.load_config().await?;
+        let state = self.state_store.load().await?;
+
+        let mut statuses = Vec::new();
+        for (name, tool) in sorted_tools(&config) {
+            let installed = state.tools.get(name);
+            let binary_exists = match installed {
+                Some(record) => self.filesystem.exists(&record.path).await?,
+                None => false,
+            };
+
+            statuses.push(ToolStatusInfo {
+                name: name.clone(),
+                wanted: tool.version.clone(),
+                installed: installed.map(|record| record.version.clone()),
+                state: tool_state(&tool.version, installed, binary_exists),
+            });
+        }
+
+        Ok(statuses)
+    }
+
+    /// Install every configured tool, or only `only`, skipping tools that are current.
+    ///
+    /// A failing tool does not stop the others; its outcome carries the error.
+    pub async fn install(
+        &self,
+        installer: &ToolInstaller,
+        only: Option<&str>,
+    ) -> DotfResult<Vec<ToolInstallOutcome>> {
+        let config = self.load_config().await?;
+
+        if let Some(name) = only {
+            if !config.tools.contains_key(name) {
+                return Err(DotfError::Config(format!(
+                    "Tool '{}' is not declared in [tools]",
+                    name
+                )));
+            }
+        }
+
+        let statuses = self.statuses().await?;
+        let mut outcomes = Vec::new();
+
+        for (name, tool) in sorted_tools(&config) {
+            if only.is_some_and(|only| only != name) {
+                continue;
+            }
+
+            let current = statuses
+                .iter()
+                .find(|status| &status.name == name)
+                .filter(|status| status.state != ToolState::Missing)
+                .and_then(|status| status.installed.clone());
+
+            let outcome = match self.install_tool(installer, name, tool, current).await {
+                Ok(outcome) => outcome,
+                Err(e) => ToolInstallOutcome {
+                    name: name.clone(),
+                    version: String::new(),
+                    status: ToolInstallStatus::Failed(e.to_string()),
+                },
+            };
+            outcomes.push(outcome);
+        }
+
+        let attempted: Vec<String> = outcomes
+            .iter()
+            .map(|outcome| outcome.name.clone())
+            .collect();
+        let deferred: Vec<String> = outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome.status, ToolInstallStatus::Deferred))
+            .map(|outcome| outcome.name.clone())
+            .collect();
+        self.state_store
+            .record_deferred("tools", &attempted, &deferred)
+            .await?;
+
+        Ok(outcomes)
+    }
+
+    async fn install_tool(
+        &self,
+        installer: &ToolInstaller,
+        name: &str,
+        tool: &ToolConfig,
+        current: Option<String>,
+    ) -> DotfResult<ToolInstallOutcome> {
+        // Pinned versions can be checked offline; `latest` needs the release lookup
+        if current.as_deref() == Some(tool.version.as_str()) {
+            return Ok(ToolInstallOutcome {
+                name: name.to_string(),
+                version: tool.version.clone(),
+                status: ToolInstallStatus::AlreadyCurrent,
+            });
+        }
+
+        let release = installer.resolve(tool).await?;
+        if current.as_deref() == Some(release.tag.as_str()) {
+            return Ok(ToolInstallOutcome {
+                name: name.to_string(),
+                version: release.tag,
+                status: ToolInstallStatus::AlreadyCurrent,
+            });
+        }
+
+        if self.metered {
+            return Ok(ToolInstallOutcome {
+                name: name.to_string(),
+                version: release.tag,
+                status: ToolInstallStatus::Deferred,
+            });
+        }
+
+        let bin_name = tool.bin.as_deref().unwrap_or(name);
+        let install_dir = self.install_dir(tool)?;
+        let installation = installer
+            .install(tool, bin_name, &release, &install_dir)
+            .await?;
+
+        self.state_store
+            .record_tool(
+                name,
+                InstalledTool {
+                    version: installation.tag.clone(),
+                    asset: installation.asset,
+                    path: installation.path.clone(),
+                    sha256: installation.sha256,
+                    verified: installation.verified,
+                    installed_at: self.state_store.now(),
+                },
+            )
+            .await?;
+
+        Ok(ToolInstallOutcome {
+            name: name.to_string(),
+            version: installation.tag,
+            status: ToolInstallStatus::Installed {
+                path: installation.path,
+                verified: installation.verified,
+            },
+        })
+    }
+
+    fn install_dir(&self, tool: &ToolConfig) -> DotfResult<PathBuf> {
+        let path = tool.install_path.as_deref().unwrap_or(DEFAULT_INSTALL_PATH);
+        if path == "~" || path.starts_with("~/") {
+            let home = dirs::home_dir().ok_or_else(|| {
+                DotfError::Operation("Could not determine home directory".to_string())
+            })?;
+            Ok(PathBuf::from(path.replacen(
+                "~",
+                &home.to_string_lossy(),
+                1,
+            )))
+        } else {
+            Ok(PathBuf::from(path))
+        }
+    }
+
+    async fn load_config(&self) -> DotfResult<DotfConfig> {
+        let settings = self.load_settings().await?;
+        loader::read_config(
+            &self.filesystem,
+            &loader::repo_path(&self.filesystem, &settings),
+        )
+        .await
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        let settings: Settings = Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))?;
+
+        Ok(settings)
+    }
+}
+
+fn sorted_tools(config: &DotfConfig) -> Vec<(&String, &ToolConfig)> {
+    let mut tools: Vec<_> = config.tools.iter().collect();
+    tools.sort_by(|a, b| a.0.cmp(b.0));
+    tools
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::config::{settings::Repository as RepositorySettings, Settings};
+    use crate::traits::filesystem::tests::MockFileSystem;
+    use chrono::Utc;
+
+    fn setup(tools: &str) -> MockFileSystem {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings {
+            repository: RepositorySettings {
+                remote: "https://github.com/user/dotfiles".to_string(),
+                branch: None,
+                local: None,
+                sparse: false,
+            },
+            last_sync: None,
+            initialized_at: Utc::now(),
+            commit: Default::default(),
+            prompts: Default::default(),
+            scripts: Default::default(),
+            aliases: Default::default(),
+            widget: Default::default(),
+            profile: None,
+            packages: Default::default(),
+            logging: Default::default(),
+            symlinks: Default::default(),
+            diff: Default::default(),
+            backups: Default::default(),
+            repositories: Default::default(),
+            features: Default::default(),
+            network: Default::default(),
+            variables: Default::default(),
+        };
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        filesystem.add_file(
+            &format!("{}/dotf.toml", filesystem.dotf_repo_path()),
+            &format!("[symlinks]\n\n{}", tools),
+        );
+        filesystem
+    }
+
+    #[tokio::test]
+    async fn test_statuses() {
+        let filesystem = setup(
+            r#"
+[tools.rg]
+repo = "BurntSushi/ripgrep"
+version = "14.1.0"
+
+[tools.fd]
+repo = "sharkdp/fd"
+version = "v10.2.0"
+
+[tools.jq]
+repo = "jqlang/jq"
+"#,
+        );
+
+        let service = ToolsService::new(filesystem.clone());
+        service
+            .state_store
+            .record_tool(
+                "rg",
+                InstalledTool {
+                    version: "14.0.0".to_string(),
+                    asset: "ripgrep.tar.gz".to_string(),
+                    path: "/home/user/.local/bin/rg".to_string(),
+                    sha256: String::new(),
+                    verified: true,
+                    installed_at: Utc::now(),
+                },
+            )
+            .await
+            .unwrap();
+        filesystem.add_file("/home/user/.local/bin/rg", "");
+
+        let statuses = service.statuses().await.unwrap();
+        let summary: Vec<(&str, &ToolState)> = statuses
+            .iter()
+            .map(|status| (status.name.as_str(), &status.state))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("fd", &ToolState::Missing),
+                ("jq", &ToolState::Missing),
+                ("rg", &ToolState::Outdated),
+            ]
+        );
+        assert_eq!(statuses[1].wanted, "latest");
+        assert_eq!(statuses[2].installed.as_deref(), Some("14.0.0"));
+    }
+
+    #[tokio::test]
+    async fn test_install_unknown_tool() {
+        let service = ToolsService::new(setup(""));
+        let result = service.install(&ToolInstaller::new(), Some("rg")).await;
+        assert!(matches!(result.unwrap_err(), DotfError::Config(_)));
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::path::Path;
+
+use crate::core::config::{loader, Settings, SymlinkSettings};
+use crate::error::{DotfError, DotfResult};
+use crate::traits::filesystem::{ChangeStream, FileSystem, WatchPath};
+use crate::utils::platform::detect_platform;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchEvent {
+    /// A new file in a linked directory got its symlink
+    Linked {
+        source_path: String,
+        target_path: String,
+    },
+    /// A new file in a linked directory was not linked because its target is taken
+    Blocked {
+        source_path: String,
+        target_path: String,
+    },
+    /// A managed symlink was replaced by a regular file or directory
+    Replaced {
+        source_path: String,
+        target_path: String,
+    },
+}
+
+/// What `dotf watch` looks after, built from dotf.toml when it starts
+#[derive(Debug, Clone, Default)]
+pub struct WatchPlan {
+    /// Linked directories: repository source to target
+    pub directories: Vec<(String, String)>,
+    /// Every expected link: target to source
+    pub links: BTreeMap<String, String>,
+    /// Targets already reported as replaced, so each is reported once
+    replaced: HashSet<String>,
+    /// Targets this machine never links
+    disabled: SymlinkSettings,
+}
+
+impl WatchPlan {
+    /// Sources of linked directories, recursively, and the directories holding each target
+    pub fn watch_paths(&self) -> Vec<WatchPath> {
+        let parents: BTreeSet<String> = self
+            .links
+            .keys()
+            .filter_map(|target| Path::new(target).parent())
+            .map(|parent| parent.to_string_lossy().to_string())
+            .collect();
+        self.directories
+            .iter()
+            .map(|(source, _)| WatchPath {
+                path: source.clone(),
+                recursive: true,
+            })
+            .chain(parents.into_iter().map(|path| WatchPath {
+                path,
+                recursive: false,
+            }))
+            .collect()
+    }
+}
+
+/// Keeps links in place while running: files added to a linked directory in the
+/// repository are linked right away, and targets replaced by real files are reported
+pub struct WatchService<F> {
+    filesystem: F,
+}
+
+impl<F: FileSystem> WatchService<F> {
+    pub fn new(filesystem: F) -> Self {
+        Self { filesystem }
+    }
+
+    pub async fn plan(&self) -> DotfResult<WatchPlan> {
+        let settings = self.load_settings().await?;
+        let config = loader::load_config(&self.filesystem, &settings).await?;
+        let repo_path = settings
+            .repository
+            .local
+            .clone()
+            .unwrap_or_else(|| self.filesystem.dotf_repo_path());
+
+        let mut symlinks = config.symlinks.clone();
+        if let Some(platform_config) = config.platform.for_platform(&detect_platform()) {
+            symlinks.extend(platform_config.symlinks.clone());
+        }
+
+        let mut plan = WatchPlan {
+            disabled: settings.symlinks.clone(),
+            ..Default::default()
+        };
+        for (source, target) in &symlinks {
+            let target = expand_home(target)?;
+            if plan.disabled.is_disabled(&target) {
+                continue;
+            }
+            let source = if source.starts_with('/') {
+                source.clone()
+            } else {
+                format!("{}/{}", repo_path, source)
+            };
+
+            if self.filesystem.exists(&source).await? && self.filesystem.is_dir(&source).await? {
+                for (file, file_target) in self.expand_directory(&source, &target).await? {
+                    if !plan.disabled.is_disabled(&file_target) {
+                        plan.links.insert(file_target, file);
+                    }
+                }
+                plan.directories.push((source, target));
+            } else {
+                plan.links.insert(target, source);
+            }
+        }
+        Ok(plan)
+    }
+
+    /// Start watching the plan's paths that exist
+    pub async fn start(&self, plan: &WatchPlan) -> DotfResult<ChangeStream> {
+        let mut paths = Vec::new();
+        for path in plan.watch_paths() {
+            if self.filesystem.exists(&path.path).await? {
+                paths.push(path);
+            }
+        }
+        self.filesystem.watch(&paths).await
+    }
+
+    /// React to a change at `path`
+    pub async fn handle_change(
+        &self,
+        plan: &mut WatchPlan,
+        path: &str,
+    ) -> DotfResult<Vec<WatchEvent>> {
+        let directory = plan.directories.iter().find_map(|(source, target)| {
+            path.strip_prefix(source.as_str())
+                .and_then(|rest| rest.strip_prefix('/'))
+                .map(|relative| format!("{}/{}", target, relative))
+        });
+        if let Some(target) = directory {
+            return self.link_new_files(plan, path, &target).await;
+        }
+
+        let Some(source) = plan.links.get(path) else {
+            return Ok(Vec::new());
+        };
+        let replaced = !self.filesystem.is_symlink(path).await.unwrap_or(false)
+            && self.filesystem.exists(path).await?;
+        if !replaced {
+            plan.replaced.remove(path);
+            return Ok(Vec::new());
+        }
+        if !plan.replaced.insert(path.to_string()) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![WatchEvent::Replaced {
+            source_path: source.clone(),
+            target_path: path.to_string(),
+        }])
+    }
+
+    /// Link `source` to `target`, or every file below it when it is a new directory
+    async fn link_new_files(
+        &self,
+        plan: &mut WatchPlan,
+        source: &str,
+        target: &str,
+    ) -> DotfResult<Vec<WatchEvent>> {
+        if !self.filesystem.exists(source).await? {
+            return Ok(Vec::new());
+        }
+        let files = if self.filesystem.is_dir(source).await? {
+            self.expand_directory(source, target).await?
+        } else {
+            vec![(source.to_string(), target.to_string())]
+        };
+
+        let mut events = Vec::new();
+        for (source_path, target_path) in files {
+            if plan.disabled.is_disabled(&target_path) {
+                continue;
+            }
+            let is_link = self
+                .filesystem
+                .is_symlink(&target_path)
+                .await
+                .unwrap_or(false);
+            if is_link
+                && self
+                    .filesystem
+                    .read_link(&target_path)
+                    .await?
+                    .to_string_lossy()
+                    == source_path
+            {
+                continue;
+            }
+            if is_link || self.filesystem.exists(&target_path).await? {
+                events.push(WatchEvent::Blocked {
+                    source_path,
+                    target_path,
+                });
+                continue;
+            }
+
+            if let Some(parent) = Path::new(&target_path).parent() {
+                self.filesystem
+                    .create_dir_all(&parent.to_string_lossy())
+                    .await?;
+            }
+            self.filesystem
+                .create_symlink(&source_path, &target_path)
+                .await?;
+            plan.links.insert(target_path.clone(), source_path.clone());
+            events.push(WatchEvent::Linked {
+                source_path,
+                target_path,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Each file below `source_dir` with its target below `target_dir`
+    async fn expand_directory(
+        &self,
+        source_dir: &str,
+        target_dir: &str,
+    ) -> DotfResult<Vec<(String, String)>> {
+        let mut files = Vec::new();
+        let mut dir_stack = vec![(source_dir.to_string(), target_dir.to_string())];
+
+        while let Some((current_source, current_target)) = dir_stack.pop() {
+            for entry in self.filesystem.list_entries(&current_source).await? {
+                let relative_path = entry
+                    .path
+                    .strip_prefix(&current_source)
+                    .unwrap_or(&entry.path)
+                    .trim_start_matches('/');
+                let target_path = format!("{}/{}", current_target, relative_path);
+
+                if entry.is_dir && !entry.is_symlink {
+                    dir_stack.push((entry.path, target_path));
+                } else if entry.is_file || entry.is_symlink {
+                    files.push((entry.path, target_path));
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn load_settings(&self) -> DotfResult<Settings> {
+        let settings_path = self.filesystem.dotf_settings_path();
+
+        if !self.filesystem.exists(&settings_path).await? {
+            return Err(DotfError::NotInitialized);
+        }
+
+        let content = self.filesystem.read_to_string(&settings_path).await?;
+        Settings::from_toml(&content)
+            .map_err(|e| DotfError::Config(format!("Failed to parse settings: {}", e)))
+    }
+}
+
+fn expand_home(target: &str) -> DotfResult<String> {
+    if !target.starts_with("~/") {
+        return Ok(target.to_string());
+    }
+    let home = dirs::home_dir()
+        .ok_or_else(|| DotfError::Operation("Could not determine home directory".to_string()))?;
+    Ok(target.replacen('~', &home.to_string_lossy(), 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::filesystem::tests::MockFileSystem;
+
+    #[tokio::test]
+    async fn test_watch_links_new_files_and_reports_replaced_targets() {
+        let filesystem = MockFileSystem::new();
+        let settings = Settings::new("https://github.com/user/dotfiles");
+        filesystem.add_file(
+            &filesystem.dotf_settings_path(),
+            &settings.to_toml().unwrap(),
+        );
+        let repo = filesystem.dotf_repo_path();
+        filesystem.add_file(
+            &format!("{}/dotf.toml", repo),
+            r#"[symlinks]
+"nvim" = "/home/user/.config/nvim"
+"zsh/.zshrc" = "/home/user/.zshrc"
+"#,
+        );
+        filesystem.add_directory(&format!("{}/nvim", repo));
+        filesystem.add_file(&format!("{}/nvim/init.lua", repo), "");
+        filesystem.add_file(&format!("{}/zsh/.zshrc", repo), "");
+        filesystem
+            .create_symlink(
+                &format!("{}/nvim/init.lua", repo),
+                "/home/user/.config/nvim/init.lua",
+            )
+            .await
+            .unwrap();
+        filesystem
+            .create_symlink(&format!("{}/zsh/.zshrc", repo), "/home/user/.zshrc")
+            .await
+            .unwrap();
+
+        let service = WatchService::new(filesystem.clone());
+        let mut plan = service.plan().await.unwrap();
+        let mut stream = service.start(&plan).await.unwrap();
+        assert!(filesystem.watched.lock().unwrap().contains(&WatchPath {
+            path: format!("{}/nvim", repo),
+            recursive: true,
+        }));
+
+        // A file added to the linked directory is linked
+        let new_file = format!("{}/nvim/lua/plugins.lua", repo);
+        filesystem.add_file(&new_file, "");
+        filesystem.emit_change(&new_file);
+        let path = stream.next().await.unwrap();
+        assert_eq!(
+            service.handle_change(&mut plan, &path).await.unwrap(),
+            vec![WatchEvent::Linked {
+                source_path: new_file.clone(),
+                target_path: "/home/user/.config/nvim/lua/plugins.lua".to_string(),
+            }]
+        );
+        assert_eq!(
+            filesystem
+                .get_symlinks()
+                .get("/home/user/.config/nvim/lua/plugins.lua"),
+            Some(&new_file)
+        );
+        assert!(service
+            .handle_change(&mut plan, &new_file)
+            .await
+            .unwrap()
+            .is_empty());
+
+        // An editor replaced the link with a real file; reported once
+        filesystem
+            .symlinks
+            .lock()
+            .unwrap()
+            .remove("/home/user/.zshrc");
+        filesystem.add_file("/home/user/.zshrc", "edited");
+        let replaced = service
+            .handle_change(&mut plan, "/home/user/.zshrc")
+            .await
+            .unwrap();
+        assert_eq!(
+            replaced,
+            vec![WatchEvent::Replaced {
+                source_path: format!("{}/zsh/.zshrc", repo),
+                target_path: "/home/user/.zshrc".to_string(),
+            }]
+        );
+        assert!(service
+            .handle_change(&mut plan, "/home/user/.zshrc")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,68 @@
+use crate::core::secrets::SecretBackend;
+use crate::error::DotfResult;
+use async_trait::async_trait;
+
+/// Encrypts and decrypts `[secrets]` files with an external tool
+#[async_trait]
+pub trait Cipher: Send + Sync {
+    /// ASCII-armored ciphertext of `plaintext`, readable by each of `recipients`
+    async fn encrypt(
+        &self,
+        backend: SecretBackend,
+        recipients: &[String],
+        plaintext: &str,
+    ) -> DotfResult<String>;
+    /// Plaintext of `ciphertext`; age reads its key from `identity`, gpg asks its agent
+    async fn decrypt(
+        &self,
+        backend: SecretBackend,
+        identity: Option<&str>,
+        ciphertext: &str,
+    ) -> DotfResult<String>;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::error::DotfError;
+
+    const HEADER: &str = "-----BEGIN MOCK ENCRYPTED FILE-----\n";
+
+    /// Reversible stand-in: the ciphertext is the plaintext behind a header
+    #[derive(Clone, Default)]
+    pub struct MockCipher;
+
+    impl MockCipher {
+        pub fn new() -> Self {
+            Self
+        }
+
+        pub fn encrypted(plaintext: &str) -> String {
+            format!("{}{}", HEADER, plaintext)
+        }
+    }
+
+    #[async_trait]
+    impl Cipher for MockCipher {
+        async fn encrypt(
+            &self,
+            _backend: SecretBackend,
+            _recipients: &[String],
+            plaintext: &str,
+        ) -> DotfResult<String> {
+            Ok(Self::encrypted(plaintext))
+        }
+
+        async fn decrypt(
+            &self,
+            _backend: SecretBackend,
+            _identity: Option<&str>,
+            ciphertext: &str,
+        ) -> DotfResult<String> {
+            ciphertext
+                .strip_prefix(HEADER)
+                .map(str::to_string)
+                .ok_or_else(|| DotfError::Operation("not a mock ciphertext".to_string()))
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+
+/// Source of the current time, injectable so timestamps are deterministic in tests
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Source of short unique identifiers used to disambiguate generated file names
+pub trait IdGenerator: Send + Sync {
+    fn next_id(&self) -> String;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    pub struct MockClock {
+        pub now: Arc<Mutex<DateTime<Utc>>>,
+    }
+
+    impl MockClock {
+        pub fn new(now: DateTime<Utc>) -> Self {
+            Self {
+                now: Arc::new(Mutex::new(now)),
+            }
+        }
+
+        pub fn set(&self, now: DateTime<Utc>) {
+            *self.now.lock().unwrap() = now;
+        }
+
+        pub fn advance(&self, duration: chrono::Duration) {
+            let mut now = self.now.lock().unwrap();
+            *now += duration;
+        }
+    }
+
+    impl Clock for MockClock {
+        fn now(&self) -> DateTime<Utc> {
+            *self.now.lock().unwrap()
+        }
+    }
+
+    #[derive(Clone, Default)]
+    pub struct SequentialIdGenerator {
+        pub counter: Arc<Mutex<u64>>,
+    }
+
+    impl SequentialIdGenerator {
+        pub fn new() -> Self {
+            Self::default()
+        }
+    }
+
+    impl IdGenerator for SequentialIdGenerator {
+        fn next_id(&self) -> String {
+            let mut counter = self.counter.lock().unwrap();
+            *counter += 1;
+            format!("{:08x}", *counter)
+        }
+    }
+}
+
+#[cfg(test)]
+mod clock_tests {
+    use super::tests::{MockClock, SequentialIdGenerator};
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_mock_clock() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        assert_eq!(clock.now(), start);
+
+        clock.advance(chrono::Duration::seconds(90));
+        assert_eq!(
+            clock.now(),
+            Utc.with_ymd_and_hms(2024, 1, 1, 12, 1, 30).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_sequential_id_generator() {
+        let ids = SequentialIdGenerator::new();
+        assert_eq!(ids.next_id(), "00000001");
+        assert_eq!(ids.next_id(), "00000002");
+    }
+}
@@ -0,0 +1,53 @@
+use crate::error::DotfResult;
+use async_trait::async_trait;
+
+/// The current user's crontab
+#[async_trait]
+pub trait Crontab: Send + Sync {
+    /// Full crontab content; empty if the user has no crontab yet
+    async fn read(&self) -> DotfResult<String>;
+    /// Replace the whole crontab with `content`
+    async fn write(&self, content: &str) -> DotfResult<()>;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    pub struct MockCrontab {
+        pub content: Arc<Mutex<String>>,
+        pub write_count: Arc<Mutex<usize>>,
+    }
+
+    impl MockCrontab {
+        pub fn new(content: &str) -> Self {
+            Self {
+                content: Arc::new(Mutex::new(content.to_string())),
+                write_count: Arc::new(Mutex::new(0)),
+            }
+        }
+
+        pub fn content(&self) -> String {
+            self.content.lock().unwrap().clone()
+        }
+
+        pub fn get_write_count(&self) -> usize {
+            *self.write_count.lock().unwrap()
+        }
+    }
+
+    #[async_trait]
+    impl Crontab for MockCrontab {
+        async fn read(&self) -> DotfResult<String> {
+            Ok(self.content())
+        }
+
+        async fn write(&self, content: &str) -> DotfResult<()> {
+            *self.content.lock().unwrap() = content.to_string();
+            *self.write_count.lock().unwrap() += 1;
+            Ok(())
+        }
+    }
+}
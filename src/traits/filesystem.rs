@@ -1,6 +1,9 @@
 use crate::error::DotfResult;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
 use std::path::PathBuf;
+use tokio::sync::mpsc::UnboundedReceiver;
 
 #[derive(Debug, Clone)]
 pub struct FileEntry {
@@ -10,6 +13,41 @@ pub struct FileEntry {
     pub is_symlink: bool,
 }
 
+/// Numeric user and group ids owning a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileOwner {
+    pub uid: u32,
+    pub gid: u32,
+}
+
+/// A directory to watch; `recursive` includes everything below it
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchPath {
+    pub path: String,
+    pub recursive: bool,
+}
+
+/// Paths reported changed by [`FileSystem::watch`]; watching stops when it is dropped
+pub struct ChangeStream {
+    receiver: UnboundedReceiver<String>,
+    _watcher: Box<dyn Any + Send>,
+}
+
+impl ChangeStream {
+    /// `watcher` is kept alive for as long as the stream
+    pub fn new(receiver: UnboundedReceiver<String>, watcher: Box<dyn Any + Send>) -> Self {
+        Self {
+            receiver,
+            _watcher: watcher,
+        }
+    }
+
+    /// The next changed path, or `None` once the watcher has stopped
+    pub async fn next(&mut self) -> Option<String> {
+        self.receiver.recv().await
+    }
+}
+
 #[async_trait]
 pub trait FileSystem: Send + Sync {
     async fn exists(&self, path: &str) -> DotfResult<bool>;
@@ -18,12 +56,32 @@ pub trait FileSystem: Send + Sync {
     async fn remove_file(&self, path: &str) -> DotfResult<()>;
     async fn remove_dir(&self, path: &str) -> DotfResult<()>;
     async fn copy_file(&self, source: &str, target: &str) -> DotfResult<()>;
+    /// Create `target` as another name for the file `source`; both must be on the same
+    /// filesystem
+    async fn hard_link(&self, source: &str, target: &str) -> DotfResult<()>;
+    /// Move `source` to `target`, which fails with a cross-device error when they lie on
+    /// different filesystems
+    async fn rename(&self, source: &str, target: &str) -> DotfResult<()>;
+    async fn read(&self, path: &str) -> DotfResult<Vec<u8>>;
     async fn read_to_string(&self, path: &str) -> DotfResult<String>;
     async fn write(&self, path: &str, content: &str) -> DotfResult<()>;
     async fn is_symlink(&self, path: &str) -> DotfResult<bool>;
     async fn read_link(&self, path: &str) -> DotfResult<PathBuf>;
     async fn is_dir(&self, path: &str) -> DotfResult<bool>;
     async fn list_entries(&self, path: &str) -> DotfResult<Vec<FileEntry>>;
+    /// Open `path` through any symlinks the way an app reading it would, listing it when
+    /// it is a directory; fails with the error the app would get
+    async fn check_readable(&self, path: &str) -> DotfResult<()>;
+    /// Permission bits of `path` (following symlinks); `None` where the platform has none
+    async fn mode(&self, path: &str) -> DotfResult<Option<u32>>;
+    async fn set_mode(&self, path: &str, mode: u32) -> DotfResult<()>;
+    /// Owning user and group of `path` (following symlinks); `None` where the platform
+    /// has none
+    async fn owner(&self, path: &str) -> DotfResult<Option<FileOwner>>;
+    /// Fails with a permission error when changing ownership needs privileges
+    async fn set_owner(&self, path: &str, owner: FileOwner) -> DotfResult<()>;
+    /// Report paths created, modified or removed in `paths` until the stream is dropped
+    async fn watch(&self, paths: &[WatchPath]) -> DotfResult<ChangeStream>;
 
     // Dotf specific path operations
     fn dotf_directory(&self) -> String {
@@ -43,6 +101,16 @@ pub trait FileSystem: Send + Sync {
             .to_string()
     }
 
+    /// Directory holding the checkouts of overlay repositories (`[[repositories]]`)
+    fn dotf_repos_path(&self) -> String {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".dotf")
+            .join("repos")
+            .to_string_lossy()
+            .to_string()
+    }
+
     fn dotf_settings_path(&self) -> String {
         dirs::home_dir()
             .unwrap_or_default()
@@ -61,6 +129,54 @@ pub trait FileSystem: Send + Sync {
             .to_string()
     }
 
+    fn dotf_history_path(&self) -> String {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".dotf")
+            .join("history.jsonl")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Per-phase timings of recent invocations, read by `dotf doctor --slow`
+    fn dotf_timings_path(&self) -> String {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".dotf")
+            .join("timings.jsonl")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn dotf_state_path(&self) -> String {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".dotf")
+            .join("state.json")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    fn dotf_logs_path(&self) -> String {
+        dirs::home_dir()
+            .unwrap_or_default()
+            .join(".dotf")
+            .join("logs")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Shell fragment generated from `[env]`
+    fn dotf_env_path(&self) -> String {
+        std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| dirs::home_dir().unwrap_or_default().join(".config"))
+            .join("dotf")
+            .join("env.sh")
+            .to_string_lossy()
+            .to_string()
+    }
+
     async fn create_dotf_directory(&self) -> DotfResult<()> {
         let dotf_dir = self.dotf_directory();
         self.create_dir_all(&dotf_dir).await
@@ -72,12 +188,21 @@ pub mod tests {
     use super::*;
     use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
 
     #[derive(Clone)]
     pub struct MockFileSystem {
         pub files: Arc<Mutex<HashMap<String, String>>>,
         pub directories: Arc<Mutex<Vec<String>>>,
         pub symlinks: Arc<Mutex<HashMap<String, String>>>,
+        pub modes: Arc<Mutex<HashMap<String, u32>>>,
+        pub owners: Arc<Mutex<HashMap<String, FileOwner>>>,
+        pub chown_denied: Arc<Mutex<bool>>,
+        pub read_only: Arc<Mutex<Vec<String>>>,
+        pub unreadable: Arc<Mutex<Vec<String>>>,
+        pub mounts: Arc<Mutex<Vec<String>>>,
+        pub watched: Arc<Mutex<Vec<WatchPath>>>,
+        pub watchers: Arc<Mutex<Vec<UnboundedSender<String>>>>,
     }
 
     impl Default for MockFileSystem {
@@ -87,11 +212,25 @@ pub mod tests {
     }
 
     impl MockFileSystem {
+        /// Owner of every file that was not given another one
+        pub const USER: FileOwner = FileOwner {
+            uid: 1000,
+            gid: 1000,
+        };
+
         pub fn new() -> Self {
             Self {
                 files: Arc::new(Mutex::new(HashMap::new())),
                 directories: Arc::new(Mutex::new(Vec::new())),
                 symlinks: Arc::new(Mutex::new(HashMap::new())),
+                modes: Arc::new(Mutex::new(HashMap::new())),
+                owners: Arc::new(Mutex::new(HashMap::new())),
+                chown_denied: Arc::new(Mutex::new(false)),
+                read_only: Arc::new(Mutex::new(Vec::new())),
+                unreadable: Arc::new(Mutex::new(Vec::new())),
+                mounts: Arc::new(Mutex::new(Vec::new())),
+                watched: Arc::new(Mutex::new(Vec::new())),
+                watchers: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
@@ -109,6 +248,60 @@ pub mod tests {
         pub fn get_symlinks(&self) -> HashMap<String, String> {
             self.symlinks.lock().unwrap().clone()
         }
+
+        /// Fail directory and symlink creation under `path` with a permission error
+        pub fn add_read_only(&self, path: &str) {
+            self.read_only.lock().unwrap().push(path.to_string());
+        }
+
+        /// Fail reads of anything under `path` with a permission error, like a directory
+        /// owned by another user with mode 700
+        pub fn add_unreadable(&self, path: &str) {
+            self.unreadable.lock().unwrap().push(path.to_string());
+        }
+
+        /// Fail `set_owner` for any owner but [`MockFileSystem::USER`], like an
+        /// unprivileged user would
+        pub fn deny_chown(&self) {
+            *self.chown_denied.lock().unwrap() = true;
+        }
+
+        /// Treat `path` as a separate filesystem: renames into or out of it fail like
+        /// they do across real mount points
+        pub fn add_mount(&self, path: &str) {
+            self.mounts.lock().unwrap().push(path.to_string());
+        }
+
+        /// Report `path` as changed to every open [`ChangeStream`]
+        pub fn emit_change(&self, path: &str) {
+            for sender in self.watchers.lock().unwrap().iter() {
+                let _ = sender.send(path.to_string());
+            }
+        }
+
+        fn check_writable(&self, path: &str) -> DotfResult<()> {
+            let read_only = self.read_only.lock().unwrap();
+            if read_only
+                .iter()
+                .any(|dir| std::path::Path::new(path).starts_with(dir))
+            {
+                return Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("Permission denied: {}", path),
+                )));
+            }
+            Ok(())
+        }
+
+        fn mount_of(&self, path: &str) -> Option<String> {
+            self.mounts
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|mount| std::path::Path::new(path).starts_with(mount))
+                .max_by_key(|mount| mount.len())
+                .cloned()
+        }
     }
 
     #[async_trait]
@@ -123,11 +316,13 @@ pub mod tests {
         }
 
         async fn create_dir_all(&self, path: &str) -> DotfResult<()> {
+            self.check_writable(path)?;
             self.directories.lock().unwrap().push(path.to_string());
             Ok(())
         }
 
         async fn create_symlink(&self, source: &str, target: &str) -> DotfResult<()> {
+            self.check_writable(target)?;
             self.symlinks
                 .lock()
                 .unwrap()
@@ -136,38 +331,64 @@ pub mod tests {
         }
 
         async fn remove_file(&self, path: &str) -> DotfResult<()> {
+            self.check_writable(path)?;
+            // Like the real one, a directory goes with everything in it
+            if self
+                .directories
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|dir| dir == path)
+            {
+                return self.remove_dir(path).await;
+            }
             self.files.lock().unwrap().remove(path);
             self.symlinks.lock().unwrap().remove(path);
+            self.modes.lock().unwrap().remove(path);
+            self.owners.lock().unwrap().remove(path);
             Ok(())
         }
 
         async fn remove_dir(&self, path: &str) -> DotfResult<()> {
-            // Remove directory itself
-            self.directories.lock().unwrap().retain(|p| p != path);
-
-            // Remove all files and subdirectories under this path
             let path_prefix = if path.ends_with('/') {
                 path.to_string()
             } else {
                 format!("{}/", path)
             };
+            let under = |p: &str| p == path || p.starts_with(&path_prefix);
 
-            self.files
-                .lock()
-                .unwrap()
-                .retain(|file_path, _| !file_path.starts_with(&path_prefix) && file_path != path);
-
-            self.directories
-                .lock()
-                .unwrap()
-                .retain(|dir_path| !dir_path.starts_with(&path_prefix) && dir_path != path);
+            // Like remove_dir_all, whatever sits in a read-only directory stays, and so
+            // do the directories holding it; everything else goes
+            let kept: Vec<String> = {
+                let files = self.files.lock().unwrap();
+                let symlinks = self.symlinks.lock().unwrap();
+                let directories = self.directories.lock().unwrap();
+                files
+                    .keys()
+                    .chain(symlinks.keys())
+                    .chain(directories.iter())
+                    .filter(|p| under(p) && self.check_writable(p).is_err())
+                    .cloned()
+                    .collect()
+            };
+            let removable = |p: &str| {
+                under(p)
+                    && !kept
+                        .iter()
+                        .any(|kept| std::path::Path::new(kept).starts_with(p))
+            };
 
-            self.symlinks
-                .lock()
-                .unwrap()
-                .retain(|link_path, _| !link_path.starts_with(&path_prefix) && link_path != path);
+            self.files.lock().unwrap().retain(|p, _| !removable(p));
+            self.directories.lock().unwrap().retain(|p| !removable(p));
+            self.symlinks.lock().unwrap().retain(|p, _| !removable(p));
 
-            Ok(())
+            match kept.first() {
+                Some(kept) => Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("Permission denied: {}", kept),
+                ))),
+                None => Ok(()),
+            }
         }
 
         async fn copy_file(&self, source: &str, target: &str) -> DotfResult<()> {
@@ -184,6 +405,99 @@ pub mod tests {
             Ok(())
         }
 
+        async fn hard_link(&self, source: &str, target: &str) -> DotfResult<()> {
+            self.check_writable(target)?;
+            let content = self.files.lock().unwrap().get(source).cloned();
+            match content {
+                Some(content) => {
+                    self.files
+                        .lock()
+                        .unwrap()
+                        .insert(target.to_string(), content);
+                    Ok(())
+                }
+                None => Err(std::io::Error::from(std::io::ErrorKind::NotFound).into()),
+            }
+        }
+
+        async fn rename(&self, source: &str, target: &str) -> DotfResult<()> {
+            if self.mount_of(source) != self.mount_of(target) {
+                return Err(crate::error::DotfError::Io(
+                    std::io::Error::from_raw_os_error(crate::core::filesystem::CROSS_DEVICE),
+                ));
+            }
+            self.check_writable(source)?;
+            self.check_writable(target)?;
+            let mode = self.modes.lock().unwrap().remove(source);
+            if let Some(mode) = mode {
+                self.modes.lock().unwrap().insert(target.to_string(), mode);
+            }
+            let owner = self.owners.lock().unwrap().remove(source);
+            if let Some(owner) = owner {
+                self.owners
+                    .lock()
+                    .unwrap()
+                    .insert(target.to_string(), owner);
+            }
+
+            let content = self.files.lock().unwrap().remove(source);
+            if let Some(content) = content {
+                self.files
+                    .lock()
+                    .unwrap()
+                    .insert(target.to_string(), content);
+                return Ok(());
+            }
+            let link = self.symlinks.lock().unwrap().remove(source);
+            if let Some(link) = link {
+                self.symlinks
+                    .lock()
+                    .unwrap()
+                    .insert(target.to_string(), link);
+                return Ok(());
+            }
+            if self.is_dir(source).await? {
+                // Everything below the directory moves with it
+                let moved = |path: &str| -> Option<String> {
+                    let rest = path.strip_prefix(source)?;
+                    (rest.is_empty() || rest.starts_with('/'))
+                        .then(|| format!("{}{}", target, rest))
+                };
+                for path in self.directories.lock().unwrap().iter_mut() {
+                    if let Some(new_path) = moved(path) {
+                        *path = new_path;
+                    }
+                }
+                let files: Vec<(String, String)> = self.files.lock().unwrap().drain().collect();
+                self.files.lock().unwrap().extend(
+                    files
+                        .into_iter()
+                        .map(|(path, content)| (moved(&path).unwrap_or(path), content)),
+                );
+                let links: Vec<(String, String)> = self.symlinks.lock().unwrap().drain().collect();
+                self.symlinks.lock().unwrap().extend(
+                    links
+                        .into_iter()
+                        .map(|(path, link)| (moved(&path).unwrap_or(path), link)),
+                );
+                let modes: Vec<(String, u32)> = self.modes.lock().unwrap().drain().collect();
+                self.modes.lock().unwrap().extend(
+                    modes
+                        .into_iter()
+                        .map(|(path, mode)| (moved(&path).unwrap_or(path), mode)),
+                );
+                return Ok(());
+            }
+            Err(crate::error::DotfError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "File not found",
+            )))
+        }
+
+        async fn read(&self, path: &str) -> DotfResult<Vec<u8>> {
+            self.read_to_string(path).await.map(String::into_bytes)
+        }
+
         async fn read_to_string(&self, path: &str) -> DotfResult<String> {
             self.files
                 .lock()
@@ -228,6 +542,52 @@ pub mod tests {
             Ok(self.directories.lock().unwrap().iter().any(|p| p == path))
         }
 
+        async fn mode(&self, path: &str) -> DotfResult<Option<u32>> {
+            if let Some(mode) = self.modes.lock().unwrap().get(path) {
+                return Ok(Some(*mode));
+            }
+            if self.is_dir(path).await? {
+                Ok(Some(0o755))
+            } else if self.exists(path).await? {
+                Ok(Some(0o644))
+            } else {
+                Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "File not found",
+                )))
+            }
+        }
+
+        async fn set_mode(&self, path: &str, mode: u32) -> DotfResult<()> {
+            self.modes.lock().unwrap().insert(path.to_string(), mode);
+            Ok(())
+        }
+
+        async fn owner(&self, path: &str) -> DotfResult<Option<FileOwner>> {
+            if let Some(owner) = self.owners.lock().unwrap().get(path) {
+                return Ok(Some(*owner));
+            }
+            if self.exists(path).await? {
+                Ok(Some(Self::USER))
+            } else {
+                Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "File not found",
+                )))
+            }
+        }
+
+        async fn set_owner(&self, path: &str, owner: FileOwner) -> DotfResult<()> {
+            if *self.chown_denied.lock().unwrap() && owner != Self::USER {
+                return Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    format!("Operation not permitted: {}", path),
+                )));
+            }
+            self.owners.lock().unwrap().insert(path.to_string(), owner);
+            Ok(())
+        }
+
         async fn list_entries(&self, path: &str) -> DotfResult<Vec<FileEntry>> {
             let mut entries = Vec::new();
             let path_prefix = if path.ends_with('/') {
@@ -292,6 +652,43 @@ pub mod tests {
 
             Ok(entries)
         }
+
+        async fn check_readable(&self, path: &str) -> DotfResult<()> {
+            let resolved = self
+                .symlinks
+                .lock()
+                .unwrap()
+                .get(path)
+                .cloned()
+                .unwrap_or_else(|| path.to_string());
+            let denied = self
+                .unreadable
+                .lock()
+                .unwrap()
+                .iter()
+                .any(|dir| std::path::Path::new(&resolved).starts_with(dir));
+            if denied {
+                return Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::PermissionDenied,
+                    "Permission denied (os error 13)",
+                )));
+            }
+            if self.exists(&resolved).await? {
+                Ok(())
+            } else {
+                Err(crate::error::DotfError::Io(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    "File not found",
+                )))
+            }
+        }
+
+        async fn watch(&self, paths: &[WatchPath]) -> DotfResult<ChangeStream> {
+            self.watched.lock().unwrap().extend_from_slice(paths);
+            let (sender, receiver) = unbounded_channel();
+            self.watchers.lock().unwrap().push(sender);
+            Ok(ChangeStream::new(receiver, Box::new(())))
+        }
     }
 }
 
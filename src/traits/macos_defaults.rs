@@ -0,0 +1,63 @@
+use crate::core::config::dotf_config::DefaultsValue;
+use crate::error::DotfResult;
+use async_trait::async_trait;
+
+/// Access to the macOS user defaults system
+#[async_trait]
+pub trait DefaultsStore: Send + Sync {
+    /// Current value as printed by `defaults read`, or `None` if the key is not set
+    async fn read(&self, domain: &str, key: &str) -> DotfResult<Option<String>>;
+    async fn write(&self, domain: &str, key: &str, value: &DefaultsValue) -> DotfResult<()>;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    type DefaultsKey = (String, String);
+
+    #[derive(Clone, Default)]
+    pub struct MockDefaultsStore {
+        pub values: Arc<Mutex<HashMap<DefaultsKey, String>>>,
+        pub writes: Arc<Mutex<Vec<(String, String, DefaultsValue)>>>,
+    }
+
+    impl MockDefaultsStore {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn set(&self, domain: &str, key: &str, value: &str) {
+            self.values
+                .lock()
+                .unwrap()
+                .insert((domain.to_string(), key.to_string()), value.to_string());
+        }
+
+        pub fn get_writes(&self) -> Vec<(String, String, DefaultsValue)> {
+            self.writes.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl DefaultsStore for MockDefaultsStore {
+        async fn read(&self, domain: &str, key: &str) -> DotfResult<Option<String>> {
+            Ok(self
+                .values
+                .lock()
+                .unwrap()
+                .get(&(domain.to_string(), key.to_string()))
+                .cloned())
+        }
+
+        async fn write(&self, domain: &str, key: &str, value: &DefaultsValue) -> DotfResult<()> {
+            self.writes
+                .lock()
+                .unwrap()
+                .push((domain.to_string(), key.to_string(), value.clone()));
+            Ok(())
+        }
+    }
+}
@@ -1,4 +1,12 @@
+pub mod cipher;
+pub mod clock;
+pub mod crontab;
 pub mod filesystem;
+pub mod macos_defaults;
+pub mod package_manager;
+pub mod privileged_helper;
+pub mod progress;
 pub mod prompt;
 pub mod repository;
 pub mod script_executor;
+pub mod tool_version_manager;
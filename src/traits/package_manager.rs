@@ -0,0 +1,80 @@
+use crate::core::config::dotf_config::Package;
+use crate::error::DotfResult;
+use async_trait::async_trait;
+
+/// Installs one kind of `[packages]` entry, e.g. Homebrew casks
+#[async_trait]
+pub trait PackageManager: Send + Sync {
+    /// Key of the list in `[packages]` this manager installs
+    fn kind(&self) -> &str;
+    /// Executable named in messages when the manager is unavailable
+    fn command(&self) -> &str;
+    /// Whether the manager can run on this machine
+    async fn is_available(&self) -> bool;
+    /// IDs of every package of this kind that is installed
+    async fn installed(&self) -> DotfResult<Vec<String>>;
+    async fn install(&self, package: &Package) -> DotfResult<()>;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    pub struct MockPackageManager {
+        pub kind: &'static str,
+        pub available: bool,
+        pub installed: Arc<Mutex<Vec<String>>>,
+        pub install_calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockPackageManager {
+        pub fn new(kind: &'static str) -> Self {
+            Self {
+                kind,
+                available: true,
+                installed: Arc::new(Mutex::new(Vec::new())),
+                install_calls: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        pub fn unavailable(mut self) -> Self {
+            self.available = false;
+            self
+        }
+
+        pub fn add_installed(&self, id: &str) {
+            self.installed.lock().unwrap().push(id.to_string());
+        }
+
+        pub fn get_install_calls(&self) -> Vec<String> {
+            self.install_calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl PackageManager for MockPackageManager {
+        fn kind(&self) -> &str {
+            self.kind
+        }
+
+        fn command(&self) -> &str {
+            self.kind
+        }
+
+        async fn is_available(&self) -> bool {
+            self.available
+        }
+
+        async fn installed(&self) -> DotfResult<Vec<String>> {
+            Ok(self.installed.lock().unwrap().clone())
+        }
+
+        async fn install(&self, package: &Package) -> DotfResult<()> {
+            self.install_calls.lock().unwrap().push(package.id.clone());
+            self.add_installed(&package.id);
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::core::symlinks::{LinkPlan, PlannedLinkResult};
+use crate::error::DotfResult;
+use async_trait::async_trait;
+
+/// Applies symlinks that need elevated permissions, the whole plan at once
+#[async_trait]
+pub trait PrivilegedHelper: Send + Sync {
+    /// One result per planned link, in plan order
+    async fn apply(&self, plan: &LinkPlan) -> DotfResult<Vec<PlannedLinkResult>>;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Records the plans it receives and reports every link as created
+    #[derive(Clone, Default)]
+    pub struct MockPrivilegedHelper {
+        pub plans: Arc<Mutex<Vec<LinkPlan>>>,
+    }
+
+    impl MockPrivilegedHelper {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn get_plans(&self) -> Vec<LinkPlan> {
+            self.plans.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl PrivilegedHelper for MockPrivilegedHelper {
+        async fn apply(&self, plan: &LinkPlan) -> DotfResult<Vec<PlannedLinkResult>> {
+            self.plans.lock().unwrap().push(plan.clone());
+            Ok(plan
+                .links
+                .iter()
+                .map(|link| PlannedLinkResult {
+                    target: link.target.clone(),
+                    error: None,
+                })
+                .collect())
+        }
+    }
+}
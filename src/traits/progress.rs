@@ -0,0 +1,104 @@
+use serde::Serialize;
+use std::fmt;
+
+/// Progress reported by long-running services while they work
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProgressEvent {
+    /// A new stage began, e.g. running the dependency script
+    StageStarted(String),
+    /// One item of the current stage finished, e.g. a symlink was created
+    ItemCompleted(String),
+    /// Something the user should know about that did not stop the operation
+    Warning(String),
+    /// Another link of a batch was handled
+    Links(LinkProgress),
+}
+
+/// How far along creating a batch of links is
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct LinkProgress {
+    pub done: usize,
+    pub total: usize,
+    pub created: usize,
+    /// Targets left as they were: already linked, or a conflict that was kept
+    pub skipped: usize,
+    /// Targets that had something else in the way, whether it was replaced or kept
+    pub conflicts: usize,
+}
+
+impl fmt::Display for LinkProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} created, {} skipped, {} conflicts",
+            self.created, self.skipped, self.conflicts
+        )
+    }
+}
+
+impl fmt::Display for ProgressEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgressEvent::StageStarted(message) => write!(f, "▶ {}", message),
+            ProgressEvent::ItemCompleted(message) => write!(f, "  {}", message),
+            ProgressEvent::Warning(message) => write!(f, "⚠️  {}", message),
+            ProgressEvent::Links(progress) => write!(
+                f,
+                "  {}/{} links ({})",
+                progress.done, progress.total, progress
+            ),
+        }
+    }
+}
+
+/// Receives progress events; the CLI implements it to drive spinners
+pub trait ProgressReporter: Send + Sync {
+    fn report(&self, event: ProgressEvent);
+
+    /// Stop drawing until `resume`, so a prompt can use the terminal
+    fn pause(&self) {}
+
+    fn resume(&self) {}
+}
+
+/// Prints each event on its own line, for callers that show no spinner
+pub struct PrintProgress;
+
+impl ProgressReporter for PrintProgress {
+    fn report(&self, event: ProgressEvent) {
+        // A line per link would drown the rest; only the final count is printed
+        if let ProgressEvent::Links(progress) = &event {
+            if progress.done < progress.total {
+                return;
+            }
+        }
+        println!("{}", event);
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    pub struct RecordingProgress {
+        pub events: Arc<Mutex<Vec<ProgressEvent>>>,
+    }
+
+    impl RecordingProgress {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn get_events(&self) -> Vec<ProgressEvent> {
+            self.events.lock().unwrap().clone()
+        }
+    }
+
+    impl ProgressReporter for RecordingProgress {
+        fn report(&self, event: ProgressEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+}
@@ -1,6 +1,8 @@
 use crate::core::config::DotfConfig;
 use crate::error::DotfResult;
 use async_trait::async_trait;
+use std::collections::HashSet;
+use std::time::Duration;
 
 #[async_trait]
 pub trait Repository {
@@ -10,11 +12,119 @@ pub trait Repository {
     async fn clone(&self, url: &str, destination: &str) -> DotfResult<()>;
     async fn clone_branch(&self, url: &str, branch: &str, destination: &str) -> DotfResult<()>;
     async fn pull(&self, repo_path: &str) -> DotfResult<()>;
-    async fn get_status(&self, repo_path: &str) -> DotfResult<RepositoryStatus>;
+    /// Local changes and how far the checkout is ahead of and behind its remote, with
+    /// the remote consulted as `fetch` allows
+    async fn get_status(&self, repo_path: &str, fetch: FetchPolicy)
+        -> DotfResult<RepositoryStatus>;
     async fn get_remote_url(&self, repo_path: &str) -> DotfResult<String>;
     async fn is_file_modified(&self, repo_path: &str, file_path: &str) -> DotfResult<bool>;
+    /// Every path (relative to the checkout) with uncommitted changes, untracked files
+    /// included, from a single status call
+    async fn get_modified_files(&self, repo_path: &str) -> DotfResult<HashSet<String>>;
     async fn get_default_branch(&self, url: &str) -> DotfResult<String>;
     async fn branch_exists(&self, url: &str, branch: &str) -> DotfResult<bool>;
+    async fn stage(&self, repo_path: &str, file_path: &str) -> DotfResult<()>;
+    /// Commit staged changes, or with `paths` only the changes to those paths (relative to
+    /// the checkout) so nothing else the user staged goes along; `sign` forces a signed
+    /// commit on top of the repo's own config
+    async fn commit(
+        &self,
+        repo_path: &str,
+        message: &str,
+        paths: &[String],
+        sign: bool,
+    ) -> DotfResult<()>;
+    /// Stage every change in the working copy, new and deleted files included, and
+    /// return the staged paths (relative to the checkout)
+    async fn add_all(&self, repo_path: &str) -> DotfResult<Vec<String>>;
+    /// Push the current branch to the remote it was cloned from
+    async fn push(&self, repo_path: &str) -> DotfResult<()>;
+    /// Whether the checkout's own configuration asks for signed commits
+    async fn signing_configured(&self, repo_path: &str) -> DotfResult<bool>;
+    /// Sign a throwaway commit object to prove the signing key and agent are usable
+    async fn test_signing(&self, repo_path: &str) -> DotfResult<()>;
+    /// Fetch and report whether the local branch can still follow its upstream
+    async fn upstream_history(&self, repo_path: &str) -> DotfResult<UpstreamHistory>;
+    /// Keep the current commit on a new rescue branch, then hard-reset to the upstream.
+    /// Returns the rescue branch name.
+    async fn rescue_and_reset(&self, repo_path: &str) -> DotfResult<String>;
+    /// Set uncommitted changes, untracked files included, aside so the working copy is
+    /// clean; `false` when there was nothing to set aside
+    async fn stash(&self, repo_path: &str) -> DotfResult<bool>;
+    /// Reapply the changes `stash` set aside and drop them. Paths (relative to the
+    /// checkout) that conflict are returned, and the changes are then kept set aside
+    /// until the user resolves them.
+    async fn stash_pop(&self, repo_path: &str) -> DotfResult<Vec<String>>;
+    /// Newest-first history touching any of `paths` (the whole repository when empty)
+    async fn log(
+        &self,
+        repo_path: &str,
+        paths: &[String],
+        limit: usize,
+    ) -> DotfResult<Vec<CommitEntry>>;
+    /// The `paths` (relative to the checkout) that `.gitattributes` marks `export-ignore`
+    async fn export_ignored(&self, repo_path: &str, paths: &[String]) -> DotfResult<Vec<String>>;
+    /// Compact the checkout's storage when it needs it; a no-op for backends without any
+    async fn gc(&self, repo_path: &str) -> DotfResult<()>;
+    /// Id of the checked-out revision, or `None` for backends without history
+    async fn revision(&self, repo_path: &str) -> DotfResult<Option<String>>;
+    /// Paths (relative to the checkout) that differ from `revision` in the working copy,
+    /// including uncommitted and untracked files
+    async fn changed_since(&self, repo_path: &str, revision: &str) -> DotfResult<Vec<String>>;
+    /// Files moved between revisions `from` and `to`, as the backend detects them; empty
+    /// for backends without history
+    async fn renames(&self, repo_path: &str, from: &str, to: &str) -> DotfResult<Vec<Rename>>;
+    /// Clone `branch` with only `paths` (relative to the checkout) in the working copy;
+    /// backends without partial checkouts clone everything
+    async fn clone_sparse(
+        &self,
+        url: &str,
+        branch: &str,
+        destination: &str,
+        paths: &[String],
+    ) -> DotfResult<()>;
+    /// Limit the working copy to `paths`, or check out everything again with `None`;
+    /// a no-op for backends without partial checkouts
+    async fn set_sparse_paths(&self, repo_path: &str, paths: Option<&[String]>) -> DotfResult<()>;
+}
+
+/// How the local branch relates to its upstream after a fetch
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum UpstreamHistory {
+    /// A normal pull (fast-forward or rebase of local commits) will work
+    Linear,
+    /// The upstream was force-pushed; `local_commits` are no longer on it
+    Rewritten { local_commits: usize },
+    /// The local branch and upstream share no history
+    Unrelated,
+}
+
+/// One commit in the repository history, with the repository paths it changed
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct CommitEntry {
+    /// Abbreviated commit id
+    pub id: String,
+    pub author: String,
+    pub date: chrono::DateTime<chrono::FixedOffset>,
+    pub summary: String,
+    pub files: Vec<String>,
+}
+
+/// A file that moved in the repository, with paths relative to the checkout
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Rename {
+    pub from: String,
+    pub to: String,
+}
+
+/// A repository implementation that can be registered with the backend registry.
+///
+/// Backends are picked by remote URL at init time and by the layout of the local
+/// checkout afterwards, so existing clones keep working without extra settings.
+pub trait RepositoryBackend: Repository + Send + Sync {
+    fn name(&self) -> &'static str;
+    fn handles_url(&self, url: &str) -> bool;
+    fn handles_checkout(&self, repo_path: &str) -> bool;
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -23,24 +133,83 @@ pub struct RepositoryStatus {
     pub ahead_count: usize,
     pub behind_count: usize,
     pub current_branch: String,
+    /// Whether ahead/behind are real commit counts. Backends without history (e.g.
+    /// tarballs) report `behind_count = 1` when an update is available instead.
+    #[serde(default = "default_tracks_upstream")]
+    pub tracks_upstream: bool,
+    /// Whether the remote was reached for this status. When it was not, ahead/behind
+    /// come from the last fetch (git) or are unknown and reported as 0.
+    #[serde(default = "default_fetched")]
+    pub fetched: bool,
+}
+
+fn default_fetched() -> bool {
+    true
+}
+
+/// How `get_status` consults the remote before counting commits ahead and behind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum FetchPolicy {
+    /// Wait a few seconds for the remote; a git fetch that takes longer keeps running in
+    /// the background for the next status
+    #[default]
+    Auto,
+    /// Wait for the remote however long it takes
+    Always,
+    /// Do not contact the remote
+    Never,
+}
+
+/// How long `FetchPolicy::Auto` waits for the remote
+pub const AUTO_FETCH_TIMEOUT: Duration = Duration::from_secs(3);
+
+fn default_tracks_upstream() -> bool {
+    true
 }
 
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
 
+    /// Checkout path and sparse paths (`None` to disable) passed to `set_sparse_paths`
+    pub type SparseCall = (String, Option<Vec<String>>);
+
     #[derive(Clone)]
     pub struct MockRepository {
         pub validate_calls: Arc<Mutex<Vec<String>>>,
         pub clone_calls: Arc<Mutex<Vec<(String, String)>>>,
         pub pull_calls: Arc<Mutex<Vec<String>>>,
+        pub stage_calls: Arc<Mutex<Vec<(String, String)>>>,
+        pub commit_calls: Arc<Mutex<Vec<(String, String)>>>,
+        pub add_all_response: Arc<Mutex<Vec<String>>>,
+        pub push_calls: Arc<Mutex<Vec<String>>>,
         pub should_fail_validate: Arc<Mutex<bool>>,
         pub config_response: Arc<Mutex<Option<DotfConfig>>>,
+        pub branch_config_responses: Arc<Mutex<HashMap<String, DotfConfig>>>,
         pub status_response: Arc<Mutex<Option<RepositoryStatus>>>,
         pub remote_url_response: Arc<Mutex<Option<String>>>,
         pub default_branch_response: Arc<Mutex<Option<String>>>,
         pub branch_exists_response: Arc<Mutex<bool>>,
+        pub history_response: Arc<Mutex<UpstreamHistory>>,
+        pub rescue_calls: Arc<Mutex<Vec<String>>>,
+        pub signing_configured_response: Arc<Mutex<bool>>,
+        pub signing_error: Arc<Mutex<Option<String>>>,
+        pub log_response: Arc<Mutex<Vec<CommitEntry>>>,
+        pub log_calls: Arc<Mutex<Vec<Vec<String>>>>,
+        pub export_ignored_response: Arc<Mutex<Vec<String>>>,
+        pub gc_calls: Arc<Mutex<Vec<String>>>,
+        pub revision_response: Arc<Mutex<Option<String>>>,
+        pub changed_since_response: Arc<Mutex<Vec<String>>>,
+        pub renames_response: Arc<Mutex<Vec<Rename>>>,
+        pub renames_error: Arc<Mutex<Option<String>>>,
+        pub modified_files: Arc<Mutex<HashSet<String>>>,
+        pub modified_files_calls: Arc<Mutex<usize>>,
+        pub sparse_calls: Arc<Mutex<Vec<SparseCall>>>,
+        pub stash_calls: Arc<Mutex<Vec<String>>>,
+        pub stash_conflicts: Arc<Mutex<Vec<String>>>,
+        pub status_fetches: Arc<Mutex<Vec<FetchPolicy>>>,
     }
 
     impl Default for MockRepository {
@@ -55,12 +224,35 @@ pub mod tests {
                 validate_calls: Arc::new(Mutex::new(Vec::new())),
                 clone_calls: Arc::new(Mutex::new(Vec::new())),
                 pull_calls: Arc::new(Mutex::new(Vec::new())),
+                stage_calls: Arc::new(Mutex::new(Vec::new())),
+                commit_calls: Arc::new(Mutex::new(Vec::new())),
+                add_all_response: Arc::new(Mutex::new(Vec::new())),
+                push_calls: Arc::new(Mutex::new(Vec::new())),
                 should_fail_validate: Arc::new(Mutex::new(false)),
                 config_response: Arc::new(Mutex::new(None)),
+                branch_config_responses: Arc::new(Mutex::new(HashMap::new())),
                 status_response: Arc::new(Mutex::new(None)),
                 remote_url_response: Arc::new(Mutex::new(None)),
                 default_branch_response: Arc::new(Mutex::new(None)),
                 branch_exists_response: Arc::new(Mutex::new(true)),
+                history_response: Arc::new(Mutex::new(UpstreamHistory::Linear)),
+                rescue_calls: Arc::new(Mutex::new(Vec::new())),
+                signing_configured_response: Arc::new(Mutex::new(false)),
+                signing_error: Arc::new(Mutex::new(None)),
+                log_response: Arc::new(Mutex::new(Vec::new())),
+                log_calls: Arc::new(Mutex::new(Vec::new())),
+                export_ignored_response: Arc::new(Mutex::new(Vec::new())),
+                gc_calls: Arc::new(Mutex::new(Vec::new())),
+                revision_response: Arc::new(Mutex::new(None)),
+                changed_since_response: Arc::new(Mutex::new(Vec::new())),
+                renames_response: Arc::new(Mutex::new(Vec::new())),
+                renames_error: Arc::new(Mutex::new(None)),
+                modified_files: Arc::new(Mutex::new(HashSet::new())),
+                modified_files_calls: Arc::new(Mutex::new(0)),
+                sparse_calls: Arc::new(Mutex::new(Vec::new())),
+                stash_calls: Arc::new(Mutex::new(Vec::new())),
+                stash_conflicts: Arc::new(Mutex::new(Vec::new())),
+                status_fetches: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
@@ -72,6 +264,14 @@ pub mod tests {
             *self.config_response.lock().unwrap() = Some(config);
         }
 
+        /// Config returned for `branch` instead of the one set with `set_config_response`
+        pub fn set_branch_config(&self, branch: &str, config: DotfConfig) {
+            self.branch_config_responses
+                .lock()
+                .unwrap()
+                .insert(branch.to_string(), config);
+        }
+
         pub fn set_status_response(&mut self, status: RepositoryStatus) {
             *self.status_response.lock().unwrap() = Some(status);
         }
@@ -88,6 +288,73 @@ pub mod tests {
             *self.branch_exists_response.lock().unwrap() = exists;
         }
 
+        pub fn set_upstream_history(&self, history: UpstreamHistory) {
+            *self.history_response.lock().unwrap() = history;
+        }
+
+        pub fn set_signing(&self, configured: bool, error: Option<&str>) {
+            *self.signing_configured_response.lock().unwrap() = configured;
+            *self.signing_error.lock().unwrap() = error.map(str::to_string);
+        }
+
+        pub fn set_log(&self, entries: Vec<CommitEntry>) {
+            *self.log_response.lock().unwrap() = entries;
+        }
+
+        pub fn set_export_ignored(&self, paths: &[&str]) {
+            *self.export_ignored_response.lock().unwrap() =
+                paths.iter().map(|path| path.to_string()).collect();
+        }
+
+        /// The checked-out revision and the paths changed since any earlier one
+        pub fn set_revision(&self, revision: Option<&str>, changed: &[&str]) {
+            *self.revision_response.lock().unwrap() = revision.map(str::to_string);
+            *self.changed_since_response.lock().unwrap() =
+                changed.iter().map(|path| path.to_string()).collect();
+        }
+
+        /// Make `renames` fail with `error`, or succeed again with `None`
+        pub fn set_renames_error(&self, error: Option<&str>) {
+            *self.renames_error.lock().unwrap() = error.map(str::to_string);
+        }
+
+        /// Renames `renames` reports between any two revisions, as `(from, to)`
+        pub fn set_renames(&self, renames: &[(&str, &str)]) {
+            *self.renames_response.lock().unwrap() = renames
+                .iter()
+                .map(|(from, to)| Rename {
+                    from: from.to_string(),
+                    to: to.to_string(),
+                })
+                .collect();
+        }
+
+        /// Paths `get_modified_files` and `is_file_modified` report as changed
+        pub fn set_modified_files(&self, paths: &[&str]) {
+            *self.modified_files.lock().unwrap() =
+                paths.iter().map(|path| path.to_string()).collect();
+        }
+
+        pub fn get_modified_files_calls(&self) -> usize {
+            *self.modified_files_calls.lock().unwrap()
+        }
+
+        pub fn get_sparse_calls(&self) -> Vec<SparseCall> {
+            self.sparse_calls.lock().unwrap().clone()
+        }
+
+        pub fn get_gc_calls(&self) -> Vec<String> {
+            self.gc_calls.lock().unwrap().clone()
+        }
+
+        pub fn get_log_calls(&self) -> Vec<Vec<String>> {
+            self.log_calls.lock().unwrap().clone()
+        }
+
+        pub fn get_rescue_calls(&self) -> Vec<String> {
+            self.rescue_calls.lock().unwrap().clone()
+        }
+
         pub fn get_validate_calls(&self) -> Vec<String> {
             self.validate_calls.lock().unwrap().clone()
         }
@@ -99,6 +366,40 @@ pub mod tests {
         pub fn get_pull_calls(&self) -> Vec<String> {
             self.pull_calls.lock().unwrap().clone()
         }
+
+        pub fn get_stage_calls(&self) -> Vec<(String, String)> {
+            self.stage_calls.lock().unwrap().clone()
+        }
+
+        pub fn get_commit_calls(&self) -> Vec<(String, String)> {
+            self.commit_calls.lock().unwrap().clone()
+        }
+
+        /// Paths `add_all` reports as staged
+        pub fn set_working_changes(&self, paths: &[&str]) {
+            *self.add_all_response.lock().unwrap() =
+                paths.iter().map(|path| path.to_string()).collect();
+        }
+
+        pub fn get_push_calls(&self) -> Vec<String> {
+            self.push_calls.lock().unwrap().clone()
+        }
+
+        /// Make `stash_pop` report `paths` as conflicting
+        pub fn set_stash_conflicts(&self, paths: &[&str]) {
+            *self.stash_conflicts.lock().unwrap() =
+                paths.iter().map(|path| path.to_string()).collect();
+        }
+
+        /// The fetch policy of every `get_status` call, in order
+        pub fn get_status_fetches(&self) -> Vec<FetchPolicy> {
+            self.status_fetches.lock().unwrap().clone()
+        }
+
+        /// `stash` and `pop`, in the order they were called
+        pub fn get_stash_calls(&self) -> Vec<String> {
+            self.stash_calls.lock().unwrap().clone()
+        }
     }
 
     #[async_trait]
@@ -124,8 +425,11 @@ pub mod tests {
         async fn fetch_config_from_branch(
             &self,
             _url: &str,
-            _branch: &str,
+            branch: &str,
         ) -> DotfResult<DotfConfig> {
+            if let Some(config) = self.branch_config_responses.lock().unwrap().get(branch) {
+                return Ok(config.clone());
+            }
             self.config_response.lock().unwrap().clone().ok_or_else(|| {
                 crate::error::DotfError::Config("No config response set".to_string())
             })
@@ -152,10 +456,22 @@ pub mod tests {
             Ok(())
         }
 
-        async fn get_status(&self, _repo_path: &str) -> DotfResult<RepositoryStatus> {
-            self.status_response.lock().unwrap().clone().ok_or_else(|| {
-                crate::error::DotfError::Repository("No status response set".to_string())
-            })
+        async fn get_status(
+            &self,
+            _repo_path: &str,
+            fetch: FetchPolicy,
+        ) -> DotfResult<RepositoryStatus> {
+            self.status_fetches.lock().unwrap().push(fetch);
+            let mut status = self
+                .status_response
+                .lock()
+                .unwrap()
+                .clone()
+                .ok_or_else(|| {
+                    crate::error::DotfError::Repository("No status response set".to_string())
+                })?;
+            status.fetched &= fetch != FetchPolicy::Never;
+            Ok(status)
         }
 
         async fn get_remote_url(&self, _repo_path: &str) -> DotfResult<String> {
@@ -168,9 +484,13 @@ pub mod tests {
                 })
         }
 
-        async fn is_file_modified(&self, _repo_path: &str, _file_path: &str) -> DotfResult<bool> {
-            // Default to false for mock
-            Ok(false)
+        async fn is_file_modified(&self, _repo_path: &str, file_path: &str) -> DotfResult<bool> {
+            Ok(self.modified_files.lock().unwrap().contains(file_path))
+        }
+
+        async fn get_modified_files(&self, _repo_path: &str) -> DotfResult<HashSet<String>> {
+            *self.modified_files_calls.lock().unwrap() += 1;
+            Ok(self.modified_files.lock().unwrap().clone())
         }
 
         async fn get_default_branch(&self, _url: &str) -> DotfResult<String> {
@@ -188,5 +508,146 @@ pub mod tests {
         async fn branch_exists(&self, _url: &str, _branch: &str) -> DotfResult<bool> {
             Ok(*self.branch_exists_response.lock().unwrap())
         }
+
+        async fn stage(&self, repo_path: &str, file_path: &str) -> DotfResult<()> {
+            self.stage_calls
+                .lock()
+                .unwrap()
+                .push((repo_path.to_string(), file_path.to_string()));
+            Ok(())
+        }
+
+        async fn commit(
+            &self,
+            repo_path: &str,
+            message: &str,
+            _paths: &[String],
+            _sign: bool,
+        ) -> DotfResult<()> {
+            self.commit_calls
+                .lock()
+                .unwrap()
+                .push((repo_path.to_string(), message.to_string()));
+            Ok(())
+        }
+
+        async fn add_all(&self, _repo_path: &str) -> DotfResult<Vec<String>> {
+            Ok(std::mem::take(&mut *self.add_all_response.lock().unwrap()))
+        }
+
+        async fn push(&self, repo_path: &str) -> DotfResult<()> {
+            self.push_calls.lock().unwrap().push(repo_path.to_string());
+            Ok(())
+        }
+
+        async fn signing_configured(&self, _repo_path: &str) -> DotfResult<bool> {
+            Ok(*self.signing_configured_response.lock().unwrap())
+        }
+
+        async fn test_signing(&self, _repo_path: &str) -> DotfResult<()> {
+            match self.signing_error.lock().unwrap().clone() {
+                Some(error) => Err(crate::error::DotfError::Git(error)),
+                None => Ok(()),
+            }
+        }
+
+        async fn upstream_history(&self, _repo_path: &str) -> DotfResult<UpstreamHistory> {
+            Ok(self.history_response.lock().unwrap().clone())
+        }
+
+        async fn rescue_and_reset(&self, repo_path: &str) -> DotfResult<String> {
+            self.rescue_calls
+                .lock()
+                .unwrap()
+                .push(repo_path.to_string());
+            *self.history_response.lock().unwrap() = UpstreamHistory::Linear;
+            Ok("dotf-rescue/test".to_string())
+        }
+
+        async fn stash(&self, _repo_path: &str) -> DotfResult<bool> {
+            self.stash_calls.lock().unwrap().push("stash".to_string());
+            Ok(true)
+        }
+
+        async fn stash_pop(&self, _repo_path: &str) -> DotfResult<Vec<String>> {
+            self.stash_calls.lock().unwrap().push("pop".to_string());
+            Ok(self.stash_conflicts.lock().unwrap().clone())
+        }
+
+        async fn log(
+            &self,
+            _repo_path: &str,
+            paths: &[String],
+            limit: usize,
+        ) -> DotfResult<Vec<CommitEntry>> {
+            self.log_calls.lock().unwrap().push(paths.to_vec());
+            let entries = self.log_response.lock().unwrap();
+            Ok(entries.iter().take(limit).cloned().collect())
+        }
+
+        async fn export_ignored(
+            &self,
+            _repo_path: &str,
+            paths: &[String],
+        ) -> DotfResult<Vec<String>> {
+            let ignored = self.export_ignored_response.lock().unwrap();
+            Ok(paths
+                .iter()
+                .filter(|path| ignored.contains(path))
+                .cloned()
+                .collect())
+        }
+
+        async fn gc(&self, repo_path: &str) -> DotfResult<()> {
+            self.gc_calls.lock().unwrap().push(repo_path.to_string());
+            Ok(())
+        }
+
+        async fn revision(&self, _repo_path: &str) -> DotfResult<Option<String>> {
+            Ok(self.revision_response.lock().unwrap().clone())
+        }
+
+        async fn changed_since(
+            &self,
+            _repo_path: &str,
+            _revision: &str,
+        ) -> DotfResult<Vec<String>> {
+            Ok(self.changed_since_response.lock().unwrap().clone())
+        }
+
+        async fn renames(
+            &self,
+            _repo_path: &str,
+            _from: &str,
+            _to: &str,
+        ) -> DotfResult<Vec<Rename>> {
+            if let Some(error) = self.renames_error.lock().unwrap().clone() {
+                return Err(crate::error::DotfError::Git(error));
+            }
+            Ok(self.renames_response.lock().unwrap().clone())
+        }
+
+        async fn clone_sparse(
+            &self,
+            url: &str,
+            branch: &str,
+            destination: &str,
+            paths: &[String],
+        ) -> DotfResult<()> {
+            self.clone_branch(url, branch, destination).await?;
+            self.set_sparse_paths(destination, Some(paths)).await
+        }
+
+        async fn set_sparse_paths(
+            &self,
+            repo_path: &str,
+            paths: Option<&[String]>,
+        ) -> DotfResult<()> {
+            self.sparse_calls
+                .lock()
+                .unwrap()
+                .push((repo_path.to_string(), paths.map(<[String]>::to_vec)));
+            Ok(())
+        }
     }
 }
@@ -1,5 +1,7 @@
+use crate::core::scripts::ScriptLimits;
 use crate::error::DotfResult;
 use async_trait::async_trait;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone)]
 pub struct ExecutionResult {
@@ -37,6 +39,23 @@ pub trait ScriptExecutor: Send + Sync {
         script_path: &str,
         args: &[String],
     ) -> DotfResult<ExecutionResult>;
+    /// Run with extra environment variables on top of dotf's own environment
+    async fn execute_with_env(
+        &self,
+        script_path: &str,
+        args: &[String],
+        env: &BTreeMap<String, String>,
+    ) -> DotfResult<ExecutionResult>;
+    /// Run with extra environment variables under the given resource limits, in
+    /// `working_dir` when given and in dotf's own working directory otherwise
+    async fn execute_limited(
+        &self,
+        script_path: &str,
+        args: &[String],
+        env: &BTreeMap<String, String>,
+        limits: &ScriptLimits,
+        working_dir: Option<&str>,
+    ) -> DotfResult<ExecutionResult>;
     async fn has_permission(&self, script_path: &str) -> DotfResult<bool>;
     async fn make_executable(&self, script_path: &str) -> DotfResult<()>;
 }
@@ -54,6 +73,9 @@ pub mod tests {
         pub execution_results: Arc<Mutex<HashMap<String, ExecutionResult>>>,
         pub permissions: Arc<Mutex<HashMap<String, bool>>>,
         pub executed_scripts: Arc<Mutex<Vec<ExecutedScript>>>,
+        pub executed_env: Arc<Mutex<Vec<BTreeMap<String, String>>>>,
+        pub executed_limits: Arc<Mutex<Vec<ScriptLimits>>>,
+        pub executed_working_dirs: Arc<Mutex<Vec<Option<String>>>>,
     }
 
     impl Default for MockScriptExecutor {
@@ -68,6 +90,9 @@ pub mod tests {
                 execution_results: Arc::new(Mutex::new(HashMap::new())),
                 permissions: Arc::new(Mutex::new(HashMap::new())),
                 executed_scripts: Arc::new(Mutex::new(Vec::new())),
+                executed_env: Arc::new(Mutex::new(Vec::new())),
+                executed_limits: Arc::new(Mutex::new(Vec::new())),
+                executed_working_dirs: Arc::new(Mutex::new(Vec::new())),
             }
         }
 
@@ -88,6 +113,21 @@ pub mod tests {
         pub fn get_executed_scripts(&self) -> Vec<(String, Vec<String>)> {
             self.executed_scripts.lock().unwrap().clone()
         }
+
+        /// Environment passed to each `execute_with_env` call, in order
+        pub fn get_executed_env(&self) -> Vec<BTreeMap<String, String>> {
+            self.executed_env.lock().unwrap().clone()
+        }
+
+        /// Limits passed to each `execute_limited` call, in order
+        pub fn get_executed_limits(&self) -> Vec<ScriptLimits> {
+            self.executed_limits.lock().unwrap().clone()
+        }
+
+        /// Working directory passed to each `execute_limited` call, in order
+        pub fn get_executed_working_dirs(&self) -> Vec<Option<String>> {
+            self.executed_working_dirs.lock().unwrap().clone()
+        }
     }
 
     #[async_trait]
@@ -134,6 +174,32 @@ pub mod tests {
                 })
         }
 
+        async fn execute_with_env(
+            &self,
+            script_path: &str,
+            args: &[String],
+            env: &BTreeMap<String, String>,
+        ) -> DotfResult<ExecutionResult> {
+            self.executed_env.lock().unwrap().push(env.clone());
+            self.execute_with_args(script_path, args).await
+        }
+
+        async fn execute_limited(
+            &self,
+            script_path: &str,
+            args: &[String],
+            env: &BTreeMap<String, String>,
+            limits: &ScriptLimits,
+            working_dir: Option<&str>,
+        ) -> DotfResult<ExecutionResult> {
+            self.executed_limits.lock().unwrap().push(*limits);
+            self.executed_working_dirs
+                .lock()
+                .unwrap()
+                .push(working_dir.map(str::to_string));
+            self.execute_with_env(script_path, args, env).await
+        }
+
         async fn has_permission(&self, script_path: &str) -> DotfResult<bool> {
             Ok(self
                 .permissions
@@ -0,0 +1,98 @@
+use crate::error::DotfResult;
+use crate::traits::script_executor::ExecutionResult;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// A runtime version declared in `.tool-versions` or `mise.toml`, e.g. `node 20.11.0`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuntimeVersion {
+    pub tool: String,
+    pub version: String,
+}
+
+/// A language runtime version manager such as mise or asdf
+#[async_trait]
+pub trait ToolVersionManager: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Files in the repository root this manager reads, in order of preference
+    fn config_files(&self) -> &'static [&'static str];
+    /// Whether the manager's executable can be run
+    async fn is_available(&self) -> bool;
+    async fn is_installed(&self, repo_path: &str, runtime: &RuntimeVersion) -> DotfResult<bool>;
+    /// Install every version declared in the repository's config files
+    async fn install(&self, repo_path: &str) -> DotfResult<ExecutionResult>;
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone)]
+    pub struct MockToolVersionManager {
+        pub name: &'static str,
+        pub config_files: &'static [&'static str],
+        pub available: bool,
+        pub installed: Arc<Mutex<Vec<RuntimeVersion>>>,
+        pub install_calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl MockToolVersionManager {
+        pub fn new(name: &'static str, config_files: &'static [&'static str]) -> Self {
+            Self {
+                name,
+                config_files,
+                available: true,
+                installed: Arc::new(Mutex::new(Vec::new())),
+                install_calls: Arc::new(Mutex::new(Vec::new())),
+            }
+        }
+
+        pub fn unavailable(mut self) -> Self {
+            self.available = false;
+            self
+        }
+
+        pub fn add_installed(&self, tool: &str, version: &str) {
+            self.installed.lock().unwrap().push(RuntimeVersion {
+                tool: tool.to_string(),
+                version: version.to_string(),
+            });
+        }
+
+        pub fn get_install_calls(&self) -> Vec<String> {
+            self.install_calls.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl ToolVersionManager for MockToolVersionManager {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn config_files(&self) -> &'static [&'static str] {
+            self.config_files
+        }
+
+        async fn is_available(&self) -> bool {
+            self.available
+        }
+
+        async fn is_installed(
+            &self,
+            _repo_path: &str,
+            runtime: &RuntimeVersion,
+        ) -> DotfResult<bool> {
+            Ok(self.installed.lock().unwrap().contains(runtime))
+        }
+
+        async fn install(&self, repo_path: &str) -> DotfResult<ExecutionResult> {
+            self.install_calls
+                .lock()
+                .unwrap()
+                .push(repo_path.to_string());
+            Ok(ExecutionResult::success(String::new()))
+        }
+    }
+}
@@ -0,0 +1,253 @@
+/// One line of a line-based diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Context(String),
+    Added(String),
+    Removed(String),
+}
+
+/// Inputs larger than this (in lines) are not diffed; the LCS table is quadratic
+const MAX_DIFF_LINES: usize = 2000;
+/// Files larger than this are neither diffed nor printed
+pub const MAX_DIFF_BYTES: usize = 512 * 1024;
+/// Lines longer than this, e.g. minified JSON, are cut when printed
+pub const MAX_LINE_CHARS: usize = 300;
+/// Bytes searched for NUL to tell binary files apart, as git does
+const BINARY_CHECK_BYTES: usize = 8000;
+
+/// Why content is not shown as a line diff
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotDiffable {
+    Binary,
+    /// Size of the larger side in bytes
+    TooLarge(usize),
+}
+
+/// Whether `content` is binary: it has a NUL byte near the start
+pub fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(BINARY_CHECK_BYTES)].contains(&0)
+}
+
+/// Check that `old` and `new` are text small enough to diff and print
+pub fn check_diffable(old: &str, new: &str) -> Result<(), NotDiffable> {
+    if is_binary(old.as_bytes()) || is_binary(new.as_bytes()) {
+        return Err(NotDiffable::Binary);
+    }
+    let size = old.len().max(new.len());
+    if size > MAX_DIFF_BYTES
+        || old.lines().count() > MAX_DIFF_LINES
+        || new.lines().count() > MAX_DIFF_LINES
+    {
+        return Err(NotDiffable::TooLarge(size));
+    }
+    Ok(())
+}
+
+/// `line` cut to `max_chars` characters, with a note of how many were left out
+pub fn truncate_line(line: &str, max_chars: usize) -> String {
+    let total = line.chars().count();
+    if total <= max_chars {
+        return line.to_string();
+    }
+    let kept: String = line.chars().take(max_chars).collect();
+    format!("{}… [{} more characters]", kept, total - max_chars)
+}
+
+/// Compute a line diff turning `old` into `new`.
+///
+/// Returns `None` when either side is too large to diff reasonably.
+pub fn line_diff(old: &str, new: &str) -> Option<Vec<DiffLine>> {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+
+    if old_lines.len() > MAX_DIFF_LINES || new_lines.len() > MAX_DIFF_LINES {
+        return None;
+    }
+
+    // lcs[i][j] = length of the longest common subsequence of old[i..] and new[j..]
+    let mut lcs = vec![vec![0usize; new_lines.len() + 1]; old_lines.len() + 1];
+    for i in (0..old_lines.len()).rev() {
+        for j in (0..new_lines.len()).rev() {
+            lcs[i][j] = if old_lines[i] == new_lines[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut diff = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_lines.len() && j < new_lines.len() {
+        if old_lines[i] == new_lines[j] {
+            diff.push(DiffLine::Context(old_lines[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] >= lcs[i][j + 1] {
+            diff.push(DiffLine::Removed(old_lines[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(new_lines[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(
+        old_lines[i..]
+            .iter()
+            .map(|l| DiffLine::Removed(l.to_string())),
+    );
+    diff.extend(
+        new_lines[j..]
+            .iter()
+            .map(|l| DiffLine::Added(l.to_string())),
+    );
+
+    Some(diff)
+}
+
+/// A run of changes with surrounding context, as in a unified diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// First line on the old side (1-based)
+    pub old_start: usize,
+    pub old_len: usize,
+    /// First line on the new side (1-based)
+    pub new_start: usize,
+    pub new_len: usize,
+    pub lines: Vec<DiffLine>,
+}
+
+/// Group a line diff into hunks keeping `context` unchanged lines around each change
+pub fn hunks(diff: &[DiffLine], context: usize) -> Vec<Hunk> {
+    let changes: Vec<usize> = diff
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| !matches!(line, DiffLine::Context(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Merge the context windows of nearby changes into index ranges
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for index in changes {
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(diff.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    // Line numbers where each diff line sits on the old and new side
+    let mut old_line = 1;
+    let mut new_line = 1;
+    let mut positions = Vec::with_capacity(diff.len());
+    for line in diff {
+        positions.push((old_line, new_line));
+        match line {
+            DiffLine::Context(_) => {
+                old_line += 1;
+                new_line += 1;
+            }
+            DiffLine::Removed(_) => old_line += 1,
+            DiffLine::Added(_) => new_line += 1,
+        }
+    }
+
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            let lines = diff[start..end].to_vec();
+            let old_len = lines
+                .iter()
+                .filter(|line| !matches!(line, DiffLine::Added(_)))
+                .count();
+            let new_len = lines
+                .iter()
+                .filter(|line| !matches!(line, DiffLine::Removed(_)))
+                .count();
+            Hunk {
+                old_start: positions[start].0,
+                old_len,
+                new_start: positions[start].1,
+                new_len,
+                lines,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_diff() {
+        let diff = line_diff("a\nb\nc\n", "a\nc\nd\n").unwrap();
+        assert_eq!(
+            diff,
+            vec![
+                DiffLine::Context("a".to_string()),
+                DiffLine::Removed("b".to_string()),
+                DiffLine::Context("c".to_string()),
+                DiffLine::Added("d".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_line_diff_identical() {
+        let diff = line_diff("same\n", "same\n").unwrap();
+        assert_eq!(diff, vec![DiffLine::Context("same".to_string())]);
+    }
+
+    #[test]
+    fn test_check_diffable_and_truncate_line() {
+        assert_eq!(check_diffable("a\n", "b\n"), Ok(()));
+        assert_eq!(
+            check_diffable("text\n", "PNG\0\0data"),
+            Err(NotDiffable::Binary)
+        );
+        let minified = "x".repeat(MAX_DIFF_BYTES + 1);
+        assert_eq!(
+            check_diffable("{}", &minified),
+            Err(NotDiffable::TooLarge(MAX_DIFF_BYTES + 1))
+        );
+
+        assert_eq!(truncate_line("short", 10), "short");
+        assert_eq!(
+            truncate_line("{\"a\":1,\"b\":2}", 6),
+            "{\"a\":1… [7 more characters]"
+        );
+    }
+
+    #[test]
+    fn test_hunks() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\ntwo\n3\n4\n5\n6\n7\n8\n9\n10\n11\n";
+        let hunks = hunks(&line_diff(old, new).unwrap(), 1);
+
+        assert_eq!(hunks.len(), 2);
+        assert_eq!(
+            (
+                hunks[0].old_start,
+                hunks[0].old_len,
+                hunks[0].new_start,
+                hunks[0].new_len
+            ),
+            (1, 3, 1, 3)
+        );
+        assert_eq!(
+            (
+                hunks[1].old_start,
+                hunks[1].old_len,
+                hunks[1].new_start,
+                hunks[1].new_len
+            ),
+            (10, 1, 10, 2)
+        );
+        assert_eq!(
+            hunks[1].lines.last(),
+            Some(&DiffLine::Added("11".to_string()))
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use crate::error::{DotfError, DotfResult};
+use std::process::Command;
+
+/// Open `path` in the user's editor ($VISUAL, then $EDITOR, falling back to vi) and wait
+pub fn open_in_editor(path: &str) -> DotfResult<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    // Editors are often configured with arguments, e.g. "code --wait"
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| DotfError::Operation("Editor command is empty".to_string()))?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| {
+            DotfError::Operation(format!("Failed to launch editor '{}': {}", editor, e))
+        })?;
+
+    if !status.success() {
+        return Err(DotfError::Operation(format!(
+            "Editor '{}' exited with {}",
+            editor, status
+        )));
+    }
+
+    Ok(())
+}
+
+/// Compare `old` and `new` with the `[diff] tool` command, e.g. "code --diff --wait"
+pub fn open_in_difftool(tool: &str, old: &str, new: &str) -> DotfResult<()> {
+    let mut parts = tool.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| DotfError::Operation("Diff tool command is empty".to_string()))?;
+
+    // Not checking the exit status: diff tools exit with 1 when the files differ
+    Command::new(program)
+        .args(parts)
+        .args([old, new])
+        .status()
+        .map_err(|e| {
+            DotfError::Operation(format!("Failed to launch diff tool '{}': {}", tool, e))
+        })?;
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use std::path::Path;
+
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::{
+    fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter, Layer, Registry,
+};
+
+use crate::core::config::LogSettings;
+use crate::error::{DotfError, DotfResult};
+
+const DEFAULT_FILE_LEVEL: &str = "debug";
+const DEFAULT_MAX_FILES: usize = 7;
+
+/// Send log events to stderr at `verbosity` (`-v` count, nothing without it) and to a
+/// daily log file in `log_dir` when `settings.file` is set. Only the first call in a
+/// process installs anything.
+pub fn init(verbosity: u8, settings: &LogSettings, log_dir: &Path) -> DotfResult<()> {
+    let mut layers: Vec<Box<dyn Layer<Registry> + Send + Sync>> = Vec::new();
+
+    if let Some(level) = stderr_level(verbosity) {
+        layers.push(
+            fmt::layer()
+                .with_writer(std::io::stderr)
+                .without_time()
+                .with_filter(filter(level))
+                .boxed(),
+        );
+    }
+
+    let mut file_error = None;
+    if settings.file {
+        let appender = RollingFileAppender::builder()
+            .rotation(Rotation::DAILY)
+            .filename_prefix("dotf")
+            .filename_suffix("log")
+            .max_log_files(settings.max_files.unwrap_or(DEFAULT_MAX_FILES).max(1))
+            .build(log_dir);
+        match appender {
+            Ok(appender) => layers.push(
+                fmt::layer()
+                    .with_writer(appender)
+                    .with_ansi(false)
+                    .with_filter(filter(
+                        settings.level.as_deref().unwrap_or(DEFAULT_FILE_LEVEL),
+                    ))
+                    .boxed(),
+            ),
+            Err(e) => file_error = Some(e),
+        }
+    }
+
+    if !layers.is_empty() {
+        // Already installed by an earlier command of the same alias
+        let _ = tracing_subscriber::registry().with(layers).try_init();
+    }
+    match file_error {
+        Some(e) => Err(DotfError::Operation(format!(
+            "Could not open log file in {}: {}",
+            log_dir.display(),
+            e
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn stderr_level(verbosity: u8) -> Option<&'static str> {
+    match verbosity {
+        0 => None,
+        1 => Some("info"),
+        2 => Some("debug"),
+        _ => Some("trace"),
+    }
+}
+
+/// A bare level applies to dotf only, so dependencies don't flood the log; full
+/// filters such as `dotf=trace,reqwest=debug` are used as given
+fn filter(level: &str) -> EnvFilter {
+    let directives = if level.contains('=') {
+        level.to_string()
+    } else {
+        format!("dotf={}", level)
+    };
+    EnvFilter::try_new(&directives).unwrap_or_else(|_| EnvFilter::new("dotf=debug"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_and_filters() {
+        assert_eq!(stderr_level(0), None);
+        assert_eq!(stderr_level(2), Some("debug"));
+        assert_eq!(stderr_level(5), Some("trace"));
+        assert_eq!(filter("info").to_string(), "dotf=info");
+        assert_eq!(filter("reqwest=debug").to_string(), "reqwest=debug");
+        assert_eq!(filter("dotf=[").to_string(), "dotf=debug");
+    }
+}
@@ -0,0 +1,76 @@
+use crate::error::{DotfError, DotfResult};
+use std::fs;
+use std::process::Command;
+
+/// Result of a three-way merge
+#[derive(Debug, Clone)]
+pub struct MergeOutcome {
+    pub content: String,
+    /// Number of conflict hunks left as `<<<<<<<`/`>>>>>>>` markers in `content`
+    pub conflicts: usize,
+}
+
+/// Merge `local` and `repository` changes made since `base` using `git merge-file`
+pub fn merge_three_way(local: &str, base: &str, repository: &str) -> DotfResult<MergeOutcome> {
+    let dir = tempfile::tempdir().map_err(DotfError::Io)?;
+    let local_path = dir.path().join("local");
+    let base_path = dir.path().join("base");
+    let repository_path = dir.path().join("repository");
+
+    fs::write(&local_path, local).map_err(DotfError::Io)?;
+    fs::write(&base_path, base).map_err(DotfError::Io)?;
+    fs::write(&repository_path, repository).map_err(DotfError::Io)?;
+
+    let output = Command::new("git")
+        .args([
+            "merge-file",
+            "-p",
+            "-L",
+            "local",
+            "-L",
+            "base",
+            "-L",
+            "repository",
+        ])
+        .arg(&local_path)
+        .arg(&base_path)
+        .arg(&repository_path)
+        .output()
+        .map_err(|e| DotfError::Git(format!("Failed to run git merge-file: {}", e)))?;
+
+    // git merge-file exits with the number of conflicts, or a negative value on error
+    let conflicts = match output.status.code() {
+        Some(code) if (0..=127).contains(&code) => code as usize,
+        _ => {
+            return Err(DotfError::Git(format!(
+                "git merge-file failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )))
+        }
+    };
+
+    Ok(MergeOutcome {
+        content: String::from_utf8_lossy(&output.stdout).to_string(),
+        conflicts,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_without_conflicts() {
+        let outcome = merge_three_way("a\nB\nc\nd\n", "a\nb\nc\nd\n", "a\nb\nc\nD\n").unwrap();
+        assert_eq!(outcome.conflicts, 0);
+        assert_eq!(outcome.content, "a\nB\nc\nD\n");
+    }
+
+    #[test]
+    fn test_merge_with_conflicts() {
+        let outcome = merge_three_way("local\n", "base\n", "remote\n").unwrap();
+        assert_eq!(outcome.conflicts, 1);
+        assert!(outcome.content.contains("<<<<<<< local"));
+        assert!(outcome.content.contains(">>>>>>> repository"));
+    }
+}
@@ -1,5 +1,9 @@
+pub mod diff;
+pub mod editor;
+pub mod logging;
+pub mod merge;
 pub mod output;
 pub mod platform;
 pub mod prompt;
 
-pub use prompt::ConsolePrompt;
+pub use prompt::{ConsolePrompt, NonInteractivePrompt};
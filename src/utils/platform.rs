@@ -1 +1,38 @@
+/// Name of the current platform as used in dotf.toml (`macos`, `linux`, ...)
+pub fn detect_platform() -> String {
+    #[cfg(target_os = "macos")]
+    return "macos".to_string();
 
+    #[cfg(target_os = "linux")]
+    return "linux".to_string();
+
+    #[cfg(target_os = "windows")]
+    return "windows".to_string();
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux", target_os = "windows")))]
+    return "unknown".to_string();
+}
+
+/// Locate an executable on `PATH`, like `which`
+pub fn find_in_path(program: &str) -> Option<std::path::PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Name of this machine, or `unknown` when it cannot be determined
+pub fn hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| {
+            std::process::Command::new("hostname")
+                .output()
+                .ok()
+                .filter(|output| output.status.success())
+                .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        })
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown".to_string())
+}
@@ -76,10 +76,83 @@ impl Prompt for ConsolePrompt {
     }
 }
 
+/// Labels of `select` options that leave things as they are, in order of preference
+const DECLINE_LABELS: &[&str] = &["Skip", "Keep", "Cancel", "Abort"];
+
+/// Answers every question without a terminal, always declining: `confirm` is no,
+/// `select` picks the first option that skips, keeps or cancels, and `input` takes the
+/// default. For read-only services and machine-readable output, where a question must
+/// never block or change anything.
+#[derive(Clone, Default)]
+pub struct NonInteractivePrompt;
+
+impl NonInteractivePrompt {
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Prompt for NonInteractivePrompt {
+    async fn input(&self, message: &str, default: Option<&str>) -> DotfResult<String> {
+        default.map(str::to_string).ok_or_else(|| {
+            DotfError::Operation(format!("No answer for '{}' without a terminal", message))
+        })
+    }
+
+    async fn confirm(&self, _message: &str) -> DotfResult<bool> {
+        Ok(false)
+    }
+
+    async fn select(&self, message: &str, options: &[(&str, &str)]) -> DotfResult<usize> {
+        DECLINE_LABELS
+            .iter()
+            .find_map(|decline| {
+                options
+                    .iter()
+                    .position(|(label, _)| label.starts_with(decline))
+            })
+            .ok_or_else(|| {
+                DotfError::Operation(format!("No answer for '{}' without a terminal", message))
+            })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[tokio::test]
+    async fn test_non_interactive_prompt_declines() {
+        let prompt = NonInteractivePrompt::new();
+
+        assert!(!prompt.confirm("Overwrite?").await.unwrap());
+        assert_eq!(
+            prompt.input("Email", Some("me@example.com")).await.unwrap(),
+            "me@example.com"
+        );
+        assert!(prompt.input("Email", None).await.is_err());
+
+        let options = [
+            ("Individual", ""),
+            ("Skip All", ""),
+            ("Backup All", ""),
+            ("Abort", ""),
+        ];
+        assert_eq!(prompt.select("Conflicts", &options).await.unwrap(), 1);
+        assert_eq!(
+            prompt
+                .select("Repo", &[("Delete", ""), ("Abort", "")])
+                .await
+                .unwrap(),
+            1
+        );
+        assert!(prompt
+            .select("Pick", &[("Repair", ""), ("Adopt", "")])
+            .await
+            .is_err());
+    }
+
     // Note: These tests are integration tests that require manual interaction
     // They are disabled by default to avoid blocking CI/CD pipelines
     // To test manually, run: cargo test test_console_prompt --ignored
@@ -0,0 +1,72 @@
+//! `dotf __apply-plan` runs as root through sudo, so it must not write anything into
+//! the user's home directory except the links in its plan
+
+use std::path::Path;
+use std::process::Command;
+
+use dotf::core::config::Settings;
+use tempfile::TempDir;
+
+/// Every path below `dir`, relative to it
+fn tree(dir: &Path) -> Vec<String> {
+    let mut paths = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in std::fs::read_dir(&current).unwrap() {
+            let path = entry.unwrap().path();
+            paths.push(
+                path.strip_prefix(dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string(),
+            );
+            if path.is_dir() && !path.is_symlink() {
+                pending.push(path);
+            }
+        }
+    }
+    paths.sort();
+    paths
+}
+
+#[test]
+fn test_apply_plan_touches_only_the_planned_links() {
+    let home = TempDir::new().unwrap();
+    let dotf_dir = home.path().join(".dotf");
+    std::fs::create_dir(&dotf_dir).unwrap();
+    let settings = Settings::new("https://github.com/user/dotfiles");
+    std::fs::write(dotf_dir.join("settings.toml"), settings.to_toml().unwrap()).unwrap();
+    let work = TempDir::new().unwrap();
+    let source = work.path().join("hosts");
+    std::fs::write(&source, "127.0.0.1 localhost").unwrap();
+    let target = home.path().join("etc/hosts");
+    let plan = work.path().join("plan.json");
+    std::fs::write(
+        &plan,
+        serde_json::json!({
+            "links": [{ "source": source, "target": target }]
+        })
+        .to_string(),
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_dotf"))
+        .args(["--verbose", "__apply-plan"])
+        .arg(&plan)
+        .env("HOME", home.path())
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .unwrap();
+
+    assert!(output.status.success(), "{:?}", output);
+    let results: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(results[0]["error"], serde_json::Value::Null);
+    assert!(output.stderr.is_empty());
+    assert_eq!(std::fs::read_link(&target).unwrap(), source);
+    // No timings, settings backup, log or lock file in ~/.dotf
+    assert_eq!(
+        tree(home.path()),
+        vec![".dotf", ".dotf/settings.toml", "etc", "etc/hosts"]
+    );
+    assert_eq!(tree(work.path()), vec!["hosts", "plan.json"]);
+}
@@ -0,0 +1,87 @@
+//! A lock file left by a dotf that crashed or was killed is taken over by the next
+//! command, which tells the user the previous one did not finish
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use chrono::Utc;
+use dotf::core::{
+    config::Settings,
+    lock::{LockHolder, LOCK_FILE},
+};
+use tempfile::TempDir;
+
+fn initialized_home() -> TempDir {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir(home.path().join(".dotf")).unwrap();
+    let settings = Settings::new("https://github.com/user/dotfiles");
+    std::fs::write(
+        home.path().join(".dotf/settings.toml"),
+        settings.to_toml().unwrap(),
+    )
+    .unwrap();
+    home
+}
+
+fn write_lock(home: &Path, pid: u32) {
+    let holder = LockHolder {
+        pid,
+        command: "install".to_string(),
+        started_at: Utc::now(),
+    };
+    std::fs::write(
+        home.join(".dotf").join(LOCK_FILE),
+        serde_json::to_string(&holder).unwrap(),
+    )
+    .unwrap();
+}
+
+fn prune_backups(home: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dotf"))
+        .args(["--yes", "backups", "prune", "--keep-last", "5"])
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_lock_of_dead_process_is_taken_over_with_a_warning() {
+    let home = initialized_home();
+    write_lock(home.path(), i32::MAX as u32);
+
+    let output = prune_backups(home.path());
+
+    assert!(output.status.success(), "{:?}", output);
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!(
+        "The previous 'dotf install' (pid {}, started",
+        i32::MAX
+    )));
+    assert!(stderr.contains("did not finish"));
+    assert!(stderr.contains("Run 'dotf symlinks repair' to fix links it left behind"));
+    // Taken over for the run, then released
+    assert!(!home.path().join(".dotf").join(LOCK_FILE).exists());
+}
+
+#[test]
+fn test_lock_of_live_process_is_left_alone() {
+    let home = initialized_home();
+    // This test is still running, so its pid holds the lock
+    write_lock(home.path(), std::process::id());
+    let lock = std::fs::read_to_string(home.path().join(".dotf").join(LOCK_FILE)).unwrap();
+
+    let output = prune_backups(home.path());
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains(&format!(
+        "Another dotf command ('install', pid {})",
+        std::process::id()
+    )));
+    assert!(!stderr.contains("did not finish"));
+    assert_eq!(
+        std::fs::read_to_string(home.path().join(".dotf").join(LOCK_FILE)).unwrap(),
+        lock
+    );
+}
@@ -0,0 +1,43 @@
+//! `dotf status --fail-on` is for cron jobs and health checks: it answers with its exit
+//! code only, never with output
+
+use std::path::Path;
+use std::process::{Command, Output};
+
+use tempfile::TempDir;
+
+fn status(home: &Path, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_dotf"))
+        .arg("status")
+        .args(args)
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .unwrap()
+}
+
+fn assert_silent(output: &Output, code: i32) {
+    assert_eq!(output.status.code(), Some(code), "{:?}", output);
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(output.stderr.is_empty(), "{:?}", output);
+}
+
+#[test]
+fn test_fail_on_reports_only_through_exit_code() {
+    let home = TempDir::new().unwrap();
+
+    // Not initialized: the bit of the selected category
+    assert_silent(&status(home.path(), &["--fail-on", "uninitialized"]), 128);
+    assert_silent(&status(home.path(), &["--fail-on", "broken,behind"]), 0);
+
+    // Unreadable settings are not warned about
+    std::fs::create_dir(home.path().join(".dotf")).unwrap();
+    std::fs::write(home.path().join(".dotf/settings.toml"), "garbage = [").unwrap();
+    assert_silent(&status(home.path(), &["--fail-on", "all"]), 128);
+
+    // An error is exit code 1, without its message
+    assert_silent(
+        &status(home.path(), &["--repo", "work", "--fail-on", "all"]),
+        1,
+    );
+}
@@ -0,0 +1,49 @@
+//! `dotf status --since` reads the recorded history; it must not add to it
+
+use std::process::Command;
+
+use chrono::{Duration, Utc};
+use dotf::core::{config::Settings, history::StatusSnapshot};
+use tempfile::TempDir;
+
+#[test]
+fn test_since_leaves_history_unchanged() {
+    let home = TempDir::new().unwrap();
+    let dotf = home.path().join(".dotf");
+    std::fs::create_dir(&dotf).unwrap();
+    let settings = Settings::new("https://github.com/user/dotfiles");
+    std::fs::write(dotf.join("settings.toml"), settings.to_toml().unwrap()).unwrap();
+    std::fs::create_dir(dotf.join("repo")).unwrap();
+    std::fs::write(dotf.join("repo/dotf.toml"), "[symlinks]\n").unwrap();
+    let snapshot = StatusSnapshot {
+        taken_at: Utc::now() - Duration::hours(1),
+        total: 4,
+        valid: 4,
+        missing: 0,
+        broken: 0,
+        conflicts: 0,
+        invalid_targets: 0,
+        modified: 0,
+        is_clean: true,
+        ahead: 0,
+        behind: 0,
+    };
+    let history = format!("{}\n", serde_json::to_string(&snapshot).unwrap());
+    std::fs::write(dotf.join("history.jsonl"), &history).unwrap();
+
+    for args in [&["--since", "7d"][..], &["--since", "7d", "--json"][..]] {
+        let output = Command::new(env!("CARGO_BIN_EXE_dotf"))
+            .arg("status")
+            .args(args)
+            .env("HOME", home.path())
+            .env_remove("XDG_CONFIG_HOME")
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{:?}", output);
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(dotf.join("history.jsonl")).unwrap(),
+        history
+    );
+}
@@ -0,0 +1,92 @@
+//! `dotf status --widget` prints the last recorded status for status bars and only
+//! starts a background refresh when that status is stale or missing
+
+use std::path::Path;
+use std::process::Command;
+
+use chrono::{Duration, Utc};
+use dotf::core::{config::Settings, history::StatusSnapshot};
+use tempfile::TempDir;
+
+fn initialized_home() -> TempDir {
+    let home = TempDir::new().unwrap();
+    std::fs::create_dir(home.path().join(".dotf")).unwrap();
+    let settings = Settings::new("https://github.com/user/dotfiles");
+    std::fs::write(
+        home.path().join(".dotf/settings.toml"),
+        settings.to_toml().unwrap(),
+    )
+    .unwrap();
+    home
+}
+
+fn record(home: &Path, age: Duration, behind: usize) {
+    let snapshot = StatusSnapshot {
+        taken_at: Utc::now() - age,
+        total: 4,
+        valid: 4,
+        missing: 0,
+        broken: 0,
+        conflicts: 0,
+        invalid_targets: 0,
+        modified: 0,
+        is_clean: true,
+        ahead: 0,
+        behind,
+    };
+    std::fs::write(
+        home.join(".dotf/history.jsonl"),
+        format!("{}\n", serde_json::to_string(&snapshot).unwrap()),
+    )
+    .unwrap();
+}
+
+fn widget(home: &Path) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_dotf"))
+        .args(["status", "--widget", "tmux"])
+        .env("HOME", home)
+        .env_remove("XDG_CONFIG_HOME")
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn refresh_started(home: &Path) -> bool {
+    home.join(".dotf/widget-refresh").exists()
+}
+
+#[test]
+fn test_widget_prints_recorded_snapshot() {
+    let home = initialized_home();
+    record(home.path(), Duration::seconds(10), 3);
+
+    assert_eq!(widget(home.path()), "#[fg=yellow]dotf ⇣3#[default]\n");
+    assert!(!refresh_started(home.path()));
+}
+
+#[test]
+fn test_widget_refreshes_stale_snapshot() {
+    let home = initialized_home();
+    record(home.path(), Duration::hours(1), 0);
+
+    // The old status is still shown while a new one is computed
+    assert_eq!(widget(home.path()), "#[fg=green]dotf ✓#[default]\n");
+    assert!(refresh_started(home.path()));
+}
+
+#[test]
+fn test_widget_refreshes_missing_snapshot() {
+    let home = initialized_home();
+
+    assert_eq!(widget(home.path()), "#[fg=colour244]dotf ?#[default]\n");
+    assert!(refresh_started(home.path()));
+
+    // Without settings there is nothing to refresh
+    let uninitialized = TempDir::new().unwrap();
+    assert_eq!(
+        widget(uninitialized.path()),
+        "#[fg=colour244]dotf ?#[default]\n"
+    );
+    assert!(!refresh_started(uninitialized.path()));
+}